@@ -0,0 +1,74 @@
+use constriction::{
+    stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode},
+    Pos, Seek,
+};
+use criterion::{black_box, criterion_group, Criterion};
+use probability::distribution::{Gaussian, Inverse};
+use rand::{RngCore, SeedableRng};
+use rand_xoshiro::Xoshiro256StarStar;
+
+criterion_group!(benches, gaussian_decoding_std_3, gaussian_decoding_std_300);
+
+#[cfg(not(miri))]
+criterion::criterion_main!(benches);
+#[cfg(miri)]
+fn main() {} // All benchmarks currently use FFI and therefore can't be tested in miri.
+
+fn gaussian_decoding_std_3(c: &mut Criterion) {
+    gaussian_decoding(c, 3.0);
+}
+
+fn gaussian_decoding_std_300(c: &mut Criterion) {
+    gaussian_decoding(c, 300.0);
+}
+
+/// Compares decoding i.i.d. symbols from a quantized Gaussian with and without the index
+/// built by `LeakilyQuantizedDistribution::with_decoder_index`.
+fn gaussian_decoding(c: &mut Criterion, std: f64) {
+    let quantizer = DefaultLeakyQuantizer::new(-10_000..=10_000);
+    let distribution = Gaussian::new(0.0, std);
+    let model = quantizer.quantize(distribution);
+    let indexed_model = model.with_decoder_index();
+
+    let mut rng = Xoshiro256StarStar::seed_from_u64(1234);
+    let data = (0..10_000)
+        .map(|_| {
+            let uniform = (rng.next_u32() as f64 + 0.5) / (1u64 << 32) as f64;
+            distribution.inverse(uniform).round() as i32
+        })
+        .collect::<Vec<_>>();
+
+    let mut coder = DefaultAnsCoder::new();
+    coder.encode_iid_symbols_reverse(&data, model).unwrap();
+    let mut coder = coder.into_seekable_decoder();
+    let reset_snapshot = coder.pos();
+
+    c.bench_function(&format!("gaussian_decoding_std_{}", std), |b| {
+        b.iter(|| {
+            coder.seek(black_box(reset_snapshot)).unwrap();
+            let mut checksum = 1234i32;
+            for symbol in coder.decode_iid_symbols(data.len(), model) {
+                checksum ^= symbol.unwrap();
+            }
+            black_box(checksum);
+        })
+    });
+
+    c.bench_function(&format!("gaussian_decoding_indexed_std_{}", std), |b| {
+        b.iter(|| {
+            coder.seek(black_box(reset_snapshot)).unwrap();
+            let mut checksum = 1234i32;
+            for symbol in coder.decode_iid_symbols(data.len(), &indexed_model) {
+                checksum ^= symbol.unwrap();
+            }
+            black_box(checksum);
+        })
+    });
+
+    coder.seek(reset_snapshot).unwrap();
+    let decoded = coder
+        .decode_iid_symbols(data.len(), &indexed_model)
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(decoded, data);
+}
@@ -0,0 +1,333 @@
+//! Derive macros for the [`constriction`](https://docs.rs/constriction/) crate
+//!
+//! You'll usually not depend on this crate directly. Instead, enable the `derive` feature
+//! of `constriction`, which re-exports the macros defined here.
+
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    spanned::Spanned,
+    Data, DeriveInput, Error, Fields, Ident, LitInt, Token,
+};
+
+/// Derives `constriction::SymbolAlphabet` for a fieldless enum.
+///
+/// The variants are mapped to the contiguous indices `0..NUM_SYMBOLS` in the order in which
+/// they are declared. Explicit discriminants (e.g., `Foo = 5`) are ignored for this purpose,
+/// so reordering variants changes the mapping (and thus the compressed representation) but
+/// assigning discriminants doesn't.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     stream::{
+///         model::DefaultContiguousCategoricalEntropyModel, stack::DefaultAnsCoder, Decode,
+///         Encode,
+///     },
+///     SymbolAlphabet,
+/// };
+///
+/// #[derive(SymbolAlphabet, Debug, Clone, Copy, PartialEq, Eq)]
+/// enum Weather {
+///     Sunny,
+///     Cloudy,
+///     Rainy,
+/// }
+///
+/// assert_eq!(Weather::NUM_SYMBOLS, 3);
+/// assert_eq!(Weather::Rainy.to_index(), 2);
+/// assert_eq!(Weather::from_index(1), Some(Weather::Cloudy));
+///
+/// let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+///     0.6, 0.3, 0.1,
+/// ])
+/// .unwrap();
+/// let mut coder = DefaultAnsCoder::new();
+/// coder.encode_enum(Weather::Rainy, &model).unwrap();
+/// coder.encode_enum(Weather::Sunny, &model).unwrap();
+/// assert_eq!(coder.decode_enum::<Weather, _>(&model).unwrap(), Weather::Sunny);
+/// assert_eq!(coder.decode_enum::<Weather, _>(&model).unwrap(), Weather::Rainy);
+/// ```
+#[proc_macro_derive(SymbolAlphabet)]
+pub fn derive_symbol_alphabet(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_symbol_alphabet(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand_symbol_alphabet(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "`SymbolAlphabet` can only be derived for enums.",
+            ))
+        }
+    };
+    if data.variants.is_empty() {
+        return Err(Error::new(
+            input.ident.span(),
+            "`SymbolAlphabet` requires an enum with at least one variant.",
+        ));
+    }
+    if let Some(variant) = data
+        .variants
+        .iter()
+        .find(|variant| !matches!(variant.fields, Fields::Unit))
+    {
+        return Err(Error::new(
+            variant.span(),
+            "`SymbolAlphabet` can only be derived for enums whose variants have no fields.",
+        ));
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let num_symbols = data.variants.len();
+    let variants = data
+        .variants
+        .iter()
+        .map(|variant| &variant.ident)
+        .collect::<Vec<_>>();
+    let indices = 0..num_symbols;
+    let indices2 = indices.clone();
+
+    Ok(quote! {
+        impl #impl_generics ::constriction::SymbolAlphabet for #name #ty_generics #where_clause {
+            const NUM_SYMBOLS: usize = #num_symbols;
+
+            #[inline]
+            fn to_index(&self) -> usize {
+                match self {
+                    #(Self::#variants => #indices,)*
+                }
+            }
+
+            #[inline]
+            fn from_index(index: usize) -> ::core::option::Option<Self> {
+                match index {
+                    #(#indices2 => ::core::option::Option::Some(Self::#variants),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    })
+}
+
+/// Computes a categorical entropy model from a list of counts at compile time.
+///
+/// `static_categorical!([counts...], PRECISION)` expands to a constant expression of type
+/// `ContiguousCategoricalEntropyModel<Probability, &'static [Probability], PRECISION>` over
+/// the symbols `0..counts.len()`, where `Probability` is `u16` if `PRECISION <= 16`, `u32` if
+/// `PRECISION <= 32`, and `u64` otherwise. Thus, for the typical precisions of 24 and 12
+/// bits, the resulting types are `DefaultContiguousCategoricalEntropyModel<&[u32]>` and
+/// `SmallContiguousCategoricalEntropyModel<&[u16]>`, respectively. Since the fixed point
+/// cumulative distribution function is computed by the compiler, you can assign the model
+/// to a `static` or a `const`, and using it costs no startup time at all (this is useful,
+/// e.g., for firmware, or in hot paths where even one-time table construction is
+/// unwanted).
+///
+/// Adding a third argument `lookup` (i.e., `static_categorical!([counts...], PRECISION,
+/// lookup)`) expands instead to a `LookupDecoderModel` that also contains a compile time
+/// computed lookup table for fast decoding. Its type is
+/// `SmallContiguousLookupDecoderModel<&'static [u16], &'static [u16]>` for `PRECISION ==
+/// 12`. Lookup tables have `1 << PRECISION` entries, so the option `lookup` is only
+/// supported for `PRECISION <= 16`.
+///
+/// The counts have to be nonnegative integer literals, at least one of them has to be
+/// nonzero, and there have to be at least two of them. Each symbol gets a nonzero
+/// probability, even if its count is zero, so that every symbol in the support can be
+/// encoded. The remaining probability mass is split proportionally to the counts in exact
+/// integer arithmetic (rounding to the nearest representable distribution by the largest
+/// remainder method, with ties broken in favor of smaller symbols). Thus, the result
+/// doesn't depend on the platform on which the code is compiled.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{
+///         static_categorical, DefaultContiguousCategoricalEntropyModel,
+///         SmallContiguousCategoricalEntropyModel, SmallContiguousLookupDecoderModel,
+///     },
+///     stack::SmallAnsCoder,
+///     Decode, Encode,
+/// };
+///
+/// static MODEL: DefaultContiguousCategoricalEntropyModel<&[u32]> =
+///     static_categorical!([10, 3, 0, 7], 24);
+/// assert_eq!(MODEL.support_size(), 4);
+///
+/// // For fast decoding, compute the lookup table at compile time too.
+/// const ENCODER_MODEL: SmallContiguousCategoricalEntropyModel<&[u16]> =
+///     static_categorical!([10, 3, 0, 7], 12);
+/// static DECODER_MODEL: SmallContiguousLookupDecoderModel<&[u16], &[u16]> =
+///     static_categorical!([10, 3, 0, 7], 12, lookup);
+///
+/// let symbols = [0, 3, 3, 1, 0, 2];
+/// let mut coder = SmallAnsCoder::new();
+/// coder.encode_iid_symbols_reverse(&symbols, ENCODER_MODEL).unwrap();
+/// let decoded = coder
+///     .decode_iid_symbols(symbols.len(), &DECODER_MODEL)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+#[proc_macro]
+pub fn static_categorical(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as StaticCategoricalInput);
+    expand_static_categorical(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+struct StaticCategoricalInput {
+    counts: Punctuated<LitInt, Token![,]>,
+    counts_span: Span,
+    precision: LitInt,
+    lookup: bool,
+}
+
+impl Parse for StaticCategoricalInput {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        let content;
+        let brackets = syn::bracketed!(content in input);
+        let counts = content.parse_terminated(LitInt::parse)?;
+        input.parse::<Token![,]>()?;
+        let precision = input.parse()?;
+
+        let mut lookup = false;
+        if input.parse::<Option<Token![,]>>()?.is_some() && !input.is_empty() {
+            let option = input.parse::<Ident>()?;
+            if option != "lookup" {
+                return Err(Error::new(
+                    option.span(),
+                    "Expected `lookup` or no third argument.",
+                ));
+            }
+            lookup = true;
+            input.parse::<Option<Token![,]>>()?;
+        }
+
+        Ok(Self {
+            counts,
+            counts_span: brackets.span,
+            precision,
+            lookup,
+        })
+    }
+}
+
+fn expand_static_categorical(input: StaticCategoricalInput) -> Result<TokenStream2, Error> {
+    let precision = input.precision.base10_parse::<usize>()?;
+    if precision == 0 || precision > 64 {
+        return Err(Error::new(
+            input.precision.span(),
+            "`PRECISION` must be between 1 and 64.",
+        ));
+    }
+    if input.lookup && precision > 16 {
+        return Err(Error::new(
+            input.precision.span(),
+            "Lookup tables are only supported for `PRECISION <= 16`.",
+        ));
+    }
+
+    let counts = input
+        .counts
+        .iter()
+        .map(LitInt::base10_parse::<u64>)
+        .collect::<Result<Vec<_>, _>>()?;
+    let probabilities = quantize_counts(&counts, precision)
+        .map_err(|message| Error::new(input.counts_span, message))?;
+
+    // The last entry of the CDF wraps around to zero if `PRECISION` is the full bit width.
+    let probability_bits = match precision {
+        0..=16 => 16,
+        17..=32 => 32,
+        _ => 64,
+    };
+    let total = if precision == probability_bits {
+        0
+    } else {
+        1u128 << precision
+    };
+    let mut cdf = Vec::with_capacity(probabilities.len() + 1);
+    let mut accumulator = 0u128;
+    for &probability in &probabilities {
+        cdf.push(accumulator);
+        accumulator += probability;
+    }
+    cdf.push(total);
+
+    let probability_type = Ident::new(&format!("u{}", probability_bits), Span::call_site());
+    let precision_literal = Literal::usize_unsuffixed(precision);
+    let cdf = cdf.into_iter().map(Literal::u128_unsuffixed);
+
+    if input.lookup {
+        let mut lookup_table = Vec::with_capacity(1 << precision);
+        for (index, &probability) in probabilities.iter().enumerate() {
+            lookup_table.resize(lookup_table.len() + probability as usize, index);
+        }
+        let lookup_table = lookup_table.into_iter().map(Literal::usize_unsuffixed);
+        Ok(quote! {
+            ::constriction::stream::model::LookupDecoderModel::<
+                usize,
+                #probability_type,
+                ::constriction::stream::model::ContiguousSymbolTable<&'static [#probability_type]>,
+                &'static [#probability_type],
+                #precision_literal,
+            >::from_static_tables(&[#(#cdf),*], &[#(#lookup_table),*])
+        })
+    } else {
+        Ok(quote! {
+            ::constriction::stream::model::ContiguousCategoricalEntropyModel::<
+                #probability_type,
+                &'static [#probability_type],
+                #precision_literal,
+            >::from_static_cdf(&[#(#cdf),*])
+        })
+    }
+}
+
+/// Returns nonzero fixed point probabilities that sum to `1 << precision` and that are, apart
+/// from the minimum probability of one per symbol, proportional to `counts`.
+fn quantize_counts(counts: &[u64], precision: usize) -> Result<Vec<u128>, &'static str> {
+    if counts.len() < 2 {
+        return Err("A categorical entropy model needs at least two symbols.");
+    }
+    let total_mass = 1u128 << precision;
+    let free_mass = total_mass
+        .checked_sub(counts.len() as u128)
+        .ok_or("Too many symbols to assign a nonzero probability to each one of them.")?;
+    let total_count = counts.iter().map(|&count| count as u128).sum::<u128>();
+    if total_count == 0 {
+        return Err("At least one count must be nonzero.");
+    }
+
+    let mut probabilities = Vec::with_capacity(counts.len());
+    let mut remainders = Vec::with_capacity(counts.len());
+    for (index, &count) in counts.iter().enumerate() {
+        let scaled = (count as u128)
+            .checked_mul(free_mass)
+            .ok_or("Counts are too large for this precision.")?;
+        probabilities.push(1 + scaled / total_count);
+        remainders.push((scaled % total_count, index));
+    }
+
+    let assigned = probabilities.iter().sum::<u128>();
+    let leftover = (total_mass - assigned) as usize;
+    remainders.sort_by(|(rem1, index1), (rem2, index2)| rem2.cmp(rem1).then(index1.cmp(index2)));
+    for &(_, index) in &remainders[..leftover] {
+        probabilities[index] += 1;
+    }
+
+    Ok(probabilities)
+}
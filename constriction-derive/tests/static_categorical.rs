@@ -0,0 +1,104 @@
+use constriction::stream::{
+    model::{
+        static_categorical, ContiguousCategoricalEntropyModel, ContiguousSymbolTable, DecoderModel,
+        DefaultContiguousCategoricalEntropyModel, EncoderModel, IterableEntropyModel,
+        LookupDecoderModel, SmallContiguousCategoricalEntropyModel,
+        SmallContiguousLookupDecoderModel,
+    },
+    queue::{SmallRangeDecoder, SmallRangeEncoder},
+    Decode, Encode,
+};
+
+static DEFAULT: DefaultContiguousCategoricalEntropyModel<&[u32]> =
+    static_categorical!([5, 0, 12, 1, 30], 24);
+
+const SMALL: SmallContiguousCategoricalEntropyModel<&[u16]> =
+    static_categorical!([5, 0, 12, 1, 30], 12);
+
+static LOOKUP: SmallContiguousLookupDecoderModel<&[u16], &[u16]> =
+    static_categorical!([5, 0, 12, 1, 30], 12, lookup);
+
+fn probabilities<'m, M>(model: &'m M) -> Vec<u64>
+where
+    M: IterableEntropyModel<'m, 24, Symbol = usize, Probability = u32>,
+{
+    model
+        .symbol_table()
+        .map(|(_, _, probability)| probability.get() as u64)
+        .collect()
+}
+
+#[test]
+fn probabilities_are_proportional_to_counts() {
+    let probabilities = probabilities(&DEFAULT);
+    assert_eq!(probabilities.iter().sum::<u64>(), 1 << 24);
+
+    // Each symbol gets one unit of probability mass plus its share of the rest.
+    let free_mass = (1u64 << 24) - 5;
+    for (&probability, &count) in probabilities.iter().zip(&[5u64, 0, 12, 1, 30]) {
+        let expected = 1 + count * free_mass / 48;
+        assert!(probability == expected || probability == expected + 1);
+    }
+    assert_eq!(probabilities[1], 1);
+}
+
+#[test]
+fn lookup_matches_categorical() {
+    for quantile in 0..1 << 12 {
+        assert_eq!(
+            LOOKUP.quantile_function(quantile),
+            SMALL.quantile_function(quantile)
+        );
+    }
+    for symbol in 0..5 {
+        let (left_cumulative, probability) = SMALL.left_cumulative_and_probability(symbol).unwrap();
+        assert_eq!(
+            LOOKUP.quantile_function(left_cumulative),
+            (symbol, left_cumulative, probability)
+        );
+    }
+}
+
+#[test]
+fn roundtrip() {
+    let symbols = [4, 2, 4, 0, 1, 3, 4, 4, 2];
+    let mut encoder = SmallRangeEncoder::new();
+    encoder.encode_iid_symbols(&symbols, SMALL).unwrap();
+
+    let mut decoder =
+        SmallRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+    let decoded = decoder
+        .decode_iid_symbols(symbols.len(), &LOOKUP)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded, symbols);
+}
+
+#[test]
+fn full_precision() {
+    // If `PRECISION` is the full bit width of the probability type, the last entry of the
+    // CDF wraps around to zero.
+    const MODEL: ContiguousCategoricalEntropyModel<u16, &[u16], 16> =
+        static_categorical!([1, 3], 16);
+    const LOOKUP: LookupDecoderModel<usize, u16, ContiguousSymbolTable<&[u16]>, &[u16], 16> =
+        static_categorical!([1, 3], 16, lookup);
+    // Both symbols have the same remainder, so the tie is broken in favor of symbol zero.
+    assert_eq!(
+        MODEL.left_cumulative_and_probability(1).unwrap().0,
+        (1 << 14) + 1
+    );
+    for quantile in (0..=u16::MAX).step_by(97) {
+        assert_eq!(
+            LOOKUP.quantile_function(quantile),
+            MODEL.quantile_function(quantile)
+        );
+    }
+
+    const WIDE: ContiguousCategoricalEntropyModel<u64, &[u64], 64> =
+        static_categorical!([1, 1, 2], 64);
+    let symbol_table = WIDE
+        .symbol_table()
+        .map(|(symbol, left_cumulative, _)| (symbol, left_cumulative))
+        .collect::<Vec<_>>();
+    assert_eq!(symbol_table, [(0, 0), (1, 1 << 62), (2, 1 << 63)]);
+}
@@ -0,0 +1,97 @@
+use constriction::{
+    stream::{
+        model::DefaultContiguousCategoricalEntropyModel,
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        Decode, Encode,
+    },
+    SymbolAlphabet,
+};
+
+#[derive(SymbolAlphabet, Debug, Clone, Copy, PartialEq, Eq)]
+enum Base {
+    A,
+    C,
+    G,
+    T,
+}
+
+#[derive(SymbolAlphabet, Debug, PartialEq, Eq)]
+enum WithDiscriminants {
+    Ten = 10,
+    Three = 3,
+}
+
+#[derive(SymbolAlphabet, Debug, PartialEq, Eq)]
+enum Single {
+    Only,
+}
+
+#[test]
+fn indices() {
+    assert_eq!(Base::NUM_SYMBOLS, 4);
+    for (index, base) in [Base::A, Base::C, Base::G, Base::T].iter().enumerate() {
+        assert_eq!(base.to_index(), index);
+        assert_eq!(Base::from_index(index), Some(*base));
+    }
+    assert_eq!(Base::from_index(4), None);
+
+    // Explicit discriminants don't affect the indices.
+    assert_eq!(WithDiscriminants::NUM_SYMBOLS, 2);
+    assert_eq!(WithDiscriminants::Ten.to_index(), 0);
+    assert_eq!(WithDiscriminants::Three.to_index(), 1);
+    assert_eq!(WithDiscriminants::from_index(10), None);
+
+    assert_eq!(Single::NUM_SYMBOLS, 1);
+    assert_eq!(Single::from_index(0), Some(Single::Only));
+}
+
+#[test]
+fn roundtrip() {
+    let sequence = [
+        Base::G,
+        Base::A,
+        Base::T,
+        Base::T,
+        Base::A,
+        Base::C,
+        Base::A,
+        Base::A,
+    ];
+    let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+        0.4, 0.1, 0.2, 0.3,
+    ])
+    .unwrap();
+
+    let mut encoder = DefaultRangeEncoder::new();
+    for &base in &sequence {
+        encoder.encode_enum(base, &model).unwrap();
+    }
+    let mut decoder =
+        DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+    for &base in &sequence {
+        assert_eq!(decoder.decode_enum::<Base, _>(&model).unwrap(), base);
+    }
+}
+
+#[test]
+fn checked_decode_enum() {
+    // The model has a larger support than the alphabet.
+    let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+        0.4, 0.1, 0.2, 0.3, 0.5,
+    ])
+    .unwrap();
+
+    let mut encoder = DefaultRangeEncoder::new();
+    encoder.encode_enum(Base::C, &model).unwrap();
+    encoder.encode_symbol(4, &model).unwrap();
+    let mut decoder =
+        DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+    assert_eq!(
+        decoder.checked_decode_enum::<Base, _>(&model).unwrap(),
+        Some(Base::C)
+    );
+    assert_eq!(
+        decoder.checked_decode_enum::<Base, _>(&model).unwrap(),
+        None
+    );
+}
@@ -0,0 +1,214 @@
+//! Self-delimiting, checksummed frames of range coded latents.
+//!
+//! Each frame holds the compressed representation of the latents of one unit of data
+//! (e.g., one image tile or one time step of a learned codec). A frame is a sequence of
+//! little-endian `u32` words:
+//!
+//! | word(s)        | content                                                  |
+//! |----------------|----------------------------------------------------------|
+//! | 0              | [`FRAME_MAGIC`]                                          |
+//! | 1              | frame index (counts up from zero within a response)      |
+//! | 2              | number of latents                                        |
+//! | 3              | scale of the entropy model (`f32` bit pattern)           |
+//! | 4              | number `n` of compressed words                           |
+//! | 5 ..= 4 + n    | compressed words, as produced by a `DefaultRangeEncoder` |
+//! | 5 + n          | FNV-1a checksum over all preceding bytes of the frame    |
+//!
+//! The header carries everything the receiver needs to reconstruct the entropy model, so
+//! frames can be decoded independently of each other. The receiver validates the length
+//! fields against hard limits *before* it allocates or decodes anything, so a malicious or
+//! corrupted stream can't make it allocate unbounded memory or decode unbounded amounts of
+//! garbage.
+
+use std::{
+    convert::TryInto,
+    fmt::{Display, Formatter},
+};
+
+use constriction::{
+    backends::{FallibleCallbackWriteWords, InfallibleIteratorReadWords},
+    stream::{
+        model::{DefaultLeakyQuantizer, LeakilyQuantizedDistribution},
+        queue::{RangeDecoder, RangeEncoder},
+        Decode, Encode,
+    },
+    CoderError,
+};
+use probability::distribution::Gaussian;
+
+use crate::Cancellation;
+
+/// The bytes `b"CNSF"`, interpreted as a little-endian `u32`.
+pub const FRAME_MAGIC: u32 = u32::from_le_bytes(*b"CNSF");
+
+/// Latents are integers in `-LATENT_BOUND..=LATENT_BOUND`.
+pub const LATENT_BOUND: i32 = 255;
+
+/// Upper bound on the number of latents per frame that a receiver accepts.
+pub const MAX_LATENTS_PER_FRAME: usize = 1 << 16;
+
+/// Upper bound on the size of a frame (in bytes) that a receiver accepts.
+pub const MAX_FRAME_BYTES: usize = 4 * (HEADER_WORDS + 2 * MAX_LATENTS_PER_FRAME + 8);
+
+const HEADER_WORDS: usize = 5;
+
+/// A decoded frame.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    pub index: u32,
+    pub scale: f32,
+    pub latents: Vec<i32>,
+}
+
+#[derive(Debug)]
+pub enum FrameError {
+    /// The sender's [`Cancellation`] was triggered while it was encoding the frame.
+    Cancelled,
+    /// The frame is malformed, truncated, or exceeds the limits of the receiver.
+    Malformed(&'static str),
+    /// The checksum doesn't match the content of the frame.
+    ChecksumMismatch,
+    /// A latent lies outside of `-LATENT_BOUND..=LATENT_BOUND`, or the scale is invalid.
+    InvalidData,
+}
+
+impl Display for FrameError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Cancelled => write!(f, "Encoding was cancelled."),
+            Self::Malformed(reason) => write!(f, "Malformed frame: {}", reason),
+            Self::ChecksumMismatch => write!(f, "Checksum mismatch."),
+            Self::InvalidData => write!(f, "Invalid latents or model parameters."),
+        }
+    }
+}
+
+impl std::error::Error for FrameError {}
+
+/// 32-bit FNV-1a hash, which is good enough to detect accidental corruption (for
+/// protection against tampering, use a MAC at the transport layer instead).
+#[derive(Debug, Clone, Copy)]
+pub struct Checksum(u32);
+
+impl Checksum {
+    pub fn new() -> Self {
+        Self(0x811c_9dc5)
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ byte as u32).wrapping_mul(0x0100_0193);
+        }
+    }
+
+    pub fn finish(self) -> u32 {
+        self.0
+    }
+}
+
+fn model(
+    scale: f32,
+) -> Result<LeakilyQuantizedDistribution<f64, i32, u32, Gaussian, 24>, FrameError> {
+    if !(scale > 0.0 && scale.is_finite()) {
+        return Err(FrameError::InvalidData);
+    }
+    let quantizer = DefaultLeakyQuantizer::new(-LATENT_BOUND..=LATENT_BOUND);
+    Ok(quantizer.quantize(Gaussian::new(0.0, scale as f64)))
+}
+
+/// Encodes `latents` into a frame.
+///
+/// The range encoder writes each compressed word through a callback directly into the
+/// frame buffer, and the callback aborts encoding as soon as `cancellation` is triggered,
+/// so that a sender doesn't waste work on a frame that nobody will receive.
+pub fn encode_frame(
+    index: u32,
+    scale: f32,
+    latents: &[i32],
+    cancellation: &Cancellation,
+) -> Result<Vec<u8>, FrameError> {
+    if latents.len() > MAX_LATENTS_PER_FRAME {
+        return Err(FrameError::Malformed("too many latents"));
+    }
+    let model = model(scale)?;
+
+    let mut frame = Vec::with_capacity(4 * (HEADER_WORDS + latents.len() / 2 + 3));
+    for word in [FRAME_MAGIC, index, latents.len() as u32, scale.to_bits(), 0] {
+        frame.extend_from_slice(&word.to_le_bytes());
+    }
+
+    let backend = FallibleCallbackWriteWords::new(|word: u32| {
+        if cancellation.is_cancelled() {
+            return Err(FrameError::Cancelled);
+        }
+        frame.extend_from_slice(&word.to_le_bytes());
+        Ok(())
+    });
+    let mut encoder = RangeEncoder::<u32, u64, _>::with_backend(backend);
+    encoder
+        .encode_iid_symbols(latents, &model)
+        .map_err(|err| match err {
+            CoderError::Frontend(_) => FrameError::InvalidData,
+            CoderError::Backend(err) => err,
+        })?;
+    encoder.into_compressed()?; // Seals the data, flushing the last words to `frame`.
+
+    let num_words = (frame.len() / 4 - HEADER_WORDS) as u32;
+    frame[4 * (HEADER_WORDS - 1)..4 * HEADER_WORDS].copy_from_slice(&num_words.to_le_bytes());
+    let mut checksum = Checksum::new();
+    checksum.update(&frame);
+    frame.extend_from_slice(&checksum.finish().to_le_bytes());
+    Ok(frame)
+}
+
+/// Validates and decodes a frame that was produced by [`encode_frame`].
+pub fn decode_frame(bytes: &[u8]) -> Result<Frame, FrameError> {
+    if bytes.len() > MAX_FRAME_BYTES {
+        return Err(FrameError::Malformed("frame too long"));
+    }
+    if bytes.len() % 4 != 0 || bytes.len() < 4 * (HEADER_WORDS + 1) {
+        return Err(FrameError::Malformed("invalid frame length"));
+    }
+
+    let (content, checksum) = bytes.split_at(bytes.len() - 4);
+    let mut expected_checksum = Checksum::new();
+    expected_checksum.update(content);
+    if u32::from_le_bytes(checksum.try_into().unwrap()) != expected_checksum.finish() {
+        return Err(FrameError::ChecksumMismatch);
+    }
+
+    let mut words = content
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()));
+    let mut header = [0u32; HEADER_WORDS];
+    for (dst, src) in header.iter_mut().zip(&mut words) {
+        *dst = src;
+    }
+    let [magic, index, num_latents, scale, num_words] = header;
+    if magic != FRAME_MAGIC {
+        return Err(FrameError::Malformed("wrong magic number"));
+    }
+    if num_latents as usize > MAX_LATENTS_PER_FRAME {
+        return Err(FrameError::Malformed("too many latents"));
+    }
+    if num_words as usize != words.len() {
+        return Err(FrameError::Malformed("inconsistent word count"));
+    }
+    let scale = f32::from_bits(scale);
+    let model = model(scale)?;
+
+    // Decodes straight from the frame's bytes, without copying them into a `Vec<u32>`.
+    let mut decoder =
+        RangeDecoder::<u32, u64, _>::with_backend(InfallibleIteratorReadWords::new(words))
+            .unwrap_or_else(|infallible| match infallible {});
+    let latents = decoder
+        .decode_iid_symbols(num_latents as usize, &model)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| FrameError::InvalidData)?;
+
+    Ok(Frame {
+        index,
+        scale,
+        latents,
+    })
+}
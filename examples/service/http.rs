@@ -0,0 +1,172 @@
+//! Just enough HTTP/1.1 to stream a response body with chunked transfer encoding.
+//!
+//! A real service would use an HTTP (or gRPC) library instead. This module only implements
+//! the parts of the protocol that the demo needs so that the demo has no dependencies
+//! beyond the ones `constriction` already uses, and so that the mapping from frames of
+//! compressed data to the wire format is easy to follow: each frame is sent as exactly one
+//! HTTP chunk, and a zero sized chunk ends the stream.
+
+use std::io::{self, BufRead, Read, Write};
+
+/// Upper bound on the length of the request line, the status line, and each header line.
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// Upper bound on the number of header lines.
+const MAX_HEADERS: usize = 64;
+
+fn invalid_data(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason)
+}
+
+/// Reads a line terminated by `"\r\n"` and returns it without the terminator.
+fn read_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = Vec::new();
+    Read::take(&mut *reader, MAX_LINE_BYTES as u64 + 2).read_until(b'\n', &mut line)?;
+    if !line.ends_with(b"\r\n") {
+        return Err(invalid_data("line too long or unexpected end of stream"));
+    }
+    line.truncate(line.len() - 2);
+    String::from_utf8(line).map_err(|_| invalid_data("line is not valid UTF-8"))
+}
+
+/// Reads the first line of an HTTP message and skips all header lines.
+fn read_head(reader: &mut impl BufRead) -> io::Result<(String, Vec<String>)> {
+    let first_line = read_line(reader)?;
+    let mut headers = Vec::new();
+    loop {
+        let line = read_line(reader)?;
+        if line.is_empty() {
+            return Ok((first_line, headers));
+        }
+        if headers.len() == MAX_HEADERS {
+            return Err(invalid_data("too many headers"));
+        }
+        headers.push(line);
+    }
+}
+
+/// Reads the head of a request and returns the method and the request target.
+pub fn read_request_head(reader: &mut impl BufRead) -> io::Result<(String, String)> {
+    let (request_line, _headers) = read_head(reader)?;
+    let mut parts = request_line.split(' ');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(method), Some(target), Some(version)) if version.starts_with("HTTP/1.") => {
+            Ok((method.to_string(), target.to_string()))
+        }
+        _ => Err(invalid_data("malformed request line")),
+    }
+}
+
+/// Reads the head of a response, checks that its status is `200 OK` and that its body
+/// uses chunked transfer encoding.
+pub fn read_response_head(reader: &mut impl BufRead) -> io::Result<()> {
+    let (status_line, headers) = read_head(reader)?;
+    if status_line.split(' ').nth(1) != Some("200") {
+        return Err(io::Error::new(io::ErrorKind::Other, status_line));
+    }
+    let is_chunked = headers.iter().any(|header| {
+        let header = header.to_ascii_lowercase();
+        header.starts_with("transfer-encoding:") && header.trim_end().ends_with("chunked")
+    });
+    if !is_chunked {
+        return Err(invalid_data("expected chunked transfer encoding"));
+    }
+    Ok(())
+}
+
+/// Writes a response that has no body.
+pub fn write_error_response(writer: &mut impl Write, status: &str) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+        status
+    )?;
+    writer.flush()
+}
+
+/// Writes the body of an HTTP response as a sequence of chunks.
+#[derive(Debug)]
+pub struct ChunkedWriter<W: Write> {
+    inner: W,
+}
+
+impl<W: Write> ChunkedWriter<W> {
+    /// Writes the head of a `200 OK` response and returns a writer for its body.
+    pub fn start_response(mut inner: W, content_type: &str) -> io::Result<Self> {
+        write!(
+            inner,
+            "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nTransfer-Encoding: chunked\r\n\
+            Connection: close\r\n\r\n",
+            content_type
+        )?;
+        inner.flush()?;
+        Ok(Self { inner })
+    }
+
+    /// Sends `data` as a single chunk and flushes it so that the receiver can start
+    /// decoding it right away. Empty `data` would end the body, so it's skipped.
+    pub fn write_chunk(&mut self, data: &[u8]) -> io::Result<()> {
+        if !data.is_empty() {
+            write!(self.inner, "{:x}\r\n", data.len())?;
+            self.inner.write_all(data)?;
+            self.inner.write_all(b"\r\n")?;
+            self.inner.flush()?;
+        }
+        Ok(())
+    }
+
+    /// Ends the body with a zero sized chunk.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.inner.write_all(b"0\r\n\r\n")?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+/// Reads the body of an HTTP response chunk by chunk.
+#[derive(Debug)]
+pub struct ChunkedReader<R: BufRead> {
+    inner: R,
+    max_chunk_bytes: usize,
+    finished: bool,
+}
+
+impl<R: BufRead> ChunkedReader<R> {
+    /// Creates a reader that rejects chunks larger than `max_chunk_bytes` before it
+    /// allocates any memory for them.
+    pub fn new(inner: R, max_chunk_bytes: usize) -> Self {
+        Self {
+            inner,
+            max_chunk_bytes,
+            finished: false,
+        }
+    }
+
+    /// Returns the next chunk, or `None` once the body has ended.
+    pub fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.finished {
+            return Ok(None);
+        }
+
+        let line = read_line(&mut self.inner)?;
+        let size = line.split(';').next().unwrap_or_default().trim(); // Ignores extensions.
+        let size = usize::from_str_radix(size, 16).map_err(|_| invalid_data("bad chunk size"))?;
+        if size > self.max_chunk_bytes {
+            return Err(invalid_data("chunk too large"));
+        }
+
+        if size == 0 {
+            // Skip trailers (if any) up to and including the final empty line.
+            while !read_line(&mut self.inner)?.is_empty() {}
+            self.finished = true;
+            return Ok(None);
+        }
+
+        let mut chunk = vec![0; size];
+        self.inner.read_exact(&mut chunk)?;
+        if !read_line(&mut self.inner)?.is_empty() {
+            return Err(invalid_data("chunk not terminated by CRLF"));
+        }
+        Ok(Some(chunk))
+    }
+}
@@ -0,0 +1,283 @@
+//! Streaming compressed latents over HTTP
+//!
+//! This example is a blueprint for integrating `constriction` into a network service. A
+//! server compresses a sequence of latent representations (e.g., the quantized latents
+//! of a learned image or video codec) frame by frame and streams the frames to a client
+//! over HTTP/1.1 with chunked transfer encoding. The client decodes each frame as soon as
+//! it arrives, without waiting for the rest of the response. The example demonstrates:
+//!
+//! - **Safe framing:** each frame is self-delimiting and carries its own model
+//!   parameters, and the receiver enforces limits on all length fields before it allocates
+//!   memory (see module `framing`). A stream that ends without the terminating empty
+//!   chunk is reported as an error rather than silently accepted as complete.
+//! - **Checksums:** each frame ends in a checksum that the receiver verifies before it
+//!   decodes anything.
+//! - **Cancellation:** the client can hang up at any time, which the server notices on its
+//!   next write; and shutting down the server aborts frames that are still being encoded
+//!   because the encoder writes its compressed words through a callback (a
+//!   [`FallibleCallbackWriteWords`](constriction::backends::FallibleCallbackWriteWords)
+//!   backend) that checks for cancellation.
+//!
+//! The example uses only the standard library's blocking sockets and threads so that it
+//! doesn't pull in any dependencies. Porting it to an async runtime or to gRPC (e.g., with
+//! server-side streaming of `bytes` messages, one message per frame) only affects module
+//! `http` and the functions `serve` and `fetch`; the framing stays the same.
+//!
+//! Run it with:
+//!
+//! ```sh
+//! cargo run --example service --features service
+//! ```
+
+mod framing;
+mod http;
+
+use std::{
+    io::{self, BufReader, BufWriter, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+use probability::distribution::{Gaussian, Inverse};
+use rand::{Rng, SeedableRng};
+use rand_xoshiro::Xoshiro256StarStar;
+
+use framing::{decode_frame, encode_frame, Frame, FrameError, LATENT_BOUND, MAX_FRAME_BYTES};
+use http::{ChunkedReader, ChunkedWriter};
+
+/// A flag, shared across threads, that signals that some work should be aborted.
+#[derive(Debug, Clone, Default)]
+pub struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+const LATENTS_PER_FRAME: usize = 4096;
+
+/// Upper bound on the number of frames that a client may request.
+const MAX_FRAMES_PER_REQUEST: u32 = 1_000_000;
+
+/// Stands in for the encoder network of a learned codec: returns the scale of the
+/// (hyper-)prior and the quantized latents of frame `index` of the data set `seed`.
+fn latents(seed: u64, index: u32) -> (f32, Vec<i32>) {
+    let mut rng = Xoshiro256StarStar::seed_from_u64(seed ^ ((index as u64) << 32));
+    let scale = rng.gen_range(0.5f32..20.0);
+    let distribution = Gaussian::new(0.0, scale as f64);
+    let latents = (0..LATENTS_PER_FRAME)
+        .map(|_| {
+            let latent = distribution
+                .inverse(rng.gen_range(1e-9..1.0 - 1e-9))
+                .round() as i32;
+            latent.clamp(-LATENT_BOUND, LATENT_BOUND)
+        })
+        .collect();
+    (scale, latents)
+}
+
+// SERVER ======================================================================
+
+struct Request {
+    seed: u64,
+    num_frames: u32,
+}
+
+/// Parses request targets of the form `/latents?seed=42&frames=16`.
+fn parse_target(target: &str) -> Option<Request> {
+    let query = target.strip_prefix("/latents?")?;
+    let mut seed = None;
+    let mut num_frames = None;
+    for pair in query.split('&') {
+        match pair.split_once('=')? {
+            ("seed", value) => seed = Some(value.parse().ok()?),
+            ("frames", value) => num_frames = Some(value.parse().ok()?),
+            _ => return None,
+        }
+    }
+    let num_frames = num_frames?;
+    if num_frames > MAX_FRAMES_PER_REQUEST {
+        return None;
+    }
+    Some(Request {
+        seed: seed?,
+        num_frames,
+    })
+}
+
+/// Accepts connections until `shutdown` is triggered, serving each one on its own thread.
+fn serve(listener: TcpListener, shutdown: Cancellation) -> io::Result<()> {
+    // Poll so that the loop notices `shutdown` even if no client connects anymore.
+    listener.set_nonblocking(true)?;
+    let mut workers = Vec::new();
+    while !shutdown.is_cancelled() {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                let shutdown = shutdown.clone();
+                workers.push(thread::spawn(move || {
+                    if let Err(err) = handle_connection(stream, &shutdown) {
+                        eprintln!("[server] Error while serving {}: {}", peer, err);
+                    }
+                }));
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(10))
+            }
+            Err(err) => return Err(err),
+        }
+    }
+
+    for worker in workers {
+        worker.join().expect("Worker thread panicked.");
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: TcpStream, shutdown: &Cancellation) -> io::Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let (method, target) = http::read_request_head(&mut BufReader::new(&stream))?;
+    let request = match (method.as_str(), parse_target(&target)) {
+        ("GET", Some(request)) => request,
+        _ => return http::write_error_response(&mut &stream, "400 Bad Request"),
+    };
+
+    let mut body = ChunkedWriter::start_response(
+        BufWriter::new(&stream),
+        "application/x-constriction-frames",
+    )?;
+    for index in 0..request.num_frames {
+        let (scale, latents) = latents(request.seed, index);
+        let frame = match encode_frame(index, scale, &latents, shutdown) {
+            Ok(frame) => frame,
+            Err(FrameError::Cancelled) => {
+                // Hang up without sending the terminating chunk so that the client can
+                // tell that the stream is incomplete.
+                println!("[server] Shutting down; aborted stream at frame {}.", index);
+                return Ok(());
+            }
+            Err(err) => return Err(io::Error::new(io::ErrorKind::Other, err)),
+        };
+
+        if let Err(err) = body.write_chunk(&frame) {
+            // The client cancelled the request (or the connection broke). Either way,
+            // there's no point in encoding any more frames.
+            println!(
+                "[server] Client hung up after receiving at most {} frames ({:?}).",
+                index,
+                err.kind()
+            );
+            return Ok(());
+        }
+    }
+    body.finish()?;
+    Ok(())
+}
+
+// CLIENT ======================================================================
+
+/// Requests `target` from the server at `addr` and calls `on_frame` with each decoded
+/// frame as soon as it arrives. Cancels the request once `on_frame` returns `false`.
+///
+/// Returns the number of frames received.
+fn fetch(
+    addr: SocketAddr,
+    target: &str,
+    mut on_frame: impl FnMut(Frame) -> bool,
+) -> io::Result<u32> {
+    let stream = TcpStream::connect(addr)?;
+    write!(
+        &stream,
+        "GET {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n\r\n",
+        target, addr
+    )?;
+
+    let mut reader = BufReader::new(&stream);
+    http::read_response_head(&mut reader)?;
+    let mut chunks = ChunkedReader::new(reader, MAX_FRAME_BYTES);
+    let mut num_frames = 0;
+    while let Some(chunk) = chunks.next_chunk()? {
+        let frame =
+            decode_frame(&chunk).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        if frame.index != num_frames {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "frames arrived out of order",
+            ));
+        }
+        num_frames += 1;
+
+        if !on_frame(frame) {
+            stream.shutdown(Shutdown::Both)?;
+            break;
+        }
+    }
+    Ok(num_frames)
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let addr = listener.local_addr()?;
+    let shutdown = Cancellation::default();
+    let server = {
+        let shutdown = shutdown.clone();
+        thread::spawn(move || serve(listener, shutdown))
+    };
+
+    // Stream a complete response and check that every frame decodes to the latents
+    // that the server compressed.
+    let seed = 42;
+    let num_frames = fetch(
+        addr,
+        &format!("/latents?seed={}&frames=16", seed),
+        |frame| {
+            let (scale, latents) = latents(seed, frame.index);
+            assert_eq!(frame.scale, scale);
+            assert_eq!(frame.latents, latents);
+            true
+        },
+    )?;
+    assert_eq!(num_frames, 16);
+    println!(
+        "[client] Received and verified {} frames ({} latents).",
+        num_frames,
+        num_frames as usize * LATENTS_PER_FRAME
+    );
+
+    // Cancel a (very long) response after a few frames. The server notices this once its
+    // socket buffers are full and stops encoding.
+    let num_frames = fetch(addr, "/latents?seed=7&frames=1000000", |frame| {
+        frame.index < 2
+    })?;
+    assert_eq!(num_frames, 3);
+    println!("[client] Cancelled request after {} frames.", num_frames);
+
+    // A corrupted frame is rejected before decoding.
+    let (scale, latents) = latents(seed, 0);
+    let mut frame = encode_frame(0, scale, &latents, &Cancellation::default())?;
+    let last_byte = frame.len() - 5;
+    frame[last_byte] ^= 0x10;
+    assert!(matches!(
+        decode_frame(&frame),
+        Err(FrameError::ChecksumMismatch)
+    ));
+    println!("[client] Detected a corrupted frame.");
+
+    // Requests for too many frames are refused.
+    let err = fetch(addr, "/latents?seed=7&frames=1000000000", |_| true).unwrap_err();
+    println!("[client] Server refused oversized request ({}).", err);
+
+    shutdown.cancel();
+    server.join().expect("Server thread panicked.")?;
+    Ok(())
+}
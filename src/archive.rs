@@ -0,0 +1,552 @@
+//! A simple file format for storing many named compressed messages in one file
+//!
+//! The entropy coders in this crate produce compressed data as a sequence of words (usually
+//! `u32`s), but they don't prescribe how to store that data. This module provides a minimal
+//! container format for the common case where a dataset consists of many independently
+//! compressed messages, each identified by a name (e.g., the file names of the original
+//! images or the keys of a database). An [`ArchiveWriter`] appends messages to any
+//! [`Write`]r, and an [`ArchiveReader`] reads them back by name from any [`Read`]er that
+//! implements [`Seek`], without reading the rest of the archive.
+//!
+//! Along with the compressed data, the archive stores the following metadata for each
+//! message (see [`Entry`]):
+//!
+//! - a `model_id`, i.e., an arbitrary `u32` with which you can record which entropy model
+//!   (or model version) you used to compress the message; and
+//! - an optional `checkpoint`, i.e., a position of a [`DefaultAnsCoder`] within the message
+//!   (as returned by [`Pos::pos`]) to which a decoder can [`Seek::seek`] directly.
+//!
+//! This module is only available with the `std` feature (which is enabled by default).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::{
+//!     archive::{ArchiveReader, ArchiveWriter},
+//!     stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode},
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+//! let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+//! let messages = [("first", vec![3, -7, 12]), ("second", vec![-20, 0, 5, 8, 1])];
+//!
+//! // Compress each message and add it to the archive.
+//! let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+//! for (name, message) in &messages {
+//!     let mut coder = DefaultAnsCoder::new();
+//!     coder.encode_iid_symbols_reverse(message, model).unwrap();
+//!     writer.add(name, &coder.into_compressed().unwrap(), 1, None).unwrap();
+//! }
+//! let file = writer.finish().unwrap();
+//!
+//! // Read back a single message by its name.
+//! let mut reader = ArchiveReader::new(std::io::Cursor::new(file)).unwrap();
+//! assert_eq!(reader.len(), 2);
+//! assert_eq!(reader.entry("second").unwrap().model_id, 1);
+//! let compressed = reader.read("second").unwrap();
+//! let mut coder = DefaultAnsCoder::from_compressed(compressed).unwrap();
+//! let decoded = coder
+//!     .decode_iid_symbols(5, model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, messages[1].1);
+//! ```
+//!
+//! # Format
+//!
+//! All integers are stored in little-endian byte order. An archive consists of:
+//!
+//! - a header: the 4 bytes `b"CNSA"` followed by the format version as a `u32`
+//!   (currently `1`);
+//! - the payload: the compressed data of all messages, as `u32` words, in the order in
+//!   which they were added;
+//! - the index: for each message, the length of its name in bytes (`u32`), the name in
+//!   UTF-8, the `offset` and `len` of its compressed data in words (`u64` each, where
+//!   `offset` is relative to the start of the payload), the `model_id` (`u32`), and a flag
+//!   byte that is `1` if a checkpoint follows (two `u64`s: position and state) and `0`
+//!   otherwise;
+//! - a footer: the byte offset of the index from the start of the archive (`u64`), the
+//!   number of messages (`u64`), and again the 4 bytes `b"CNSA"`.
+//!
+//! Since the index is at the end, an [`ArchiveWriter`] can stream messages to its output
+//! without knowing in advance how many messages there will be.
+//!
+//! [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+//! [`Pos::pos`]: crate::Pos::pos
+//! [`Seek::seek`]: crate::Seek::seek
+
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    fmt::Display,
+    io::{self, Read, Seek, SeekFrom, Write},
+    prelude::v1::*,
+};
+
+const MAGIC: [u8; 4] = *b"CNSA";
+const VERSION: u32 = 1;
+const HEADER_LEN: u64 = 8;
+const FOOTER_LEN: u64 = 20;
+
+/// Metadata of a single message in an archive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Entry {
+    /// Position of the first word of the message, counted in words from the start of the
+    /// payload.
+    pub offset: u64,
+
+    /// Number of `u32` words of compressed data.
+    pub len: u64,
+
+    /// Arbitrary identifier of the entropy model with which the message was compressed.
+    pub model_id: u32,
+
+    /// An optional `(position, state)` pair, e.g., as returned by
+    /// [`AnsCoder::pos`](crate::Pos::pos).
+    pub checkpoint: Option<(u64, u64)>,
+}
+
+/// Error type for reading and writing archives.
+///
+/// Returned by all fallible methods of [`ArchiveWriter`] and [`ArchiveReader`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ArchiveError {
+    /// Reading from or writing to the underlying file failed.
+    Io(io::Error),
+
+    /// The data is not a valid archive (or it was written by an incompatible version).
+    InvalidFormat,
+
+    /// An [`ArchiveWriter`] already contains a message with this name.
+    DuplicateName(String),
+
+    /// The name of a message is too long to be stored in an archive (the format stores
+    /// name lengths as `u32`s). Contains the length of the name in bytes.
+    NameTooLong(usize),
+
+    /// An [`ArchiveReader`] contains no message with this name.
+    NotFound(String),
+}
+
+impl Display for ArchiveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "I/O error: {}", err),
+            Self::InvalidFormat => write!(f, "Invalid archive format."),
+            Self::DuplicateName(name) => write!(f, "Duplicate message name \"{}\".", name),
+            Self::NameTooLong(len) => write!(f, "Message name too long ({} bytes).", len),
+            Self::NotFound(name) => write!(f, "No message with name \"{}\".", name),
+        }
+    }
+}
+
+impl std::error::Error for ArchiveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Writes compressed messages into an archive.
+///
+/// Call [`add`](Self::add) for each message and then [`finish`](Self::finish), which writes
+/// the index. Dropping an `ArchiveWriter` without calling `finish` leaves an invalid
+/// archive. See [module level documentation](self) for an example.
+#[derive(Debug)]
+pub struct ArchiveWriter<W: Write> {
+    writer: W,
+    entries: BTreeMap<String, Entry>,
+    /// The order in which messages were added, so that the index is written in this order.
+    names: Vec<String>,
+    num_words: u64,
+}
+
+impl<W: Write> ArchiveWriter<W> {
+    /// Writes the header of an archive to `writer`.
+    pub fn new(mut writer: W) -> Result<Self, ArchiveError> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_le_bytes())?;
+        Ok(Self {
+            writer,
+            entries: BTreeMap::new(),
+            names: Vec::new(),
+            num_words: 0,
+        })
+    }
+
+    /// Appends a message with the given `name` to the archive.
+    ///
+    /// Returns an error if the archive already contains a message with this name, or if
+    /// `name` is longer than `u32::MAX` bytes.
+    pub fn add(
+        &mut self,
+        name: &str,
+        compressed: &[u32],
+        model_id: u32,
+        checkpoint: Option<(u64, u64)>,
+    ) -> Result<(), ArchiveError> {
+        let entry = Entry {
+            offset: self.num_words,
+            len: compressed.len() as u64,
+            model_id,
+            checkpoint,
+        };
+        if self.entries.contains_key(name) {
+            return Err(ArchiveError::DuplicateName(name.to_string()));
+        }
+        if u32::try_from(name.len()).is_err() {
+            return Err(ArchiveError::NameTooLong(name.len()));
+        }
+
+        let bytes = compressed
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .collect::<Vec<_>>();
+        self.writer.write_all(&bytes)?;
+        self.entries.insert(name.to_string(), entry);
+        self.names.push(name.to_string());
+        self.num_words += entry.len;
+        Ok(())
+    }
+
+    /// Returns the number of messages added so far.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Returns `true` if no messages have been added yet.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Writes the index and footer and returns the underlying writer.
+    pub fn finish(mut self) -> Result<W, ArchiveError> {
+        let mut index = Vec::new();
+        for name in &self.names {
+            let entry = &self.entries[name];
+            let name_len =
+                u32::try_from(name.len()).map_err(|_| ArchiveError::NameTooLong(name.len()))?;
+            index.extend_from_slice(&name_len.to_le_bytes());
+            index.extend_from_slice(name.as_bytes());
+            index.extend_from_slice(&entry.offset.to_le_bytes());
+            index.extend_from_slice(&entry.len.to_le_bytes());
+            index.extend_from_slice(&entry.model_id.to_le_bytes());
+            match entry.checkpoint {
+                Some((position, state)) => {
+                    index.push(1);
+                    index.extend_from_slice(&position.to_le_bytes());
+                    index.extend_from_slice(&state.to_le_bytes());
+                }
+                None => index.push(0),
+            }
+        }
+
+        let index_offset = HEADER_LEN + self.num_words * 4;
+        index.extend_from_slice(&index_offset.to_le_bytes());
+        index.extend_from_slice(&(self.names.len() as u64).to_le_bytes());
+        index.extend_from_slice(&MAGIC);
+        self.writer.write_all(&index)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads compressed messages from an archive by their names.
+///
+/// Creating an `ArchiveReader` reads only the footer and the index; each call to
+/// [`read`](Self::read) then seeks to the requested message and reads only its compressed
+/// data. See [module level documentation](self) for an example.
+#[derive(Debug)]
+pub struct ArchiveReader<R: Read + Seek> {
+    reader: R,
+    entries: BTreeMap<String, Entry>,
+    payload_len: u64,
+}
+
+impl<R: Read + Seek> ArchiveReader<R> {
+    /// Reads the index of the archive in `reader`.
+    ///
+    /// Returns [`ArchiveError::InvalidFormat`] if `reader` does not contain a valid archive.
+    pub fn new(mut reader: R) -> Result<Self, ArchiveError> {
+        let total_len = reader.seek(SeekFrom::End(0))?;
+        if total_len < HEADER_LEN + FOOTER_LEN {
+            return Err(ArchiveError::InvalidFormat);
+        }
+
+        reader.seek(SeekFrom::Start(0))?;
+        let mut header = [0u8; HEADER_LEN as usize];
+        reader.read_exact(&mut header)?;
+        if header[..4] != MAGIC || header[4..] != VERSION.to_le_bytes() {
+            return Err(ArchiveError::InvalidFormat);
+        }
+
+        reader.seek(SeekFrom::Start(total_len - FOOTER_LEN))?;
+        let mut footer = [0u8; FOOTER_LEN as usize];
+        reader.read_exact(&mut footer)?;
+        let mut footer = &footer[..];
+        let index_offset = read_u64(&mut footer)?;
+        let num_entries = read_u64(&mut footer)?;
+        if footer != MAGIC
+            || index_offset < HEADER_LEN
+            || index_offset > total_len - FOOTER_LEN
+            || (index_offset - HEADER_LEN) & 3 != 0
+        {
+            return Err(ArchiveError::InvalidFormat);
+        }
+
+        reader.seek(SeekFrom::Start(index_offset))?;
+        let index_len = usize::try_from(total_len - FOOTER_LEN - index_offset)
+            .map_err(|_| ArchiveError::InvalidFormat)?;
+        let mut index = vec![0u8; index_len];
+        reader.read_exact(&mut index)?;
+
+        let payload_len = (index_offset - HEADER_LEN) / 4;
+        let mut remaining = &index[..];
+        let mut entries = BTreeMap::new();
+        for _ in 0..num_entries {
+            let name_len = read_u32(&mut remaining)? as usize;
+            let name = take(&mut remaining, name_len)?;
+            let name = std::str::from_utf8(name).map_err(|_| ArchiveError::InvalidFormat)?;
+            let offset = read_u64(&mut remaining)?;
+            let len = read_u64(&mut remaining)?;
+            let model_id = read_u32(&mut remaining)?;
+            let checkpoint = match take(&mut remaining, 1)? {
+                [0] => None,
+                [1] => Some((read_u64(&mut remaining)?, read_u64(&mut remaining)?)),
+                _ => return Err(ArchiveError::InvalidFormat),
+            };
+
+            if offset
+                .checked_add(len)
+                .filter(|&end| end <= payload_len)
+                .is_none()
+            {
+                return Err(ArchiveError::InvalidFormat);
+            }
+            let entry = Entry {
+                offset,
+                len,
+                model_id,
+                checkpoint,
+            };
+            if entries.insert(name.to_string(), entry).is_some() {
+                return Err(ArchiveError::InvalidFormat);
+            }
+        }
+        if !remaining.is_empty() {
+            return Err(ArchiveError::InvalidFormat);
+        }
+
+        Ok(Self {
+            reader,
+            entries,
+            payload_len,
+        })
+    }
+
+    /// Returns the metadata of the message with the given `name`, or `None` if the archive
+    /// contains no such message.
+    pub fn entry(&self, name: &str) -> Option<&Entry> {
+        self.entries.get(name)
+    }
+
+    /// Returns `true` if the archive contains a message with the given `name`.
+    pub fn contains(&self, name: &str) -> bool {
+        self.entries.contains_key(name)
+    }
+
+    /// Iterates over the names and metadata of all messages, sorted by name.
+    pub fn entries(&self) -> impl Iterator<Item = (&str, &Entry)> + '_ {
+        self.entries
+            .iter()
+            .map(|(name, entry)| (name.as_str(), entry))
+    }
+
+    /// Returns the number of messages in the archive.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if the archive contains no messages.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the total number of words of compressed data of all messages.
+    pub fn payload_len(&self) -> u64 {
+        self.payload_len
+    }
+
+    /// Reads the compressed data of the message with the given `name`.
+    pub fn read(&mut self, name: &str) -> Result<Vec<u32>, ArchiveError> {
+        let entry = *self
+            .entries
+            .get(name)
+            .ok_or_else(|| ArchiveError::NotFound(name.to_string()))?;
+        let num_bytes = usize::try_from(entry.len * 4).map_err(|_| ArchiveError::InvalidFormat)?;
+
+        self.reader
+            .seek(SeekFrom::Start(HEADER_LEN + entry.offset * 4))?;
+        let mut bytes = vec![0u8; num_bytes];
+        self.reader.read_exact(&mut bytes)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect())
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], ArchiveError> {
+    if bytes.len() < len {
+        return Err(ArchiveError::InvalidFormat);
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
+
+fn read_u32(bytes: &mut &[u8]) -> Result<u32, ArchiveError> {
+    let head = take(bytes, 4)?;
+    Ok(u32::from_le_bytes([head[0], head[1], head[2], head[3]]))
+}
+
+fn read_u64(bytes: &mut &[u8]) -> Result<u64, ArchiveError> {
+    Ok(read_u32(bytes)? as u64 | (read_u32(bytes)? as u64) << 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stream::{
+            model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, stack::DefaultAnsCoder,
+            Decode, Encode,
+        },
+        Pos, Seek as _,
+    };
+
+    use probability::distribution::Gaussian;
+    use std::io::Cursor;
+
+    #[test]
+    fn round_trip() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 20.0));
+        let messages = (0..10)
+            .map(|i| {
+                let message = (0..10 * i)
+                    .map(|j| (i * 37 + j * 11) % 61 - 30)
+                    .collect::<Vec<_>>();
+                (format!("message-{}", i), message)
+            })
+            .collect::<Vec<_>>();
+
+        let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+        let mut checkpoints = Vec::new();
+        for (i, (name, message)) in messages.iter().enumerate() {
+            let mut coder = DefaultAnsCoder::new();
+            coder
+                .encode_iid_symbols_reverse(&message[message.len() / 2..], model)
+                .unwrap();
+            let (position, state) = coder.pos();
+            coder
+                .encode_iid_symbols_reverse(&message[..message.len() / 2], model)
+                .unwrap();
+            let checkpoint = (position as u64, state);
+            checkpoints.push(checkpoint);
+            writer
+                .add(
+                    name,
+                    &coder.into_compressed().unwrap(),
+                    i as u32,
+                    Some(checkpoint),
+                )
+                .unwrap();
+        }
+        assert!(matches!(
+            writer.add("message-3", &[1, 2, 3][..], 0, None),
+            Err(ArchiveError::DuplicateName(_))
+        ));
+        let file = writer.finish().unwrap();
+
+        let mut reader = ArchiveReader::new(Cursor::new(file)).unwrap();
+        assert_eq!(reader.len(), messages.len());
+        assert!(!reader.contains("message-10"));
+        assert!(matches!(
+            reader.read("message-10"),
+            Err(ArchiveError::NotFound(_))
+        ));
+
+        // Read messages in a different order than they were written.
+        for (i, (name, message)) in messages.iter().enumerate().rev() {
+            let entry = *reader.entry(name).unwrap();
+            assert_eq!(entry.model_id, i as u32);
+            assert_eq!(entry.checkpoint, Some(checkpoints[i]));
+
+            let compressed = reader.read(name).unwrap();
+            assert_eq!(compressed.len() as u64, entry.len);
+            let mut coder = DefaultAnsCoder::from_compressed(compressed.clone()).unwrap();
+            let decoded = coder
+                .decode_iid_symbols(message.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, message);
+
+            // Jump directly to the second half of the message.
+            let (position, state) = entry.checkpoint.unwrap();
+            let mut coder = DefaultAnsCoder::from_compressed(compressed).unwrap();
+            coder.seek((position as usize, state)).unwrap();
+            let decoded = coder
+                .decode_iid_symbols(message.len() - message.len() / 2, model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, message[message.len() / 2..]);
+        }
+    }
+
+    #[test]
+    fn empty_and_invalid() {
+        let file = ArchiveWriter::new(Vec::new()).unwrap().finish().unwrap();
+        assert_eq!(file.len() as u64, HEADER_LEN + FOOTER_LEN);
+        let reader = ArchiveReader::new(Cursor::new(file)).unwrap();
+        assert!(reader.is_empty());
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder
+            .encode_iid_symbols(
+                [1, 2, 3],
+                DefaultLeakyQuantizer::new(-10..=10).quantize(Gaussian::new(0.0, 3.0)),
+            )
+            .unwrap();
+        let mut writer = ArchiveWriter::new(Vec::new()).unwrap();
+        writer
+            .add("", &encoder.into_compressed().unwrap(), 7, None)
+            .unwrap();
+        let file = writer.finish().unwrap();
+
+        for len in 0..file.len() {
+            assert!(ArchiveReader::new(Cursor::new(&file[..len])).is_err());
+        }
+        let mut corrupted = file;
+        *corrupted.last_mut().unwrap() = b'X';
+        assert!(matches!(
+            ArchiveReader::new(Cursor::new(&corrupted[..])),
+            Err(ArchiveError::InvalidFormat)
+        ));
+    }
+}
@@ -46,7 +46,16 @@
 //! backend traits roughly what [`std::io::Cursor`] is to the `std::io::{Read, Write}`
 //! traits). A [`Cursor`] can hold either owned or borrowed data and can be used for reading
 //! and/or writing (if the buffer is mutable) with both [`Queue`] and [`Stack`] read/write
-//! semantics.
+//! semantics. If your compressed data is stored as bytes rather than words, then a
+//! [`StackByteCursor`] lets you decode it with `Stack` semantics without reversing it first.
+//! If you want to process each word as it is written (e.g., to send it over the network or
+//! to place it at a computed position in some container format), use one of the callback
+//! adapters [`FallibleCallbackWriteWords`] or [`InfallibleCallbackWriteWords`], whose
+//! `new_indexed` constructors also pass the position of each word to the callback. To send
+//! the same compressed data to two sinks at once (e.g., to a file and to a checksum), wrap
+//! them in a [`TeeWriteWords`]. To measure how many words an entropy coder reads and writes
+//! and how large its buffer grows (e.g., for capacity planning), wrap its backend in a
+//! [`CountingWords`].
 //!
 //! # Read/Write Semantics
 //!
@@ -93,7 +102,7 @@
 //!     backends::{FallibleCallbackWriteWords, FallibleIteratorReadWords},
 //!     stream::{
 //!         model::DefaultLeakyQuantizer,
-//!         queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+//!         queue::{ByteRangeDecoder, ByteRangeEncoder, DefaultRangeDecoder, DefaultRangeEncoder},
 //!         Decode, Encode,
 //!     },
 //! };
@@ -178,7 +187,7 @@
 //! [`ChainCoder`]: crate::stream::chain::ChainCoder
 //! [`AnsCoder`]: crate::stream::stack::AnsCoder
 
-use alloc::{boxed::Box, vec::Vec};
+use alloc::{boxed::Box, rc::Rc, sync::Arc, vec::Vec};
 use core::{
     convert::Infallible,
     fmt::{Debug, Display},
@@ -1069,10 +1078,14 @@ pub struct Cursor<Word, Buf> {
 /// of bounds without bounds checks.
 pub unsafe trait SafeBuf<Word>: AsRef<[Word]> {}
 
-unsafe impl<'a, Word> SafeBuf<Word> for &'a [Word] {}
-unsafe impl<'a, Word> SafeBuf<Word> for &'a mut [Word] {}
+unsafe impl<Word> SafeBuf<Word> for &[Word] {}
+unsafe impl<Word> SafeBuf<Word> for &mut [Word] {}
 unsafe impl<Word> SafeBuf<Word> for Vec<Word> {}
 unsafe impl<Word> SafeBuf<Word> for Box<[Word]> {}
+unsafe impl<Word> SafeBuf<Word> for Rc<[Word]> {}
+unsafe impl<Word> SafeBuf<Word> for Arc<[Word]> {}
+#[cfg(feature = "bytes")]
+unsafe impl SafeBuf<u8> for bytes::Bytes {}
 
 impl<Word, Buf> Cursor<Word, Buf> {
     /// Creates a `Cursor` for the buffer `buf` and initializes the cursor position to point
@@ -1596,6 +1609,202 @@ where
     }
 }
 
+// STACK ADAPTER FOR IN-MEMORY BYTE BUFFERS ===================================
+
+/// Reads little-endian `Word`s from the back of an in-memory byte buffer.
+///
+/// Wraps a [`std::io::Cursor<Buf>`](std::io::Cursor) and implements
+/// [`ReadWords<Word, Stack>`](ReadWords) by reading `Word`s backwards, starting at the
+/// cursor's current position. This is the natural way to decode with an [`AnsCoder`] from
+/// bytes that you wrote (e.g., with an `std::io::Cursor`) in the order in which
+/// [`AnsCoder::into_compressed`] returns its words, and it avoids the `O(n)` reversal that
+/// [`Cursor::into_reversed`] would perform. Each word is expected to be serialized in
+/// little-endian byte order, which is what `u32::to_le_bytes` produces and what the Python
+/// API uses on all common platforms.
+///
+/// Bytes after the position of the wrapped `std::io::Cursor` are ignored. Use
+/// [`into_inner`](Self::into_inner) to get the `std::io::Cursor` back; its position will
+/// then point to the end of the remaining (i.e., not yet read) bytes.
+///
+/// The byte buffer `Buf` defaults to `Vec<u8>` but can be any type that implements
+/// `AsRef<[u8]>`. Using a reference-counted buffer such as `Arc<[u8]>` or (with feature
+/// `bytes`) `bytes::Bytes` allows you to decode from a shared buffer without copying it, and
+/// cloning the resulting `StackByteCursor` (e.g., to decode several regions concurrently)
+/// then only increments a reference count.
+///
+/// `StackByteCursor` only implements `Stack` semantics. To decode with `Queue` semantics
+/// (e.g., with a range decoder) from a byte buffer, use a coder with `u8` words, such as a
+/// [`ByteRangeDecoder`]. Such a coder reads directly from any `AsRef<[u8]>` buffer via a
+/// [`Cursor`], e.g., `ByteRangeDecoder::<Cursor<u8, bytes::Bytes>>::from_compressed(buf)`.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     backends::StackByteCursor,
+///     stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode},
+///     UnwrapInfallible,
+/// };
+/// use probability::distribution::Gaussian;
+/// use std::io::Write;
+///
+/// let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+/// let symbols = [12, -7, 0, 33, -21];
+/// let mut coder = DefaultAnsCoder::new();
+/// coder.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+///
+/// // Write the compressed words in natural order into an in-memory byte buffer.
+/// let mut bytes = std::io::Cursor::new(Vec::new());
+/// for word in coder.into_compressed().unwrap_infallible() {
+///     bytes.write_all(&word.to_le_bytes()).unwrap();
+/// }
+///
+/// // Decode directly from the bytes, without reversing them.
+/// let backend = StackByteCursor::new(bytes).unwrap();
+/// let mut decoder = DefaultAnsCoder::from_compressed(backend).unwrap();
+/// let decoded = decoder
+///     .decode_iid_symbols(5, &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap_infallible();
+/// assert_eq!(decoded, symbols);
+/// assert!(decoder.is_empty());
+/// ```
+///
+/// [`AnsCoder`]: crate::stream::stack::AnsCoder
+/// [`AnsCoder::into_compressed`]: crate::stream::stack::AnsCoder::into_compressed
+/// [`ByteRangeDecoder`]: crate::stream::queue::ByteRangeDecoder
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct StackByteCursor<Word, Buf = Vec<u8>> {
+    buf: Buf,
+
+    /// Index one past the last byte of the next word to be read. Always satisfies
+    /// `pos <= buf.as_ref().len()` and is a multiple of `size_of::<Word>()`.
+    pos: usize,
+
+    phantom: PhantomData<Word>,
+}
+
+#[cfg(feature = "std")]
+impl<Word: crate::BitArray> StackByteCursor<Word> {
+    /// Creates a `StackByteCursor` that starts reading at the end of `buf`.
+    ///
+    /// Returns an error if the length of `buf` is not a multiple of the word size in bytes.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_vec(buf: Vec<u8>) -> Result<Self, ()> {
+        Self::from_buf(buf)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Word: crate::BitArray, Buf: AsRef<[u8]>> StackByteCursor<Word, Buf> {
+    /// Creates a `StackByteCursor` that starts reading at the position of `cursor`.
+    ///
+    /// Returns an error if the position of `cursor` is beyond the end of its buffer or if it
+    /// is not a multiple of the word size in bytes.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(cursor: std::io::Cursor<Buf>) -> Result<Self, ()> {
+        let pos = cursor.position();
+        let buf = cursor.into_inner();
+        match core::convert::TryInto::<usize>::try_into(pos) {
+            Ok(pos) if pos <= buf.as_ref().len() && pos % core::mem::size_of::<Word>() == 0 => {
+                Ok(Self {
+                    buf,
+                    pos,
+                    phantom: PhantomData,
+                })
+            }
+            _ => Err(()),
+        }
+    }
+
+    /// Creates a `StackByteCursor` that starts reading at the end of `buf`.
+    ///
+    /// Returns an error if the length of `buf` is not a multiple of the word size in bytes.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_buf(buf: Buf) -> Result<Self, ()> {
+        let pos = buf.as_ref().len() as u64;
+        let mut cursor = std::io::Cursor::new(buf);
+        cursor.set_position(pos);
+        Self::new(cursor)
+    }
+
+    /// Returns the underlying byte buffer, positioned at the end of the unread bytes.
+    pub fn into_inner(self) -> std::io::Cursor<Buf> {
+        let mut cursor = std::io::Cursor::new(self.buf);
+        cursor.set_position(self.pos as u64);
+        cursor
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Word: crate::BitArray, Buf: AsRef<[u8]>> ReadWords<Word, Stack>
+    for StackByteCursor<Word, Buf>
+{
+    type ReadError = Infallible;
+
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<Word>, Self::ReadError> {
+        let word_size = core::mem::size_of::<Word>();
+        if self.pos == 0 {
+            return Ok(None);
+        }
+        let start = self.pos - word_size;
+        let word = self.buf.as_ref()[start..self.pos].iter().enumerate().fold(
+            Word::zero(),
+            |acc, (i, &byte)| {
+                acc | Word::from(byte).expect("`Word` holds at least one byte") << (8 * i)
+            },
+        );
+        self.pos = start;
+        Ok(Some(word))
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        BoundedReadWords::<Word, Stack>::is_exhausted(self)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Word: crate::BitArray, Buf: AsRef<[u8]>> BoundedReadWords<Word, Stack>
+    for StackByteCursor<Word, Buf>
+{
+    #[inline(always)]
+    fn remaining(&self) -> usize {
+        self.pos / core::mem::size_of::<Word>()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Word, Buf> PosSeek for StackByteCursor<Word, Buf> {
+    type Position = usize;
+}
+
+#[cfg(feature = "std")]
+impl<Word: crate::BitArray, Buf: AsRef<[u8]>> Pos for StackByteCursor<Word, Buf> {
+    /// Returns the number of remaining words (i.e., the position in units of words).
+    #[inline(always)]
+    fn pos(&self) -> usize {
+        self.pos / core::mem::size_of::<Word>()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Word: crate::BitArray, Buf: AsRef<[u8]>> Seek for StackByteCursor<Word, Buf> {
+    /// Seeks to a position measured in units of words (see [`Pos::pos`]).
+    #[inline(always)]
+    fn seek(&mut self, pos: usize) -> Result<(), ()> {
+        match pos.checked_mul(core::mem::size_of::<Word>()) {
+            Some(pos) if pos <= self.buf.as_ref().len() => {
+                self.pos = pos;
+                Ok(())
+            }
+            _ => Err(()),
+        }
+    }
+}
+
 // READ ADAPTER FOR ITERATORS =================================================
 
 /// Adapter that turns an iterator over `Result<Word, ReadError>` into a data source.
@@ -1696,10 +1905,9 @@ impl<Iter: Iterator> InfallibleIteratorReadWords<Iter> {
     /// equivalent of that). The adapter calls `iter.fuse()` to ensure correct behavior.
     ///
     /// You can get the (fused) iterator back by calling [IntoIterator::into_iter].
-    pub fn new<I, Word, ReadError>(iter: I) -> Self
+    pub fn new<I>(iter: I) -> Self
     where
         I: IntoIterator<IntoIter = Iter>,
-        Iter: Iterator<Item = Result<Word, ReadError>>,
     {
         Self {
             inner: iter.into_iter().fuse(),
@@ -1782,6 +1990,70 @@ where
     }
 }
 
+impl<Callback> FallibleCallbackWriteWords<WithIndex<Callback>> {
+    /// Creates the adapter for a callback that receives the index of each word in addition
+    /// to the word itself.
+    ///
+    /// The callback is called as `write_callback(index, word)`, where `index` counts the
+    /// words written through this adapter, starting at `start_index`. Multiply `index` by
+    /// `core::mem::size_of::<Word>()` to obtain a byte offset, e.g., if you need to place
+    /// each word at a computed position in a container format.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     backends::FallibleCallbackWriteWords,
+    ///     stream::{model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, Encode},
+    /// };
+    /// use probability::distribution::Gaussian;
+    ///
+    /// // Reserve the first two words of `buf` for a header.
+    /// let mut buf = vec![0u32; 2];
+    /// let backend = FallibleCallbackWriteWords::new_indexed(2, |index, word| {
+    ///     if index >= buf.len() {
+    ///         buf.resize(index + 1, 0);
+    ///     }
+    ///     buf[index] = word;
+    ///     Ok::<(), std::convert::Infallible>(())
+    /// });
+    ///
+    /// let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+    /// let mut encoder = DefaultRangeEncoder::with_backend(backend);
+    /// encoder.encode_iid_symbols(&[3, -7, 12, 0, 25], &model).unwrap();
+    /// let num_words = encoder.into_compressed().unwrap().into_inner().next_index() - 2;
+    ///
+    /// assert_eq!(buf.len(), 2 + num_words);
+    /// ```
+    pub fn new_indexed(start_index: usize, write_callback: Callback) -> Self {
+        Self {
+            write_callback: WithIndex {
+                callback: write_callback,
+                next_index: start_index,
+            },
+        }
+    }
+}
+
+impl<Word, WriteError, Callback> WriteWords<Word>
+    for FallibleCallbackWriteWords<WithIndex<Callback>>
+where
+    Callback: FnMut(usize, Word) -> Result<(), WriteError>,
+    WriteError: Debug,
+{
+    type WriteError = WriteError;
+
+    fn write(&mut self, word: Word) -> Result<(), Self::WriteError> {
+        let WithIndex {
+            callback,
+            next_index,
+        } = &mut self.write_callback;
+        callback(*next_index, word)?;
+        *next_index += 1;
+        Ok(())
+    }
+}
+
 /// Adapter that turns an infallible callback into an infallible data sink.
 ///
 /// Wraps a callback function from `Word` to `()` and implements [`WriteWords<Word,
@@ -1819,9 +2091,449 @@ where
     }
 }
 
+impl<Callback> InfallibleCallbackWriteWords<WithIndex<Callback>> {
+    /// Creates the adapter for a callback that receives the index of each word in addition
+    /// to the word itself.
+    ///
+    /// The callback is called as `write_callback(index, word)`, where `index` counts the
+    /// words written through this adapter, starting at `start_index`. See
+    /// [`FallibleCallbackWriteWords::new_indexed`] for an example.
+    pub fn new_indexed(start_index: usize, write_callback: Callback) -> Self {
+        Self {
+            write_callback: WithIndex {
+                callback: write_callback,
+                next_index: start_index,
+            },
+        }
+    }
+}
+
+impl<Word, Callback> WriteWords<Word> for InfallibleCallbackWriteWords<WithIndex<Callback>>
+where
+    Callback: FnMut(usize, Word),
+{
+    type WriteError = Infallible;
+
+    fn write(&mut self, word: Word) -> Result<(), Infallible> {
+        let WithIndex {
+            callback,
+            next_index,
+        } = &mut self.write_callback;
+        callback(*next_index, word);
+        *next_index += 1;
+        Ok(())
+    }
+}
+
+/// A callback that gets passed the index of each word it writes.
+///
+/// You'll usually not construct a `WithIndex` directly but rather call
+/// [`FallibleCallbackWriteWords::new_indexed`] or
+/// [`InfallibleCallbackWriteWords::new_indexed`]. You get a `WithIndex` back when you call
+/// `into_inner` on the resulting adapter.
+#[derive(Clone, Debug)]
+pub struct WithIndex<Callback> {
+    callback: Callback,
+    next_index: usize,
+}
+
+impl<Callback> WithIndex<Callback> {
+    /// Returns the index that will be passed to the callback for the next written word.
+    ///
+    /// This is the `start_index` plus the number of words written so far.
+    pub fn next_index(&self) -> usize {
+        self.next_index
+    }
+
+    /// Returns the wrapped callback.
+    pub fn into_inner(self) -> Callback {
+        self.callback
+    }
+}
+
+// TEE ADAPTER ================================================================
+
+/// Adapter that duplicates every written word into two data sinks.
+///
+/// A `TeeWriteWords(first, second)` implements [`WriteWords<Word>`](WriteWords) if both
+/// `first` and `second` do. Each word is written first to `first` and then (a clone of it)
+/// to `second`. This allows you to, e.g., write compressed data to a file while at the
+/// same time computing a checksum or gathering statistics on the fly, without having to
+/// encode the data twice. Nesting `TeeWriteWords` lets you fan out to more than two sinks.
+///
+/// If writing to `first` fails then the word is not written to `second`, and `write`
+/// returns a [`TeeWriteError::First`]. If writing to `second` fails then the word has
+/// already been written to `first`, and `write` returns a [`TeeWriteError::Second`]. In
+/// either case, the two sinks may have diverged, so you should usually abandon both.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     backends::{InfallibleCallbackWriteWords, TeeWriteWords},
+///     stream::{model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, Encode},
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// // Archive the compressed data in a `Vec` and, at the same time, compute a simple
+/// // checksum of it (a real application might write to a file and feed a hasher).
+/// let mut checksum = 0u32;
+/// let backend = TeeWriteWords(
+///     Vec::new(),
+///     InfallibleCallbackWriteWords::new(|word: u32| checksum = checksum.rotate_left(5) ^ word),
+/// );
+///
+/// let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+/// let mut encoder = DefaultRangeEncoder::with_backend(backend);
+/// encoder.encode_iid_symbols(&[3, -7, 12, 0, 25], &model).unwrap();
+/// let TeeWriteWords(compressed, _) = encoder.into_compressed().unwrap();
+///
+/// let expected = compressed.iter().fold(0u32, |acc, &word| acc.rotate_left(5) ^ word);
+/// assert_eq!(checksum, expected);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TeeWriteWords<First, Second>(pub First, pub Second);
+
+impl<Word, First, Second> WriteWords<Word> for TeeWriteWords<First, Second>
+where
+    Word: Clone,
+    First: WriteWords<Word>,
+    Second: WriteWords<Word>,
+{
+    type WriteError = TeeWriteError<First::WriteError, Second::WriteError>;
+
+    #[inline(always)]
+    fn write(&mut self, word: Word) -> Result<(), Self::WriteError> {
+        self.0.write(word.clone()).map_err(TeeWriteError::First)?;
+        self.1.write(word).map_err(TeeWriteError::Second)
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.0.maybe_full() || self.1.maybe_full()
+    }
+}
+
+impl<Word, First, Second> BoundedWriteWords<Word> for TeeWriteWords<First, Second>
+where
+    Word: Clone,
+    First: BoundedWriteWords<Word>,
+    Second: BoundedWriteWords<Word>,
+{
+    /// Returns the smaller one of the two `space_left`s of the wrapped data sinks.
+    #[inline(always)]
+    fn space_left(&self) -> usize {
+        core::cmp::min(self.0.space_left(), self.1.space_left())
+    }
+}
+
+impl<First: PosSeek, Second> PosSeek for TeeWriteWords<First, Second> {
+    type Position = First::Position;
+}
+
+impl<First: Pos, Second> Pos for TeeWriteWords<First, Second> {
+    /// Returns the position of the first data sink (which is usually the one that stores
+    /// the compressed data, while the second one only observes it).
+    #[inline(always)]
+    fn pos(&self) -> First::Position {
+        self.0.pos()
+    }
+}
+
+/// Error type for [`TeeWriteWords`].
+///
+/// Indicates which one of the two wrapped data sinks failed.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum TeeWriteError<FirstError, SecondError> {
+    /// Writing to the first data sink failed (the word was not passed on to the second one).
+    First(FirstError),
+
+    /// Writing to the second data sink failed (after the word was written to the first one).
+    Second(SecondError),
+}
+
+impl<FirstError: Display, SecondError: Display> Display for TeeWriteError<FirstError, SecondError> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::First(err) => write!(f, "Error while writing to first data sink: {}", err),
+            Self::Second(err) => write!(f, "Error while writing to second data sink: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<FirstError, SecondError> std::error::Error for TeeWriteError<FirstError, SecondError>
+where
+    FirstError: std::error::Error + 'static,
+    SecondError: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::First(err) => Some(err),
+            Self::Second(err) => Some(err),
+        }
+    }
+}
+
+// ADAPTER FOR GATHERING STATISTICS ===========================================
+
+/// Statistics about the words that were transferred through a [`CountingWords`].
+///
+/// Use these statistics for capacity planning and buffer tuning, e.g., to determine how
+/// large a preallocated buffer or a network packet has to be for typical data. See
+/// [`CountingWords`] for an example.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct BackendStats {
+    /// The number of words that were successfully read from the backend.
+    pub num_words_read: u64,
+
+    /// The number of words that were successfully written to the backend.
+    pub num_words_written: u64,
+
+    /// The largest number of words that the backend held at the same time.
+    ///
+    /// The occupancy starts at the `initial_occupancy` passed to
+    /// [`CountingWords::with_initial_occupancy`] (or at zero), increases with each written
+    /// word, and decreases with each read word.
+    pub max_occupancy: usize,
+}
+
+/// A trait for backends that gather [`BackendStats`].
+///
+/// The entropy coders in `constriction` forward this trait through methods like
+/// [`AnsCoder::backend_stats`] and [`RangeEncoder::backend_stats`].
+///
+/// [`AnsCoder::backend_stats`]: crate::stream::stack::AnsCoder::backend_stats
+/// [`RangeEncoder::backend_stats`]: crate::stream::queue::RangeEncoder::backend_stats
+pub trait ReportStats {
+    /// Returns the statistics that were gathered since the backend was created or since the
+    /// statistics were last reset.
+    fn stats(&self) -> BackendStats;
+}
+
+/// Adapter that counts the words read from and written to the wrapped backend.
+///
+/// A `CountingWords<Backend>` implements the same backend traits as `Backend` (i.e.,
+/// [`ReadWords`], [`WriteWords`], their bounded variants, [`Pos`], and [`Seek`]) by
+/// delegating to the wrapped backend, and it keeps track of the number of transferred
+/// words and of the largest occupancy of the backend (see [`BackendStats`]). The
+/// bookkeeping costs only a few integer operations per word, so you can keep it enabled in
+/// production code and, e.g., log the statistics to find out how large your buffers have
+/// to be. Wrap any of the provided backends (e.g., a `Vec`, a [`Cursor`], or one of the
+/// callback adapters) or your own backend in a `CountingWords` to gather its statistics.
+///
+/// Seeking doesn't affect the statistics since a backend that is not bounded can't tell
+/// how many words it holds after seeking.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     backends::{CountingWords, ReportStats},
+///     stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode, Encode},
+///     UnwrapInfallible,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+/// let symbols = (-50..50).collect::<Vec<_>>();
+///
+/// let mut coder = DefaultAnsCoder::<CountingWords<Vec<u32>>>::default();
+/// coder.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+/// let encoder_stats = coder.backend_stats();
+/// assert_eq!(encoder_stats.num_words_read, 0);
+/// assert_eq!(encoder_stats.max_occupancy, coder.bulk().get_ref().len());
+///
+/// let decoded = coder
+///     .decode_iid_symbols(symbols.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap_infallible();
+/// assert_eq!(decoded, symbols);
+///
+/// // The decoder consumed all words that the encoder wrote.
+/// let stats = coder.bulk().stats();
+/// assert_eq!(stats.num_words_read, stats.num_words_written);
+/// assert_eq!(stats.max_occupancy, encoder_stats.max_occupancy);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CountingWords<Backend> {
+    inner: Backend,
+    occupancy: usize,
+    stats: BackendStats,
+}
+
+impl<Backend> CountingWords<Backend> {
+    /// Wraps `inner`, assuming that it initially holds no words.
+    ///
+    /// Use [`with_initial_occupancy`](Self::with_initial_occupancy) if you want to read
+    /// from a backend that already holds some data.
+    pub fn new(inner: Backend) -> Self {
+        Self::with_initial_occupancy(inner, 0)
+    }
+
+    /// Wraps `inner`, assuming that it initially holds `initial_occupancy` words.
+    ///
+    /// The `initial_occupancy` serves as the starting point for tracking the
+    /// `max_occupancy` (see [`BackendStats`]). For a bounded data source, you'll typically
+    /// set it to the number of words that are [`remaining`](BoundedReadWords::remaining).
+    pub fn with_initial_occupancy(inner: Backend, initial_occupancy: usize) -> Self {
+        Self {
+            inner,
+            occupancy: initial_occupancy,
+            stats: BackendStats {
+                max_occupancy: initial_occupancy,
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Returns a reference to the wrapped backend.
+    ///
+    /// There's deliberately no method that returns a mutable reference to the wrapped
+    /// backend since reading from or writing to it directly would bypass the statistics.
+    pub fn get_ref(&self) -> &Backend {
+        &self.inner
+    }
+
+    /// Returns the wrapped backend, discarding the statistics.
+    pub fn into_inner(self) -> Backend {
+        self.inner
+    }
+
+    /// Returns the number of words that the backend currently holds according to the
+    /// bookkeeping (see [`BackendStats::max_occupancy`]).
+    pub fn occupancy(&self) -> usize {
+        self.occupancy
+    }
+
+    /// Resets all counters and sets the `max_occupancy` to the current occupancy.
+    pub fn reset_stats(&mut self) {
+        self.stats = BackendStats {
+            max_occupancy: self.occupancy,
+            ..Default::default()
+        };
+    }
+}
+
+impl<Backend> ReportStats for CountingWords<Backend> {
+    #[inline(always)]
+    fn stats(&self) -> BackendStats {
+        self.stats
+    }
+}
+
+impl<B: ReportStats> ReportStats for Reverse<B> {
+    #[inline(always)]
+    fn stats(&self) -> BackendStats {
+        self.0.stats()
+    }
+}
+
+impl<Word, S: Semantics, Backend: ReadWords<Word, S>> ReadWords<Word, S>
+    for CountingWords<Backend>
+{
+    type ReadError = Backend::ReadError;
+
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<Word>, Self::ReadError> {
+        let word = self.inner.read()?;
+        if word.is_some() {
+            self.stats.num_words_read += 1;
+            self.occupancy = self.occupancy.saturating_sub(1);
+        }
+        Ok(word)
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.inner.maybe_exhausted()
+    }
+}
+
+/// Temporary read access doesn't count reads since it can't modify the statistics.
+impl<'a, Word, S: Semantics, Backend: AsReadWords<'a, Word, S>> AsReadWords<'a, Word, S>
+    for CountingWords<Backend>
+{
+    type AsReadWords = Backend::AsReadWords;
+
+    #[inline(always)]
+    fn as_read_words(&'a self) -> Self::AsReadWords {
+        self.inner.as_read_words()
+    }
+}
+
+impl<Word, Backend: WriteWords<Word>> WriteWords<Word> for CountingWords<Backend> {
+    type WriteError = Backend::WriteError;
+
+    #[inline(always)]
+    fn write(&mut self, word: Word) -> Result<(), Self::WriteError> {
+        self.inner.write(word)?;
+        self.stats.num_words_written += 1;
+        self.occupancy += 1;
+        self.stats.max_occupancy = core::cmp::max(self.stats.max_occupancy, self.occupancy);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.inner.maybe_full()
+    }
+}
+
+impl<Word, S: Semantics, Backend: BoundedReadWords<Word, S>> BoundedReadWords<Word, S>
+    for CountingWords<Backend>
+{
+    #[inline(always)]
+    fn remaining(&self) -> usize {
+        self.inner.remaining()
+    }
+
+    #[inline(always)]
+    fn is_exhausted(&self) -> bool {
+        self.inner.is_exhausted()
+    }
+}
+
+impl<Word, Backend: BoundedWriteWords<Word>> BoundedWriteWords<Word> for CountingWords<Backend> {
+    #[inline(always)]
+    fn space_left(&self) -> usize {
+        self.inner.space_left()
+    }
+
+    #[inline(always)]
+    fn is_full(&self) -> bool {
+        self.inner.is_full()
+    }
+}
+
+impl<Backend: PosSeek> PosSeek for CountingWords<Backend> {
+    type Position = Backend::Position;
+}
+
+impl<Backend: Pos> Pos for CountingWords<Backend> {
+    #[inline(always)]
+    fn pos(&self) -> Backend::Position {
+        self.inner.pos()
+    }
+}
+
+impl<Backend: Seek> Seek for CountingWords<Backend> {
+    #[inline(always)]
+    fn seek(&mut self, pos: Backend::Position) -> Result<(), ()> {
+        self.inner.seek(pos)
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode};
+    use super::*;
+    use crate::stream::{
+        model::DefaultLeakyQuantizer,
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+        Decode, Encode,
+    };
     use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
     use probability::distribution::Gaussian;
     use std::{
@@ -1842,7 +2554,7 @@ mod tests {
             });
 
             let mut encoder = DefaultAnsCoder::new();
-            encoder.encode_iid_symbols_reverse(symbols, &model).unwrap();
+            encoder.encode_iid_symbols_reverse(symbols, model).unwrap();
             let compressed = encoder.into_compressed().unwrap();
 
             let mut file = BufWriter::new(File::create("backend_stack_example.tmp").unwrap());
@@ -1889,4 +2601,286 @@ mod tests {
         encode_to_file(1000);
         decode_from_file_on_the_fly(1000);
     }
+
+    #[test]
+    fn stack_byte_cursor() {
+        let words = [0x0102u16, 0xa0b0, 0xffff, 0x0000, 0x1234];
+        let mut bytes = Vec::new();
+        for word in &words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+
+        assert!(StackByteCursor::<u16>::from_vec(bytes[..3].to_vec()).is_err());
+        assert!(StackByteCursor::<u32>::from_vec(bytes.clone()).is_err());
+        let mut cursor = std::io::Cursor::new(bytes.clone());
+        cursor.set_position(3);
+        assert!(StackByteCursor::<u16>::new(cursor.clone()).is_err());
+        cursor.set_position(12);
+        assert!(StackByteCursor::<u16>::new(cursor.clone()).is_err());
+
+        // Starting in the middle ignores all bytes after the position.
+        cursor.set_position(6);
+        let mut backend = StackByteCursor::<u16>::new(cursor).unwrap();
+        assert_eq!(backend.remaining(), 3);
+        assert_eq!(backend.read(), Ok(Some(0xffff)));
+        assert_eq!(backend.pos(), 2);
+        let cursor = backend.into_inner();
+        assert_eq!(cursor.position(), 4);
+        assert_eq!(cursor.get_ref(), &bytes);
+
+        let mut backend = StackByteCursor::<u16>::from_vec(bytes.clone()).unwrap();
+        let mut expected = Cursor::new_at_write_end(&words[..]);
+        for _ in 0..words.len() {
+            assert_eq!(
+                ReadWords::<u16, Stack>::read(&mut backend),
+                ReadWords::<u16, Stack>::read(&mut expected)
+            );
+        }
+        assert!(backend.is_exhausted());
+        assert_eq!(backend.read(), Ok(None));
+
+        assert!(backend.seek(6).is_err());
+        backend.seek(2).unwrap();
+        assert_eq!(backend.read(), Ok(Some(0xa0b0)));
+
+        let mut backend = StackByteCursor::<u8>::from_vec(bytes.clone()).unwrap();
+        assert_eq!(backend.read(), Ok(Some(0x12)));
+        assert_eq!(backend.read(), Ok(Some(0x34)));
+    }
+
+    #[test]
+    fn shared_buffers() {
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        let model = quantizer.quantize(Gaussian::new(0.0, 100.0));
+        let symbols = (0..100).map(|i| (i * 41) % 512 - 256).collect::<Vec<i32>>();
+
+        let mut encoder = DefaultAnsCoder::new();
+        encoder
+            .encode_iid_symbols_reverse(&symbols, &model)
+            .unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+
+        // Clones of a decoder over an `Arc<[u32]>` share the buffer but decode independently.
+        let shared: Arc<[u32]> = compressed.clone().into();
+        let mut decoder =
+            DefaultAnsCoder::from_compressed(Cursor::new_at_write_end(Arc::clone(&shared)))
+                .unwrap();
+        let mut decoder_clone = decoder.clone();
+        for &symbol in &symbols {
+            assert_eq!(decoder.decode_symbol(model).unwrap(), symbol);
+        }
+        assert!(decoder.is_empty());
+        assert_eq!(decoder_clone.decode_symbol(model).unwrap(), symbols[0]);
+        assert_eq!(Arc::strong_count(&shared), 3);
+
+        let mut bytes = Vec::new();
+        for word in &compressed {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        let shared_bytes: Arc<[u8]> = bytes.clone().into();
+        let backend = StackByteCursor::<u32, _>::from_buf(Arc::clone(&shared_bytes)).unwrap();
+        let mut decoder = DefaultAnsCoder::from_compressed(backend).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+
+        #[cfg(feature = "bytes")]
+        {
+            let backend = StackByteCursor::<u32, _>::from_buf(bytes::Bytes::from(bytes)).unwrap();
+            let mut decoder = DefaultAnsCoder::from_compressed(backend).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+
+            // Coders with `u8` words decode with `Queue` semantics directly from `Bytes`.
+            use crate::stream::{
+                model::LeakyQuantizer,
+                queue::{ByteRangeDecoder, ByteRangeEncoder},
+            };
+            let quantizer = LeakyQuantizer::<f64, i32, u8, 8>::new(-20..=20);
+            let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+            let symbols = symbols
+                .iter()
+                .map(|&symbol| symbol % 21)
+                .collect::<Vec<_>>();
+
+            let mut encoder = ByteRangeEncoder::new();
+            encoder.encode_iid_symbols(&symbols, model).unwrap();
+            let shared = bytes::Bytes::from(encoder.into_compressed().unwrap());
+            let mut decoder =
+                ByteRangeDecoder::<Cursor<u8, bytes::Bytes>>::from_compressed(shared).unwrap();
+            let mut decoder_clone = decoder.clone();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert_eq!(decoder_clone.decode_symbol(model).unwrap(), symbols[0]);
+        }
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let shared: Rc<[u32]> = encoder.into_compressed().unwrap().into();
+        let mut decoder = DefaultRangeDecoder::from_compressed(Rc::clone(&shared)).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+    }
+
+    #[test]
+    fn indexed_callbacks() {
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        let model = quantizer.quantize(Gaussian::new(0.0, 100.0));
+        let symbols = (0..100).map(|i| (i * 37) % 512 - 256).collect::<Vec<i32>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let expected = encoder.into_compressed().unwrap();
+
+        let mut written = Vec::new();
+        let backend = InfallibleCallbackWriteWords::new_indexed(5, |index, word| {
+            written.push((index, word));
+        });
+        let mut encoder = DefaultRangeEncoder::with_backend(backend);
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let callback = encoder.into_compressed().unwrap().into_inner();
+        assert_eq!(callback.next_index(), 5 + expected.len());
+        assert_eq!(
+            written,
+            expected
+                .iter()
+                .enumerate()
+                .map(|(i, &word)| (5 + i, word))
+                .collect::<Vec<_>>()
+        );
+
+        // A failing callback doesn't advance the index.
+        let mut backend = FallibleCallbackWriteWords::new_indexed(0, |index, _word: u32| {
+            if index == 2 {
+                Err(index)
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(backend.write(10), Ok(()));
+        assert_eq!(backend.write(11), Ok(()));
+        assert_eq!(backend.write(12), Err(2));
+        assert_eq!(backend.write(13), Err(2));
+        assert_eq!(backend.into_inner().next_index(), 2);
+    }
+
+    #[test]
+    fn tee() {
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        let model = quantizer.quantize(Gaussian::new(0.0, 100.0));
+        let symbols = (0..100).map(|i| (i * 41) % 512 - 256).collect::<Vec<i32>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let expected_pos = encoder.pos();
+        let expected = encoder.into_compressed().unwrap();
+
+        let mut observed = Vec::new();
+        let backend = TeeWriteWords(
+            Vec::new(),
+            InfallibleCallbackWriteWords::new(|word| observed.push(word)),
+        );
+        let mut encoder = DefaultRangeEncoder::with_backend(backend);
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        assert_eq!(encoder.pos(), expected_pos);
+        let TeeWriteWords(compressed, _) = encoder.into_compressed().unwrap();
+        assert_eq!(compressed, expected);
+        assert_eq!(observed, expected);
+
+        // Errors report which sink failed, and a failing first sink shields the second one.
+        let mut backend = TeeWriteWords(
+            Cursor::new_at_write_beginning([0u32; 2]),
+            Cursor::new_at_write_beginning([0u32; 3]),
+        );
+        assert_eq!(backend.space_left(), 2);
+        assert_eq!(backend.write(1), Ok(()));
+        assert_eq!(backend.write(2), Ok(()));
+        assert!(backend.is_full());
+        assert_eq!(
+            backend.write(3),
+            Err(TeeWriteError::First(BoundedWriteError::OutOfSpace))
+        );
+        assert_eq!(backend.1.pos(), 2);
+
+        let mut backend = TeeWriteWords(Vec::new(), Cursor::new_at_write_beginning([0u32; 1]));
+        assert_eq!(backend.write(1), Ok(()));
+        assert_eq!(
+            backend.write(2),
+            Err(TeeWriteError::Second(BoundedWriteError::OutOfSpace))
+        );
+        assert_eq!(backend.0, [1, 2]);
+    }
+
+    #[test]
+    fn counting() {
+        let quantizer = DefaultLeakyQuantizer::new(-256..=255);
+        let model = quantizer.quantize(Gaussian::new(0.0, 100.0));
+        let symbols = (0..100).map(|i| (i * 43) % 512 - 256).collect::<Vec<i32>>();
+
+        // Queue semantics: the encoder writes into a callback, the decoder reads a `Cursor`.
+        let mut compressed = Vec::new();
+        let backend = CountingWords::new(InfallibleCallbackWriteWords::new(|word| {
+            compressed.push(word)
+        }));
+        let mut encoder = DefaultRangeEncoder::with_backend(backend);
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let stats = encoder.into_compressed().unwrap().stats();
+        assert_eq!(stats.num_words_written, compressed.len() as u64);
+        assert_eq!(stats.num_words_read, 0);
+        assert_eq!(stats.max_occupancy, compressed.len());
+
+        let len = compressed.len();
+        let backend =
+            CountingWords::with_initial_occupancy(Cursor::new_at_write_beginning(compressed), len);
+        let mut decoder = DefaultRangeDecoder::with_backend(backend).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        let stats = decoder.backend_stats();
+        assert_eq!(stats.num_words_read, len as u64);
+        assert_eq!(stats.num_words_written, 0);
+        assert_eq!(stats.max_occupancy, len);
+
+        // Stack semantics: the occupancy goes up and down with interleaved encoding and
+        // decoding.
+        let mut coder = DefaultAnsCoder::<CountingWords<Vec<u32>>>::default();
+        coder.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let high_water_mark = coder.bulk().occupancy();
+        assert_eq!(high_water_mark, coder.bulk().get_ref().len());
+        for &symbol in &symbols[..50] {
+            assert_eq!(coder.decode_symbol(&model).unwrap(), symbol);
+        }
+        assert!(coder.bulk().occupancy() < high_water_mark);
+        coder.encode_symbol(0, model).unwrap();
+        let stats = coder.backend_stats();
+        assert_eq!(stats.max_occupancy, high_water_mark);
+        assert_eq!(
+            stats.num_words_written - stats.num_words_read,
+            coder.bulk().get_ref().len() as u64
+        );
+
+        // `extend_from_iter` and resetting the statistics.
+        let mut backend = CountingWords::new(Cursor::new_at_write_beginning([0u32; 4]));
+        backend.extend_from_iter([1, 2, 3].iter().cloned()).unwrap();
+        assert_eq!(backend.stats().num_words_written, 3);
+        assert_eq!(backend.space_left(), 1);
+        backend.reset_stats();
+        assert_eq!(backend.stats().num_words_written, 0);
+        assert_eq!(backend.stats().max_occupancy, 3);
+        assert!(backend.extend_from_iter([4, 5].iter().cloned()).is_err());
+        assert_eq!(backend.stats().num_words_written, 1);
+    }
 }
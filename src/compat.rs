@@ -0,0 +1,63 @@
+//! Deprecated aliases for the pre-0.2 module layout
+//!
+//! Before version 0.2, `constriction` exposed its entropy models in a top-level module
+//! `distributions` and its ANS coder in a top-level module `stack`. These modules have
+//! since moved to [`stream::model`] and [`stream::stack`], respectively, and some of their
+//! types were renamed. The modules in here (which are also re-exported at the crate root
+//! under their old paths) map the old names to the new API so that downstream code can be
+//! migrated one module at a time. Every use of an old path triggers a deprecation warning
+//! that points to its replacement.
+//!
+//! Note that these are only *name* aliases: the old `DiscreteDistribution` trait, for
+//! example, has been split up into [`EntropyModel`], [`EncoderModel`], and
+//! [`DecoderModel`], and code that implements it has to be adjusted accordingly.
+//!
+//! [`stream::model`]: crate::stream::model
+//! [`stream::stack`]: crate::stream::stack
+//! [`EntropyModel`]: crate::stream::model::EntropyModel
+//! [`EncoderModel`]: crate::stream::model::EncoderModel
+//! [`DecoderModel`]: crate::stream::model::DecoderModel
+
+/// Deprecated alias for [`stream::model`](crate::stream::model).
+#[deprecated(
+    since = "0.2.0",
+    note = "Use `constriction::stream::model` instead (`Categorical` is now \
+            `ContiguousCategoricalEntropyModel`, and `DiscreteDistribution` is now \
+            `EntropyModel` together with `EncoderModel` and `DecoderModel`)."
+)]
+pub mod distributions {
+    use alloc::vec::Vec;
+
+    pub use crate::stream::model::{
+        DecoderModel, EncoderModel, EntropyModel, EntropyModel as DiscreteDistribution,
+        LeakilyQuantizedDistribution, LeakyQuantizer,
+    };
+
+    #[cfg(feature = "probability")]
+    pub use crate::stream::model::{Distribution, Inverse};
+
+    /// Deprecated name of a
+    /// [`ContiguousCategoricalEntropyModel`](crate::stream::model::ContiguousCategoricalEntropyModel)
+    /// that owns its table.
+    pub type Categorical<Probability, const PRECISION: usize> =
+        crate::stream::model::ContiguousCategoricalEntropyModel<
+            Probability,
+            Vec<Probability>,
+            PRECISION,
+        >;
+}
+
+/// Deprecated alias for [`stream::stack`](crate::stream::stack).
+#[deprecated(
+    since = "0.2.0",
+    note = "Use `constriction::stream::stack` instead (`Stack` is now `AnsCoder`)."
+)]
+pub mod stack {
+    use alloc::vec::Vec;
+
+    pub use crate::stream::stack::{AnsCoder, DefaultAnsCoder, SmallAnsCoder};
+
+    /// Deprecated name of an [`AnsCoder`](crate::stream::stack::AnsCoder).
+    pub type Stack<Word, State, Backend = Vec<Word>> =
+        crate::stream::stack::AnsCoder<Word, State, Backend>;
+}
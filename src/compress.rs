@@ -0,0 +1,620 @@
+//! A common interface for complete compression strategies
+//!
+//! The [`stream`] and [`symbol`] modules provide building blocks: entropy coders, entropy
+//! models, and codebooks, which you combine and configure yourself. This module provides a
+//! higher level interface on top of them. A *compression strategy* bundles a coder with a
+//! model or codebook and turns a message (a sequence of symbols) into a `Vec<u8>` and back.
+//! This makes it easy to benchmark different strategies against each other, or to make the
+//! strategy configurable in an application.
+//!
+//! There are two pairs of traits:
+//!
+//! - [`Compressor`] and [`Decompressor`] are the generic interface. They accept messages as
+//!   any iterator over (borrowed) symbols and report errors with a strategy specific error
+//!   type.
+//! - [`DynCompressor`] and [`DynDecompressor`] are object safe versions of these traits, so
+//!   that you can hold strategies of different types in a `Box<dyn DynCompressor<Symbol>>`
+//!   (e.g., in a plugin system). They are implemented automatically for all types that
+//!   implement the generic traits, and they box up errors. These traits are only available
+//!   with the `std` feature (which is enabled by default).
+//!
+//! This module provides the following strategies:
+//!
+//! - [`RangeCoding`]: a [`DefaultRangeEncoder`] with an entropy model from
+//!   [`stream::model`]. The compressed format is the one of [`stream::easy`].
+//! - [`AnsCoding`]: a [`DefaultAnsCoder`] with an entropy model from [`stream::model`].
+//! - [`PrefixCoding`]: a prefix code (such as a Huffman code) with a codebook from
+//!   [`symbol`].
+//!
+//! All strategies serialize the compressed data as a sequence of `u32` words in
+//! little-endian byte order. None of them stores the length of the message, so you have to
+//! provide it when decompressing.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::{
+//!     compress::{AnsCoding, DynCompressor, DynDecompressor, PrefixCoding, RangeCoding},
+//!     stream::model::DefaultContiguousCategoricalEntropyModel,
+//!     symbol::huffman::{DecoderHuffmanTree, EncoderHuffmanTree},
+//! };
+//!
+//! let probabilities = [0.05, 0.1, 0.6, 0.2, 0.05];
+//! let message = [2, 3, 2, 1, 2, 2, 0, 2, 3, 4, 2, 3, 2, 2, 1, 2];
+//!
+//! let model =
+//!     DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&probabilities)
+//!         .unwrap();
+//! let encoder_tree = EncoderHuffmanTree::from_float_probabilities::<f64, _>(&probabilities)
+//!     .unwrap();
+//! let decoder_tree = DecoderHuffmanTree::from_float_probabilities::<f64, _>(&probabilities)
+//!     .unwrap();
+//!
+//! // Strategies of different types behind a common interface.
+//! type Strategy = (&'static str, Box<dyn DynCompressor<usize>>, Box<dyn DynDecompressor<usize>>);
+//! let strategies: Vec<Strategy> = vec![
+//!     (
+//!         "range coding",
+//!         Box::new(RangeCoding::new(model.clone())),
+//!         Box::new(RangeCoding::new(model.clone())),
+//!     ),
+//!     ("ANS", Box::new(AnsCoding::new(model.clone())), Box::new(AnsCoding::new(model))),
+//!     (
+//!         "Huffman coding",
+//!         Box::new(PrefixCoding::new(encoder_tree)),
+//!         Box::new(PrefixCoding::new(decoder_tree)),
+//!     ),
+//! ];
+//!
+//! for (name, compressor, decompressor) in &strategies {
+//!     let compressed = compressor.compress_dyn(&message).unwrap();
+//!     let estimate = compressor.estimate_num_bits_dyn(&message).unwrap();
+//!     println!("{}: {} bytes (estimated {:.1} bits)", name, compressed.len(), estimate);
+//!
+//!     let decompressed = decompressor.decompress_dyn(&compressed, message.len()).unwrap();
+//!     assert_eq!(decompressed, message);
+//! }
+//! ```
+//!
+//! [`stream`]: crate::stream
+//! [`symbol`]: crate::symbol
+//! [`stream::model`]: crate::stream::model
+//! [`stream::easy`]: crate::stream::easy
+//! [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+//! [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, convert::Infallible};
+
+#[cfg(feature = "std")]
+use alloc::boxed::Box;
+
+use num::cast::AsPrimitive;
+
+pub use crate::stream::easy::DecodingError;
+
+use crate::{
+    backends::Cursor,
+    stream::{
+        easy,
+        model::{DecoderModel, EncoderModel, EntropyModel},
+        stack::DefaultAnsCoder,
+        Decode,
+    },
+    symbol::{
+        DecoderCodebook, DefaultQueueDecoder, DefaultQueueEncoder, EncoderCodebook, ReadBitStream,
+        WriteBitStream,
+    },
+    CoderError, DefaultEncoderFrontendError, UnwrapInfallible,
+};
+
+// TRAITS =====================================================================
+
+/// A compression strategy that turns a message into bytes.
+///
+/// See [module level documentation](self) for an overview and an example.
+pub trait Compressor<Symbol> {
+    /// The error type returned when a message cannot be compressed.
+    type Error;
+
+    /// Compresses `message` into a sequence of bytes.
+    fn compress<S: Borrow<Symbol>>(
+        &self,
+        message: impl IntoIterator<Item = S>,
+    ) -> Result<Vec<u8>, Self::Error>;
+
+    /// Estimates the number of bits that [`compress`](Self::compress) would use for
+    /// `message`.
+    ///
+    /// The estimate is meant for comparing strategies without compressing any data. It
+    /// does not necessarily include constant overhead (such as rounding up to whole
+    /// words), so it can be slightly lower than `8 * self.compress(message)?.len()`. The
+    /// default implementation actually compresses `message` and returns the exact size.
+    fn estimate_num_bits<S: Borrow<Symbol>>(
+        &self,
+        message: impl IntoIterator<Item = S>,
+    ) -> Result<f64, Self::Error> {
+        Ok((8 * self.compress(message)?.len()) as f64)
+    }
+}
+
+/// A compression strategy that turns bytes back into a message.
+///
+/// See [module level documentation](self) for an overview and an example.
+pub trait Decompressor<Symbol> {
+    /// The error type returned when `bytes` cannot be decompressed.
+    type Error;
+
+    /// Decompresses `amt` symbols from `bytes` that were generated by the corresponding
+    /// [`Compressor`].
+    ///
+    /// Since none of the provided strategies stores the length of the message, decoding
+    /// more symbols than were encoded does not necessarily fail but may return arbitrary
+    /// symbols.
+    fn decompress(&self, bytes: &[u8], amt: usize) -> Result<Vec<Symbol>, Self::Error>;
+}
+
+/// Object safe version of [`Compressor`].
+///
+/// This trait is implemented automatically for all types that implement [`Compressor`]
+/// with an error type that implements [`std::error::Error`].
+#[cfg(feature = "std")]
+pub trait DynCompressor<Symbol> {
+    /// See [`Compressor::compress`].
+    fn compress_dyn(&self, message: &[Symbol]) -> Result<Vec<u8>, DynError>;
+
+    /// See [`Compressor::estimate_num_bits`].
+    fn estimate_num_bits_dyn(&self, message: &[Symbol]) -> Result<f64, DynError>;
+}
+
+/// Object safe version of [`Decompressor`].
+///
+/// This trait is implemented automatically for all types that implement [`Decompressor`]
+/// with an error type that implements [`std::error::Error`].
+#[cfg(feature = "std")]
+pub trait DynDecompressor<Symbol> {
+    /// See [`Decompressor::decompress`].
+    fn decompress_dyn(&self, bytes: &[u8], amt: usize) -> Result<Vec<Symbol>, DynError>;
+}
+
+/// Error type of [`DynCompressor`] and [`DynDecompressor`].
+#[cfg(feature = "std")]
+pub type DynError = Box<dyn std::error::Error + Send + Sync>;
+
+#[cfg(feature = "std")]
+impl<Symbol, C> DynCompressor<Symbol> for C
+where
+    C: Compressor<Symbol>,
+    C::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn compress_dyn(&self, message: &[Symbol]) -> Result<Vec<u8>, DynError> {
+        Ok(self.compress(message)?)
+    }
+
+    fn estimate_num_bits_dyn(&self, message: &[Symbol]) -> Result<f64, DynError> {
+        Ok(self.estimate_num_bits(message)?)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Symbol, D> DynDecompressor<Symbol> for D
+where
+    D: Decompressor<Symbol>,
+    D::Error: std::error::Error + Send + Sync + 'static,
+{
+    fn decompress_dyn(&self, bytes: &[u8], amt: usize) -> Result<Vec<Symbol>, DynError> {
+        Ok(self.decompress(bytes, amt)?)
+    }
+}
+
+// STRATEGIES =================================================================
+
+/// Range coding with the same entropy model for each symbol.
+///
+/// Encodes with a [`DefaultRangeEncoder`] and produces the same bytes as
+/// [`stream::easy::encode_to_bytes`]. The entropy model `M` has to use 32-bit (or smaller)
+/// probabilities, as do all models in [`stream::model`] with the "Default" or "Small"
+/// preset. `decompress` returns an error if `bytes` aren't a whole number of words. See
+/// [module level documentation](self) for an example.
+///
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`stream::easy::encode_to_bytes`]: crate::stream::easy::encode_to_bytes
+/// [`stream::model`]: crate::stream::model
+#[derive(Debug, Clone)]
+pub struct RangeCoding<M, const PRECISION: usize> {
+    model: M,
+}
+
+/// ANS coding (i.e., stack based entropy coding) with the same entropy model for each
+/// symbol.
+///
+/// Encodes with a [`DefaultAnsCoder`], encoding the symbols in reverse order so that they
+/// decompress in their original order. Since an ANS coder is a stack, `compress` has to
+/// collect the message into a temporary buffer first. The entropy model `M` has to use
+/// 32-bit (or smaller) probabilities. `decompress` returns an error if `bytes` aren't a
+/// whole number of words or if they end in a zero word (which `compress` never
+/// produces). See [module level documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct AnsCoding<M, const PRECISION: usize> {
+    model: M,
+}
+
+/// Prefix coding (e.g., Huffman coding) with the same codebook for each symbol.
+///
+/// Use an encoder codebook (such as an [`EncoderHuffmanTree`]) for compression, and a
+/// decoder codebook (such as a [`DecoderHuffmanTree`]) for decompression. The codewords
+/// are packed into `u32` words with a [`DefaultQueueEncoder`], and the last word is padded
+/// with zero bits. See [module level documentation](self) for an example.
+///
+/// [`EncoderHuffmanTree`]: crate::symbol::huffman::EncoderHuffmanTree
+/// [`DecoderHuffmanTree`]: crate::symbol::huffman::DecoderHuffmanTree
+#[derive(Debug, Clone)]
+pub struct PrefixCoding<C> {
+    codebook: C,
+}
+
+impl<M: EntropyModel<PRECISION>, const PRECISION: usize> RangeCoding<M, PRECISION> {
+    /// Creates a strategy that encodes and decodes each symbol with `model`.
+    pub fn new(model: M) -> Self {
+        Self { model }
+    }
+
+    /// Returns a reference to the entropy model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Consumes the strategy and returns the entropy model.
+    pub fn into_model(self) -> M {
+        self.model
+    }
+}
+
+impl<M: EntropyModel<PRECISION>, const PRECISION: usize> AnsCoding<M, PRECISION> {
+    /// Creates a strategy that encodes and decodes each symbol with `model`.
+    pub fn new(model: M) -> Self {
+        Self { model }
+    }
+
+    /// Returns a reference to the entropy model.
+    pub fn model(&self) -> &M {
+        &self.model
+    }
+
+    /// Consumes the strategy and returns the entropy model.
+    pub fn into_model(self) -> M {
+        self.model
+    }
+}
+
+impl<C> PrefixCoding<C> {
+    /// Creates a strategy that encodes or decodes each symbol with `codebook`.
+    pub fn new(codebook: C) -> Self {
+        Self { codebook }
+    }
+
+    /// Returns a reference to the codebook.
+    pub fn codebook(&self) -> &C {
+        &self.codebook
+    }
+
+    /// Consumes the strategy and returns the codebook.
+    pub fn into_codebook(self) -> C {
+        self.codebook
+    }
+}
+
+impl<M, const PRECISION: usize> Compressor<M::Symbol> for RangeCoding<M, PRECISION>
+where
+    M: EncoderModel<PRECISION>,
+    M::Probability: Into<u32> + Into<f64>,
+    u32: AsPrimitive<M::Probability>,
+{
+    type Error = DefaultEncoderFrontendError;
+
+    fn compress<S: Borrow<M::Symbol>>(
+        &self,
+        message: impl IntoIterator<Item = S>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        easy::encode_to_bytes(message, &self.model)
+    }
+
+    /// Returns the information content of `message` under the entropy model.
+    ///
+    /// The actual compressed size is at most a few words larger since a range coder has to
+    /// be sealed, and the output is rounded up to whole words.
+    fn estimate_num_bits<S: Borrow<M::Symbol>>(
+        &self,
+        message: impl IntoIterator<Item = S>,
+    ) -> Result<f64, Self::Error> {
+        information_content(&self.model, message)
+    }
+}
+
+impl<M, const PRECISION: usize> Decompressor<M::Symbol> for RangeCoding<M, PRECISION>
+where
+    M: DecoderModel<PRECISION>,
+    M::Probability: Into<u32>,
+    u32: AsPrimitive<M::Probability>,
+{
+    type Error = DecodingError;
+
+    fn decompress(&self, bytes: &[u8], amt: usize) -> Result<Vec<M::Symbol>, Self::Error> {
+        easy::decode_from_bytes(bytes, &self.model, amt)
+    }
+}
+
+impl<M, const PRECISION: usize> Compressor<M::Symbol> for AnsCoding<M, PRECISION>
+where
+    M: EncoderModel<PRECISION>,
+    M::Probability: Into<u32> + Into<f64>,
+    u32: AsPrimitive<M::Probability>,
+{
+    type Error = DefaultEncoderFrontendError;
+
+    fn compress<S: Borrow<M::Symbol>>(
+        &self,
+        message: impl IntoIterator<Item = S>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        // An ANS coder is a stack, so we have to encode in reverse order. We can't just
+        // reverse the iterator since `message` isn't necessarily double-ended.
+        let message = message.into_iter().collect::<Vec<_>>();
+        let mut coder = DefaultAnsCoder::new();
+        coder
+            .encode_iid_symbols_reverse(message, &self.model)
+            .map_err(CoderError::into_frontend_error)?;
+
+        Ok(words_to_le_bytes(
+            &coder.into_compressed().unwrap_infallible(),
+        ))
+    }
+
+    /// Returns the information content of `message` under the entropy model.
+    ///
+    /// The actual compressed size is at most a few words larger due to the initial state
+    /// of the ANS coder and rounding up to whole words.
+    fn estimate_num_bits<S: Borrow<M::Symbol>>(
+        &self,
+        message: impl IntoIterator<Item = S>,
+    ) -> Result<f64, Self::Error> {
+        information_content(&self.model, message)
+    }
+}
+
+impl<M, const PRECISION: usize> Decompressor<M::Symbol> for AnsCoding<M, PRECISION>
+where
+    M: DecoderModel<PRECISION>,
+    M::Probability: Into<u32>,
+    u32: AsPrimitive<M::Probability>,
+{
+    type Error = DecodingError;
+
+    fn decompress(&self, bytes: &[u8], amt: usize) -> Result<Vec<M::Symbol>, Self::Error> {
+        let compressed = le_bytes_to_words(bytes)?;
+        // `from_compressed` fails if the last word is zero, which `compress` never returns.
+        let mut coder =
+            DefaultAnsCoder::from_compressed(compressed).map_err(|_| DecodingError::InvalidData)?;
+        Ok(coder
+            .decode_iid_symbols(amt, &self.model)
+            .map(UnwrapInfallible::unwrap_infallible)
+            .collect())
+    }
+}
+
+impl<C: EncoderCodebook> Compressor<C::Symbol> for PrefixCoding<C> {
+    type Error = DefaultEncoderFrontendError;
+
+    fn compress<S: Borrow<C::Symbol>>(
+        &self,
+        message: impl IntoIterator<Item = S>,
+    ) -> Result<Vec<u8>, Self::Error> {
+        let mut encoder = DefaultQueueEncoder::new();
+        encoder
+            .encode_iid_symbols(message, &self.codebook)
+            .map_err(CoderError::into_frontend_error)?;
+
+        Ok(words_to_le_bytes(
+            &encoder.into_compressed().unwrap_infallible(),
+        ))
+    }
+
+    /// Returns the total length of all codewords, i.e., the exact size of the compressed
+    /// data before it gets padded to whole words.
+    fn estimate_num_bits<S: Borrow<C::Symbol>>(
+        &self,
+        message: impl IntoIterator<Item = S>,
+    ) -> Result<f64, Self::Error> {
+        let mut num_bits = 0usize;
+        for symbol in message {
+            self.codebook
+                .encode_symbol_prefix(symbol, |_| {
+                    num_bits += 1;
+                    Ok::<(), Infallible>(())
+                })
+                .map_err(CoderError::into_frontend_error)?;
+        }
+
+        Ok(num_bits as f64)
+    }
+}
+
+impl<C: DecoderCodebook> Decompressor<C::Symbol> for PrefixCoding<C> {
+    type Error = DecodingError;
+
+    fn decompress(&self, bytes: &[u8], amt: usize) -> Result<Vec<C::Symbol>, Self::Error> {
+        let compressed = le_bytes_to_words(bytes)?;
+        let mut decoder =
+            DefaultQueueDecoder::from_compressed(Cursor::new_at_write_beginning(compressed));
+        decoder
+            .decode_iid_symbols(amt, &self.codebook)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| DecodingError::InvalidData)
+    }
+}
+
+// HELPERS ====================================================================
+
+fn information_content<M, S, const PRECISION: usize>(
+    model: &M,
+    message: impl IntoIterator<Item = S>,
+) -> Result<f64, DefaultEncoderFrontendError>
+where
+    M: EncoderModel<PRECISION>,
+    M::Probability: Into<f64>,
+    S: Borrow<M::Symbol>,
+{
+    let mut num_bits = 0.0;
+    for symbol in message {
+        let bit_cost = model.bit_cost::<f64>(symbol);
+        if bit_cost == f64::INFINITY {
+            return Err(DefaultEncoderFrontendError::ImpossibleSymbol);
+        }
+        num_bits += bit_cost;
+    }
+
+    Ok(num_bits)
+}
+
+fn words_to_le_bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_le_bytes()).collect()
+}
+
+fn le_bytes_to_words(bytes: &[u8]) -> Result<Vec<u32>, DecodingError> {
+    if bytes.len() % 4 != 0 {
+        return Err(DecodingError::InvalidLength);
+    }
+    Ok(bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stream::model::{DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer},
+        symbol::huffman::{DecoderHuffmanTree, EncoderHuffmanTree},
+    };
+    use alloc::vec;
+
+    use probability::distribution::Gaussian;
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    fn check_strategy<C, D>(compressor: &C, decompressor: &D, message: &[usize]) -> usize
+    where
+        C: Compressor<usize>,
+        C::Error: core::fmt::Debug,
+        D: Decompressor<usize>,
+        D::Error: core::fmt::Debug,
+    {
+        let compressed = compressor.compress(message).unwrap();
+        let estimate = compressor.estimate_num_bits(message).unwrap();
+        assert!(estimate <= (8 * compressed.len()) as f64);
+        assert!(estimate + 96.0 >= (8 * compressed.len()) as f64);
+
+        let decompressed = decompressor.decompress(&compressed, message.len()).unwrap();
+        assert_eq!(decompressed, message);
+        compressed.len()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn all_strategies() {
+        let probabilities = [0.85, 0.06, 0.04, 0.03, 0.02];
+        let mut rng = Xoshiro256StarStar::seed_from_u64(740);
+        let message = (0..1000)
+            .map(|_| {
+                let x = rng.next_u32() as f64 / u32::MAX as f64;
+                let mut accum = 0.0;
+                probabilities
+                    .iter()
+                    .position(|&p| {
+                        accum += p;
+                        accum > x
+                    })
+                    .unwrap_or(probabilities.len() - 1)
+            })
+            .collect::<Vec<_>>();
+
+        let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+            &probabilities,
+        )
+        .unwrap();
+        let range_coding = RangeCoding::new(model.clone());
+        let range_len = check_strategy(&range_coding, &range_coding, &message);
+        let ans_coding = AnsCoding::new(model.clone());
+        let ans_len = check_strategy(&ans_coding, &ans_coding, &message);
+
+        let encoder_tree =
+            EncoderHuffmanTree::from_float_probabilities::<f64, _>(&probabilities).unwrap();
+        let decoder_tree =
+            DecoderHuffmanTree::from_float_probabilities::<f64, _>(&probabilities).unwrap();
+        let huffman = PrefixCoding::new(encoder_tree);
+        let huffman_len = check_strategy(&huffman, &PrefixCoding::new(decoder_tree), &message);
+
+        // The estimate for prefix codes is exact up to padding to a whole `u32` word.
+        let huffman_bits = huffman.estimate_num_bits(&message).unwrap() as usize;
+        assert_eq!(huffman_len, 4 * huffman_bits.div_ceil(32));
+
+        // Stream codes beat Huffman coding since one symbol has probability above 1/2.
+        assert!(range_len < huffman_len);
+        assert!(ans_len < huffman_len);
+
+        // `RangeCoding` uses the same format as `stream::easy`.
+        assert_eq!(
+            range_coding.compress(&message).unwrap(),
+            easy::encode_to_bytes(&message, &model).unwrap()
+        );
+    }
+
+    #[test]
+    fn dyn_strategies() {
+        let quantizer = DefaultLeakyQuantizer::new(-50..=50);
+        let model = quantizer.quantize(Gaussian::new(1.5, 8.0));
+        let message = [3, -7, 0, 12, -1, 5, 49, -50, 2];
+
+        type Strategy = (Box<dyn DynCompressor<i32>>, Box<dyn DynDecompressor<i32>>);
+        let strategies: Vec<Strategy> = vec![
+            (
+                Box::new(RangeCoding::new(model)),
+                Box::new(RangeCoding::new(model)),
+            ),
+            (
+                Box::new(AnsCoding::new(model)),
+                Box::new(AnsCoding::new(model)),
+            ),
+        ];
+        for (compressor, decompressor) in &strategies {
+            let compressed = compressor.compress_dyn(&message).unwrap();
+            let decompressed = decompressor
+                .decompress_dyn(&compressed, message.len())
+                .unwrap();
+            assert_eq!(decompressed, message);
+
+            let err = decompressor
+                .decompress_dyn(&compressed[1..], message.len())
+                .unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<DecodingError>(),
+                Some(&DecodingError::InvalidLength)
+            );
+        }
+
+        let narrow_model =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+                0.5, 0.5,
+            ])
+            .unwrap();
+        let compressor: Box<dyn DynCompressor<usize>> = Box::new(AnsCoding::new(narrow_model));
+        let err = compressor.estimate_num_bits_dyn(&[0, 1, 2]).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<DefaultEncoderFrontendError>(),
+            Some(&DefaultEncoderFrontendError::ImpossibleSymbol)
+        );
+        assert!(compressor.compress_dyn(&[0, 1, 2]).is_err());
+    }
+}
@@ -43,6 +43,9 @@
 //!   regime of low entropy per symbol, which is common in machine-learning based
 //!   compression methods.
 //!
+//! For integer time series (e.g., sensor data), the module [`timeseries`] combines delta
+//! prediction with an adaptive Rice code into a ready-to-use codec.
+//!
 //! Further, `constriction` provides implementations of common probability distributions in
 //! fixed-point arithmetic, which can be used as entropy models in either of the above
 //! stream codes. The crate also provides adapters for turning custom probability
@@ -247,6 +250,42 @@
 //! assert_eq!(decode_sample_data(compressed), [23, -15, 78, 43, -69]);
 //! ```
 //!
+//! # Avoiding Panics
+//!
+//! Most methods in `constriction` report invalid input by returning an error. Methods that
+//! panic on input that is under the caller's control (rather than only on violated internal
+//! invariants) document this in a "Panics" section. Where such input typically depends on
+//! the data (or on untrusted parameters), there's a variant with a `checked_` prefix that
+//! doesn't panic on it. In particular:
+//!
+//! - [`EncoderHuffmanTree::checked_from_probabilities`] and
+//!   [`DecoderHuffmanTree::checked_from_probabilities`] (and their `_float_` variants)
+//!   return an error for an empty alphabet;
+//! - `checked_num_words` and `checked_num_bits` on [`AnsCoder`] and [`RangeEncoder`]
+//!   return `None` if the size of the compressed data doesn't fit into a `usize`;
+//! - [`Decode::checked_decode_enum`] returns `Ok(None)` if the entropy model decodes a
+//!   symbol outside of the alphabet;
+//! - [`LeakyQuantizer::checked_new`] (and `checked_new_f32`),
+//!   [`UniformModel::checked_new`], [`WrappedDistribution::checked_new`], and
+//!   [`VonMises::checked_new`] return `None` for invalid parameters.
+//!
+//! The `checked_` variants are always available, i.e., they aren't gated behind a cargo
+//! feature, and they don't change the behavior of the methods they complement. Thus,
+//! existing code keeps compiling and behaving as before, and code that must not panic on
+//! untrusted input can switch to the `checked_` variants one call at a time.
+//!
+//! [`EncoderHuffmanTree::checked_from_probabilities`]:
+//!     symbol::huffman::EncoderHuffmanTree::checked_from_probabilities
+//! [`DecoderHuffmanTree::checked_from_probabilities`]:
+//!     symbol::huffman::DecoderHuffmanTree::checked_from_probabilities
+//! [`AnsCoder`]: stream::stack::AnsCoder
+//! [`RangeEncoder`]: stream::queue::RangeEncoder
+//! [`Decode::checked_decode_enum`]: stream::Decode::checked_decode_enum
+//! [`LeakyQuantizer::checked_new`]: stream::model::LeakyQuantizer::checked_new
+//! [`UniformModel::checked_new`]: stream::model::UniformModel::checked_new
+//! [`WrappedDistribution::checked_new`]: stream::model::WrappedDistribution::checked_new
+//! [`VonMises::checked_new`]: stream::model::VonMises::checked_new
+//!
 //! # Where to Go Next?
 //!
 //! If you already have an entropy model and you just want to encode and decode some
@@ -268,9 +307,18 @@ extern crate std;
 #[cfg(feature = "pybindings")]
 mod pybindings;
 
+#[cfg(feature = "std")]
+pub mod archive;
 pub mod backends;
+pub mod compat;
+pub mod compress;
 pub mod stream;
 pub mod symbol;
+pub mod timeseries;
+
+#[allow(deprecated)]
+#[doc(hidden)]
+pub use compat::{distributions, stack};
 
 use core::{
     convert::Infallible,
@@ -409,6 +457,80 @@ impl DefaultEncoderFrontendError {
     }
 }
 
+// SYMBOL ALPHABETS ===========================================================
+
+/// A finite set of symbols that can be mapped to the contiguous indices `0..NUM_SYMBOLS`
+///
+/// Implementing this trait for a type lets you encode and decode values of this type
+/// directly with entropy models over `usize` symbols, e.g., with a
+/// [`ContiguousCategoricalEntropyModel`] (see [`Encode::encode_enum`] and
+/// [`Decode::decode_enum`]). This is more type safe than converting between your own types
+/// and raw `usize` symbols by hand throughout your code.
+///
+/// You'll usually not implement this trait manually. For fieldless enums, enable the
+/// `derive` feature of `constriction` and use `#[derive(SymbolAlphabet)]`, which maps the
+/// variants to indices in the order in which they are declared.
+///
+/// # Example
+///
+/// A manual implementation that is equivalent to what `#[derive(SymbolAlphabet)]` would
+/// generate:
+///
+/// ```
+/// use constriction::SymbolAlphabet;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// enum Direction {
+///     North,
+///     East,
+///     South,
+///     West,
+/// }
+///
+/// impl SymbolAlphabet for Direction {
+///     const NUM_SYMBOLS: usize = 4;
+///
+///     fn to_index(&self) -> usize {
+///         match self {
+///             Self::North => 0,
+///             Self::East => 1,
+///             Self::South => 2,
+///             Self::West => 3,
+///         }
+///     }
+///
+///     fn from_index(index: usize) -> Option<Self> {
+///         match index {
+///             0 => Some(Self::North),
+///             1 => Some(Self::East),
+///             2 => Some(Self::South),
+///             3 => Some(Self::West),
+///             _ => None,
+///         }
+///     }
+/// }
+///
+/// assert_eq!(Direction::from_index(Direction::South.to_index()), Some(Direction::South));
+/// assert_eq!(Direction::from_index(Direction::NUM_SYMBOLS), None);
+/// ```
+///
+/// [`ContiguousCategoricalEntropyModel`]: stream::model::ContiguousCategoricalEntropyModel
+/// [`Encode::encode_enum`]: stream::Encode::encode_enum
+/// [`Decode::decode_enum`]: stream::Decode::decode_enum
+pub trait SymbolAlphabet: Sized {
+    /// The number of distinct symbols.
+    const NUM_SYMBOLS: usize;
+
+    /// Returns the index of `self`, which must be smaller than `NUM_SYMBOLS`.
+    fn to_index(&self) -> usize;
+
+    /// Returns the symbol with index `index`, or `None` if `index >= NUM_SYMBOLS`.
+    fn from_index(index: usize) -> Option<Self>;
+}
+
+#[cfg(feature = "derive")]
+pub use constriction_derive::SymbolAlphabet;
+
 /// Trait for coders or backends that *might* implement [`Pos`] and/or [`Seek`]
 ///
 /// If a type implements `PosSeek` then that doesn't necessarily mean that it also
@@ -577,7 +699,30 @@ pub trait Seek: PosSeek {
     /// assert!(decoder.is_empty()); // <-- We've reached the end of the compressed data.
     /// ```
     ///
+    /// # Seeking Close to the End of the Compressed Data
+    ///
+    /// All seekable decoders in `constriction` (currently [`RangeDecoder`] and
+    /// [`AnsCoder`]) provide the following guarantees at the boundaries of the compressed
+    /// data:
+    ///
+    /// - **The final position is valid.** Seeking to the snapshot taken after encoding the
+    ///   last symbol (for a `RangeDecoder`) or before encoding the first symbol (for an
+    ///   `AnsCoder`, which decodes in reverse order) succeeds, and the decoder then reports
+    ///   that it [may be exhausted](stream::Decode::maybe_exhausted).
+    /// - **Positions up to and including one past the last word are valid.** Seeking to
+    ///   such a position succeeds even if the compressed data after it doesn't fill up the
+    ///   coder's entire `State` (e.g., if only a single `Word` remains but the coder's
+    ///   `State` has the size of two `Word`s). In this case, the decoder treats the missing
+    ///   `Word`s exactly as if it had decoded past the end of the compressed data (a
+    ///   `RangeDecoder` pads with zero words, and an `AnsCoder` doesn't need any further
+    ///   words). Thus, decoding after a `seek` is indistinguishable from decoding up to the
+    ///   same position without seeking.
+    /// - **Positions beyond the end are rejected.** Seeking to a position beyond one past
+    ///   the last word returns `Err(())` and leaves the decoder unchanged.
+    ///
     /// [`DefaultAnsCoder`]: stream::stack::DefaultAnsCoder
+    /// [`RangeDecoder`]: stream::queue::RangeDecoder
+    /// [`AnsCoder`]: stream::stack::AnsCoder
     #[allow(clippy::result_unit_err)]
     fn seek(&mut self, pos: Self::Position) -> Result<(), ()>;
 }
@@ -676,7 +821,7 @@ pub unsafe trait NonZeroBitArray: Copy + Display + Debug + Eq + Hash + 'static {
 /// initial zero chunks.
 fn bit_array_to_chunks_truncated<Data, Chunk>(
     data: Data,
-) -> impl Iterator<Item = Chunk> + ExactSizeIterator + DoubleEndedIterator
+) -> impl ExactSizeIterator<Item = Chunk> + DoubleEndedIterator
 where
     Data: BitArray + AsPrimitive<Chunk>,
     Chunk: BitArray,
@@ -0,0 +1,186 @@
+use std::prelude::v1::*;
+
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter},
+};
+
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::{prelude::*, types::PyDict};
+
+use crate::archive::{self, ArchiveError};
+
+pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_class::<ArchiveWriter>()?;
+    module.add_class::<ArchiveReader>()?;
+    Ok(())
+}
+
+impl From<ArchiveError> for PyErr {
+    fn from(err: ArchiveError) -> Self {
+        match err {
+            ArchiveError::Io(err) => err.into(),
+            ArchiveError::NotFound(_) => pyo3::exceptions::PyKeyError::new_err(err.to_string()),
+            _ => pyo3::exceptions::PyValueError::new_err(err.to_string()),
+        }
+    }
+}
+
+/// Writes named compressed messages into a new archive file.
+///
+/// Creates (or overwrites) the file at `path`. Call the method `add` for each message and
+/// then `close`, which writes the index of the archive. The file is not a valid archive
+/// until you call `close`.
+///
+/// ## Example
+///
+/// ```python
+/// model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 10.0)
+/// writer = constriction.archive.ArchiveWriter("messages.cnsa")
+/// for name, message in [("first", [3, -7, 12]), ("second", [-20, 0, 5, 8, 1])]:
+///     coder = constriction.stream.stack.AnsCoder()
+///     coder.encode_reverse(np.array(message, dtype=np.int32), model)
+///     writer.add(name, coder.get_compressed(), model_id=1)
+/// writer.close()
+///
+/// reader = constriction.archive.ArchiveReader("messages.cnsa")
+/// assert "second" in reader
+/// coder = constriction.stream.stack.AnsCoder(reader.read("second"))
+/// assert np.all(coder.decode(model, 5) == [-20, 0, 5, 8, 1])
+/// ```
+#[pyclass]
+#[pyo3(text_signature = "(path)")]
+#[derive(Debug)]
+pub struct ArchiveWriter {
+    inner: Option<archive::ArchiveWriter<BufWriter<File>>>,
+}
+
+#[pymethods]
+impl ArchiveWriter {
+    #[new]
+    pub fn new(path: &str) -> PyResult<Self> {
+        let inner = archive::ArchiveWriter::new(BufWriter::new(File::create(path)?))?;
+        Ok(Self { inner: Some(inner) })
+    }
+
+    /// Appends a message to the archive.
+    ///
+    /// ## Arguments
+    ///
+    /// - **name** --- a string that identifies the message; must be unique within the
+    ///   archive.
+    /// - **compressed** --- the compressed data as a rank-1 numpy array with
+    ///   `dtype=np.uint32`, e.g., as returned by `AnsCoder.get_compressed`.
+    /// - **model_id** --- an optional integer (between `0` and `2**32 - 1`) that records
+    ///   which entropy model was used for compression. Defaults to `0`.
+    /// - **checkpoint** --- an optional tuple `(position, state)`, e.g., as returned by
+    ///   `AnsCoder.pos`.
+    #[pyo3(text_signature = "(self, name, compressed, model_id=0, checkpoint=None)")]
+    pub fn add(
+        &mut self,
+        name: &str,
+        compressed: PyReadonlyArray1<'_, u32>,
+        model_id: Option<u32>,
+        checkpoint: Option<(u64, u64)>,
+    ) -> PyResult<()> {
+        self.inner_mut()?.add(
+            name,
+            compressed.as_slice()?,
+            model_id.unwrap_or(0),
+            checkpoint,
+        )?;
+        Ok(())
+    }
+
+    /// Writes the index of the archive and closes the file.
+    #[pyo3(text_signature = "(self)")]
+    pub fn close(&mut self) -> PyResult<()> {
+        if let Some(inner) = self.inner.take() {
+            inner.finish()?;
+        }
+        Ok(())
+    }
+
+    pub fn __len__(&self) -> PyResult<usize> {
+        Ok(self.inner_ref()?.len())
+    }
+}
+
+impl ArchiveWriter {
+    fn inner_ref(&self) -> PyResult<&archive::ArchiveWriter<BufWriter<File>>> {
+        self.inner.as_ref().ok_or_else(closed_error)
+    }
+
+    fn inner_mut(&mut self) -> PyResult<&mut archive::ArchiveWriter<BufWriter<File>>> {
+        self.inner.as_mut().ok_or_else(closed_error)
+    }
+}
+
+fn closed_error() -> PyErr {
+    pyo3::exceptions::PyValueError::new_err("The archive has already been closed.")
+}
+
+/// Reads compressed messages by their names from an archive file.
+///
+/// Opening an archive reads only its index. The method `read` then reads only the
+/// compressed data of the requested message. See `ArchiveWriter` for an example.
+#[pyclass]
+#[pyo3(text_signature = "(path)")]
+#[derive(Debug)]
+pub struct ArchiveReader {
+    inner: archive::ArchiveReader<BufReader<File>>,
+}
+
+#[pymethods]
+impl ArchiveReader {
+    #[new]
+    pub fn new(path: &str) -> PyResult<Self> {
+        let inner = archive::ArchiveReader::new(BufReader::new(File::open(path)?))?;
+        Ok(Self { inner })
+    }
+
+    /// Returns a list of the names of all messages in the archive, sorted alphabetically.
+    #[pyo3(text_signature = "(self)")]
+    pub fn keys(&self) -> Vec<String> {
+        self.inner
+            .entries()
+            .map(|(name, _)| name.to_string())
+            .collect()
+    }
+
+    /// Returns the metadata of a message as a dictionary with the entries `"offset"` and
+    /// `"len"` (position and size of the compressed data, in units of 32-bit words),
+    /// `"model_id"`, and `"checkpoint"` (either a tuple `(position, state)` or `None`).
+    ///
+    /// Raises a `KeyError` if the archive contains no message with the given name.
+    #[pyo3(text_signature = "(self, name)")]
+    pub fn entry<'py>(&self, py: Python<'py>, name: &str) -> PyResult<&'py PyDict> {
+        let entry = self
+            .inner
+            .entry(name)
+            .ok_or_else(|| ArchiveError::NotFound(name.to_string()))?;
+        let dict = PyDict::new(py);
+        dict.set_item("offset", entry.offset)?;
+        dict.set_item("len", entry.len)?;
+        dict.set_item("model_id", entry.model_id)?;
+        dict.set_item("checkpoint", entry.checkpoint)?;
+        Ok(dict)
+    }
+
+    /// Reads the compressed data of a message and returns it as a rank-1 numpy array with
+    /// `dtype=np.uint32`.
+    ///
+    /// Raises a `KeyError` if the archive contains no message with the given name.
+    #[pyo3(text_signature = "(self, name)")]
+    pub fn read<'py>(&mut self, py: Python<'py>, name: &str) -> PyResult<&'py PyArray1<u32>> {
+        Ok(PyArray1::from_vec(py, self.inner.read(name)?))
+    }
+
+    pub fn __len__(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn __contains__(&self, name: &str) -> bool {
+        self.inner.contains(name)
+    }
+}
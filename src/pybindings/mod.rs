@@ -1,9 +1,15 @@
+pub mod archive;
 pub mod stream;
 pub mod symbol;
+pub mod timeseries;
 
 use std::prelude::v1::*;
 
-use pyo3::{prelude::*, wrap_pymodule};
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyList},
+    wrap_pyfunction, wrap_pymodule,
+};
 
 /// ## Entropy Coders for Research and Production
 ///
@@ -174,9 +180,61 @@ use pyo3::{prelude::*, wrap_pymodule};
 fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
     module.add_wrapped(wrap_pymodule!(stream))?;
     module.add_wrapped(wrap_pymodule!(symbol))?;
+    module.add_wrapped(wrap_pymodule!(timeseries))?;
+    module.add_wrapped(wrap_pymodule!(archive))?;
+    module.add_function(wrap_pyfunction!(info, module)?)?;
     Ok(())
 }
 
+/// Returns a dictionary that describes how this build of `constriction` was compiled.
+///
+/// The dictionary has the following entries:
+///
+/// - `"version"`: the version of `constriction` as a string;
+/// - `"features"`: a list of the cargo features that were enabled at compile time; and
+/// - `"coders"`: a dictionary that maps the name of each stream coder to its configuration,
+///   i.e., to the same dictionary that you get from the coder's `config` attribute.
+///
+/// Include the output of this function in bug reports, and consider storing it alongside
+/// the results of compression experiments so that they can be reproduced exactly.
+///
+/// ## Example
+///
+/// ```python
+/// info = constriction.info()
+/// print(info["version"])  # (prints, e.g.: 0.2.4)
+/// assert info["coders"]["AnsCoder"] == constriction.stream.stack.AnsCoder().config
+/// print(info["coders"]["AnsCoder"])
+/// # (prints: {'word_size': 32, 'state_size': 64, 'precision': 24})
+/// ```
+#[pyfunction]
+#[pyo3(text_signature = "()")]
+fn info(py: Python<'_>) -> PyResult<&PyDict> {
+    let features = [
+        ("std", cfg!(feature = "std")),
+        ("probability", cfg!(feature = "probability")),
+        ("derive", cfg!(feature = "derive")),
+        ("pybindings", cfg!(feature = "pybindings")),
+    ];
+    let features = features
+        .iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| *name)
+        .collect::<Vec<_>>();
+
+    let coders = PyDict::new(py);
+    coders.set_item("RangeEncoder", stream::coder_config::<u32, u64>(py)?)?;
+    coders.set_item("RangeDecoder", stream::coder_config::<u32, u64>(py)?)?;
+    coders.set_item("AnsCoder", stream::coder_config::<u32, u64>(py)?)?;
+    coders.set_item("ChainCoder", stream::coder_config::<u32, u64>(py)?)?;
+
+    let info = PyDict::new(py);
+    info.set_item("version", env!("CARGO_PKG_VERSION"))?;
+    info.set_item("features", PyList::new(py, features))?;
+    info.set_item("coders", coders)?;
+    Ok(info)
+}
+
 /// Stream codes, i.e., entropy codes that amortize compressed bits over several symbols.
 ///
 /// We provide two main stream codes:
@@ -196,11 +254,23 @@ fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
 /// We further provide an experimental new "Chain Coder"  (in submodule [`chain`](stream/chain.html)),
 /// which is intended for special new compression methods.
 ///
+/// All coders and models in these submodules use 32-bit words of compressed data and
+/// represent probabilities with 24 bits of precision.
+///
 /// ## Examples
 ///
 /// See top of the documentations of both submodules [`queue`](stream/queue.html) and
 /// [`stack`](stream/stack.html).
 ///
+/// ## Error Handling
+///
+/// Encoding or decoding an array of symbols is *transactional*: if any symbol in the array
+/// fails (e.g., because it has zero probability under its entropy model, because a model
+/// parameter is invalid, or because a custom model raises an exception), then the method
+/// raises an exception and leaves the entropy coder in exactly the state it was in before
+/// the call. No symbols from the failed array get encoded or decoded, so you can catch the
+/// exception and continue using the same coder, e.g., with corrected model parameters.
+///
 /// ## References
 ///
 /// [1] Pasco, Richard Clark. Source coding algorithms for fast data compression. Diss.
@@ -312,3 +382,55 @@ fn stream(py: Python<'_>, module: &PyModule) -> PyResult<()> {
 fn symbol(py: Python<'_>, module: &PyModule) -> PyResult<()> {
     symbol::init_module(py, module)
 }
+
+/// Lossless compression of integer time series (e.g., sensor readings).
+///
+/// The function `encode` predicts each sample from its predecessors (using either first or
+/// second order differences), and it encodes the prediction residuals with a Rice code
+/// whose parameter adapts to the magnitude of recent residuals. The result is a
+/// self-contained array of compressed data, which the function `decode` turns back into
+/// the original samples. All arithmetic wraps around, so arbitrary `int32` samples can be
+/// encoded, including sudden jumps between the most extreme values.
+///
+/// ## Example
+///
+/// ```python
+/// samples = np.array([1000, 1003, 1007, 1010, 1012, 1015, 1017, 1018, 1020, 1021], dtype=np.int32)
+/// compressed = constriction.timeseries.encode(samples, order=2)
+/// print(f"compressed {samples.nbytes} bytes into {compressed.nbytes} bytes.")
+/// # (prints: "compressed 40 bytes into 8 bytes.")
+///
+/// decoded = constriction.timeseries.decode(compressed)
+/// assert np.all(decoded == samples)
+/// ```
+#[pymodule]
+fn timeseries(py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    timeseries::init_module(py, module)
+}
+
+/// A file format for storing many named compressed messages in one file.
+///
+/// An `ArchiveWriter` appends compressed messages (i.e., numpy arrays with
+/// `dtype=np.uint32` as returned by the `get_compressed` method of the stream coders) to a
+/// file, together with a name, an optional integer `model_id`, and an optional checkpoint
+/// for each message. An `ArchiveReader` reads back individual messages by their names
+/// without reading the rest of the file. The file format is the same as the one used by the
+/// Rust API of `constriction`, so archives can be shared between Python and Rust.
+///
+/// ## Example
+///
+/// ```python
+/// writer = constriction.archive.ArchiveWriter("dataset.cnsa")
+/// writer.add("image-0", np.array([0x1234, 0x5678], dtype=np.uint32))
+/// writer.add("image-1", np.array([0x9abc], dtype=np.uint32), model_id=2)
+/// writer.close()
+///
+/// reader = constriction.archive.ArchiveReader("dataset.cnsa")
+/// print(reader.keys()) # (prints: ['image-0', 'image-1'])
+/// print(reader.entry("image-1")["model_id"]) # (prints: 2)
+/// print(reader.read("image-1")) # (prints: [39612])
+/// ```
+#[pymodule]
+fn archive(py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    archive::init_module(py, module)
+}
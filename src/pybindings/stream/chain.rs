@@ -2,7 +2,10 @@ use std::prelude::v1::*;
 
 use numpy::{PyArray1, PyReadonlyArray1};
 use probability::distribution::Gaussian;
-use pyo3::{prelude::*, types::PyTuple};
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyTuple},
+};
 
 use crate::{
     pybindings::stream::model::Model,
@@ -14,7 +17,7 @@ use crate::{
     UnwrapInfallible,
 };
 
-use super::model::internals::EncoderDecoderModel;
+use super::{model::internals::EncoderDecoderModel, validate_symbols, DecodedSymbols, Symbols};
 
 pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
     module.add_class::<ChainCoder>()?;
@@ -23,6 +26,10 @@ pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
 
 /// See [above usage instructions](#usage-for-bits-back-coding) for explanation of
 /// constructor arguments.
+///
+/// Unlike its Rust counterpart, this class has no methods `increase_precision`,
+/// `decrease_precision`, or `change_precision` since all entropy models in the Python API
+/// use the same fixed point precision of 24 bits.
 #[pyclass]
 #[pyo3(text_signature = "(compressed, is_remainders=False, seal=False)")]
 #[derive(Debug, Clone)]
@@ -65,6 +72,57 @@ impl ChainCoder {
         Ok(Self { inner })
     }
 
+    /// Creates a `ChainCoder` for decoding from arbitrary binary data.
+    ///
+    /// Equivalent to calling the constructor with `is_remainders=False` and `seal=True`,
+    /// i.e., `data` may end in a zero word. This is the Python equivalent of
+    /// `ChainCoder::from_binary` in the Rust API. The matching method for getting the
+    /// original data back after re-encoding is
+    /// [`get_binary`](#constriction.stream.chain.ChainCoder.get_binary).
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// data = np.array([0x0123_4567, 0x89ab_cdef], dtype=np.uint32)
+    /// model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 10.0)
+    ///
+    /// coder = constriction.stream.chain.ChainCoder.from_binary(data)
+    /// symbols = coder.decode(model, 1)
+    /// assert not coder.is_whole() # (24 bits consumed from `data`)
+    /// coder.encode_reverse(symbols, model)
+    /// assert coder.is_whole()
+    /// prefix, suffix = coder.get_binary()
+    /// assert np.all(np.concatenate((prefix, suffix)) == data)
+    /// ```
+    #[staticmethod]
+    #[pyo3(text_signature = "(data)")]
+    pub fn from_binary(data: PyReadonlyArray1<'_, u32>) -> PyResult<Self> {
+        Self::new(data, Some(false), Some(true))
+    }
+
+    /// Returns `True` iff the compressed data currently consists of an integer number of
+    /// words.
+    ///
+    /// A `ChainCoder` consumes the fixed amount of 24 bits from its compressed data per
+    /// decoded symbol, and it writes the same amount of bits back per encoded symbol. Since
+    /// 24 bits aren't a whole 32 bit word, the compressed data can only be exported (with
+    /// [`get_data`](#constriction.stream.chain.ChainCoder.get_data) or
+    /// [`get_binary`](#constriction.stream.chain.ChainCoder.get_binary)) at points where
+    /// this method returns `True`. This is always the case once you've re-encoded all
+    /// symbols that you decoded from a freshly constructed `ChainCoder`.
+    #[pyo3(text_signature = "()")]
+    pub fn is_whole(&self) -> bool {
+        self.inner.is_whole()
+    }
+
+    /// The configuration of this coder, i.e., a dictionary with the entries `"word_size"`,
+    /// `"state_size"`, and `"precision"` (all in bits). Equal to
+    /// `constriction.info()["coders"]["ChainCoder"]`.
+    #[getter]
+    pub fn config<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        super::coder_config::<u32, u64>(py)
+    }
+
     /// Returns a copy of the compressed data after re-encoding symbols, split into two
     /// arrays that you may want to concatenate.
     ///
@@ -75,13 +133,12 @@ impl ChainCoder {
         unseal: Option<bool>,
         py: Python<'p>,
     ) -> PyResult<(&'p PyArray1<u32>, &'p PyArray1<u32>)> {
-        let cloned = self.inner.clone();
-        let data = if unseal == Some(true) {
-            cloned.into_binary()
-        } else {
-            cloned.into_compressed()
-        };
-        let (remainders, compressed) = data.map_err(|_| {
+        if unseal == Some(true) {
+            return self.get_binary(py);
+        }
+
+        self.check_whole()?;
+        let (remainders, compressed) = self.inner.clone().into_compressed().map_err(|_| {
             pyo3::exceptions::PyAssertionError::new_err(
                 "Fractional number of words in compressed or remainders data.",
             )
@@ -92,6 +149,36 @@ impl ChainCoder {
         Ok((remainders, compressed))
     }
 
+    /// Returns a copy of the original binary data after re-encoding symbols, split into two
+    /// arrays that you may want to concatenate.
+    ///
+    /// Use this method if you constructed the coder with
+    /// [`from_binary`](#constriction.stream.chain.ChainCoder.from_binary) (or, equivalently,
+    /// with `seal=True`). It is equivalent to `get_data(unseal=True)`.
+    ///
+    /// Raises an `AssertionError` if the coder currently doesn't hold an integer number of
+    /// words (see [`is_whole`](#constriction.stream.chain.ChainCoder.is_whole)), or if the
+    /// remainders don't fit into whole words, which typically means that you haven't yet
+    /// re-encoded all symbols that you decoded (or that you re-encoded them with different
+    /// entropy models).
+    #[pyo3(text_signature = "()")]
+    pub fn get_binary<'p>(
+        &self,
+        py: Python<'p>,
+    ) -> PyResult<(&'p PyArray1<u32>, &'p PyArray1<u32>)> {
+        self.check_whole()?;
+        let (remainders, compressed) = self.inner.clone().into_binary().map_err(|_| {
+            pyo3::exceptions::PyAssertionError::new_err(
+                "Fractional number of words in remainders data. Did you re-encode all decoded \
+                symbols with the same entropy models that you used for decoding?",
+            )
+        })?;
+
+        let remainders = PyArray1::from_vec(py, remainders);
+        let compressed = PyArray1::from_vec(py, compressed);
+        Ok((remainders, compressed))
+    }
+
     /// Returns a copy of the remainders after decoding some symbols, split into two arrays
     /// that you may want to concatenate.
     ///
@@ -115,14 +202,25 @@ impl ChainCoder {
     /// buffer, regardless of the employed entropy model(s), and it consumes (24 bits - inf_content)
     /// per symbol from the internal "remainders" buffer (where "inf_content" is the information
     /// content of the encoded symbol under the employed entropy model).
-    #[pyo3(text_signature = "(symbols, model, optional_model_params)")]
-    #[args(symbols, model, params = "*")]
+    ///
+    /// If encoding fails for any symbol (e.g., because there are not enough remainders left),
+    /// then this method raises an exception and leaves both buffers unchanged. Note that this
+    /// guarantee is implemented by temporarily copying the coder, so the overhead of each call
+    /// grows with the size of the buffers.
+    ///
+    /// If you set `validate=True`, then this method first checks that all `symbols` lie in
+    /// the support of their respective entropy models (at the cost of constructing each
+    /// entropy model twice). If any symbols don't, then it raises a `KeyError` that lists the
+    /// indices of the first few offending symbols, and it doesn't encode anything.
+    #[pyo3(text_signature = "(symbols, model, optional_model_params, validate=False)")]
+    #[args(symbols, model, params = "*", validate = "false")]
     pub fn encode_reverse(
         &mut self,
         py: Python<'_>,
         symbols: &PyAny,
         model: &Model,
         params: &PyTuple,
+        validate: bool,
     ) -> PyResult<()> {
         if let Ok(symbol) = symbols.extract::<i32>() {
             if !params.is_empty() {
@@ -143,33 +241,39 @@ impl ChainCoder {
             });
         }
 
-        // Don't use an `else` branch here because, if the following `extract` fails, the returned
-        // error message is actually pretty user friendly.
-        let symbols = symbols.extract::<PyReadonlyArray1<'_, i32>>()?;
-        let symbols = symbols.as_slice()?;
+        let symbols = Symbols::extract(py, symbols)?;
+        let symbols = symbols.iter();
+        if validate {
+            validate_symbols(py, symbols.clone(), model, params)?;
+        }
 
         if params.is_empty() {
-            model.0.as_parameterized(py, &mut |model| {
-                self.inner
-                    .encode_iid_symbols_reverse(symbols, EncoderDecoderModel(model))?;
-                Ok(())
-            })?;
+            self.inner.encode_transactionally(|inner| {
+                model.0.as_parameterized(py, &mut |model| {
+                    inner
+                        .encode_iid_symbols_reverse(symbols.clone(), EncoderDecoderModel(model))?;
+                    Ok(())
+                })
+            })
         } else {
             if symbols.len() != model.0.len(&params[0])? {
                 return Err(pyo3::exceptions::PyAttributeError::new_err(
                     "`symbols` argument has wrong length.",
                 ));
             }
-            let mut symbol_iter = symbols.iter().rev();
-            model.0.parameterize(py, params, true, &mut |model| {
-                let symbol = symbol_iter.next().expect("TODO");
-                self.inner
-                    .encode_symbol(*symbol, EncoderDecoderModel(model))?;
-                Ok(())
-            })?;
+            let mut symbol_iter = symbols.rev();
+            self.inner.encode_transactionally(|inner| {
+                model.0.parameterize(py, params, true, &mut |model| {
+                    let symbol = symbol_iter.next().ok_or_else(|| {
+                        pyo3::exceptions::PyAttributeError::new_err(
+                            "`symbols` argument has wrong length.",
+                        )
+                    })?;
+                    inner.encode_symbol(symbol, EncoderDecoderModel(model))?;
+                    Ok(())
+                })
+            })
         }
-
-        Ok(())
     }
 
     /// .. deprecated:: 0.2.0
@@ -202,21 +306,20 @@ impl ChainCoder {
         }
 
         let quantizer = DefaultLeakyQuantizer::new(min_supported_symbol..=max_supported_symbol);
-        self.inner.try_encode_symbols_reverse(
-            symbols
-                .iter()
-                .zip(means.iter())
-                .zip(stds.iter())
-                .map(|((&symbol, &mean), &std)| {
-                    if std > 0.0 && std.is_finite() && mean.is_finite() {
-                        Ok((symbol, quantizer.quantize(Gaussian::new(mean, std))))
-                    } else {
-                        Err(())
-                    }
-                }),
-        )?;
-
-        Ok(())
+        self.inner.encode_transactionally(|inner| {
+            inner.try_encode_symbols_reverse(
+                symbols.iter().zip(means.iter()).zip(stds.iter()).map(
+                    |((&symbol, &mean), &std)| {
+                        if std > 0.0 && std.is_finite() && mean.is_finite() {
+                            Ok((symbol, quantizer.quantize(Gaussian::new(mean, std))))
+                        } else {
+                            Err(())
+                        }
+                    },
+                ),
+            )?;
+            Ok(())
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -248,15 +351,16 @@ impl ChainCoder {
             )
         })?;
 
-        self.inner.encode_iid_symbols_reverse(
-            symbols
-                .as_slice()?
-                .iter()
-                .map(|s| s.wrapping_sub(min_supported_symbol) as usize),
-            &model,
-        )?;
-
-        Ok(())
+        let symbols = symbols.as_slice()?;
+        self.inner.encode_transactionally(|inner| {
+            inner.encode_iid_symbols_reverse(
+                symbols
+                    .iter()
+                    .map(|s| s.wrapping_sub(min_supported_symbol) as usize),
+                &model,
+            )?;
+            Ok(())
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -279,7 +383,7 @@ impl ChainCoder {
             None
         );
 
-        self.encode_reverse(py, &symbols, model, PyTuple::empty(py))
+        self.encode_reverse(py, &symbols, model, PyTuple::empty(py), false)
     }
 
     /// Decodes one or more symbols.
@@ -291,13 +395,17 @@ impl ChainCoder {
     /// bits - inf_content) per symbol to the internal "remainders" buffer (where
     /// "inf_content" is the information content of the decoded symbol under the employed
     /// entropy model).
-    #[pyo3(text_signature = "(model, optional_amt_or_model_params)")]
-    #[args(symbols, model, params = "*")]
+    ///
+    /// As with `encode_reverse`, a failure for any symbol raises an exception and leaves both
+    /// buffers unchanged.
+    #[pyo3(text_signature = "(model, optional_amt_or_model_params, dtype=None)")]
+    #[args(symbols, model, params = "*", dtype = "None")]
     pub fn decode<'py>(
         &mut self,
         py: Python<'py>,
         model: &Model,
         params: &PyTuple,
+        dtype: Option<PyObject>,
     ) -> PyResult<PyObject> {
         match params.len() {
             0 => {
@@ -313,34 +421,87 @@ impl ChainCoder {
             }
             1 => {
                 if let Ok(amt) = usize::extract(params.as_slice()[0]) {
-                    let mut symbols = Vec::with_capacity(amt);
-                    model.0.as_parameterized(py, &mut |model| {
-                        for symbol in self
-                            .inner
-                            .decode_iid_symbols(amt, EncoderDecoderModel(model))
-                        {
-                            let symbol = symbol.expect("We use constant `PRECISION`.");
-                            symbols.push(symbol);
-                        }
-                        Ok(())
+                    let mut symbols = DecodedSymbols::with_capacity(py, dtype, amt)?;
+                    self.inner.decode_transactionally(|inner| {
+                        model.0.as_parameterized(py, &mut |model| {
+                            for symbol in inner.decode_iid_symbols(amt, EncoderDecoderModel(model))
+                            {
+                                let symbol = symbol.expect("We use constant `PRECISION`.");
+                                symbols.push(symbol)?;
+                            }
+                            Ok(())
+                        })
                     })?;
-                    return Ok(PyArray1::from_iter(py, symbols).to_object(py));
+                    return Ok(symbols.into_py(py));
                 }
             }
             _ => {} // Fall through to code below.
         };
 
-        let mut symbols = Vec::with_capacity(model.0.len(&params[0])?);
-        model.0.parameterize(py, params, false, &mut |model| {
-            let symbol = self
-                .inner
-                .decode_symbol(EncoderDecoderModel(model))
-                .expect("We use constant `PRECISION`.");
-            symbols.push(symbol);
-            Ok(())
+        let mut symbols = DecodedSymbols::with_capacity(py, dtype, model.0.len(&params[0])?)?;
+        self.inner.decode_transactionally(|inner| {
+            model.0.parameterize(py, params, false, &mut |model| {
+                let symbol = inner
+                    .decode_symbol(EncoderDecoderModel(model))
+                    .expect("We use constant `PRECISION`.");
+                symbols.push(symbol)?;
+                Ok(())
+            })
         })?;
 
-        Ok(PyArray1::from_vec(py, symbols).to_object(py))
+        Ok(symbols.into_py(py))
+    }
+
+    /// Advances the decoder past one or more symbols without returning them.
+    ///
+    /// Takes the same arguments as
+    /// [`decode`](#constriction.stream.chain.ChainCoder.decode) (except for `dtype`), i.e.,
+    /// `skip(model)` skips a single symbol, `skip(model, amt)` skips `amt` i.i.d. symbols,
+    /// and `skip(model_family, params1, params2, ...)` skips one symbol per entry of the
+    /// model parameters. The decoder ends up in the same state as if you had called
+    /// `decode` with the same arguments, but no numpy array of decoded symbols is
+    /// allocated. This is useful for jumping into the middle of a stream when there's no
+    /// checkpoint close to the position you want to jump to.
+    ///
+    /// Like `decode`, skipping a symbol consumes 24 bits from the internal "compressed"
+    /// buffer and appends to the internal "remainders" buffer.
+    ///
+    /// As with `decode`, a failure raises an exception and leaves the decoder unchanged.
+    #[pyo3(text_signature = "(model, optional_amt_or_model_params)")]
+    #[args(model, params = "*")]
+    pub fn skip(&mut self, py: Python<'_>, model: &Model, params: &PyTuple) -> PyResult<()> {
+        match params.len() {
+            0 => {
+                return model.0.as_parameterized(py, &mut |model| {
+                    self.inner
+                        .decode_symbol(EncoderDecoderModel(model))
+                        .expect("We use constant `PRECISION`.");
+                    Ok(())
+                });
+            }
+            1 => {
+                if let Ok(amt) = usize::extract(params.as_slice()[0]) {
+                    return self.inner.decode_transactionally(|inner| {
+                        model.0.as_parameterized(py, &mut |model| {
+                            inner
+                                .skip_iid_symbols(amt, EncoderDecoderModel(model))
+                                .expect("We use constant `PRECISION`.");
+                            Ok(())
+                        })
+                    });
+                }
+            }
+            _ => {} // Fall through to code below.
+        };
+
+        self.inner.decode_transactionally(|inner| {
+            model.0.parameterize(py, params, false, &mut |model| {
+                inner
+                    .decode_symbol(EncoderDecoderModel(model))
+                    .expect("We use constant `PRECISION`.");
+                Ok(())
+            })
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -371,21 +532,23 @@ impl ChainCoder {
         }
 
         let quantizer = DefaultLeakyQuantizer::new(min_supported_symbol..=max_supported_symbol);
-        let symbols = self
-            .inner
-            .try_decode_symbols(means.iter()?.zip(stds.iter()?).map(|(&mean, &std)| {
-                if std > 0.0 && std.is_finite() && mean.is_finite() {
-                    Ok(quantizer.quantize(Gaussian::new(mean, std)))
-                } else {
-                    Err(())
-                }
-            }))
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .map_err(|_| {
-                pyo3::exceptions::PyValueError::new_err(
-                    "Invalid model parameters (`std` must be strictly positive and both `std` and `mean` must be finite.).",
-                )
-            })?;
+        let (means, stds) = (means.iter()?, stds.iter()?);
+        let symbols = self.inner.decode_transactionally(|inner| {
+            inner
+                .try_decode_symbols(means.zip(stds).map(|(&mean, &std)| {
+                    if std > 0.0 && std.is_finite() && mean.is_finite() {
+                        Ok(quantizer.quantize(Gaussian::new(mean, std)))
+                    } else {
+                        Err(())
+                    }
+                }))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_| {
+                    pyo3::exceptions::PyValueError::new_err(
+                        "Invalid model parameters (`std` must be strictly positive and both `std` and `mean` must be finite.).",
+                    )
+                })
+        })?;
 
         Ok(PyArray1::from_vec(py, symbols))
     }
@@ -448,7 +611,7 @@ impl ChainCoder {
             None
         );
 
-        self.decode(py, model, PyTuple::new(py, [amt]))
+        self.decode(py, model, PyTuple::new(py, [amt]), None)
     }
 
     /// Creates a deep copy of the coder and returns it.
@@ -462,6 +625,19 @@ impl ChainCoder {
     }
 }
 
+impl ChainCoder {
+    fn check_whole(&self) -> PyResult<()> {
+        if self.inner.is_whole() {
+            Ok(())
+        } else {
+            Err(pyo3::exceptions::PyAssertionError::new_err(
+                "Fractional number of words in compressed data (`is_whole()` is `False`). Encode \
+                or decode more symbols until the coder holds whole words of compressed data.",
+            ))
+        }
+    }
+}
+
 impl From<EncoderFrontendError> for PyErr {
     fn from(err: EncoderFrontendError) -> Self {
         match err {
@@ -0,0 +1,82 @@
+use std::prelude::v1::*;
+
+use pyo3::{
+    prelude::*,
+    types::{PyBytes, PyTuple},
+    wrap_pyfunction,
+};
+
+use super::{
+    model::Model,
+    queue::{RangeDecoder, RangeEncoder},
+};
+
+pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(encode_to_bytes, module)?)?;
+    module.add_function(wrap_pyfunction!(decode_from_bytes, module)?)?;
+    Ok(())
+}
+
+/// Compresses `symbols` and returns the compressed data as a `bytes` object.
+///
+/// The arguments are the same as for the method
+/// [`RangeEncoder.encode`](queue.html#constriction.stream.queue.RangeEncoder.encode),
+/// i.e., you can either pass a concrete model or a model family followed by arrays of
+/// model parameters. The returned bytes are the words returned by `RangeEncoder.get_compressed`
+/// in little-endian byte order, i.e., they are the same as
+/// `encoder.get_compressed().astype('<u4').tobytes()` on all platforms.
+///
+/// ## Example
+///
+/// ```python
+/// model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+/// symbols = np.array([5, -3, 12, 0, 7, 4], dtype=np.int32)
+/// data = constriction.stream.easy.encode_to_bytes(symbols, model)
+/// decoded = constriction.stream.easy.decode_from_bytes(data, model, len(symbols))
+/// assert np.all(decoded == symbols)
+/// ```
+#[pyfunction(symbols, model, params = "*")]
+#[pyo3(text_signature = "(symbols, model, optional_model_params)")]
+fn encode_to_bytes<'py>(
+    py: Python<'py>,
+    symbols: &PyAny,
+    model: &Model,
+    params: &PyTuple,
+) -> PyResult<&'py PyBytes> {
+    let mut encoder = RangeEncoder::new();
+    encoder.encode(py, symbols, model, params, false, None)?;
+    let bytes = encoder
+        .into_compressed()
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect::<Vec<_>>();
+    Ok(PyBytes::new(py, &bytes))
+}
+
+/// Decompresses `data` that was generated by the function `encode_to_bytes`.
+///
+/// The remaining arguments are the same as for the method
+/// [`RangeDecoder.decode`](queue.html#constriction.stream.queue.RangeDecoder.decode),
+/// i.e., either a concrete model followed by the number of symbols to decode, or a model
+/// family followed by arrays of model parameters, plus an optional keyword argument `dtype`.
+/// Raises a `ValueError` if the length of `data` is not a multiple of four bytes.
+#[pyfunction(data, model, params = "*", dtype = "None")]
+#[pyo3(text_signature = "(data, model, optional_amt_or_model_params, dtype=None)")]
+fn decode_from_bytes(
+    py: Python<'_>,
+    data: &[u8],
+    model: &Model,
+    params: &PyTuple,
+    dtype: Option<PyObject>,
+) -> PyResult<PyObject> {
+    if data.len() % 4 != 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "The length of the compressed data must be a multiple of four bytes.",
+        ));
+    }
+    let compressed = data
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect();
+    RangeDecoder::from_vec(compressed).decode(py, model, params, dtype, None)
+}
@@ -1,22 +1,257 @@
 mod chain;
+mod easy;
 mod model;
 mod queue;
 mod stack;
 
-use pyo3::{prelude::*, wrap_pymodule};
+use ndarray::{iter::Iter, ArrayView1, Ix1};
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::{
+    exceptions::{PyOverflowError, PyTypeError, PyValueError},
+    prelude::*,
+    types::{PyDict, PyList, PyTuple},
+    wrap_pyfunction, wrap_pymodule,
+};
 
 use std::prelude::v1::*;
 
-use crate::{stream::TryCodingError, CoderError, DefaultEncoderFrontendError};
+use self::model::internals::{DefaultEntropyModel, EncoderDecoderModel};
+
+use crate::{
+    stream::{
+        model::DecoderModel,
+        strict::{StrictDecode, StrictDecodeError},
+        Encode, InvalidSymbolsError, TryCodingError,
+    },
+    BitArray, CoderError, DefaultEncoderFrontendError,
+};
 
 pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
     module.add_wrapped(wrap_pymodule!(model))?;
     module.add_wrapped(wrap_pymodule!(queue))?;
     module.add_wrapped(wrap_pymodule!(stack))?;
     module.add_wrapped(wrap_pymodule!(chain))?;
+    module.add_wrapped(wrap_pymodule!(easy))?;
+    module.add_function(wrap_pyfunction!(transcode, module)?)?;
+    module.add_function(wrap_pyfunction!(verify_checksum, module)?)?;
     Ok(())
 }
 
+/// Number of bits with which all entropy models in the Python API represent probabilities.
+pub(crate) const PRECISION: u32 = 24;
+
+/// Returns the `config` attribute of a coder with the given `Word` and `State` types.
+pub(crate) fn coder_config<Word: BitArray, State: BitArray>(py: Python<'_>) -> PyResult<&PyDict> {
+    let dict = PyDict::new(py);
+    dict.set_item("word_size", Word::BITS)?;
+    dict.set_item("state_size", State::BITS)?;
+    dict.set_item("precision", PRECISION)?;
+    Ok(dict)
+}
+
+/// Decodes symbols from `decoder` and re-encodes them onto `encoder` in a single pass.
+///
+/// Use this function to migrate compressed data from one coder or entropy model to another
+/// one without holding the decoded message in memory. The `decoder` may be either a
+/// [`stack.AnsCoder`](stack.html#constriction.stream.stack.AnsCoder) or a
+/// [`queue.RangeDecoder`](queue.html#constriction.stream.queue.RangeDecoder), and the
+/// `encoder` may be either an `AnsCoder` or a
+/// [`queue.RangeEncoder`](queue.html#constriction.stream.queue.RangeEncoder) (but not the
+/// same object as `decoder`).
+///
+/// The arguments `model` and `optional_amt_or_model_params` are the same as for the
+/// `decode` method of `decoder`, i.e., `transcode(decoder, encoder, model)` transcodes a
+/// single symbol, `transcode(decoder, encoder, model, amt)` transcodes `amt` i.i.d.
+/// symbols, and `transcode(decoder, encoder, model_family, params1, params2, ...)`
+/// transcodes one symbol per entry of the model parameters. By default, symbols are
+/// re-encoded with the same entropy model with which they were decoded. To use a different
+/// model for re-encoding, pass a *concrete* model as the keyword argument `target_model`.
+///
+/// Returns the number of transcoded symbols. If decoding or re-encoding any symbol fails,
+/// an exception is raised and both `decoder` and `encoder` are left unchanged.
+///
+/// Symbols are encoded in the order in which they are decoded. Thus, if `encoder` is an
+/// `AnsCoder`, which is a stack, then the transcoded symbols will later be decoded from it
+/// in reverse order.
+///
+/// ## Example
+///
+/// ```python
+/// model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+/// symbols = np.array([5, -3, 12, 0, 7, 4], dtype=np.int32)
+/// ans = constriction.stream.stack.AnsCoder()
+/// ans.encode_reverse(symbols, model)
+///
+/// # Migrate the data to a range coder that uses a different model.
+/// target_model = constriction.stream.model.QuantizedLaplace(-100, 100, 3.2, 8.0)
+/// encoder = constriction.stream.queue.RangeEncoder()
+/// count = constriction.stream.transcode(
+///     ans, encoder, model, len(symbols), target_model=target_model)
+/// assert count == len(symbols)
+/// assert ans.is_empty()
+///
+/// decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+/// assert np.all(decoder.decode(target_model, len(symbols)) == symbols)
+/// ```
+#[pyfunction(decoder, encoder, model, params = "*", target_model = "None")]
+#[pyo3(
+    text_signature = "(decoder, encoder, model, optional_amt_or_model_params, target_model=None)"
+)]
+fn transcode(
+    py: Python<'_>,
+    decoder: &PyAny,
+    encoder: &PyAny,
+    model: &model::Model,
+    params: &PyTuple,
+    target_model: Option<PyRef<'_, model::Model>>,
+) -> PyResult<usize> {
+    if core::ptr::eq(decoder, encoder) {
+        return Err(PyValueError::new_err(
+            "The `decoder` and the `encoder` must be different objects.",
+        ));
+    }
+    let target_model = target_model.as_deref();
+
+    if let Ok(decoder) = decoder.downcast::<PyCell<stack::AnsCoder>>() {
+        let mut decoder = decoder.try_borrow_mut()?;
+        let strict = decoder.strict;
+        decoder.decode_transactionally(|decoder| {
+            transcode_onto(py, decoder, strict, encoder, model, params, target_model)
+        })
+    } else if let Ok(decoder) = decoder.downcast::<PyCell<queue::RangeDecoder>>() {
+        let mut decoder = decoder.try_borrow_mut()?;
+        let strict = decoder.strict;
+        decoder.decode_transactionally(|decoder| {
+            transcode_onto(py, decoder, strict, encoder, model, params, target_model)
+        })
+    } else {
+        Err(PyTypeError::new_err(
+            "The `decoder` must be an `AnsCoder` or a `RangeDecoder`.",
+        ))
+    }
+}
+
+/// Verifies and strips off a CRC-32 checksum that was appended to compressed data by the
+/// method `seal_with_checksum` of a `RangeEncoder` or an `AnsCoder`.
+///
+/// Returns the compressed data without the checksum (as a rank-1 numpy array of
+/// `dtype=np.uint32`), which you can pass to the constructor of a `RangeDecoder` or an
+/// `AnsCoder`, respectively. Raises a `ValueError` if the checksum doesn't match, i.e., if
+/// the compressed data was corrupted (e.g., during transmission or storage).
+///
+/// ## Example
+///
+/// ```python
+/// model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+/// symbols = np.array([5, -3, 12, 0, 7, 4], dtype=np.int32)
+/// encoder = constriction.stream.queue.RangeEncoder()
+/// encoder.encode(symbols, model)
+/// sealed = encoder.seal_with_checksum()
+///
+/// compressed = constriction.stream.verify_checksum(sealed)
+/// decoder = constriction.stream.queue.RangeDecoder(compressed)
+/// assert np.all(decoder.decode(model, len(symbols)) == symbols)
+///
+/// sealed[0] ^= 1 << 20  # Simulates a flipped bit.
+/// try:
+///     constriction.stream.verify_checksum(sealed)
+///     assert False
+/// except ValueError:
+///     pass  # Corruption detected.
+/// ```
+#[pyfunction]
+#[pyo3(text_signature = "(compressed)")]
+fn verify_checksum<'p>(
+    py: Python<'p>,
+    compressed: PyReadonlyArray1<'_, u32>,
+) -> PyResult<&'p PyArray1<u32>> {
+    let compressed = crate::stream::checksum::verify_checksum_slice(compressed.as_slice()?)
+        .map_err(|err| PyValueError::new_err(err.to_string()))?;
+    Ok(PyArray1::from_slice(py, compressed))
+}
+
+/// Dispatches [`transcode`] on the type of the `encoder`.
+fn transcode_onto<Decoder>(
+    py: Python<'_>,
+    decoder: &mut Decoder,
+    strict: bool,
+    encoder: &PyAny,
+    model: &model::Model,
+    params: &PyTuple,
+    target_model: Option<&model::Model>,
+) -> PyResult<usize>
+where
+    Decoder: StrictDecode<24, Word = u32>,
+    CoderError<Decoder::FrontendError, Decoder::BackendError>: Into<PyErr>,
+{
+    if let Ok(encoder) = encoder.downcast::<PyCell<stack::AnsCoder>>() {
+        encoder.try_borrow_mut()?.encode_transactionally(|encoder| {
+            transcode_symbols(py, decoder, strict, encoder, model, params, target_model)
+        })
+    } else if let Ok(encoder) = encoder.downcast::<PyCell<queue::RangeEncoder>>() {
+        encoder.try_borrow_mut()?.encode_transactionally(|encoder| {
+            transcode_symbols(py, decoder, strict, encoder, model, params, target_model)
+        })
+    } else {
+        Err(PyTypeError::new_err(
+            "The `encoder` must be an `AnsCoder` or a `RangeEncoder`.",
+        ))
+    }
+}
+
+/// Implements [`transcode`] for concrete decoder and encoder types.
+fn transcode_symbols<Decoder, Encoder>(
+    py: Python<'_>,
+    decoder: &mut Decoder,
+    strict: bool,
+    encoder: &mut Encoder,
+    model: &model::Model,
+    params: &PyTuple,
+    target_model: Option<&model::Model>,
+) -> PyResult<usize>
+where
+    Decoder: StrictDecode<24, Word = u32>,
+    CoderError<Decoder::FrontendError, Decoder::BackendError>: Into<PyErr>,
+    Encoder: Encode<24, Word = u32>,
+    Encoder::FrontendError: Into<PyErr>,
+    Encoder::BackendError: Into<PyErr>,
+{
+    let mut count = 0;
+    let mut run = |target: Option<&dyn DefaultEntropyModel>| {
+        let mut transcode_symbol = |model: &dyn DefaultEntropyModel| -> PyResult<()> {
+            let symbol = decode_symbol(decoder, EncoderDecoderModel(model), strict)?;
+            encoder
+                .encode_symbol(symbol, EncoderDecoderModel(target.unwrap_or(model)))
+                .map_err(PyErr::from)?;
+            count += 1;
+            Ok(())
+        };
+
+        if params.is_empty() {
+            model.0.as_parameterized(py, &mut transcode_symbol)
+        } else if let (1, Ok(amt)) = (params.len(), usize::extract(params.as_slice()[0])) {
+            model.0.as_parameterized(py, &mut |model| {
+                for _ in 0..amt {
+                    transcode_symbol(model)?;
+                }
+                Ok(())
+            })
+        } else {
+            model
+                .0
+                .parameterize(py, params, false, &mut transcode_symbol)
+        }
+    };
+
+    match target_model {
+        None => run(None)?,
+        Some(target_model) => target_model
+            .0
+            .as_parameterized(py, &mut |target| run(Some(target)))?,
+    }
+    Ok(count)
+}
+
 /// Entropy models and model families for use with any of the stream codes from the sister
 /// modules [`stack`](stack.html), [`queue`](queue.html), and [`chain`](chain.html).
 ///
@@ -55,6 +290,13 @@ pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
 ///   efficient than constructing a new concrete model for each symbol and looping over the
 ///   symbols in Python).
 ///
+/// Wherever the documentation of a model asks for arrays of real-valued model parameters
+/// (or probabilities) with `dtype=np.float64`, you may also provide them with
+/// `dtype=np.float32` (e.g., if they are the output of a neural network). Such arrays are
+/// read directly, i.e., they are not first converted into a copy with `dtype=np.float64`.
+/// The resulting entropy models are identical to the ones you'd get by passing the same
+/// values as `np.float64`.
+///
 /// ## Examples
 ///
 /// Constructing and using a *concrete* [`QuantizedGaussian`](#constriction.stream.model.QuantizedGaussian)
@@ -411,11 +653,47 @@ fn stack(py: Python<'_>, module: &PyModule) -> PyResult<()> {
 ///    `.get_data(unseal=True)` (if you set `seal=False` in encoder step 2 above, then set
 ///    `unseal=False` now). The method returns two arrays, which you may concatenate in
 ///    order.
+///
+/// If you set `seal=True` in encoder step 2, you can equivalently write it as
+/// `ChainCoder.from_binary(data)`, and decoder step 4 as `.get_binary()`. Both `get_data` and
+/// `get_binary` raise an `AssertionError` if the coder doesn't currently hold a whole
+/// number of words, which you can check upfront with `.is_whole()`.
+///
+/// Note that all entropy models in the Python API use a fixed point precision of 24 bits.
+/// Therefore, there's no need to change the precision of a `ChainCoder` between symbols (as
+/// provided by the method `change_precision` in the Rust API), and the Python API doesn't
+/// expose this functionality.
 #[pymodule]
 fn chain(py: Python<'_>, module: &PyModule) -> PyResult<()> {
     chain::init_module(py, module)
 }
 
+/// The simplest way to compress a sequence of symbols into `bytes` and back.
+///
+/// This module provides just two functions, `encode_to_bytes` and `decode_from_bytes`, which
+/// wrap a [`RangeEncoder`](queue.html#constriction.stream.queue.RangeEncoder) and a
+/// [`RangeDecoder`](queue.html#constriction.stream.queue.RangeDecoder), respectively. The
+/// compressed data is a `bytes` object with a platform independent byte order, so you can
+/// write it directly to a file or send it over the network. The compressed data is
+/// compatible with the functions of the same names in the Rust API.
+///
+/// ## Example
+///
+/// ```python
+/// model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+/// symbols = np.array([5, -3, 12, 0, 7, 4], dtype=np.int32)
+///
+/// data = constriction.stream.easy.encode_to_bytes(symbols, model)
+/// print(data) # (prints 4 bytes)
+///
+/// decoded = constriction.stream.easy.decode_from_bytes(data, model, len(symbols))
+/// assert np.all(decoded == symbols)
+/// ```
+#[pymodule]
+fn easy(py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    easy::init_module(py, module)
+}
+
 impl<CodingError: Into<PyErr>, ModelError> From<TryCodingError<CodingError, ModelError>> for PyErr {
     fn from(err: TryCodingError<CodingError, ModelError>) -> Self {
         match err {
@@ -438,6 +716,42 @@ impl<FrontendError: Into<PyErr>, BackendError: Into<PyErr>>
     }
 }
 
+impl<FrontendError: Into<PyErr> + core::fmt::Display> From<StrictDecodeError<FrontendError>>
+    for PyErr
+{
+    fn from(err: StrictDecodeError<FrontendError>) -> Self {
+        match err {
+            StrictDecodeError::OutOfCompressedData => {
+                pyo3::exceptions::PyAssertionError::new_err(err.to_string())
+            }
+            StrictDecodeError::Inner(err) => err.into(),
+        }
+    }
+}
+
+/// Decodes a single symbol. If `strict` is set, raises an `AssertionError` instead of
+/// decoding past the end of the compressed data, leaving `decoder` unchanged.
+pub(crate) fn decode_symbol<Decoder, M>(
+    decoder: &mut Decoder,
+    model: M,
+    strict: bool,
+) -> PyResult<i32>
+where
+    Decoder: StrictDecode<24, Word = u32>,
+    M: DecoderModel<24, Symbol = i32, Probability = u32>,
+    CoderError<Decoder::FrontendError, Decoder::BackendError>: Into<PyErr>,
+{
+    if strict {
+        match decoder.decode_symbol_within_data(model) {
+            Ok(Some(symbol)) => Ok(symbol),
+            Ok(None) => Err(StrictDecodeError::<PyErr>::OutOfCompressedData.into()),
+            Err(err) => Err(err.into()),
+        }
+    } else {
+        decoder.decode_symbol(model).map_err(Into::into)
+    }
+}
+
 impl From<DefaultEncoderFrontendError> for PyErr {
     fn from(err: DefaultEncoderFrontendError) -> Self {
         match err {
@@ -447,3 +761,234 @@ impl From<DefaultEncoderFrontendError> for PyErr {
         }
     }
 }
+
+/// Symbols passed to an `encode` method, either as a numpy array (borrowed without copying)
+/// or as a Python list or tuple of ints (converted into a `Vec`).
+///
+/// Numpy arrays may have dtype `int32`, `int16`, or `uint8`. Narrower dtypes are widened to
+/// `i32` on the fly while iterating, so callers never have to allocate a widened copy.
+pub(crate) enum Symbols<'py> {
+    Array(PyReadonlyArray1<'py, i32>),
+    Int16Array(PyReadonlyArray1<'py, i16>),
+    Uint8Array(PyReadonlyArray1<'py, u8>),
+    Sequence(Vec<i32>),
+}
+
+impl<'py> Symbols<'py> {
+    pub(crate) fn extract(py: Python<'py>, symbols: &'py PyAny) -> PyResult<Self> {
+        let items = if let Ok(list) = symbols.downcast::<PyList>() {
+            list.iter().collect::<Vec<_>>()
+        } else if let Ok(tuple) = symbols.downcast::<PyTuple>() {
+            tuple.as_slice().to_vec()
+        } else {
+            return match symbols.extract() {
+                Ok(array) => Ok(Self::Array(array)),
+                Err(err) => {
+                    if let Ok(array) = symbols.extract() {
+                        Ok(Self::Int16Array(array))
+                    } else if let Ok(array) = symbols.extract() {
+                        Ok(Self::Uint8Array(array))
+                    } else {
+                        // The error message from numpy is pretty user friendly.
+                        Err(err)
+                    }
+                }
+            };
+        };
+
+        let symbols = items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| {
+                item.extract::<i32>().map_err(|err| {
+                    if err.is_instance::<PyOverflowError>(py) {
+                        PyOverflowError::new_err(format!(
+                            "Symbol {} at index {} does not fit into a 32-bit signed integer.",
+                            item, index
+                        ))
+                    } else {
+                        err
+                    }
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(Self::Sequence(symbols))
+    }
+
+    /// Iterates over the symbols, widening them to `i32` if necessary.
+    ///
+    /// Iterates over array views rather than slices, so that this also works for numpy
+    /// arrays that are not contiguous in memory (e.g., a column `matrix[:, 0]` of a larger
+    /// matrix) without copying them first.
+    pub(crate) fn iter(&self) -> SymbolsIter<'_> {
+        match self {
+            Self::Array(array) => SymbolsIter::Int32(array.as_array().into_iter()),
+            Self::Int16Array(array) => SymbolsIter::Int16(array.as_array().into_iter()),
+            Self::Uint8Array(array) => SymbolsIter::Uint8(array.as_array().into_iter()),
+            Self::Sequence(vec) => SymbolsIter::Int32(ArrayView1::from(vec.as_slice()).into_iter()),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) enum SymbolsIter<'a> {
+    Int32(Iter<'a, i32, Ix1>),
+    Int16(Iter<'a, i16, Ix1>),
+    Uint8(Iter<'a, u8, Ix1>),
+}
+
+impl Iterator for SymbolsIter<'_> {
+    type Item = i32;
+
+    #[inline]
+    fn next(&mut self) -> Option<i32> {
+        match self {
+            Self::Int32(iter) => iter.next().copied(),
+            Self::Int16(iter) => iter.next().map(|&symbol| symbol.into()),
+            Self::Uint8(iter) => iter.next().map(|&symbol| symbol.into()),
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.len();
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for SymbolsIter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<i32> {
+        match self {
+            Self::Int32(iter) => iter.next_back().copied(),
+            Self::Int16(iter) => iter.next_back().map(|&symbol| symbol.into()),
+            Self::Uint8(iter) => iter.next_back().map(|&symbol| symbol.into()),
+        }
+    }
+}
+
+impl ExactSizeIterator for SymbolsIter<'_> {
+    #[inline]
+    fn len(&self) -> usize {
+        match self {
+            Self::Int32(iter) => iter.len(),
+            Self::Int16(iter) => iter.len(),
+            Self::Uint8(iter) => iter.len(),
+        }
+    }
+}
+
+/// Buffer for symbols returned from a `decode` method, stored with the numpy dtype requested
+/// by the caller (`int32` by default; `int16` and `uint8` save memory for small alphabets).
+pub(crate) enum DecodedSymbols {
+    Int32(Vec<i32>),
+    Int16(Vec<i16>),
+    Uint8(Vec<u8>),
+}
+
+impl DecodedSymbols {
+    /// Parses the optional `dtype` argument of a `decode` method, which may be anything that
+    /// `numpy.dtype` understands (e.g., `np.uint8` or the string `"uint8"`).
+    pub(crate) fn with_capacity(
+        py: Python<'_>,
+        dtype: Option<PyObject>,
+        capacity: usize,
+    ) -> PyResult<Self> {
+        let dtype = match dtype {
+            None => return Ok(Self::Int32(Vec::with_capacity(capacity))),
+            Some(dtype) => py
+                .import("numpy")?
+                .getattr("dtype")?
+                .call1((dtype,))?
+                .getattr("name")?
+                .extract::<String>()?,
+        };
+
+        match dtype.as_str() {
+            "int32" => Ok(Self::Int32(Vec::with_capacity(capacity))),
+            "int16" => Ok(Self::Int16(Vec::with_capacity(capacity))),
+            "uint8" => Ok(Self::Uint8(Vec::with_capacity(capacity))),
+            _ => Err(PyValueError::new_err(format!(
+                "Unsupported dtype `{}` for decoded symbols. Supported dtypes are `int32`, \
+                `int16`, and `uint8`.",
+                dtype
+            ))),
+        }
+    }
+
+    /// Appends `symbol`, or raises an `OverflowError` if it doesn't fit into the dtype.
+    #[inline]
+    pub(crate) fn push(&mut self, symbol: i32) -> PyResult<()> {
+        use core::convert::TryFrom;
+
+        fn narrow<T: TryFrom<i32>>(symbol: i32, dtype: &str) -> PyResult<T> {
+            T::try_from(symbol).map_err(|_| {
+                PyOverflowError::new_err(format!(
+                    "Decoded symbol {} does not fit into dtype `{}`.",
+                    symbol, dtype
+                ))
+            })
+        }
+
+        match self {
+            Self::Int32(vec) => vec.push(symbol),
+            Self::Int16(vec) => vec.push(narrow(symbol, "int16")?),
+            Self::Uint8(vec) => vec.push(narrow(symbol, "uint8")?),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn into_py(self, py: Python<'_>) -> PyObject {
+        match self {
+            Self::Int32(vec) => PyArray1::from_vec(py, vec).to_object(py),
+            Self::Int16(vec) => PyArray1::from_vec(py, vec).to_object(py),
+            Self::Uint8(vec) => PyArray1::from_vec(py, vec).to_object(py),
+        }
+    }
+}
+
+/// Checks that each of the `symbols` lies in the support of its entropy model, raising a
+/// `KeyError` that lists the offending indices otherwise.
+///
+/// Implements the `validate=True` option of the `encode` methods, which calls this function
+/// before encoding anything. Symbols are checked in their original order (also for stack
+/// coders) so that the reported indices refer to positions in the `symbols` array.
+pub(crate) fn validate_symbols(
+    py: Python<'_>,
+    symbols: SymbolsIter<'_>,
+    model: &model::Model,
+    params: &PyTuple,
+) -> PyResult<()> {
+    let mut invalid = InvalidSymbolsError::new();
+
+    if params.is_empty() {
+        model.0.as_parameterized(py, &mut |model| {
+            for (index, symbol) in symbols.clone().enumerate() {
+                if model.left_cumulative_and_probability(symbol).is_none() {
+                    invalid.record(index);
+                }
+            }
+            Ok(())
+        })?;
+    } else {
+        if symbols.len() != model.0.len(&params[0])? {
+            return Err(pyo3::exceptions::PyAttributeError::new_err(
+                "`symbols` argument has wrong length.",
+            ));
+        }
+        let mut symbols = symbols.enumerate();
+        model.0.parameterize(py, params, false, &mut |model| {
+            let (index, symbol) = symbols.next().ok_or_else(|| {
+                pyo3::exceptions::PyAttributeError::new_err("`symbols` argument has wrong length.")
+            })?;
+            if model.left_cumulative_and_probability(symbol).is_none() {
+                invalid.record(index);
+            }
+            Ok(())
+        })?;
+    }
+
+    invalid
+        .into_result()
+        .map_err(|err| pyo3::exceptions::PyKeyError::new_err(err.to_string()))
+}
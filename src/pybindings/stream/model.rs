@@ -3,11 +3,29 @@ pub mod internals;
 use std::prelude::v1::*;
 
 use alloc::sync::Arc;
-use numpy::PyReadonlyArray1;
-use pyo3::prelude::*;
+use numpy::{PyArray1, PyReadonlyArray1, PyReadonlyArray2};
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyTuple, PyType},
+    PyClassInitializer,
+};
 
+use super::Symbols;
+use crate::stream::markov::DefaultMarkovModel;
 use crate::stream::model::{
-    DefaultContiguousCategoricalEntropyModel, LeakyQuantizer, UniformModel,
+    audio::{DefaultAdaptiveLaplace, LongTailLaplace},
+    bernoulli::DefaultBernoulli,
+    binomial::DefaultBinomial,
+    cauchy::Cauchy,
+    dyn_precision::DynPrecisionModel,
+    empirical::DefaultEmpirical,
+    geometric::{DefaultGeometric, DefaultTwoSidedGeometric},
+    mixture::{GaussianMixture, LogisticMixture},
+    negative_binomial::DefaultNegativeBinomial,
+    poisson::DefaultPoisson,
+    zipf::DefaultZipf,
+    DefaultContiguousCategoricalEntropyModel, DefaultUniformRangeModel, LeakyQuantizer,
+    QuantizationReport, RoundingPolicy, UniformModel,
 };
 
 pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
@@ -18,7 +36,22 @@ pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
     module.add_class::<Uniform>()?;
     module.add_class::<QuantizedGaussian>()?;
     module.add_class::<QuantizedLaplace>()?;
+    module.add_class::<QuantizedLogistic>()?;
+    module.add_class::<QuantizedCauchy>()?;
+    module.add_class::<QuantizedGaussianMixture>()?;
+    module.add_class::<QuantizedLogisticMixture>()?;
+    module.add_class::<QuantizedLongTailLaplace>()?;
+    module.add_class::<AdaptiveLaplace>()?;
+    module.add_class::<MarkovModel>()?;
     module.add_class::<Binomial>()?;
+    module.add_class::<ExactBinomial>()?;
+    module.add_class::<Poisson>()?;
+    module.add_class::<NegativeBinomial>()?;
+    module.add_class::<Geometric>()?;
+    module.add_class::<TwoSidedGeometric>()?;
+    module.add_class::<Zipf>()?;
+    module.add_class::<Empirical>()?;
+    module.add_class::<DynPrecisionCategorical>()?;
     module.add_class::<Bernoulli>()?;
     Ok(())
 }
@@ -31,6 +64,209 @@ pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
 #[allow(missing_debug_implementations)]
 pub struct Model(pub Arc<dyn internals::Model>);
 
+#[pymethods]
+impl Model {
+    /// Returns the entropy of the model in bits.
+    ///
+    /// The entropy is the theoretical lower bound on the expected bit rate when encoding
+    /// symbols drawn from the model with the same model, so you can use it to estimate bit
+    /// rates without running an entropy coder. The entropy is calculated from the quantized
+    /// fixed point probabilities that the entropy coders actually use (i.e., for quantized
+    /// continuous distributions, it is *not* the differential entropy of the underlying
+    /// distribution).
+    ///
+    /// For a concrete model, this method returns a float. For a model family, provide the
+    /// model parameters as numpy arrays (as you would for the entropy coders' `encode`
+    /// method), and the method returns a rank-1 numpy array with the entropy of each
+    /// model.
+    ///
+    /// ```python
+    /// model = constriction.stream.model.Categorical(
+    ///     np.array([0.5, 0.25, 0.25], dtype=np.float64))
+    /// assert model.entropy() == 1.5
+    ///
+    /// model_family = constriction.stream.model.QuantizedGaussian(-100, 100)
+    /// means = np.array([0.0, 10.0], dtype=np.float64)
+    /// stds = np.array([1.0, 10.0], dtype=np.float64)
+    /// entropies = model_family.entropy(means, stds)
+    /// assert entropies.shape == (2,) and entropies[0] < entropies[1]
+    /// ```
+    #[pyo3(text_signature = "(self, optional_model_params)")]
+    #[args(params = "*")]
+    pub fn entropy(&self, py: Python<'_>, params: &PyTuple) -> PyResult<PyObject> {
+        if params.is_empty() {
+            let mut entropy = 0.0;
+            self.0.as_parameterized(py, &mut |model| {
+                entropy = internals::cross_entropy_base2(model, model);
+                Ok(())
+            })?;
+            Ok(entropy.into_py(py))
+        } else {
+            let mut entropies = Vec::with_capacity(self.0.len(&params[0])?);
+            self.0.parameterize(py, params, false, &mut |model| {
+                entropies.push(internals::cross_entropy_base2(model, model));
+                Ok(())
+            })?;
+            Ok(PyArray1::from_vec(py, entropies).into_py(py))
+        }
+    }
+
+    /// Returns the information content of symbols in bits, i.e., the number of bits that
+    /// encoding them would add to the compressed data.
+    ///
+    /// The bit cost of a symbol is the negative base-2 logarithm of its probability, using
+    /// the same quantized fixed point probabilities that the entropy coders use. This lets
+    /// you query the exact cost that an entropy coder would charge for a symbol (up to the
+    /// small constant overhead of the coder) without encoding it, e.g., for rate-distortion
+    /// optimization. Symbols outside the support of the model have infinite bit cost.
+    ///
+    /// The argument `symbols` can be a scalar or a rank-1 numpy array (or list) of
+    /// symbols, and the method returns a float or a rank-1 numpy array, respectively. As
+    /// with the entropy coders' `encode` method, you can either use a concrete model for
+    /// all symbols, or a model family together with one set of model parameters per
+    /// symbol.
+    ///
+    /// ```python
+    /// model = constriction.stream.model.Categorical(
+    ///     np.array([0.5, 0.25, 0.25], dtype=np.float64))
+    /// assert model.bit_cost(0) == 1.0
+    /// assert np.all(model.bit_cost(np.array([1, 0, 2], dtype=np.int32)) == [2.0, 1.0, 2.0])
+    ///
+    /// model_family = constriction.stream.model.QuantizedGaussian(-100, 100)
+    /// symbols = np.array([0, 0], dtype=np.int32)
+    /// means = np.array([0.0, 10.0], dtype=np.float64)
+    /// stds = np.array([5.0, 5.0], dtype=np.float64)
+    /// costs = model_family.bit_cost(symbols, means, stds)
+    /// assert costs[0] < costs[1]
+    /// ```
+    #[pyo3(text_signature = "(self, symbols, optional_model_params)")]
+    #[args(symbols, params = "*")]
+    pub fn bit_cost(
+        &self,
+        py: Python<'_>,
+        symbols: &PyAny,
+        params: &PyTuple,
+    ) -> PyResult<PyObject> {
+        if let Ok(symbol) = symbols.extract::<i32>() {
+            if !params.is_empty() {
+                return Err(pyo3::exceptions::PyAttributeError::new_err(
+                    "To query the bit cost of a single symbol, use a concrete model, i.e., pass \
+                    the model parameters directly to the constructor of the model.",
+                ));
+            }
+            let mut bit_cost = 0.0;
+            self.0.as_parameterized(py, &mut |model| {
+                bit_cost = internals::bit_cost(model, symbol);
+                Ok(())
+            })?;
+            return Ok(bit_cost.into_py(py));
+        }
+
+        let symbols = Symbols::extract(py, symbols)?;
+        let symbols = symbols.iter();
+        let mut bit_costs = Vec::with_capacity(symbols.len());
+        if params.is_empty() {
+            self.0.as_parameterized(py, &mut |model| {
+                bit_costs.extend(
+                    symbols
+                        .clone()
+                        .map(|symbol| internals::bit_cost(model, symbol)),
+                );
+                Ok(())
+            })?;
+        } else {
+            if symbols.len() != self.0.len(&params[0])? {
+                return Err(pyo3::exceptions::PyAttributeError::new_err(
+                    "`symbols` argument has wrong length.",
+                ));
+            }
+            let mut symbols = symbols;
+            self.0.parameterize(py, params, false, &mut |model| {
+                let symbol = symbols.next().ok_or_else(|| {
+                    pyo3::exceptions::PyAttributeError::new_err(
+                        "`symbols` argument has wrong length.",
+                    )
+                })?;
+                bit_costs.push(internals::bit_cost(model, symbol));
+                Ok(())
+            })?;
+        }
+        Ok(PyArray1::from_vec(py, bit_costs).into_py(py))
+    }
+
+    /// Returns the cross entropy between this model and `other` in bits.
+    ///
+    /// The cross entropy is the expected bit rate (up to the small constant overhead of the
+    /// entropy coder) when encoding symbols that are distributed according to this model
+    /// with the model `other`. Both models must be concrete models, i.e., you have to
+    /// provide all model parameters to their constructors. Returns infinity if this model
+    /// has a symbol in its support that `other` can't encode.
+    ///
+    /// ```python
+    /// data_model = constriction.stream.model.Categorical(
+    ///     np.array([0.5, 0.25, 0.25], dtype=np.float64))
+    /// coding_model = constriction.stream.model.Categorical(
+    ///     np.array([0.25, 0.25, 0.5], dtype=np.float64))
+    /// assert data_model.cross_entropy(coding_model) == 1.75
+    /// ```
+    #[pyo3(text_signature = "(self, other)")]
+    pub fn cross_entropy(&self, py: Python<'_>, other: &Model) -> PyResult<f64> {
+        self.with_concrete_pair(py, other, internals::cross_entropy_base2)
+    }
+
+    /// Returns the Kullback-Leibler divergence from `other` to this model in bits.
+    ///
+    /// The KL divergence is the expected number of bits per symbol that you waste if you
+    /// encode symbols that are distributed according to this model with the model `other`
+    /// instead, i.e., it is the difference between the [cross
+    /// entropy](#constriction.stream.model.Model.cross_entropy) and the
+    /// [entropy](#constriction.stream.model.Model.entropy). Both models must be concrete
+    /// models.
+    ///
+    /// ```python
+    /// data_model = constriction.stream.model.Categorical(
+    ///     np.array([0.5, 0.25, 0.25], dtype=np.float64))
+    /// coding_model = constriction.stream.model.Categorical(
+    ///     np.array([0.25, 0.25, 0.5], dtype=np.float64))
+    /// assert data_model.kl_divergence(coding_model) == 0.25
+    /// assert data_model.kl_divergence(data_model) == 0.0
+    /// ```
+    #[pyo3(text_signature = "(self, other)")]
+    pub fn kl_divergence(&self, py: Python<'_>, other: &Model) -> PyResult<f64> {
+        self.with_concrete_pair(py, other, internals::kl_divergence_base2)
+    }
+}
+
+impl Model {
+    fn with_concrete_pair(
+        &self,
+        py: Python<'_>,
+        other: &Model,
+        f: fn(&dyn internals::DefaultEntropyModel, &dyn internals::DefaultEntropyModel) -> f64,
+    ) -> PyResult<f64> {
+        let mut result = 0.0;
+        self.0.as_parameterized(py, &mut |model| {
+            other.0.as_parameterized(py, &mut |other_model| {
+                result = f(model, other_model);
+                Ok(())
+            })
+        })?;
+        Ok(result)
+    }
+
+    /// Returns the model presented at `precision` bits, or a copy of `self` if `precision`
+    /// is `None`.
+    ///
+    /// Only supported for `DynPrecisionCategorical` models; used for the `precision`
+    /// argument of the entropy coders' `encode` and `decode` methods.
+    pub(crate) fn at_precision(&self, precision: Option<usize>) -> PyResult<Model> {
+        match precision {
+            None => Ok(Model(Arc::clone(&self.0))),
+            Some(precision) => Ok(Model(self.0.at_precision(precision)?)),
+        }
+    }
+}
+
 /// Wrapper for a model (or model family) defined via custom callback functions
 ///
 /// A `CustomModel` provides maximum flexibility for defining entropy models. It
@@ -146,14 +382,14 @@ impl CustomModel {
         approximate_inverse_cdf: PyObject,
         min_symbol_inclusive: i32,
         max_symbol_inclusive: i32,
-    ) -> (Self, Model) {
+    ) -> PyResult<(Self, Model)> {
         let model = internals::UnspecializedPythonModel::new(
             cdf,
             approximate_inverse_cdf,
             min_symbol_inclusive,
             max_symbol_inclusive,
-        );
-        (Self, Model(Arc::new(model)))
+        )?;
+        Ok((Self, Model(Arc::new(model))))
     }
 }
 
@@ -233,27 +469,102 @@ impl CustomModel {
 /// - **min_symbol_inclusive** and **max_symbol_inclusive** --- define the range of integer
 ///   symbols that you will be able to encode with this model, see "Guarantees And
 ///   Requirements" below.
+/// - **validate** --- whether to check the model's CDF upfront if `model` is a concrete
+///   (i.e., fully parameterized) scipy model, see "Validation" below. Defaults to `True`.
+///
+/// ## Validation
+///
+/// The guarantees listed in the documentation of
+/// [`CustomModel`](#constriction.stream.model.CustomModel) only hold if the CDF is
+/// nondecreasing and between 0.0 and 1.0. A CDF that violates these conditions (e.g., due
+/// to numerical issues far out in the tails) can silently corrupt the compressed data. The
+/// constructor therefore evaluates the CDF of a concrete scipy model on all points at which
+/// the entropy coder will evaluate it, rounds the results to the coder's precision, and
+/// raises a `ValueError` that lists all offending intervals if it finds any problems.
+///
+/// For a model *family*, the constructor cannot validate the CDF because model parameters
+/// are only known at encoding or decoding time. Call the method `validate` with a set of
+/// scalar model parameters instead, e.g., `model_family.validate(26.2, 4.3)` in the second
+/// example above.
 #[pyclass(extends=CustomModel)]
-#[pyo3(text_signature = "(scipy_model, min_symbol_inclusive, max_symbol_inclusive)")]
+#[pyo3(text_signature = "(scipy_model, min_symbol_inclusive, max_symbol_inclusive, validate=True)")]
 #[derive(Debug)]
-pub struct ScipyModel;
+pub struct ScipyModel {
+    cdf: PyObject,
+    min_symbol_inclusive: i32,
+    max_symbol_inclusive: i32,
+}
 
 #[pymethods]
 impl ScipyModel {
     #[new]
+    #[args(model, min_symbol_inclusive, max_symbol_inclusive, validate = "true")]
     pub fn new(
         py: Python<'_>,
         model: PyObject,
         min_symbol_inclusive: i32,
         max_symbol_inclusive: i32,
+        validate: bool,
     ) -> PyResult<PyClassInitializer<Self>> {
+        let cdf = model.getattr(py, "cdf")?;
+        let scipy_model = ScipyModel {
+            cdf: cdf.clone_ref(py),
+            min_symbol_inclusive,
+            max_symbol_inclusive,
+        };
+
+        let stats = py.import("scipy.stats")?;
+        let is_model_family = stats
+            .getattr("rv_continuous")?
+            .downcast::<PyType>()?
+            .is_instance(model.as_ref(py))?
+            || stats
+                .getattr("rv_discrete")?
+                .downcast::<PyType>()?
+                .is_instance(model.as_ref(py))?;
+        if validate && !is_model_family {
+            scipy_model.validate(py, PyTuple::empty(py))?;
+        }
+
         let custom_model = CustomModel::new(
-            model.getattr(py, "cdf")?,
+            cdf,
             model.getattr(py, "ppf")?,
             min_symbol_inclusive,
             max_symbol_inclusive,
-        );
-        Ok(PyClassInitializer::from(custom_model).add_subclass(ScipyModel))
+        )?;
+        Ok(PyClassInitializer::from(custom_model).add_subclass(scipy_model))
+    }
+
+    /// Checks that the CDF is valid on all points at which the entropy coder evaluates it.
+    ///
+    /// Raises a `ValueError` that lists all intervals on which the CDF decreases or leaves
+    /// the interval [0.0, 1.0] (after rounding to the precision used by the entropy
+    /// coders). For a model family, pass one scalar value for each model parameter, i.e.,
+    /// the same parameters you would pass (as arrays) to an entropy coder's `encode` or
+    /// `decode` method.
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// import scipy.stats
+    ///
+    /// model_family = constriction.stream.model.ScipyModel(scipy.stats.norm, -100, 100)
+    /// model_family.validate(3.2, 10.1) # (passes)
+    /// try:
+    ///     model_family.validate(3.2, -1.0) # (invalid since scale < 0)
+    /// except ValueError as e:
+    ///     print(e) # (prints a list of offending intervals)
+    /// ```
+    #[pyo3(text_signature = "(self, *params)")]
+    #[args(params = "*")]
+    pub fn validate(&self, py: Python<'_>, params: &PyTuple) -> PyResult<()> {
+        internals::validate_cdf(
+            py,
+            &self.cdf,
+            self.min_symbol_inclusive,
+            self.max_symbol_inclusive,
+            params,
+        )
     }
 }
 
@@ -326,35 +637,169 @@ impl ScipyModel {
 /// the second example above, you still have to *call* the constructor of the model, i.e.,
 /// `model_family = constriction.stream.model.Categorical()` --- note the empty parentheses
 /// `()` at the end.
+///
+/// ## Rounding
+///
+/// The optional keyword argument `rounding` determines how the provided probabilities get
+/// rounded to the fixed point representation that the entropy coders use internally. It
+/// must be one of:
+///
+/// - `"minimal_cross_entropy"` (the default) --- chooses the fixed point probabilities that
+///   minimize the expected bit rate. This is the best choice for compression performance,
+///   but it is the slowest option to construct, which can matter if you provide a separate
+///   probability table for each symbol (as in the second example above).
+/// - `"floor_with_redistribution"` --- the fastest option; rounds down and assigns all
+///   leftover probability mass to the most probable symbol.
+/// - `"round_half_even"` --- rounds to the nearest representable probability and then
+///   corrects the normalization via the most probable symbols.
+/// - `"largest_remainder"` --- rounds down and distributes the leftover probability mass
+///   to the symbols with the largest rounding errors.
+///
+/// All options guarantee that every symbol gets a nonzero probability. Encoder and decoder
+/// have to use the same rounding policy. See [`RoundingPolicy` in the Rust
+/// API](https://docs.rs/constriction/latest/constriction/stream/model/enum.RoundingPolicy.html)
+/// for the exact rules.
 #[pyclass(extends=Model)]
-#[pyo3(text_signature = "(probabilities=None)")]
+#[pyo3(text_signature = "(probabilities=None, rounding=\"minimal_cross_entropy\")")]
 #[derive(Debug)]
 struct Categorical;
 
 #[pymethods]
 impl Categorical {
     #[new]
-    pub fn new(probabilities: Option<PyReadonlyArray1<'_, f64>>) -> PyResult<(Self, Model)> {
+    #[args(probabilities = "None", rounding = "\"minimal_cross_entropy\"")]
+    pub fn new(
+        py: Python<'_>,
+        probabilities: Option<PyObject>,
+        rounding: &str,
+    ) -> PyResult<(Self, Model)> {
+        let rounding = parse_rounding(rounding)?;
         let model = match probabilities {
-            None => Arc::new(internals::UnparameterizedCategoricalDistribution)
+            None => Arc::new(internals::UnparameterizedCategoricalDistribution { rounding })
                 as Arc<dyn internals::Model>,
             Some(probabilities) => {
-                let model =
-                    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
-                        probabilities.as_slice()?,
-                    )
-                    .map_err(|()| {
-                        pyo3::exceptions::PyValueError::new_err(
-                            "Probability distribution not normalizable (the array of probabilities\n\
-                            might be empty, contain negative values or NaNs, or sum to infinity).",
-                        )
-                    })?;
+                let model = match internals::ParamArray::<f64>::extract(probabilities.as_ref(py))? {
+                    internals::ParamArray::Native(probabilities) => {
+                        internals::categorical_from_probabilities(
+                            probabilities.as_slice()?,
+                            rounding,
+                        )?
+                    }
+                    internals::ParamArray::F32(probabilities) => {
+                        internals::categorical_from_probabilities(
+                            probabilities.as_slice()?,
+                            rounding,
+                        )?
+                    }
+                };
                 Arc::new(model) as Arc<dyn internals::Model>
             }
         };
 
         Ok((Self, Model(model)))
     }
+
+    /// Creates a categorical distribution with exact rational probabilities
+    /// `numerators[i] / denominator` for the symbols `i = 0, 1, ..., len(numerators) - 1`.
+    ///
+    /// Use this constructor instead of `Categorical(probabilities)` if a protocol specifies
+    /// probabilities exactly (e.g., in a standards document) since it bypasses the
+    /// quantization of floating point probabilities. The `numerators` must be a rank-1 numpy
+    /// array with `dtype=np.uint32` whose entries are all nonzero and sum up to exactly
+    /// `denominator`, and `denominator` must be a power of two that is at most `2**24`.
+    /// There have to be at least two numerators.
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// # P(X=0) = 3/16, P(X=1) = 8/16, and P(X=2) = 5/16:
+    /// numerators = np.array([3, 8, 5], dtype=np.uint32)
+    /// model = constriction.stream.model.Categorical.from_rational(numerators, 16)
+    ///
+    /// symbols = np.array([1, 2, 0, 1], dtype=np.int32)
+    /// coder = constriction.stream.stack.AnsCoder()
+    /// coder.encode_reverse(symbols, model)
+    /// assert np.all(coder.decode(model, 4) == symbols)
+    /// ```
+    #[staticmethod]
+    #[pyo3(text_signature = "(numerators, denominator)")]
+    pub fn from_rational(
+        py: Python<'_>,
+        numerators: PyReadonlyArray1<'_, u32>,
+        denominator: u64,
+    ) -> PyResult<Py<Self>> {
+        let model = DefaultContiguousCategoricalEntropyModel::from_rational_probabilities(
+            numerators.as_slice()?,
+            denominator,
+        )
+        .map_err(|()| {
+            pyo3::exceptions::PyValueError::new_err(
+                "Invalid rational probabilities (the denominator must be a power of two that is\n\
+                at most 2**24, and there must be at least two numerators, which must all be\n\
+                nonzero and sum up to the denominator).",
+            )
+        })?;
+        let initializer =
+            PyClassInitializer::from(Model(Arc::new(model) as Arc<dyn internals::Model>))
+                .add_subclass(Self);
+        Py::new(py, initializer)
+    }
+
+    /// Creates a categorical distribution with the exact fixed point probabilities
+    /// `probabilities[i] / 2**24` for the symbols `i = 0, 1, ..., len(probabilities) - 1`.
+    ///
+    /// The `probabilities` are used verbatim, without any floating point arithmetic or
+    /// rounding, so the resulting model is reproducible bit for bit on all platforms. This
+    /// is useful, e.g., if you transmit a model as part of the compressed data. The
+    /// `probabilities` must be a rank-1 numpy array with `dtype=np.uint32` whose entries
+    /// are all nonzero and sum up to exactly `2**24`. There have to be at least two
+    /// entries.
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// probabilities = np.array([3 << 20, 1, (1 << 23) - 1, 5 << 20], dtype=np.uint32)
+    /// assert probabilities.sum() == 2**24
+    /// model = constriction.stream.model.Categorical.from_fixed_point(probabilities)
+    ///
+    /// symbols = np.array([1, 2, 0, 3, 2], dtype=np.int32)
+    /// coder = constriction.stream.stack.AnsCoder()
+    /// coder.encode_reverse(symbols, model)
+    /// assert np.all(coder.decode(model, 5) == symbols)
+    /// ```
+    #[staticmethod]
+    #[pyo3(text_signature = "(probabilities)")]
+    pub fn from_fixed_point(
+        py: Python<'_>,
+        probabilities: PyReadonlyArray1<'_, u32>,
+    ) -> PyResult<Py<Self>> {
+        let model = DefaultContiguousCategoricalEntropyModel::from_fixed_point_probabilities(
+            probabilities.as_slice()?,
+        )
+        .map_err(|()| {
+            pyo3::exceptions::PyValueError::new_err(
+                "Invalid fixed point probabilities (there must be at least two of them, and\n\
+                they must all be nonzero and sum up to exactly 2**24).",
+            )
+        })?;
+        let initializer =
+            PyClassInitializer::from(Model(Arc::new(model) as Arc<dyn internals::Model>))
+                .add_subclass(Self);
+        Py::new(py, initializer)
+    }
+}
+
+fn parse_rounding(rounding: &str) -> PyResult<RoundingPolicy> {
+    match rounding {
+        "minimal_cross_entropy" => Ok(RoundingPolicy::MinimalCrossEntropy),
+        "floor_with_redistribution" => Ok(RoundingPolicy::FloorWithRedistribution),
+        "round_half_even" => Ok(RoundingPolicy::RoundHalfEven),
+        "largest_remainder" => Ok(RoundingPolicy::LargestRemainder),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(
+            "`rounding` must be one of \"minimal_cross_entropy\", \"floor_with_redistribution\",\n\
+            \"round_half_even\", or \"largest_remainder\".",
+        )),
+    }
 }
 
 /// A uniform distribution over the alphabet `{0, 1, ..., size-1}`, where `size` is an
@@ -386,17 +831,61 @@ impl Uniform {
         let model = match size {
             None => {
                 let model = internals::ParameterizableModel::new(|(size,): (i32,)| {
-                    UniformModel::new(size as u32)
+                    UniformModel::checked_new(size as u32).ok_or(UNIFORM_ERROR)
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
-            Some(size) => Arc::new(UniformModel::new(size as u32)) as Arc<dyn internals::Model>,
+            Some(size) => {
+                let model = UniformModel::checked_new(size as u32)
+                    .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(UNIFORM_ERROR))?;
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
         };
 
         Ok((Self, Model(model)))
     }
+
+    /// Creates a uniform distribution over the integers
+    /// `{min_symbol_inclusive, min_symbol_inclusive + 1, ..., max_symbol_inclusive}`.
+    ///
+    /// Encoding and decoding with the resulting model takes only a few arithmetic
+    /// operations per symbol, regardless of the size of the range, so you don't need to
+    /// build a large `Categorical` model to encode "raw" symbols that are uniformly
+    /// distributed over a large range. The range must contain at least two and at most
+    /// `2**24` symbols.
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// model = constriction.stream.model.Uniform.from_range(-1000, 1000)
+    ///
+    /// symbols = np.array([-1000, 0, 17, 1000, -3], dtype=np.int32)
+    /// coder = constriction.stream.stack.AnsCoder()
+    /// coder.encode_reverse(symbols, model)
+    /// assert np.all(coder.decode(model, 5) == symbols)
+    /// ```
+    #[staticmethod]
+    #[pyo3(text_signature = "(min_symbol_inclusive, max_symbol_inclusive)")]
+    pub fn from_range(
+        py: Python<'_>,
+        min_symbol_inclusive: i32,
+        max_symbol_inclusive: i32,
+    ) -> PyResult<Py<Self>> {
+        let model = DefaultUniformRangeModel::new(min_symbol_inclusive..=max_symbol_inclusive)
+            .map_err(|()| {
+                pyo3::exceptions::PyValueError::new_err(
+                    "The range must contain at least two and at most 2**24 symbols.",
+                )
+            })?;
+        let initializer =
+            PyClassInitializer::from(Model(Arc::new(model) as Arc<dyn internals::Model>))
+                .add_subclass(Self);
+        Py::new(py, initializer)
+    }
 }
 
+const UNIFORM_ERROR: &str = "`size` must be at least 2 and smaller than 2**24.";
+
 /// A Gaussian distribution, quantized over bins of size 1 centered at integer values.
 ///
 /// This kind of entropy model is often used in novel deep-learning based compression
@@ -439,7 +928,11 @@ impl Uniform {
 #[pyclass(extends=Model)]
 #[pyo3(text_signature = "(min_symbol_inclusive, max_symbol_inclusive, mean=None, std=None)")]
 #[derive(Debug)]
-struct QuantizedGaussian;
+struct QuantizedGaussian {
+    quantizer: LeakyQuantizer<f64, i32, u32, 24>,
+    mean: Option<f64>,
+    std: Option<f64>,
+}
 
 #[pymethods]
 impl QuantizedGaussian {
@@ -450,47 +943,86 @@ impl QuantizedGaussian {
         mean: Option<f64>,
         std: Option<f64>,
     ) -> PyResult<(Self, Model)> {
+        let quantizer =
+            LeakyQuantizer::<f64, _, _, 24>::new(min_symbol_inclusive..=max_symbol_inclusive);
         let model = match (mean, std) {
             (None, None) => {
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(
-                    min_symbol_inclusive..=max_symbol_inclusive,
-                );
                 let model = internals::ParameterizableModel::new(move |(mean, std): (f64, f64)| {
                     let distribution = probability::distribution::Gaussian::new(mean, std);
-                    quantizer.quantize(distribution)
+                    Ok(quantizer.quantize(distribution))
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
             (Some(mean), Some(std)) => {
                 let distribution = probability::distribution::Gaussian::new(mean, std);
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(
-                    min_symbol_inclusive..=max_symbol_inclusive,
-                );
                 Arc::new(quantizer.quantize(distribution)) as Arc<dyn internals::Model>
             }
             (None, Some(std)) => {
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(
-                    min_symbol_inclusive..=max_symbol_inclusive,
-                );
                 let model = internals::ParameterizableModel::new(move |(mean,): (f64,)| {
                     let distribution = probability::distribution::Gaussian::new(mean, std);
-                    quantizer.quantize(distribution)
+                    Ok(quantizer.quantize(distribution))
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
             (Some(mean), None) => {
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(
-                    min_symbol_inclusive..=max_symbol_inclusive,
-                );
                 let model = internals::ParameterizableModel::new(move |(std,): (f64,)| {
                     let distribution = probability::distribution::Gaussian::new(mean, std);
-                    quantizer.quantize(distribution)
+                    Ok(quantizer.quantize(distribution))
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
         };
 
-        Ok((Self, Model(model)))
+        Ok((
+            Self {
+                quantizer,
+                mean,
+                std,
+            },
+            Model(model),
+        ))
+    }
+
+    /// Reports how well the quantized model fits its support.
+    ///
+    /// Returns a dictionary with the following entries:
+    ///
+    /// - **"support"** --- the tuple `(min_symbol_inclusive, max_symbol_inclusive)`, i.e., the
+    ///   range of symbols that can be encoded with the model;
+    /// - **"effective_support"** --- the smallest range (as a tuple of inclusive bounds) that
+    ///   contains all symbols whose probability is larger than the minimal probability that
+    ///   `constriction` assigns to every symbol within the support (or `None` if there's no
+    ///   such symbol);
+    /// - **"min_probability"** --- the smallest probability of any symbol within the
+    ///   support after quantization to fixed-point precision;
+    /// - **"tail_mass"** --- the probability mass that the Gaussian distribution puts
+    ///   outside of the support, which gets added to the probabilities of the two symbols at
+    ///   the boundaries of the support.
+    ///
+    /// A large `tail_mass` indicates that the support is too narrow, and an
+    /// `effective_support` that is much narrower than the `support` indicates that the
+    /// support is needlessly wide. Both cost bit rate.
+    ///
+    /// If you delayed model parameters to encoding or decoding, then you have to pass
+    /// scalar values for them to this method.
+    #[pyo3(text_signature = "(self, mean=None, std=None)")]
+    pub fn describe<'py>(
+        &self,
+        py: Python<'py>,
+        mean: Option<f64>,
+        std: Option<f64>,
+    ) -> PyResult<&'py PyDict> {
+        let (mean, std) = match (mean.or(self.mean), std.or(self.std)) {
+            (Some(mean), Some(std)) => (mean, std),
+            _ => {
+                return Err(pyo3::exceptions::PyAttributeError::new_err(
+                    "Model parameters are missing. Pass scalar values for all parameters that \
+                    weren't specified when constructing the model.",
+                ))
+            }
+        };
+        let distribution = probability::distribution::Gaussian::new(mean, std);
+        report_to_dict(py, self.quantizer.quantize(distribution).describe())
     }
 }
 
@@ -519,7 +1051,11 @@ impl QuantizedGaussian {
 #[pyclass(extends=Model)]
 #[pyo3(text_signature = "(min_symbol_inclusive, max_symbol_inclusive, mean=None, scale=None)")]
 #[derive(Debug)]
-struct QuantizedLaplace;
+struct QuantizedLaplace {
+    quantizer: LeakyQuantizer<f64, i32, u32, 24>,
+    mean: Option<f64>,
+    scale: Option<f64>,
+}
 
 #[pymethods]
 impl QuantizedLaplace {
@@ -530,164 +1066,1755 @@ impl QuantizedLaplace {
         mean: Option<f64>,
         scale: Option<f64>,
     ) -> PyResult<(Self, Model)> {
+        let quantizer =
+            LeakyQuantizer::<f64, _, _, 24>::new(min_symbol_inclusive..=max_symbol_inclusive);
         let model = match (mean, scale) {
             (None, None) => {
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(
-                    min_symbol_inclusive..=max_symbol_inclusive,
-                );
                 let model =
                     internals::ParameterizableModel::new(move |(mean, scale): (f64, f64)| {
                         let distribution = probability::distribution::Laplace::new(mean, scale);
-                        quantizer.quantize(distribution)
+                        Ok(quantizer.quantize(distribution))
                     });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
             (Some(mean), Some(scale)) => {
                 let distribution = probability::distribution::Laplace::new(mean, scale);
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(
-                    min_symbol_inclusive..=max_symbol_inclusive,
-                );
                 Arc::new(quantizer.quantize(distribution)) as Arc<dyn internals::Model>
             }
             (Some(mean), None) => {
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(
-                    min_symbol_inclusive..=max_symbol_inclusive,
-                );
                 let model = internals::ParameterizableModel::new(move |(scale,): (f64,)| {
                     let distribution = probability::distribution::Laplace::new(mean, scale);
-                    quantizer.quantize(distribution)
+                    Ok(quantizer.quantize(distribution))
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
             (None, Some(scale)) => {
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(
-                    min_symbol_inclusive..=max_symbol_inclusive,
-                );
                 let model = internals::ParameterizableModel::new(move |(mean,): (f64,)| {
                     let distribution = probability::distribution::Laplace::new(mean, scale);
-                    quantizer.quantize(distribution)
+                    Ok(quantizer.quantize(distribution))
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
         };
 
-        Ok((Self, Model(model)))
+        Ok((
+            Self {
+                quantizer,
+                mean,
+                scale,
+            },
+            Model(model),
+        ))
+    }
+
+    /// Reports how well the quantized model fits its support.
+    ///
+    /// Returns a dictionary with the following entries:
+    ///
+    /// - **"support"** --- the tuple `(min_symbol_inclusive, max_symbol_inclusive)`, i.e., the
+    ///   range of symbols that can be encoded with the model;
+    /// - **"effective_support"** --- the smallest range (as a tuple of inclusive bounds) that
+    ///   contains all symbols whose probability is larger than the minimal probability that
+    ///   `constriction` assigns to every symbol within the support (or `None` if there's no
+    ///   such symbol);
+    /// - **"min_probability"** --- the smallest probability of any symbol within the
+    ///   support after quantization to fixed-point precision;
+    /// - **"tail_mass"** --- the probability mass that the Laplace distribution puts
+    ///   outside of the support, which gets added to the probabilities of the two symbols at
+    ///   the boundaries of the support.
+    ///
+    /// A large `tail_mass` indicates that the support is too narrow, and an
+    /// `effective_support` that is much narrower than the `support` indicates that the
+    /// support is needlessly wide. Both cost bit rate.
+    ///
+    /// If you delayed model parameters to encoding or decoding, then you have to pass
+    /// scalar values for them to this method.
+    #[pyo3(text_signature = "(self, mean=None, scale=None)")]
+    pub fn describe<'py>(
+        &self,
+        py: Python<'py>,
+        mean: Option<f64>,
+        scale: Option<f64>,
+    ) -> PyResult<&'py PyDict> {
+        let (mean, scale) = match (mean.or(self.mean), scale.or(self.scale)) {
+            (Some(mean), Some(scale)) => (mean, scale),
+            _ => {
+                return Err(pyo3::exceptions::PyAttributeError::new_err(
+                    "Model parameters are missing. Pass scalar values for all parameters that \
+                    weren't specified when constructing the model.",
+                ))
+            }
+        };
+        let distribution = probability::distribution::Laplace::new(mean, scale);
+        report_to_dict(py, self.quantizer.quantize(distribution).describe())
     }
 }
 
-/// A Binomial distribution over the alphabet {0, 1, ..., n}.
+/// A logistic distribution, quantized over bins of size 1 centered at integer values.
 ///
-/// Models the number of successful trials out of `n` trials where the trials are
-/// independent from each other and each one succeeds with probability `p`.
+/// Analogous to [`QuantizedGaussian`](#constriction.stream.model.QuantizedGaussian), just
+/// starting from a logistic distribution rather than a Gaussian. The cumulative
+/// distribution function of a logistic distribution is the sigmoid function, so evaluating
+/// this model is cheap. Quantized logistic distributions are a popular choice of prior in
+/// learned compression methods.
+///
+/// ## Fixed Arguments
+///
+/// The following arguments always have to be provided directly to the constructor of the
+/// model. They cannot be delayed until encoding or decoding.
+///
+/// - **min_symbol_inclusive** and **max_symbol_inclusive** --- specify the integer range on
+///   which the model is defined.
 ///
 /// ## Model Parameters
 ///
-/// Each model parameter can either be specified as a scalar when constructing the model, or
-/// as a rank-1 numpy array (with `dtype=np.int32` for `n` and `dtype=np.float64` for `p`)
-/// when calling the entropy coder's encode or decode method (see [discussion
-/// above](#concrete-models-vs-model-families)). Note that, even if you delay all model
-/// parameters to the point of encoding or decoding, then  you still have to *call* the
-/// constructor of the model, i.e.: `model_family = constriction.stream.model.Binomial()`
-/// --- note the trailing `()`.
+/// Each of the following model parameters can either be specified as a scalar when
+/// constructing the model, or as a rank-1 numpy array (with `dtype=np.float64`) when
+/// calling the entropy coder's encode or decode method.
 ///
-/// - **n** --- the number of trials;
-/// - **p** --- the probability that any given trial succeeds; must be between 0.0 and 1.0
-///   (both inclusive). For your convenience, `constriction` always assigns a (possibly
-///   tiny but) nonzero probability to all symbols in the range {0, 1, ..., n}, even if you
-///   set `p = 0.0` or `p = 1.0` so that all symbols in this range can in principle be
-///   encoded, albeit possibly at a high bitrate.
+/// - **mean** --- the mean of the logistic distribution before quantization; must be finite.
+/// - **scale** --- the scale parameter `s` of the logistic distribution before quantization
+///   (resulting in a variance of `(pi * scale)**2 / 3`); must be strictly positive and
+///   finite.
+///
+/// Invalid parameters raise a `ValueError`.
 #[pyclass(extends=Model)]
-#[pyo3(text_signature = "(n=None, p=None)")]
+#[pyo3(text_signature = "(min_symbol_inclusive, max_symbol_inclusive, mean=None, scale=None)")]
 #[derive(Debug)]
-struct Binomial;
+struct QuantizedLogistic {
+    quantizer: LeakyQuantizer<f64, i32, u32, 24>,
+    mean: Option<f64>,
+    scale: Option<f64>,
+}
 
 #[pymethods]
-impl Binomial {
+impl QuantizedLogistic {
     #[new]
-    pub fn new(n: Option<i32>, p: Option<f64>) -> PyResult<(Self, Model)> {
-        let model = match (n, p) {
+    pub fn new(
+        min_symbol_inclusive: i32,
+        max_symbol_inclusive: i32,
+        mean: Option<f64>,
+        scale: Option<f64>,
+    ) -> PyResult<(Self, Model)> {
+        let quantizer =
+            LeakyQuantizer::<f64, _, _, 24>::new(min_symbol_inclusive..=max_symbol_inclusive);
+        let model = match (mean, scale) {
             (None, None) => {
-                let model = internals::ParameterizableModel::new(move |(n, p): (i32, f64)| {
-                    let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(0..=n);
-                    let distribution = probability::distribution::Binomial::new(n as usize, p);
-                    quantizer.quantize(distribution)
-                });
+                let model =
+                    internals::ParameterizableModel::new(move |(mean, scale): (f64, f64)| {
+                        let distribution = checked_logistic(mean, scale)?;
+                        Ok(quantizer.quantize(distribution))
+                    });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
-            (Some(n), None) => {
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(0..=n);
-                let model = internals::ParameterizableModel::new(move |(p,): (f64,)| {
-                    let distribution = probability::distribution::Binomial::new(n as usize, p);
-                    quantizer.quantize(distribution)
+            (Some(mean), Some(scale)) => {
+                let distribution = checked_logistic(mean, scale)
+                    .map_err(pyo3::exceptions::PyValueError::new_err)?;
+                Arc::new(quantizer.quantize(distribution)) as Arc<dyn internals::Model>
+            }
+            (Some(mean), None) => {
+                let model = internals::ParameterizableModel::new(move |(scale,): (f64,)| {
+                    let distribution = checked_logistic(mean, scale)?;
+                    Ok(quantizer.quantize(distribution))
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
-            (Some(n), Some(p)) => {
-                let distribution = probability::distribution::Binomial::new(n as usize, p);
-                let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(0..=n);
-                Arc::new(quantizer.quantize(distribution)) as Arc<dyn internals::Model>
-            }
-            (None, Some(p)) => {
-                let model = internals::ParameterizableModel::new(move |(n,): (i32,)| {
-                    let quantizer = LeakyQuantizer::<f64, _, _, 24>::new(0..=n);
-                    let distribution = probability::distribution::Binomial::new(n as usize, p);
-                    quantizer.quantize(distribution)
+            (None, Some(scale)) => {
+                let model = internals::ParameterizableModel::new(move |(mean,): (f64,)| {
+                    let distribution = checked_logistic(mean, scale)?;
+                    Ok(quantizer.quantize(distribution))
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
         };
 
-        Ok((Self, Model(model)))
+        Ok((
+            Self {
+                quantizer,
+                mean,
+                scale,
+            },
+            Model(model),
+        ))
+    }
+
+    /// Reports how well the quantized model fits its support.
+    ///
+    /// Returns a dictionary with the following entries:
+    ///
+    /// - **"support"** --- the tuple `(min_symbol_inclusive, max_symbol_inclusive)`, i.e., the
+    ///   range of symbols that can be encoded with the model;
+    /// - **"effective_support"** --- the smallest range (as a tuple of inclusive bounds) that
+    ///   contains all symbols whose probability is larger than the minimal probability that
+    ///   `constriction` assigns to every symbol within the support (or `None` if there's no
+    ///   such symbol);
+    /// - **"min_probability"** --- the smallest probability of any symbol within the
+    ///   support after quantization to fixed-point precision;
+    /// - **"tail_mass"** --- the probability mass that the logistic distribution puts
+    ///   outside of the support, which gets added to the probabilities of the two symbols at
+    ///   the boundaries of the support.
+    ///
+    /// A large `tail_mass` indicates that the support is too narrow, and an
+    /// `effective_support` that is much narrower than the `support` indicates that the
+    /// support is needlessly wide. Both cost bit rate.
+    ///
+    /// If you delayed model parameters to encoding or decoding, then you have to pass
+    /// scalar values for them to this method.
+    #[pyo3(text_signature = "(self, mean=None, scale=None)")]
+    pub fn describe<'py>(
+        &self,
+        py: Python<'py>,
+        mean: Option<f64>,
+        scale: Option<f64>,
+    ) -> PyResult<&'py PyDict> {
+        let (mean, scale) = match (mean.or(self.mean), scale.or(self.scale)) {
+            (Some(mean), Some(scale)) => (mean, scale),
+            _ => {
+                return Err(pyo3::exceptions::PyAttributeError::new_err(
+                    "Model parameters are missing. Pass scalar values for all parameters that \
+                    weren't specified when constructing the model.",
+                ))
+            }
+        };
+        let distribution =
+            checked_logistic(mean, scale).map_err(pyo3::exceptions::PyValueError::new_err)?;
+        report_to_dict(py, self.quantizer.quantize(distribution).describe())
     }
 }
 
-/// A Bernoulli distribution over the alphabet {0, 1}.
+const LOGISTIC_ERROR: &str = "`mean` must be finite, and `scale` must be > 0.0 and finite.";
+
+fn checked_logistic(
+    mean: f64,
+    scale: f64,
+) -> Result<probability::distribution::Logistic, &'static str> {
+    if mean.is_finite() && scale > 0.0 && scale.is_finite() {
+        Ok(probability::distribution::Logistic::new(mean, scale))
+    } else {
+        Err(LOGISTIC_ERROR)
+    }
+}
+
+/// A Cauchy distribution, quantized over bins of size 1 centered at integer values.
 ///
-/// ## Model Parameter
+/// Analogous to [`QuantizedGaussian`](#constriction.stream.model.QuantizedGaussian), just
+/// starting from a Cauchy distribution rather than a Gaussian. The tails of a Cauchy
+/// distribution are much heavier than those of a Gaussian or a Laplace distribution, which
+/// makes this model a good fit for data with occasional outliers (e.g., heavy-tailed
+/// prediction residuals).
 ///
-/// The model parameter can either be specified as a scalar when constructing the model, or
-/// as a rank-1 numpy array with `dtype=np.float64` when calling the entropy coder's encode
-/// or decode method (see [discussion above](#concrete-models-vs-model-families)). Note
-/// that, in the latter case, you still have to *call* the constructor of the model, i.e.:
-/// `model_family = constriction.stream.model.Bernoulli()` --- note the trailing `()`.
+/// ## Fixed Arguments
 ///
-/// - **p** --- the probability for the symbol being `1` rather than `0`. Must be between
-///   0.0 and 1.0 (both inclusive). Note that, even if you set `p = 0.0` or `p = 1.0`,
-///   `constriction` still assigns a tiny probability to the disallowed outcome so that both
-///   symbols `0` and `1` can always be encoded, albeit at a potentially large cost in
-///   bitrate.
+/// The following arguments always have to be provided directly to the constructor of the
+/// model. They cannot be delayed until encoding or decoding.
+///
+/// - **min_symbol_inclusive** and **max_symbol_inclusive** --- specify the integer range on
+///   which the model is defined.
+///
+/// ## Model Parameters
+///
+/// Each of the following model parameters can either be specified as a scalar when
+/// constructing the model, or as a rank-1 numpy array (with `dtype=np.float64`) when
+/// calling the entropy coder's encode or decode method.
+///
+/// - **mean** --- the location (i.e., median) of the Cauchy distribution before
+///   quantization; must be finite.
+/// - **scale** --- the scale parameter of the Cauchy distribution before quantization
+///   (i.e., half the interquartile range; a Cauchy distribution has no finite variance);
+///   must be strictly positive and finite.
+///
+/// Invalid parameters raise a `ValueError`.
 #[pyclass(extends=Model)]
-#[pyo3(text_signature = "(p=None)")]
+#[pyo3(text_signature = "(min_symbol_inclusive, max_symbol_inclusive, mean=None, scale=None)")]
 #[derive(Debug)]
-struct Bernoulli;
+struct QuantizedCauchy {
+    quantizer: LeakyQuantizer<f64, i32, u32, 24>,
+    mean: Option<f64>,
+    scale: Option<f64>,
+}
 
 #[pymethods]
-impl Bernoulli {
+impl QuantizedCauchy {
     #[new]
-    pub fn new(p: Option<f64>) -> PyResult<(Self, Model)> {
-        let model = match p {
-            None => {
-                let model = internals::ParameterizableModel::new(move |(p,): (f64,)| {
-                    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
-                        1.0 - p,
-                        p,
-                    ])
-                    .expect("`p` must be >= 0.0 and <= 1.0.")
+    pub fn new(
+        min_symbol_inclusive: i32,
+        max_symbol_inclusive: i32,
+        mean: Option<f64>,
+        scale: Option<f64>,
+    ) -> PyResult<(Self, Model)> {
+        let quantizer =
+            LeakyQuantizer::<f64, _, _, 24>::new(min_symbol_inclusive..=max_symbol_inclusive);
+        let model = match (mean, scale) {
+            (None, None) => {
+                let model =
+                    internals::ParameterizableModel::new(move |(mean, scale): (f64, f64)| {
+                        let distribution =
+                            Cauchy::try_new(mean, scale).map_err(|()| CAUCHY_ERROR)?;
+                        Ok(quantizer.quantize(distribution))
+                    });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(mean), Some(scale)) => {
+                let distribution = Cauchy::try_new(mean, scale)
+                    .map_err(|()| pyo3::exceptions::PyValueError::new_err(CAUCHY_ERROR))?;
+                Arc::new(quantizer.quantize(distribution)) as Arc<dyn internals::Model>
+            }
+            (Some(mean), None) => {
+                let model = internals::ParameterizableModel::new(move |(scale,): (f64,)| {
+                    let distribution = Cauchy::try_new(mean, scale).map_err(|()| CAUCHY_ERROR)?;
+                    Ok(quantizer.quantize(distribution))
                 });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
-            Some(p) => {
-                let model =
-                    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
-                        1.0 - p,
-                        p,
-                    ])
-                    .map_err(|()| {
-                        pyo3::exceptions::PyValueError::new_err("`p` must be >= 0.0 and <= 1.0.")
-                    })?;
+            (None, Some(scale)) => {
+                let model = internals::ParameterizableModel::new(move |(mean,): (f64,)| {
+                    let distribution = Cauchy::try_new(mean, scale).map_err(|()| CAUCHY_ERROR)?;
+                    Ok(quantizer.quantize(distribution))
+                });
                 Arc::new(model) as Arc<dyn internals::Model>
             }
         };
-        Ok((Self, Model(model)))
+
+        Ok((
+            Self {
+                quantizer,
+                mean,
+                scale,
+            },
+            Model(model),
+        ))
+    }
+
+    /// Reports how well the quantized model fits its support.
+    ///
+    /// Returns a dictionary with the following entries:
+    ///
+    /// - **"support"** --- the tuple `(min_symbol_inclusive, max_symbol_inclusive)`, i.e., the
+    ///   range of symbols that can be encoded with the model;
+    /// - **"effective_support"** --- the smallest range (as a tuple of inclusive bounds) that
+    ///   contains all symbols whose probability is larger than the minimal probability that
+    ///   `constriction` assigns to every symbol within the support (or `None` if there's no
+    ///   such symbol);
+    /// - **"min_probability"** --- the smallest probability of any symbol within the
+    ///   support after quantization to fixed-point precision;
+    /// - **"tail_mass"** --- the probability mass that the Cauchy distribution puts
+    ///   outside of the support, which gets added to the probabilities of the two symbols at
+    ///   the boundaries of the support.
+    ///
+    /// A large `tail_mass` indicates that the support is too narrow, and an
+    /// `effective_support` that is much narrower than the `support` indicates that the
+    /// support is needlessly wide. Both cost bit rate.
+    ///
+    /// If you delayed model parameters to encoding or decoding, then you have to pass
+    /// scalar values for them to this method.
+    #[pyo3(text_signature = "(self, mean=None, scale=None)")]
+    pub fn describe<'py>(
+        &self,
+        py: Python<'py>,
+        mean: Option<f64>,
+        scale: Option<f64>,
+    ) -> PyResult<&'py PyDict> {
+        let (mean, scale) = match (mean.or(self.mean), scale.or(self.scale)) {
+            (Some(mean), Some(scale)) => (mean, scale),
+            _ => {
+                return Err(pyo3::exceptions::PyAttributeError::new_err(
+                    "Model parameters are missing. Pass scalar values for all parameters that \
+                    weren't specified when constructing the model.",
+                ))
+            }
+        };
+        let distribution = Cauchy::try_new(mean, scale)
+            .map_err(|()| pyo3::exceptions::PyValueError::new_err(CAUCHY_ERROR))?;
+        report_to_dict(py, self.quantizer.quantize(distribution).describe())
     }
 }
+
+const CAUCHY_ERROR: &str = "`mean` must be finite, and `scale` must be > 0.0 and finite.";
+
+/// A mixture of Gaussian distributions, quantized over bins of size 1 centered at integer
+/// values.
+///
+/// Gaussian mixture models (GMMs) are popular conditional entropy models in learned image
+/// and video compression. This model evaluates the mixture's cumulative distribution
+/// function in compiled code, which is much faster than wrapping the mixture in a
+/// [`CustomModel`](#constriction.stream.model.CustomModel).
+///
+/// ## Example
+///
+/// ```python
+/// # A model family over the integers from -100 to 100 (both ends inclusive):
+/// model_family = constriction.stream.model.QuantizedGaussianMixture(-100, 100)
+///
+/// # Per-symbol parameters of a mixture with 2 components (one row per symbol):
+/// weights = np.array([[0.7, 0.3], [0.5, 0.5], [0.9, 0.1]], dtype=np.float64)
+/// means = np.array([[-3.2, 10.5], [0.0, 4.0], [20.1, -7.5]], dtype=np.float64)
+/// stds = np.array([[2.0, 5.5], [1.0, 1.0], [3.3, 12.0]], dtype=np.float64)
+///
+/// symbols = np.array([-4, 3, 18], dtype=np.int32)
+/// coder = constriction.stream.stack.AnsCoder()
+/// coder.encode_reverse(symbols, model_family, weights, means, stds)
+/// assert np.all(coder.decode(model_family, weights, means, stds) == symbols)
+/// ```
+///
+/// ## Fixed Arguments
+///
+/// The following arguments always have to be provided directly to the constructor of the
+/// model. They cannot be delayed until encoding or decoding.
+///
+/// - **min_symbol_inclusive** and **max_symbol_inclusive** --- specify the integer range on
+///   which the model is defined.
+///
+/// ## Model Parameters
+///
+/// You can either provide all of the following model parameters to the constructor as
+/// rank-1 numpy arrays with `dtype=np.float64` and one entry per mixture component, or you
+/// can provide none of them to the constructor and instead pass them to the entropy coder's
+/// encode or decode method as rank-2 numpy arrays with `dtype=np.float64`, where the i'th
+/// row lists the parameters of the mixture for the i'th symbol. All arrays must have the
+/// same shape.
+///
+/// - **weights** --- the (not necessarily normalized) weights of the mixture components;
+///   must be nonnegative, and their sum must be nonzero and finite.
+/// - **means** --- the means of the mixture components before quantization.
+/// - **stds** --- the standard deviations of the mixture components before quantization;
+///   must be positive.
+#[pyclass(extends=Model)]
+#[pyo3(
+    text_signature = "(min_symbol_inclusive, max_symbol_inclusive, weights=None, means=None, stds=None)"
+)]
+#[derive(Debug)]
+struct QuantizedGaussianMixture;
+
+#[pymethods]
+impl QuantizedGaussianMixture {
+    #[new]
+    pub fn new(
+        min_symbol_inclusive: i32,
+        max_symbol_inclusive: i32,
+        weights: Option<PyReadonlyArray1<'_, f64>>,
+        means: Option<PyReadonlyArray1<'_, f64>>,
+        stds: Option<PyReadonlyArray1<'_, f64>>,
+    ) -> PyResult<(Self, Model)> {
+        let quantizer =
+            LeakyQuantizer::<f64, _, _, 24>::new(min_symbol_inclusive..=max_symbol_inclusive);
+        let model = match (weights, means, stds) {
+            (None, None, None) => {
+                let model =
+                    internals::UnparameterizedMixture::new(quantizer, GaussianMixture::new, "stds");
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(weights), Some(means), Some(stds)) => {
+                let mixture =
+                    GaussianMixture::new(&weights.to_vec()?, &means.to_vec()?, &stds.to_vec()?)
+                        .map_err(|()| {
+                            pyo3::exceptions::PyValueError::new_err(GAUSSIAN_MIXTURE_ERROR)
+                        })?;
+                Arc::new(quantizer.quantize(mixture)) as Arc<dyn internals::Model>
+            }
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Provide either all or none of the model parameters to the constructor.",
+                ))
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const GAUSSIAN_MIXTURE_ERROR: &str =
+    "`weights`, `means`, and `stds` must have the same nonzero length, `weights` must be >= 0.0 \
+    with a finite nonzero sum, `means` must be finite, and `stds` must be > 0.0 and finite.";
+
+/// A mixture of logistic distributions, quantized over bins of size 1 centered at integer
+/// values.
+///
+/// This is the discretized logistic mixture likelihood of PixelCNN++ and of many other
+/// autoregressive image models. It is analogous to
+/// [`QuantizedGaussianMixture`](#constriction.stream.model.QuantizedGaussianMixture), just
+/// with logistic rather than Gaussian mixture components.
+///
+/// Note that PixelCNN++ predicts unnormalized log-weights (logits) and the logarithms of the
+/// scales. Apply `np.exp` to both before passing them to this model (there's no need to
+/// normalize the weights). Also, PixelCNN++ rescales pixel values to the interval `[-1, 1]`
+/// whereas this model quantizes to bins of size 1, so multiply the means and scales by
+/// `127.5` and shift the means by `127.5` to model pixel values in `{0, 1, ..., 255}`.
+///
+/// ## Example
+///
+/// ```python
+/// model_family = constriction.stream.model.QuantizedLogisticMixture(0, 255)
+///
+/// # Per-pixel parameters of a mixture with 2 components (one row per pixel):
+/// weights = np.array([[0.7, 0.3], [0.5, 0.5], [0.9, 0.1]], dtype=np.float64)
+/// means = np.array([[12.3, 200.5], [128.0, 4.0], [250.1, 17.5]], dtype=np.float64)
+/// scales = np.array([[2.0, 5.5], [10.0, 1.0], [3.3, 12.0]], dtype=np.float64)
+///
+/// pixels = np.array([14, 130, 255], dtype=np.int32)
+/// coder = constriction.stream.stack.AnsCoder()
+/// coder.encode_reverse(pixels, model_family, weights, means, scales)
+/// assert np.all(coder.decode(model_family, weights, means, scales) == pixels)
+/// ```
+///
+/// ## Fixed Arguments
+///
+/// The following arguments always have to be provided directly to the constructor of the
+/// model. They cannot be delayed until encoding or decoding.
+///
+/// - **min_symbol_inclusive** and **max_symbol_inclusive** --- specify the integer range on
+///   which the model is defined.
+///
+/// ## Model Parameters
+///
+/// You can either provide all of the following model parameters to the constructor as
+/// rank-1 numpy arrays with `dtype=np.float64` and one entry per mixture component, or you
+/// can provide none of them to the constructor and instead pass them to the entropy coder's
+/// encode or decode method as rank-2 numpy arrays with `dtype=np.float64`, where the i'th
+/// row lists the parameters of the mixture for the i'th symbol. All arrays must have the
+/// same shape.
+///
+/// - **weights** --- the (not necessarily normalized) weights of the mixture components;
+///   must be nonnegative, and their sum must be nonzero and finite.
+/// - **means** --- the means of the mixture components before quantization.
+/// - **scales** --- the scale parameters of the mixture components before quantization;
+///   must be positive.
+#[pyclass(extends=Model)]
+#[pyo3(
+    text_signature = "(min_symbol_inclusive, max_symbol_inclusive, weights=None, means=None, scales=None)"
+)]
+#[derive(Debug)]
+struct QuantizedLogisticMixture;
+
+#[pymethods]
+impl QuantizedLogisticMixture {
+    #[new]
+    pub fn new(
+        min_symbol_inclusive: i32,
+        max_symbol_inclusive: i32,
+        weights: Option<PyReadonlyArray1<'_, f64>>,
+        means: Option<PyReadonlyArray1<'_, f64>>,
+        scales: Option<PyReadonlyArray1<'_, f64>>,
+    ) -> PyResult<(Self, Model)> {
+        let quantizer =
+            LeakyQuantizer::<f64, _, _, 24>::new(min_symbol_inclusive..=max_symbol_inclusive);
+        let model = match (weights, means, scales) {
+            (None, None, None) => {
+                let model = internals::UnparameterizedMixture::new(
+                    quantizer,
+                    LogisticMixture::new,
+                    "scales",
+                );
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(weights), Some(means), Some(scales)) => {
+                let mixture =
+                    LogisticMixture::new(&weights.to_vec()?, &means.to_vec()?, &scales.to_vec()?)
+                        .map_err(|()| {
+                        pyo3::exceptions::PyValueError::new_err(LOGISTIC_MIXTURE_ERROR)
+                    })?;
+                Arc::new(quantizer.quantize(mixture)) as Arc<dyn internals::Model>
+            }
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Provide either all or none of the model parameters to the constructor.",
+                ))
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const LOGISTIC_MIXTURE_ERROR: &str =
+    "`weights`, `means`, and `scales` must have the same nonzero length, `weights` must be \
+    >= 0.0 with a finite nonzero sum, `means` must be finite, and `scales` must be > 0.0 and \
+    finite.";
+
+/// A mixture of two Laplace distributions, quantized over bins of size 1 centered at
+/// integer values.
+///
+/// With probability `1 - tail_weight`, a symbol is drawn from a quantized Laplace
+/// distribution with the given `mean` and `scale`; with probability `tail_weight`, it is
+/// drawn from a quantized Laplace distribution whose scale is `tail_factor` times larger.
+/// This gives the model a heavier tail than a
+/// [`QuantizedLaplace`](#constriction.stream.model.QuantizedLaplace) with the same core,
+/// which is useful for prediction residuals of audio signals, where transients produce
+/// occasional outliers.
+///
+/// ## Example
+///
+/// ```python
+/// model = constriction.stream.model.QuantizedLongTailLaplace(
+///     -32768, 32767, 0.01, 100.0, mean=0.0, scale=3.0)
+/// residuals = np.array([2, -1, 0, 5, -3, 1200, -2, 0], dtype=np.int32)
+/// coder = constriction.stream.stack.AnsCoder()
+/// coder.encode_reverse(residuals, model)
+/// assert np.all(coder.decode(model, len(residuals)) == residuals)
+/// ```
+///
+/// ## Fixed Arguments
+///
+/// The following arguments always have to be provided directly to the constructor of the
+/// model. They cannot be delayed until encoding or decoding.
+///
+/// - **min_symbol_inclusive** and **max_symbol_inclusive** --- specify the integer range on
+///   which the model is defined.
+/// - **tail_weight** --- the weight of the wide mixture component; must be between 0.0 and
+///   1.0 (both inclusive).
+/// - **tail_factor** --- the ratio between the scales of the wide and the core mixture
+///   component; must be at least 1.0.
+///
+/// ## Model Parameters
+///
+/// Each of the following model parameters can either be specified as a scalar when
+/// constructing the model, or as a rank-1 numpy array (with `dtype=np.float64`) when
+/// calling the entropy coder's encode or decode method.
+///
+/// - **mean** --- the mean of both mixture components before quantization; must be finite.
+/// - **scale** --- the scale parameter `b` of the core mixture component before
+///   quantization; must be strictly positive, and `tail_factor * scale` must be finite.
+///
+/// Invalid parameters raise a `ValueError`.
+#[pyclass(extends=Model)]
+#[pyo3(
+    text_signature = "(min_symbol_inclusive, max_symbol_inclusive, tail_weight, tail_factor, mean=None, scale=None)"
+)]
+#[derive(Debug)]
+struct QuantizedLongTailLaplace;
+
+#[pymethods]
+impl QuantizedLongTailLaplace {
+    #[new]
+    pub fn new(
+        min_symbol_inclusive: i32,
+        max_symbol_inclusive: i32,
+        tail_weight: f64,
+        tail_factor: f64,
+        mean: Option<f64>,
+        scale: Option<f64>,
+    ) -> PyResult<(Self, Model)> {
+        if !(0.0..=1.0).contains(&tail_weight) || tail_factor.is_nan() || tail_factor < 1.0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "`tail_weight` must be >= 0.0 and <= 1.0, and `tail_factor` must be >= 1.0.",
+            ));
+        }
+
+        let quantizer =
+            LeakyQuantizer::<f64, _, _, 24>::new(min_symbol_inclusive..=max_symbol_inclusive);
+        let model = match (mean, scale) {
+            (None, None) => {
+                let model =
+                    internals::ParameterizableModel::new(move |(mean, scale): (f64, f64)| {
+                        let distribution =
+                            LongTailLaplace::try_new(mean, scale, tail_weight, tail_factor)
+                                .map_err(|()| LONG_TAIL_LAPLACE_ERROR)?;
+                        Ok(quantizer.quantize(distribution))
+                    });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(mean), Some(scale)) => {
+                let distribution = LongTailLaplace::try_new(mean, scale, tail_weight, tail_factor)
+                    .map_err(|()| {
+                        pyo3::exceptions::PyValueError::new_err(LONG_TAIL_LAPLACE_ERROR)
+                    })?;
+                Arc::new(quantizer.quantize(distribution)) as Arc<dyn internals::Model>
+            }
+            (Some(mean), None) => {
+                let model = internals::ParameterizableModel::new(move |(scale,): (f64,)| {
+                    let distribution =
+                        LongTailLaplace::try_new(mean, scale, tail_weight, tail_factor)
+                            .map_err(|()| LONG_TAIL_LAPLACE_ERROR)?;
+                    Ok(quantizer.quantize(distribution))
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (None, Some(scale)) => {
+                let model = internals::ParameterizableModel::new(move |(mean,): (f64,)| {
+                    let distribution =
+                        LongTailLaplace::try_new(mean, scale, tail_weight, tail_factor)
+                            .map_err(|()| LONG_TAIL_LAPLACE_ERROR)?;
+                    Ok(quantizer.quantize(distribution))
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const LONG_TAIL_LAPLACE_ERROR: &str =
+    "`mean` must be finite, and `scale` and `tail_factor * scale` must be > 0.0 and finite.";
+
+/// Tracks the scale of prediction residuals whose loudness drifts over time.
+///
+/// An `AdaptiveLaplace` models each residual with a
+/// [`QuantizedLaplace`](#constriction.stream.model.QuantizedLaplace) distribution with
+/// mean zero. Its scale is an exponential moving average of the magnitudes of all preceding
+/// residuals, where larger values of `rate` let the scale react faster to changes in
+/// loudness. An `AdaptiveLaplace` is not itself an entropy model since it changes after
+/// each residual. Instead, use the method `scales` to obtain the model parameters for
+/// encoding, and the methods `model` and `update` for decoding, as in the example below.
+///
+/// ## Example
+///
+/// ```python
+/// residuals = np.array([0, 1, -1, 0, 2, 35, -48, 22, 61, -17], dtype=np.int32)
+///
+/// # Encode all residuals at once with individual scales:
+/// adaptive = constriction.stream.model.AdaptiveLaplace(-1000, 1000, 1.0, 0.25)
+/// scales = adaptive.scales(residuals)
+/// model_family = constriction.stream.model.QuantizedLaplace(-1000, 1000)
+/// coder = constriction.stream.stack.AnsCoder()
+/// coder.encode_reverse(residuals, model_family, np.zeros(len(residuals)), scales)
+///
+/// # Decode one residual at a time since each scale depends on the preceding residuals:
+/// adaptive = constriction.stream.model.AdaptiveLaplace(-1000, 1000, 1.0, 0.25)
+/// decoded = []
+/// for _ in range(len(residuals)):
+///     residual = coder.decode(adaptive.model())
+///     adaptive.update(residual)
+///     decoded.append(residual)
+/// assert np.all(np.array(decoded) == residuals)
+/// ```
+///
+/// ## Arguments
+///
+/// - **min_symbol_inclusive** and **max_symbol_inclusive** --- specify the integer range of
+///   residuals.
+/// - **initial_scale** --- the scale for the first residual; must be positive.
+/// - **rate** --- the weight of each new residual in the moving average; must be larger
+///   than 0.0 and at most 1.0.
+#[pyclass]
+#[pyo3(text_signature = "(min_symbol_inclusive, max_symbol_inclusive, initial_scale, rate)")]
+#[derive(Debug)]
+struct AdaptiveLaplace {
+    inner: DefaultAdaptiveLaplace,
+}
+
+#[pymethods]
+impl AdaptiveLaplace {
+    #[new]
+    pub fn new(
+        min_symbol_inclusive: i32,
+        max_symbol_inclusive: i32,
+        initial_scale: f64,
+        rate: f64,
+    ) -> PyResult<Self> {
+        let inner = DefaultAdaptiveLaplace::new(
+            min_symbol_inclusive..=max_symbol_inclusive,
+            initial_scale,
+            rate,
+        )
+        .map_err(|()| {
+            pyo3::exceptions::PyValueError::new_err(
+                "`initial_scale` must be > 0.0, and `rate` must be > 0.0 and <= 1.0.",
+            )
+        })?;
+        Ok(Self { inner })
+    }
+
+    /// Returns the scale of the Laplace distribution for the next residual.
+    #[pyo3(text_signature = "(self)")]
+    pub fn scale(&self) -> f64 {
+        self.inner.scale()
+    }
+
+    /// Returns a concrete entropy model for the next residual.
+    #[pyo3(text_signature = "(self)")]
+    pub fn model(&self, py: Python<'_>) -> PyResult<Py<Model>> {
+        Py::new(py, Model(Arc::new(self.inner.model())))
+    }
+
+    /// Updates the scale with an observed residual.
+    #[pyo3(text_signature = "(self, residual)")]
+    pub fn update(&mut self, residual: i32) {
+        self.inner.update(residual);
+    }
+
+    /// Returns the scale for each residual in the provided rank-1 numpy array (with
+    /// `dtype=np.int32`) and updates the scale with all residuals.
+    ///
+    /// Pass the result together with an array of zeros as model parameters to a
+    /// [`QuantizedLaplace`](#constriction.stream.model.QuantizedLaplace) model family.
+    #[pyo3(text_signature = "(self, residuals)")]
+    pub fn scales<'py>(
+        &mut self,
+        py: Python<'py>,
+        residuals: PyReadonlyArray1<'_, i32>,
+    ) -> PyResult<&'py PyArray1<f64>> {
+        let scales = residuals
+            .as_slice()?
+            .iter()
+            .map(|&residual| {
+                let scale = self.inner.scale();
+                self.inner.update(residual);
+                scale
+            })
+            .collect::<Vec<_>>();
+        Ok(PyArray1::from_vec(py, scales))
+    }
+}
+
+/// A first-order Markov chain over the alphabet `{0, 1, ..., n-1}`.
+///
+/// Models the first symbol of a sequence with the distribution `initial_probabilities`,
+/// and each subsequent symbol with the row of `transition_probabilities` that corresponds
+/// to the preceding symbol. A `MarkovModel` is not itself an entropy model since the model
+/// for each symbol depends on the preceding symbol. Instead, use its methods `encode`,
+/// `encode_reverse`, and `decode`, which keep track of the preceding symbol for you and
+/// which work with any entropy coder, as in the example below.
+///
+/// ## Example
+///
+/// ```python
+/// initial_probabilities = np.array([0.5, 0.25, 0.25], dtype=np.float64)
+/// transition_probabilities = np.array(
+///     [[0.8, 0.1, 0.1],  # (distribution of a symbol that follows a 0)
+///      [0.1, 0.8, 0.1],  # (distribution of a symbol that follows a 1)
+///      [0.1, 0.1, 0.8]], # (distribution of a symbol that follows a 2)
+///     dtype=np.float64)
+/// markov = constriction.stream.model.MarkovModel(
+///     initial_probabilities, transition_probabilities)
+///
+/// symbols = np.array([0, 0, 0, 2, 2, 2, 2, 1, 1, 0, 0, 0], dtype=np.int32)
+/// coder = constriction.stream.stack.AnsCoder()
+/// markov.encode_reverse(coder, symbols)
+///
+/// reconstructed = markov.decode(coder, len(symbols))
+/// assert np.all(reconstructed == symbols)
+/// ```
+///
+/// ## Arguments
+///
+/// - **initial_probabilities** --- a rank-1 numpy array with `dtype=np.float64` and length
+///   `n >= 2`; the distribution of the first symbol of a sequence.
+/// - **transition_probabilities** --- a rank-2 numpy array with `dtype=np.float64` and shape
+///   `(n, n)`, where `transition_probabilities[i, j]` is the probability that symbol `j`
+///   follows symbol `i`.
+///
+/// All probabilities must be nonnegative and finite, but neither the initial distribution
+/// nor the rows of the transition matrix have to sum to one. As with a
+/// [`Categorical`](#constriction.stream.model.Categorical) model, each symbol gets a
+/// nonzero probability even if its provided probability is zero.
+#[pyclass]
+#[pyo3(text_signature = "(initial_probabilities, transition_probabilities)")]
+#[derive(Debug)]
+struct MarkovModel {
+    inner: Arc<DefaultMarkovModel>,
+}
+
+impl MarkovModel {
+    /// Returns the model family over all rows together with the preceding symbol of each
+    /// of the `symbols` (with `-1` for the first symbol).
+    fn transitions<'py>(
+        &self,
+        py: Python<'py>,
+        symbols: &PyReadonlyArray1<'_, i32>,
+    ) -> PyResult<(Py<Model>, &'py PyArray1<i32>)> {
+        let family = Py::new(
+            py,
+            Model(Arc::new(internals::MarkovTransitions(Arc::clone(
+                &self.inner,
+            )))),
+        )?;
+        let previous = core::iter::once(-1)
+            .chain(symbols.as_array().iter().copied())
+            .take(symbols.len())
+            .collect::<Vec<_>>();
+        Ok((family, PyArray1::from_vec(py, previous)))
+    }
+}
+
+#[pymethods]
+impl MarkovModel {
+    #[new]
+    pub fn new(
+        initial_probabilities: PyReadonlyArray1<'_, f64>,
+        transition_probabilities: PyReadonlyArray2<'_, f64>,
+    ) -> PyResult<Self> {
+        let alphabet_size = initial_probabilities.len();
+        if transition_probabilities.shape() != [alphabet_size, alphabet_size] {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "`transition_probabilities` must have shape `(n, n)` where `n` is the length of\n\
+                `initial_probabilities`.",
+            ));
+        }
+        let inner = DefaultMarkovModel::new(
+            initial_probabilities.as_slice()?,
+            transition_probabilities.as_slice()?,
+        )
+        .map_err(|()| {
+            pyo3::exceptions::PyValueError::new_err(
+                "Invalid probabilities (there must be at least two symbols, and all\n\
+                probabilities must be nonnegative and finite and not all zero).",
+            )
+        })?;
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    /// Returns the number of symbols `n` in the alphabet `{0, 1, ..., n-1}`.
+    #[pyo3(text_signature = "(self)")]
+    pub fn alphabet_size(&self) -> usize {
+        self.inner.alphabet_size()
+    }
+
+    /// Returns a concrete entropy model for a symbol that follows the symbol `previous`,
+    /// or the model for the first symbol of a sequence if `previous` is `None`.
+    ///
+    /// Use this method if you want to interleave a Markov chain with other data on the
+    /// same coder, or if you want to encode or decode a sequence in several pieces.
+    #[pyo3(text_signature = "(self, previous=None)")]
+    pub fn model(&self, py: Python<'_>, previous: Option<i32>) -> PyResult<Py<Model>> {
+        let row = internals::MarkovRow::new(Arc::clone(&self.inner), previous.unwrap_or(-1))?;
+        Py::new(py, Model(Arc::new(row)))
+    }
+
+    /// Encodes a sequence of `symbols` (a rank-1 numpy array with `dtype=np.int32`) on an
+    /// `encoder` with queue semantics (i.e., a
+    /// [`RangeEncoder`](queue.html#constriction.stream.queue.RangeEncoder)).
+    ///
+    /// For entropy coders with stack semantics, use `encode_reverse` instead.
+    #[pyo3(text_signature = "(self, encoder, symbols)")]
+    pub fn encode(
+        &self,
+        py: Python<'_>,
+        encoder: &PyAny,
+        symbols: PyReadonlyArray1<'_, i32>,
+    ) -> PyResult<()> {
+        let (family, previous) = self.transitions(py, &symbols)?;
+        encoder.call_method1("encode", (symbols, family, previous))?;
+        Ok(())
+    }
+
+    /// Encodes a sequence of `symbols` (a rank-1 numpy array with `dtype=np.int32`) in
+    /// reverse order on a `coder` with stack semantics (i.e., an
+    /// [`AnsCoder`](stack.html#constriction.stream.stack.AnsCoder) or a
+    /// [`ChainCoder`](chain.html#constriction.stream.chain.ChainCoder)), so that `decode`
+    /// decodes them in their original order.
+    #[pyo3(text_signature = "(self, coder, symbols)")]
+    pub fn encode_reverse(
+        &self,
+        py: Python<'_>,
+        coder: &PyAny,
+        symbols: PyReadonlyArray1<'_, i32>,
+    ) -> PyResult<()> {
+        let (family, previous) = self.transitions(py, &symbols)?;
+        coder.call_method1("encode_reverse", (symbols, family, previous))?;
+        Ok(())
+    }
+
+    /// Decodes a sequence of `amt` symbols from any entropy coder and returns them as a
+    /// rank-1 numpy array with `dtype=np.int32`.
+    ///
+    /// Works both for data encoded with `encode` on a coder with queue semantics and for
+    /// data encoded with `encode_reverse` on a coder with stack semantics.
+    #[pyo3(text_signature = "(self, decoder, amt)")]
+    pub fn decode<'py>(
+        &self,
+        py: Python<'py>,
+        decoder: &PyAny,
+        amt: usize,
+    ) -> PyResult<&'py PyArray1<i32>> {
+        let mut symbols = Vec::with_capacity(amt);
+        let mut previous = -1;
+        for _ in 0..amt {
+            let row = internals::MarkovRow::new(Arc::clone(&self.inner), previous)?;
+            let model = Py::new(py, Model(Arc::new(row)))?;
+            previous = decoder.call_method1("decode", (model,))?.extract::<i32>()?;
+            symbols.push(previous);
+        }
+        Ok(PyArray1::from_vec(py, symbols))
+    }
+}
+
+fn report_to_dict(py: Python<'_>, report: QuantizationReport<i32, u32>) -> PyResult<&PyDict> {
+    let to_float = |probability: u32| probability as f64 / (1u32 << 24) as f64;
+    let dict = PyDict::new(py);
+    dict.set_item("support", (*report.support.start(), *report.support.end()))?;
+    dict.set_item(
+        "effective_support",
+        report
+            .effective_support
+            .map(|range| (*range.start(), *range.end())),
+    )?;
+    dict.set_item("min_probability", to_float(report.min_probability.get()))?;
+    dict.set_item("tail_mass", to_float(report.tail_mass))?;
+    Ok(dict)
+}
+
+/// A Binomial distribution over the alphabet {0, 1, ..., n}.
+///
+/// Models the number of successful trials out of `n` trials where the trials are
+/// independent from each other and each one succeeds with probability `p`.
+///
+/// ## Model Parameters
+///
+/// Each model parameter can either be specified as a scalar when constructing the model, or
+/// as a rank-1 numpy array (with `dtype=np.int32` for `n` and `dtype=np.float64` for `p`)
+/// when calling the entropy coder's encode or decode method (see [discussion
+/// above](#concrete-models-vs-model-families)). Note that, even if you delay all model
+/// parameters to the point of encoding or decoding, then  you still have to *call* the
+/// constructor of the model, i.e.: `model_family = constriction.stream.model.Binomial()`
+/// --- note the trailing `()`.
+///
+/// - **n** --- the number of trials;
+/// - **p** --- the probability that any given trial succeeds; must be between 0.0 and 1.0
+///   (both inclusive). For your convenience, `constriction` always assigns a (possibly
+///   tiny but) nonzero probability to all symbols in the range {0, 1, ..., n}, even if you
+///   set `p = 0.0` or `p = 1.0` so that all symbols in this range can in principle be
+///   encoded, albeit possibly at a high bitrate.
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(n=None, p=None)")]
+#[derive(Debug)]
+struct Binomial;
+
+#[pymethods]
+impl Binomial {
+    #[new]
+    pub fn new(n: Option<i32>, p: Option<f64>) -> PyResult<(Self, Model)> {
+        let model = match (n, p) {
+            (None, None) => {
+                let model = internals::ParameterizableModel::new(move |(n, p): (i32, f64)| {
+                    let quantizer = LeakyQuantizer::<f64, _, _, 24>::checked_new(0..=n)
+                        .ok_or(BINOMIAL_ERROR)?;
+                    let distribution = probability::distribution::Binomial::new(n as usize, p);
+                    Ok(quantizer.quantize(distribution))
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(n), None) => {
+                let quantizer = LeakyQuantizer::<f64, _, _, 24>::checked_new(0..=n)
+                    .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(BINOMIAL_ERROR))?;
+                let model = internals::ParameterizableModel::new(move |(p,): (f64,)| {
+                    let distribution = probability::distribution::Binomial::new(n as usize, p);
+                    Ok(quantizer.quantize(distribution))
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(n), Some(p)) => {
+                let distribution = probability::distribution::Binomial::new(n as usize, p);
+                let quantizer = LeakyQuantizer::<f64, _, _, 24>::checked_new(0..=n)
+                    .ok_or_else(|| pyo3::exceptions::PyValueError::new_err(BINOMIAL_ERROR))?;
+                Arc::new(quantizer.quantize(distribution)) as Arc<dyn internals::Model>
+            }
+            (None, Some(p)) => {
+                let model = internals::ParameterizableModel::new(move |(n,): (i32,)| {
+                    let quantizer = LeakyQuantizer::<f64, _, _, 24>::checked_new(0..=n)
+                        .ok_or(BINOMIAL_ERROR)?;
+                    let distribution = probability::distribution::Binomial::new(n as usize, p);
+                    Ok(quantizer.quantize(distribution))
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const BINOMIAL_ERROR: &str = "`n` must be at least 1 and smaller than 2**24.";
+
+/// A Binomial distribution over the alphabet {0, 1, ..., n} whose fixed point
+/// probabilities are reproducible bit for bit across platforms.
+///
+/// Models the same distribution as [`Binomial`](#constriction.stream.model.Binomial), but
+/// it computes the fixed point probabilities with only basic arithmetic operations, whose
+/// results are specified exactly by the IEEE 754 standard. By contrast, `Binomial` relies
+/// on floating point implementations of special functions that may differ in their last
+/// bits across platforms. Use `ExactBinomial` if the decoder of your compressed data may
+/// run on a different machine than the encoder, e.g., for bits-back coding of count data.
+/// Note that `Binomial` and `ExactBinomial` generally produce different compressed data.
+///
+/// Constructing an `ExactBinomial` builds a table with `n + 1` entries, so it is more
+/// expensive than constructing a `Binomial` for large `n`.
+///
+/// ## Example
+///
+/// ```python
+/// model = constriction.stream.model.ExactBinomial(20, 0.3)
+/// counts = np.array([6, 4, 7, 0, 20, 5], dtype=np.int32)
+///
+/// coder = constriction.stream.stack.AnsCoder()
+/// coder.encode_reverse(counts, model)
+/// assert np.all(coder.decode(model, 6) == counts)
+/// ```
+///
+/// ## Model Parameters
+///
+/// Each model parameter can either be specified as a scalar when constructing the model, or
+/// as a rank-1 numpy array (with `dtype=np.int32` for `n` and `dtype=np.float64` for `p`)
+/// when calling the entropy coder's encode or decode method, analogous to `Binomial`.
+///
+/// - **n** --- the number of trials; must be at least 1 and smaller than `2**24`;
+/// - **p** --- the probability that any given trial succeeds; must be between 0.0 and 1.0
+///   (both inclusive). All symbols in the range {0, 1, ..., n} get a nonzero probability,
+///   even if you set `p = 0.0` or `p = 1.0`.
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(n=None, p=None)")]
+#[derive(Debug)]
+struct ExactBinomial;
+
+#[pymethods]
+impl ExactBinomial {
+    #[new]
+    pub fn new(n: Option<i32>, p: Option<f64>) -> PyResult<(Self, Model)> {
+        let model = match (n, p) {
+            (None, None) => {
+                let model = internals::ParameterizableModel::new(move |(n, p): (i32, f64)| {
+                    DefaultBinomial::new(n as usize, p).map_err(|()| EXACT_BINOMIAL_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(n), None) => {
+                let model = internals::ParameterizableModel::new(move |(p,): (f64,)| {
+                    DefaultBinomial::new(n as usize, p).map_err(|()| EXACT_BINOMIAL_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(n), Some(p)) => {
+                let model = DefaultBinomial::new(n as usize, p)
+                    .map_err(|()| pyo3::exceptions::PyValueError::new_err(EXACT_BINOMIAL_ERROR))?;
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (None, Some(p)) => {
+                let model = internals::ParameterizableModel::new(move |(n,): (i32,)| {
+                    DefaultBinomial::new(n as usize, p).map_err(|()| EXACT_BINOMIAL_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const EXACT_BINOMIAL_ERROR: &str =
+    "`n` must be at least 1 and smaller than 2**24, and `p` must be >= 0.0 and <= 1.0.";
+
+/// A Poisson distribution over the alphabet {0, 1, ..., max_symbol}, where `max_symbol`
+/// absorbs the probability mass of all larger counts.
+///
+/// Since a Poisson distribution has unbounded support, this model folds the entire tail
+/// mass `P(X >= max_symbol)` into the symbol `max_symbol`. You can either choose
+/// `max_symbol` large enough that larger counts can't occur in your application, or you
+/// can use it as an escape symbol: encode `min(count, max_symbol)` with this model and, if
+/// `count >= max_symbol`, encode the excess `count - max_symbol` with some other model.
+/// Like `ExactBinomial`, the fixed point probabilities are reproducible bit for bit across
+/// platforms.
+///
+/// ## Example
+///
+/// ```python
+/// max_symbol = 20
+/// model = constriction.stream.model.Poisson(max_symbol, 3.5)
+/// excess_model = constriction.stream.model.Uniform(1 << 16)
+/// counts = np.array([3, 5, 0, 2, 1000, 7], dtype=np.int32)
+///
+/// encoder = constriction.stream.queue.RangeEncoder()
+/// for count in counts:
+///     encoder.encode(min(count, max_symbol), model)
+///     if count >= max_symbol:
+///         encoder.encode(count - max_symbol, excess_model)
+///
+/// decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+/// for count in counts:
+///     decoded = decoder.decode(model)
+///     if decoded == max_symbol:
+///         decoded += decoder.decode(excess_model)
+///     assert decoded == count
+/// ```
+///
+/// ## Model Parameters
+///
+/// - **max_symbol** --- the largest symbol in the support; must be at least 1 and smaller
+///   than `2**23` (since every symbol gets two units of probability mass). Has to be
+///   specified when constructing the model since it determines the size of the alphabet.
+///   Constructing the model builds a table with `max_symbol + 1` entries.
+/// - **lam** --- the rate parameter (often called "lambda", which is a reserved keyword in
+///   Python), i.e., the expected count before folding the tail into `max_symbol`; must be
+///   nonnegative and smaller than `2**63` (`2**31` on 32-bit platforms). Can either be
+///   specified as a scalar when constructing the model, or as a rank-1 numpy array with
+///   `dtype=np.float64` when calling the entropy coder's encode or decode method (see
+///   [discussion above](#concrete-models-vs-model-families)). The construction time grows
+///   with the square root of `lam`.
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(max_symbol, lam=None)")]
+#[derive(Debug)]
+struct Poisson;
+
+#[pymethods]
+impl Poisson {
+    #[new]
+    pub fn new(max_symbol: i32, lam: Option<f64>) -> PyResult<(Self, Model)> {
+        if !(1..1 << 23).contains(&max_symbol) {
+            return Err(pyo3::exceptions::PyValueError::new_err(POISSON_ERROR));
+        }
+        let max_symbol = max_symbol as usize;
+
+        let model = match lam {
+            None => {
+                let model = internals::ParameterizableModel::new(move |(lam,): (f64,)| {
+                    DefaultPoisson::new(lam, max_symbol).map_err(|()| POISSON_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            Some(lam) => {
+                let model = DefaultPoisson::new(lam, max_symbol)
+                    .map_err(|()| pyo3::exceptions::PyValueError::new_err(POISSON_ERROR))?;
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const POISSON_ERROR: &str =
+    "`max_symbol` must be at least 1 and smaller than 2**24, and `lam` must be >= 0.0 and \
+    smaller than 2**63.";
+
+/// A negative binomial distribution over the alphabet {0, 1, ..., max_symbol}, where
+/// `max_symbol` absorbs the probability mass of all larger values.
+///
+/// Models overdispersed counts, i.e., counts whose variance is larger than their mean. The
+/// distribution has mean `r * (1 - p) / p` and variance `r * (1 - p) / p**2`. For integer
+/// `r`, it models the number of failures before the `r`-th success in independent trials
+/// that each succeed with probability `p`. Like
+/// [`Poisson`](#constriction.stream.model.Poisson), this model folds the entire tail mass
+/// `P(X >= max_symbol)` into the symbol `max_symbol`, and it evaluates its probability
+/// mass function natively, so you don't need `scipy` to use it.
+///
+/// ## Example
+///
+/// ```python
+/// counts = np.array([4, 0, 13, 6, 2, 25, 100, 7], dtype=np.int32)
+/// model = constriction.stream.model.NegativeBinomial(100, 3.0, 1 / 3)
+///
+/// encoder = constriction.stream.queue.RangeEncoder()
+/// encoder.encode(counts, model)
+/// decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+/// assert np.all(decoder.decode(model, len(counts)) == counts)
+/// ```
+///
+/// ## Fixed Arguments
+///
+/// The following argument always has to be provided directly to the constructor of the
+/// model. It cannot be delayed until encoding or decoding.
+///
+/// - **max_symbol** --- the largest symbol in the support; must be at least 1 and smaller
+///   than `2**24`.
+///
+/// ## Model Parameters
+///
+/// Each of the following model parameters can either be specified as a scalar when
+/// constructing the model, or as a rank-1 numpy array (with `dtype=np.float64`) when
+/// calling the entropy coder's encode or decode method.
+///
+/// - **r** --- the number of successes; must be positive and finite but doesn't have to be
+///   an integer.
+/// - **p** --- the success probability of each trial; must be larger than 0.0 and at most
+///   1.0.
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(max_symbol, r=None, p=None)")]
+#[derive(Debug)]
+struct NegativeBinomial;
+
+#[pymethods]
+impl NegativeBinomial {
+    #[new]
+    pub fn new(max_symbol: i32, r: Option<f64>, p: Option<f64>) -> PyResult<(Self, Model)> {
+        if !(1..1 << 23).contains(&max_symbol) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                NEGATIVE_BINOMIAL_ERROR,
+            ));
+        }
+        let max_symbol = max_symbol as usize;
+
+        let model = match (r, p) {
+            (None, None) => {
+                let model = internals::ParameterizableModel::new(move |(r, p): (f64, f64)| {
+                    DefaultNegativeBinomial::new(r, p, max_symbol)
+                        .map_err(|()| NEGATIVE_BINOMIAL_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(r), Some(p)) => {
+                let model = DefaultNegativeBinomial::new(r, p, max_symbol).map_err(|()| {
+                    pyo3::exceptions::PyValueError::new_err(NEGATIVE_BINOMIAL_ERROR)
+                })?;
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(r), None) => {
+                let model = internals::ParameterizableModel::new(move |(p,): (f64,)| {
+                    DefaultNegativeBinomial::new(r, p, max_symbol)
+                        .map_err(|()| NEGATIVE_BINOMIAL_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (None, Some(p)) => {
+                let model = internals::ParameterizableModel::new(move |(r,): (f64,)| {
+                    DefaultNegativeBinomial::new(r, p, max_symbol)
+                        .map_err(|()| NEGATIVE_BINOMIAL_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const NEGATIVE_BINOMIAL_ERROR: &str =
+    "`max_symbol` must be at least 1 and smaller than 2**24, `r` must be > 0.0 and finite, \
+    and `p` must be > 0.0 and <= 1.0.";
+
+/// A geometric distribution over the alphabet {0, 1, ..., max_symbol}, where `max_symbol`
+/// absorbs the probability mass of all larger values.
+///
+/// Models the number of failures before the first success in independent trials that each
+/// succeed with probability `p`, e.g., run lengths of zeros in sparse binary data. Since
+/// the geometric distribution has unbounded support, this model folds the entire tail mass
+/// `P(X >= max_symbol)` into the symbol `max_symbol`, analogous to
+/// [`Poisson`](#constriction.stream.model.Poisson), which also shows how to use
+/// `max_symbol` as an escape symbol. The fixed point probabilities are reproducible bit for
+/// bit across platforms.
+///
+/// If you provide `p` as a scalar, the model tabulates its fixed point probabilities once
+/// upon construction. If you provide `p` as an array when encoding or decoding, the model
+/// instead evaluates its cumulative distribution function in closed form for each symbol,
+/// which takes constant time regardless of `max_symbol`. Both ways result in the exact
+/// same fixed point probabilities.
+///
+/// ## Example
+///
+/// ```python
+/// bits = np.array([0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 1, 0, 1], dtype=np.int32)
+/// run_lengths = np.diff(np.concatenate(([-1], np.nonzero(bits)[0]))) - 1
+/// run_lengths = run_lengths.astype(np.int32)
+/// print(run_lengths) # (prints: [3, 0, 5, 1])
+///
+/// model = constriction.stream.model.Geometric(20, 0.3)
+/// coder = constriction.stream.stack.AnsCoder()
+/// coder.encode_reverse(run_lengths, model)
+/// assert np.all(coder.decode(model, len(run_lengths)) == run_lengths)
+///
+/// # Alternatively, provide an individual success probability for each symbol:
+/// ps = np.array([0.2, 0.9, 0.1, 0.5], dtype=np.float64)
+/// model_family = constriction.stream.model.Geometric(1000)
+/// coder.encode_reverse(run_lengths, model_family, ps)
+/// assert np.all(coder.decode(model_family, ps) == run_lengths)
+/// ```
+///
+/// ## Model Parameters
+///
+/// - **max_symbol** --- the largest symbol in the support; must be at least 1 and smaller
+///   than `2**23` (since every symbol gets two units of probability mass). Has to be
+///   specified when constructing the model since it determines the size of the alphabet.
+/// - **p** --- the success probability of each trial; must be larger than 0.0 and at most
+///   1.0. Can either be specified as a scalar when constructing the model, or as a rank-1
+///   numpy array with `dtype=np.float64` when calling the entropy coder's encode or decode
+///   method (see [discussion above](#concrete-models-vs-model-families)).
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(max_symbol, p=None)")]
+#[derive(Debug)]
+struct Geometric;
+
+#[pymethods]
+impl Geometric {
+    #[new]
+    pub fn new(max_symbol: i32, p: Option<f64>) -> PyResult<(Self, Model)> {
+        if !(1..1 << 23).contains(&max_symbol) {
+            return Err(pyo3::exceptions::PyValueError::new_err(GEOMETRIC_ERROR));
+        }
+        let max_symbol = max_symbol as usize;
+
+        let model = match p {
+            None => {
+                let model = internals::ParameterizableModel::new(move |(p,): (f64,)| {
+                    DefaultGeometric::new_closed_form(p, max_symbol).map_err(|()| GEOMETRIC_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            Some(p) => {
+                let model = DefaultGeometric::new(p, max_symbol)
+                    .map_err(|()| pyo3::exceptions::PyValueError::new_err(GEOMETRIC_ERROR))?;
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const GEOMETRIC_ERROR: &str =
+    "`max_symbol` must be at least 1 and smaller than 2**23, and `p` must be > 0.0 and <= 1.0.";
+
+/// A two-sided geometric distribution over a range of integers, as used for prediction
+/// residuals in lossless image codecs like JPEG-LS.
+///
+/// The probability of a symbol `x` is proportional to `decay**abs(x - offset)` on the set
+/// of all integers, and the probability mass outside of the support is assigned to the
+/// first and last symbol of the support, respectively. This is the discrete analog of a
+/// Laplace distribution, but unlike
+/// [`QuantizedLaplace`](#constriction.stream.model.QuantizedLaplace), it is defined directly
+/// on the integers rather than by integrating a continuous density over bins. The fixed
+/// point probabilities are reproducible bit for bit across platforms.
+///
+/// If you provide both `decay` and `offset` as scalars, the model tabulates its fixed point
+/// probabilities once upon construction. Otherwise, it evaluates its cumulative
+/// distribution function in closed form for each symbol, which takes constant time
+/// regardless of the size of the support. Both ways result in the exact same fixed point
+/// probabilities.
+///
+/// ## Example
+///
+/// ```python
+/// residuals = np.array([0, -1, 2, 0, 0, 1, -4, 17, 0], dtype=np.int32)
+/// model = constriction.stream.model.TwoSidedGeometric(-255, 255, 0.6, -0.3)
+///
+/// encoder = constriction.stream.queue.RangeEncoder()
+/// encoder.encode(residuals, model)
+/// decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+/// assert np.all(decoder.decode(model, len(residuals)) == residuals)
+///
+/// # Alternatively, provide an individual (e.g., context dependent) decay for each symbol:
+/// decays = np.array([0.3, 0.5, 0.5, 0.2, 0.3, 0.6, 0.7, 0.9, 0.4], dtype=np.float64)
+/// model_family = constriction.stream.model.TwoSidedGeometric(-255, 255, offset=0.0)
+/// encoder = constriction.stream.queue.RangeEncoder()
+/// encoder.encode(residuals, model_family, decays)
+/// decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+/// assert np.all(decoder.decode(model_family, decays) == residuals)
+/// ```
+///
+/// ## Fixed Arguments
+///
+/// The following arguments always have to be provided directly to the constructor of the
+/// model. They cannot be delayed until encoding or decoding.
+///
+/// - **min_symbol_inclusive** and **max_symbol_inclusive** --- specify the integer range on
+///   which the model is defined. It must contain at least two and at most `2**23` symbols
+///   (since every symbol gets two units of probability mass).
+///
+/// ## Model Parameters
+///
+/// Each of the following model parameters can either be specified as a scalar when
+/// constructing the model, or as a rank-1 numpy array (with `dtype=np.float64`) when
+/// calling the entropy coder's encode or decode method.
+///
+/// - **decay** --- the factor by which the probability decays per unit distance from
+///   `offset`; must be larger than 0.0 and smaller than 1.0.
+/// - **offset** --- the (not necessarily integer) position around which the probability
+///   mass is centered; must be finite but may lie outside of the support.
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(min_symbol_inclusive, max_symbol_inclusive, decay=None, offset=None)")]
+#[derive(Debug)]
+struct TwoSidedGeometric;
+
+#[pymethods]
+impl TwoSidedGeometric {
+    #[new]
+    pub fn new(
+        min_symbol_inclusive: i32,
+        max_symbol_inclusive: i32,
+        decay: Option<f64>,
+        offset: Option<f64>,
+    ) -> PyResult<(Self, Model)> {
+        let support = min_symbol_inclusive..=max_symbol_inclusive;
+        let model = match (decay, offset) {
+            (None, None) => {
+                let model =
+                    internals::ParameterizableModel::new(move |(decay, offset): (f64, f64)| {
+                        DefaultTwoSidedGeometric::new_closed_form(support.clone(), decay, offset)
+                            .map_err(|()| TWO_SIDED_GEOMETRIC_ERROR)
+                    });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(decay), Some(offset)) => {
+                let model =
+                    DefaultTwoSidedGeometric::new(support, decay, offset).map_err(|()| {
+                        pyo3::exceptions::PyValueError::new_err(TWO_SIDED_GEOMETRIC_ERROR)
+                    })?;
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (Some(decay), None) => {
+                let model = internals::ParameterizableModel::new(move |(offset,): (f64,)| {
+                    DefaultTwoSidedGeometric::new_closed_form(support.clone(), decay, offset)
+                        .map_err(|()| TWO_SIDED_GEOMETRIC_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            (None, Some(offset)) => {
+                let model = internals::ParameterizableModel::new(move |(decay,): (f64,)| {
+                    DefaultTwoSidedGeometric::new_closed_form(support.clone(), decay, offset)
+                        .map_err(|()| TWO_SIDED_GEOMETRIC_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const TWO_SIDED_GEOMETRIC_ERROR: &str =
+    "The support must contain at least 2 and at most 2**23 symbols, `decay` must be > 0.0 \
+    and < 1.0, and `offset` must be finite.";
+
+/// A power-law (Zipf) distribution over the alphabet {0, 1, ..., n - 1}.
+///
+/// The probability of symbol `k` is proportional to `(k + 1)**(-s)`, i.e., symbol `k`
+/// represents the `(k + 1)`-th most frequent item. This is a common model for ranks, e.g.,
+/// for word or token IDs that are sorted by frequency. Unlike a
+/// [`Categorical`](#constriction.stream.model.Categorical) model, you don't have to
+/// materialize an array of `n` probabilities yourself; the model evaluates the power law
+/// internally.
+///
+/// ## Example
+///
+/// ```python
+/// token_ids = np.array([0, 3, 1, 0, 17, 2, 0, 4711, 1, 8], dtype=np.int32)
+/// model = constriction.stream.model.Zipf(50_000, 1.1)
+///
+/// coder = constriction.stream.stack.AnsCoder()
+/// coder.encode_reverse(token_ids, model)
+/// assert np.all(coder.decode(model, len(token_ids)) == token_ids)
+/// ```
+///
+/// ## Model Parameters
+///
+/// - **n** --- the size of the alphabet; must be at least 2 and at most `2**24`. Has to be
+///   specified when constructing the model since it determines the size of the alphabet.
+/// - **s** --- the exponent of the power law; must be nonnegative and finite, where `s=0.0`
+///   results in a uniform distribution. Can either be specified as a scalar when
+///   constructing the model, or as a rank-1 numpy array with `dtype=np.float64` when
+///   calling the entropy coder's encode or decode method (see [discussion
+///   above](#concrete-models-vs-model-families)). Note that the latter constructs a table
+///   of `n` probabilities for each encoded or decoded symbol, so prefer a scalar `s` for
+///   large alphabets.
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(n, s=None)")]
+#[derive(Debug)]
+struct Zipf;
+
+#[pymethods]
+impl Zipf {
+    #[new]
+    pub fn new(n: i32, s: Option<f64>) -> PyResult<(Self, Model)> {
+        if !(2..=1 << 24).contains(&n) {
+            return Err(pyo3::exceptions::PyValueError::new_err(ZIPF_ERROR));
+        }
+        let n = n as usize;
+
+        let model = match s {
+            None => {
+                let model = internals::ParameterizableModel::new(move |(s,): (f64,)| {
+                    DefaultZipf::new(s, n).map_err(|()| ZIPF_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            Some(s) => {
+                let model = DefaultZipf::new(s, n)
+                    .map_err(|()| pyo3::exceptions::PyValueError::new_err(ZIPF_ERROR))?;
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+
+        Ok((Self, Model(model)))
+    }
+}
+
+const ZIPF_ERROR: &str =
+    "`n` must be at least 2 and at most 2**24, and `s` must be >= 0.0 and finite.";
+
+/// A nonparametric model that follows the smoothed histogram of some sample data.
+///
+/// Use this model instead of a parametric model like
+/// [`QuantizedGaussian`](#constriction.stream.model.QuantizedGaussian) if your data (e.g.,
+/// prediction residuals) doesn't follow any simple distribution. The model counts how often
+/// each integer occurs in `samples`, adds `pseudo_count` to each count, and normalizes. The
+/// resulting fixed point probabilities are reproducible bit for bit across platforms.
+///
+/// The decoder needs an identical model. If the decoder only knows the histogram `counts`
+/// (e.g., because you transmitted it along with the compressed data) rather than the
+/// original samples, then it can rebuild the model by passing
+/// `np.repeat(np.arange(min_symbol_inclusive, max_symbol_inclusive + 1, dtype=np.int32),
+/// counts)` as `samples` along with the same support and `pseudo_count`.
+///
+/// ## Example
+///
+/// ```python
+/// training_data = np.array([0, 1, -1, 0, 0, 3, -2, 0, 1, 0, 12, -1, 0, 2], dtype=np.int32)
+/// model = constriction.stream.model.Empirical(training_data, -20, 20)
+///
+/// residuals = np.array([1, 0, 0, -2, 5, 0, 1], dtype=np.int32)
+/// coder = constriction.stream.stack.AnsCoder()
+/// coder.encode_reverse(residuals, model)
+/// assert np.all(coder.decode(model, len(residuals)) == residuals)
+/// ```
+///
+/// ## Fixed Arguments
+///
+/// The model has no model parameters, so all arguments have to be provided directly to the
+/// constructor.
+///
+/// - **samples** --- a rank-1 numpy array with `dtype=np.int32` of sample data.
+/// - **min_symbol_inclusive** and **max_symbol_inclusive** --- optional; the integer range
+///   on which the model is defined. All `samples` must lie within this range. If omitted,
+///   the range spans from the smallest to the largest sample, so you won't be able to
+///   encode any symbols outside of this range. You have to provide either both or neither.
+/// - **pseudo_count** --- optional; a nonnegative number that gets added to the count of
+///   each symbol in the support before normalizing (default: `0.5`). Larger values make
+///   the model closer to a uniform distribution over the support.
+#[pyclass(extends=Model)]
+#[pyo3(
+    text_signature = "(samples, min_symbol_inclusive=None, max_symbol_inclusive=None, pseudo_count=0.5)"
+)]
+#[derive(Debug)]
+struct Empirical;
+
+#[pymethods]
+impl Empirical {
+    #[new]
+    #[args(
+        samples,
+        min_symbol_inclusive = "None",
+        max_symbol_inclusive = "None",
+        pseudo_count = "0.5"
+    )]
+    pub fn new(
+        samples: PyReadonlyArray1<'_, i32>,
+        min_symbol_inclusive: Option<i32>,
+        max_symbol_inclusive: Option<i32>,
+        pseudo_count: f64,
+    ) -> PyResult<(Self, Model)> {
+        let samples = samples.as_slice()?;
+        let model = match (min_symbol_inclusive, max_symbol_inclusive) {
+            (None, None) => DefaultEmpirical::from_samples(samples, pseudo_count),
+            (Some(min), Some(max)) => {
+                DefaultEmpirical::from_samples_with_support(samples, min..=max, pseudo_count)
+            }
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "Provide either both or neither of `min_symbol_inclusive` and \
+                    `max_symbol_inclusive`.",
+                ))
+            }
+        }
+        .map_err(|()| {
+            pyo3::exceptions::PyValueError::new_err(
+                "Invalid arguments: the support must contain at least two and fewer than 2**24\n\
+                symbols, all samples must lie within the support, and `pseudo_count` must be\n\
+                nonnegative and finite (and positive if `samples` is empty).",
+            )
+        })?;
+
+        Ok((Self, Model(Arc::new(model))))
+    }
+}
+
+/// A categorical distribution that can be re-rounded to any fixed point precision when
+/// encoding or decoding.
+///
+/// This model is meant for experiments that compare how the fixed point precision of
+/// entropy models affects the bit rate. It quantizes `probabilities` only once, at 32 bit
+/// precision, and then re-rounds them on the fly to whichever precision you pass as the
+/// `precision` keyword argument to the entropy coder's `encode` or `decode` method (default:
+/// 24, which is the precision of all entropy coders in the python API). Re-rounding is
+/// deterministic, so an encoder and a decoder that pass the same `precision` always use
+/// identical models. The model is leaky, i.e., every symbol gets a nonzero probability at
+/// every precision.
+///
+/// The `precision` has to be at most 24 and large enough to give each symbol at least one
+/// unit of probability, i.e., `2**precision >= len(probabilities)`.
+///
+/// ## Example
+///
+/// ```python
+/// probabilities = np.array([0.1, 0.6, 0.3], dtype=np.float64)
+/// model = constriction.stream.model.DynPrecisionCategorical(probabilities)
+/// symbols = np.array([0, 2, 1, 2, 0, 2, 0, 2, 1], dtype=np.int32)
+///
+/// for precision in [4, 8, 12, 24]:
+///     encoder = constriction.stream.queue.RangeEncoder()
+///     encoder.encode(symbols, model, precision=precision)
+///     print(precision, encoder.num_bits())
+///
+///     decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+///     decoded = decoder.decode(model, len(symbols), precision=precision)
+///     assert np.all(decoded == symbols)
+/// ```
+///
+/// ## Fixed Arguments
+///
+/// - **probabilities** --- a rank-1 numpy array with `dtype=np.float64` and at least two
+///   entries that specifies the probabilities of the symbols `0, 1, ...`. The
+///   probabilities don't need to be normalized.
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(probabilities)")]
+#[derive(Debug)]
+struct DynPrecisionCategorical;
+
+#[pymethods]
+impl DynPrecisionCategorical {
+    #[new]
+    pub fn new(probabilities: PyReadonlyArray1<'_, f64>) -> PyResult<(Self, Model)> {
+        let model = DynPrecisionModel::from_floating_point_probabilities(probabilities.as_slice()?)
+            .map_err(|()| {
+                pyo3::exceptions::PyValueError::new_err(
+                    "Probability distribution not normalizable (the array of probabilities\n\
+                    might have fewer than two entries, contain negative values or NaNs, or sum\n\
+                    to infinity).",
+                )
+            })?;
+        Ok((
+            Self,
+            Model(Arc::new(internals::DynPrecisionCategorical::new(model))),
+        ))
+    }
+}
+
+/// A Bernoulli distribution over the alphabet {0, 1}.
+///
+/// ## Model Parameter
+///
+/// The model parameter can either be specified as a scalar when constructing the model, or
+/// as a rank-1 numpy array with `dtype=np.float64` when calling the entropy coder's encode
+/// or decode method (see [discussion above](#concrete-models-vs-model-families)). Note
+/// that, in the latter case, you still have to *call* the constructor of the model, i.e.:
+/// `model_family = constriction.stream.model.Bernoulli()` --- note the trailing `()`.
+///
+/// - **p** --- the probability for the symbol being `1` rather than `0`. Must be between
+///   0.0 and 1.0 (both inclusive). Note that, even if you set `p = 0.0` or `p = 1.0`,
+///   `constriction` still assigns a tiny probability to the disallowed outcome so that both
+///   symbols `0` and `1` can always be encoded, albeit at a potentially large cost in
+///   bitrate.
+#[pyclass(extends=Model)]
+#[pyo3(text_signature = "(p=None)")]
+#[derive(Debug)]
+struct Bernoulli;
+
+#[pymethods]
+impl Bernoulli {
+    #[new]
+    pub fn new(p: Option<f64>) -> PyResult<(Self, Model)> {
+        let model = match p {
+            None => {
+                let model = internals::ParameterizableModel::new(move |(p,): (f64,)| {
+                    DefaultBernoulli::new(p).map_err(|()| BERNOULLI_ERROR)
+                });
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+            Some(p) => {
+                let model = DefaultBernoulli::new(p)
+                    .map_err(|()| pyo3::exceptions::PyValueError::new_err(BERNOULLI_ERROR))?;
+                Arc::new(model) as Arc<dyn internals::Model>
+            }
+        };
+        Ok((Self, Model(model)))
+    }
+}
+
+const BERNOULLI_ERROR: &str = "`p` must be >= 0.0 and <= 1.0.";
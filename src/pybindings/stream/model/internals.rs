@@ -1,14 +1,22 @@
-use core::{cell::RefCell, marker::PhantomData, num::NonZeroU32};
-use std::prelude::v1::*;
+use core::{cell::RefCell, convert::TryFrom, marker::PhantomData, num::NonZeroU32};
+use std::{collections::HashMap, prelude::v1::*};
 
-use alloc::vec;
+use alloc::{sync::Arc, vec};
+use ndarray::{ArrayView1, ArrayView2};
+use num::Float;
 use numpy::{PyArray1, PyReadonlyArray1, PyReadonlyArray2};
 use probability::distribution::{Distribution, Inverse};
 use pyo3::{prelude::*, types::PyTuple};
 
-use crate::stream::model::{
-    DecoderModel, DefaultContiguousCategoricalEntropyModel, EncoderModel, EntropyModel,
-    LeakyQuantizer, UniformModel,
+use crate::stream::{
+    markov::DefaultMarkovModel,
+    model::{
+        bernoulli::DefaultBernoulli, binomial::DefaultBinomial, dyn_precision::DynPrecisionModel,
+        geometric::DefaultGeometric, mixture::ContinuousMixture,
+        negative_binomial::DefaultNegativeBinomial, poisson::DefaultPoisson, zipf::DefaultZipf,
+        DecoderModel, DefaultContiguousCategoricalEntropyModel, EncoderModel, EntropyModel,
+        LeakyQuantizer, RoundingPolicy, UniformModel,
+    },
 };
 
 /// Workaround for the fact that rust for some reason cannot create
@@ -33,6 +41,60 @@ where
     }
 }
 
+/// Calls `f` with each symbol in the support of `model` and its probability, in order of
+/// increasing cumulative, by walking the quantile function.
+pub fn for_each_symbol(model: &dyn DefaultEntropyModel, mut f: impl FnMut(i32, NonZeroU32)) {
+    let mut quantile = 0u32;
+    while quantile < 1 << 24 {
+        let (symbol, left_cumulative, probability) = model.quantile_function(quantile);
+        f(symbol, probability);
+        quantile = left_cumulative + probability.get();
+    }
+}
+
+/// Returns the information content of `symbol` under `model` in bits, or infinity if
+/// `symbol` is not in the support of `model`.
+pub fn bit_cost(model: &dyn DefaultEntropyModel, symbol: i32) -> f64 {
+    EncoderDecoderModel(model).bit_cost(symbol)
+}
+
+/// Returns the cross entropy between `data_model` and `coding_model` in bits, i.e., the
+/// expected bit rate for encoding symbols distributed according to `data_model` with
+/// `coding_model` (which is the entropy of `data_model` if both models are the same).
+pub fn cross_entropy_base2(
+    data_model: &dyn DefaultEntropyModel,
+    coding_model: &dyn DefaultEntropyModel,
+) -> f64 {
+    let mut cross_entropy = 0.0;
+    for_each_symbol(data_model, |symbol, probability| {
+        let information_content = match coding_model.left_cumulative_and_probability(symbol) {
+            Some((_, other_probability)) => 24.0 - (other_probability.get() as f64).log2(),
+            None => f64::INFINITY,
+        };
+        cross_entropy += probability.get() as f64 * information_content;
+    });
+    cross_entropy / (1u32 << 24) as f64
+}
+
+/// Returns the Kullback-Leibler divergence from `coding_model` to `data_model` in bits.
+pub fn kl_divergence_base2(
+    data_model: &dyn DefaultEntropyModel,
+    coding_model: &dyn DefaultEntropyModel,
+) -> f64 {
+    let mut kl_divergence = 0.0;
+    for_each_symbol(data_model, |symbol, probability| {
+        let probability = probability.get() as f64;
+        let log_ratio = match coding_model.left_cumulative_and_probability(symbol) {
+            Some((_, other_probability)) => {
+                probability.log2() - (other_probability.get() as f64).log2()
+            }
+            None => f64::INFINITY,
+        };
+        kl_divergence += probability * log_ratio;
+    });
+    kl_divergence / (1u32 << 24) as f64
+}
+
 pub struct EncoderDecoderModel<M>(pub M);
 
 impl<'m, M> Clone for EncoderDecoderModel<&'m M>
@@ -109,12 +171,138 @@ pub trait Model: Send + Sync {
             "Model parameters were specified but the model is already fully parameterized.",
         ))
     }
+
+    fn at_precision(&self, _precision: usize) -> PyResult<Arc<dyn Model>> {
+        Err(pyo3::exceptions::PyValueError::new_err(
+            "The `precision` argument is only supported for `DynPrecisionCategorical` models.",
+        ))
+    }
+}
+
+/// Type of a per-symbol model parameter, i.e., `f64` for real-valued parameters and `i32`
+/// for integer parameters.
+pub trait Param: numpy::Element + Copy + Send + Sync {
+    /// Whether arrays of this parameter may also be provided with `dtype=np.float32`.
+    const ACCEPTS_F32: bool;
+
+    fn from_f32(x: f32) -> Self;
 }
 
+impl Param for f64 {
+    const ACCEPTS_F32: bool = true;
+
+    #[inline(always)]
+    fn from_f32(x: f32) -> Self {
+        x.into()
+    }
+}
+
+impl Param for i32 {
+    const ACCEPTS_F32: bool = false;
+
+    fn from_f32(_x: f32) -> Self {
+        unreachable!("Integer parameters are never read from float arrays.")
+    }
+}
+
+/// A rank-1 numpy array of model parameters.
+///
+/// Real-valued parameters may be provided with either `dtype=np.float64` or
+/// `dtype=np.float32`. We read `np.float32` arrays directly and convert each entry only when
+/// we use it, rather than requiring the caller to upcast the whole array (neural networks
+/// usually output `np.float32`, and upcasting would double the memory traffic).
+pub enum ParamArray<'py, T: Param> {
+    Native(PyReadonlyArray1<'py, T>),
+    F32(PyReadonlyArray1<'py, f32>),
+}
+
+impl<'py, T: Param> ParamArray<'py, T> {
+    pub fn extract(param: &'py PyAny) -> PyResult<Self> {
+        match param.extract() {
+            Ok(array) => Ok(Self::Native(array)),
+            Err(err) if T::ACCEPTS_F32 => param.extract().map(Self::F32).map_err(|_| err),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Native(array) => array.len(),
+            Self::F32(array) => array.len(),
+        }
+    }
+
+    pub fn view(&self) -> ParamView<'_, T> {
+        match self {
+            Self::Native(array) => ParamView::Native(array.as_array()),
+            Self::F32(array) => ParamView::F32(array.as_array()),
+        }
+    }
+}
+
+/// Borrowed view into a [`ParamArray`]. We use views rather than slices so that
+/// non-contiguous arrays work too.
+pub enum ParamView<'a, T> {
+    Native(ArrayView1<'a, T>),
+    F32(ArrayView1<'a, f32>),
+}
+
+impl<T: Param> ParamView<'_, T> {
+    #[inline(always)]
+    pub fn get(&self, index: usize) -> T {
+        match self {
+            Self::Native(view) => view[index],
+            Self::F32(view) => T::from_f32(view[index]),
+        }
+    }
+}
+
+/// A rank-2 numpy array of real-valued model parameters with either `dtype=np.float64` or
+/// `dtype=np.float32` (see [`ParamArray`]).
+pub enum FloatArray2<'py> {
+    F64(PyReadonlyArray2<'py, f64>),
+    F32(PyReadonlyArray2<'py, f32>),
+}
+
+impl<'py> FloatArray2<'py> {
+    pub fn extract(param: &'py PyAny) -> PyResult<Self> {
+        match param.extract() {
+            Ok(array) => Ok(Self::F64(array)),
+            Err(err) => param.extract().map(Self::F32).map_err(|_| err),
+        }
+    }
+
+    pub fn shape(&self) -> &[usize] {
+        match self {
+            Self::F64(array) => array.shape(),
+            Self::F32(array) => array.shape(),
+        }
+    }
+
+    /// Copies row `index` into `dst`, converting to `f64` if necessary.
+    fn copy_row(&self, index: usize, dst: &mut [f64]) {
+        fn copy<F: Float + Into<f64>>(src: ArrayView2<'_, F>, index: usize, dst: &mut [f64]) {
+            for (dst, &src) in dst.iter_mut().zip(src.row(index)) {
+                *dst = src.into();
+            }
+        }
+        match self {
+            Self::F64(array) => copy(array.as_array(), index, dst),
+            Self::F32(array) => copy(array.as_array(), index, dst),
+        }
+    }
+}
+
+/// A model family whose parameters are provided per symbol when encoding or decoding.
+///
+/// The closure `build_model` constructs the model for a single symbol. It returns an error
+/// message if the parameters are invalid, which `parameterize` raises as a `ValueError`
+/// that names the offending index (so that coders can roll back the entire call rather than
+/// panicking half way through it).
 pub struct ParameterizableModel<P, M, F>
 where
     M: DefaultEntropyModel,
-    F: Fn(P) -> M,
+    F: Fn(P) -> Result<M, &'static str>,
 {
     build_model: F,
     phantom: PhantomData<P>,
@@ -123,7 +311,7 @@ where
 impl<P, M, F> ParameterizableModel<P, M, F>
 where
     M: DefaultEntropyModel,
-    F: Fn(P) -> M,
+    F: Fn(P) -> Result<M, &'static str>,
 {
     pub fn new(build_model: F) -> Self {
         Self {
@@ -150,10 +338,10 @@ macro_rules! impl_model_for_parameterizable_model {
     {$expected_len: literal, $p0:ident: $ty0:tt $(, $ps:ident: $tys:tt)* $(,)?} => {
         impl<$ty0, $($tys,)* M, F> Model for ParameterizableModel<($ty0, $($tys,)*), M, F>
         where
-            $ty0: numpy::Element + Copy + Send + Sync,
-            $($tys: numpy::Element + Copy + Send + Sync,)*
+            $ty0: Param,
+            $($tys: Param,)*
             M: DefaultEntropyModel,
-            F: Fn(($ty0, $($tys,)*)) -> M + Send + Sync,
+            F: Fn(($ty0, $($tys,)*)) -> Result<M, &'static str> + Send + Sync,
         {
             fn parameterize(
                 &self,
@@ -170,38 +358,43 @@ macro_rules! impl_model_for_parameterizable_model {
                     )));
                 }
 
-                let $p0 = params[0].extract::<PyReadonlyArray1<'_, $ty0>>()?;
+                let $p0 = ParamArray::<$ty0>::extract(&params[0])?;
 
-                #[allow(unused_variables)] // (`len` remains unused when macro is invoked with only one parameter.)
                 let len = $p0.len();
                 $(
-                    let $ps = params[1].extract::<PyReadonlyArray1<'_, $tys>>()?;
+                    let $ps = ParamArray::<$tys>::extract(&params[1])?;
                     if $ps.len() != len {
-                        return Err(pyo3::exceptions::PyAttributeError::new_err(alloc::format!(
+                        return Err(pyo3::exceptions::PyValueError::new_err(
                             "Model parameters have unequal shape",
-                        )));
+                        ));
                     }
                 )*
 
-                if reverse{
-                    $(
-                        let mut $ps = $ps.as_slice()?.iter().rev();
-                    )*
-                    for &$p0 in $p0.as_slice()?.iter().rev() {
-                        $(
-                            let $ps = *$ps.next().expect("We checked that all params have same length.");
-                        )*
-                        callback(&(self.build_model)(($p0, $($ps,)*)))?;
+                // Obtain all views upfront so that the loops below don't have to check for
+                // errors.
+                let $p0 = $p0.view();
+                $(
+                    let $ps = $ps.view();
+                )*
+                let mut build_and_call = |index: usize| {
+                    let model = (self.build_model)(($p0.get(index), $($ps.get(index),)*))
+                        .map_err(|message| {
+                            pyo3::exceptions::PyValueError::new_err(alloc::format!(
+                                "Invalid model parameters at index {}: {}",
+                                index,
+                                message
+                            ))
+                        })?;
+                    callback(&model)
+                };
+
+                if reverse {
+                    for index in (0..len).rev() {
+                        build_and_call(index)?;
                     }
                 } else {
-                    $(
-                        let mut $ps = $ps.iter()?;
-                    )*
-                    for &$p0 in $p0.iter()? {
-                        $(
-                            let $ps = *$ps.next().expect("We checked that all params have same length.");
-                        )*
-                        callback(&(self.build_model)(($p0, $($ps,)*)))?;
+                    for index in 0..len {
+                        build_and_call(index)?;
                     }
                 }
 
@@ -209,7 +402,7 @@ macro_rules! impl_model_for_parameterizable_model {
             }
 
             fn len(&self, $p0: &PyAny) -> PyResult<usize> {
-                Ok($p0.extract::<PyReadonlyArray1<'_, $ty0>>()?.len())
+                Ok(ParamArray::<$ty0>::extract($p0)?.len())
             }
         }
     }
@@ -231,12 +424,19 @@ impl UnspecializedPythonModel {
         approximate_inverse_cdf: PyObject,
         min_symbol_inclusive: i32,
         max_symbol_inclusive: i32,
-    ) -> Self {
-        Self {
+    ) -> PyResult<Self> {
+        let quantizer = LeakyQuantizer::checked_new(min_symbol_inclusive..=max_symbol_inclusive)
+            .ok_or_else(|| {
+                pyo3::exceptions::PyValueError::new_err(
+                    "`max_symbol_inclusive` must be larger than `min_symbol_inclusive`, and the \
+                    support must contain fewer than 2**24 symbols.",
+                )
+            })?;
+        Ok(Self {
             cdf,
             approximate_inverse_cdf,
-            quantizer: LeakyQuantizer::new(min_symbol_inclusive..=max_symbol_inclusive),
-        }
+            quantizer,
+        })
     }
 }
 
@@ -246,14 +446,21 @@ impl Model for UnspecializedPythonModel {
         py: Python<'_>,
         callback: &mut dyn FnMut(&dyn DefaultEntropyModel) -> PyResult<()>,
     ) -> PyResult<()> {
+        // All symbols in an i.i.d. batch share the same model, so we memoize the (slow)
+        // calls into python across symbols. The quantizer only evaluates the CDF on
+        // half-integers within the support, which bounds the size of the cache.
         let mut value_and_params = [0.0f64];
         let distribution = SpecializedPythonDistribution {
             cdf: &self.cdf,
             approximate_inverse_cdf: &self.approximate_inverse_cdf,
             value_and_params: RefCell::new(&mut value_and_params),
+            cdf_cache: Some(RefCell::new(HashMap::new())),
+            error: RefCell::new(None),
             py,
         };
-        (callback)(&self.quantizer.quantize(distribution))
+        let model = self.quantizer.quantize(distribution);
+        let result = (callback)(&model);
+        model.into_inner().check(result)
     }
 
     fn parameterize<'py>(
@@ -264,76 +471,56 @@ impl Model for UnspecializedPythonModel {
         callback: &mut dyn FnMut(&dyn DefaultEntropyModel) -> PyResult<()>,
     ) -> PyResult<()> {
         let params = params.as_slice();
-        let p0 = params[0].extract::<PyReadonlyArray1<'_, f64>>()?;
+        let p0 = ParamArray::<f64>::extract(params[0])?;
         let len = p0.len();
+        let remaining_params = params[1..]
+            .iter()
+            .map(|&param| {
+                let param = ParamArray::<f64>::extract(param)?;
+                if param.len() != len {
+                    return Err(pyo3::exceptions::PyValueError::new_err(
+                        "Model parameters have unequal lengths.",
+                    ));
+                };
+                Ok(param)
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+
+        // Obtain all views upfront so that the loop below only reads plain `f64`s (this also
+        // works for non-contiguous arrays).
+        let p0 = p0.view();
+        let remaining_params = remaining_params
+            .iter()
+            .map(|param| param.view())
+            .collect::<Vec<_>>();
 
         let mut value_and_params = vec![0.0f64; params.len() + 1];
-        if reverse {
-            let mut remaining_params = params[1..]
-                .iter()
-                .map(|&param| {
-                    let param = param.extract::<&PyArray1<f64>>()?;
-                    if param.len() != len {
-                        return Err(pyo3::exceptions::PyAttributeError::new_err(alloc::format!(
-                            "Model parameters have unequal lengths.",
-                        )));
-                    };
-                    Ok(param
-                        .as_cell_slice()
-                        .map_err(|e| pyo3::exceptions::PyValueError::new_err(e.to_string()))?
-                        .iter()
-                        .rev())
-                })
-                .collect::<PyResult<Vec<_>>>()?;
-
-            for &p0 in p0.as_slice()?.iter().rev() {
-                value_and_params[1] = p0;
-                for (src, dst) in remaining_params.iter_mut().zip(&mut value_and_params[2..]) {
-                    *dst = src
-                        .next()
-                        .expect("We checked that all arrays have the same size.")
-                        .get();
-                }
+        let mut specialize_and_call = |index: usize| {
+            value_and_params[1] = p0.get(index);
+            for (src, dst) in remaining_params.iter().zip(&mut value_and_params[2..]) {
+                *dst = src.get(index);
+            }
 
-                let distribution = SpecializedPythonDistribution {
-                    cdf: &self.cdf,
-                    approximate_inverse_cdf: &self.approximate_inverse_cdf,
-                    value_and_params: RefCell::new(&mut value_and_params),
-                    py,
-                };
+            let distribution = SpecializedPythonDistribution {
+                cdf: &self.cdf,
+                approximate_inverse_cdf: &self.approximate_inverse_cdf,
+                value_and_params: RefCell::new(&mut value_and_params),
+                cdf_cache: None,
+                error: RefCell::new(None),
+                py,
+            };
+            let model = self.quantizer.quantize(distribution);
+            let result = (callback)(&model);
+            model.into_inner().check(result)
+        };
 
-                (callback)(&self.quantizer.quantize(distribution))?;
+        if reverse {
+            for index in (0..len).rev() {
+                specialize_and_call(index)?;
             }
         } else {
-            let mut remaining_params = params[1..]
-                .iter()
-                .map(|&param| {
-                    let param = param.extract::<PyReadonlyArray1<'_, f64>>()?;
-                    if param.len() != len {
-                        return Err(pyo3::exceptions::PyAttributeError::new_err(alloc::format!(
-                            "Model parameters have unequal lengths.",
-                        )));
-                    };
-                    param.iter()
-                })
-                .collect::<PyResult<Vec<_>>>()?;
-
-            for &p0 in p0.iter()? {
-                value_and_params[1] = p0;
-                for (src, dst) in remaining_params.iter_mut().zip(&mut value_and_params[2..]) {
-                    *dst = *src
-                        .next()
-                        .expect("We checked that all arrays have the same size.");
-                }
-
-                let distribution = SpecializedPythonDistribution {
-                    cdf: &self.cdf,
-                    approximate_inverse_cdf: &self.approximate_inverse_cdf,
-                    value_and_params: RefCell::new(&mut value_and_params),
-                    py,
-                };
-
-                (callback)(&self.quantizer.quantize(distribution))?;
+            for index in 0..len {
+                specialize_and_call(index)?;
             }
         }
 
@@ -341,7 +528,7 @@ impl Model for UnspecializedPythonModel {
     }
 
     fn len(&self, param0: &PyAny) -> PyResult<usize> {
-        Ok(param0.extract::<PyReadonlyArray1<'_, f64>>()?.len())
+        Ok(ParamArray::<f64>::extract(param0)?.len())
     }
 }
 
@@ -349,40 +536,262 @@ struct SpecializedPythonDistribution<'py, 'p> {
     cdf: &'py PyObject,
     approximate_inverse_cdf: &'py PyObject,
     value_and_params: RefCell<&'p mut [f64]>,
+
+    /// Maps the bit representation of `x` to `cdf(x)`. Only set if the distribution is
+    /// used for more than one symbol.
+    cdf_cache: Option<RefCell<HashMap<u64, f64>>>,
+
+    /// The first error raised by (or when converting the result of) a python callback.
+    ///
+    /// The traits `Distribution` and `Inverse` don't allow us to report errors, so we
+    /// remember the error and return it from [`check`](Self::check) once the coder is done
+    /// with the model. After an error, we no longer call into python and instead return
+    /// a CDF of `1.0` and an inverse CDF of NaN. This keeps the `LeakyQuantizer` from
+    /// panicking (the resulting garbage is discarded since the caller rolls back the coder
+    /// when we return an error).
+    error: RefCell<Option<PyErr>>,
+
     py: Python<'py>,
 }
 
-impl<'py, 'p> Distribution for SpecializedPythonDistribution<'py, 'p> {
-    type Value = f64;
+impl SpecializedPythonDistribution<'_, '_> {
+    /// Calls `callback` with the current value and parameters, or returns `None` if this or
+    /// any previous call failed.
+    fn call(&self, callback: &PyObject, name: &str) -> Option<f64> {
+        if self.error.borrow().is_some() {
+            return None;
+        }
 
-    fn distribution(&self, x: f64) -> f64 {
-        self.value_and_params.borrow_mut()[0] = x;
-        self.cdf
+        let result = callback
             .call1(
                 self.py,
                 PyTuple::new(self.py, &**self.value_and_params.borrow()),
             )
-            .expect("TODO")
-            .extract::<f64>(self.py)
-            .expect("TODO")
+            .and_then(|value| {
+                value.extract::<f64>(self.py).map_err(|_| {
+                    pyo3::exceptions::PyTypeError::new_err(alloc::format!(
+                        "The python callback `{}` must return a float.",
+                        name
+                    ))
+                })
+            });
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                *self.error.borrow_mut() = Some(err);
+                None
+            }
+        }
+    }
+
+    /// Returns `result` unless a python callback failed, in which case it returns the
+    /// error raised by the callback.
+    fn check<T>(self, result: PyResult<T>) -> PyResult<T> {
+        match self.error.into_inner() {
+            Some(err) => Err(err),
+            None => result,
+        }
+    }
+}
+
+impl<'py, 'p> Distribution for SpecializedPythonDistribution<'py, 'p> {
+    type Value = f64;
+
+    fn distribution(&self, x: f64) -> f64 {
+        if let Some(cache) = &self.cdf_cache {
+            if let Some(&value) = cache.borrow().get(&x.to_bits()) {
+                return value;
+            }
+        }
+
+        self.value_and_params.borrow_mut()[0] = x;
+        let value = match self.call(self.cdf, "cdf") {
+            Some(value) => value,
+            None => return 1.0,
+        };
+
+        if let Some(cache) = &self.cdf_cache {
+            cache.borrow_mut().insert(x.to_bits(), value);
+        }
+        value
     }
 }
 
 impl<'py, 'p> Inverse for SpecializedPythonDistribution<'py, 'p> {
     fn inverse(&self, xi: f64) -> f64 {
         self.value_and_params.borrow_mut()[0] = xi;
-        self.approximate_inverse_cdf
-            .call1(
-                self.py,
-                PyTuple::new(self.py, &**self.value_and_params.borrow()),
-            )
-            .expect("TODO")
-            .extract::<f64>(self.py)
-            .expect("TODO")
+        self.call(self.approximate_inverse_cdf, "approximate_inverse_cdf")
+            .unwrap_or(f64::NAN)
     }
 }
 
-pub struct UnparameterizedCategoricalDistribution;
+/// Checks that a (vectorized) python callback `cdf` is a valid CDF on the grid on which the
+/// `LeakyQuantizer` used by `UnspecializedPythonModel` evaluates it.
+///
+/// The quantizer only ever evaluates the CDF on half-integers strictly between
+/// `min_symbol_inclusive` and `max_symbol_inclusive`, and it rounds each value down to
+/// fixed point precision before comparing neighboring values. So we check exactly that: all
+/// values on the grid must lie in `[0.0, 1.0]`, and their fixed point representations must
+/// be nondecreasing. Tiny non-monotonicities that vanish in the rounding are harmless.
+pub fn validate_cdf(
+    py: Python<'_>,
+    cdf: &PyObject,
+    min_symbol_inclusive: i32,
+    max_symbol_inclusive: i32,
+    params: &PyTuple,
+) -> PyResult<()> {
+    const CHUNK_SIZE: i64 = 1 << 16;
+    const MAX_REPORTED: usize = 10;
+
+    let num_points = max_symbol_inclusive as i64 - min_symbol_inclusive as i64;
+    if num_points <= 0 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "`max_symbol_inclusive` must be larger than `min_symbol_inclusive`.",
+        ));
+    } else if num_points >= 1 << 24 {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "The support is too large to assign a nonzero probability to each symbol.",
+        ));
+    }
+    // Same as the `free_weight` of a `LeakyQuantizer<f64, i32, u32, 24>`.
+    let free_weight = ((1u32 << 24) - 1 - num_points as u32) as f64;
+
+    let mut problems = Vec::<CdfProblemInterval>::new();
+    let mut previous = None;
+    let mut chunk_start = 0;
+    while chunk_start < num_points {
+        let chunk_end = (chunk_start + CHUNK_SIZE).min(num_points);
+        let xs = (chunk_start..chunk_end)
+            .map(|i| (min_symbol_inclusive as i64 + i) as f64 + 0.5)
+            .collect::<Vec<_>>();
+        let args = core::iter::once(PyArray1::from_slice(py, &xs).to_object(py))
+            .chain(params.iter().map(|param| param.to_object(py)))
+            .collect::<Vec<_>>();
+        let values = cdf.call1(py, PyTuple::new(py, args))?;
+        let values = values.extract::<PyReadonlyArray1<'_, f64>>(py)?;
+        let values = values.as_slice()?;
+        if values.len() != xs.len() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "The CDF must be vectorized, i.e., it must return an array of the same shape as \
+                its argument.",
+            ));
+        }
+
+        for (&x, &value) in xs.iter().zip(values) {
+            if !(0.0..=1.0).contains(&value) {
+                CdfProblem::OutOfRange.record(&mut problems, (x, value));
+                previous = None;
+                continue;
+            }
+            let quantized = (free_weight * value) as u32;
+            if let Some((previous_x, previous_value, previous_quantized)) = previous {
+                if quantized < previous_quantized {
+                    CdfProblem::Decreasing.record_step(
+                        &mut problems,
+                        (previous_x, previous_value),
+                        (x, value),
+                    );
+                }
+            }
+            previous = Some((x, value, quantized));
+        }
+
+        chunk_start = chunk_end;
+    }
+
+    if problems.is_empty() {
+        return Ok(());
+    }
+
+    let mut message = alloc::format!(
+        "Invalid CDF: the CDF must be nondecreasing and between 0.0 and 1.0 (inclusive) \
+        on all points x = {} + 0.5, ..., {} - 0.5 (found {} problem(s) at 24 bit precision):",
+        min_symbol_inclusive,
+        max_symbol_inclusive,
+        problems.len()
+    );
+    for problem in problems.iter().take(MAX_REPORTED) {
+        message.push_str("\n  - ");
+        message.push_str(&problem.to_string());
+    }
+    if problems.len() > MAX_REPORTED {
+        message.push_str(&alloc::format!(
+            "\n  ... and {} more.",
+            problems.len() - MAX_REPORTED
+        ));
+    }
+    Err(pyo3::exceptions::PyValueError::new_err(message))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CdfProblem {
+    OutOfRange,
+    Decreasing,
+}
+
+/// A maximal interval (in terms of `(x, cdf(x))` at its boundaries) on which the CDF
+/// exhibits the same problem.
+#[derive(Debug)]
+struct CdfProblemInterval {
+    kind: CdfProblem,
+    start: (f64, f64),
+    end: (f64, f64),
+}
+
+impl CdfProblem {
+    /// Records an out-of-range value at a single grid point, merging it with the previous
+    /// problem interval if that ended at the preceding grid point.
+    fn record(self, problems: &mut Vec<CdfProblemInterval>, point: (f64, f64)) {
+        match problems.last_mut() {
+            Some(last) if last.kind == self && last.end.0 + 1.0 == point.0 => last.end = point,
+            _ => problems.push(CdfProblemInterval {
+                kind: self,
+                start: point,
+                end: point,
+            }),
+        }
+    }
+
+    /// Records a decreasing step from `from` to `to`, merging it with the previous problem
+    /// interval if that ended at `from`.
+    fn record_step(self, problems: &mut Vec<CdfProblemInterval>, from: (f64, f64), to: (f64, f64)) {
+        match problems.last_mut() {
+            Some(last) if last.kind == self && last.end.0 == from.0 => last.end = to,
+            _ => problems.push(CdfProblemInterval {
+                kind: self,
+                start: from,
+                end: to,
+            }),
+        }
+    }
+}
+
+impl core::fmt::Display for CdfProblemInterval {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.kind {
+            CdfProblem::OutOfRange if self.start.0 == self.end.0 => write!(
+                f,
+                "cdf({}) = {} is not between 0.0 and 1.0",
+                self.start.0, self.start.1
+            ),
+            CdfProblem::OutOfRange => write!(
+                f,
+                "cdf(x) is not between 0.0 and 1.0 for all x from {} to {} \
+                (cdf({}) = {}, cdf({}) = {})",
+                self.start.0, self.end.0, self.start.0, self.start.1, self.end.0, self.end.1
+            ),
+            CdfProblem::Decreasing => write!(
+                f,
+                "cdf(x) decreases between x = {} and x = {} (from {} to {})",
+                self.start.0, self.end.0, self.start.1, self.end.1
+            ),
+        }
+    }
+}
+
+pub struct UnparameterizedCategoricalDistribution {
+    pub rounding: RoundingPolicy,
+}
 
 impl Model for UnparameterizedCategoricalDistribution {
     fn parameterize(
@@ -405,37 +814,172 @@ impl Model for UnparameterizedCategoricalDistribution {
             )));
         }
 
-        let probabilities = params[0].extract::<PyReadonlyArray2<'_, f64>>()?;
-        let range = probabilities.shape()[1];
-        let probabilities = probabilities.as_slice()?;
+        // Build the models directly from `np.float32` rows (if provided as such) rather
+        // than converting them to `f64` first.
+        match FloatArray2::extract(&params[0])? {
+            FloatArray2::F64(probabilities) => {
+                self.parameterize_with(probabilities.as_array(), reverse, callback)
+            }
+            FloatArray2::F32(probabilities) => {
+                self.parameterize_with(probabilities.as_array(), reverse, callback)
+            }
+        }
+    }
+
+    fn len(&self, param0: &PyAny) -> PyResult<usize> {
+        Ok(FloatArray2::extract(param0)?.shape()[0])
+    }
+}
+
+impl UnparameterizedCategoricalDistribution {
+    fn parameterize_with<F>(
+        &self,
+        probabilities: ArrayView2<'_, F>,
+        reverse: bool,
+        callback: &mut dyn FnMut(&dyn DefaultEntropyModel) -> PyResult<()>,
+    ) -> PyResult<()>
+    where
+        F: Float + core::iter::Sum<F> + Into<f64>,
+    {
+        // Borrow rows that are contiguous in memory and copy all other rows into a reusable
+        // buffer, so that non-contiguous arrays (e.g., `matrix[:, :5]`) work too.
+        let mut buf = vec![F::zero(); probabilities.shape()[1]];
+        let mut build_and_call = |index: usize| {
+            let row = probabilities.row(index);
+            let row = match row.as_slice() {
+                Some(row) => row,
+                None => {
+                    for (dst, &src) in buf.iter_mut().zip(row) {
+                        *dst = src;
+                    }
+                    &buf[..]
+                }
+            };
+            callback(&categorical_from_probabilities(row, self.rounding)?)
+        };
 
+        let len = probabilities.shape()[0];
         if reverse {
-            for probabilities in probabilities.chunks_exact(range).rev() {
-                let model =
-                    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
-                        probabilities,
-                    )
-                    .map_err(|()| {
-                        pyo3::exceptions::PyValueError::new_err(
-                        "Probability distribution not normalizable (the array of probabilities\n\
-                        might be empty, contain negative values or NaNs, or sum to infinity).",
-                    )
-                    })?;
-                callback(&model)?;
+            for index in (0..len).rev() {
+                build_and_call(index)?;
             }
         } else {
-            for probabilities in probabilities.chunks_exact(range) {
-                let model =
-                    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
-                        probabilities,
-                    )
-                    .map_err(|()| {
-                        pyo3::exceptions::PyValueError::new_err(
-                        "Probability distribution not normalizable (the array of probabilities\n\
-                        might be empty, contain negative values or NaNs, or sum to infinity).",
-                    )
-                    })?;
-                callback(&model)?;
+            for index in 0..len {
+                build_and_call(index)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Builds a categorical model from floating point probabilities with either `F = f64` or
+/// `F = f32` (see [`ParamArray`]).
+pub fn categorical_from_probabilities<F>(
+    probabilities: &[F],
+    rounding: RoundingPolicy,
+) -> PyResult<DefaultContiguousCategoricalEntropyModel>
+where
+    F: Float + core::iter::Sum<F> + Into<f64>,
+{
+    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+        probabilities,
+        rounding,
+    )
+    .map_err(|()| {
+        pyo3::exceptions::PyValueError::new_err(
+            "Probability distribution not normalizable (the array of probabilities\n\
+            might be empty, contain negative values or NaNs, or sum to infinity).",
+        )
+    })
+}
+
+/// Signature of the constructors `GaussianMixture::new` and `LogisticMixture::new`.
+type BuildMixture<D> = fn(&[f64], &[f64], &[f64]) -> Result<ContinuousMixture<D>, ()>;
+
+/// A family of mixture models whose parameters are provided as rank-2 arrays, with one row
+/// per symbol and one column per mixture component.
+pub struct UnparameterizedMixture<D> {
+    quantizer: LeakyQuantizer<f64, i32, u32, 24>,
+    build_mixture: BuildMixture<D>,
+
+    /// Name of the third model parameter, i.e., "stds" or "scales" (for error messages).
+    scale_name: &'static str,
+}
+
+impl<D> UnparameterizedMixture<D> {
+    pub fn new(
+        quantizer: LeakyQuantizer<f64, i32, u32, 24>,
+        build_mixture: BuildMixture<D>,
+        scale_name: &'static str,
+    ) -> Self {
+        Self {
+            quantizer,
+            build_mixture,
+            scale_name,
+        }
+    }
+}
+
+impl<D> Model for UnparameterizedMixture<D>
+where
+    D: Distribution<Value = f64> + Inverse + Send + Sync,
+{
+    fn parameterize(
+        &self,
+        _py: Python<'_>,
+        params: &PyTuple,
+        reverse: bool,
+        callback: &mut dyn FnMut(&dyn DefaultEntropyModel) -> PyResult<()>,
+    ) -> PyResult<()> {
+        if params.len() != 3 {
+            return Err(pyo3::exceptions::PyAttributeError::new_err(alloc::format!(
+                "Wrong number of model parameters: expected 3 (weights, means, and {}), got {}.",
+                self.scale_name,
+                params.len()
+            )));
+        }
+
+        let weights = FloatArray2::extract(&params[0])?;
+        let means = FloatArray2::extract(&params[1])?;
+        let scales = FloatArray2::extract(&params[2])?;
+        if weights.shape() != means.shape() || weights.shape() != scales.shape() {
+            return Err(pyo3::exceptions::PyAttributeError::new_err(
+                "Model parameters have unequal shape",
+            ));
+        }
+
+        // Copy each row into contiguous buffers so that non-contiguous arrays work too.
+        let num_components = weights.shape()[1];
+        let mut buf = vec![0.0; 3 * num_components];
+        let mut build_and_call = |index: usize| {
+            let (weights_buf, rest) = buf.split_at_mut(num_components);
+            let (means_buf, scales_buf) = rest.split_at_mut(num_components);
+            weights.copy_row(index, weights_buf);
+            means.copy_row(index, means_buf);
+            scales.copy_row(index, scales_buf);
+
+            let mixture =
+                (self.build_mixture)(weights_buf, means_buf, scales_buf).map_err(|()| {
+                    pyo3::exceptions::PyValueError::new_err(alloc::format!(
+                        "Invalid mixture parameters for symbol {} (each row needs at least one\n\
+                    component, nonnegative weights with a finite nonzero sum, finite means,\n\
+                    and positive finite {}).",
+                        index,
+                        self.scale_name
+                    ))
+                })?;
+            callback(&self.quantizer.quantize(mixture))
+        };
+
+        let len = weights.shape()[0];
+        if reverse {
+            for index in (0..len).rev() {
+                build_and_call(index)?;
+            }
+        } else {
+            for index in 0..len {
+                build_and_call(index)?;
             }
         }
 
@@ -443,7 +987,82 @@ impl Model for UnparameterizedCategoricalDistribution {
     }
 
     fn len(&self, param0: &PyAny) -> PyResult<usize> {
-        Ok(param0.extract::<PyReadonlyArray2<'_, f64>>()?.shape()[0])
+        Ok(FloatArray2::extract(param0)?.shape()[0])
+    }
+}
+
+/// Returns the row of `markov` for a symbol that follows `previous`, where `previous = -1`
+/// selects the initial distribution.
+fn markov_row(
+    markov: &DefaultMarkovModel,
+    previous: i32,
+) -> PyResult<&DefaultContiguousCategoricalEntropyModel> {
+    if previous == -1 {
+        Ok(markov.initial_model())
+    } else if previous >= 0 && (previous as usize) < markov.alphabet_size() {
+        Ok(markov.transition_model(previous as usize))
+    } else {
+        Err(pyo3::exceptions::PyValueError::new_err(alloc::format!(
+            "Symbol {} is not in the alphabet of the Markov model.",
+            previous
+        )))
+    }
+}
+
+/// A single row of a `MarkovModel`, i.e., a concrete entropy model.
+pub struct MarkovRow {
+    markov: Arc<DefaultMarkovModel>,
+    previous: i32,
+}
+
+impl MarkovRow {
+    pub fn new(markov: Arc<DefaultMarkovModel>, previous: i32) -> PyResult<Self> {
+        markov_row(&markov, previous)?;
+        Ok(Self { markov, previous })
+    }
+}
+
+impl Model for MarkovRow {
+    fn as_parameterized(
+        &self,
+        _py: Python<'_>,
+        callback: &mut dyn FnMut(&dyn DefaultEntropyModel) -> PyResult<()>,
+    ) -> PyResult<()> {
+        callback(markov_row(&self.markov, self.previous)?)
+    }
+}
+
+/// All rows of a `MarkovModel`, i.e., a model family whose single model parameter is the
+/// preceding symbol of each symbol (with `-1` for the first symbol of a sequence).
+pub struct MarkovTransitions(pub Arc<DefaultMarkovModel>);
+
+impl Model for MarkovTransitions {
+    fn parameterize(
+        &self,
+        _py: Python<'_>,
+        params: &PyTuple,
+        reverse: bool,
+        callback: &mut dyn FnMut(&dyn DefaultEntropyModel) -> PyResult<()>,
+    ) -> PyResult<()> {
+        if params.len() != 1 {
+            return Err(pyo3::exceptions::PyAttributeError::new_err(alloc::format!(
+                "Wrong number of model parameters: expected 1, got {}.",
+                params.len()
+            )));
+        }
+
+        let previous = params[0].extract::<PyReadonlyArray1<'_, i32>>()?;
+        let previous = previous.as_array();
+        let mut process = |&previous: &i32| callback(markov_row(&self.0, previous)?);
+        if reverse {
+            previous.iter().rev().try_for_each(&mut process)
+        } else {
+            previous.iter().try_for_each(&mut process)
+        }
+    }
+
+    fn len(&self, param0: &PyAny) -> PyResult<usize> {
+        Ok(param0.extract::<PyReadonlyArray1<'_, i32>>()?.len())
     }
 }
 
@@ -461,6 +1080,94 @@ impl DefaultEntropyModel for DefaultContiguousCategoricalEntropyModel {
     }
 }
 
+impl DefaultEntropyModel for DefaultBernoulli {
+    #[inline]
+    fn left_cumulative_and_probability(&self, symbol: i32) -> Option<(u32, NonZeroU32)> {
+        match symbol {
+            0 => EncoderModel::left_cumulative_and_probability(self, false),
+            1 => EncoderModel::left_cumulative_and_probability(self, true),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn quantile_function(&self, quantile: u32) -> (i32, u32, NonZeroU32) {
+        let (symbol, left_cumulative, probability) =
+            DecoderModel::quantile_function(self, quantile);
+        (symbol as i32, left_cumulative, probability)
+    }
+}
+
+impl DefaultEntropyModel for DefaultBinomial {
+    #[inline]
+    fn left_cumulative_and_probability(&self, symbol: i32) -> Option<(u32, NonZeroU32)> {
+        EncoderModel::left_cumulative_and_probability(self, symbol as usize)
+    }
+
+    #[inline]
+    fn quantile_function(&self, quantile: u32) -> (i32, u32, NonZeroU32) {
+        let (symbol, left_cumulative, probability) =
+            DecoderModel::quantile_function(self, quantile);
+        (symbol as i32, left_cumulative, probability)
+    }
+}
+
+impl DefaultEntropyModel for DefaultPoisson {
+    #[inline]
+    fn left_cumulative_and_probability(&self, symbol: i32) -> Option<(u32, NonZeroU32)> {
+        EncoderModel::left_cumulative_and_probability(self, symbol as usize)
+    }
+
+    #[inline]
+    fn quantile_function(&self, quantile: u32) -> (i32, u32, NonZeroU32) {
+        let (symbol, left_cumulative, probability) =
+            DecoderModel::quantile_function(self, quantile);
+        (symbol as i32, left_cumulative, probability)
+    }
+}
+
+impl DefaultEntropyModel for DefaultNegativeBinomial {
+    #[inline]
+    fn left_cumulative_and_probability(&self, symbol: i32) -> Option<(u32, NonZeroU32)> {
+        EncoderModel::left_cumulative_and_probability(self, symbol as usize)
+    }
+
+    #[inline]
+    fn quantile_function(&self, quantile: u32) -> (i32, u32, NonZeroU32) {
+        let (symbol, left_cumulative, probability) =
+            DecoderModel::quantile_function(self, quantile);
+        (symbol as i32, left_cumulative, probability)
+    }
+}
+
+impl DefaultEntropyModel for DefaultGeometric {
+    #[inline]
+    fn left_cumulative_and_probability(&self, symbol: i32) -> Option<(u32, NonZeroU32)> {
+        EncoderModel::left_cumulative_and_probability(self, symbol as usize)
+    }
+
+    #[inline]
+    fn quantile_function(&self, quantile: u32) -> (i32, u32, NonZeroU32) {
+        let (symbol, left_cumulative, probability) =
+            DecoderModel::quantile_function(self, quantile);
+        (symbol as i32, left_cumulative, probability)
+    }
+}
+
+impl DefaultEntropyModel for DefaultZipf {
+    #[inline]
+    fn left_cumulative_and_probability(&self, symbol: i32) -> Option<(u32, NonZeroU32)> {
+        EncoderModel::left_cumulative_and_probability(self, symbol as usize)
+    }
+
+    #[inline]
+    fn quantile_function(&self, quantile: u32) -> (i32, u32, NonZeroU32) {
+        let (symbol, left_cumulative, probability) =
+            DecoderModel::quantile_function(self, quantile);
+        (symbol as i32, left_cumulative, probability)
+    }
+}
+
 impl DefaultEntropyModel for UniformModel<u32, 24> {
     #[inline]
     fn left_cumulative_and_probability(&self, symbol: i32) -> Option<(u32, NonZeroU32)> {
@@ -474,3 +1181,79 @@ impl DefaultEntropyModel for UniformModel<u32, 24> {
         (symbol as i32, left_cumulative, probability)
     }
 }
+
+/// A [`DynPrecisionModel`] presented at `precision <= 24` bits.
+///
+/// The entropy coders in the python API all use 24 bit precision, so we scale all
+/// re-rounded cumulatives up by `2^(24 - precision)`. This doesn't change the information
+/// content of any symbol.
+#[derive(Debug)]
+pub struct DynPrecisionCategorical {
+    model: Arc<DynPrecisionModel>,
+    precision: usize,
+}
+
+impl DynPrecisionCategorical {
+    pub fn new(model: DynPrecisionModel) -> Self {
+        Self {
+            model: Arc::new(model),
+            precision: 24,
+        }
+    }
+}
+
+impl Model for DynPrecisionCategorical {
+    fn as_parameterized(
+        &self,
+        _py: Python<'_>,
+        callback: &mut dyn FnMut(&dyn DefaultEntropyModel) -> PyResult<()>,
+    ) -> PyResult<()> {
+        (callback)(&ScaledDynPrecisionModel(self))
+    }
+
+    fn at_precision(&self, precision: usize) -> PyResult<Arc<dyn Model>> {
+        let min_precision = self.model.min_precision();
+        if precision < min_precision || precision > 24 {
+            return Err(pyo3::exceptions::PyValueError::new_err(alloc::format!(
+                "`precision` must be between {} (for {} symbols) and 24.",
+                min_precision,
+                self.model.num_symbols()
+            )));
+        }
+
+        Ok(Arc::new(Self {
+            model: Arc::clone(&self.model),
+            precision,
+        }))
+    }
+}
+
+struct ScaledDynPrecisionModel<'m>(&'m DynPrecisionCategorical);
+
+impl DefaultEntropyModel for ScaledDynPrecisionModel<'_> {
+    #[inline]
+    fn left_cumulative_and_probability(&self, symbol: i32) -> Option<(u32, NonZeroU32)> {
+        let DynPrecisionCategorical { model, precision } = self.0;
+        let shift = 24 - precision;
+        let (left_cumulative, probability) =
+            model.left_cumulative_and_probability_at(usize::try_from(symbol).ok()?, *precision)?;
+        let probability = NonZeroU32::new((probability.get() as u32) << shift)
+            .expect("probability < 2^precision");
+        Some(((left_cumulative as u32) << shift, probability))
+    }
+
+    #[inline]
+    fn quantile_function(&self, quantile: u32) -> (i32, u32, NonZeroU32) {
+        let DynPrecisionCategorical { model, precision } = self.0;
+        let shift = 24 - precision;
+        let (symbol, left_cumulative, probability) =
+            model.quantile_function_at((quantile >> shift) as u64, *precision);
+        let probability = NonZeroU32::new((probability.get() as u32) << shift)
+            .expect("probability < 2^precision");
+        (
+            symbol as i32,
+            (left_cumulative as u32) << shift,
+            probability,
+        )
+    }
+}
@@ -2,7 +2,12 @@ use std::prelude::v1::*;
 
 use numpy::{PyArray1, PyReadonlyArray1};
 use probability::distribution::Gaussian;
-use pyo3::{prelude::*, types::PyTuple};
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyTuple},
+    PyIterProtocol,
+};
+use std::sync::Arc;
 
 use crate::{
     stream::{
@@ -13,11 +18,19 @@ use crate::{
     Pos, Seek, UnwrapInfallible,
 };
 
-use super::model::{internals::EncoderDecoderModel, Model};
+use super::{
+    decode_symbol,
+    model::{
+        internals::{self, EncoderDecoderModel},
+        Model,
+    },
+    validate_symbols, DecodedSymbols, Symbols,
+};
 
 pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
     module.add_class::<RangeEncoder>()?;
     module.add_class::<RangeDecoder>()?;
+    module.add_class::<RangeDecoderIter>()?;
     Ok(())
 }
 
@@ -52,6 +65,14 @@ impl RangeEncoder {
         Self { inner }
     }
 
+    /// The configuration of this coder, i.e., a dictionary with the entries `"word_size"`,
+    /// `"state_size"`, and `"precision"` (all in bits). Equal to
+    /// `constriction.info()["coders"]["RangeEncoder"]`.
+    #[getter]
+    pub fn config<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        super::coder_config::<u32, u64>(py)
+    }
+
     /// Resets the encoder to an empty state.
     ///
     /// This removes any existing compressed data on the coder. It is equivalent to replacing the
@@ -96,8 +117,9 @@ impl RangeEncoder {
     /// This is 32 times the result of what [`num_words`](#constriction.stream.queue.RangeEncoder.num_words)
     /// would return.
     #[pyo3(text_signature = "()")]
-    pub fn num_bits(&self) -> usize {
-        self.inner.num_bits()
+    pub fn num_bits(&self) -> u64 {
+        // Widen before multiplying so that this doesn't overflow on 32-bit platforms.
+        self.inner.num_words() as u64 * 32
     }
 
     /// Returns `True` iff the coder is in its default initial state.
@@ -152,6 +174,19 @@ impl RangeEncoder {
         PyArray1::from_slice(py, &*self.inner.get_compressed())
     }
 
+    /// Same as [`get_compressed`](#constriction.stream.queue.RangeEncoder.get_compressed), but
+    /// appends a CRC-32 checksum to the returned compressed data.
+    ///
+    /// Pass the returned data through
+    /// [`constriction.stream.verify_checksum`](../stream.html#constriction.stream.verify_checksum)
+    /// before decoding it to detect corrupted data instead of silently decoding garbage. Like
+    /// `get_compressed`, this method has no side effects on the encoder.
+    #[pyo3(text_signature = "()")]
+    pub fn seal_with_checksum<'p>(&mut self, py: Python<'p>) -> &'p PyArray1<u32> {
+        let compressed = self.inner.get_compressed().to_vec();
+        PyArray1::from_vec(py, crate::stream::checksum::seal_with_checksum(compressed))
+    }
+
     /// Returns a `RangeDecoder` that is initialized with a copy of the compressed data currently on
     /// this `RangeEncoder`.
     ///
@@ -245,8 +280,8 @@ impl RangeEncoder {
         }
 
         let quantizer = DefaultLeakyQuantizer::new(min_supported_symbol..=max_supported_symbol);
-        self.inner
-            .try_encode_symbols(symbols.iter().zip(means.iter()).zip(stds.iter()).map(
+        self.encode_transactionally(|inner| {
+            inner.try_encode_symbols(symbols.iter().zip(means.iter()).zip(stds.iter()).map(
                 |((&symbol, &mean), &std)| {
                     if std > 0.0 && std.is_finite() && mean.is_finite() {
                         Ok((symbol, quantizer.quantize(Gaussian::new(mean, std))))
@@ -255,8 +290,8 @@ impl RangeEncoder {
                     }
                 },
             ))?;
-
-        Ok(())
+            Ok(())
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -321,19 +356,24 @@ impl RangeEncoder {
             )
         })?;
 
-        self.inner.encode_iid_symbols(
-            symbols
-                .as_slice()?
-                .iter()
-                .map(|s| s.wrapping_sub(min_supported_symbol) as usize),
-            &model,
-        )?;
-
-        Ok(())
+        let symbols = symbols.as_slice()?;
+        self.encode_transactionally(|inner| {
+            inner.encode_iid_symbols(
+                symbols
+                    .iter()
+                    .map(|s| s.wrapping_sub(min_supported_symbol) as usize),
+                &model,
+            )?;
+            Ok(())
+        })
     }
 
     /// Encodes one or more symbols, appending them to the encapsulated compressed data.
     ///
+    /// If encoding fails for any symbol (e.g., because the symbol has zero probability under
+    /// its entropy model), then this method raises an exception and leaves the encoder
+    /// unchanged, i.e., none of the provided symbols get encoded.
+    ///
     /// This method can be called in 3 different ways:
     ///
     /// ## Option 1: encode(symbol, model)
@@ -375,6 +415,15 @@ impl RangeEncoder {
     /// print(encoder.get_compressed()) # (prints: [369323576])
     /// ```
     ///
+    /// Instead of a numpy array, `symbols` may also be a plain Python list or tuple of ints,
+    /// e.g., `encoder.encode([0, 2, 1, 2, 0, 2, 0, 2, 1], model)`. The list gets converted
+    /// internally, which raises an `OverflowError` if any symbol is out of the range of a
+    /// 32-bit signed integer. This also works for the `symbols` argument in Option 3 below.
+    ///
+    /// Numpy arrays of dtype `np.int16` or `np.uint8` are accepted as well and are read without
+    /// widening them to a temporary `np.int32` copy, which saves memory for large messages over
+    /// small alphabets (e.g., pixel values of images).
+    ///
     /// ## Option 3: encode(symbols, model_family, params1, params2, ...)
     ///
     /// Encodes multiple symbols, using the same *family* of entropy models (e.g., categorical or
@@ -422,15 +471,32 @@ impl RangeEncoder {
     /// encoder.encode(symbols, model_family, probabilities)
     /// print(encoder.get_compressed()) # (prints: [2705829535])
     /// ```
-    #[pyo3(text_signature = "(symbols, model, optional_model_params)")]
-    #[args(symbols, model, params = "*")]
+    ///
+    /// If you set `validate=True`, then this method first checks that all `symbols` lie in
+    /// the support of their respective entropy models (at the cost of constructing each
+    /// entropy model twice). If any symbols don't, then it raises a `KeyError` that lists the
+    /// indices of the first few offending symbols, and it doesn't encode anything.
+    ///
+    /// If `model` is a
+    /// [`DynPrecisionCategorical`](model.html#constriction.stream.model.DynPrecisionCategorical)
+    /// then you can set `precision` to an integer between the model's minimum precision and
+    /// 24 to encode with the model's probabilities re-rounded to that many bits. The decoder
+    /// has to pass the same `precision`. Setting `precision` for any other model raises a
+    /// `ValueError`.
+    #[pyo3(
+        text_signature = "(symbols, model, optional_model_params, validate=False, precision=None)"
+    )]
+    #[args(symbols, model, params = "*", validate = "false", precision = "None")]
     pub fn encode(
         &mut self,
         py: Python<'_>,
         symbols: &PyAny,
         model: &Model,
         params: &PyTuple,
+        validate: bool,
+        precision: Option<usize>,
     ) -> PyResult<()> {
+        let model = &model.at_precision(precision)?;
         // TODO: also allow encoding and decoding with model type instead of instance for
         // models that take no range.
         if let Ok(symbol) = symbols.extract::<i32>() {
@@ -452,33 +518,82 @@ impl RangeEncoder {
             });
         }
 
-        // Don't use an `else` branch here because, if the following `extract` fails, the returned
-        // error message is actually pretty user friendly.
-        let symbols = symbols.extract::<PyReadonlyArray1<'_, i32>>()?;
-        let symbols = symbols.as_slice()?;
+        let symbols = Symbols::extract(py, symbols)?;
+        let symbols = symbols.iter();
+        if validate {
+            validate_symbols(py, symbols.clone(), model, params)?;
+        }
 
         if params.is_empty() {
-            model.0.as_parameterized(py, &mut |model| {
-                self.inner
-                    .encode_iid_symbols(symbols, EncoderDecoderModel(model))?;
-                Ok(())
-            })?;
+            self.encode_transactionally(|inner| {
+                model.0.as_parameterized(py, &mut |model| {
+                    inner.encode_iid_symbols(symbols.clone(), EncoderDecoderModel(model))?;
+                    Ok(())
+                })
+            })
         } else {
             if symbols.len() != model.0.len(&params[0])? {
                 return Err(pyo3::exceptions::PyAttributeError::new_err(
                     "`symbols` argument has wrong length.",
                 ));
             }
-            let mut symbol_iter = symbols.iter();
-            model.0.parameterize(py, params, false, &mut |model| {
-                let symbol = symbol_iter.next().expect("TODO");
-                self.inner
-                    .encode_symbol(*symbol, EncoderDecoderModel(model))?;
-                Ok(())
-            })?;
+            let mut symbol_iter = symbols;
+            self.encode_transactionally(|inner| {
+                model.0.parameterize(py, params, false, &mut |model| {
+                    let symbol = symbol_iter.next().ok_or_else(|| {
+                        pyo3::exceptions::PyAttributeError::new_err(
+                            "`symbols` argument has wrong length.",
+                        )
+                    })?;
+                    inner.encode_symbol(symbol, EncoderDecoderModel(model))?;
+                    Ok(())
+                })
+            })
         }
+    }
 
-        Ok(())
+    /// Encodes `symbols` and returns the resulting compressed data in a single call.
+    ///
+    /// The call `compressed = encoder.encode_to_array(symbols, model, *params)` is equivalent
+    /// to the two calls
+    ///
+    /// ```python
+    /// encoder.encode(symbols, model, *params)
+    /// compressed = encoder.get_compressed()
+    /// ```
+    ///
+    /// but it is faster for many small messages. It avoids returning to the Python
+    /// interpreter between the two steps, and it copies the compressed data only once. While
+    /// copying, it releases the global interpreter lock (GIL), so that other Python threads
+    /// can run. Encoding itself still holds the GIL since some entropy models (e.g., a
+    /// [`CustomModel`](model.html#constriction.stream.model.CustomModel)) call back into
+    /// Python.
+    ///
+    /// As with `get_compressed`, the encoder keeps its compressed data, so you can continue
+    /// to encode more symbols afterwards.
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// model = constriction.stream.model.QuantizedGaussian(-100, 100, 10.3, 24.2)
+    /// symbols = np.array([12, -13, 25], dtype=np.int32)
+    /// encoder = constriction.stream.queue.RangeEncoder()
+    /// compressed = encoder.encode_to_array(symbols, model)
+    /// assert np.all(compressed == encoder.get_compressed())
+    /// ```
+    #[pyo3(text_signature = "(symbols, model, optional_model_params)")]
+    #[args(symbols, model, params = "*")]
+    pub fn encode_to_array<'p>(
+        &mut self,
+        py: Python<'p>,
+        symbols: &PyAny,
+        model: &Model,
+        params: &PyTuple,
+    ) -> PyResult<&'p PyArray1<u32>> {
+        self.encode(py, symbols, model, params, false, None)?;
+        let inner = &mut self.inner;
+        let compressed = py.allow_threads(move || inner.get_compressed().to_vec());
+        Ok(PyArray1::from_vec(py, compressed))
     }
 
     /// .. deprecated:: 0.2.0
@@ -549,7 +664,7 @@ impl RangeEncoder {
             None
         );
 
-        self.encode(py, &symbols, model, PyTuple::empty(py))
+        self.encode(py, &symbols, model, PyTuple::empty(py), false, None)
     }
 
     /// Creates a deep copy of the coder and returns it.
@@ -563,6 +678,26 @@ impl RangeEncoder {
     }
 }
 
+impl RangeEncoder {
+    pub(crate) fn into_compressed(self) -> Vec<u32> {
+        self.inner.into_compressed().unwrap_infallible()
+    }
+
+    /// Runs `f` on the wrapped encoder and restores the encoder's original compressed data
+    /// and state if `f` fails.
+    pub(crate) fn encode_transactionally<T>(
+        &mut self,
+        f: impl FnOnce(&mut crate::stream::queue::DefaultRangeEncoder) -> PyResult<T>,
+    ) -> PyResult<T> {
+        let checkpoint = self.inner.checkpoint();
+        let result = f(&mut self.inner);
+        if result.is_err() {
+            self.inner.rollback(checkpoint);
+        }
+        result
+    }
+}
+
 /// A decoder of data that was previously encoded with a `RangeEncoder`.
 ///
 /// The constructor expects a single argument `compressed`, which has to be a rank-1 numpy array
@@ -575,21 +710,43 @@ impl RangeEncoder {
 /// operation consumes some portion of the compressed data from the `RangeDecoder`'s internal
 /// buffer.
 ///
+/// By default, a `RangeDecoder` keeps decoding (deterministic but meaningless) symbols once it
+/// has run out of compressed data. Set the optional constructor argument `strict=True` to catch
+/// bugs where you decode more symbols than you encoded: a strict `RangeDecoder` raises an
+/// `AssertionError` (and leaves the decoder unchanged) once decoding a symbol would require more
+/// compressed data than the encoder could have omitted at the end. Since the last few words of
+/// compressed data can often accommodate some additional symbols, a strict `RangeDecoder` may
+/// still decode a few symbols past the end of the message before it raises an error, so strict
+/// mode is no replacement for storing the message length.
+///
 /// ## Example
 ///
 /// See [module level example](#example).
 #[pyclass]
-#[pyo3(text_signature = "(compressed)")]
+#[pyo3(text_signature = "(compressed, strict=False)")]
 #[derive(Debug, Clone)]
 pub struct RangeDecoder {
     inner: crate::stream::queue::DefaultRangeDecoder,
+    pub(crate) strict: bool,
 }
 
 #[pymethods]
 impl RangeDecoder {
     #[new]
-    pub fn new(compressed: PyReadonlyArray1<'_, u32>) -> PyResult<Self> {
-        Ok(Self::from_vec(compressed.to_vec()?))
+    #[args(compressed, strict = "false")]
+    pub fn new(compressed: PyReadonlyArray1<'_, u32>, strict: bool) -> PyResult<Self> {
+        Ok(Self {
+            strict,
+            ..Self::from_vec(compressed.to_vec()?)
+        })
+    }
+
+    /// The configuration of this coder, i.e., a dictionary with the entries `"word_size"`,
+    /// `"state_size"`, and `"precision"` (all in bits). Equal to
+    /// `constriction.info()["coders"]["RangeDecoder"]`.
+    #[getter]
+    pub fn config<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        super::coder_config::<u32, u64>(py)
     }
 
     /// Jumps to a checkpoint recorded with method
@@ -598,6 +755,14 @@ impl RangeDecoder {
     /// This allows random-access decoding. The arguments `position` and `state` are the two values
     /// returned by the `RangeEncoder`'s method [`pos`](#constriction.stream.queue.RangeEncoder.pos).
     ///
+    /// Seeking to the checkpoint recorded after encoding the last symbol is valid, after which
+    /// [`maybe_exhausted`](#constriction.stream.queue.RangeDecoder.maybe_exhausted) returns
+    /// `True`. More generally, any `position` up to and including `len(compressed)` is valid,
+    /// even if the compressed data after `position` is too short to fill up the decoder's
+    /// internal state; the decoder then behaves exactly as if it had decoded up to `position`
+    /// without seeking. Positions beyond `len(compressed)` raise an error and leave the decoder
+    /// unchanged.
+    ///
     /// ## Example
     ///
     /// ```python
@@ -712,20 +877,21 @@ impl RangeDecoder {
         }
 
         let quantizer = DefaultLeakyQuantizer::new(min_supported_symbol..=max_supported_symbol);
-        let symbols = self
-            .inner
-            .try_decode_symbols(means.iter()?.zip(stds.iter()?).map(|(&mean, &std)| {
-                if std > 0.0 && std.is_finite() && mean.is_finite() {
-                    Ok(quantizer.quantize(Gaussian::new(mean, std)))
-                } else {
-                    Err(                pyo3::exceptions::PyValueError::new_err(
-                        "Invalid model parameters (`std` must be strictly positive and both `std` and `mean` must be finite.).",
-                    )
-    )
-                }
-            }))
-            .collect::<std::result::Result<Vec<_>, _>>()
-            ?;
+        let (means, stds) = (means.iter()?, stds.iter()?);
+        let symbols = self.decode_transactionally(|inner| {
+            inner
+                .try_decode_symbols(means.zip(stds).map(|(&mean, &std)| {
+                    if std > 0.0 && std.is_finite() && mean.is_finite() {
+                        Ok(quantizer.quantize(Gaussian::new(mean, std)))
+                    } else {
+                        Err(pyo3::exceptions::PyValueError::new_err(
+                            "Invalid model parameters (`std` must be strictly positive and both `std` and `mean` must be finite.).",
+                        ))
+                    }
+                }))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(Into::into)
+        })?;
 
         Ok(PyArray1::from_vec(py, symbols))
     }
@@ -793,17 +959,25 @@ impl RangeDecoder {
             )
         })?;
 
-        Ok(PyArray1::from_iter(
-            py,
-            self.inner.decode_iid_symbols(amt, &model).map(|symbol| {
-                let symbol = symbol.unwrap_or_else(|e| panic!("{}", e)) as i32;
-                symbol.wrapping_add(min_supported_symbol)
-            }),
-        ))
+        let symbols = self
+            .inner
+            .decode_iid_symbols(amt, &model)
+            .map(|symbol| Ok((symbol? as i32).wrapping_add(min_supported_symbol)))
+            .collect::<PyResult<Vec<_>>>()?;
+        Ok(PyArray1::from_vec(py, symbols))
     }
 
     /// Decodes one or more symbols, consuming them from the encapsulated compressed data.
     ///
+    /// If decoding fails for any symbol (e.g., because a custom entropy model raises an
+    /// exception), then this method raises the exception and resets the decoder to the
+    /// position and state it had before the call.
+    ///
+    /// Options 2 and 3 below return a numpy array of dtype `np.int32` by default. For small
+    /// alphabets, you may pass the keyword argument `dtype=np.int16` or `dtype=np.uint8` to
+    /// get a more compact array instead. If a decoded symbol doesn't fit into the requested
+    /// dtype, this method raises an `OverflowError` (with the same guarantees as above).
+    ///
     /// This method can be called in 3 different ways:
     ///
     /// ## Option 1: decode(model)
@@ -897,49 +1071,113 @@ impl RangeDecoder {
     /// symbols = decoder.decode(model_family, probabilities)
     /// print(symbols) # (prints: [3, 1])
     /// ```
-    #[pyo3(text_signature = "(model, optional_amt_or_model_params)")]
-    #[args(symbols, model, params = "*")]
+    ///
+    /// If `model` is a
+    /// [`DynPrecisionCategorical`](model.html#constriction.stream.model.DynPrecisionCategorical)
+    /// then you can set `precision` to decode with the model's probabilities re-rounded to
+    /// that many bits. It has to match the `precision` that was used for encoding.
+    #[pyo3(text_signature = "(model, optional_amt_or_model_params, dtype=None, precision=None)")]
+    #[args(symbols, model, params = "*", dtype = "None", precision = "None")]
     pub fn decode<'py>(
         &mut self,
         py: Python<'py>,
         model: &Model,
         params: &PyTuple,
+        dtype: Option<PyObject>,
+        precision: Option<usize>,
     ) -> PyResult<PyObject> {
+        let model = &model.at_precision(precision)?;
+        let strict = self.strict;
         match params.len() {
             0 => {
                 let mut symbol = 0;
                 model.0.as_parameterized(py, &mut |model| {
-                    symbol = self.inner.decode_symbol(EncoderDecoderModel(model))?;
+                    symbol = decode_symbol(&mut self.inner, EncoderDecoderModel(model), strict)?;
                     Ok(())
                 })?;
                 return Ok(symbol.to_object(py));
             }
             1 => {
                 if let Ok(amt) = usize::extract(params.as_slice()[0]) {
-                    let mut symbols = Vec::with_capacity(amt);
-                    model.0.as_parameterized(py, &mut |model| {
-                        for symbol in self
-                            .inner
-                            .decode_iid_symbols(amt, EncoderDecoderModel(model))
-                        {
-                            symbols.push(symbol?);
-                        }
-                        Ok(())
+                    let mut symbols = DecodedSymbols::with_capacity(py, dtype, amt)?;
+                    self.decode_transactionally(|inner| {
+                        model.0.as_parameterized(py, &mut |model| {
+                            for _ in 0..amt {
+                                symbols.push(decode_symbol(
+                                    inner,
+                                    EncoderDecoderModel(model),
+                                    strict,
+                                )?)?;
+                            }
+                            Ok(())
+                        })
                     })?;
-                    return Ok(PyArray1::from_iter(py, symbols).to_object(py));
+                    return Ok(symbols.into_py(py));
                 }
             }
             _ => {} // Fall through to code below.
         };
 
-        let mut symbols = Vec::with_capacity(model.0.len(&params[0])?);
-        model.0.parameterize(py, params, false, &mut |model| {
-            let symbol = self.inner.decode_symbol(EncoderDecoderModel(model))?;
-            symbols.push(symbol);
-            Ok(())
+        let mut symbols = DecodedSymbols::with_capacity(py, dtype, model.0.len(&params[0])?)?;
+        self.decode_transactionally(|inner| {
+            model.0.parameterize(py, params, false, &mut |model| {
+                let symbol = decode_symbol(inner, EncoderDecoderModel(model), strict)?;
+                symbols.push(symbol)?;
+                Ok(())
+            })
         })?;
 
-        Ok(PyArray1::from_vec(py, symbols).to_object(py))
+        Ok(symbols.into_py(py))
+    }
+
+    /// Advances the decoder past one or more symbols without returning them.
+    ///
+    /// Takes the same arguments as
+    /// [`decode`](#constriction.stream.queue.RangeDecoder.decode) (except for `dtype`), i.e.,
+    /// `skip(model)` skips a single symbol, `skip(model, amt)` skips `amt` i.i.d. symbols,
+    /// and `skip(model_family, params1, params2, ...)` skips one symbol per entry of the
+    /// model parameters. The decoder ends up in the same state as if you had called
+    /// `decode` with the same arguments, but no numpy array of decoded symbols is
+    /// allocated. This is useful for jumping into the middle of a stream when there's no
+    /// checkpoint close to the position you want to jump to.
+    ///
+    /// As with `decode`, a failure raises an exception and leaves the decoder unchanged.
+    #[pyo3(text_signature = "(model, optional_amt_or_model_params)")]
+    #[args(model, params = "*")]
+    pub fn skip(&mut self, py: Python<'_>, model: &Model, params: &PyTuple) -> PyResult<()> {
+        let strict = self.strict;
+        match params.len() {
+            0 => {
+                return model.0.as_parameterized(py, &mut |model| {
+                    decode_symbol(&mut self.inner, EncoderDecoderModel(model), strict)?;
+                    Ok(())
+                });
+            }
+            1 => {
+                if let Ok(amt) = usize::extract(params.as_slice()[0]) {
+                    return self.decode_transactionally(|inner| {
+                        model.0.as_parameterized(py, &mut |model| {
+                            if strict {
+                                for _ in 0..amt {
+                                    decode_symbol(inner, EncoderDecoderModel(model), true)?;
+                                }
+                            } else {
+                                inner.skip_iid_symbols(amt, EncoderDecoderModel(model))?;
+                            }
+                            Ok(())
+                        })
+                    });
+                }
+            }
+            _ => {} // Fall through to code below.
+        };
+
+        self.decode_transactionally(|inner| {
+            model.0.parameterize(py, params, false, &mut |model| {
+                decode_symbol(inner, EncoderDecoderModel(model), strict)?;
+                Ok(())
+            })
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -992,7 +1230,36 @@ impl RangeDecoder {
             None
         );
 
-        self.decode(py, model, PyTuple::new(py, [amt]))
+        self.decode(py, model, PyTuple::new(py, [amt]), None, None)
+    }
+
+    /// Returns an iterator that lazily decodes up to `amt` i.i.d. symbols with the concrete
+    /// entropy model `model`, one symbol per call to `next`.
+    ///
+    /// This is equivalent to `decoder.decode(model, amt)` except that it never materializes
+    /// the decoded symbols in a numpy array. Use it if you consume symbols one at a time
+    /// anyway, e.g., to stop decoding early once you've found a terminator symbol:
+    ///
+    /// ```python
+    /// probabilities = np.array([0.1, 0.6, 0.3], dtype=np.float64)
+    /// model = constriction.stream.model.Categorical(probabilities)
+    /// compressed = np.array([369323576], dtype=np.uint32)
+    /// decoder = constriction.stream.queue.RangeDecoder(compressed)
+    /// for symbol in decoder.iter_decode(model, 9):
+    ///     print(symbol) # (prints: 0, 2, 1, 2, 0, 2, 0, 2, 1)
+    /// ```
+    ///
+    /// The iterator doesn't copy the decoder. Each step decodes from the current position of
+    /// `decoder`, so you can interleave iteration with calls to other methods of `decoder`
+    /// (e.g., `decode` with a different model). If a step fails then the iterator raises the
+    /// exception and leaves `decoder` in the state it had before that step.
+    #[pyo3(text_signature = "(model, amt)")]
+    pub fn iter_decode(slf: PyRef<'_, Self>, model: &Model, amt: usize) -> RangeDecoderIter {
+        RangeDecoderIter {
+            decoder: slf.into(),
+            model: Arc::clone(&model.0),
+            remaining: amt,
+        }
     }
 
     /// Creates a deep copy of the coder and returns it.
@@ -1006,11 +1273,66 @@ impl RangeDecoder {
     }
 }
 
+/// Iterator returned by
+/// [`RangeDecoder.iter_decode`](#constriction.stream.queue.RangeDecoder.iter_decode).
+#[pyclass]
+#[allow(missing_debug_implementations)]
+pub struct RangeDecoderIter {
+    decoder: Py<RangeDecoder>,
+    model: Arc<dyn internals::Model>,
+    remaining: usize,
+}
+
+#[pyproto]
+impl PyIterProtocol for RangeDecoderIter {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> PyResult<Option<i32>> {
+        if slf.remaining == 0 {
+            return Ok(None);
+        }
+
+        let py = slf.py();
+        let mut decoder = slf.decoder.try_borrow_mut(py)?;
+        let strict = decoder.strict;
+        let mut symbol = 0;
+        decoder.decode_transactionally(|inner| {
+            slf.model.as_parameterized(py, &mut |model| {
+                symbol = decode_symbol(inner, EncoderDecoderModel(model), strict)?;
+                Ok(())
+            })
+        })?;
+        drop(decoder);
+
+        slf.remaining -= 1;
+        Ok(Some(symbol))
+    }
+}
+
 impl RangeDecoder {
     pub fn from_vec(compressed: Vec<u32>) -> Self {
         let inner = crate::stream::queue::DefaultRangeDecoder::from_compressed(compressed)
             .unwrap_infallible();
-        Self { inner }
+        Self {
+            inner,
+            strict: false,
+        }
+    }
+
+    /// Runs `f` on the wrapped decoder and restores the decoder's original position and
+    /// state if `f` fails.
+    pub(crate) fn decode_transactionally<T>(
+        &mut self,
+        f: impl FnOnce(&mut crate::stream::queue::DefaultRangeDecoder) -> PyResult<T>,
+    ) -> PyResult<T> {
+        let checkpoint = self.inner.checkpoint();
+        let result = f(&mut self.inner);
+        if result.is_err() {
+            self.inner.rollback(checkpoint);
+        }
+        result
     }
 }
 
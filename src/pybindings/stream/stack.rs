@@ -3,20 +3,38 @@ use std::prelude::v1::*;
 
 use numpy::{PyArray1, PyReadonlyArray1};
 use probability::distribution::Gaussian;
-use pyo3::{prelude::*, types::PyTuple};
+use pyo3::{
+    prelude::*,
+    types::{PyDict, PyTuple},
+    PyIterProtocol,
+};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
 
 use crate::{
+    backends::Cursor,
     stream::{
         model::{DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer},
-        Decode, Encode, TryCodingError,
+        Code, Decode, Encode, TryCodingError,
     },
     CoderError, Pos, Seek, UnwrapInfallible,
 };
 
-use super::model::{internals::EncoderDecoderModel, Model};
+use super::{
+    decode_symbol,
+    model::{
+        internals::{self, EncoderDecoderModel},
+        Model,
+    },
+    validate_symbols, DecodedSymbols, Symbols,
+};
 
 pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
     module.add_class::<AnsCoder>()?;
+    module.add_class::<AnsCoderIter>()?;
+    module.add_class::<AnsCoderSnapshot>()?;
     Ok(())
 }
 
@@ -99,6 +117,8 @@ pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
 /// Arguments:
 /// compressed (optional) -- initial compressed data, as a numpy array with
 ///     dtype `uint32`.
+/// strict (optional) -- accepted for symmetry with `RangeDecoder`, but has no
+///     effect since an `AnsCoder` can't detect the end of its data (default: `False`).
 ///
 /// [Asymmetric Numeral Systems (ANS)]: https://en.wikipedia.org/wiki/Asymmetric_numeral_systems
 /// [`constriction::stream::ans::DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
@@ -108,15 +128,18 @@ pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
 /// [1] Duda, Jarek, et al. "The use of asymmetric numeral systems as an accurate
 /// replacement for Huffman coding." 2015 Picture Coding Symposium (PCS). IEEE, 2015.
 #[pyclass]
-#[pyo3(text_signature = "([compressed], seal=False)")]
+#[pyo3(text_signature = "([compressed], seal=False, strict=False)")]
 #[derive(Debug, Clone)]
 pub struct AnsCoder {
     inner: crate::stream::stack::DefaultAnsCoder,
+    pub(crate) strict: bool,
+    undo: UndoLog,
 }
 
 #[pymethods]
 impl AnsCoder {
-    /// The constructor has the call signature `AnsCoder([compressed, [seal=False]])`.
+    /// The constructor has the call signature `AnsCoder([compressed, [seal=False]],
+    /// strict=False)`.
     ///
     /// - If you want to encode a message, call the constructor with no arguments.
     /// - If you want to decode a message that was previously encoded with an `AnsCoder`, call the
@@ -130,10 +153,20 @@ impl AnsCoder {
     ///   then the `AnsCoder` will truncate any trailing zero words from `compressed`). Once you've
     ///   decoded and re-encoded some symbols, you can get back the original `compressed` data by
     ///   calling `.get_compressed(unseal=True)`.
+    ///
+    /// An `AnsCoder` keeps decoding (deterministic but meaningless) symbols once it has run out
+    /// of compressed data, which is what bits-back coding relies on. Unlike for a `RangeDecoder`,
+    /// setting `strict=True` doesn't change this: encoding the first symbol of a categorical
+    /// model (or, more generally, any symbol whose left-sided cumulative probability is zero)
+    /// onto an empty `AnsCoder` leaves it empty, so even an empty `AnsCoder` may still hold
+    /// valid symbols. If you need to detect the end of a message, store the message length or
+    /// use a `RangeEncoder` and a strict `RangeDecoder`.
     #[new]
+    #[args(compressed = "None", seal = "None", strict = "false")]
     pub fn new(
         compressed: Option<PyReadonlyArray1<'_, u32>>,
         seal: Option<bool>,
+        strict: bool,
     ) -> PyResult<Self> {
         if compressed.is_none() && seal.is_some() {
             return Err(pyo3::exceptions::PyValueError::new_err(
@@ -155,7 +188,19 @@ impl AnsCoder {
             crate::stream::stack::AnsCoder::new()
         };
 
-        Ok(Self { inner })
+        Ok(Self {
+            inner,
+            strict,
+            undo: UndoLog::default(),
+        })
+    }
+
+    /// The configuration of this coder, i.e., a dictionary with the entries `"word_size"`,
+    /// `"state_size"`, and `"precision"` (all in bits). Equal to
+    /// `constriction.info()["coders"]["AnsCoder"]`.
+    #[getter]
+    pub fn config<'py>(&self, py: Python<'py>) -> PyResult<&'py PyDict> {
+        super::coder_config::<u32, u64>(py)
     }
 
     /// Records a checkpoint to which you can jump during decoding using
@@ -189,6 +234,10 @@ impl AnsCoder {
     /// (seeking backward is supported for Range Coding, and for both ANS and Range Coding in
     /// `constriction`'s Rust API).
     ///
+    /// Seeking to the checkpoint recorded before encoding the first symbol is valid, after which
+    /// [`is_empty`](#constriction.stream.stack.AnsCoder.is_empty) returns `True`. Positions
+    /// beyond the current position (see above) raise an error and leave the coder unchanged.
+    ///
     /// ## Example
     ///
     /// ```python
@@ -217,6 +266,7 @@ impl AnsCoder {
     /// ```
     #[pyo3(text_signature = "(position, state)")]
     pub fn seek(&mut self, position: usize, state: u64) -> PyResult<()> {
+        self.undo.record_truncation(self.inner.bulk(), position);
         self.inner.seek((position, state)).map_err(|()| {
             pyo3::exceptions::PyAttributeError::new_err(
                 "Tried to seek past end of stream. Note: in an ANS coder,\n\
@@ -226,13 +276,85 @@ impl AnsCoder {
         })
     }
 
+    /// Records the current state of the coder so that you can later return to it with
+    /// [`restore`](#constriction.stream.stack.AnsCoder.restore), even after decoding.
+    ///
+    /// Unlike [`pos`](#constriction.stream.stack.AnsCoder.pos) and
+    /// [`seek`](#constriction.stream.stack.AnsCoder.seek), which can only jump forward, a
+    /// snapshot allows you to undo both encoding *and* decoding. Unlike
+    /// [`clone`](#constriction.stream.stack.AnsCoder.clone) (or `copy.deepcopy`), taking a
+    /// snapshot doesn't copy the compressed data. It only records the coder's head state and the
+    /// current number of words on the stack. Once a snapshot has been taken, the coder keeps the
+    /// words that decoding pops off the stack in an undo log, so that `restore` can push them
+    /// back. Thus, taking a snapshot costs constant time, and restoring one costs time
+    /// proportional to the number of words decoded since. This makes snapshots well suited for
+    /// speculative decoding, e.g., for rejection sampling or beam search over compressed data.
+    ///
+    /// You can restore the same snapshot several times, and you can hold on to several
+    /// snapshots at once. However, if you decode past a snapshot `s1`, then encode some new
+    /// symbols, and then take a new snapshot `s2`, then `s1` becomes invalid because the new
+    /// symbols overwrite the words that `s1` relies on (trying to restore `s1` raises a
+    /// `ValueError`). Calling [`clear`](#constriction.stream.stack.AnsCoder.clear) invalidates
+    /// all snapshots and discards the undo log.
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// model = constriction.stream.model.QuantizedGaussian(-100, 100, 12.6, 7.3)
+    /// coder = constriction.stream.stack.AnsCoder()
+    /// coder.encode_reverse(np.array([12, 15, 4, -2, 18, 5], dtype=np.int32), model)
+    ///
+    /// snapshot = coder.snapshot()
+    /// for attempt in range(3):
+    ///     decoded = coder.decode(model, 3)  # Speculatively decode some symbols ...
+    ///     coder.encode_reverse(np.array([1, 2], dtype=np.int32), model)  # ... and re-encode.
+    ///     coder.restore(snapshot)  # Undo both decoding and encoding.
+    ///
+    /// assert np.all(coder.decode(model, 6) == np.array([12, 15, 4, -2, 18, 5]))
+    /// ```
+    #[pyo3(text_signature = "()")]
+    pub fn snapshot(&mut self) -> AnsCoderSnapshot {
+        let (len, state) = self.inner.pos();
+        let epoch = self.undo.start_snapshot(len);
+        AnsCoderSnapshot { len, state, epoch }
+    }
+
+    /// Returns the coder to the state it was in when
+    /// [`snapshot`](#constriction.stream.stack.AnsCoder.snapshot) returned `snapshot`.
+    ///
+    /// Raises a `ValueError` and leaves the coder unchanged if `snapshot` was taken from a
+    /// different coder or if it has become invalid (see
+    /// [`snapshot`](#constriction.stream.stack.AnsCoder.snapshot)).
+    #[pyo3(text_signature = "(snapshot)")]
+    pub fn restore(&mut self, snapshot: &AnsCoderSnapshot) -> PyResult<()> {
+        if self.undo.epoch != Some(snapshot.epoch) {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "The snapshot is no longer valid. Snapshots become invalid when they are \
+                restored on a different coder, after calling `clear`, or when a newer snapshot \
+                was taken after both decoding past the older snapshot and encoding new data.",
+            ));
+        }
+
+        let (mut bulk, _) =
+            core::mem::replace(&mut self.inner, crate::stream::stack::AnsCoder::new())
+                .into_raw_parts();
+        self.undo.rewind(&mut bulk, snapshot.len);
+        // SAFETY: `(bulk, snapshot.state)` is identical to the raw parts of `self.inner` at
+        // the time when `snapshot` was taken, so it satisfies the coder's invariants.
+        self.inner =
+            unsafe { crate::stream::stack::AnsCoder::from_raw_parts(bulk, snapshot.state) };
+        Ok(())
+    }
+
     /// Resets the encoder to an empty state.
     ///
     /// This removes any existing compressed data on the encoder. It is equivalent to replacing the
-    /// encoder with a new one but slightly more efficient.
+    /// encoder with a new one but slightly more efficient. Any existing
+    /// [snapshots](#constriction.stream.stack.AnsCoder.snapshot) become invalid.
     #[pyo3(text_signature = "()")]
     pub fn clear(&mut self) {
         self.inner.clear();
+        self.undo = UndoLog::default();
     }
 
     /// Returns the current size of the encapsulated compressed data, in `np.uint32` words.
@@ -250,16 +372,18 @@ impl AnsCoder {
     /// This is 32 times the result of what [`num_words`](#constriction.stream.queue.RangeEncoder.num_words)
     /// would return.
     #[pyo3(text_signature = "()")]
-    pub fn num_bits(&self) -> usize {
-        self.inner.num_bits()
+    pub fn num_bits(&self) -> u64 {
+        // Widen before multiplying so that this doesn't overflow on 32-bit platforms.
+        self.inner.num_words() as u64 * 32
     }
 
     /// The current size of the compressed data, in bits, not rounded up to full words.
     ///
     /// This can be at most 32 smaller than `.num_bits()`.
     #[pyo3(text_signature = "()")]
-    pub fn num_valid_bits(&self) -> usize {
-        self.inner.num_valid_bits()
+    pub fn num_valid_bits(&self) -> u64 {
+        let state_bits = core::cmp::max(64 - self.inner.state().leading_zeros(), 1) - 1;
+        self.inner.bulk().len() as u64 * 32 + state_bits as u64
     }
 
     /// Returns `True` iff the coder is in its default initial state.
@@ -346,6 +470,34 @@ impl AnsCoder {
         }
     }
 
+    /// Same as [`get_compressed`](#constriction.stream.stack.AnsCoder.get_compressed), but
+    /// appends a CRC-32 checksum to the returned compressed data.
+    ///
+    /// Pass the returned data through
+    /// [`constriction.stream.verify_checksum`](../stream.html#constriction.stream.verify_checksum)
+    /// before decoding it to detect corrupted data instead of silently decoding garbage. Like
+    /// `get_compressed`, this method has no side effects on the coder. (The checksum is
+    /// unrelated to the optional argument `seal` of the constructor.)
+    ///
+    /// ## Example
+    ///
+    /// ```python
+    /// model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+    /// symbols = np.array([5, -3, 12, 0, 7, 4], dtype=np.int32)
+    /// coder = constriction.stream.stack.AnsCoder()
+    /// coder.encode_reverse(symbols, model)
+    /// sealed = coder.seal_with_checksum()
+    ///
+    /// compressed = constriction.stream.verify_checksum(sealed)
+    /// decoder = constriction.stream.stack.AnsCoder(compressed)
+    /// assert np.all(decoder.decode(model, len(symbols)) == symbols)
+    /// ```
+    #[pyo3(text_signature = "()")]
+    pub fn seal_with_checksum<'p>(&mut self, py: Python<'p>) -> &'p PyArray1<u32> {
+        let compressed = self.inner.get_compressed().unwrap_infallible().to_vec();
+        PyArray1::from_vec(py, crate::stream::checksum::seal_with_checksum(compressed))
+    }
+
     /// .. deprecated:: 0.2.0
     ///    Superseded by `.get_compressed(unseal=True)`.
     #[pyo3(text_signature = "(DEPRECATED)")]
@@ -355,6 +507,10 @@ impl AnsCoder {
 
     /// Encodes one or more symbols, appending them to the encapsulated compressed data.
     ///
+    /// If encoding fails for any symbol (e.g., because the symbol has zero probability under
+    /// its entropy model), then this method raises an exception and leaves the coder
+    /// unchanged, i.e., none of the provided symbols get encoded.
+    ///
     /// This method can be called in 3 different ways:
     ///
     /// ## Option 1: encode_reverse(symbol, model)
@@ -400,6 +556,15 @@ impl AnsCoder {
     /// print(coder.get_compressed()) # (prints: [1276728145, 172])
     /// ```
     ///
+    /// For quick scripts and tests, you may also pass `symbols` as a Python list or tuple of
+    /// ints instead of a numpy array (here and in Option 3 below), e.g.,
+    /// `coder.encode_reverse([0, 2, 1, 2, 0, 2, 0, 2, 1], model)`. Symbols that don't fit
+    /// into a 32-bit signed integer raise an `OverflowError`.
+    ///
+    /// Numpy arrays of dtype `np.int16` or `np.uint8` are accepted as well and are read without
+    /// widening them to a temporary `np.int32` copy, which saves memory for large messages over
+    /// small alphabets (e.g., pixel values of images).
+    ///
     /// ## Option 3: encode_reverse(symbols, model_family, params1, params2, ...)
     ///
     /// Encodes multiple symbols, using the same *family* of entropy models (e.g., categorical or
@@ -451,15 +616,32 @@ impl AnsCoder {
     /// coder.encode_reverse(symbols, model_family, probabilities)
     /// print(coder.get_compressed()) # (prints: [45298483])
     /// ```
-    #[pyo3(text_signature = "(symbols, model, optional_model_params)")]
-    #[args(symbols, model, params = "*")]
+    ///
+    /// If you set `validate=True`, then this method first checks that all `symbols` lie in
+    /// the support of their respective entropy models (at the cost of constructing each
+    /// entropy model twice). If any symbols don't, then it raises a `KeyError` that lists the
+    /// indices of the first few offending symbols, and it doesn't encode anything.
+    ///
+    /// If `model` is a
+    /// [`DynPrecisionCategorical`](model.html#constriction.stream.model.DynPrecisionCategorical)
+    /// then you can set `precision` to an integer between the model's minimum precision and
+    /// 24 to encode with the model's probabilities re-rounded to that many bits. The decoder
+    /// has to pass the same `precision`. Setting `precision` for any other model raises a
+    /// `ValueError`.
+    #[pyo3(
+        text_signature = "(symbols, model, optional_model_params, validate=False, precision=None)"
+    )]
+    #[args(symbols, model, params = "*", validate = "false", precision = "None")]
     pub fn encode_reverse(
         &mut self,
         py: Python<'_>,
         symbols: &PyAny,
         model: &Model,
         params: &PyTuple,
+        validate: bool,
+        precision: Option<usize>,
     ) -> PyResult<()> {
+        let model = &model.at_precision(precision)?;
         if let Ok(symbol) = symbols.extract::<i32>() {
             if !params.is_empty() {
                 return Err(pyo3::exceptions::PyAttributeError::new_err(
@@ -479,33 +661,39 @@ impl AnsCoder {
             });
         }
 
-        // Don't use an `else` branch here because, if the following `extract` fails, the returned
-        // error message is actually pretty user friendly.
-        let symbols = symbols.extract::<PyReadonlyArray1<'_, i32>>()?;
-        let symbols = symbols.as_slice()?;
+        let symbols = Symbols::extract(py, symbols)?;
+        let symbols = symbols.iter();
+        if validate {
+            validate_symbols(py, symbols.clone(), model, params)?;
+        }
 
         if params.is_empty() {
-            model.0.as_parameterized(py, &mut |model| {
-                self.inner
-                    .encode_iid_symbols_reverse(symbols, EncoderDecoderModel(model))?;
-                Ok(())
-            })?;
+            self.encode_transactionally(|inner| {
+                model.0.as_parameterized(py, &mut |model| {
+                    inner
+                        .encode_iid_symbols_reverse(symbols.clone(), EncoderDecoderModel(model))?;
+                    Ok(())
+                })
+            })
         } else {
             if symbols.len() != model.0.len(&params[0])? {
                 return Err(pyo3::exceptions::PyAttributeError::new_err(
                     "`symbols` argument has wrong length.",
                 ));
             }
-            let mut symbol_iter = symbols.iter().rev();
-            model.0.parameterize(py, params, true, &mut |model| {
-                let symbol = symbol_iter.next().expect("TODO");
-                self.inner
-                    .encode_symbol(*symbol, EncoderDecoderModel(model))?;
-                Ok(())
-            })?;
+            let mut symbol_iter = symbols.rev();
+            self.encode_transactionally(|inner| {
+                model.0.parameterize(py, params, true, &mut |model| {
+                    let symbol = symbol_iter.next().ok_or_else(|| {
+                        pyo3::exceptions::PyAttributeError::new_err(
+                            "`symbols` argument has wrong length.",
+                        )
+                    })?;
+                    inner.encode_symbol(symbol, EncoderDecoderModel(model))?;
+                    Ok(())
+                })
+            })
         }
-
-        Ok(())
     }
 
     /// .. deprecated:: 0.2.0
@@ -577,21 +765,20 @@ impl AnsCoder {
         }
 
         let quantizer = DefaultLeakyQuantizer::new(min_supported_symbol..=max_supported_symbol);
-        self.inner.try_encode_symbols_reverse(
-            symbols
-                .iter()
-                .zip(means.iter())
-                .zip(stds.iter())
-                .map(|((&symbol, &mean), &std)| {
-                    if std > 0.0 && std.is_finite() && mean.is_finite() {
-                        Ok((symbol, quantizer.quantize(Gaussian::new(mean, std))))
-                    } else {
-                        Err(())
-                    }
-                }),
-        )?;
-
-        Ok(())
+        self.encode_transactionally(|inner| {
+            inner.try_encode_symbols_reverse(
+                symbols.iter().zip(means.iter()).zip(stds.iter()).map(
+                    |((&symbol, &mean), &std)| {
+                        if std > 0.0 && std.is_finite() && mean.is_finite() {
+                            Ok((symbol, quantizer.quantize(Gaussian::new(mean, std))))
+                        } else {
+                            Err(())
+                        }
+                    },
+                ),
+            )?;
+            Ok(())
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -656,15 +843,16 @@ impl AnsCoder {
             )
         })?;
 
-        self.inner.encode_iid_symbols_reverse(
-            symbols
-                .as_slice()?
-                .iter()
-                .map(|s| s.wrapping_sub(min_supported_symbol) as usize),
-            &model,
-        )?;
-
-        Ok(())
+        let symbols = symbols.as_slice()?;
+        self.encode_transactionally(|inner| {
+            inner.encode_iid_symbols_reverse(
+                symbols
+                    .iter()
+                    .map(|s| s.wrapping_sub(min_supported_symbol) as usize),
+                &model,
+            )?;
+            Ok(())
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -735,11 +923,20 @@ impl AnsCoder {
             None
         );
 
-        self.encode_reverse(py, &symbols, model, PyTuple::empty(py))
+        self.encode_reverse(py, &symbols, model, PyTuple::empty(py), false, None)
     }
 
     /// Decodes one or more symbols, consuming them from the encapsulated compressed data.
     ///
+    /// If decoding fails for any symbol (e.g., because a custom entropy model raises an
+    /// exception), then this method raises the exception and doesn't consume any compressed
+    /// data.
+    ///
+    /// Options 2 and 3 below return a numpy array of dtype `np.int32` by default. For small
+    /// alphabets, you may pass the keyword argument `dtype=np.int16` or `dtype=np.uint8` to
+    /// get a more compact array instead. If a decoded symbol doesn't fit into the requested
+    /// dtype, this method raises an `OverflowError` (with the same guarantees as above).
+    ///
     /// This method can be called in 3 different ways:
     ///
     /// ## Option 1: decode(model)
@@ -833,55 +1030,122 @@ impl AnsCoder {
     /// symbols = coder.decode(model_family, probabilities)
     /// print(symbols) # (prints: [3, 1])
     /// ```
-    #[pyo3(text_signature = "(model, optional_amt_or_model_params)")]
-    #[args(symbols, model, params = "*")]
+    ///
+    /// If `model` is a
+    /// [`DynPrecisionCategorical`](model.html#constriction.stream.model.DynPrecisionCategorical)
+    /// then you can set `precision` to decode with the model's probabilities re-rounded to
+    /// that many bits. It has to match the `precision` that was used for encoding.
+    #[pyo3(text_signature = "(model, optional_amt_or_model_params, dtype=None, precision=None)")]
+    #[args(symbols, model, params = "*", dtype = "None", precision = "None")]
     pub fn decode<'py>(
         &mut self,
         py: Python<'py>,
         model: &Model,
         params: &PyTuple,
+        dtype: Option<PyObject>,
+        precision: Option<usize>,
     ) -> PyResult<PyObject> {
+        let model = &model.at_precision(precision)?;
+        let strict = self.strict;
         match params.len() {
             0 => {
                 let mut symbol = 0;
-                model.0.as_parameterized(py, &mut |model| {
-                    symbol = self
-                        .inner
-                        .decode_symbol(EncoderDecoderModel(model))
-                        .unwrap_infallible();
-                    Ok(())
+                self.decode_transactionally(|decoder| {
+                    model.0.as_parameterized(py, &mut |model| {
+                        symbol = decode_symbol(decoder, EncoderDecoderModel(model), strict)?;
+                        Ok(())
+                    })
                 })?;
                 return Ok(symbol.to_object(py));
             }
             1 => {
                 if let Ok(amt) = usize::extract(params.as_slice()[0]) {
-                    let mut symbols = Vec::with_capacity(amt);
-                    model.0.as_parameterized(py, &mut |model| {
-                        for symbol in self
-                            .inner
-                            .decode_iid_symbols(amt, EncoderDecoderModel(model))
-                        {
-                            symbols.push(symbol.unwrap_infallible());
-                        }
-                        Ok(())
+                    let mut symbols = DecodedSymbols::with_capacity(py, dtype, amt)?;
+                    self.decode_transactionally(|decoder| {
+                        model.0.as_parameterized(py, &mut |model| {
+                            for _ in 0..amt {
+                                symbols.push(decode_symbol(
+                                    decoder,
+                                    EncoderDecoderModel(model),
+                                    strict,
+                                )?)?;
+                            }
+                            Ok(())
+                        })
                     })?;
-                    return Ok(PyArray1::from_iter(py, symbols).to_object(py));
+                    return Ok(symbols.into_py(py));
                 }
             }
             _ => {} // Fall through to code below.
         };
 
-        let mut symbols = Vec::with_capacity(model.0.len(&params[0])?);
-        model.0.parameterize(py, params, false, &mut |model| {
-            let symbol = self
-                .inner
-                .decode_symbol(EncoderDecoderModel(model))
-                .unwrap_infallible();
-            symbols.push(symbol);
-            Ok(())
+        let mut symbols = DecodedSymbols::with_capacity(py, dtype, model.0.len(&params[0])?)?;
+        self.decode_transactionally(|decoder| {
+            model.0.parameterize(py, params, false, &mut |model| {
+                let symbol = decode_symbol(decoder, EncoderDecoderModel(model), strict)?;
+                symbols.push(symbol)?;
+                Ok(())
+            })
         })?;
 
-        Ok(PyArray1::from_vec(py, symbols).to_object(py))
+        Ok(symbols.into_py(py))
+    }
+
+    /// Advances the decoder past one or more symbols without returning them.
+    ///
+    /// Takes the same arguments as
+    /// [`decode`](#constriction.stream.stack.AnsCoder.decode) (except for `dtype`), i.e.,
+    /// `skip(model)` skips a single symbol, `skip(model, amt)` skips `amt` i.i.d. symbols,
+    /// and `skip(model_family, params1, params2, ...)` skips one symbol per entry of the
+    /// model parameters. The decoder ends up in the same state as if you had called
+    /// `decode` with the same arguments, but no numpy array of decoded symbols is
+    /// allocated. This is useful for jumping into the middle of a stream when there's no
+    /// checkpoint close to the position you want to jump to.
+    ///
+    /// Skipping symbols on an `AnsCoder` removes them from the stack, just like decoding
+    /// them would.
+    ///
+    /// As with `decode`, a failure raises an exception and leaves the decoder unchanged.
+    #[pyo3(text_signature = "(model, optional_amt_or_model_params)")]
+    #[args(model, params = "*")]
+    pub fn skip(&mut self, py: Python<'_>, model: &Model, params: &PyTuple) -> PyResult<()> {
+        let strict = self.strict;
+        match params.len() {
+            0 => {
+                return self.decode_transactionally(|decoder| {
+                    model.0.as_parameterized(py, &mut |model| {
+                        decode_symbol(decoder, EncoderDecoderModel(model), strict)?;
+                        Ok(())
+                    })
+                });
+            }
+            1 => {
+                if let Ok(amt) = usize::extract(params.as_slice()[0]) {
+                    return self.decode_transactionally(|decoder| {
+                        model.0.as_parameterized(py, &mut |model| {
+                            if strict {
+                                for _ in 0..amt {
+                                    decode_symbol(decoder, EncoderDecoderModel(model), true)?;
+                                }
+                            } else {
+                                decoder
+                                    .skip_iid_symbols(amt, EncoderDecoderModel(model))
+                                    .unwrap_infallible();
+                            }
+                            Ok(())
+                        })
+                    });
+                }
+            }
+            _ => {} // Fall through to code below.
+        };
+
+        self.decode_transactionally(|decoder| {
+            model.0.parameterize(py, params, false, &mut |model| {
+                decode_symbol(decoder, EncoderDecoderModel(model), strict)?;
+                Ok(())
+            })
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -950,21 +1214,23 @@ impl AnsCoder {
         }
 
         let quantizer = DefaultLeakyQuantizer::new(min_supported_symbol..=max_supported_symbol);
-        let symbols = self
-            .inner
-            .try_decode_symbols(means.iter()?.zip(stds.iter()?).map(|(&mean, &std)| {
-                if std > 0.0 && std.is_finite() && mean.is_finite() {
-                    Ok(quantizer.quantize(Gaussian::new(mean, std)))
-                } else {
-                    Err(())
-                }
-            }))
-            .collect::<std::result::Result<Vec<_>, _>>()
-            .map_err(|_err:TryCodingError<CoderError<Infallible, Infallible>, ()>| {
-                pyo3::exceptions::PyValueError::new_err(
-                    "Invalid model parameters (`std` must be strictly positive and both `std` and `mean` must be finite.).",
-                )
-            })?;
+        let (means, stds) = (means.iter()?, stds.iter()?);
+        let symbols = self.decode_transactionally(|decoder| {
+            decoder
+                .try_decode_symbols(means.zip(stds).map(|(&mean, &std)| {
+                    if std > 0.0 && std.is_finite() && mean.is_finite() {
+                        Ok(quantizer.quantize(Gaussian::new(mean, std)))
+                    } else {
+                        Err(())
+                    }
+                }))
+                .collect::<std::result::Result<Vec<_>, _>>()
+                .map_err(|_err:TryCodingError<CoderError<Infallible, Infallible>, ()>| {
+                    pyo3::exceptions::PyValueError::new_err(
+                        "Invalid model parameters (`std` must be strictly positive and both `std` and `mean` must be finite.).",
+                    )
+                })
+        })?;
 
         Ok(PyArray1::from_vec(py, symbols))
     }
@@ -1032,12 +1298,14 @@ impl AnsCoder {
             )
         })?;
 
-        Ok(PyArray1::from_iter(
-            py,
-            self.inner.decode_iid_symbols(amt, &model).map(|symbol| {
-                (symbol.unwrap_infallible() as i32).wrapping_add(min_supported_symbol)
-            }),
-        ))
+        self.decode_transactionally(|decoder| {
+            Ok(PyArray1::from_iter(
+                py,
+                decoder.decode_iid_symbols(amt, &model).map(|symbol| {
+                    (symbol.unwrap_infallible() as i32).wrapping_add(min_supported_symbol)
+                }),
+            ))
+        })
     }
 
     /// .. deprecated:: 0.2.0
@@ -1090,7 +1358,38 @@ impl AnsCoder {
             None
         );
 
-        self.decode(py, model, PyTuple::new(py, [amt]))
+        self.decode(py, model, PyTuple::new(py, [amt]), None, None)
+    }
+
+    /// Returns an iterator that lazily decodes up to `amt` i.i.d. symbols with the concrete
+    /// entropy model `model`, popping one symbol off the stack per call to `next`.
+    ///
+    /// This yields the same symbols as `coder.decode(model, amt)` but without collecting them
+    /// into a numpy array first. This is useful in loops that feed each decoded symbol back
+    /// into the model before decoding the next one, or that may stop decoding early:
+    ///
+    /// ```python
+    /// model = constriction.stream.model.QuantizedGaussian(-100, 100, 12.6, 7.3)
+    /// coder = constriction.stream.stack.AnsCoder()
+    /// coder.encode_reverse(np.array([12, 15, 4, -2, 18, 5], dtype=np.int32), model)
+    ///
+    /// for symbol in coder.iter_decode(model, 6):
+    ///     if symbol < 0:
+    ///         break
+    ///     print(symbol) # (prints: 12, 15, 4)
+    /// ```
+    ///
+    /// The iterator operates directly on `coder` rather than on a copy of it, so you can
+    /// interleave iteration with other method calls on `coder` (including `encode_reverse`).
+    /// If a step fails then the iterator raises the exception without consuming any compressed
+    /// data in that step.
+    #[pyo3(text_signature = "(model, amt)")]
+    pub fn iter_decode(slf: PyRef<'_, Self>, model: &Model, amt: usize) -> AnsCoderIter {
+        AnsCoderIter {
+            coder: slf.into(),
+            model: Arc::clone(&model.0),
+            remaining: amt,
+        }
     }
 
     /// Creates a deep copy of the coder and returns it.
@@ -1103,3 +1402,159 @@ impl AnsCoder {
         Clone::clone(self)
     }
 }
+
+/// Opaque snapshot of an `AnsCoder`, returned by
+/// [`AnsCoder.snapshot`](#constriction.stream.stack.AnsCoder.snapshot).
+///
+/// Pass it to [`AnsCoder.restore`](#constriction.stream.stack.AnsCoder.restore) to return
+/// the coder to the recorded state.
+#[pyclass]
+#[derive(Debug, Clone, Copy)]
+pub struct AnsCoderSnapshot {
+    len: usize,
+    state: u64,
+    epoch: u64,
+}
+
+/// Source of unique identifiers for the epochs of all `UndoLog`s, so that a snapshot can't be
+/// restored on an unrelated coder.
+static NEXT_EPOCH: AtomicU64 = AtomicU64::new(0);
+
+/// Words that decoding popped off an `AnsCoder` since its snapshots were taken.
+///
+/// Within an epoch, the snapshots of a coder all refer to (prefixes of) a single reference
+/// stack, which consists of the bottom `low` words of the coder's current stack followed by
+/// the words in `spilled` (in reverse order). Encoding only pushes words above `low`, so it
+/// doesn't modify the reference stack; decoding below `low` moves words from the stack into
+/// `spilled`.
+#[derive(Debug, Clone, Default)]
+struct UndoLog {
+    /// `None` until the first call to `snapshot` so that coders without snapshots don't
+    /// keep an undo log.
+    epoch: Option<u64>,
+    low: usize,
+    /// The word at position `low` of the reference stack comes last.
+    spilled: Vec<u32>,
+}
+
+impl UndoLog {
+    /// Must be called before truncating `bulk` to length `new_len`.
+    fn record_truncation(&mut self, bulk: &[u32], new_len: usize) {
+        if self.epoch.is_some() && new_len < self.low {
+            self.spilled.extend(bulk[new_len..self.low].iter().rev());
+            self.low = new_len;
+        }
+    }
+
+    /// Returns the epoch for a snapshot of a stack with `len` words.
+    fn start_snapshot(&mut self, len: usize) -> u64 {
+        match self.epoch {
+            Some(epoch) if len == self.low => epoch,
+            Some(epoch) if self.spilled.is_empty() => {
+                // The stack grew but nothing below `low` changed, so existing snapshots
+                // remain valid.
+                self.low = len;
+                epoch
+            }
+            _ => {
+                let epoch = NEXT_EPOCH.fetch_add(1, Ordering::Relaxed);
+                *self = Self {
+                    epoch: Some(epoch),
+                    low: len,
+                    spilled: Vec::new(),
+                };
+                epoch
+            }
+        }
+    }
+
+    /// Turns `bulk` into the first `len` words of the reference stack.
+    fn rewind(&mut self, bulk: &mut Vec<u32>, len: usize) {
+        if len <= self.low {
+            self.record_truncation(bulk, len);
+            bulk.truncate(len);
+        } else {
+            bulk.truncate(self.low);
+            let num_restored = len - self.low;
+            let new_spilled_len = self.spilled.len() - num_restored;
+            bulk.extend(self.spilled.drain(new_spilled_len..).rev());
+            self.low = len;
+        }
+    }
+}
+
+/// Iterator returned by
+/// [`AnsCoder.iter_decode`](#constriction.stream.stack.AnsCoder.iter_decode).
+#[pyclass]
+#[allow(missing_debug_implementations)]
+pub struct AnsCoderIter {
+    coder: Py<AnsCoder>,
+    model: Arc<dyn internals::Model>,
+    remaining: usize,
+}
+
+#[pyproto]
+impl PyIterProtocol for AnsCoderIter {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> PyResult<Option<i32>> {
+        if slf.remaining == 0 {
+            return Ok(None);
+        }
+
+        let py = slf.py();
+        let mut coder = slf.coder.try_borrow_mut(py)?;
+        let strict = coder.strict;
+        let mut symbol = 0;
+        coder.decode_transactionally(|decoder| {
+            slf.model.as_parameterized(py, &mut |model| {
+                symbol = decode_symbol(decoder, EncoderDecoderModel(model), strict)?;
+                Ok(())
+            })
+        })?;
+        drop(coder);
+
+        slf.remaining -= 1;
+        Ok(Some(symbol))
+    }
+}
+
+impl AnsCoder {
+    /// Runs `f` on the wrapped coder and, if `f` fails, truncates the compressed data back
+    /// to where it was before `f` started. This is sufficient because encoding onto an
+    /// `AnsCoder` only ever pushes words onto its stack.
+    pub(crate) fn encode_transactionally<T>(
+        &mut self,
+        f: impl FnOnce(&mut crate::stream::stack::DefaultAnsCoder) -> PyResult<T>,
+    ) -> PyResult<T> {
+        let checkpoint = self.inner.pos();
+        let result = f(&mut self.inner);
+        if result.is_err() {
+            self.inner
+                .seek(checkpoint)
+                .expect("Encoding never removes words from the stack.");
+        }
+        result
+    }
+
+    /// Runs `f` on a temporary decoder that reads from the wrapped coder's compressed data
+    /// without consuming it, and commits the consumption to the wrapped coder only if `f`
+    /// succeeds. Decoding pops words off the stack, so it can't be undone after the fact.
+    pub(crate) fn decode_transactionally<T>(
+        &mut self,
+        f: impl FnOnce(
+            &mut crate::stream::stack::AnsCoder<u32, u64, Cursor<u32, &[u32]>>,
+        ) -> PyResult<T>,
+    ) -> PyResult<T> {
+        let mut decoder = self.inner.as_seekable_decoder();
+        let result = f(&mut decoder)?;
+        let pos = decoder.pos();
+        self.undo.record_truncation(self.inner.bulk(), pos.0);
+        self.inner
+            .seek(pos)
+            .expect("Decoding never pushes words onto the stack.");
+        Ok(result)
+    }
+}
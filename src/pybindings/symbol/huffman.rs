@@ -3,7 +3,7 @@ use std::prelude::v1::*;
 use numpy::PyReadonlyArray1;
 use pyo3::prelude::*;
 
-use crate::symbol::huffman::{self, NanError};
+use crate::symbol::huffman::{self, HuffmanError, NanError, TieBreaking};
 
 pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
     module.add_class::<EncoderHuffmanTree>()?;
@@ -21,11 +21,18 @@ pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
 /// rescaling probabilities can, in edge cases, affect the shape of the Huffman tree due
 /// to rounding errors, so be consistent with how you scale probabilities).
 ///
+/// The optional argument `tie_breaking` determines which nodes get merged if several nodes
+/// of the Huffman tree have the same probability. It must be one of `"by_index"` (the
+/// default), `"by_symbol"`, or `"merged_first"`; see [`TieBreaking` in the Rust
+/// API](https://docs.rs/constriction/latest/constriction/symbol/huffman/enum.TieBreaking.html)
+/// for the exact rules. An `EncoderHuffmanTree` and a `DecoderHuffmanTree` are compatible
+/// if and only if they were built from the same probabilities with the same policy.
+///
 /// # Examples
 ///
 /// See [examples](../symbol.html#examples) in parent module.
 #[pyclass]
-#[pyo3(text_signature = "(probabilities)")]
+#[pyo3(text_signature = "(probabilities, tie_breaking=\"by_index\")")]
 #[derive(Debug)]
 pub struct EncoderHuffmanTree {
     pub(crate) inner: huffman::EncoderHuffmanTree,
@@ -34,10 +41,13 @@ pub struct EncoderHuffmanTree {
 #[pymethods]
 impl EncoderHuffmanTree {
     #[new]
-    pub fn new(probabilities: PyReadonlyArray1<'_, f64>) -> PyResult<Self> {
-        let inner = huffman::EncoderHuffmanTree::from_float_probabilities::<f64, _>(
-            probabilities.iter().unwrap(),
-        )?;
+    #[args(probabilities, tie_breaking = "\"by_index\"")]
+    pub fn new(probabilities: PyReadonlyArray1<'_, f64>, tie_breaking: &str) -> PyResult<Self> {
+        let inner =
+            huffman::EncoderHuffmanTree::checked_from_float_probabilities_with_tie_breaking::<
+                f64,
+                _,
+            >(probabilities.as_array(), parse_tie_breaking(tie_breaking)?)?;
 
         Ok(Self { inner })
     }
@@ -53,11 +63,18 @@ impl EncoderHuffmanTree {
 /// rescaling probabilities can, in edge cases, affect the shape of the Huffman tree due
 /// to rounding errors, so be consistent with how you scale probabilities).
 ///
+/// The optional argument `tie_breaking` determines which nodes get merged if several nodes
+/// of the Huffman tree have the same probability. It must be one of `"by_index"` (the
+/// default), `"by_symbol"`, or `"merged_first"`; see [`TieBreaking` in the Rust
+/// API](https://docs.rs/constriction/latest/constriction/symbol/huffman/enum.TieBreaking.html)
+/// for the exact rules. An `EncoderHuffmanTree` and a `DecoderHuffmanTree` are compatible
+/// if and only if they were built from the same probabilities with the same policy.
+///
 /// # Examples
 ///
 /// See [examples](../symbol.html#examples) in parent module.
 #[pyclass]
-#[pyo3(text_signature = "(probabilities)")]
+#[pyo3(text_signature = "(probabilities, tie_breaking=\"by_index\")")]
 #[derive(Debug)]
 pub struct DecoderHuffmanTree {
     pub(crate) inner: huffman::DecoderHuffmanTree,
@@ -66,15 +83,29 @@ pub struct DecoderHuffmanTree {
 #[pymethods]
 impl DecoderHuffmanTree {
     #[new]
-    pub fn new(probabilities: PyReadonlyArray1<'_, f64>) -> PyResult<Self> {
-        let inner = huffman::DecoderHuffmanTree::from_float_probabilities::<f64, _>(
-            probabilities.iter().unwrap(),
-        )?;
+    #[args(probabilities, tie_breaking = "\"by_index\"")]
+    pub fn new(probabilities: PyReadonlyArray1<'_, f64>, tie_breaking: &str) -> PyResult<Self> {
+        let inner =
+            huffman::DecoderHuffmanTree::checked_from_float_probabilities_with_tie_breaking::<
+                f64,
+                _,
+            >(probabilities.as_array(), parse_tie_breaking(tie_breaking)?)?;
 
         Ok(Self { inner })
     }
 }
 
+fn parse_tie_breaking(tie_breaking: &str) -> PyResult<TieBreaking> {
+    match tie_breaking {
+        "by_index" => Ok(TieBreaking::ByIndex),
+        "by_symbol" => Ok(TieBreaking::BySymbol),
+        "merged_first" => Ok(TieBreaking::MergedFirst),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(
+            "`tie_breaking` must be one of \"by_index\", \"by_symbol\", or \"merged_first\".",
+        )),
+    }
+}
+
 impl From<NanError> for PyErr {
     fn from(err: NanError) -> Self {
         match err {
@@ -82,3 +113,17 @@ impl From<NanError> for PyErr {
         }
     }
 }
+
+impl From<HuffmanError<NanError>> for PyErr {
+    fn from(err: HuffmanError<NanError>) -> Self {
+        match err {
+            HuffmanError::InvalidProbability(err) => err.into(),
+            HuffmanError::EmptyAlphabet => {
+                pyo3::exceptions::PyValueError::new_err("`probabilities` must not be empty.")
+            }
+            HuffmanError::TooManySymbols => {
+                pyo3::exceptions::PyValueError::new_err("Too many symbols for a Huffman tree.")
+            }
+        }
+    }
+}
@@ -0,0 +1,65 @@
+use std::prelude::v1::*;
+
+use numpy::{PyArray1, PyReadonlyArray1};
+use pyo3::{prelude::*, wrap_pyfunction};
+
+use crate::timeseries::{self, Predictor};
+
+pub fn init_module(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_function(wrap_pyfunction!(encode, module)?)?;
+    module.add_function(wrap_pyfunction!(decode, module)?)?;
+    Ok(())
+}
+
+/// Compresses a time series of integer samples.
+///
+/// Returns the compressed data as a rank-1 numpy array with `dtype=np.uint32`, which can be
+/// passed to the function `decode` to reconstruct the samples. The compressed data is
+/// self-contained, i.e., it records the number of samples and the employed predictor in a
+/// compact header.
+///
+/// ## Arguments
+///
+/// - **samples** --- a rank-1 numpy array with `dtype=np.int32`.
+/// - **order** --- the order of the linear predictor: either `1` (encodes differences
+///   between consecutive samples; works well for signals that resemble a random walk) or
+///   `2` (encodes differences between consecutive differences; works well for smooth
+///   signals). Defaults to `1`.
+///
+/// ## Example
+///
+/// ```python
+/// samples = np.array([1000, 1003, 1007, 1010, 1012, 1015, 1017, 1018, 1020, 1021], dtype=np.int32)
+/// compressed = constriction.timeseries.encode(samples, 2)
+/// print(compressed) # (prints: [1173357416 183334571])
+/// decoded = constriction.timeseries.decode(compressed)
+/// assert np.all(decoded == samples)
+/// ```
+#[pyfunction]
+#[pyo3(text_signature = "(samples, order=1)")]
+fn encode<'py>(
+    py: Python<'py>,
+    samples: PyReadonlyArray1<'_, i32>,
+    order: Option<u32>,
+) -> PyResult<&'py PyArray1<u32>> {
+    let predictor = Predictor::from_order(order.unwrap_or(1))
+        .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("`order` must be either 1 or 2."))?;
+    let compressed = timeseries::encode(samples.as_slice()?, predictor);
+    Ok(PyArray1::from_vec(py, compressed))
+}
+
+/// Reconstructs a time series from the output of the function `encode`.
+///
+/// Returns a rank-1 numpy array with `dtype=np.int32`. Raises a `ValueError` if
+/// `compressed` is not a valid compressed time series (not all invalid data can be
+/// detected, though).
+#[pyfunction]
+#[pyo3(text_signature = "(compressed)")]
+fn decode<'py>(
+    py: Python<'py>,
+    compressed: PyReadonlyArray1<'_, u32>,
+) -> PyResult<&'py PyArray1<i32>> {
+    let samples = timeseries::decode(compressed.as_slice()?)
+        .map_err(|err| pyo3::exceptions::PyValueError::new_err(err.to_string()))?;
+    Ok(PyArray1::from_vec(py, samples))
+}
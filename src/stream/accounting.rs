@@ -0,0 +1,410 @@
+//! Attributing bit rate to several logical streams within a single encoder
+//!
+//! Many compression pipelines encode several logical streams of symbols into the same
+//! entropy coder, e.g., the side information ("hyperprior") and the latent representation
+//! in a hierarchical codec. The size of the compressed data tells you only the total bit
+//! rate, which makes it hard to tell which part of the model should be improved.
+//!
+//! An [`AccountingEncoder`] wraps any encoder and labels each encoded symbol with the
+//! logical stream that it belongs to (set via [`set_stream`]). It attributes the
+//! information content of each symbol under its entropy model (i.e., `-log2(P(symbol))`
+//! with the fixed-point probability that is actually used for encoding) to the current
+//! stream. The resulting [`BitAllocation`] lists the number of symbols and bits for each
+//! stream. Summed over all streams, the attributed bits equal the compressed size up to the
+//! small constant overhead of the entropy coder, regardless of whether the coder has queue
+//! or stack semantics (the compressed words of an [`AnsCoder`] can't be attributed to
+//! individual symbols directly). To convert bits into a number of compressed words, divide
+//! by `Word::BITS`.
+//!
+//! Labels can be of any type that implements `PartialEq`, e.g., a fieldless `enum` with one
+//! variant per logical stream. Streams appear in the report in the order in which they were
+//! first used.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     accounting::AccountingEncoder, model::DefaultLeakyQuantizer, stack::DefaultAnsCoder,
+//!     Encode,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+//! enum Stream {
+//!     Hyperprior,
+//!     Latents,
+//! }
+//!
+//! let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+//! let side_model = quantizer.quantize(Gaussian::new(0.0, 2.0));
+//! let latent_model = quantizer.quantize(Gaussian::new(0.0, 20.0));
+//!
+//! let mut encoder = AccountingEncoder::new(DefaultAnsCoder::new(), Stream::Latents);
+//! encoder.encode_iid_symbols(&[12, -31, 7, 25, -3, 40], &latent_model).unwrap();
+//! encoder.set_stream(Stream::Hyperprior);
+//! encoder.encode_iid_symbols(&[1, -2], &side_model).unwrap();
+//!
+//! let (coder, allocation) = encoder.into_parts();
+//! let latents = allocation.get(&Stream::Latents).unwrap();
+//! let hyperprior = allocation.get(&Stream::Hyperprior).unwrap();
+//! assert_eq!(latents.num_symbols(), 6);
+//! assert_eq!(hyperprior.num_symbols(), 2);
+//! assert!(latents.bits() > hyperprior.bits());
+//!
+//! // The attributed bits add up to the compressed size (up to a small constant overhead).
+//! assert!((allocation.total_bits() - coder.num_valid_bits() as f64).abs() < 32.0);
+//! ```
+//!
+//! [`set_stream`]: AccountingEncoder::set_stream
+//! [`AnsCoder`]: super::stack::AnsCoder
+
+use alloc::vec::Vec;
+use core::{
+    borrow::Borrow,
+    fmt::{Debug, Display},
+};
+
+use num::{cast::AsPrimitive, Float, ToPrimitive};
+
+use super::{model::EncoderModel, Code, Encode};
+use crate::{BitArray, CoderError, NonZeroBitArray, Pos, PosSeek};
+
+/// Wrapper around an encoder that attributes the bit rate of each encoded symbol to a
+/// labeled logical stream.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct AccountingEncoder<Encoder, Label> {
+    inner: Encoder,
+    current: Label,
+    allocation: BitAllocation<Label>,
+}
+
+impl<Encoder, Label: PartialEq> AccountingEncoder<Encoder, Label> {
+    /// Wraps `inner` and attributes all symbols to the stream `initial_stream` until the
+    /// next call to [`set_stream`](Self::set_stream).
+    pub fn new(inner: Encoder, initial_stream: Label) -> Self {
+        Self {
+            inner,
+            current: initial_stream,
+            allocation: BitAllocation::new(),
+        }
+    }
+
+    /// Attributes all symbols that get encoded from now on to the stream `label`.
+    pub fn set_stream(&mut self, label: Label) {
+        self.current = label;
+    }
+
+    /// Returns the label of the stream to which newly encoded symbols get attributed.
+    pub fn stream(&self) -> &Label {
+        &self.current
+    }
+
+    /// Returns the bit allocation of all symbols that have been encoded so far.
+    pub fn allocation(&self) -> &BitAllocation<Label> {
+        &self.allocation
+    }
+
+    /// Returns a reference to the wrapped encoder.
+    pub fn get_ref(&self) -> &Encoder {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped encoder.
+    ///
+    /// Symbols that are encoded directly on the returned reference are not accounted for.
+    pub fn get_mut(&mut self) -> &mut Encoder {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped encoder.
+    pub fn into_inner(self) -> Encoder {
+        self.inner
+    }
+
+    /// Consumes the wrapper and returns both the wrapped encoder and the bit allocation.
+    pub fn into_parts(self) -> (Encoder, BitAllocation<Label>) {
+        (self.inner, self.allocation)
+    }
+}
+
+impl<Encoder: Code, Label> Code for AccountingEncoder<Encoder, Label> {
+    type Word = Encoder::Word;
+    type State = Encoder::State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Encoder, Label, const PRECISION: usize> Encode<PRECISION> for AccountingEncoder<Encoder, Label>
+where
+    Encoder: Encode<PRECISION>,
+    Label: PartialEq + Clone,
+{
+    type FrontendError = Encoder::FrontendError;
+    type BackendError = Encoder::BackendError;
+
+    /// Encodes a symbol with the wrapped encoder and, on success, attributes its
+    /// information content to the current stream.
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        let probability = model
+            .left_cumulative_and_probability(symbol.borrow())
+            .map(|(_, probability)| probability.get());
+        self.inner.encode_symbol(symbol, model)?;
+
+        // If the symbol had zero probability then encoding would have failed above.
+        if let Some(probability) = probability {
+            let probability = probability.to_f64().unwrap_or(f64::NAN);
+            let bits = PRECISION as f64 - Float::log2(probability);
+            self.allocation.record(&self.current, bits);
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.inner.maybe_full()
+    }
+}
+
+impl<Encoder: PosSeek, Label> PosSeek for AccountingEncoder<Encoder, Label> {
+    type Position = Encoder::Position;
+}
+
+impl<Encoder: Pos, Label> Pos for AccountingEncoder<Encoder, Label> {
+    #[inline(always)]
+    fn pos(&self) -> Self::Position {
+        self.inner.pos()
+    }
+}
+
+/// Breakdown of the bit rate by logical stream, as recorded by an [`AccountingEncoder`].
+///
+/// Implements `Display` (if `Label` does) to print a table with one row per stream.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitAllocation<Label> {
+    streams: Vec<StreamAllocation<Label>>,
+}
+
+impl<Label> Default for BitAllocation<Label> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Label> BitAllocation<Label> {
+    pub fn new() -> Self {
+        Self {
+            streams: Vec::new(),
+        }
+    }
+
+    /// Returns the per-stream entries in the order in which the streams were first used.
+    pub fn streams(&self) -> &[StreamAllocation<Label>] {
+        &self.streams
+    }
+
+    /// Returns the entry for the stream `label`, or `None` if no symbol was attributed to
+    /// it.
+    pub fn get(&self, label: &Label) -> Option<&StreamAllocation<Label>>
+    where
+        Label: PartialEq,
+    {
+        self.streams.iter().find(|stream| stream.label == *label)
+    }
+
+    /// Returns the total number of bits attributed to all streams.
+    pub fn total_bits(&self) -> f64 {
+        self.streams.iter().map(|stream| stream.bits).sum()
+    }
+
+    /// Returns the total number of symbols in all streams.
+    pub fn total_symbols(&self) -> usize {
+        self.streams.iter().map(|stream| stream.num_symbols).sum()
+    }
+
+    /// Attributes a symbol with information content `bits` to the stream `label`.
+    pub fn record(&mut self, label: &Label, bits: f64)
+    where
+        Label: PartialEq + Clone,
+    {
+        match self
+            .streams
+            .iter_mut()
+            .find(|stream| stream.label == *label)
+        {
+            Some(stream) => {
+                stream.num_symbols += 1;
+                stream.bits += bits;
+            }
+            None => self.streams.push(StreamAllocation {
+                label: label.clone(),
+                num_symbols: 1,
+                bits,
+            }),
+        }
+    }
+}
+
+impl<Label: Display> Display for BitAllocation<Label> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let total_bits = self.total_bits();
+        writeln!(
+            f,
+            "{:<16} {:>12} {:>16} {:>12} {:>8}",
+            "stream", "symbols", "bits", "bits/symbol", "share"
+        )?;
+        for stream in &self.streams {
+            let share = if total_bits > 0.0 {
+                100.0 * stream.bits / total_bits
+            } else {
+                0.0
+            };
+            writeln!(
+                f,
+                "{:<16} {:>12} {:>16.1} {:>12.3} {:>7.1}%",
+                alloc::format!("{}", stream.label),
+                stream.num_symbols,
+                stream.bits,
+                stream.bits_per_symbol(),
+                share
+            )?;
+        }
+        write!(
+            f,
+            "{:<16} {:>12} {:>16.1}",
+            "total",
+            self.total_symbols(),
+            total_bits
+        )
+    }
+}
+
+/// Number of symbols and bits attributed to a single logical stream.
+///
+/// See [`BitAllocation`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamAllocation<Label> {
+    label: Label,
+    num_symbols: usize,
+    bits: f64,
+}
+
+impl<Label> StreamAllocation<Label> {
+    pub fn label(&self) -> &Label {
+        &self.label
+    }
+
+    pub fn num_symbols(&self) -> usize {
+        self.num_symbols
+    }
+
+    /// Returns the total information content (in bits) of all symbols in the stream.
+    pub fn bits(&self) -> f64 {
+        self.bits
+    }
+
+    /// Returns the number of compressed `Word`s that the bits of this stream correspond to
+    /// (not rounded since streams share words at their boundaries).
+    pub fn words<Word: BitArray>(&self) -> f64 {
+        self.bits / Word::BITS as f64
+    }
+
+    pub fn bits_per_symbol(&self) -> f64 {
+        self.bits / self.num_symbols as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::DefaultLeakyQuantizer,
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        Decode,
+    };
+    use super::*;
+
+    use alloc::{string::ToString, vec::Vec};
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn allocation() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let side_model = quantizer.quantize(Gaussian::new(0.0, 2.0));
+        let latent_model = quantizer.quantize(Gaussian::new(0.0, 20.0));
+        let side = (0..30).map(|i| (i * 3) % 7 - 3).collect::<Vec<i32>>();
+        let latents = (0..200).map(|i| (i * 17) % 81 - 40).collect::<Vec<i32>>();
+
+        let mut encoder = AccountingEncoder::new(DefaultRangeEncoder::new(), "side");
+        encoder.encode_iid_symbols(&side, &side_model).unwrap();
+        encoder.set_stream("latents");
+        encoder
+            .encode_iid_symbols(&latents[..100], &latent_model)
+            .unwrap();
+        encoder.set_stream("side");
+        encoder.encode_symbol(2, &side_model).unwrap();
+        encoder.set_stream("latents");
+        encoder
+            .encode_iid_symbols(&latents[100..], &latent_model)
+            .unwrap();
+
+        // Failed encoding operations aren't accounted for.
+        assert!(encoder.encode_symbol(1000, &latent_model).is_err());
+
+        let (encoder, allocation) = encoder.into_parts();
+        let labels = allocation
+            .streams()
+            .iter()
+            .map(|stream| *stream.label())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, ["side", "latents"]);
+        assert_eq!(allocation.get(&"side").unwrap().num_symbols(), 31);
+        assert_eq!(allocation.get(&"latents").unwrap().num_symbols(), 200);
+        assert_eq!(allocation.total_symbols(), 231);
+        assert!(allocation.get(&"other").is_none());
+
+        let expected_side = side
+            .iter()
+            .chain(core::iter::once(&2))
+            .map(|&symbol| -side_model.floating_point_probability::<f64>(symbol).log2())
+            .sum::<f64>();
+        let side_bits = allocation.get(&"side").unwrap().bits();
+        assert!((side_bits - expected_side).abs() < 1e-6);
+
+        let num_bits = encoder.num_bits() as f64;
+        assert!(allocation.total_bits() <= num_bits);
+        assert!(allocation.total_bits() > num_bits - 64.0);
+        let words = allocation
+            .streams()
+            .iter()
+            .map(|stream| stream.words::<u32>())
+            .sum::<f64>();
+        assert!((words * 32.0 - allocation.total_bits()).abs() < 1e-6);
+
+        let report = allocation.to_string();
+        assert!(report.contains("side"));
+        assert!(report.contains("latents"));
+        assert!(report.contains("total"));
+
+        // Accounting doesn't change the compressed data.
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(30, &side_model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, side);
+    }
+}
@@ -0,0 +1,871 @@
+//! Entropy models that learn the statistics of the source while coding
+//!
+//! The entropy models in the sister module [`model`] have fixed parameters, so you have to
+//! know (or transmit) the statistics of your data before you can compress it. The models in
+//! this module instead start from an uninformed state and update themselves after each
+//! encoded or decoded symbol. Since the decoder performs the same updates as the encoder,
+//! no model parameters have to be transmitted, and you can compress data with unknown
+//! statistics in a single pass.
+//!
+//! Currently, this module provides two adaptive categorical models (both of which implement
+//! the [`ContextModel`] trait with an empty context, so you can swap them for a model that
+//! takes preceding symbols into account, like an [`OrderNContextModel`]):
+//!
+//! - the [`KTEstimator`], a Bayesian mixture over all categorical distributions on a finite
+//!   alphabet, which comes with a provable bound on how many bits it spends in excess of
+//!   the best fixed categorical distribution; and
+//! - the [`DecayingEstimator`], which gradually forgets old observations and is therefore
+//!   better suited for non-stationary sources (e.g., log streams whose statistics drift
+//!   over time).
+//!
+//! [`model`]: super::model
+//! [`OrderNContextModel`]: super::context::OrderNContextModel
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use num::cast::AsPrimitive;
+
+use super::{context::ContextModel, model::ContiguousCategoricalEntropyModel, Decode, Encode};
+use crate::{BitArray, CoderError};
+
+/// An adaptive categorical model that uses the Krichevsky–Trofimov (KT) estimator.
+///
+/// A `KTEstimator` models symbols from the alphabet `0..alphabet_size`. It counts how
+/// often each symbol has occurred so far and assigns the probability
+///
+/// ```text
+/// P(next symbol = i) = (count_i + 1/2) / (num_observations + alphabet_size / 2)
+/// ```
+///
+/// to the next symbol. This is the posterior predictive distribution of a categorical
+/// distribution with a Dirichlet(1/2, ..., 1/2) prior, i.e., coding a sequence with a
+/// `KTEstimator` is equivalent to coding it with a Bayesian mixture over all categorical
+/// distributions. The resulting code length exceeds the code length under the best
+/// categorical distribution chosen *in hindsight* by at most
+/// `(alphabet_size - 1) / 2 * log2(N) + O(1)` bits for a sequence of length `N`, which
+/// is the optimal rate for universal coding of memoryless sources. For a binary alphabet,
+/// the excess is at most `log2(N) / 2 + 1` bits. On top of this, the actual code length
+/// includes a small overhead due to approximating the probabilities in fixed point
+/// arithmetic with `PRECISION` bits.
+///
+/// Each call to [`model`](Self::model) builds a categorical entropy model from the current
+/// counts using [`MinimalCrossEntropy`] rounding, which takes at least `Θ(N log(N))`
+/// time for `N = alphabet_size`, plus an input dependent number of linear passes over the
+/// alphabet. The `KTEstimator` is therefore intended for small alphabets.
+///
+/// [`MinimalCrossEntropy`]: super::model::RoundingPolicy::MinimalCrossEntropy
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     adaptive::DefaultKTEstimator,
+///     queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+/// };
+///
+/// // A heavily biased binary source whose bias is unknown to the encoder and decoder.
+/// let bits = [0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0];
+///
+/// let mut encoder = DefaultRangeEncoder::new();
+/// let mut estimator = DefaultKTEstimator::new(2).unwrap();
+/// for &bit in &bits {
+///     estimator.encode_symbol(&mut encoder, bit).unwrap();
+/// }
+/// let compressed = encoder.into_compressed().unwrap();
+///
+/// // The decoder starts from a fresh estimator and performs the same updates.
+/// let mut decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+/// let mut estimator = DefaultKTEstimator::new(2).unwrap();
+/// let decoded = (0..bits.len())
+///     .map(|_| estimator.decode_symbol(&mut decoder).unwrap())
+///     .collect::<Vec<_>>();
+/// assert_eq!(decoded, bits);
+/// assert_eq!(estimator.counts(), [17, 3]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct KTEstimator<Probability: BitArray, const PRECISION: usize> {
+    counts: Vec<u64>,
+    num_observations: u64,
+    phantom: PhantomData<Probability>,
+}
+
+/// Type alias for a [`KTEstimator`] with sane settings.
+///
+/// Generates entropy models that can be used with a [`DefaultAnsCoder`] or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: super::stack::DefaultAnsCoder
+/// [`DefaultRangeEncoder`]: super::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: super::queue::DefaultRangeDecoder
+pub type DefaultKTEstimator = KTEstimator<u32, 24>;
+
+/// Type alias for a [`KTEstimator`] optimized for compatibility with lookup decoder
+/// models.
+///
+/// Generates entropy models that can be used with a [`SmallAnsCoder`] or a
+/// [`SmallRangeEncoder`] / [`SmallRangeDecoder`].
+///
+/// [`SmallAnsCoder`]: super::stack::SmallAnsCoder
+/// [`SmallRangeEncoder`]: super::queue::SmallRangeEncoder
+/// [`SmallRangeDecoder`]: super::queue::SmallRangeDecoder
+pub type SmallKTEstimator = KTEstimator<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> KTEstimator<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Creates an estimator for the alphabet `0..alphabet_size` that has not observed any
+    /// symbols yet, i.e., whose entropy model is uniform.
+    ///
+    /// Returns an error if `alphabet_size` is smaller than two or too large to assign a
+    /// nonzero probability to each symbol with `PRECISION` bits.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(alphabet_size: usize) -> Result<Self, ()> {
+        if alphabet_size < 2 {
+            return Err(());
+        }
+        let estimator = Self {
+            counts: alloc::vec![0; alphabet_size],
+            num_observations: 0,
+            phantom: PhantomData,
+        };
+
+        // Since all probabilities are bounded away from zero and the model is leaky, this
+        // check only fails if the alphabet is too large for `PRECISION`, and it can't start
+        // failing later once counts change.
+        let _: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+                &estimator.probabilities(),
+            )?;
+        Ok(estimator)
+    }
+
+    /// Returns the size of the alphabet, i.e., the number of distinct symbols.
+    pub fn alphabet_size(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns how often each symbol has been observed so far.
+    pub fn counts(&self) -> &[u64] {
+        &self.counts
+    }
+
+    /// Returns the total number of observed symbols, i.e., the sum of all [`counts`].
+    ///
+    /// [`counts`]: Self::counts
+    pub fn num_observations(&self) -> u64 {
+        self.num_observations
+    }
+
+    /// Returns the KT estimates of the probabilities of all symbols in floating point
+    /// arithmetic.
+    ///
+    /// The returned probabilities are strictly positive and sum to one (up to rounding
+    /// errors). The entropy model returned by [`model`](Self::model) approximates them in
+    /// fixed point arithmetic.
+    pub fn probabilities(&self) -> Vec<f64> {
+        let denominator = self.num_observations as f64 + 0.5 * self.counts.len() as f64;
+        self.counts
+            .iter()
+            .map(|&count| (count as f64 + 0.5) / denominator)
+            .collect()
+    }
+
+    /// Returns the entropy model for the next symbol.
+    pub fn model(
+        &self,
+    ) -> ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        ContiguousCategoricalEntropyModel::from_floating_point_probabilities(&self.probabilities())
+            .expect("Checked in constructor.")
+    }
+
+    /// Records an observation of `symbol`.
+    ///
+    /// You'll usually not have to call this method directly since [`encode_symbol`] and
+    /// [`decode_symbol`] call it for you.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` is not smaller than the alphabet size.
+    ///
+    /// [`encode_symbol`]: Self::encode_symbol
+    /// [`decode_symbol`]: Self::decode_symbol
+    pub fn update(&mut self, symbol: usize) {
+        self.counts[symbol] += 1;
+        self.num_observations += 1;
+    }
+
+    /// Encodes `symbol` with the current [`model`](Self::model) and then [`update`]s the
+    /// estimator.
+    ///
+    /// Use this method with coders that have queue semantics (e.g., a [`RangeEncoder`]).
+    /// For coders with stack semantics, use [`encode_symbols_reverse`] instead.
+    ///
+    /// If `symbol` is not smaller than the alphabet size, then the `encoder` returns its
+    /// error for impossible symbols, and neither the `encoder` nor the estimator is changed.
+    ///
+    /// [`update`]: Self::update
+    /// [`RangeEncoder`]: super::queue::RangeEncoder
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    pub fn encode_symbol<E>(
+        &mut self,
+        encoder: &mut E,
+        symbol: usize,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        encoder.encode_symbol(symbol, self.model())?;
+        self.update(symbol);
+        Ok(())
+    }
+
+    /// Encodes `symbols` on a coder with stack semantics (e.g., an [`AnsCoder`]) such that
+    /// calling [`decode_symbol`] repeatedly decodes them in their original order.
+    ///
+    /// Since a stack decodes symbols in reverse order, this method first runs the estimator
+    /// over all `symbols` to determine the entropy model for each one, and then encodes
+    /// them in reverse order. This requires memory proportional to
+    /// `symbols.len() * alphabet_size`. Afterwards, the estimator is in the same state as
+    /// after calling [`encode_symbol`] for each symbol.
+    ///
+    /// If any of the `symbols` is not smaller than the alphabet size, then the `encoder`
+    /// returns its error for impossible symbols, and neither the `encoder` nor the
+    /// estimator is changed.
+    ///
+    /// [`AnsCoder`]: super::stack::AnsCoder
+    /// [`decode_symbol`]: Self::decode_symbol
+    /// [`encode_symbol`]: Self::encode_symbol
+    pub fn encode_symbols_reverse<E>(
+        &mut self,
+        encoder: &mut E,
+        symbols: &[usize],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        if let Some(&symbol) = symbols
+            .iter()
+            .find(|&&symbol| symbol >= self.alphabet_size())
+        {
+            // Fails without changing the `encoder` since the model has no probability mass
+            // for `symbol`.
+            return encoder.encode_symbol(symbol, self.model());
+        }
+
+        let models = symbols
+            .iter()
+            .map(|&symbol| {
+                let model = self.model();
+                self.update(symbol);
+                model
+            })
+            .collect::<Vec<_>>();
+        encoder.encode_symbols(symbols.iter().zip(models).rev())
+    }
+
+    /// Decodes a symbol with the current [`model`](Self::model) and then [`update`]s the
+    /// estimator with the decoded symbol.
+    ///
+    /// Works both for data encoded with [`encode_symbol`] on a coder with queue semantics
+    /// and for data encoded with [`encode_symbols_reverse`] on a coder with stack semantics.
+    ///
+    /// [`update`]: Self::update
+    /// [`encode_symbol`]: Self::encode_symbol
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    pub fn decode_symbol<D>(
+        &mut self,
+        decoder: &mut D,
+    ) -> Result<usize, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        Probability: Into<D::Word>,
+        D::Word: AsPrimitive<Probability>,
+    {
+        let symbol = decoder.decode_symbol(self.model())?;
+        self.update(symbol);
+        Ok(symbol)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> ContextModel<PRECISION>
+    for KTEstimator<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    type Probability = Probability;
+
+    fn alphabet_size(&self) -> usize {
+        KTEstimator::alphabet_size(self)
+    }
+
+    fn model(&self) -> ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        KTEstimator::model(self)
+    }
+
+    fn update(&mut self, symbol: usize) {
+        KTEstimator::update(self, symbol)
+    }
+}
+
+/// An adaptive categorical model whose counts decay exponentially with a given half-life.
+///
+/// A `DecayingEstimator` works like a [`KTEstimator`] except that each observation loses
+/// weight as more symbols are observed: an observation that lies `half_life` symbols in
+/// the past counts only half as much as the most recent one. The probability of the next
+/// symbol is
+///
+/// ```text
+/// P(next symbol = i) = (w_i + 1/2) / (sum_j w_j + alphabet_size / 2)
+/// ```
+///
+/// where `w_i = sum_t 2^(-age_t / half_life)`, and the sum runs over all past occurrences
+/// of symbol `i`, with `age_t = 0` for the most recent observation. Thus, the model tracks
+/// sources whose statistics change over time, at the cost of a noisier estimate on
+/// stationary sources. An infinite `half_life` turns off forgetting, and the
+/// `DecayingEstimator` then assigns the same probabilities as a [`KTEstimator`].
+///
+/// Updates take amortized constant time: instead of multiplying all counts by the decay
+/// factor after each observation, the estimator stores counts in a scaled unit that grows
+/// by the inverse decay factor with each observation, and it only touches all counts when
+/// the scale has to be reset to avoid overflow. Building an entropy model with
+/// [`model`](Self::model) still takes at least linearithmic time in `alphabet_size` (see
+/// [`KTEstimator`]).
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     adaptive::{DefaultDecayingEstimator, DefaultKTEstimator},
+///     queue::DefaultRangeEncoder,
+/// };
+///
+/// // A source that switches from mostly zeros to mostly ones halfway through.
+/// let symbols = (0..2000)
+///     .map(|i| ((i < 1000) != (i % 10 == 0)) as usize ^ 1)
+///     .collect::<Vec<_>>();
+///
+/// let mut decaying_encoder = DefaultRangeEncoder::new();
+/// let mut decaying = DefaultDecayingEstimator::new(2, 100.0).unwrap();
+/// let mut kt_encoder = DefaultRangeEncoder::new();
+/// let mut kt = DefaultKTEstimator::new(2).unwrap();
+/// for &symbol in &symbols {
+///     decaying.encode_symbol(&mut decaying_encoder, symbol).unwrap();
+///     kt.encode_symbol(&mut kt_encoder, symbol).unwrap();
+/// }
+///
+/// // Forgetting the statistics of the first half pays off in the second half.
+/// assert!(decaying_encoder.num_bits() < kt_encoder.num_bits());
+/// ```
+///
+/// Decoding works as with a [`KTEstimator`]: start from a fresh `DecayingEstimator` with
+/// the same parameters and call [`decode_symbol`](Self::decode_symbol) repeatedly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecayingEstimator<Probability: BitArray, const PRECISION: usize> {
+    /// Counts in units of `scale`, i.e., the effective counts are `counts[i] / scale`.
+    counts: Vec<f64>,
+
+    /// Sum of `counts`, in units of `scale`.
+    total: f64,
+
+    /// Weight of the most recent observation in the scaled unit of `counts`.
+    scale: f64,
+
+    /// Factor by which the weight of each observation decays per observed symbol.
+    decay: f64,
+
+    half_life: f64,
+    phantom: PhantomData<Probability>,
+}
+
+/// Type alias for a [`DecayingEstimator`] with sane settings.
+///
+/// Generates entropy models that can be used with a [`DefaultAnsCoder`] or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: super::stack::DefaultAnsCoder
+/// [`DefaultRangeEncoder`]: super::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: super::queue::DefaultRangeDecoder
+pub type DefaultDecayingEstimator = DecayingEstimator<u32, 24>;
+
+/// Type alias for a [`DecayingEstimator`] optimized for compatibility with lookup decoder
+/// models.
+///
+/// Generates entropy models that can be used with a [`SmallAnsCoder`] or a
+/// [`SmallRangeEncoder`] / [`SmallRangeDecoder`].
+///
+/// [`SmallAnsCoder`]: super::stack::SmallAnsCoder
+/// [`SmallRangeEncoder`]: super::queue::SmallRangeEncoder
+/// [`SmallRangeDecoder`]: super::queue::SmallRangeDecoder
+pub type SmallDecayingEstimator = DecayingEstimator<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> DecayingEstimator<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Once `scale` exceeds this value, all counts are converted back to unit scale.
+    const MAX_SCALE: f64 = (1u64 << 32) as f64;
+
+    /// Creates an estimator for the alphabet `0..alphabet_size` that has not observed any
+    /// symbols yet, i.e., whose entropy model is uniform.
+    ///
+    /// The weight of each observation halves every `half_life` symbols. Pass
+    /// `f64::INFINITY` to turn off forgetting.
+    ///
+    /// Returns an error if `alphabet_size` is smaller than two or too large to assign a
+    /// nonzero probability to each symbol with `PRECISION` bits, or if `half_life` is not
+    /// positive (or NaN).
+    #[allow(clippy::result_unit_err)]
+    pub fn new(alphabet_size: usize, half_life: f64) -> Result<Self, ()> {
+        if alphabet_size < 2 || half_life.is_nan() || half_life <= 0.0 {
+            return Err(());
+        }
+        let estimator = Self {
+            counts: alloc::vec![0.0; alphabet_size],
+            total: 0.0,
+            scale: 1.0,
+            decay: (-1.0 / half_life).exp2(),
+            half_life,
+            phantom: PhantomData,
+        };
+
+        // Same argument as in `KTEstimator::new`: probabilities are bounded away from zero.
+        let _: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+                &estimator.probabilities(),
+            )?;
+        Ok(estimator)
+    }
+
+    /// Returns the size of the alphabet, i.e., the number of distinct symbols.
+    pub fn alphabet_size(&self) -> usize {
+        self.counts.len()
+    }
+
+    /// Returns the number of observations after which the weight of an observation halves.
+    pub fn half_life(&self) -> f64 {
+        self.half_life
+    }
+
+    /// Returns the decayed counts `w_i` of all symbols (see [struct level
+    /// documentation](Self)).
+    pub fn effective_counts(&self) -> Vec<f64> {
+        self.counts
+            .iter()
+            .map(|&count| count / self.scale)
+            .collect()
+    }
+
+    /// Returns the sum of all [`effective_counts`](Self::effective_counts).
+    ///
+    /// For an infinite half-life, this is the number of observed symbols. Otherwise, it
+    /// approaches `1 / (1 - 2^(-1 / half_life))`, which is about `1.44 * half_life` for
+    /// long half-lives.
+    pub fn effective_num_observations(&self) -> f64 {
+        self.total / self.scale
+    }
+
+    /// Returns the estimated probabilities of all symbols in floating point arithmetic.
+    ///
+    /// The returned probabilities are strictly positive and sum to one (up to rounding
+    /// errors). The entropy model returned by [`model`](Self::model) approximates them in
+    /// fixed point arithmetic.
+    pub fn probabilities(&self) -> Vec<f64> {
+        // Multiply the prior by `scale` rather than dividing all counts by it, so that an
+        // infinite half-life reproduces the exact floating point results of `KTEstimator`.
+        let prior = 0.5 * self.scale;
+        let denominator = self.total + prior * self.counts.len() as f64;
+        self.counts
+            .iter()
+            .map(|&count| (count + prior) / denominator)
+            .collect()
+    }
+
+    /// Returns the entropy model for the next symbol.
+    pub fn model(
+        &self,
+    ) -> ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        ContiguousCategoricalEntropyModel::from_floating_point_probabilities(&self.probabilities())
+            .expect("Checked in constructor.")
+    }
+
+    /// Records an observation of `symbol` and lets all previous observations decay.
+    ///
+    /// You'll usually not have to call this method directly since [`encode_symbol`] and
+    /// [`decode_symbol`] call it for you.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` is not smaller than the alphabet size.
+    ///
+    /// [`encode_symbol`]: Self::encode_symbol
+    /// [`decode_symbol`]: Self::decode_symbol
+    pub fn update(&mut self, symbol: usize) {
+        assert!(symbol < self.alphabet_size());
+        // Growing the unit of all counts is equivalent to shrinking all effective counts.
+        self.scale /= self.decay;
+        self.counts[symbol] += self.scale;
+        self.total += self.scale;
+
+        if self.scale > Self::MAX_SCALE {
+            for count in &mut self.counts {
+                *count /= self.scale;
+            }
+            self.total /= self.scale;
+            self.scale = 1.0;
+        }
+    }
+
+    /// Encodes `symbol` with the current [`model`](Self::model) and then [`update`]s the
+    /// estimator.
+    ///
+    /// Use this method with coders that have queue semantics (e.g., a [`RangeEncoder`]).
+    /// For coders with stack semantics, use [`encode_symbols_reverse`] instead.
+    ///
+    /// If `symbol` is not smaller than the alphabet size, then the `encoder` returns its
+    /// error for impossible symbols, and neither the `encoder` nor the estimator is changed.
+    ///
+    /// [`update`]: Self::update
+    /// [`RangeEncoder`]: super::queue::RangeEncoder
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    pub fn encode_symbol<E>(
+        &mut self,
+        encoder: &mut E,
+        symbol: usize,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        encoder.encode_symbol(symbol, self.model())?;
+        self.update(symbol);
+        Ok(())
+    }
+
+    /// Encodes `symbols` on a coder with stack semantics (e.g., an [`AnsCoder`]) such that
+    /// calling [`decode_symbol`] repeatedly decodes them in their original order.
+    ///
+    /// See [`KTEstimator::encode_symbols_reverse`] for details and memory requirements.
+    ///
+    /// If any of the `symbols` is not smaller than the alphabet size, then the `encoder`
+    /// returns its error for impossible symbols, and neither the `encoder` nor the
+    /// estimator is changed.
+    ///
+    /// [`AnsCoder`]: super::stack::AnsCoder
+    /// [`decode_symbol`]: Self::decode_symbol
+    pub fn encode_symbols_reverse<E>(
+        &mut self,
+        encoder: &mut E,
+        symbols: &[usize],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        if let Some(&symbol) = symbols
+            .iter()
+            .find(|&&symbol| symbol >= self.alphabet_size())
+        {
+            // Fails without changing the `encoder` since the model has no probability mass
+            // for `symbol`.
+            return encoder.encode_symbol(symbol, self.model());
+        }
+
+        let models = symbols
+            .iter()
+            .map(|&symbol| {
+                let model = self.model();
+                self.update(symbol);
+                model
+            })
+            .collect::<Vec<_>>();
+        encoder.encode_symbols(symbols.iter().zip(models).rev())
+    }
+
+    /// Decodes a symbol with the current [`model`](Self::model) and then [`update`]s the
+    /// estimator with the decoded symbol.
+    ///
+    /// [`update`]: Self::update
+    pub fn decode_symbol<D>(
+        &mut self,
+        decoder: &mut D,
+    ) -> Result<usize, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        Probability: Into<D::Word>,
+        D::Word: AsPrimitive<Probability>,
+    {
+        let symbol = decoder.decode_symbol(self.model())?;
+        self.update(symbol);
+        Ok(symbol)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> ContextModel<PRECISION>
+    for DecayingEstimator<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    type Probability = Probability;
+
+    fn alphabet_size(&self) -> usize {
+        DecayingEstimator::alphabet_size(self)
+    }
+
+    fn model(&self) -> ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        DecayingEstimator::model(self)
+    }
+
+    fn update(&mut self, symbol: usize) {
+        DecayingEstimator::update(self, symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+            stack::DefaultAnsCoder,
+        },
+        *,
+    };
+    use crate::DefaultEncoderFrontendError;
+
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DefaultKTEstimator::new(0).is_err());
+        assert!(DefaultKTEstimator::new(1).is_err());
+        assert!(KTEstimator::<u16, 4>::new(16).is_ok());
+        assert!(KTEstimator::<u16, 4>::new(17).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn binary_regret() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(728);
+        let amt = 10_000;
+        let bits = (0..amt)
+            .map(|_| (rng.next_u32() % 20 == 0) as usize)
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        let mut estimator = DefaultKTEstimator::new(2).unwrap();
+        for &bit in &bits {
+            estimator.encode_symbol(&mut encoder, bit).unwrap();
+        }
+        assert_eq!(estimator.num_observations(), amt as u64);
+
+        // Compare to the code length under the best Bernoulli distribution in hindsight.
+        let p = estimator.counts()[1] as f64 / amt as f64;
+        let hindsight = -(amt as f64) * (p * p.log2() + (1.0 - p) * (1.0 - p).log2());
+        let regret_bound = 0.5 * (amt as f64).log2() + 1.0;
+        let num_bits = encoder.num_bits() as f64;
+        assert!(num_bits > hindsight);
+        // Allow for the overheads of fixed point arithmetic and of sealing the range coder.
+        assert!(num_bits < hindsight + regret_bound + 0.001 * amt as f64 + 64.0);
+
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        let mut estimator = DefaultKTEstimator::new(2).unwrap();
+        for &bit in &bits {
+            assert_eq!(estimator.decode_symbol(&mut decoder).unwrap(), bit);
+        }
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn multialphabet_stack() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7280);
+        let symbols = (0..1000)
+            .map(|_| {
+                let x = rng.next_u32() % 64;
+                (x * x / 512) as usize
+            })
+            .collect::<Vec<_>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        let mut encoder_estimator = DefaultKTEstimator::new(8).unwrap();
+        encoder_estimator
+            .encode_symbols_reverse(&mut ans, &symbols)
+            .unwrap();
+
+        let mut decoder_estimator = DefaultKTEstimator::new(8).unwrap();
+        for &symbol in &symbols {
+            assert_eq!(decoder_estimator.decode_symbol(&mut ans).unwrap(), symbol);
+        }
+        assert!(ans.is_empty());
+        assert_eq!(decoder_estimator, encoder_estimator);
+    }
+
+    #[test]
+    fn invalid_symbols() {
+        let error = Err(DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error());
+
+        let mut encoder = DefaultRangeEncoder::new();
+        let mut estimator = DefaultKTEstimator::new(3).unwrap();
+        estimator.encode_symbol(&mut encoder, 2).unwrap();
+        assert_eq!(estimator.encode_symbol(&mut encoder, 3), error);
+        let mut ans = DefaultAnsCoder::new();
+        assert_eq!(
+            estimator.encode_symbols_reverse(&mut ans, &[0, 5, 1]),
+            error
+        );
+        assert!(ans.is_empty());
+        assert_eq!(estimator.num_observations(), 1);
+
+        let mut estimator = DefaultDecayingEstimator::new(3, 10.0).unwrap();
+        estimator.encode_symbol(&mut encoder, 2).unwrap();
+        let probabilities = estimator.probabilities();
+        assert_eq!(estimator.encode_symbol(&mut encoder, 3), error);
+        assert_eq!(
+            estimator.encode_symbols_reverse(&mut ans, &[0, 5, 1]),
+            error
+        );
+        assert!(ans.is_empty());
+        assert_eq!(estimator.probabilities(), probabilities);
+    }
+
+    #[test]
+    fn decaying_invalid_construction() {
+        assert!(DefaultDecayingEstimator::new(1, 10.0).is_err());
+        assert!(DefaultDecayingEstimator::new(2, 0.0).is_err());
+        assert!(DefaultDecayingEstimator::new(2, -1.0).is_err());
+        assert!(DefaultDecayingEstimator::new(2, f64::NAN).is_err());
+        assert!(DefaultDecayingEstimator::new(2, f64::INFINITY).is_ok());
+        assert!(DecayingEstimator::<u16, 4>::new(17, 10.0).is_err());
+    }
+
+    #[test]
+    fn decaying_half_life() {
+        let mut estimator = DefaultDecayingEstimator::new(3, 4.0).unwrap();
+        estimator.update(0);
+        for _ in 0..4 {
+            estimator.update(1);
+        }
+        let counts = estimator.effective_counts();
+        assert!((counts[0] - 0.5).abs() < 1e-12);
+        assert!(
+            (counts[1] - (1.0 + 0.5f64.powf(0.25) + 0.5f64.sqrt() + 0.5f64.powf(0.75))).abs()
+                < 1e-12
+        );
+        assert_eq!(counts[2], 0.0);
+        assert!(
+            (estimator.effective_num_observations() - counts.iter().sum::<f64>()).abs() < 1e-12
+        );
+
+        // Rescaling the internal counts doesn't change the effective counts.
+        let mut estimator = DefaultDecayingEstimator::new(2, 1.0).unwrap();
+        for i in 0..100 {
+            estimator.update(i % 2);
+        }
+        let counts = estimator.effective_counts();
+        assert!((counts[0] + counts[1] - 2.0).abs() < 1e-12);
+        assert!((counts[1] - 4.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn decaying_without_forgetting_matches_kt() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(741);
+        let symbols = (0..1000)
+            .map(|_| (rng.next_u32() % 5) as usize)
+            .collect::<Vec<_>>();
+
+        let mut decaying_encoder = DefaultRangeEncoder::new();
+        let mut decaying = DefaultDecayingEstimator::new(5, f64::INFINITY).unwrap();
+        let mut kt_encoder = DefaultRangeEncoder::new();
+        let mut kt = DefaultKTEstimator::new(5).unwrap();
+        for &symbol in &symbols {
+            decaying
+                .encode_symbol(&mut decaying_encoder, symbol)
+                .unwrap();
+            kt.encode_symbol(&mut kt_encoder, symbol).unwrap();
+        }
+
+        assert_eq!(
+            decaying.effective_counts(),
+            kt.counts()
+                .iter()
+                .map(|&count| count as f64)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            decaying_encoder.into_compressed().unwrap(),
+            kt_encoder.into_compressed().unwrap()
+        );
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn decaying_non_stationary() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(7410);
+        // The most frequent symbol changes every 2000 symbols.
+        let symbols = (0..10_000)
+            .map(|i| {
+                let x = rng.next_u32() % 16;
+                if x < 12 {
+                    (i / 2000) % 4
+                } else {
+                    (x % 4) as usize
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        let mut estimator = DefaultDecayingEstimator::new(4, 200.0).unwrap();
+        let mut kt_encoder = DefaultRangeEncoder::new();
+        let mut kt = DefaultKTEstimator::new(4).unwrap();
+        for &symbol in &symbols {
+            estimator.encode_symbol(&mut encoder, symbol).unwrap();
+            kt.encode_symbol(&mut kt_encoder, symbol).unwrap();
+        }
+        assert!(encoder.num_bits() < kt_encoder.num_bits() * 3 / 4);
+
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        let mut decoder_estimator = DefaultDecayingEstimator::new(4, 200.0).unwrap();
+        for &symbol in &symbols {
+            assert_eq!(
+                decoder_estimator.decode_symbol(&mut decoder).unwrap(),
+                symbol
+            );
+        }
+        assert!(decoder.maybe_exhausted());
+        assert_eq!(decoder_estimator, estimator);
+
+        // Same on a stack.
+        let mut ans = DefaultAnsCoder::new();
+        let mut encoder_estimator = DefaultDecayingEstimator::new(4, 200.0).unwrap();
+        encoder_estimator
+            .encode_symbols_reverse(&mut ans, &symbols)
+            .unwrap();
+        let mut decoder_estimator = DefaultDecayingEstimator::new(4, 200.0).unwrap();
+        for &symbol in &symbols {
+            assert_eq!(decoder_estimator.decode_symbol(&mut ans).unwrap(), symbol);
+        }
+        assert!(ans.is_empty());
+        assert_eq!(decoder_estimator, encoder_estimator);
+    }
+}
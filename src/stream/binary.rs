@@ -0,0 +1,1205 @@
+//! Binary arithmetic coding compatible with CABAC (the "M-coder" of H.264 and HEVC)
+//!
+//! This module provides a [`CabacEncoder`] and a [`CabacDecoder`] that implement the
+//! binary arithmetic coding engine of the Context-based Adaptive Binary Arithmetic Coding
+//! (CABAC) scheme \[1] used by the H.264/AVC and H.265/HEVC video coding standards. The
+//! coders operate on *bins* (binary decisions) rather than on symbols from an entropy
+//! model, and they estimate probabilities with the standard's 64-state probability state
+//! machine, which is represented by a [`CabacContext`]. Given the same sequence of bins,
+//! contexts, and terminations, the coders produce and consume the same bit stream as the
+//! arithmetic coding engine of a standard compliant video codec. This allows you to use
+//! `constriction` as the entropy coding stage of a video-codec-style pipeline, where the
+//! binarization and the selection of contexts is left to the caller.
+//!
+//! Unlike the general purpose entropy coders in the sister modules [`stack`] and [`queue`],
+//! the coders in this module don't implement the [`Encode`] and [`Decode`] traits since
+//! CABAC adapts its probability estimates with every coded bin and only supports the 9 bit
+//! integer arithmetic prescribed by the standard. Instead, they provide methods for the
+//! three kinds of bins defined by CABAC:
+//!
+//! - *regular* bins, coded with an adaptive [`CabacContext`], see
+//!   [`CabacEncoder::encode_decision`];
+//! - *bypass* bins, which have a fixed probability of one half, see
+//!   [`CabacEncoder::encode_bypass`]; and
+//! - *terminating* bins, which signal the end of a slice, see
+//!   [`CabacEncoder::encode_terminate`].
+//!
+//! Regular bins can also be coded with any other implementation of the [`BinaryContext`]
+//! trait, e.g., with a [`ShiftContext`], which uses a more precise shift-based
+//! probability estimator than the one prescribed by H.264 and HEVC (at the cost of
+//! compatibility with these standards). A [`ContextSet`] manages a table of contexts that
+//! are addressed by a context ID.
+//!
+//! The compressed data is a sequence of bytes. Each slice (i.e., each sequence of bins up
+//! to and including a terminating bin with value `true`) ends with a stop bit followed by
+//! zero bits up to the next byte boundary, so slices can be located and decoded
+//! independently.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::binary::{CabacContext, CabacDecoder, CabacEncoder};
+//!
+//! // Initialize two contexts from HEVC-style initialization values at a given QP.
+//! let initial_contexts = [
+//!     CabacContext::from_init_value(139, 32),
+//!     CabacContext::from_init_value(154, 32),
+//! ];
+//! let bins = [(true, 0), (false, 1), (true, 0), (true, 0), (false, 1), (true, 1)];
+//!
+//! let mut encoder = CabacEncoder::new();
+//! let mut contexts = initial_contexts;
+//! for &(bin, context_id) in &bins {
+//!     encoder.encode_decision(bin, &mut contexts[context_id]).unwrap();
+//! }
+//! encoder.encode_bypass_bits(0b1011, 4).unwrap();
+//! encoder.encode_terminate(true).unwrap(); // End of slice.
+//! let compressed = encoder.into_compressed().unwrap();
+//!
+//! let mut decoder = CabacDecoder::from_compressed(compressed);
+//! let mut contexts = initial_contexts;
+//! for &(bin, context_id) in &bins {
+//!     assert_eq!(decoder.decode_decision(&mut contexts[context_id]).unwrap(), bin);
+//! }
+//! assert_eq!(decoder.decode_bypass_bits(4).unwrap(), 0b1011);
+//! assert!(decoder.decode_terminate().unwrap());
+//! assert!(decoder.maybe_exhausted());
+//! ```
+//!
+//! # References
+//!
+//! \[1] Marpe, Detlev, Heiko Schwarz, and Thomas Wiegand. "Context-based adaptive binary
+//! arithmetic coding in the H.264/AVC video compression standard." IEEE Transactions on
+//! circuits and systems for video technology 13.7 (2003): 620-636.
+//!
+//! [`stack`]: super::stack
+//! [`queue`]: super::queue
+//! [`Encode`]: super::Encode
+//! [`Decode`]: super::Decode
+
+use alloc::vec::Vec;
+
+use crate::{
+    backends::{Cursor, IntoReadWords, ReadWords, WriteWords},
+    Queue,
+};
+
+/// Initial value of the coding interval's width after (re-)initializing the coding engine.
+const INITIAL_RANGE: u32 = 510;
+
+/// Width of the LPS subinterval for each probability state and quantized interval width
+/// (`rangeTabLPS` in the standards).
+const RANGE_TAB_LPS: [[u8; 4]; 64] = [
+    [128, 176, 208, 240],
+    [128, 167, 197, 227],
+    [128, 158, 187, 216],
+    [123, 150, 178, 205],
+    [116, 142, 169, 195],
+    [111, 135, 160, 185],
+    [105, 128, 152, 175],
+    [100, 122, 144, 166],
+    [95, 116, 137, 158],
+    [90, 110, 130, 150],
+    [85, 104, 123, 142],
+    [81, 99, 117, 135],
+    [77, 94, 111, 128],
+    [73, 89, 105, 122],
+    [69, 85, 100, 116],
+    [66, 80, 95, 110],
+    [62, 76, 90, 104],
+    [59, 72, 86, 99],
+    [56, 69, 81, 94],
+    [53, 65, 77, 89],
+    [51, 62, 73, 85],
+    [48, 59, 69, 80],
+    [46, 56, 66, 76],
+    [43, 53, 63, 72],
+    [41, 50, 59, 69],
+    [39, 48, 56, 65],
+    [37, 45, 54, 62],
+    [35, 43, 51, 59],
+    [33, 41, 48, 56],
+    [32, 39, 46, 53],
+    [30, 37, 43, 50],
+    [29, 35, 41, 48],
+    [27, 33, 39, 45],
+    [26, 31, 37, 43],
+    [24, 30, 35, 41],
+    [23, 28, 33, 39],
+    [22, 27, 32, 37],
+    [21, 26, 30, 35],
+    [20, 24, 29, 33],
+    [19, 23, 27, 31],
+    [18, 22, 26, 30],
+    [17, 21, 25, 28],
+    [16, 20, 23, 27],
+    [15, 19, 22, 25],
+    [14, 18, 21, 24],
+    [14, 17, 20, 23],
+    [13, 16, 19, 22],
+    [12, 15, 18, 21],
+    [12, 14, 17, 20],
+    [11, 14, 16, 19],
+    [11, 13, 15, 18],
+    [10, 12, 15, 17],
+    [10, 12, 14, 16],
+    [9, 11, 13, 15],
+    [9, 11, 12, 14],
+    [8, 10, 12, 14],
+    [8, 9, 11, 13],
+    [7, 9, 11, 12],
+    [7, 9, 10, 12],
+    [7, 8, 10, 11],
+    [6, 8, 9, 11],
+    [6, 7, 9, 10],
+    [6, 7, 8, 9],
+    [2, 2, 2, 2],
+];
+
+/// Next probability state after coding a least probable symbol (`transIdxLPS` in the
+/// standards). The next state after coding a most probable symbol is simply
+/// `min(state + 1, 62)`.
+const TRANS_IDX_LPS: [u8; 64] = [
+    0, 0, 1, 2, 2, 4, 4, 5, 6, 7, 8, 9, 9, 11, 11, 12, 13, 13, 15, 15, 16, 16, 18, 18, 19, 19, 21,
+    21, 22, 22, 23, 24, 24, 25, 26, 26, 27, 27, 28, 29, 29, 30, 30, 30, 31, 32, 32, 33, 33, 33, 34,
+    34, 35, 35, 35, 36, 36, 36, 37, 37, 37, 38, 38, 63,
+];
+
+/// Largest probability state that can be used for regular bins.
+const MAX_STATE: u8 = 62;
+
+/// Adaptive probability estimate for regular bins.
+///
+/// A `BinaryContext` is a small state machine that estimates the probability of the next
+/// bin and that learns from each bin coded with it. The [`CabacEncoder`] and
+/// [`CabacDecoder`] can code regular bins with any `BinaryContext`. This module provides
+/// the standard compliant [`CabacContext`] and the more precise [`ShiftContext`], and you
+/// can manage a collection of contexts with a [`ContextSet`].
+pub trait BinaryContext {
+    /// Returns the value of the most probable symbol (MPS), i.e., the value of the next bin
+    /// that is considered more likely.
+    fn mps(&self) -> bool;
+
+    /// Returns the width of the subinterval for the least probable symbol (LPS) if the
+    /// coding interval has width `range`.
+    ///
+    /// Is only called with `256 <= range <= 510` and has to return a value `lps_range`
+    /// that satisfies `0 < lps_range < range`. Bins are coded with a model that assigns
+    /// probability `lps_range / range` to the LPS.
+    fn lps_range(&self, range: u32) -> u32;
+
+    /// Updates the probability estimate after coding `bin`.
+    fn update(&mut self, bin: bool);
+}
+
+/// Standard compliant adaptive probability estimate for regular bins in a [`CabacEncoder`]
+/// or [`CabacDecoder`].
+///
+/// A `CabacContext` consists of the value of the *most probable symbol* (MPS) and a
+/// probability state index between 0 and 62, where state 0 corresponds to a probability of
+/// (approximately) one half for the least probable symbol (LPS), and each higher state
+/// corresponds to a lower LPS probability. Encoding or decoding a regular bin updates the
+/// context, so the encoder and the decoder have to start from contexts in the same state.
+/// The [`Default`] context has state 0 and MPS `false`. Use the methods of the
+/// [`BinaryContext`] trait to query the MPS.
+///
+/// See [module level documentation](self) for an example.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CabacContext {
+    state: u8,
+    mps: bool,
+}
+
+impl CabacContext {
+    /// Creates a context with the given probability state index and most probable symbol.
+    ///
+    /// Returns an error if `state > 62`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(state: u8, mps: bool) -> Result<Self, ()> {
+        if state > MAX_STATE {
+            Err(())
+        } else {
+            Ok(Self { state, mps })
+        }
+    }
+
+    /// Initializes a context from the slope `m` and offset `n` of the H.264/AVC context
+    /// initialization tables, given the quantization parameter `qp` of the slice.
+    pub fn from_slope_and_offset(m: i32, n: i32, qp: i32) -> Self {
+        let pre_state = (((m * qp.clamp(0, 51)) >> 4) + n).clamp(1, 126);
+        if pre_state <= 63 {
+            Self {
+                state: (63 - pre_state) as u8,
+                mps: false,
+            }
+        } else {
+            Self {
+                state: (pre_state - 64) as u8,
+                mps: true,
+            }
+        }
+    }
+
+    /// Initializes a context from an 8 bit initialization value of the H.265/HEVC context
+    /// initialization tables, given the quantization parameter `qp` of the slice.
+    pub fn from_init_value(init_value: u8, qp: i32) -> Self {
+        let m = (init_value >> 4) as i32 * 5 - 45;
+        let n = (((init_value & 15) as i32) << 3) - 16;
+        Self::from_slope_and_offset(m, n, qp)
+    }
+
+    /// Returns the probability state index (between 0 and 62).
+    pub fn state(self) -> u8 {
+        self.state
+    }
+}
+
+impl BinaryContext for CabacContext {
+    #[inline(always)]
+    fn mps(&self) -> bool {
+        self.mps
+    }
+
+    #[inline(always)]
+    fn lps_range(&self, range: u32) -> u32 {
+        RANGE_TAB_LPS[self.state as usize][((range >> 6) & 3) as usize] as u32
+    }
+
+    #[inline(always)]
+    fn update(&mut self, bin: bool) {
+        if bin == self.mps {
+            self.state = (self.state + 1).min(MAX_STATE);
+        } else {
+            if self.state == 0 {
+                self.mps = !self.mps;
+            }
+            self.state = TRANS_IDX_LPS[self.state as usize];
+        }
+    }
+}
+
+/// Number of fractional bits of the probability estimate of a [`ShiftContext`].
+const SHIFT_CONTEXT_PRECISION: u32 = 15;
+
+/// Adaptation rate of a [`Default`] [`ShiftContext`].
+const DEFAULT_SHIFT_CONTEXT_RATE: u8 = 5;
+
+/// Adaptive probability estimate for regular bins based on an exponential moving average.
+///
+/// A `ShiftContext` tracks the probability that the next bin is `true` in 15 bit fixed
+/// point precision. Each coded bin moves the estimate by a fraction `2^-rate` of the
+/// distance towards the bin's value, which can be implemented with a single bit shift. A
+/// small `rate` adapts quickly to changing statistics while a large `rate` converges to a
+/// more accurate estimate for stationary data. This is the kind of estimator used by more
+/// recent video codecs (e.g., AV1 and H.266/VVC). It can represent probabilities much
+/// closer to zero or one than a [`CabacContext`], but it isn't compatible with H.264/AVC
+/// and H.265/HEVC. The [`Default`] context starts at probability one half with `rate = 5`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ShiftContext {
+    /// Invariant: `0 < probability_of_one < 1 << SHIFT_CONTEXT_PRECISION`.
+    probability_of_one: u16,
+    rate: u8,
+}
+
+impl ShiftContext {
+    /// Creates a context with the given initial probability of a `true` bin and adaptation
+    /// rate.
+    ///
+    /// Returns an error unless `0.0 < probability_of_one < 1.0` and `1 <= rate <= 15`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(probability_of_one: f64, rate: u8) -> Result<Self, ()> {
+        if !(probability_of_one > 0.0 && probability_of_one < 1.0)
+            || rate == 0
+            || rate as u32 > SHIFT_CONTEXT_PRECISION
+        {
+            return Err(());
+        }
+
+        let scale = (1u32 << SHIFT_CONTEXT_PRECISION) as f64;
+        let probability_of_one =
+            (probability_of_one * scale).round().clamp(1.0, scale - 1.0) as u16;
+        Ok(Self {
+            probability_of_one,
+            rate,
+        })
+    }
+
+    /// Returns the current estimate of the probability that the next bin is `true`.
+    pub fn probability_of_one(self) -> f64 {
+        self.probability_of_one as f64 / (1u32 << SHIFT_CONTEXT_PRECISION) as f64
+    }
+
+    /// Returns the adaptation rate.
+    pub fn rate(self) -> u8 {
+        self.rate
+    }
+}
+
+impl Default for ShiftContext {
+    fn default() -> Self {
+        Self {
+            probability_of_one: 1 << (SHIFT_CONTEXT_PRECISION - 1),
+            rate: DEFAULT_SHIFT_CONTEXT_RATE,
+        }
+    }
+}
+
+impl BinaryContext for ShiftContext {
+    #[inline(always)]
+    fn mps(&self) -> bool {
+        self.probability_of_one >= 1 << (SHIFT_CONTEXT_PRECISION - 1)
+    }
+
+    #[inline(always)]
+    fn lps_range(&self, range: u32) -> u32 {
+        let probability_of_one = self.probability_of_one as u32;
+        let lps_probability =
+            probability_of_one.min((1 << SHIFT_CONTEXT_PRECISION) - probability_of_one);
+        // Since `lps_probability <= 1 << (SHIFT_CONTEXT_PRECISION - 1)`, this is at most
+        // `range / 2`.
+        ((range * lps_probability) >> SHIFT_CONTEXT_PRECISION).max(1)
+    }
+
+    #[inline(always)]
+    fn update(&mut self, bin: bool) {
+        // Neither update can reach `0` or `1 << SHIFT_CONTEXT_PRECISION` since `rate >= 1`.
+        if bin {
+            self.probability_of_one +=
+                ((1 << SHIFT_CONTEXT_PRECISION) - self.probability_of_one) >> self.rate;
+        } else {
+            self.probability_of_one -= self.probability_of_one >> self.rate;
+        }
+    }
+}
+
+/// A collection of [`BinaryContext`]s that are addressed by an index ("context ID").
+///
+/// Video-codec-style pipelines typically maintain a fixed table of contexts and select a
+/// context for each regular bin based on the syntax element and on previously coded data.
+/// A `ContextSet` holds such a table, codes bins given a `(bin, context_id)` pair, and
+/// remembers the initial state of all contexts so that you can [`reset`](Self::reset) them
+/// (e.g., at the beginning of each slice).
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::binary::{CabacDecoder, CabacEncoder, ContextSet, ShiftContext};
+///
+/// let bins = [(true, 2), (false, 0), (false, 0), (true, 1), (false, 2), (false, 0)];
+/// let mut contexts = ContextSet::<ShiftContext>::with_default_contexts(3);
+///
+/// let mut encoder = CabacEncoder::new();
+/// for &(bin, context_id) in &bins {
+///     contexts.encode_bin(&mut encoder, bin, context_id).unwrap();
+/// }
+/// let compressed = encoder.into_compressed().unwrap();
+///
+/// contexts.reset();
+/// let mut decoder = CabacDecoder::from_compressed(compressed);
+/// for &(bin, context_id) in &bins {
+///     assert_eq!(contexts.decode_bin(&mut decoder, context_id).unwrap(), bin);
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ContextSet<C = CabacContext> {
+    contexts: Vec<C>,
+    initial_contexts: Vec<C>,
+}
+
+impl<C: BinaryContext + Clone> ContextSet<C> {
+    /// Creates a set of contexts that starts from the provided states.
+    pub fn new(contexts: Vec<C>) -> Self {
+        Self {
+            initial_contexts: contexts.clone(),
+            contexts,
+        }
+    }
+
+    /// Creates a set of `len` contexts that all start in their [`Default`] state.
+    pub fn with_default_contexts(len: usize) -> Self
+    where
+        C: Default,
+    {
+        Self::new(alloc::vec![C::default(); len])
+    }
+
+    /// Returns the number of contexts.
+    pub fn len(&self) -> usize {
+        self.contexts.len()
+    }
+
+    /// Returns `true` if the set contains no contexts.
+    pub fn is_empty(&self) -> bool {
+        self.contexts.is_empty()
+    }
+
+    /// Returns the current states of all contexts, indexed by their context ID.
+    pub fn contexts(&self) -> &[C] {
+        &self.contexts
+    }
+
+    /// Returns the current state of the context with ID `context_id`, or `None` if there
+    /// is no such context.
+    pub fn get(&self, context_id: usize) -> Option<&C> {
+        self.contexts.get(context_id)
+    }
+
+    /// Resets all contexts to the states with which the `ContextSet` was created.
+    pub fn reset(&mut self) {
+        self.contexts.clone_from(&self.initial_contexts);
+    }
+
+    /// Encodes a regular bin with the context with ID `context_id`, and updates the context.
+    ///
+    /// # Panics
+    ///
+    /// If `context_id >= self.len()`.
+    pub fn encode_bin<Backend: WriteWords<u8>>(
+        &mut self,
+        encoder: &mut CabacEncoder<Backend>,
+        bin: bool,
+        context_id: usize,
+    ) -> Result<(), Backend::WriteError> {
+        encoder.encode_decision(bin, &mut self.contexts[context_id])
+    }
+
+    /// Decodes a regular bin with the context with ID `context_id`, and updates the context.
+    ///
+    /// # Panics
+    ///
+    /// If `context_id >= self.len()`.
+    pub fn decode_bin<Backend: ReadWords<u8, Queue>>(
+        &mut self,
+        decoder: &mut CabacDecoder<Backend>,
+        context_id: usize,
+    ) -> Result<bool, Backend::ReadError> {
+        decoder.decode_decision(&mut self.contexts[context_id])
+    }
+}
+
+impl ContextSet<CabacContext> {
+    /// Creates a set of [`CabacContext`]s from H.265/HEVC-style initialization values for
+    /// the quantization parameter `qp`, see [`CabacContext::from_init_value`].
+    pub fn from_init_values(init_values: &[u8], qp: i32) -> Self {
+        Self::new(
+            init_values
+                .iter()
+                .map(|&init_value| CabacContext::from_init_value(init_value, qp))
+                .collect(),
+        )
+    }
+}
+
+/// Binary arithmetic encoder of the CABAC scheme.
+///
+/// Writes the compressed data as bytes into the `Backend`. See [module level
+/// documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct CabacEncoder<Backend = Vec<u8>> {
+    bulk: Backend,
+
+    /// Lower end of the coding interval (`codILow`), with 10 significant bits.
+    low: u32,
+
+    /// Width of the coding interval (`codIRange`), with 9 significant bits.
+    range: u32,
+
+    /// Number of bits whose value depends on a potential future carry.
+    bits_outstanding: u32,
+
+    /// Whether the next call to `put_bit` should suppress its bit (`firstBitFlag`).
+    first_bit: bool,
+
+    /// Whether any bins have been encoded since the last terminating bin with value `true`.
+    in_slice: bool,
+
+    /// Bits of the current byte that haven't been written to `bulk` yet.
+    partial_byte: u8,
+    num_partial_bits: u32,
+}
+
+impl CabacEncoder<Vec<u8>> {
+    /// Creates an empty encoder that writes to a `Vec<u8>`.
+    pub fn new() -> Self {
+        Self::with_backend(Vec::new())
+    }
+}
+
+impl Default for CabacEncoder<Vec<u8>> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Backend: WriteWords<u8>> CabacEncoder<Backend> {
+    /// Creates an encoder that appends compressed data to `backend`.
+    pub fn with_backend(backend: Backend) -> Self {
+        Self {
+            bulk: backend,
+            low: 0,
+            range: INITIAL_RANGE,
+            bits_outstanding: 0,
+            first_bit: true,
+            in_slice: false,
+            partial_byte: 0,
+            num_partial_bits: 0,
+        }
+    }
+
+    /// Encodes a regular bin with the adaptive probability estimate `context`, and updates
+    /// `context` accordingly (`EncodeDecision` in the standards).
+    pub fn encode_decision<C: BinaryContext>(
+        &mut self,
+        bin: bool,
+        context: &mut C,
+    ) -> Result<(), Backend::WriteError> {
+        self.in_slice = true;
+        let lps_range = context.lps_range(self.range);
+        self.range -= lps_range;
+        if bin != context.mps() {
+            self.low += self.range;
+            self.range = lps_range;
+        }
+        context.update(bin);
+        self.renormalize()
+    }
+
+    /// Encodes a bin with a fixed probability of one half (`EncodeBypass` in the
+    /// standards).
+    pub fn encode_bypass(&mut self, bin: bool) -> Result<(), Backend::WriteError> {
+        self.in_slice = true;
+        self.low <<= 1;
+        if bin {
+            self.low += self.range;
+        }
+
+        if self.low >= 1024 {
+            self.low -= 1024;
+            self.put_bit(true)
+        } else if self.low < 512 {
+            self.put_bit(false)
+        } else {
+            self.low -= 512;
+            self.bits_outstanding += 1;
+            Ok(())
+        }
+    }
+
+    /// Encodes the `num_bits` least significant bits of `value` as bypass bins, starting
+    /// with the most significant one.
+    ///
+    /// # Panics
+    ///
+    /// If `num_bits > 32`.
+    pub fn encode_bypass_bits(
+        &mut self,
+        value: u32,
+        num_bits: u32,
+    ) -> Result<(), Backend::WriteError> {
+        assert!(num_bits <= 32);
+        for i in (0..num_bits).rev() {
+            self.encode_bypass((value >> i) & 1 != 0)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes a terminating bin (`EncodeTerminate` in the standards).
+    ///
+    /// A terminating bin with value `true` ends the current slice: it flushes the coding
+    /// engine, writes a stop bit, pads the compressed data with zero bits up to the next
+    /// byte boundary, and reinitializes the coding engine for the next slice. Contexts are
+    /// not reset, so you'll usually want to reinitialize them too.
+    pub fn encode_terminate(&mut self, bin: bool) -> Result<(), Backend::WriteError> {
+        self.in_slice = true;
+        self.range -= 2;
+        if bin {
+            self.low += self.range;
+            self.flush()
+        } else {
+            self.renormalize()
+        }
+    }
+
+    /// Terminates the current slice (unless it's empty) and returns the compressed data.
+    pub fn into_compressed(mut self) -> Result<Backend, Backend::WriteError> {
+        if self.in_slice {
+            self.encode_terminate(true)?;
+        }
+        Ok(self.bulk)
+    }
+
+    /// Returns `true` if no bins have been encoded since the last terminated slice.
+    pub fn is_at_slice_boundary(&self) -> bool {
+        !self.in_slice
+    }
+
+    /// Returns a reference to the backend.
+    ///
+    /// The backend doesn't yet contain the compressed data of the current slice.
+    pub fn get_ref(&self) -> &Backend {
+        &self.bulk
+    }
+
+    /// `EncodeFlush` in the standards, followed by byte alignment and reinitialization.
+    fn flush(&mut self) -> Result<(), Backend::WriteError> {
+        self.range = 2;
+        self.renormalize()?;
+        self.put_bit((self.low >> 9) & 1 != 0)?;
+        self.write_bit((self.low >> 8) & 1 != 0)?;
+        self.write_bit(true)?; // Stop bit.
+        while self.num_partial_bits != 0 {
+            self.write_bit(false)?;
+        }
+
+        self.low = 0;
+        self.range = INITIAL_RANGE;
+        self.first_bit = true;
+        self.in_slice = false;
+        Ok(())
+    }
+
+    /// `RenormE` in the standards.
+    #[inline(always)]
+    fn renormalize(&mut self) -> Result<(), Backend::WriteError> {
+        while self.range < 256 {
+            if self.low < 256 {
+                self.put_bit(false)?;
+            } else if self.low >= 512 {
+                self.low -= 512;
+                self.put_bit(true)?;
+            } else {
+                self.low -= 256;
+                self.bits_outstanding += 1;
+            }
+            self.range <<= 1;
+            self.low <<= 1;
+        }
+        Ok(())
+    }
+
+    /// `PutBit` in the standards.
+    fn put_bit(&mut self, bit: bool) -> Result<(), Backend::WriteError> {
+        if self.first_bit {
+            self.first_bit = false;
+        } else {
+            self.write_bit(bit)?;
+        }
+        while self.bits_outstanding != 0 {
+            self.write_bit(!bit)?;
+            self.bits_outstanding -= 1;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn write_bit(&mut self, bit: bool) -> Result<(), Backend::WriteError> {
+        self.partial_byte = (self.partial_byte << 1) | bit as u8;
+        self.num_partial_bits += 1;
+        if self.num_partial_bits == 8 {
+            self.bulk.write(self.partial_byte)?;
+            self.partial_byte = 0;
+            self.num_partial_bits = 0;
+        }
+        Ok(())
+    }
+}
+
+/// Binary arithmetic decoder of the CABAC scheme.
+///
+/// Reads compressed data that was written by a [`CabacEncoder`] (or by the arithmetic
+/// coding engine of a standard compliant video encoder). When the decoder reads past the
+/// end of the compressed data, it pads it with zero bits. See [module level
+/// documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct CabacDecoder<Backend = Cursor<u8, Vec<u8>>> {
+    bulk: Backend,
+
+    /// Width of the coding interval (`codIRange`), with 9 significant bits.
+    range: u32,
+
+    /// Offset of the compressed data within the coding interval (`codIOffset`).
+    offset: u32,
+
+    /// Whether the coding engine has to be (re-)initialized before decoding the next bin.
+    needs_init: bool,
+
+    /// Bits of the current byte that haven't been read yet.
+    current_byte: u8,
+    num_remaining_bits: u32,
+}
+
+impl<Backend: ReadWords<u8, Queue>> CabacDecoder<Backend> {
+    /// Creates a decoder for the provided compressed data.
+    pub fn from_compressed<Buf>(compressed: Buf) -> Self
+    where
+        Buf: IntoReadWords<u8, Queue, IntoReadWords = Backend>,
+    {
+        Self::with_backend(compressed.into_read_words())
+    }
+
+    /// Creates a decoder that reads compressed data from `backend`.
+    pub fn with_backend(backend: Backend) -> Self {
+        Self {
+            bulk: backend,
+            range: INITIAL_RANGE,
+            offset: 0,
+            needs_init: true,
+            current_byte: 0,
+            num_remaining_bits: 0,
+        }
+    }
+
+    /// Decodes a regular bin with the adaptive probability estimate `context`, and updates
+    /// `context` accordingly (`DecodeDecision` in the standards).
+    pub fn decode_decision<C: BinaryContext>(
+        &mut self,
+        context: &mut C,
+    ) -> Result<bool, Backend::ReadError> {
+        self.ensure_initialized()?;
+        let lps_range = context.lps_range(self.range);
+        self.range -= lps_range;
+        let bin = if self.offset < self.range {
+            context.mps()
+        } else {
+            self.offset -= self.range;
+            self.range = lps_range;
+            !context.mps()
+        };
+        context.update(bin);
+        self.renormalize()?;
+        Ok(bin)
+    }
+
+    /// Decodes a bin with a fixed probability of one half (`DecodeBypass` in the
+    /// standards).
+    pub fn decode_bypass(&mut self) -> Result<bool, Backend::ReadError> {
+        self.ensure_initialized()?;
+        self.offset = (self.offset << 1) | self.read_bit()? as u32;
+        if self.offset >= self.range {
+            self.offset -= self.range;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Decodes `num_bits` bypass bins and returns them as the least significant bits of an
+    /// integer, with the first decoded bin as the most significant one.
+    ///
+    /// # Panics
+    ///
+    /// If `num_bits > 32`.
+    pub fn decode_bypass_bits(&mut self, num_bits: u32) -> Result<u32, Backend::ReadError> {
+        assert!(num_bits <= 32);
+        let mut value = 0u64;
+        for _ in 0..num_bits {
+            value = (value << 1) | self.decode_bypass()? as u64;
+        }
+        Ok(value as u32)
+    }
+
+    /// Decodes a terminating bin (`DecodeTerminate` in the standards).
+    ///
+    /// If the decoded bin is `true`, then the decoder skips the padding up to the next byte
+    /// boundary and reinitializes its coding engine for the next slice.
+    pub fn decode_terminate(&mut self) -> Result<bool, Backend::ReadError> {
+        self.ensure_initialized()?;
+        self.range -= 2;
+        if self.offset >= self.range {
+            self.num_remaining_bits = 0;
+            self.needs_init = true;
+            Ok(true)
+        } else {
+            self.renormalize()?;
+            Ok(false)
+        }
+    }
+
+    /// Returns `true` if the decoder may have consumed all compressed data.
+    ///
+    /// This is the case if the backend is exhausted, and if the decoder either is at a
+    /// slice boundary or has read all bits of the current slice into its internal state.
+    pub fn maybe_exhausted(&self) -> bool {
+        self.num_remaining_bits == 0 && self.bulk.maybe_exhausted()
+    }
+
+    /// Returns a reference to the backend.
+    pub fn get_ref(&self) -> &Backend {
+        &self.bulk
+    }
+
+    /// Consumes the decoder and returns the backend, positioned at the next byte boundary.
+    pub fn into_inner(self) -> Backend {
+        self.bulk
+    }
+
+    #[inline(always)]
+    fn ensure_initialized(&mut self) -> Result<(), Backend::ReadError> {
+        if self.needs_init {
+            self.range = INITIAL_RANGE;
+            self.offset = 0;
+            for _ in 0..9 {
+                self.offset = (self.offset << 1) | self.read_bit()? as u32;
+            }
+            self.needs_init = false;
+        }
+        Ok(())
+    }
+
+    /// `RenormD` in the standards.
+    #[inline(always)]
+    fn renormalize(&mut self) -> Result<(), Backend::ReadError> {
+        while self.range < 256 {
+            self.range <<= 1;
+            self.offset = (self.offset << 1) | self.read_bit()? as u32;
+        }
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn read_bit(&mut self) -> Result<bool, Backend::ReadError> {
+        if self.num_remaining_bits == 0 {
+            self.current_byte = self.bulk.read()?.unwrap_or(0);
+            self.num_remaining_bits = 8;
+        }
+        self.num_remaining_bits -= 1;
+        Ok((self.current_byte >> self.num_remaining_bits) & 1 != 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Bin {
+        Decision(bool, usize),
+        Bypass(bool),
+        Terminate(bool),
+    }
+
+    fn random_bins(rng: &mut Xoshiro256StarStar, amt: usize) -> Vec<Bin> {
+        // Context `i` emits `true` with probability `(i + 1) / 16`.
+        (0..amt)
+            .map(|_| {
+                let r = rng.next_u32();
+                match r % 32 {
+                    0 => Bin::Terminate(r & (1 << 8) != 0 && r & (1 << 9) != 0),
+                    1..=4 => Bin::Bypass(r & (1 << 8) != 0),
+                    _ => {
+                        let context_id = (r >> 8) as usize % 15;
+                        Bin::Decision(((r >> 16) & 15) as usize <= context_id, context_id)
+                    }
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let bins = random_bins(&mut rng, 20_000);
+        let initial_contexts = (0..15)
+            .map(|i| CabacContext::from_init_value(((i * 17) % 256) as u8, 30))
+            .collect::<Vec<_>>();
+
+        let mut encoder = CabacEncoder::new();
+        let mut contexts = initial_contexts.clone();
+        for &bin in &bins {
+            match bin {
+                Bin::Decision(bin, context_id) => encoder
+                    .encode_decision(bin, &mut contexts[context_id])
+                    .unwrap(),
+                Bin::Bypass(bin) => encoder.encode_bypass(bin).unwrap(),
+                Bin::Terminate(bin) => encoder.encode_terminate(bin).unwrap(),
+            }
+        }
+        let compressed = encoder.into_compressed().unwrap();
+
+        let mut decoder = CabacDecoder::from_compressed(&compressed[..]);
+        let mut contexts = initial_contexts;
+        for &bin in &bins {
+            let decoded = match bin {
+                Bin::Decision(_, context_id) => Bin::Decision(
+                    decoder.decode_decision(&mut contexts[context_id]).unwrap(),
+                    context_id,
+                ),
+                Bin::Bypass(_) => Bin::Bypass(decoder.decode_bypass().unwrap()),
+                Bin::Terminate(_) => Bin::Terminate(decoder.decode_terminate().unwrap()),
+            };
+            assert_eq!(decoded, bin);
+        }
+        assert!(decoder.decode_terminate().unwrap());
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    fn h264_known_answer() {
+        // Reference bytes were computed by stepping through the encoding process of
+        // ITU-T H.264, clause 9.3.4, with contexts initialized per clause 9.3.1.1 from the
+        // (m, n) pairs for ctxIdx 0..=2 at SliceQPY = 26.
+        use Bin::*;
+        let bins = [
+            Decision(false, 0),
+            Decision(true, 1),
+            Decision(true, 2),
+            Decision(false, 0),
+            Decision(true, 0),
+            Decision(true, 1),
+            Bypass(true),
+            Bypass(false),
+            Bypass(true),
+            Decision(false, 2),
+            Decision(true, 2),
+            Terminate(false),
+            Decision(true, 1),
+            Decision(false, 0),
+            Bypass(true),
+            Decision(true, 2),
+            Decision(true, 2),
+            Decision(false, 1),
+            Terminate(true),
+        ];
+        let initial_contexts = [
+            CabacContext::from_slope_and_offset(20, -15, 26),
+            CabacContext::from_slope_and_offset(2, 54, 26),
+            CabacContext::from_slope_and_offset(3, 74, 26),
+        ];
+        let final_states = [(34, false), (2, false), (15, true)];
+
+        let mut encoder = CabacEncoder::new();
+        let mut contexts = initial_contexts;
+        for &bin in &bins {
+            match bin {
+                Decision(bin, context_id) => encoder
+                    .encode_decision(bin, &mut contexts[context_id])
+                    .unwrap(),
+                Bypass(bin) => encoder.encode_bypass(bin).unwrap(),
+                Terminate(bin) => encoder.encode_terminate(bin).unwrap(),
+            }
+        }
+        for (context, &expected) in contexts.iter().zip(&final_states) {
+            assert_eq!((context.state(), context.mps()), expected);
+        }
+        let compressed = encoder.into_compressed().unwrap();
+        assert_eq!(compressed, [0xdb, 0x75, 0x8d, 0x40]);
+
+        let mut decoder = CabacDecoder::from_compressed(&compressed[..]);
+        let mut contexts = initial_contexts;
+        for &bin in &bins {
+            let decoded = match bin {
+                Decision(_, context_id) => Decision(
+                    decoder.decode_decision(&mut contexts[context_id]).unwrap(),
+                    context_id,
+                ),
+                Bypass(_) => Bypass(decoder.decode_bypass().unwrap()),
+                Terminate(_) => Terminate(decoder.decode_terminate().unwrap()),
+            };
+            assert_eq!(decoded, bin);
+        }
+        assert!(decoder.maybe_exhausted());
+
+        // Terminating an empty slice flushes seven outstanding bits, bit 8 of `codILow`,
+        // and the stop bit.
+        let mut encoder = CabacEncoder::new();
+        encoder.encode_terminate(true).unwrap();
+        assert_eq!(encoder.into_compressed().unwrap(), [0xfe, 0x80]);
+    }
+
+    #[test]
+    fn independent_slices() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(456);
+        let mut encoder = CabacEncoder::new();
+        let mut slices = Vec::new();
+        let mut offsets = Vec::new();
+        for _ in 0..5 {
+            offsets.push(encoder.get_ref().len());
+            let mut context = CabacContext::default();
+            let bins = (0..1000)
+                .map(|_| rng.next_u32() % 8 == 0)
+                .collect::<Vec<_>>();
+            for &bin in &bins {
+                encoder.encode_decision(bin, &mut context).unwrap();
+            }
+            encoder.encode_terminate(true).unwrap();
+            assert!(encoder.is_at_slice_boundary());
+            slices.push(bins);
+        }
+        let compressed = encoder.into_compressed().unwrap();
+
+        // Decode the slices in reverse order, starting each one at its own byte offset.
+        for (bins, &offset) in slices.iter().zip(&offsets).rev() {
+            let mut decoder = CabacDecoder::from_compressed(&compressed[offset..]);
+            let mut context = CabacContext::default();
+            for &bin in bins {
+                assert_eq!(decoder.decode_decision(&mut context).unwrap(), bin);
+            }
+            assert!(decoder.decode_terminate().unwrap());
+        }
+    }
+
+    #[test]
+    fn compression_effectiveness() {
+        // Bins with a skewed distribution should compress close to their entropy.
+        let mut rng = Xoshiro256StarStar::seed_from_u64(789);
+        let mut encoder = CabacEncoder::new();
+        let mut context = CabacContext::default();
+        let amt = 100_000;
+        for _ in 0..amt {
+            encoder
+                .encode_decision(rng.next_u32() % 20 == 0, &mut context)
+                .unwrap();
+        }
+        let compressed = encoder.into_compressed().unwrap();
+
+        let p = 0.05f64;
+        let entropy = -(p * p.log2() + (1.0 - p) * (1.0 - p).log2());
+        let bits_per_bin = (compressed.len() * 8) as f64 / amt as f64;
+        assert!(bits_per_bin > entropy);
+        // CABAC's coarse probability states and fast adaptation cost a few percent.
+        assert!(bits_per_bin < 1.1 * entropy);
+
+        // Bypass bins take exactly one bit each, and terminating a slice takes 9 bits (plus
+        // padding to a byte boundary): 48 + 9 = 57 bits, i.e., 8 bytes.
+        let mut encoder = CabacEncoder::new();
+        encoder.encode_bypass_bits(0xdead_beef, 32).unwrap();
+        encoder.encode_bypass_bits(0x1234, 16).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+        assert_eq!(compressed.len(), 8);
+        let mut decoder = CabacDecoder::from_compressed(compressed);
+        assert_eq!(decoder.decode_bypass_bits(32).unwrap(), 0xdead_beef);
+        assert_eq!(decoder.decode_bypass_bits(16).unwrap(), 0x1234);
+        assert!(decoder.decode_terminate().unwrap());
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    fn shift_contexts() {
+        assert!(ShiftContext::new(0.0, 5).is_err());
+        assert!(ShiftContext::new(1.0, 5).is_err());
+        assert!(ShiftContext::new(0.5, 0).is_err());
+        assert!(ShiftContext::new(0.5, 16).is_err());
+        assert_eq!(ShiftContext::new(0.5, 5).unwrap(), ShiftContext::default());
+
+        // Estimates converge to the empirical probability but never reach zero or one.
+        let mut context = ShiftContext::new(0.5, 4).unwrap();
+        for _ in 0..1000 {
+            context.update(false);
+        }
+        assert!(context.probability_of_one() > 0.0);
+        assert!(context.probability_of_one() < 0.001);
+        assert!(!context.mps());
+        assert!(context.lps_range(256) >= 1);
+        for _ in 0..1000 {
+            context.update(true);
+        }
+        assert!(context.probability_of_one() < 1.0);
+        assert!(context.probability_of_one() > 0.999);
+        assert!(context.mps());
+
+        // For very skewed bins, a slowly adapting `ShiftContext` beats CABAC's state machine,
+        // whose smallest LPS probability is about 0.02.
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1011);
+        let bins = (0..100_000)
+            .map(|_| rng.next_u32() % 200 == 0)
+            .collect::<Vec<_>>();
+        let mut shift_contexts = ContextSet::new(alloc::vec![ShiftContext::new(0.5, 7).unwrap()]);
+        let mut cabac_contexts = ContextSet::<CabacContext>::with_default_contexts(1);
+        let mut shift_encoder = CabacEncoder::new();
+        let mut cabac_encoder = CabacEncoder::new();
+        for &bin in &bins {
+            shift_contexts
+                .encode_bin(&mut shift_encoder, bin, 0)
+                .unwrap();
+            cabac_contexts
+                .encode_bin(&mut cabac_encoder, bin, 0)
+                .unwrap();
+        }
+        let shift_compressed = shift_encoder.into_compressed().unwrap();
+        let cabac_compressed = cabac_encoder.into_compressed().unwrap();
+        assert!(shift_compressed.len() < cabac_compressed.len());
+
+        shift_contexts.reset();
+        assert_eq!(
+            shift_contexts.contexts(),
+            &[ShiftContext::new(0.5, 7).unwrap()]
+        );
+        let mut decoder = CabacDecoder::from_compressed(shift_compressed);
+        for &bin in &bins {
+            assert_eq!(shift_contexts.decode_bin(&mut decoder, 0).unwrap(), bin);
+        }
+        assert!(decoder.decode_terminate().unwrap());
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    fn context_set() {
+        let init_values = [139, 154, 110, 63, 200];
+        let mut contexts = ContextSet::from_init_values(&init_values, 27);
+        assert_eq!(contexts.len(), 5);
+        assert_eq!(contexts.get(1), Some(&CabacContext::new(0, true).unwrap()));
+        assert_eq!(contexts.get(5), None);
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(1213);
+        let bins = (0..5000)
+            .map(|_| {
+                let r = rng.next_u32();
+                let context_id = r as usize % 5;
+                ((r >> 8) % 8 < context_id as u32 + 1, context_id)
+            })
+            .collect::<Vec<_>>();
+
+        // Code two slices, resetting the contexts at the beginning of each slice.
+        let mut encoder = CabacEncoder::new();
+        for slice in bins.chunks(2500) {
+            contexts.reset();
+            for &(bin, context_id) in slice {
+                contexts.encode_bin(&mut encoder, bin, context_id).unwrap();
+            }
+            encoder.encode_terminate(true).unwrap();
+        }
+        let compressed = encoder.into_compressed().unwrap();
+
+        let mut decoder = CabacDecoder::from_compressed(compressed);
+        for slice in bins.chunks(2500) {
+            contexts.reset();
+            for &(bin, context_id) in slice {
+                assert_eq!(contexts.decode_bin(&mut decoder, context_id).unwrap(), bin);
+            }
+            assert!(decoder.decode_terminate().unwrap());
+        }
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    fn context_initialization() {
+        // HEVC's init value 154 corresponds to an equiprobable context for any QP.
+        for qp in [0, 22, 37, 51] {
+            assert_eq!(
+                CabacContext::from_init_value(154, qp),
+                CabacContext::new(0, true).unwrap()
+            );
+        }
+
+        let context = CabacContext::from_slope_and_offset(-28, 127, 26);
+        assert_eq!((context.state(), context.mps()), (17, true));
+        let context = CabacContext::from_slope_and_offset(20, -15, 26);
+        assert_eq!((context.state(), context.mps()), (46, false));
+
+        assert!(CabacContext::new(62, false).is_ok());
+        assert!(CabacContext::new(63, false).is_err());
+
+        let mut context = CabacContext::new(0, false).unwrap();
+        context.update(true);
+        assert_eq!((context.state(), context.mps()), (0, true));
+        for _ in 0..100 {
+            context.update(true);
+        }
+        assert_eq!(context.state(), 62);
+    }
+}
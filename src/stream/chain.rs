@@ -320,18 +320,42 @@ where
     /// [`AnsCoder`]: super::stack::AnsCoder
     /// [`from_compressed`]: Self::from_compressed
     pub fn from_binary(
-        mut data: CompressedBackend,
+        data: CompressedBackend,
     ) -> Result<Self, CoderError<CompressedBackend, CompressedBackend::ReadError>>
     where
         CompressedBackend: ReadWords<Word, Stack>,
         RemaindersBackend: Default,
+    {
+        Self::from_binary_with_remainders_backend(data, RemaindersBackend::default())
+            .map_err(|err| err.map_frontend(|(data, _)| data))
+    }
+
+    /// Same as [`from_binary`], but writes remainders to the provided backend instead of
+    /// to a default-constructed one.
+    ///
+    /// This allows you to decode with bounded memory, e.g., by reading `data` from a
+    /// [`Cursor`] or an iterator and writing the remainders to a
+    /// [callback](crate::backends::InfallibleCallbackWriteWords), as neither of these
+    /// backends implements `Default`. The `remainders` backend should be empty. On error,
+    /// both backends are returned to the caller.
+    ///
+    /// [`from_binary`]: Self::from_binary
+    /// [`Cursor`]: crate::backends::Cursor
+    pub fn from_binary_with_remainders_backend(
+        mut data: CompressedBackend,
+        remainders: RemaindersBackend,
+    ) -> Result<
+        Self,
+        CoderError<(CompressedBackend, RemaindersBackend), CompressedBackend::ReadError>,
+    >
+    where
+        CompressedBackend: ReadWords<Word, Stack>,
     {
         let heads = match ChainCoderHeads::new(&mut data, true) {
             Ok(heads) => heads,
-            Err(CoderError::Frontend(())) => return Err(CoderError::Frontend(data)),
+            Err(CoderError::Frontend(())) => return Err(CoderError::Frontend((data, remainders))),
             Err(CoderError::Backend(err)) => return Err(CoderError::Backend(err)),
         };
-        let remainders = RemaindersBackend::default();
 
         Ok(Self {
             compressed: data,
@@ -352,18 +376,40 @@ where
     /// [`AnsCoder`]: super::stack::AnsCoder
     /// [`AnsCoder::into_compressed`]: super::stack::AnsCoder::into_compressed
     pub fn from_compressed(
-        mut compressed: CompressedBackend,
+        compressed: CompressedBackend,
     ) -> Result<Self, CoderError<CompressedBackend, CompressedBackend::ReadError>>
     where
         CompressedBackend: ReadWords<Word, Stack>,
         RemaindersBackend: Default,
+    {
+        Self::from_compressed_with_remainders_backend(compressed, RemaindersBackend::default())
+            .map_err(|err| err.map_frontend(|(compressed, _)| compressed))
+    }
+
+    /// Same as [`from_compressed`], but writes remainders to the provided backend instead
+    /// of to a default-constructed one.
+    ///
+    /// See [`from_binary_with_remainders_backend`] for a discussion of use cases.
+    ///
+    /// [`from_compressed`]: Self::from_compressed
+    /// [`from_binary_with_remainders_backend`]: Self::from_binary_with_remainders_backend
+    pub fn from_compressed_with_remainders_backend(
+        mut compressed: CompressedBackend,
+        remainders: RemaindersBackend,
+    ) -> Result<
+        Self,
+        CoderError<(CompressedBackend, RemaindersBackend), CompressedBackend::ReadError>,
+    >
+    where
+        CompressedBackend: ReadWords<Word, Stack>,
     {
         let heads = match ChainCoderHeads::new(&mut compressed, false) {
             Ok(heads) => heads,
-            Err(CoderError::Frontend(())) => return Err(CoderError::Frontend(compressed)),
+            Err(CoderError::Frontend(())) => {
+                return Err(CoderError::Frontend((compressed, remainders)))
+            }
             Err(CoderError::Backend(err)) => return Err(CoderError::Backend(err)),
         };
-        let remainders = RemaindersBackend::default();
 
         Ok(Self {
             compressed,
@@ -424,25 +470,49 @@ where
     ///
     /// [`into_remainders`]: Self::into_remainders
     pub fn from_remainders(
-        mut remainders: RemaindersBackend,
+        remainders: RemaindersBackend,
     ) -> Result<Self, CoderError<RemaindersBackend, RemaindersBackend::ReadError>>
     where
         RemaindersBackend: ReadWords<Word, Stack>,
         CompressedBackend: Default,
+    {
+        Self::from_remainders_with_compressed_backend(remainders, CompressedBackend::default())
+            .map_err(|err| err.map_frontend(|(remainders, _)| remainders))
+    }
+
+    /// Same as [`from_remainders`], but writes compressed data to the provided backend
+    /// instead of to a default-constructed one.
+    ///
+    /// This allows you to encode with bounded memory, e.g., by writing the compressed data
+    /// to a [callback](crate::backends::InfallibleCallbackWriteWords) or into a
+    /// preallocated [`Cursor`]. The `compressed` backend should be empty. On error, both
+    /// backends are returned to the caller.
+    ///
+    /// [`from_remainders`]: Self::from_remainders
+    /// [`Cursor`]: crate::backends::Cursor
+    pub fn from_remainders_with_compressed_backend(
+        mut remainders: RemaindersBackend,
+        compressed: CompressedBackend,
+    ) -> Result<
+        Self,
+        CoderError<(RemaindersBackend, CompressedBackend), RemaindersBackend::ReadError>,
+    >
+    where
+        RemaindersBackend: ReadWords<Word, Stack>,
     {
         let compressed_head = match remainders.read()?.and_then(Word::into_nonzero) {
             Some(word) => word,
-            _ => return Err(CoderError::Frontend(remainders)),
+            _ => return Err(CoderError::Frontend((remainders, compressed))),
         };
         let mut heads = match ChainCoderHeads::new(&mut remainders, false) {
             Ok(heads) => heads,
-            Err(CoderError::Frontend(())) => return Err(CoderError::Frontend(remainders)),
+            Err(CoderError::Frontend(())) => {
+                return Err(CoderError::Frontend((remainders, compressed)))
+            }
             Err(CoderError::Backend(err)) => return Err(CoderError::Backend(err)),
         };
         heads.compressed = compressed_head;
 
-        let compressed = CompressedBackend::default();
-
         Ok(Self {
             compressed,
             remainders,
@@ -754,6 +824,99 @@ where
     }
 }
 
+#[cfg(feature = "pybindings")]
+impl<Word, State, const PRECISION: usize> ChainCoder<Word, State, Vec<Word>, Vec<Word>, PRECISION>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    /// Runs `f` on a coder that reads `compressed` without consuming it, and commits the
+    /// changes to `self` only if `f` succeeds.
+    ///
+    /// Decoding only ever pops words off `compressed` and pushes words onto `remainders`.
+    /// So we can undo a failed call to `f` by truncating `remainders`, without having to
+    /// copy either buffer.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn decode_transactionally<T, E>(
+        &mut self,
+        f: impl FnOnce(
+            &mut ChainCoder<
+                Word,
+                State,
+                crate::backends::Cursor<Word, &[Word]>,
+                Vec<Word>,
+                PRECISION,
+            >,
+        ) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let remainders_len = self.remainders.len();
+        let mut coder = ChainCoder {
+            compressed: crate::backends::Cursor::new_at_write_end(&self.compressed[..]),
+            remainders: core::mem::take(&mut self.remainders),
+            heads: self.heads,
+        };
+        let result = f(&mut coder);
+
+        let ChainCoder {
+            compressed,
+            mut remainders,
+            heads,
+        } = coder;
+        if result.is_ok() {
+            let compressed_len = compressed.pos();
+            self.compressed.truncate(compressed_len);
+            self.heads = heads;
+        } else {
+            remainders.truncate(remainders_len);
+        }
+        self.remainders = remainders;
+        result
+    }
+
+    /// Runs `f` on a coder that reads `remainders` without consuming it, and commits the
+    /// changes to `self` only if `f` succeeds.
+    ///
+    /// This is the mirror image of [`decode_transactionally`](Self::decode_transactionally)
+    /// since encoding only ever pops words off `remainders` and pushes words onto
+    /// `compressed`.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn encode_transactionally<T, E>(
+        &mut self,
+        f: impl FnOnce(
+            &mut ChainCoder<
+                Word,
+                State,
+                Vec<Word>,
+                crate::backends::Cursor<Word, &[Word]>,
+                PRECISION,
+            >,
+        ) -> Result<T, E>,
+    ) -> Result<T, E> {
+        let compressed_len = self.compressed.len();
+        let mut coder = ChainCoder {
+            compressed: core::mem::take(&mut self.compressed),
+            remainders: crate::backends::Cursor::new_at_write_end(&self.remainders[..]),
+            heads: self.heads,
+        };
+        let result = f(&mut coder);
+
+        let ChainCoder {
+            mut compressed,
+            remainders,
+            heads,
+        } = coder;
+        if result.is_ok() {
+            let remainders_len = remainders.pos();
+            self.remainders.truncate(remainders_len);
+            self.heads = heads;
+        } else {
+            compressed.truncate(compressed_len);
+        }
+        self.compressed = compressed;
+        result
+    }
+}
+
 impl<Word, State, CompressedBackend, RemaindersBackend, const PRECISION: usize> Code
     for ChainCoder<Word, State, CompressedBackend, RemaindersBackend, PRECISION>
 where
@@ -1158,6 +1321,7 @@ where
 mod tests {
     use super::super::model::LeakyQuantizer;
     use super::*;
+    use crate::UnwrapInfallible;
 
     use probability::distribution::Gaussian;
     use rand_xoshiro::{
@@ -1257,6 +1421,60 @@ mod tests {
         generic_restore_many::<u8, u32, u8, 8>(1024, 1000);
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn streaming_backends() {
+        use crate::backends::{Cursor, InfallibleCallbackWriteWords, InfallibleIteratorReadWords};
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(720);
+        let mut compressed = (0..100).map(|_| rng.next_u32()).collect::<Vec<_>>();
+        *compressed.last_mut().unwrap() |= 1;
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-100..=100);
+        let models = (0..50).map(|i| quantizer.quantize(Gaussian::new(i as f64, 10.0)));
+
+        // Reference: decode with `Vec` backends.
+        let mut coder = DefaultChainCoder::from_compressed(compressed.clone()).unwrap();
+        let symbols = coder
+            .decode_symbols(models.clone())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let (prefix, suffix) = coder.into_remainders().unwrap();
+
+        // Decode by pulling words from an iterator and pushing remainders to a callback.
+        let mut streamed_suffix = Vec::new();
+        let mut coder = ChainCoder::<u32, u64, _, _, 24>::from_compressed_with_remainders_backend(
+            InfallibleIteratorReadWords::new(compressed.iter().rev().copied()),
+            InfallibleCallbackWriteWords::new(|word| streamed_suffix.push(word)),
+        )
+        .unwrap_or_else(|_| panic!("not enough compressed data"));
+        let streamed_symbols = coder
+            .decode_symbols(models.clone())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let (unread, _) = coder.into_remainders().unwrap_infallible();
+        let mut streamed_prefix = unread.into_iter().collect::<Vec<_>>();
+        streamed_prefix.reverse();
+        assert_eq!(streamed_symbols, symbols);
+        assert_eq!(streamed_prefix, prefix);
+        assert_eq!(streamed_suffix, suffix);
+
+        // Encode by reading remainders from a `Cursor` and writing into a preallocated buffer.
+        let mut coder = ChainCoder::<u32, u64, _, _, 24>::from_remainders_with_compressed_backend(
+            Cursor::new_at_write_end(&suffix[..]),
+            Cursor::new_at_write_beginning(vec![0u32; compressed.len()]),
+        )
+        .unwrap();
+        coder
+            .encode_symbols_reverse(symbols.iter().zip(models))
+            .unwrap();
+        let (remainders, written) = coder.into_compressed().unwrap();
+        assert_eq!(remainders.pos(), 0);
+        let (buf, len) = written.into_buf_and_pos();
+        let mut reconstructed = prefix;
+        reconstructed.extend_from_slice(&buf[..len]);
+        assert_eq!(reconstructed, compressed);
+    }
+
     fn generic_restore_many<Word, State, Probability, const PRECISION: usize>(
         amt_compressed_words: usize,
         amt_symbols: usize,
@@ -1316,7 +1534,7 @@ mod tests {
         let coder2 = ChainCoder::from_remainders(remainders).unwrap();
         let coder3 = ChainCoder::from_remainders(remainders_suffix).unwrap();
 
-        for (mut coder, prefix) in vec![
+        for (mut coder, prefix) in [
             (coder, vec![]),
             (coder2, vec![]),
             (coder3, remainders_prefix),
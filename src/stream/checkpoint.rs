@@ -0,0 +1,372 @@
+//! Named checkpoints for random access
+//!
+//! The traits [`Pos`] and [`Seek`] let you take snapshots of a coder and jump back to them
+//! later, but the snapshots are raw pairs of a word offset and a coder state, which you
+//! have to keep track of yourself. In practice, random access usually needs *named*
+//! anchors, e.g., "frame 1234" or "layer conv3", and the mapping from names to snapshots
+//! has to travel along with the compressed data from the encoder to the decoder.
+//!
+//! A [`TaggedCoder`] wraps any coder and maintains such a mapping in a [`CheckpointIndex`]
+//! on the side: call [`pos_tagged`](TaggedCoder::pos_tagged) instead of [`pos`](Pos::pos)
+//! to record the current position under a tag of your choice, and call
+//! [`seek_to_tag`](TaggedCoder::seek_to_tag) instead of [`seek`](Seek::seek) to jump to
+//! the position that was recorded under a given tag. Tags can be of any type that
+//! implements [`Ord`], e.g., `u64` for numbered frames, or `Vec<u8>` or `String` for
+//! names. Since an encoder and a decoder are usually different objects, you can take the
+//! index out of the encoder with [`into_parts`](TaggedCoder::into_parts) and attach it to
+//! the decoder with [`with_index`](TaggedCoder::with_index).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     checkpoint::TaggedCoder,
+//!     model::DefaultLeakyQuantizer,
+//!     queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+//!     Decode, Encode,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+//! let frames = (0..10)
+//!     .map(|frame| (0..20).map(|i| (frame * 7 + i * 3) % 41 - 20).collect::<Vec<i32>>())
+//!     .collect::<Vec<_>>();
+//!
+//! // Encode all frames and record a checkpoint at the beginning of each one.
+//! let mut encoder = TaggedCoder::new(DefaultRangeEncoder::new());
+//! for (frame_number, frame) in frames.iter().enumerate() {
+//!     encoder.pos_tagged(frame_number as u64).unwrap();
+//!     encoder.encode_iid_symbols(frame, &model).unwrap();
+//! }
+//! let (encoder, index) = encoder.into_parts();
+//! assert_eq!(index.len(), 10);
+//!
+//! // Decode only frame 7.
+//! let decoder = DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap())
+//!     .unwrap();
+//! let mut decoder = TaggedCoder::with_index(decoder, index);
+//! decoder.seek_to_tag(&7).unwrap();
+//! let decoded = decoder
+//!     .decode_iid_symbols(20, &model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, frames[7]);
+//! ```
+
+use alloc::collections::{btree_map, BTreeMap};
+use core::borrow::Borrow;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    model::{DecoderModel, EncoderModel},
+    Code, Decode, Encode,
+};
+use crate::{CoderError, Pos, PosSeek, Seek};
+
+/// Wrapper around a coder that records positions under user-provided tags.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct TaggedCoder<Coder: PosSeek, Tag> {
+    inner: Coder,
+    index: CheckpointIndex<Tag, Coder::Position>,
+}
+
+impl<Coder: PosSeek, Tag: Ord> TaggedCoder<Coder, Tag> {
+    /// Wraps `inner`, starting with an empty index.
+    pub fn new(inner: Coder) -> Self {
+        Self::with_index(inner, CheckpointIndex::new())
+    }
+
+    /// Wraps `inner` and attaches an existing index, e.g., one that was recorded by the
+    /// encoder of the compressed data that `inner` decodes.
+    pub fn with_index(inner: Coder, index: CheckpointIndex<Tag, Coder::Position>) -> Self {
+        Self { inner, index }
+    }
+
+    /// Returns the current position like [`Pos::pos`] and records it under `tag`.
+    ///
+    /// Returns an error and leaves the index unchanged if the index already contains a
+    /// position with the same tag.
+    #[allow(clippy::result_unit_err)]
+    pub fn pos_tagged(&mut self, tag: Tag) -> Result<Coder::Position, ()>
+    where
+        Coder: Pos,
+    {
+        let pos = self.inner.pos();
+        self.index.insert(tag, pos.clone())?;
+        Ok(pos)
+    }
+
+    /// Jumps to the position that was recorded under `tag`.
+    ///
+    /// Returns an error if the index contains no position with the provided tag, or if
+    /// the wrapped coder fails to seek to it (see [`Seek::seek`]).
+    #[allow(clippy::result_unit_err)]
+    pub fn seek_to_tag<Q>(&mut self, tag: &Q) -> Result<(), ()>
+    where
+        Coder: Seek,
+        Tag: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        let pos = self.index.get(tag).ok_or(())?.clone();
+        self.inner.seek(pos)
+    }
+
+    /// Returns the index of all positions that have been recorded or attached so far.
+    pub fn index(&self) -> &CheckpointIndex<Tag, Coder::Position> {
+        &self.index
+    }
+
+    /// Returns a reference to the wrapped coder.
+    pub fn get_ref(&self) -> &Coder {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped coder.
+    pub fn get_mut(&mut self) -> &mut Coder {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped coder.
+    pub fn into_inner(self) -> Coder {
+        self.inner
+    }
+
+    /// Consumes the wrapper and returns both the wrapped coder and the index.
+    pub fn into_parts(self) -> (Coder, CheckpointIndex<Tag, Coder::Position>) {
+        (self.inner, self.index)
+    }
+}
+
+impl<Coder: Code + PosSeek, Tag> Code for TaggedCoder<Coder, Tag> {
+    type Word = Coder::Word;
+    type State = Coder::State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Coder, Tag, const PRECISION: usize> Encode<PRECISION> for TaggedCoder<Coder, Tag>
+where
+    Coder: Encode<PRECISION> + PosSeek,
+{
+    type FrontendError = Coder::FrontendError;
+    type BackendError = Coder::BackendError;
+
+    #[inline(always)]
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.inner.encode_symbol(symbol, model)
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.inner.maybe_full()
+    }
+}
+
+impl<Coder, Tag, const PRECISION: usize> Decode<PRECISION> for TaggedCoder<Coder, Tag>
+where
+    Coder: Decode<PRECISION> + PosSeek,
+{
+    type FrontendError = Coder::FrontendError;
+    type BackendError = Coder::BackendError;
+
+    #[inline(always)]
+    fn decode_symbol<D>(
+        &mut self,
+        model: D,
+    ) -> Result<D::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<D::Probability>,
+    {
+        self.inner.decode_symbol(model)
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.inner.maybe_exhausted()
+    }
+}
+
+impl<Coder: PosSeek, Tag> PosSeek for TaggedCoder<Coder, Tag> {
+    type Position = Coder::Position;
+}
+
+impl<Coder: Pos, Tag> Pos for TaggedCoder<Coder, Tag> {
+    #[inline(always)]
+    fn pos(&self) -> Self::Position {
+        self.inner.pos()
+    }
+}
+
+impl<Coder: Seek, Tag> Seek for TaggedCoder<Coder, Tag> {
+    #[inline(always)]
+    fn seek(&mut self, pos: Self::Position) -> Result<(), ()> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Mapping from user-provided tags to positions in compressed data, as recorded by a
+/// [`TaggedCoder`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CheckpointIndex<Tag, Position> {
+    positions: BTreeMap<Tag, Position>,
+}
+
+impl<Tag, Position> Default for CheckpointIndex<Tag, Position> {
+    fn default() -> Self {
+        Self {
+            positions: BTreeMap::new(),
+        }
+    }
+}
+
+impl<Tag: Ord, Position> CheckpointIndex<Tag, Position> {
+    /// Creates an empty index.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `pos` under `tag`.
+    ///
+    /// Returns an error and leaves the index unchanged if it already contains a position
+    /// with the same tag.
+    #[allow(clippy::result_unit_err)]
+    pub fn insert(&mut self, tag: Tag, pos: Position) -> Result<(), ()> {
+        match self.positions.entry(tag) {
+            btree_map::Entry::Vacant(entry) => {
+                entry.insert(pos);
+                Ok(())
+            }
+            btree_map::Entry::Occupied(_) => Err(()),
+        }
+    }
+
+    /// Returns the position that was recorded under `tag`, if any.
+    pub fn get<Q>(&self, tag: &Q) -> Option<&Position>
+    where
+        Tag: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.positions.get(tag)
+    }
+
+    /// Removes the position that was recorded under `tag` from the index and returns it.
+    pub fn remove<Q>(&mut self, tag: &Q) -> Option<Position>
+    where
+        Tag: Borrow<Q>,
+        Q: Ord + ?Sized,
+    {
+        self.positions.remove(tag)
+    }
+
+    /// Returns the number of tags in the index.
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    /// Returns `true` if the index contains no tags.
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Iterates over all recorded tags and their positions, sorted by tag.
+    pub fn iter(&self) -> impl Iterator<Item = (&Tag, &Position)> + '_ {
+        self.positions.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::{DefaultLeakyQuantizer, SmallLeakyQuantizer},
+        queue::{SmallRangeDecoder, SmallRangeEncoder},
+        stack::DefaultAnsCoder,
+    };
+    use super::*;
+
+    use alloc::vec::Vec;
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn byte_tags_on_ans() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        let layers: [(&[u8], Vec<i32>); 3] = [
+            (b"conv1", (0..30).map(|i| (i * 7) % 201 - 100).collect()),
+            (b"conv2", (0..40).map(|i| (i * 11) % 201 - 100).collect()),
+            (b"conv3", (0..50).map(|i| (i * 13) % 201 - 100).collect()),
+        ];
+
+        let mut encoder = TaggedCoder::<_, Vec<u8>>::new(DefaultAnsCoder::new());
+        for (name, symbols) in &layers {
+            encoder
+                .encode_iid_symbols(symbols.iter().rev(), &model)
+                .unwrap();
+            encoder.pos_tagged(name.to_vec()).unwrap();
+        }
+        // Duplicate tags are rejected.
+        assert!(encoder.pos_tagged(b"conv2".to_vec()).is_err());
+        let (encoder, index) = encoder.into_parts();
+        assert_eq!(index.len(), 3);
+        assert!(index.iter().map(|(tag, _)| &tag[..]).eq([
+            &b"conv1"[..],
+            &b"conv2"[..],
+            &b"conv3"[..]
+        ]));
+
+        let mut decoder = TaggedCoder::with_index(encoder.into_seekable_decoder(), index);
+        for (name, symbols) in layers.iter().rev() {
+            decoder.seek_to_tag(*name).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, symbols);
+        }
+        assert!(decoder.seek_to_tag(&b"conv4"[..]).is_err());
+    }
+
+    #[test]
+    fn numbered_tags_on_range_coder() {
+        let model = SmallLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        let symbols = (0..300).map(|i| (i * 17) % 201 - 100).collect::<Vec<i32>>();
+
+        let mut encoder = TaggedCoder::new(SmallRangeEncoder::new());
+        for (i, chunk) in symbols.chunks(30).enumerate() {
+            encoder.pos_tagged(i as u64 * 1000).unwrap();
+            encoder.encode_iid_symbols(chunk, &model).unwrap();
+        }
+        let (encoder, mut index) = encoder.into_parts();
+        let last = *index.get(&9000).unwrap();
+        assert_eq!(index.remove(&9000), Some(last));
+        assert_eq!(index.len(), 9);
+
+        let decoder =
+            SmallRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        let mut decoder = TaggedCoder::with_index(decoder, index);
+        for i in (0..9).rev() {
+            decoder.seek_to_tag(&(i * 1000)).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(30, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols[i as usize * 30..(i as usize + 1) * 30]);
+        }
+        assert!(decoder.seek_to_tag(&9000).is_err());
+    }
+}
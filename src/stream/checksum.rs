@@ -0,0 +1,319 @@
+//! Detecting corrupted compressed data with a CRC-32 checksum
+//!
+//! Stream codes in `constriction` are surjective, or nearly so: a decoder decodes
+//! *something* from almost any sequence of words, so flipped or lost bits in compressed data
+//! usually go unnoticed and just result in garbage symbols. If your compressed data passes
+//! through an unreliable channel or storage medium, you can append a checksum to it with
+//! [`seal_with_checksum`] (or with the convenience methods
+//! [`RangeEncoder::seal_with_checksum`] and [`AnsCoder::seal_with_checksum`]) and verify and
+//! strip off the checksum with [`verify_checksum`] before decoding (or with
+//! [`RangeDecoder::from_checksummed`] and [`AnsCoder::from_checksummed`]).
+//!
+//! The checksum is the standard CRC-32 (as used by, e.g., zlib, PNG, and Ethernet) of the
+//! compressed words in little-endian byte order, so it doesn't depend on the platform. It
+//! is stored in little-endian byte order in the last 32 bits' worth of words, i.e., in a
+//! single word if `Word` has at least 32 bits, and in four words for `Word = u8`. A CRC-32
+//! detects all burst errors of up to 32 bits and, with high probability, most other forms of
+//! corruption. It is not a cryptographic hash, though, so it doesn't protect against
+//! deliberate manipulation.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     checksum::{verify_checksum, ChecksumError},
+//!     model::DefaultLeakyQuantizer,
+//!     queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+//!     Decode, Encode,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+//! let message = [12, -5, 31, 0, -18];
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! encoder.encode_iid_symbols(&message, &model).unwrap();
+//! let compressed = encoder.seal_with_checksum();
+//!
+//! // Intact data can be decoded.
+//! let mut decoder = DefaultRangeDecoder::from_checksummed(compressed.clone()).unwrap();
+//! let decoded = decoder.decode_iid_symbols(5, &model).collect::<Result<Vec<_>, _>>();
+//! assert_eq!(decoded.unwrap(), message);
+//!
+//! // A single flipped bit is detected.
+//! let mut corrupted = compressed;
+//! corrupted[0] ^= 1 << 17;
+//! assert!(matches!(
+//!     verify_checksum(corrupted),
+//!     Err(ChecksumError::Mismatch { .. })
+//! ));
+//! ```
+//!
+//! [`RangeEncoder::seal_with_checksum`]: super::queue::RangeEncoder::seal_with_checksum
+//! [`RangeDecoder::from_checksummed`]: super::queue::RangeDecoder::from_checksummed
+//! [`AnsCoder::seal_with_checksum`]: super::stack::AnsCoder::seal_with_checksum
+//! [`AnsCoder::from_checksummed`]: super::stack::AnsCoder::from_checksummed
+
+use alloc::vec::Vec;
+use core::fmt::Display;
+
+use num::NumCast;
+
+use crate::BitArray;
+
+/// Lookup table for the CRC-32 with the (reflected) polynomial `0xEDB8_8320`.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xEDB8_8320
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+/// Computes the CRC-32 of a sequence of words in little-endian byte order.
+///
+/// For `Word = u8`, this is the standard CRC-32 of the byte sequence `words`.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::checksum::crc32;
+///
+/// assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+/// assert_eq!(crc32(&[0x3433_3231u32, 0x3837_3635]), crc32(b"12345678"));
+/// ```
+pub fn crc32<Word: BitArray>(words: &[Word]) -> u32 {
+    let mut crc = !0u32;
+    for &word in words {
+        let word = word.to_u128().expect("`Word` has at most 128 bits");
+        for i in 0..Word::BITS / 8 {
+            let byte = (word >> (8 * i)) as u8;
+            crc = (crc >> 8) ^ CRC32_TABLE[((crc as u8) ^ byte) as usize];
+        }
+    }
+    !crc
+}
+
+/// Returns the number of words in which the checksum is stored.
+fn num_checksum_words<Word: BitArray>() -> usize {
+    core::cmp::max(1, 32 / Word::BITS)
+}
+
+/// Appends the CRC-32 of `compressed` to `compressed`.
+///
+/// Use [`verify_checksum`] to check and strip off the checksum before decoding. See [module
+/// level documentation](self) for an example.
+pub fn seal_with_checksum<Word: BitArray>(mut compressed: Vec<Word>) -> Vec<Word> {
+    let checksum = crc32(&compressed);
+    let bits_per_word = core::cmp::min(Word::BITS, 32);
+    compressed.extend((0..num_checksum_words::<Word>()).map(|i| {
+        let chunk = checksum >> (i * bits_per_word) & (!0u32 >> (32 - bits_per_word));
+        <Word as NumCast>::from(chunk).expect("chunk fits into `Word`")
+    }));
+    compressed
+}
+
+/// Checks and strips off a checksum that was appended by [`seal_with_checksum`].
+///
+/// Returns the compressed data without the checksum if the checksum matches. See [module
+/// level documentation](self) for an example.
+pub fn verify_checksum<Word: BitArray>(
+    mut compressed: Vec<Word>,
+) -> Result<Vec<Word>, ChecksumError> {
+    let len = verify_checksum_slice(&compressed)?.len();
+    compressed.truncate(len);
+    Ok(compressed)
+}
+
+/// Same as [`verify_checksum`] but for borrowed data.
+pub fn verify_checksum_slice<Word: BitArray>(
+    compressed: &[Word],
+) -> Result<&[Word], ChecksumError> {
+    let len = compressed
+        .len()
+        .checked_sub(num_checksum_words::<Word>())
+        .ok_or(ChecksumError::Missing)?;
+    let (data, checksum_words) = compressed.split_at(len);
+
+    let bits_per_word = core::cmp::min(Word::BITS, 32);
+    let stored = checksum_words
+        .iter()
+        .enumerate()
+        .fold(0u32, |acc, (i, &word)| {
+            let word = word.to_u128().expect("`Word` has at most 128 bits");
+            acc | ((word as u32) << (i * bits_per_word))
+        });
+    let computed = crc32(data);
+
+    // The check of the high bits matters for words with more than 32 bits.
+    if stored == computed
+        && checksum_words
+            .iter()
+            .all(|&word| Word::BITS <= 32 || word >> 32 == Word::zero())
+    {
+        Ok(data)
+    } else {
+        Err(ChecksumError::Mismatch { stored, computed })
+    }
+}
+
+/// Error type for [`verify_checksum`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumError {
+    /// The compressed data is too short to contain a checksum.
+    Missing,
+
+    /// The stored checksum doesn't match the checksum of the data, i.e., the data (or the
+    /// checksum) is corrupted.
+    Mismatch {
+        /// The checksum that was stored at the end of the data.
+        stored: u32,
+
+        /// The checksum computed from the data.
+        computed: u32,
+    },
+
+    /// The checksum matches, but the data is not valid compressed data for the decoder.
+    InvalidData,
+}
+
+impl Display for ChecksumError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Missing => write!(f, "Compressed data is too short to contain a checksum."),
+            Self::Mismatch { stored, computed } => write!(
+                f,
+                "Checksum mismatch (stored: {:#010x}, computed: {:#010x}); \
+                the compressed data is corrupted.",
+                stored, computed
+            ),
+            Self::InvalidData => write!(
+                f,
+                "Checksum matches, but the data is not valid compressed data for this decoder."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ChecksumError {}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::DefaultLeakyQuantizer,
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+        Decode, Encode,
+    };
+    use super::*;
+
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn crc32_reference_values() {
+        assert_eq!(crc32::<u8>(&[]), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(
+            crc32(b"The quick brown fox jumps over the lazy dog"),
+            0x414F_A339
+        );
+        assert_eq!(crc32(&[0x3231u16, 0x3433]), crc32(b"1234"));
+        assert_eq!(crc32(&[0x3837_3635_3433_3231u64]), crc32(b"12345678"));
+    }
+
+    #[test]
+    fn coders() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        let symbols = (0..100).map(|i| (i * 17) % 61 - 30).collect::<Vec<i32>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let compressed = ans.seal_with_checksum();
+        let mut decoder = DefaultAnsCoder::from_checksummed(compressed.clone()).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(100, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.is_empty());
+
+        let mut corrupted = compressed;
+        corrupted[3] ^= 0x100;
+        assert!(matches!(
+            DefaultAnsCoder::from_checksummed(corrupted),
+            Err(ChecksumError::Mismatch { .. })
+        ));
+
+        // A trailing zero word is never valid data for an `AnsCoder`.
+        let invalid = seal_with_checksum(alloc::vec![5u32, 0]);
+        assert_eq!(
+            DefaultAnsCoder::from_checksummed(invalid).unwrap_err(),
+            ChecksumError::InvalidData
+        );
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let compressed = encoder.seal_with_checksum();
+        let mut decoder = DefaultRangeDecoder::from_checksummed(compressed.clone()).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(100, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.maybe_exhausted());
+
+        let truncated = compressed[..compressed.len() - 2].to_vec();
+        assert!(DefaultRangeDecoder::from_checksummed(truncated).is_err());
+    }
+
+    #[test]
+    fn seal_and_verify() {
+        fn check<Word: BitArray>(data: Vec<Word>, expected_overhead: usize) {
+            let sealed = seal_with_checksum(data.clone());
+            assert_eq!(sealed.len(), data.len() + expected_overhead);
+            assert_eq!(verify_checksum_slice(&sealed), Ok(&data[..]));
+            assert_eq!(verify_checksum(sealed.clone()), Ok(data.clone()));
+
+            for i in 0..sealed.len() {
+                for bit in [0, Word::BITS / 2, Word::BITS - 1] {
+                    let mut corrupted = sealed.clone();
+                    corrupted[i] = corrupted[i] ^ (Word::one() << bit);
+                    assert!(matches!(
+                        verify_checksum(corrupted),
+                        Err(ChecksumError::Mismatch { .. })
+                    ));
+                }
+            }
+
+            assert!(verify_checksum(sealed[..sealed.len() - 1].to_vec()).is_err());
+        }
+
+        check::<u8>(b"constriction".to_vec(), 4);
+        check::<u8>(Vec::new(), 4);
+        check::<u16>(alloc::vec![0x1234, 0, 0xffff], 2);
+        check::<u32>(alloc::vec![0xdead_beef, 0x0123_4567], 1);
+        check::<u64>(alloc::vec![u64::MAX, 5], 1);
+
+        assert_eq!(
+            verify_checksum::<u32>(Vec::new()),
+            Err(ChecksumError::Missing)
+        );
+        assert_eq!(
+            verify_checksum::<u8>(alloc::vec![1, 2]),
+            Err(ChecksumError::Missing)
+        );
+    }
+}
@@ -0,0 +1,989 @@
+//! Modeling symbols in the context of other symbols
+//!
+//! This module provides two tools for data whose statistics vary depending on context:
+//!
+//! - [`ContextModels`] selects one of several fixed entropy models per *block* of symbols.
+//!   Data often consists of blocks with different statistics (e.g., smooth and noisy
+//!   regions of a signal), and a single entropy model fitted to all blocks compresses
+//!   poorly. A common remedy is to train a small set of models on a partition of the data,
+//!   choose the best model for each block, and transmit the index ("model ID") of the
+//!   chosen model along with the block. `ContextModels` encodes each block's model ID with
+//!   a categorical entropy model on the same stream as the block's symbols, so that you
+//!   only have to manage a single coder and a single buffer of compressed data.
+//! - The [`ContextModel`] trait describes adaptive models that predict each *symbol* from
+//!   the symbols that precede it and learn their statistics while coding. The
+//!   [`OrderNContextModel`] implements it by collecting statistics for each context of up
+//!   to `N` preceding symbols, which makes `constriction` usable as the entropy coding
+//!   stage of a general-purpose (e.g., byte oriented) compressor. The adaptive models from
+//!   the sister module [`adaptive`](super::adaptive) implement the trait too (with an empty
+//!   context).
+//!
+//! # Example: Selecting a Model per Block
+//!
+//! ```
+//! use constriction::stream::{
+//!     context::ContextModels, model::DefaultLeakyQuantizer, stack::DefaultAnsCoder,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! // Two models, e.g., trained on the smooth and on the noisy parts of some training data.
+//! let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+//! let models = vec![
+//!     quantizer.quantize(Gaussian::new(0.0, 2.0)),
+//!     quantizer.quantize(Gaussian::new(0.0, 30.0)),
+//! ];
+//! // Smooth blocks are three times as common as noisy blocks in our (imaginary) data.
+//! let context_models = ContextModels::new(models, &[0.75, 0.25]).unwrap();
+//!
+//! let blocks = [(0, vec![1, -2, 0, 1]), (1, vec![-40, 27, 3, 58]), (0, vec![0, 0, 2, -1])];
+//!
+//! // Encode blocks in reverse order since the `AnsCoder` operates as a stack.
+//! let mut coder = DefaultAnsCoder::new();
+//! for (id, symbols) in blocks.iter().rev() {
+//!     context_models.encode_block_reverse(&mut coder, *id, symbols).unwrap();
+//! }
+//!
+//! // Decoding recovers each block's model ID along with its symbols.
+//! for (id, symbols) in &blocks {
+//!     let (decoded_id, decoded_symbols) = context_models.decode_block(&mut coder, 4).unwrap();
+//!     assert_eq!(decoded_id, *id);
+//!     assert_eq!(&decoded_symbols, symbols);
+//! }
+//! assert!(coder.is_empty());
+//! ```
+//!
+//! # Example: Compressing Bytes With an Order-2 Context Model
+//!
+//! ```
+//! use constriction::stream::{
+//!     context::{ContextBlending, ContextModel, DefaultOrderNContextModel},
+//!     queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+//! };
+//!
+//! let text = "the cat sat on the mat; the rat sat on the hat; the bat sat on the cat. ";
+//! let bytes = text.repeat(20).bytes().map(usize::from).collect::<Vec<_>>();
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! let mut model = DefaultOrderNContextModel::new(256, 2, ContextBlending::Mix { share: 0.01 })
+//!     .unwrap();
+//! model.encode_symbols(&mut encoder, &bytes).unwrap();
+//! let compressed = encoder.into_compressed().unwrap();
+//! assert!(compressed.len() * 32 < bytes.len() * 3); // Less than 3 bits per byte.
+//!
+//! // The decoder starts from a fresh model with the same settings.
+//! let mut decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+//! let mut model = DefaultOrderNContextModel::new(256, 2, ContextBlending::Mix { share: 0.01 })
+//!     .unwrap();
+//! let decoded = model.decode_symbols(&mut decoder, bytes.len()).unwrap();
+//! assert_eq!(decoded, bytes);
+//! ```
+
+use alloc::{collections::VecDeque, vec::Vec};
+use core::{borrow::Borrow, convert::TryFrom, marker::PhantomData};
+
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    model::{ContiguousCategoricalEntropyModel, DecoderModel, EncoderModel},
+    Decode, Encode,
+};
+use crate::{BitArray, CoderError};
+
+/// A set of entropy models together with a categorical distribution over model IDs.
+///
+/// See [module level documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct ContextModels<M, Probability: BitArray, const PRECISION: usize> {
+    models: Vec<M>,
+    id_model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+impl<M, Probability: BitArray, const PRECISION: usize> ContextModels<M, Probability, PRECISION> {
+    /// Creates a `ContextModels` from a list of `models` and the probabilities with which
+    /// each model is expected to be selected.
+    ///
+    /// There must be at least two `models`, and `id_probabilities` must have the same
+    /// length as `models`. The `id_probabilities` must be nonnegative and finite but they
+    /// don't need to be normalized. Returns an error if these conditions are violated.
+    /// You'll typically set `id_probabilities` to the (relative) frequencies with which
+    /// each model was chosen on some training data. The more concentrated the
+    /// distribution, the fewer bits are spent on model IDs.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(models: Vec<M>, id_probabilities: &[f64]) -> Result<Self, ()>
+    where
+        Probability: Into<f64> + AsPrimitive<usize>,
+        f64: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+    {
+        if models.len() != id_probabilities.len() {
+            return Err(());
+        }
+        let id_model =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities(id_probabilities)?;
+        Ok(Self { models, id_model })
+    }
+
+    /// Returns the entropy models from which each block selects one.
+    pub fn models(&self) -> &[M] {
+        &self.models
+    }
+
+    /// Returns the entropy model for model IDs.
+    pub fn id_model(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.id_model
+    }
+
+    /// Encodes the model ID `id`, followed by all `symbols` with the model `models()[id]`.
+    ///
+    /// Use this method with coders that have queue semantics (e.g., a [`RangeEncoder`]).
+    /// For coders with stack semantics, use [`encode_block_reverse`] instead.
+    ///
+    /// If `id` is not smaller than the number of models, then the `encoder` returns its
+    /// error for impossible symbols (e.g., [`DefaultEncoderFrontendError::ImpossibleSymbol`]),
+    /// and the `encoder` is not changed.
+    ///
+    /// [`RangeEncoder`]: super::queue::RangeEncoder
+    /// [`encode_block_reverse`]: Self::encode_block_reverse
+    /// [`DefaultEncoderFrontendError::ImpossibleSymbol`]:
+    ///     crate::DefaultEncoderFrontendError::ImpossibleSymbol
+    pub fn encode_block<E, S>(
+        &self,
+        encoder: &mut E,
+        id: usize,
+        symbols: impl IntoIterator<Item = S>,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        M: EncoderModel<PRECISION, Probability = Probability>,
+        S: Borrow<M::Symbol>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        // Encoding `id` fails without side effects unless it is a valid index into `models`.
+        encoder.encode_symbol(id, &self.id_model)?;
+        encoder.encode_iid_symbols(symbols, &self.models[id])
+    }
+
+    /// Same as [`encode_block`] but for coders with stack semantics (e.g., an
+    /// [`AnsCoder`]).
+    ///
+    /// Encodes the `symbols` in reverse order, followed by the model ID, so that
+    /// [`decode_block`] decodes the model ID first and then the symbols in their original
+    /// order. When encoding several blocks, encode them in reverse order too.
+    ///
+    /// If `id` is not smaller than the number of models, then the `encoder` returns its
+    /// error for impossible symbols, and the `encoder` is not changed (see
+    /// [`encode_block`]).
+    ///
+    /// [`encode_block`]: Self::encode_block
+    /// [`decode_block`]: Self::decode_block
+    /// [`AnsCoder`]: super::stack::AnsCoder
+    pub fn encode_block_reverse<E, S, I>(
+        &self,
+        encoder: &mut E,
+        id: usize,
+        symbols: I,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        M: EncoderModel<PRECISION, Probability = Probability>,
+        S: Borrow<M::Symbol>,
+        I: IntoIterator<Item = S>,
+        I::IntoIter: DoubleEndedIterator,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        let model = match self.models.get(id) {
+            Some(model) => model,
+            // Let the encoder generate its error for an impossible symbol.
+            None => return encoder.encode_symbol(id, &self.id_model),
+        };
+        encoder.encode_iid_symbols(symbols.into_iter().rev(), model)?;
+        encoder.encode_symbol(id, &self.id_model)
+    }
+
+    /// Decodes a model ID and then `amt` symbols with the selected model.
+    ///
+    /// Returns a tuple of the model ID and the decoded symbols. Works with both queue and
+    /// stack semantics, i.e., for data encoded with either [`encode_block`] or
+    /// [`encode_block_reverse`].
+    ///
+    /// [`encode_block`]: Self::encode_block
+    /// [`encode_block_reverse`]: Self::encode_block_reverse
+    #[allow(clippy::type_complexity)]
+    pub fn decode_block<D>(
+        &self,
+        decoder: &mut D,
+        amt: usize,
+    ) -> Result<(usize, Vec<M::Symbol>), CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        M: DecoderModel<PRECISION, Probability = Probability>,
+        Probability: Into<D::Word>,
+        D::Word: AsPrimitive<Probability>,
+    {
+        let id = decoder.decode_symbol(&self.id_model)?;
+        let symbols = decoder
+            .decode_iid_symbols(amt, &self.models[id])
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((id, symbols))
+    }
+}
+
+/// An adaptive model that predicts each symbol from the symbols that precede it.
+///
+/// A `ContextModel` models symbols from the alphabet `0..alphabet_size()`. Its current
+/// [`model`](Self::model) is the entropy model for the next symbol, and [`update`] records
+/// the symbol that actually occurred, which both updates the model's statistics and moves
+/// the context forward. Since a decoder performs the same updates as the encoder, an
+/// encoder and a decoder that start from identical `ContextModel`s stay in sync.
+///
+/// The provided methods [`encode_symbol`], [`encode_symbols`], [`encode_symbols_reverse`],
+/// [`decode_symbol`], and [`decode_symbols`] drive an [`Encode`] or [`Decode`]r with the
+/// `ContextModel` and call `update` for you.
+///
+/// [`update`]: Self::update
+/// [`encode_symbol`]: Self::encode_symbol
+/// [`encode_symbols`]: Self::encode_symbols
+/// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+/// [`decode_symbol`]: Self::decode_symbol
+/// [`decode_symbols`]: Self::decode_symbols
+pub trait ContextModel<const PRECISION: usize> {
+    /// The type used to represent fixed point probabilities.
+    type Probability: BitArray;
+
+    /// Returns the size of the alphabet, i.e., the number of distinct symbols.
+    fn alphabet_size(&self) -> usize;
+
+    /// Returns the entropy model for the next symbol, given the current context.
+    fn model(
+        &self,
+    ) -> ContiguousCategoricalEntropyModel<Self::Probability, Vec<Self::Probability>, PRECISION>;
+
+    /// Records an observation of `symbol` in the current context and appends `symbol` to
+    /// the context.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` is not smaller than the alphabet size.
+    fn update(&mut self, symbol: usize);
+
+    /// Encodes `symbol` with the current [`model`](Self::model) and then
+    /// [`update`](Self::update)s the `ContextModel`.
+    ///
+    /// Use this method with coders that have queue semantics (e.g., a [`RangeEncoder`]).
+    /// For coders with stack semantics, use [`encode_symbols_reverse`] instead.
+    ///
+    /// If `symbol` is not smaller than the alphabet size, then the `encoder` returns its
+    /// error for impossible symbols (e.g., [`DefaultEncoderFrontendError::ImpossibleSymbol`]),
+    /// and neither the `encoder` nor the `ContextModel` is changed.
+    ///
+    /// [`RangeEncoder`]: super::queue::RangeEncoder
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    /// [`DefaultEncoderFrontendError::ImpossibleSymbol`]:
+    ///     crate::DefaultEncoderFrontendError::ImpossibleSymbol
+    fn encode_symbol<E>(
+        &mut self,
+        encoder: &mut E,
+        symbol: usize,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Self::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Self::Probability>,
+    {
+        // The model has no probability mass for symbols outside of the alphabet, so the
+        // encoder rejects them before we get to `update`.
+        encoder.encode_symbol(symbol, self.model())?;
+        self.update(symbol);
+        Ok(())
+    }
+
+    /// Calls [`encode_symbol`](Self::encode_symbol) for each of the `symbols` in order.
+    ///
+    /// Use this method with coders that have queue semantics (e.g., a [`RangeEncoder`]).
+    ///
+    /// [`RangeEncoder`]: super::queue::RangeEncoder
+    fn encode_symbols<E>(
+        &mut self,
+        encoder: &mut E,
+        symbols: &[usize],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Self::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Self::Probability>,
+    {
+        for &symbol in symbols {
+            self.encode_symbol(encoder, symbol)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes `symbols` on a coder with stack semantics (e.g., an [`AnsCoder`]) such that
+    /// [`decode_symbols`] decodes them in their original order.
+    ///
+    /// Since a stack decodes symbols in reverse order, this method first runs the
+    /// `ContextModel` over all `symbols` to determine the entropy model for each one, and
+    /// then encodes them in reverse order. This requires memory proportional to
+    /// `symbols.len() * alphabet_size()`. Afterwards, the `ContextModel` is in the same
+    /// state as after calling [`encode_symbol`] for each symbol.
+    ///
+    /// If any of the `symbols` is not smaller than the alphabet size, then the `encoder`
+    /// returns its error for impossible symbols, and neither the `encoder` nor the
+    /// `ContextModel` is changed.
+    ///
+    /// [`AnsCoder`]: super::stack::AnsCoder
+    /// [`decode_symbols`]: Self::decode_symbols
+    /// [`encode_symbol`]: Self::encode_symbol
+    fn encode_symbols_reverse<E>(
+        &mut self,
+        encoder: &mut E,
+        symbols: &[usize],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Self::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Self::Probability>,
+    {
+        if let Some(&symbol) = symbols
+            .iter()
+            .find(|&&symbol| symbol >= self.alphabet_size())
+        {
+            // Fails without changing the `encoder` since the model has no probability mass
+            // for `symbol`.
+            return encoder.encode_symbol(symbol, self.model());
+        }
+
+        let models = symbols
+            .iter()
+            .map(|&symbol| {
+                let model = self.model();
+                self.update(symbol);
+                model
+            })
+            .collect::<Vec<_>>();
+        encoder.encode_symbols(symbols.iter().zip(models).rev())
+    }
+
+    /// Decodes a symbol with the current [`model`](Self::model) and then
+    /// [`update`](Self::update)s the `ContextModel` with the decoded symbol.
+    ///
+    /// Works both for data encoded with [`encode_symbol`] or [`encode_symbols`] on a coder
+    /// with queue semantics and for data encoded with [`encode_symbols_reverse`] on a coder
+    /// with stack semantics.
+    ///
+    /// [`encode_symbol`]: Self::encode_symbol
+    /// [`encode_symbols`]: Self::encode_symbols
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    fn decode_symbol<D>(
+        &mut self,
+        decoder: &mut D,
+    ) -> Result<usize, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        Self::Probability: Into<D::Word>,
+        D::Word: AsPrimitive<Self::Probability>,
+    {
+        let symbol = decoder.decode_symbol(self.model())?;
+        self.update(symbol);
+        Ok(symbol)
+    }
+
+    /// Calls [`decode_symbol`](Self::decode_symbol) `amt` times and collects the results.
+    fn decode_symbols<D>(
+        &mut self,
+        decoder: &mut D,
+        amt: usize,
+    ) -> Result<Vec<usize>, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        Self::Probability: Into<D::Word>,
+        D::Word: AsPrimitive<Self::Probability>,
+    {
+        (0..amt).map(|_| self.decode_symbol(decoder)).collect()
+    }
+}
+
+/// Strategy with which an [`OrderNContextModel`] combines the statistics of contexts of
+/// different lengths.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContextBlending {
+    /// Uses only the statistics of the longest context that has occurred before.
+    ///
+    /// Falls back to shorter contexts (down to the empty context) if the preceding
+    /// symbols have never occurred in this order before. This is cheap, and it works well
+    /// for highly repetitive data, but it trusts long contexts after a single observation.
+    Select,
+
+    /// Mixes the predictions of all context lengths from `0` to `N`.
+    ///
+    /// Each context length gets a weight that is updated after each symbol in proportion
+    /// to the probability that it assigned to the symbol (i.e., a Bayesian mixture over
+    /// context lengths). After each update, a fraction `share` of the total weight is
+    /// redistributed uniformly over all context lengths so that the mixture can switch
+    /// quickly when a different context length starts to predict better (the "fixed
+    /// share" rule). Typical values of `share` are between `0.001` and `0.05`; `share =
+    /// 0.0` turns off redistribution.
+    Mix {
+        /// Fraction of the total weight that is redistributed after each symbol; must be
+        /// within the closed interval `[0, 1]`.
+        share: f64,
+    },
+}
+
+/// An adaptive model that predicts each symbol from up to `N` preceding symbols.
+///
+/// An `OrderNContextModel` models symbols from the alphabet `0..alphabet_size`. For each
+/// context length `k` from `0` to the `order` `N`, it counts how often each symbol has
+/// occurred after each sequence of `k` symbols. It estimates the probabilities of the
+/// next symbol in each context like a [`KTEstimator`] (i.e., with half a pseudo-count per
+/// symbol), and it combines the estimates for the current contexts of all lengths as
+/// specified by a [`ContextBlending`].
+///
+/// At the beginning of a sequence (and after [`reset_context`]), fewer than `N` preceding
+/// symbols are available, and only contexts up to the available length are used.
+///
+/// The model allocates a table of `alphabet_size` counts for each distinct context it
+/// encounters, so its memory usage grows with the amount of (diverse) data it sees.
+/// Building an entropy model takes time proportional to `(N + 1) * alphabet_size`. Since
+/// the estimates are computed in floating point arithmetic, encoder and decoder have to
+/// run on platforms with identical `f64` arithmetic (which is the case for all IEEE 754
+/// compliant platforms as long as the code isn't compiled with flags that enable
+/// non-standard floating point optimizations).
+///
+/// See [module level documentation](self) for an example.
+///
+/// [`KTEstimator`]: super::adaptive::KTEstimator
+/// [`reset_context`]: Self::reset_context
+#[derive(Debug, Clone)]
+pub struct OrderNContextModel<Probability: BitArray, const PRECISION: usize> {
+    alphabet_size: usize,
+    blending: ContextBlending,
+
+    /// The up to `order` most recent symbols, oldest first.
+    context: VecDeque<usize>,
+
+    /// `statistics[k]` maps each context of length `k` (encoded as a number in base
+    /// `alphabet_size`) to the counts of the symbols that followed it.
+    statistics: Vec<HashMap<u64, ContextStatistics>>,
+
+    /// Weight of each context length (only used for `ContextBlending::Mix`).
+    weights: Vec<f64>,
+
+    phantom: PhantomData<Probability>,
+}
+
+#[derive(Debug, Clone)]
+struct ContextStatistics {
+    counts: Vec<u32>,
+    total: u64,
+}
+
+/// Type alias for an [`OrderNContextModel`] with sane settings.
+///
+/// Generates entropy models that can be used with a [`DefaultAnsCoder`] or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: super::stack::DefaultAnsCoder
+/// [`DefaultRangeEncoder`]: super::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: super::queue::DefaultRangeDecoder
+pub type DefaultOrderNContextModel = OrderNContextModel<u32, 24>;
+
+/// Type alias for an [`OrderNContextModel`] optimized for compatibility with lookup
+/// decoder models.
+///
+/// Generates entropy models that can be used with a [`SmallAnsCoder`] or a
+/// [`SmallRangeEncoder`] / [`SmallRangeDecoder`]. Note that the alphabet has to fit into
+/// the `2^12` available fixed point probability units.
+///
+/// [`SmallAnsCoder`]: super::stack::SmallAnsCoder
+/// [`SmallRangeEncoder`]: super::queue::SmallRangeEncoder
+/// [`SmallRangeDecoder`]: super::queue::SmallRangeDecoder
+pub type SmallOrderNContextModel = OrderNContextModel<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> OrderNContextModel<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Creates a model for the alphabet `0..alphabet_size` that takes up to `order`
+    /// preceding symbols into account and that has not observed any symbols yet.
+    ///
+    /// Returns an error if `alphabet_size` is smaller than two or too large to assign a
+    /// nonzero probability to each symbol with `PRECISION` bits, if `alphabet_size^order`
+    /// exceeds `2^64` (e.g., if `order > 8` for bytes), or if the `share` of a
+    /// [`ContextBlending::Mix`] is not within `[0, 1]`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(alphabet_size: usize, order: usize, blending: ContextBlending) -> Result<Self, ()> {
+        if alphabet_size < 2 {
+            return Err(());
+        }
+        if let ContextBlending::Mix { share } = blending {
+            if !(0.0..=1.0).contains(&share) {
+                return Err(());
+            }
+        }
+        let max_num_contexts = u32::try_from(order)
+            .ok()
+            .and_then(|order| (alphabet_size as u128).checked_pow(order))
+            .ok_or(())?;
+        if max_num_contexts > 1 << 64 {
+            return Err(());
+        }
+
+        let model = Self {
+            alphabet_size,
+            blending,
+            context: VecDeque::with_capacity(order + 1),
+            statistics: (0..=order).map(|_| HashMap::new()).collect(),
+            weights: alloc::vec![1.0 / (order + 1) as f64; order + 1],
+            phantom: PhantomData,
+        };
+
+        // Same argument as in `KTEstimator::new`: probabilities are bounded away from zero.
+        let _: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+                &model.probabilities(),
+            )?;
+        Ok(model)
+    }
+
+    /// Returns the maximum number `N` of preceding symbols that the model takes into
+    /// account.
+    pub fn order(&self) -> usize {
+        self.statistics.len() - 1
+    }
+
+    /// Returns the strategy with which the model combines contexts of different lengths.
+    pub fn blending(&self) -> ContextBlending {
+        self.blending
+    }
+
+    /// Returns the current context, i.e., the up to [`order`](Self::order) most recent
+    /// symbols, oldest first.
+    pub fn context(&self) -> impl ExactSizeIterator<Item = usize> + '_ {
+        self.context.iter().copied()
+    }
+
+    /// Returns the number of distinct contexts (of all lengths) for which the model has
+    /// collected statistics so far.
+    pub fn num_contexts(&self) -> usize {
+        self.statistics.iter().map(HashMap::len).sum()
+    }
+
+    /// Clears the current context but retains all statistics.
+    ///
+    /// Call this method (on both the encoder and the decoder side) at the beginning of a
+    /// new message if the symbols at the end of the previous message don't say anything
+    /// about the symbols at the beginning of the next one.
+    pub fn reset_context(&mut self) {
+        self.context.clear();
+    }
+
+    /// Returns the keys of the current contexts of lengths `0` to `context.len()`.
+    fn context_keys(&self) -> impl Iterator<Item = u64> + '_ {
+        let mut key = 0u64;
+        let mut factor = 1u64;
+        core::iter::once(0).chain(self.context.iter().rev().map(move |&symbol| {
+            key = key.wrapping_add(factor.wrapping_mul(symbol as u64));
+            factor = factor.wrapping_mul(self.alphabet_size as u64);
+            key
+        }))
+    }
+
+    /// Returns the KT estimate of the probability of `symbol` given `statistics`.
+    fn estimate(&self, statistics: Option<&ContextStatistics>, symbol: usize) -> f64 {
+        let half_alphabet = 0.5 * self.alphabet_size as f64;
+        match statistics {
+            Some(statistics) => {
+                (statistics.counts[symbol] as f64 + 0.5) / (statistics.total as f64 + half_alphabet)
+            }
+            None => 0.5 / half_alphabet,
+        }
+    }
+
+    /// Returns the estimated probabilities of all symbols in floating point arithmetic.
+    ///
+    /// The returned probabilities are strictly positive and sum to one (up to rounding
+    /// errors). The entropy model returned by [`model`](ContextModel::model) approximates
+    /// them in fixed point arithmetic.
+    pub fn probabilities(&self) -> Vec<f64> {
+        let contexts = self
+            .context_keys()
+            .zip(&self.statistics)
+            .map(|(key, statistics)| statistics.get(&key))
+            .collect::<Vec<_>>();
+
+        match self.blending {
+            ContextBlending::Select => {
+                let statistics = contexts.iter().rev().find_map(|&statistics| statistics);
+                (0..self.alphabet_size)
+                    .map(|symbol| self.estimate(statistics, symbol))
+                    .collect()
+            }
+            ContextBlending::Mix { .. } => {
+                let weights = &self.weights[..contexts.len()];
+                let normalization = weights.iter().sum::<f64>();
+                (0..self.alphabet_size)
+                    .map(|symbol| {
+                        contexts
+                            .iter()
+                            .zip(weights)
+                            .map(|(&statistics, &weight)| {
+                                weight * self.estimate(statistics, symbol)
+                            })
+                            .sum::<f64>()
+                            / normalization
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> ContextModel<PRECISION>
+    for OrderNContextModel<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    type Probability = Probability;
+
+    fn alphabet_size(&self) -> usize {
+        self.alphabet_size
+    }
+
+    fn model(&self) -> ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        ContiguousCategoricalEntropyModel::from_floating_point_probabilities(&self.probabilities())
+            .expect("Checked in constructor.")
+    }
+
+    fn update(&mut self, symbol: usize) {
+        assert!(symbol < self.alphabet_size);
+        let num_available = self.context.len() + 1;
+
+        if let ContextBlending::Mix { share } = self.blending {
+            let mut weights = core::mem::take(&mut self.weights);
+            for ((weight, key), statistics) in weights
+                .iter_mut()
+                .zip(self.context_keys())
+                .zip(&self.statistics)
+            {
+                // Flooring the weights keeps them (and thus their normalization) positive,
+                // which matters if `share == 0.0`, where they could otherwise underflow.
+                *weight =
+                    (*weight * self.estimate(statistics.get(&key), symbol)).max(f64::MIN_POSITIVE);
+            }
+            let available = &mut weights[..num_available];
+            let normalization = available.iter().sum::<f64>();
+            let uniform = 1.0 / num_available as f64;
+            for weight in available {
+                *weight = (1.0 - share) * *weight / normalization + share * uniform;
+            }
+            self.weights = weights;
+        }
+
+        let keys = self.context_keys().collect::<Vec<_>>();
+        let alphabet_size = self.alphabet_size;
+        for (key, statistics) in keys.into_iter().zip(&mut self.statistics) {
+            let statistics = statistics.entry(key).or_insert_with(|| ContextStatistics {
+                counts: alloc::vec![0; alphabet_size],
+                total: 0,
+            });
+            // Counts saturate rather than overflow after `u32::MAX` observations.
+            if let Some(count) = statistics.counts[symbol].checked_add(1) {
+                statistics.counts[symbol] = count;
+                statistics.total += 1;
+            }
+        }
+
+        if self.context.len() == self.order() {
+            self.context.pop_front();
+        }
+        if self.order() != 0 {
+            self.context.push_back(symbol);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            adaptive::DefaultKTEstimator,
+            model::{DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer},
+            queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+            stack::DefaultAnsCoder,
+        },
+        *,
+    };
+    use crate::DefaultEncoderFrontendError;
+
+    use alloc::vec;
+    use probability::distribution::Gaussian;
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    fn invalid_construction() {
+        let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+            0.5, 0.5,
+        ])
+        .unwrap();
+        let models = vec![model.clone(), model];
+        assert!(ContextModels::<_, u32, 24>::new(models.clone(), &[1.0]).is_err());
+        assert!(ContextModels::<_, u32, 24>::new(models.clone(), &[-1.0, 2.0]).is_err());
+        assert!(ContextModels::<_, u32, 24>::new(models[..1].to_vec(), &[1.0]).is_err());
+        assert!(ContextModels::<_, u32, 24>::new(models, &[1.0, 3.0]).is_ok());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn roundtrip() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(722);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let models = [1.0, 5.0, 25.0]
+            .iter()
+            .map(|&std| quantizer.quantize(Gaussian::new(0.0, std)))
+            .collect::<Vec<_>>();
+        let context_models = ContextModels::new(models, &[0.5, 0.3, 0.2]).unwrap();
+
+        let blocks = (0..100)
+            .map(|_| {
+                let id = (rng.next_u32() % 3) as usize;
+                let len = (rng.next_u32() % 20) as usize;
+                let symbols = (0..len)
+                    .map(|_| (rng.next_u32() % 21) as i32 - 10)
+                    .collect::<Vec<_>>();
+                (id, symbols)
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        for (id, symbols) in &blocks {
+            context_models
+                .encode_block(&mut encoder, *id, symbols)
+                .unwrap();
+        }
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        for (id, symbols) in &blocks {
+            assert_eq!(
+                context_models
+                    .decode_block(&mut decoder, symbols.len())
+                    .unwrap(),
+                (*id, symbols.clone())
+            );
+        }
+        assert!(decoder.maybe_exhausted());
+
+        let mut coder = DefaultAnsCoder::new();
+        for (id, symbols) in blocks.iter().rev() {
+            context_models
+                .encode_block_reverse(&mut coder, *id, symbols)
+                .unwrap();
+        }
+        for (id, symbols) in &blocks {
+            assert_eq!(
+                context_models
+                    .decode_block(&mut coder, symbols.len())
+                    .unwrap(),
+                (*id, symbols.clone())
+            );
+        }
+        assert!(coder.is_empty());
+    }
+
+    #[test]
+    fn invalid_block_ids() {
+        let quantizer = DefaultLeakyQuantizer::new(-10..=10);
+        let models = [1.0, 5.0]
+            .iter()
+            .map(|&std| quantizer.quantize(Gaussian::new(0.0, std)))
+            .collect::<Vec<_>>();
+        let context_models = ContextModels::new(models, &[0.5, 0.5]).unwrap();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        assert_eq!(
+            context_models.encode_block(&mut encoder, 2, [1, 2, 3]),
+            Err(DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())
+        );
+        assert!(encoder.is_empty());
+
+        let mut coder = DefaultAnsCoder::new();
+        assert_eq!(
+            context_models.encode_block_reverse(&mut coder, 2, [1, 2, 3]),
+            Err(DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())
+        );
+        assert!(coder.is_empty());
+    }
+
+    /// Generates symbols from the alphabet `0..4` where each symbol is (mostly) determined
+    /// by the two preceding symbols.
+    fn order_2_source(amt: usize, seed: u64) -> Vec<usize> {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(seed);
+        let mut symbols = vec![0, 1];
+        while symbols.len() < amt {
+            let n = symbols.len();
+            let symbol = if rng.next_u32() % 16 == 0 {
+                (rng.next_u32() % 4) as usize
+            } else {
+                (symbols[n - 1] + 2 * symbols[n - 2] + 1) % 4
+            };
+            symbols.push(symbol);
+        }
+        symbols
+    }
+
+    fn compressed_len(
+        model: &mut impl ContextModel<24, Probability = u32>,
+        symbols: &[usize],
+    ) -> usize {
+        let mut encoder = DefaultRangeEncoder::new();
+        model.encode_symbols(&mut encoder, symbols).unwrap();
+        encoder.into_compressed().unwrap().len()
+    }
+
+    #[test]
+    fn order_n_invalid_construction() {
+        let mix = ContextBlending::Mix { share: 0.01 };
+        assert!(DefaultOrderNContextModel::new(1, 2, mix).is_err());
+        assert!(DefaultOrderNContextModel::new(256, 8, mix).is_ok());
+        assert!(DefaultOrderNContextModel::new(256, 9, mix).is_err());
+        assert!(DefaultOrderNContextModel::new(2, 64, mix).is_ok());
+        assert!(DefaultOrderNContextModel::new(2, 65, mix).is_err());
+        assert!(
+            DefaultOrderNContextModel::new(4, 2, ContextBlending::Mix { share: -0.1 }).is_err()
+        );
+        assert!(DefaultOrderNContextModel::new(4, 2, ContextBlending::Mix { share: 1.1 }).is_err());
+        assert!(SmallOrderNContextModel::new(4096, 1, ContextBlending::Select).is_ok());
+        assert!(SmallOrderNContextModel::new(4097, 1, ContextBlending::Select).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn order_n_roundtrip() {
+        let symbols = order_2_source(2000, 123);
+        for &blending in &[
+            ContextBlending::Select,
+            ContextBlending::Mix { share: 0.0 },
+            ContextBlending::Mix { share: 0.02 },
+        ] {
+            for order in 0..4 {
+                let new_model = || DefaultOrderNContextModel::new(4, order, blending).unwrap();
+
+                let mut encoder = DefaultRangeEncoder::new();
+                let mut model = new_model();
+                model.encode_symbols(&mut encoder, &symbols).unwrap();
+                assert_eq!(model.context().len(), order.min(symbols.len()));
+                let mut decoder =
+                    DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap())
+                        .unwrap();
+                let mut model = new_model();
+                assert_eq!(
+                    model.decode_symbols(&mut decoder, symbols.len()).unwrap(),
+                    symbols
+                );
+                assert!(decoder.maybe_exhausted());
+
+                let mut coder = DefaultAnsCoder::new();
+                let mut model = new_model();
+                model.encode_symbols_reverse(&mut coder, &symbols).unwrap();
+                let num_contexts = model.num_contexts();
+                let mut model = new_model();
+                assert_eq!(
+                    model.decode_symbols(&mut coder, symbols.len()).unwrap(),
+                    symbols
+                );
+                assert_eq!(model.num_contexts(), num_contexts);
+                assert!(coder.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn order_n_mix_without_share_stays_finite() {
+        // With `share == 0.0`, the weights of the shorter contexts decay exponentially, so
+        // they would underflow without flooring, and resetting the context would then make
+        // the normalization of the remaining weights zero.
+        let symbols = order_2_source(10_000, 42);
+        let mut model =
+            DefaultOrderNContextModel::new(4, 2, ContextBlending::Mix { share: 0.0 }).unwrap();
+        compressed_len(&mut model, &symbols);
+        model.reset_context();
+        assert!(model
+            .probabilities()
+            .iter()
+            .all(|p| p.is_finite() && *p > 0.0));
+        compressed_len(&mut model, &symbols);
+    }
+
+    #[test]
+    fn context_model_rejects_invalid_symbols() {
+        let mut model =
+            DefaultOrderNContextModel::new(4, 2, ContextBlending::Mix { share: 0.01 }).unwrap();
+        let mut encoder = DefaultRangeEncoder::new();
+        model.encode_symbols(&mut encoder, &[1, 3, 2]).unwrap();
+        let compressed_len = encoder.num_bits();
+        assert_eq!(
+            model.encode_symbol(&mut encoder, 4),
+            Err(DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())
+        );
+        assert_eq!(encoder.num_bits(), compressed_len);
+        assert_eq!(model.context().collect::<Vec<_>>(), [3, 2]);
+
+        let mut coder = DefaultAnsCoder::new();
+        assert_eq!(
+            model.encode_symbols_reverse(&mut coder, &[0, 1, 7, 2]),
+            Err(DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())
+        );
+        assert!(coder.is_empty());
+        assert_eq!(model.context().collect::<Vec<_>>(), [3, 2]);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn order_n_exploits_context() {
+        let symbols = order_2_source(5000, 321);
+        let mut kt = DefaultKTEstimator::new(4).unwrap();
+        let kt_len = compressed_len(&mut kt, &symbols);
+
+        // Without any context, an `OrderNContextModel` is just a `KTEstimator`.
+        let mut order_0 = DefaultOrderNContextModel::new(4, 0, ContextBlending::Select).unwrap();
+        assert_eq!(compressed_len(&mut order_0, &symbols), kt_len);
+
+        for &blending in &[
+            ContextBlending::Select,
+            ContextBlending::Mix { share: 0.01 },
+        ] {
+            let mut order_1 = DefaultOrderNContextModel::new(4, 1, blending).unwrap();
+            let mut order_2 = DefaultOrderNContextModel::new(4, 2, blending).unwrap();
+            let order_1_len = compressed_len(&mut order_1, &symbols);
+            let order_2_len = compressed_len(&mut order_2, &symbols);
+            assert!(order_2_len < order_1_len);
+            assert!(order_2_len * 2 < kt_len);
+        }
+
+        // Resetting the context retains the statistics.
+        let mut model =
+            DefaultOrderNContextModel::new(4, 2, ContextBlending::Mix { share: 0.01 }).unwrap();
+        let first_len = compressed_len(&mut model, &symbols);
+        model.reset_context();
+        assert_eq!(model.context().len(), 0);
+        assert!(compressed_len(&mut model, &symbols) < first_len);
+    }
+}
@@ -0,0 +1,201 @@
+//! The simplest way to compress and decompress a sequence of symbols
+//!
+//! This module provides just two functions, [`encode_to_bytes`] and [`decode_from_bytes`],
+//! which compress a slice of i.i.d. symbols into a `Vec<u8>` and back. They use a
+//! [`DefaultRangeEncoder`] and [`DefaultRangeDecoder`] under the hood, so you don't have to
+//! worry about words, sealing, or backends. If you need more control (e.g., individual
+//! entropy models for each symbol, random access, or bits-back coding), use the coders in
+//! the submodules [`queue`] and [`stack`] directly.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{easy, model::DefaultLeakyQuantizer};
+//! use probability::distribution::Gaussian;
+//!
+//! let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(3.2, 10.0));
+//! let symbols = [5, -3, 12, 0, 7, 4];
+//!
+//! let bytes = easy::encode_to_bytes(&symbols, &model).unwrap();
+//! assert_eq!(bytes.len(), 4);
+//!
+//! let decoded = easy::decode_from_bytes(&bytes, &model, symbols.len()).unwrap();
+//! assert_eq!(decoded, symbols);
+//! ```
+//!
+//! # Format
+//!
+//! The returned bytes are the words of [`DefaultRangeEncoder::into_compressed`], each
+//! serialized in little-endian byte order. This layout is the same on all platforms, and
+//! it is also what the functions of the same names in the Python API produce. The number of
+//! encoded symbols is *not* stored, so you have to provide it to [`decode_from_bytes`].
+//!
+//! [`DefaultRangeEncoder`]: super::queue::DefaultRangeEncoder
+//! [`DefaultRangeDecoder`]: super::queue::DefaultRangeDecoder
+//! [`DefaultRangeEncoder::into_compressed`]: super::queue::RangeEncoder::into_compressed
+//! [`queue`]: super::queue
+//! [`stack`]: super::stack
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, fmt::Display};
+
+use num::cast::AsPrimitive;
+
+use super::{
+    model::{DecoderModel, EncoderModel},
+    queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+    Decode, Encode,
+};
+use crate::{CoderError, DefaultEncoderFrontendError, UnwrapInfallible};
+
+/// Compresses `symbols` with the same entropy `model` for each symbol.
+///
+/// See [module level documentation](self) for an example and for the format of the returned
+/// bytes.
+///
+/// # Errors
+///
+/// Returns an error if one of the `symbols` has zero probability under `model`.
+pub fn encode_to_bytes<S, M, const PRECISION: usize>(
+    symbols: impl IntoIterator<Item = S>,
+    model: &M,
+) -> Result<Vec<u8>, DefaultEncoderFrontendError>
+where
+    S: Borrow<M::Symbol>,
+    M: EncoderModel<PRECISION>,
+    M::Probability: Into<u32>,
+    u32: AsPrimitive<M::Probability>,
+{
+    let mut encoder = DefaultRangeEncoder::new();
+    encoder
+        .encode_iid_symbols(symbols, model)
+        .map_err(|err| match err {
+            CoderError::Frontend(err) => err,
+            CoderError::Backend(infallible) => match infallible {},
+        })?;
+
+    Ok(encoder
+        .into_compressed()
+        .unwrap_infallible()
+        .iter()
+        .flat_map(|word| word.to_le_bytes())
+        .collect())
+}
+
+/// Decompresses `amt` symbols from `bytes` that were generated by [`encode_to_bytes`].
+///
+/// The `model` must be the same one that was used for encoding. Decoding more symbols than
+/// were encoded does not fail but returns arbitrary symbols.
+///
+/// # Errors
+///
+/// Returns [`DecodingError::InvalidLength`] if the length of `bytes` is not a multiple of
+/// four (since [`encode_to_bytes`] always returns whole `u32` words).
+pub fn decode_from_bytes<M, const PRECISION: usize>(
+    bytes: &[u8],
+    model: &M,
+    amt: usize,
+) -> Result<Vec<M::Symbol>, DecodingError>
+where
+    M: DecoderModel<PRECISION>,
+    M::Probability: Into<u32>,
+    u32: AsPrimitive<M::Probability>,
+{
+    if bytes.len() % 4 != 0 {
+        return Err(DecodingError::InvalidLength);
+    }
+    let compressed = bytes
+        .chunks_exact(4)
+        .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect::<Vec<_>>();
+
+    let mut decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap_infallible();
+    decoder
+        .decode_iid_symbols(amt, model)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| DecodingError::InvalidData)
+}
+
+/// Error type for [`decode_from_bytes`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodingError {
+    /// The number of bytes is not a multiple of four.
+    InvalidLength,
+
+    /// The compressed data is invalid for the provided entropy model.
+    InvalidData,
+}
+
+impl Display for DecodingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidLength => write!(
+                f,
+                "The length of the compressed data must be a multiple of four bytes."
+            ),
+            Self::InvalidData => write!(f, "Invalid compressed data."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodingError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::model::{DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer},
+        *,
+    };
+
+    use probability::distribution::Gaussian;
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    fn roundtrip() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(727);
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(-5.3, 20.0));
+        for &amt in &[0, 1, 2, 10, 1000] {
+            let symbols = (0..amt)
+                .map(|_| (rng.next_u32() % 201) as i32 - 100)
+                .collect::<Vec<_>>();
+            let bytes = encode_to_bytes(&symbols, &model).unwrap();
+            assert_eq!(bytes.len() % 4, 0);
+
+            // The bytes are the compressed words in little-endian byte order.
+            let mut encoder = DefaultRangeEncoder::new();
+            encoder.encode_iid_symbols(&symbols, &model).unwrap();
+            let words = encoder.into_compressed().unwrap_infallible();
+            assert_eq!(bytes.len(), 4 * words.len());
+            if let Some(&first) = words.first() {
+                assert_eq!(bytes[..4], first.to_le_bytes());
+            }
+
+            let decoded = decode_from_bytes(&bytes, &model, amt).unwrap();
+            assert_eq!(decoded, symbols);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+            0.5, 0.0, 0.5,
+        ])
+        .unwrap();
+        let bytes = encode_to_bytes([0usize, 2, 2], &model).unwrap();
+        assert_eq!(
+            decode_from_bytes(&bytes[..bytes.len() - 1], &model, 3),
+            Err(DecodingError::InvalidLength)
+        );
+
+        let model = DefaultLeakyQuantizer::new(-10..=10).quantize(Gaussian::new(0.0, 3.0));
+        assert_eq!(
+            encode_to_bytes([3, 11], &model),
+            Err(DefaultEncoderFrontendError::ImpossibleSymbol)
+        );
+    }
+}
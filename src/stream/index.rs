@@ -0,0 +1,267 @@
+//! Recording which compressed words each encoded symbol ended up in
+//!
+//! Entropy coders don't assign whole words to individual symbols: a symbol's information
+//! content is first absorbed into the coder's internal state, and the coder writes out a
+//! compressed word whenever its state fills up. An [`IndexingEncoder`] wraps any encoder
+//! that implements [`Pos`] and records, after each encoded symbol, how many compressed
+//! words the wrapped encoder has written so far. The resulting [`SymbolIndex`] tells you
+//! (approximately) which words each symbol's information went into, which is useful for
+//! analyzing where the bits of a finished stream went, and for coarse random access without
+//! having to call [`pos`](Pos::pos) explicitly while encoding.
+//!
+//! The index is approximate since a symbol's information content may still sit in the
+//! coder's internal state when the symbol has been encoded. It gets written out as part of
+//! later words (at the latest when the encoder is sealed), i.e., the words that a
+//! [`SymbolIndex`] reports for a symbol are a lower bound on where its information ends up.
+//! Also, for coders with stack semantics (such as an [`AnsCoder`]), the decoder reads the
+//! words in reverse order.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     index::IndexingEncoder, model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, Encode,
+//! };
+//! use constriction::Pos;
+//! use probability::distribution::Gaussian;
+//!
+//! let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+//! let cheap_model = quantizer.quantize(Gaussian::new(0.0, 0.3));
+//! let expensive_model = quantizer.quantize(Gaussian::new(0.0, 50.0));
+//!
+//! let mut encoder = IndexingEncoder::new(DefaultRangeEncoder::new());
+//! encoder.encode_iid_symbols(&[0; 100], &cheap_model).unwrap();
+//! let expensive_symbols = (0..20).map(|i| (i * 37) % 181 - 90).collect::<Vec<i32>>();
+//! encoder.encode_iid_symbols(&expensive_symbols, &expensive_model).unwrap();
+//! let (encoder, index) = encoder.into_parts();
+//! assert_eq!(index.num_symbols(), 120);
+//!
+//! // The 100 cheap symbols together fill at most one word, whereas the 20 expensive
+//! // symbols fill several words.
+//! assert!(index.word_offsets()[99] <= 1);
+//! assert!(encoder.pos().0 - index.word_offsets()[99] >= 3);
+//!
+//! // The word at position 2 was written while encoding one of the expensive symbols.
+//! assert!(index.flushing_symbol(2).unwrap() >= 100);
+//! ```
+//!
+//! [`AnsCoder`]: super::stack::AnsCoder
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, ops::Range};
+
+use num::cast::AsPrimitive;
+
+use super::{model::EncoderModel, Code, Encode};
+use crate::{CoderError, Pos, PosSeek};
+
+/// Wrapper around an encoder that records how many compressed words had been written
+/// after each encoded symbol.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct IndexingEncoder<Encoder> {
+    inner: Encoder,
+    index: SymbolIndex,
+}
+
+impl<Encoder> IndexingEncoder<Encoder> {
+    /// Wraps `inner`, starting with an empty index.
+    pub fn new(inner: Encoder) -> Self {
+        Self {
+            inner,
+            index: SymbolIndex::new(),
+        }
+    }
+
+    /// Returns the index of all symbols that have been encoded so far.
+    pub fn index(&self) -> &SymbolIndex {
+        &self.index
+    }
+
+    /// Returns a reference to the wrapped encoder.
+    pub fn get_ref(&self) -> &Encoder {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped encoder.
+    ///
+    /// Symbols that are encoded directly on the returned reference are not indexed, but
+    /// the words that they write get attributed to the next indexed symbol.
+    pub fn get_mut(&mut self) -> &mut Encoder {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped encoder.
+    pub fn into_inner(self) -> Encoder {
+        self.inner
+    }
+
+    /// Consumes the wrapper and returns both the wrapped encoder and the index.
+    pub fn into_parts(self) -> (Encoder, SymbolIndex) {
+        (self.inner, self.index)
+    }
+}
+
+impl<Encoder: Code> Code for IndexingEncoder<Encoder> {
+    type Word = Encoder::Word;
+    type State = Encoder::State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Encoder, const PRECISION: usize> Encode<PRECISION> for IndexingEncoder<Encoder>
+where
+    Encoder: Encode<PRECISION> + Pos<Position = (usize, <Encoder as Code>::State)>,
+{
+    type FrontendError = Encoder::FrontendError;
+    type BackendError = Encoder::BackendError;
+
+    /// Encodes a symbol with the wrapped encoder and, on success, records the number of
+    /// compressed words that the wrapped encoder has written so far.
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.inner.encode_symbol(symbol, model)?;
+        self.index.word_offsets.push(self.inner.pos().0);
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.inner.maybe_full()
+    }
+}
+
+impl<Encoder: PosSeek> PosSeek for IndexingEncoder<Encoder> {
+    type Position = Encoder::Position;
+}
+
+impl<Encoder: Pos> Pos for IndexingEncoder<Encoder> {
+    #[inline(always)]
+    fn pos(&self) -> Self::Position {
+        self.inner.pos()
+    }
+}
+
+/// Number of compressed words written after each symbol, as recorded by an
+/// [`IndexingEncoder`].
+///
+/// Symbols are numbered in the order in which they were encoded, starting at zero.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SymbolIndex {
+    /// The number of words that had been written after encoding each symbol
+    /// (nondecreasing).
+    word_offsets: Vec<usize>,
+}
+
+impl SymbolIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn num_symbols(&self) -> usize {
+        self.word_offsets.len()
+    }
+
+    /// Returns, for each symbol, the number of compressed words that had been written right
+    /// after encoding it.
+    pub fn word_offsets(&self) -> &[usize] {
+        &self.word_offsets
+    }
+
+    /// Returns the range of words that the encoder wrote while encoding the symbol with
+    /// number `symbol` (which is empty if encoding the symbol didn't fill up a word).
+    ///
+    /// # Panics
+    ///
+    /// If `symbol >= self.num_symbols()`.
+    pub fn words_flushed_by(&self, symbol: usize) -> Range<usize> {
+        let start = match symbol {
+            0 => 0,
+            _ => self.word_offsets[symbol - 1],
+        };
+        start..self.word_offsets[symbol]
+    }
+
+    /// Returns the number of the symbol during whose encoding the word at position `word`
+    /// was written, or `None` if the encoder hadn't written that word yet when it encoded
+    /// the last indexed symbol.
+    pub fn flushing_symbol(&self, word: usize) -> Option<usize> {
+        let symbol = self.word_offsets.partition_point(|&offset| offset <= word);
+        if symbol < self.word_offsets.len() {
+            Some(symbol)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::DefaultLeakyQuantizer,
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+        Decode,
+    };
+    use super::*;
+
+    use alloc::vec::Vec;
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn index() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 30.0));
+        let symbols = (0..500).map(|i| (i * 37) % 201 - 100).collect::<Vec<i32>>();
+
+        let mut encoder = IndexingEncoder::new(DefaultRangeEncoder::new());
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        // Failed encoding operations aren't indexed.
+        assert!(encoder.encode_symbol(1000, model).is_err());
+        let (encoder, index) = encoder.into_parts();
+
+        assert_eq!(index.num_symbols(), symbols.len());
+        let offsets = index.word_offsets();
+        assert!(offsets.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(*offsets.last().unwrap(), encoder.pos().0);
+
+        // The ranges returned by `words_flushed_by` partition all written words.
+        let mut expected_start = 0;
+        for symbol in 0..symbols.len() {
+            let words = index.words_flushed_by(symbol);
+            assert_eq!(words.start, expected_start);
+            for word in words.clone() {
+                assert_eq!(index.flushing_symbol(word), Some(symbol));
+            }
+            expected_start = words.end;
+        }
+        assert_eq!(index.flushing_symbol(expected_start), None);
+
+        // Indexing doesn't change the compressed data.
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+
+        let mut ans = IndexingEncoder::new(DefaultAnsCoder::new());
+        ans.encode_iid_symbols(symbols.iter().rev(), &model)
+            .unwrap();
+        let (ans, index) = ans.into_parts();
+        assert_eq!(*index.word_offsets().last().unwrap(), ans.pos().0);
+    }
+}
@@ -0,0 +1,297 @@
+//! Bounding the number of symbols that can be decoded from untrusted data
+//!
+//! Decoders in `constriction` don't know how many symbols are encoded in a bit string;
+//! it's up to the caller to decide when to stop decoding. If the number of symbols is read
+//! from the compressed data itself (e.g., from a header), then a malicious input could
+//! claim an enormous number of symbols and make a service spend unbounded time and memory
+//! on decoding them (a so-called "decompression bomb"). This is especially relevant for
+//! [`AnsCoder`]s since they are surjective: they happily keep decoding symbols even after
+//! they've run out of compressed data (which is what makes bits-back coding work), so
+//! running out of data won't stop a runaway decoding loop either.
+//!
+//! A [`LimitedDecoder`] wraps any decoder and fails with [`DecodeLimitError::LimitExceeded`]
+//! once a given number of symbols has been decoded. Call [`set_decode_limit`] once to
+//! bound the total number of symbols decoded by the coder, or call it before each batch of
+//! decoding calls to bound each batch individually.
+//!
+//! The limit counts symbols, not compressed words. There's no separate budget for words
+//! because the stream coders in `constriction` read at most one compressed word per
+//! decoded symbol (since `PRECISION` never exceeds the word size), so a limit of `n`
+//! symbols also bounds the number of words that the wrapped decoder can consume to `n`
+//! (on top of the words that it already read when it was constructed).
+//! If you need a tighter bound on words, e.g., because the backend reads from a slow or
+//! unbounded source, then limit the backend itself (for example by decoding from a
+//! [`Cursor`] over a buffer of bounded size).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::{
+//!     stream::{
+//!         limit::{DecodeLimitError, LimitedDecoder},
+//!         model::DefaultLeakyQuantizer,
+//!         stack::DefaultAnsCoder,
+//!         Decode,
+//!     },
+//!     CoderError,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+//!
+//! // Pretend that we received some data from an untrusted source, and that its header
+//! // claims that the data contains 10^12 symbols.
+//! let untrusted_data = vec![0x1234_5678u32, 0x9ABC_DEF0];
+//! let claimed_len = 1_000_000_000_000usize;
+//!
+//! let ans = DefaultAnsCoder::from_compressed(untrusted_data).unwrap();
+//! let mut decoder = LimitedDecoder::new(ans, 1000);
+//! let result = decoder
+//!     .decode_iid_symbols(claimed_len, &model)
+//!     .collect::<Result<Vec<_>, _>>();
+//! assert_eq!(result, Err(CoderError::Frontend(DecodeLimitError::LimitExceeded)));
+//! assert_eq!(decoder.remaining(), Some(0));
+//! ```
+//!
+//! [`AnsCoder`]: super::stack::AnsCoder
+//! [`set_decode_limit`]: LimitedDecoder::set_decode_limit
+//! [`Cursor`]: crate::backends::Cursor
+
+use core::fmt::{Debug, Display};
+
+use num::cast::AsPrimitive;
+
+use super::{model::DecoderModel, Code, Decode};
+use crate::{CoderError, Pos, PosSeek, Seek};
+
+/// Wrapper around a decoder that limits the number of symbols that can be decoded.
+///
+/// This also bounds the number of compressed words that the wrapped decoder can consume,
+/// see [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct LimitedDecoder<Decoder> {
+    inner: Decoder,
+    remaining: Option<usize>,
+}
+
+impl<Decoder> LimitedDecoder<Decoder> {
+    /// Wraps `inner` so that at most `max_symbols` symbols can be decoded from it.
+    pub fn new(inner: Decoder, max_symbols: usize) -> Self {
+        Self {
+            inner,
+            remaining: Some(max_symbols),
+        }
+    }
+
+    /// Sets the number of symbols that may still be decoded from now on.
+    ///
+    /// This replaces any previous limit (it does not add to it), regardless of how many
+    /// symbols were decoded so far. Set `max_symbols` to `None` to lift the limit.
+    pub fn set_decode_limit(&mut self, max_symbols: Option<usize>) {
+        self.remaining = max_symbols;
+    }
+
+    /// Returns the number of symbols that may still be decoded, or `None` if there's no
+    /// limit.
+    pub fn remaining(&self) -> Option<usize> {
+        self.remaining
+    }
+
+    /// Returns a reference to the wrapped decoder.
+    pub fn get_ref(&self) -> &Decoder {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped decoder.
+    ///
+    /// Decoding directly from the returned reference bypasses the limit.
+    pub fn get_mut(&mut self) -> &mut Decoder {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped decoder.
+    pub fn into_inner(self) -> Decoder {
+        self.inner
+    }
+}
+
+impl<Decoder: Code> Code for LimitedDecoder<Decoder> {
+    type Word = Decoder::Word;
+    type State = Decoder::State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Decoder, const PRECISION: usize> Decode<PRECISION> for LimitedDecoder<Decoder>
+where
+    Decoder: Decode<PRECISION>,
+{
+    type FrontendError = DecodeLimitError<Decoder::FrontendError>;
+    type BackendError = Decoder::BackendError;
+
+    /// Decodes a symbol from the wrapped decoder unless the limit has been reached.
+    ///
+    /// Only successfully decoded symbols count towards the limit.
+    fn decode_symbol<D>(
+        &mut self,
+        model: D,
+    ) -> Result<D::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<D::Probability>,
+    {
+        if self.remaining == Some(0) {
+            return Err(CoderError::Frontend(DecodeLimitError::LimitExceeded));
+        }
+
+        let symbol = self
+            .inner
+            .decode_symbol(model)
+            .map_err(|err| err.map_frontend(DecodeLimitError::Inner))?;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= 1;
+        }
+        Ok(symbol)
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.inner.maybe_exhausted()
+    }
+}
+
+impl<Decoder: PosSeek> PosSeek for LimitedDecoder<Decoder> {
+    type Position = Decoder::Position;
+}
+
+impl<Decoder: Pos> Pos for LimitedDecoder<Decoder> {
+    #[inline(always)]
+    fn pos(&self) -> Self::Position {
+        self.inner.pos()
+    }
+}
+
+impl<Decoder: Seek> Seek for LimitedDecoder<Decoder> {
+    /// Seeks the wrapped decoder. This does not reset the limit.
+    #[inline(always)]
+    fn seek(&mut self, pos: Self::Position) -> Result<(), ()> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Frontend error type of a [`LimitedDecoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeLimitError<FrontendError> {
+    /// Decoding another symbol would exceed the limit set with
+    /// [`LimitedDecoder::new`] or [`LimitedDecoder::set_decode_limit`].
+    LimitExceeded,
+
+    /// The wrapped decoder returned a frontend error.
+    Inner(FrontendError),
+}
+
+impl<FrontendError: Display> Display for DecodeLimitError<FrontendError> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::LimitExceeded => write!(f, "Exceeded the maximum number of decoded symbols."),
+            Self::Inner(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<FrontendError: std::error::Error + 'static> std::error::Error
+    for DecodeLimitError<FrontendError>
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::LimitExceeded => None,
+            Self::Inner(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::DefaultLeakyQuantizer,
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+        Encode,
+    };
+    use super::*;
+
+    use alloc::vec::Vec;
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn limits() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        let symbols = (0..50).map(|i| (i * 7) % 201 - 100).collect::<Vec<i32>>();
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let mut decoder = LimitedDecoder::new(ans, 30);
+        let decoded = decoder
+            .decode_iid_symbols(30, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[..30]);
+        assert_eq!(
+            decoder.decode_symbol(&model),
+            Err(CoderError::Frontend(DecodeLimitError::LimitExceeded))
+        );
+
+        // Resetting the limit allows decoding the next batch.
+        decoder.set_decode_limit(Some(20));
+        let decoded = decoder
+            .decode_iid_symbols(20, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[30..]);
+        assert!(decoder.get_ref().is_empty());
+
+        // Without a limit, an `AnsCoder` keeps decoding even after it ran out of data.
+        decoder.set_decode_limit(None);
+        for _ in 0..1000 {
+            decoder.decode_symbol(&model).unwrap();
+        }
+        assert_eq!(decoder.remaining(), None);
+    }
+
+    #[test]
+    fn seek() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        let symbols = (0..50).map(|i| (i * 11) % 201 - 100).collect::<Vec<i32>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols[..25], &model).unwrap();
+        let checkpoint = encoder.pos();
+        encoder.encode_iid_symbols(&symbols[25..], &model).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+
+        let range_decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+        let mut decoder = LimitedDecoder::new(range_decoder, 30);
+        decoder.seek(checkpoint).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(25, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[25..]);
+        assert_eq!(decoder.remaining(), Some(5));
+
+        // Seeking doesn't reset the limit.
+        decoder.seek(checkpoint).unwrap();
+        let result = decoder
+            .decode_iid_symbols(25, &model)
+            .collect::<Result<Vec<_>, _>>();
+        assert!(matches!(
+            result,
+            Err(CoderError::Frontend(DecodeLimitError::LimitExceeded))
+        ));
+    }
+}
@@ -0,0 +1,420 @@
+//! Entropy models for first-order Markov sources
+//!
+//! Many sources are well described by a first-order Markov chain, i.e., the distribution of
+//! each symbol depends only on the symbol that immediately precedes it (e.g., runs of
+//! pixels in a binary image, or the states of a slowly changing categorical signal). This
+//! module provides the [`MarkovModel`], which stores a distribution over the first symbol
+//! and a matrix of transition probabilities, and which encodes and decodes entire sequences
+//! of symbols while keeping track of the preceding symbol for you.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{markov::DefaultMarkovModel, stack::DefaultAnsCoder};
+//!
+//! // A "sticky" source over the alphabet {0, 1, 2} that tends to repeat the last symbol.
+//! let initial_probabilities = [0.5, 0.25, 0.25];
+//! let transition_probabilities = [
+//!     0.8, 0.1, 0.1, // Row 0: distribution of a symbol that follows a `0`.
+//!     0.1, 0.8, 0.1, // Row 1: distribution of a symbol that follows a `1`.
+//!     0.1, 0.1, 0.8, // Row 2: distribution of a symbol that follows a `2`.
+//! ];
+//! let markov_model =
+//!     DefaultMarkovModel::new(&initial_probabilities, &transition_probabilities).unwrap();
+//!
+//! let symbols = [0, 0, 0, 2, 2, 2, 2, 1, 1, 0, 0, 0];
+//! let mut coder = DefaultAnsCoder::new();
+//! markov_model.encode_symbols_reverse(&mut coder, &symbols).unwrap();
+//!
+//! let decoded = markov_model.decode_symbols(&mut coder, symbols.len()).unwrap();
+//! assert_eq!(decoded, symbols);
+//! assert!(coder.is_empty());
+//! ```
+
+use alloc::vec::Vec;
+
+use num::cast::AsPrimitive;
+
+use super::{model::ContiguousCategoricalEntropyModel, Decode, Encode};
+use crate::{BitArray, CoderError};
+
+/// A first-order Markov chain over the alphabet `0..alphabet_size`.
+///
+/// The first symbol of a sequence is modeled by an initial distribution, and each
+/// subsequent symbol is modeled by the row of a transition matrix that corresponds to the
+/// preceding symbol. Both the initial distribution and all rows of the transition matrix
+/// are approximated in fixed point arithmetic with `PRECISION` bits when the
+/// `MarkovModel` is constructed, so that encoding and decoding don't have to build any
+/// models on the fly.
+///
+/// See [module level documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct MarkovModel<Probability: BitArray, const PRECISION: usize> {
+    initial_model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+    transition_models:
+        Vec<ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>>,
+}
+
+/// Type alias for a [`MarkovModel`] with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`] or a [`DefaultRangeEncoder`] /
+/// [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: super::stack::DefaultAnsCoder
+/// [`DefaultRangeEncoder`]: super::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: super::queue::DefaultRangeDecoder
+pub type DefaultMarkovModel = MarkovModel<u32, 24>;
+
+/// Type alias for a [`MarkovModel`] optimized for compatibility with lookup decoder
+/// models.
+///
+/// Can be used with a [`SmallAnsCoder`] or a [`SmallRangeEncoder`] /
+/// [`SmallRangeDecoder`].
+///
+/// [`SmallAnsCoder`]: super::stack::SmallAnsCoder
+/// [`SmallRangeEncoder`]: super::queue::SmallRangeEncoder
+/// [`SmallRangeDecoder`]: super::queue::SmallRangeDecoder
+pub type SmallMarkovModel = MarkovModel<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> MarkovModel<Probability, PRECISION> {
+    /// Creates a `MarkovModel` from the distribution of the first symbol and a transition
+    /// matrix.
+    ///
+    /// The alphabet size is `initial_probabilities.len()`, which must be at least two. The
+    /// `transition_probabilities` are a square matrix in row-major order, i.e., the
+    /// probability that symbol `j` follows symbol `i` is
+    /// `transition_probabilities[i * alphabet_size + j]`. All probabilities must be
+    /// nonnegative and finite, but neither the initial distribution nor the rows of the
+    /// transition matrix need to be normalized. As with
+    /// [`ContiguousCategoricalEntropyModel::from_floating_point_probabilities`], each
+    /// symbol gets at least the smallest representable nonzero probability, even if its
+    /// provided probability is zero.
+    ///
+    /// Returns an error if `transition_probabilities` doesn't have length
+    /// `alphabet_size * alphabet_size` or if any distribution is invalid.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(initial_probabilities: &[f64], transition_probabilities: &[f64]) -> Result<Self, ()>
+    where
+        Probability: Into<f64> + AsPrimitive<usize>,
+        f64: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+    {
+        let alphabet_size = initial_probabilities.len();
+        if alphabet_size < 2 || transition_probabilities.len() != alphabet_size * alphabet_size {
+            return Err(());
+        }
+
+        let initial_model = ContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+            initial_probabilities,
+        )?;
+        let transition_models = transition_probabilities
+            .chunks_exact(alphabet_size)
+            .map(ContiguousCategoricalEntropyModel::from_floating_point_probabilities)
+            .collect::<Result<Vec<_>, ()>>()?;
+
+        Ok(Self {
+            initial_model,
+            transition_models,
+        })
+    }
+
+    /// Returns the size of the alphabet, i.e., the number of distinct symbols.
+    pub fn alphabet_size(&self) -> usize {
+        self.transition_models.len()
+    }
+
+    /// Returns the entropy model for the first symbol of a sequence.
+    pub fn initial_model(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.initial_model
+    }
+
+    /// Returns the entropy model for a symbol that follows the symbol `previous`.
+    ///
+    /// # Panics
+    ///
+    /// If `previous` is not smaller than the alphabet size.
+    pub fn transition_model(
+        &self,
+        previous: usize,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.transition_models[previous]
+    }
+
+    /// Returns the entropy model for a symbol that follows `previous`, or the
+    /// [`initial_model`](Self::initial_model) if `previous` is `None`.
+    ///
+    /// Use this method if you want to interleave a Markov chain with other data on the
+    /// same coder, or if you want to encode or decode a sequence in several pieces.
+    ///
+    /// # Panics
+    ///
+    /// If `previous` is `Some(i)` where `i` is not smaller than the alphabet size.
+    pub fn model(
+        &self,
+        previous: Option<usize>,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        match previous {
+            None => &self.initial_model,
+            Some(previous) => &self.transition_models[previous],
+        }
+    }
+
+    /// Encodes the sequence `symbols`, starting from the initial distribution.
+    ///
+    /// Use this method with coders that have queue semantics (e.g., a [`RangeEncoder`]).
+    /// For coders with stack semantics, use [`encode_symbols_reverse`] instead.
+    ///
+    /// If any of the `symbols` is not smaller than the alphabet size, then the `encoder`
+    /// returns its error for impossible symbols (e.g.,
+    /// [`DefaultEncoderFrontendError::ImpossibleSymbol`]) and is left unchanged.
+    ///
+    /// [`RangeEncoder`]: super::queue::RangeEncoder
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    /// [`DefaultEncoderFrontendError::ImpossibleSymbol`]:
+    ///     crate::DefaultEncoderFrontendError::ImpossibleSymbol
+    pub fn encode_symbols<E>(
+        &self,
+        encoder: &mut E,
+        symbols: &[usize],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        self.check_symbols(encoder, symbols)?;
+        let previous = core::iter::once(None).chain(symbols.iter().copied().map(Some));
+        encoder.encode_symbols(
+            symbols
+                .iter()
+                .zip(previous)
+                .map(|(symbol, previous)| (symbol, self.model(previous))),
+        )
+    }
+
+    /// Same as [`encode_symbols`] but for coders with stack semantics (e.g., an
+    /// [`AnsCoder`]).
+    ///
+    /// Encodes the `symbols` in reverse order so that [`decode_symbols`] decodes them in
+    /// their original order.
+    ///
+    /// If any of the `symbols` is not smaller than the alphabet size, then the `encoder`
+    /// returns its error for impossible symbols (e.g.,
+    /// [`DefaultEncoderFrontendError::ImpossibleSymbol`]) and is left unchanged.
+    ///
+    /// [`encode_symbols`]: Self::encode_symbols
+    /// [`decode_symbols`]: Self::decode_symbols
+    /// [`AnsCoder`]: super::stack::AnsCoder
+    /// [`DefaultEncoderFrontendError::ImpossibleSymbol`]:
+    ///     crate::DefaultEncoderFrontendError::ImpossibleSymbol
+    pub fn encode_symbols_reverse<E>(
+        &self,
+        encoder: &mut E,
+        symbols: &[usize],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        self.check_symbols(encoder, symbols)?;
+        encoder.encode_symbols(symbols.iter().enumerate().rev().map(|(i, symbol)| {
+            let previous = i.checked_sub(1).map(|i| symbols[i]);
+            (symbol, self.model(previous))
+        }))
+    }
+
+    /// Decodes a sequence of `amt` symbols, starting from the initial distribution.
+    ///
+    /// Works both for data encoded with [`encode_symbols`] on a coder with queue semantics
+    /// and for data encoded with [`encode_symbols_reverse`] on a coder with stack semantics.
+    ///
+    /// [`encode_symbols`]: Self::encode_symbols
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    pub fn decode_symbols<D>(
+        &self,
+        decoder: &mut D,
+        amt: usize,
+    ) -> Result<Vec<usize>, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        Probability: Into<D::Word>,
+        D::Word: AsPrimitive<Probability>,
+    {
+        let mut previous = None;
+        (0..amt)
+            .map(|_| {
+                let symbol = decoder.decode_symbol(self.model(previous))?;
+                previous = Some(symbol);
+                Ok(symbol)
+            })
+            .collect()
+    }
+
+    /// Returns the `encoder`'s error for impossible symbols if any of the `symbols` is not
+    /// smaller than the alphabet size, without changing the `encoder`.
+    fn check_symbols<E>(
+        &self,
+        encoder: &mut E,
+        symbols: &[usize],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        match symbols
+            .iter()
+            .find(|&&symbol| symbol >= self.alphabet_size())
+        {
+            // No model has any probability mass for `symbol`, so this fails.
+            Some(&symbol) => encoder.encode_symbol(symbol, &self.initial_model),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            model::EncoderModel,
+            queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+            stack::DefaultAnsCoder,
+        },
+        *,
+    };
+    use crate::DefaultEncoderFrontendError;
+
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    fn invalid_construction() {
+        let transitions = [0.5, 0.5, 0.25, 0.75];
+        assert!(DefaultMarkovModel::new(&[0.5, 0.5], &transitions).is_ok());
+        assert!(DefaultMarkovModel::new(&[1.0], &[1.0]).is_err());
+        assert!(DefaultMarkovModel::new(&[0.5, 0.5], &transitions[..3]).is_err());
+        assert!(DefaultMarkovModel::new(&[0.5, 0.5], &[0.5, 0.5, -1.0, 1.0]).is_err());
+        assert!(DefaultMarkovModel::new(&[0.5, f64::NAN], &transitions).is_err());
+    }
+
+    /// Samples a sequence from a sticky Markov chain over `alphabet_size` symbols.
+    fn sticky_chain(alphabet_size: usize, amt: usize, seed: u64) -> Vec<usize> {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(seed);
+        let mut symbol = 0;
+        (0..amt)
+            .map(|_| {
+                if rng.next_u32() % 10 == 0 {
+                    symbol = (rng.next_u32() as usize) % alphabet_size;
+                }
+                symbol
+            })
+            .collect()
+    }
+
+    fn sticky_model(alphabet_size: usize) -> DefaultMarkovModel {
+        let stay = 0.9 + 0.1 / alphabet_size as f64;
+        let switch = 0.1 / alphabet_size as f64;
+        let transitions = (0..alphabet_size * alphabet_size)
+            .map(|i| {
+                if i / alphabet_size == i % alphabet_size {
+                    stay
+                } else {
+                    switch
+                }
+            })
+            .collect::<Vec<_>>();
+        DefaultMarkovModel::new(&alloc::vec![1.0; alphabet_size], &transitions).unwrap()
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn queue_roundtrip() {
+        let symbols = sticky_chain(5, 1000, 762);
+        let markov_model = sticky_model(5);
+
+        let mut encoder = DefaultRangeEncoder::new();
+        markov_model.encode_symbols(&mut encoder, &symbols).unwrap();
+
+        // The bit rate is close to the information content under the model.
+        let information_content = symbols
+            .iter()
+            .enumerate()
+            .map(|(i, &symbol)| {
+                let previous = i.checked_sub(1).map(|i| symbols[i]);
+                let (_, probability) = markov_model
+                    .model(previous)
+                    .left_cumulative_and_probability(symbol)
+                    .unwrap();
+                24.0 - (probability.get() as f64).log2()
+            })
+            .sum::<f64>();
+        let num_bits = encoder.num_bits() as f64;
+        assert!(num_bits >= information_content);
+        assert!(num_bits < information_content + 64.0);
+
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        let decoded = markov_model
+            .decode_symbols(&mut decoder, symbols.len())
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn stack_roundtrip() {
+        let symbols = sticky_chain(3, 1000, 7620);
+        let markov_model = sticky_model(3);
+
+        let mut coder = DefaultAnsCoder::new();
+        markov_model
+            .encode_symbols_reverse(&mut coder, &symbols)
+            .unwrap();
+
+        // Decoding symbol by symbol with explicit models is equivalent.
+        let mut coder_clone = coder.clone();
+        let mut previous = None;
+        for &symbol in &symbols {
+            let decoded = coder_clone
+                .decode_symbol(markov_model.model(previous))
+                .unwrap();
+            assert_eq!(decoded, symbol);
+            previous = Some(decoded);
+        }
+        assert!(coder_clone.is_empty());
+
+        let decoded = markov_model
+            .decode_symbols(&mut coder, symbols.len())
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(coder.is_empty());
+    }
+
+    #[test]
+    fn invalid_symbol() {
+        let error = Err(DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error());
+        let model = sticky_model(3);
+
+        let mut coder = DefaultAnsCoder::new();
+        model.encode_symbols_reverse(&mut coder, &[2, 1]).unwrap();
+        let compressed = coder.clone().into_compressed().unwrap();
+        assert_eq!(
+            model.encode_symbols_reverse(&mut coder, &[0, 1, 3, 2]),
+            error
+        );
+        assert_eq!(coder.into_compressed().unwrap(), compressed);
+
+        let mut encoder = DefaultRangeEncoder::new();
+        model.encode_symbols(&mut encoder, &[2, 1]).unwrap();
+        let num_bits = encoder.num_bits();
+        assert_eq!(model.encode_symbols(&mut encoder, &[0, 1, 2, 7]), error);
+        assert_eq!(encoder.num_bits(), num_bits);
+    }
+}
@@ -23,15 +23,67 @@
 //!   effectiveness of stream codes with the locality of symbol codes; it is meant for
 //!   experimental new compression approaches that perform joint inference, quantization,
 //!   and bits-back coding in an end-to-end optimization. See submodule [`chain`].
+//! - **Table-driven ANS (tANS):** a variant of ANS for very fast coding of i.i.d. symbols
+//!   at low precision. See submodule [`tans`].
+//! - **Binary arithmetic coding:** an adaptive binary arithmetic coder for
+//!   video-codec-style pipelines, compatible with CABAC. See submodule [`binary`].
 //!
-//! All of these stream codes are provided through types that implement the [`Encode`] and
-//! [`Decode`] traits defined in this module.
+//! The stack, queue, and chain coders are provided through types that implement the
+//! [`Encode`] and [`Decode`] traits defined in this module.
+//!
+//! If you just want to compress a sequence of i.i.d. symbols into bytes and back, the
+//! submodule [`easy`] wraps a range coder in two functions and hides all other details.
 //!
 //! ## Provided Utilities for Entropy Models
 //!
 //! To encode or decode a sequence of symbols with one of the above stream codes, you have
-//! to specify an [`EntropyModel`] for each symbol. The submodule [`model`] provides
-//! utilities for defining `EntropyModel`s.
+//! to specify an [`EntropyModel`] for each symbol. The following submodules help with
+//! that:
+//!
+//! - [`model`]: common entropy models and utilities for defining your own ones.
+//! - [`context`]: selects a model per block of data and encodes the selection on the same
+//!   stream; also provides models that predict each symbol from up to `N` preceding
+//!   symbols.
+//! - [`adaptive`]: models that learn the statistics of the data while coding.
+//! - [`markov`]: codes sequences whose symbols depend on the respective preceding symbol.
+//!
+//! ## Provided Utilities for Coders
+//!
+//! The following submodules wrap or extend the above coders.
+//!
+//! **Decoding untrusted data:**
+//!
+//! - [`limit`]: bounds the number of symbols that a decoder may produce.
+//! - [`strict`]: makes a decoder fail instead of silently decoding past the end of its
+//!   data.
+//! - [`checksum`]: appends a checksum to compressed data to detect corruption.
+//!
+//! **Structuring compressed data:**
+//!
+//! - [`terminated`]: appends the number of encoded symbols to the output of a range coder
+//!   so that the decoder knows exactly where a variable-length message ends.
+//! - [`shard`]: cuts one long stream into independently decodable shards, e.g., for
+//!   several workers or storage blocks.
+//! - [`checkpoint`]: jumps to named checkpoints (e.g., "frame 1234") rather than to raw
+//!   positions, see [`checkpoint::TaggedCoder`].
+//! - [`transcode`]: re-encodes compressed data for a different coder or model
+//!   configuration in a single streaming pass, see [`transcode::transcode`].
+//!
+//! **Debugging and analysis:**
+//!
+//! - [`trace`]: records an encoding session in a [`trace::Trace`] that you can replay
+//!   against any coder configuration.
+//! - [`accounting`]: splits up the bit rate between several logical streams that share a
+//!   coder, see [`accounting::AccountingEncoder`].
+//! - [`index`]: finds out which compressed words each symbol went into, see
+//!   [`index::IndexingEncoder`].
+//!
+//! **Performance and type safety:**
+//!
+//! - [`session`]: reuses a buffer of symbols across batches in a tight loop, see
+//!   [`session::DecodeSession`] and [`session::EncodeSession`].
+//! - [`precision`]: makes it a compile error to use an entropy model with the wrong fixed
+//!   point precision on a coder, see [`precision::FixedPrecision`].
 //!
 //! # Examples
 //!
@@ -213,6 +265,15 @@
 //!     [`SmallContiguousCategoricalEntropyModel`],
 //!     [`SmallNonContiguousCategoricalEncoderModel`],
 //!     [`SmallNonContiguousCategoricalDecoderModel`], and [`SmallLeakyQuantizer`].
+//! - **"Wide" presets** ([`WideRangeEncoder`] and [`WideRangeDecoder`]) use 64&nbsp;bit
+//!   words and a 128&nbsp;bit state. They support entropy models with a numerical
+//!   precision of more than 32&nbsp;bits, and they renormalize less often on 64&nbsp;bit
+//!   hardware.
+//! - **"Byte" presets** ([`ByteRangeEncoder`] and [`ByteRangeDecoder`]) use 8&nbsp;bit
+//!   words, so the compressed data is a plain byte stream that can be embedded in
+//!   byte-oriented container formats. They only support entropy models with a numerical
+//!   precision of at most 8&nbsp;bits. Their state has 32&nbsp;bits, or 64&nbsp;bits for
+//!   [`ByteRangeEncoder64`] and [`ByteRangeDecoder64`].
 //!
 //! You'll usually want to use matching presets for entropy *coders* and entropy *models*.
 //! However, it is legal to use entropy models with the "small" preset for an entropy coder
@@ -289,6 +350,12 @@
 //! [`SmallRangeEncoder`]: queue::SmallRangeEncoder
 //! [`SmallRangeDecoder`]: queue::SmallRangeDecoder
 //! [`SmallChainCoder`]: chain::SmallChainCoder
+//! [`WideRangeEncoder`]: queue::WideRangeEncoder
+//! [`WideRangeDecoder`]: queue::WideRangeDecoder
+//! [`ByteRangeEncoder`]: queue::ByteRangeEncoder
+//! [`ByteRangeEncoder64`]: queue::ByteRangeEncoder64
+//! [`ByteRangeDecoder64`]: queue::ByteRangeDecoder64
+//! [`ByteRangeDecoder`]: queue::ByteRangeDecoder
 //! [`SmallLeakyQuantizer`]: model::SmallLeakyQuantizer
 //! [`SmallContiguousLookupDecoderModel`]: model::SmallContiguousLookupDecoderModel
 //! [`SmallNonContiguousLookupDecoderModel`]: model::SmallNonContiguousLookupDecoderModel
@@ -306,17 +373,38 @@
 
 #![allow(clippy::type_complexity)]
 
+pub mod accounting;
+pub mod adaptive;
+pub mod binary;
 pub mod chain;
+pub mod checkpoint;
+pub mod checksum;
+pub mod context;
+pub mod easy;
+pub mod index;
+pub mod limit;
+pub mod markov;
 pub mod model;
+pub mod precision;
 pub mod queue;
+mod renorm;
+pub mod session;
+pub mod shard;
 pub mod stack;
+pub mod strict;
+pub mod tans;
+pub mod terminated;
+pub mod trace;
+pub mod transcode;
 
 use core::{
     borrow::Borrow,
     fmt::{Debug, Display},
 };
 
-use crate::{BitArray, CoderError};
+use alloc::vec::Vec;
+
+use crate::{BitArray, CoderError, SymbolAlphabet};
 use model::{DecoderModel, EncoderModel, EntropyModel};
 use num::cast::AsPrimitive;
 
@@ -630,6 +718,77 @@ pub trait Encode<const PRECISION: usize>: Code {
         Ok(())
     }
 
+    /// Encodes a sequence of symbols after checking that they can all be encoded.
+    ///
+    /// This method is equivalent to [`encode_symbols`](Self::encode_symbols), except that it
+    /// first iterates over all `symbols_and_models` to check that each symbol has a nonzero
+    /// probability under its entropy model. Only if this is the case does it iterate a
+    /// second time and encode the symbols. Thus, if some symbols can't be encoded, then this
+    /// method returns `Err(TryCodingError::InvalidEntropyModel(e))` without having written
+    /// any compressed data, and `e` reports the positions (in the order of iteration) of
+    /// the offending symbols. By contrast, `encode_symbols` fails only once it reaches the
+    /// first offending symbol, after having already encoded all preceding symbols.
+    ///
+    /// Encoding may still fail with `Err(TryCodingError::CodingError(e))` for reasons other
+    /// than an invalid symbol (e.g., a backend error). If you use an encoder with "stack"
+    /// semantics, such as an [`AnsCoder`](stack::AnsCoder), then pass in a reversed iterator
+    /// (the reported positions then count from the end of your original sequence).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, Encode, TryCodingError,
+    /// };
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-10..=10);
+    /// let model = quantizer.quantize(probability::distribution::Gaussian::new(0.0, 3.0));
+    /// let symbols = [2, -4, 15, 0, -23, 7];
+    ///
+    /// let mut encoder = DefaultRangeEncoder::new();
+    /// let err = encoder
+    ///     .encode_symbols_validated(symbols.iter().map(|symbol| (symbol, model)))
+    ///     .unwrap_err();
+    /// match err {
+    ///     TryCodingError::InvalidEntropyModel(err) => {
+    ///         assert_eq!(err.indices(), &[2, 4]);
+    ///         assert_eq!(err.num_invalid(), 2);
+    ///     }
+    ///     TryCodingError::CodingError(_) => unreachable!(),
+    /// }
+    /// assert!(encoder.is_empty()); // Nothing has been encoded.
+    /// ```
+    #[inline]
+    fn encode_symbols_validated<S, M, I>(
+        &mut self,
+        symbols_and_models: I,
+    ) -> Result<
+        (),
+        TryCodingError<CoderError<Self::FrontendError, Self::BackendError>, InvalidSymbolsError>,
+    >
+    where
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = (S, M)>,
+        I::IntoIter: Clone,
+    {
+        let symbols_and_models = symbols_and_models.into_iter();
+
+        let mut invalid = InvalidSymbolsError::new();
+        for (index, (symbol, model)) in symbols_and_models.clone().enumerate() {
+            if model.left_cumulative_and_probability(symbol).is_none() {
+                invalid.record(index);
+            }
+        }
+        invalid
+            .into_result()
+            .map_err(TryCodingError::InvalidEntropyModel)?;
+
+        Ok(self.encode_symbols(symbols_and_models)?)
+    }
+
     /// Encodes a sequence of symbols, all with the same entropy model.
     ///
     /// This method short-circuits as soon as encoding leads to an error (see discussion of
@@ -673,6 +832,59 @@ pub trait Encode<const PRECISION: usize>: Code {
         self.encode_symbols(symbols.into_iter().map(|symbol| (symbol, model)))
     }
 
+    /// Encodes a value of a type that implements [`SymbolAlphabet`] (e.g., an enum with
+    /// `#[derive(SymbolAlphabet)]`) with an entropy model over its indices.
+    ///
+    /// This is equivalent to `self.encode_symbol(symbol.to_index(), model)`. The `model`
+    /// should assign a nonzero probability to all indices in `0..A::NUM_SYMBOLS`, e.g., a
+    /// [`ContiguousCategoricalEntropyModel`] with `A::NUM_SYMBOLS` entries. See
+    /// [`Decode::decode_enum`] for the inverse operation.
+    ///
+    /// [`ContiguousCategoricalEntropyModel`]: model::ContiguousCategoricalEntropyModel
+    #[inline(always)]
+    fn encode_enum<A, M>(
+        &mut self,
+        symbol: A,
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        A: SymbolAlphabet,
+        M: EncoderModel<PRECISION, Symbol = usize>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.encode_symbol(symbol.to_index(), model)
+    }
+
+    /// Encodes a sequence of bits with a binary entropy model, e.g., a [`Bernoulli`] model.
+    ///
+    /// This is a convenience wrapper that just forwards to [`encode_iid_symbols`]; it
+    /// restricts the symbol type to `bool` but it doesn't take a separate code path, so it
+    /// is exactly as fast as calling `encode_iid_symbols` directly. Any speedup over
+    /// categorical models comes from the [`Bernoulli`] model, which takes only a single
+    /// comparison per bit in addition to the update of the coder's state. As with
+    /// `encode_iid_symbols`, the bits are encoded in the order in which `bits` yields them,
+    /// so you'll want to reverse them for an encoder with stack semantics (like an
+    /// [`AnsCoder`]). See [`Decode::decode_bits`] for the inverse operation.
+    ///
+    /// [`Bernoulli`]: model::bernoulli::Bernoulli
+    /// [`encode_iid_symbols`]: Self::encode_iid_symbols
+    /// [`AnsCoder`]: stack::AnsCoder
+    #[inline(always)]
+    fn encode_bits<B, M>(
+        &mut self,
+        bits: impl IntoIterator<Item = B>,
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        B: Borrow<bool>,
+        M: EncoderModel<PRECISION, Symbol = bool> + Copy,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.encode_iid_symbols(bits, model)
+    }
+
     /// Checks if there might not be any room to encode more data.
     ///
     /// If this method returns `false` then encoding one more symbol must not fail due to a
@@ -1021,6 +1233,153 @@ pub trait Decode<const PRECISION: usize>: Code {
         }
     }
 
+    /// Advances the decoder past a sequence of symbols without returning them, using an
+    /// individual entropy model for each symbol.
+    ///
+    /// This performs the same updates of the decoder's state as [`decode_symbols`] (so any
+    /// subsequent decoding picks up right after the skipped symbols), but it discards the
+    /// decoded symbols instead of yielding them. Unlike [`decode_symbols`], this method is
+    /// eager and short-circuits on the first error, in which case the decoder may be left
+    /// in the middle of the skipped sequence.
+    ///
+    /// This is useful for random access into the middle of a stream when there's no
+    /// [checkpoint](crate::Seek) close to the position you want to jump to: seek to the
+    /// last checkpoint before the target position, then skip the symbols between the
+    /// checkpoint and the target position. Note that you still need the entropy models
+    /// for all skipped symbols.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode},
+    ///     UnwrapInfallible,
+    /// };
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-100i32..=100);
+    /// let models = (0..6)
+    ///     .map(|i| quantizer.quantize(probability::distribution::Gaussian::new(i as f64, 10.0)))
+    ///     .collect::<Vec<_>>();
+    /// let symbols = [3, -12, 7, 20, 5, -1];
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_symbols_reverse(symbols.iter().zip(&models)).unwrap();
+    ///
+    /// // Skip the first four symbols, then decode the remaining ones.
+    /// ans.skip_symbols(&models[..4]).unwrap_infallible();
+    /// let tail = ans.decode_symbols(&models[4..]).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(tail.unwrap_infallible(), &symbols[4..]);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_symbols`]: Self::decode_symbols
+    #[inline(always)]
+    fn skip_symbols<I, M>(
+        &mut self,
+        models: I,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        I: IntoIterator<Item = M>,
+        M: DecoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        for model in models {
+            self.decode_symbol(model)?;
+        }
+        Ok(())
+    }
+
+    /// Advances the decoder past `amt` symbols that all use the same entropy model.
+    ///
+    /// This is the i.i.d. variant of [`skip_symbols`], analogous to how
+    /// [`decode_iid_symbols`] relates to [`decode_symbols`]. As with
+    /// [`decode_iid_symbols`], you'll typically want to pass `model` by reference.
+    ///
+    /// [`skip_symbols`]: Self::skip_symbols
+    /// [`decode_iid_symbols`]: Self::decode_iid_symbols
+    /// [`decode_symbols`]: Self::decode_symbols
+    #[inline(always)]
+    fn skip_iid_symbols<M>(
+        &mut self,
+        amt: usize,
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: DecoderModel<PRECISION> + Copy,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        for _ in 0..amt {
+            self.decode_symbol(model)?;
+        }
+        Ok(())
+    }
+
+    /// Decodes `amt` bits with a binary entropy model, e.g., a [`Bernoulli`] model.
+    ///
+    /// This is a convenience wrapper that just forwards to [`decode_iid_symbols`] (see
+    /// [`Encode::encode_bits`] for a discussion of performance). It is the inverse of
+    /// `Encode::encode_bits` if both use the same `model`.
+    ///
+    /// [`Bernoulli`]: model::bernoulli::Bernoulli
+    /// [`decode_iid_symbols`]: Self::decode_iid_symbols
+    #[inline(always)]
+    fn decode_bits<M>(&mut self, amt: usize, model: M) -> DecodeIidSymbols<'_, Self, M, PRECISION>
+    where
+        M: DecoderModel<PRECISION, Symbol = bool> + Copy,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.decode_iid_symbols(amt, model)
+    }
+
+    /// Decodes a value of a type that implements [`SymbolAlphabet`] (e.g., an enum with
+    /// `#[derive(SymbolAlphabet)]`).
+    ///
+    /// Decodes an index with `model` and converts it with [`SymbolAlphabet::from_index`].
+    /// This is the inverse of [`Encode::encode_enum`] if both use the same `model`.
+    ///
+    /// # Panics
+    ///
+    /// If `model` decodes an index that is not smaller than `A::NUM_SYMBOLS`. This can't
+    /// happen if the support of `model` is `0..A::NUM_SYMBOLS`, e.g., if `model` is a
+    /// [`ContiguousCategoricalEntropyModel`] with `A::NUM_SYMBOLS` entries.
+    ///
+    /// [`ContiguousCategoricalEntropyModel`]: model::ContiguousCategoricalEntropyModel
+    #[inline(always)]
+    fn decode_enum<A, M>(
+        &mut self,
+        model: M,
+    ) -> Result<A, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        A: SymbolAlphabet,
+        M: DecoderModel<PRECISION, Symbol = usize>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        Ok(self
+            .checked_decode_enum(model)?
+            .expect("Entropy model has a larger support than the alphabet."))
+    }
+
+    /// Like [`decode_enum`](Self::decode_enum), but returns `Ok(None)` instead of
+    /// panicking if `model` decodes an index that is not smaller than `A::NUM_SYMBOLS`.
+    #[inline(always)]
+    fn checked_decode_enum<A, M>(
+        &mut self,
+        model: M,
+    ) -> Result<Option<A>, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        A: SymbolAlphabet,
+        M: DecoderModel<PRECISION, Symbol = usize>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        let index = self.decode_symbol(model)?;
+        Ok(A::from_index(index))
+    }
+
     /// Checks if there might be no compressed data left for decoding.
     ///
     /// If this method returns `false` then there must be additional data left to decode. If
@@ -1300,10 +1659,14 @@ where
 }
 
 /// The error type for [`Encode::try_encode_symbols`] and [`Decode::try_decode_symbols`].
+///
+/// Also used by [`Encode::encode_symbols_validated`], with `ModelError` being an
+/// [`InvalidSymbolsError`].
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub enum TryCodingError<CodingError, ModelError> {
     /// The iterator provided to [`Encode::try_encode_symbols`] or
-    /// [`Decode::try_decode_symbols`] yielded `Err(_)`.
+    /// [`Decode::try_decode_symbols`] yielded `Err(_)`, or
+    /// [`Encode::encode_symbols_validated`] found symbols that can't be encoded.
     ///
     /// The variant wraps the original error, which can also be retrieved via
     /// [`Error::source`] if both `ModelError` and `CodingError` implement
@@ -1356,3 +1719,70 @@ impl<CodingError, ModelError> From<CodingError> for TryCodingError<CodingError,
         Self::CodingError(err)
     }
 }
+
+/// Error that reports which symbols lie outside the support of their entropy models.
+///
+/// Returned (wrapped in a [`TryCodingError::InvalidEntropyModel`]) by
+/// [`Encode::encode_symbols_validated`]. Lists the positions of at most
+/// [`MAX_REPORTED`](Self::MAX_REPORTED) offending symbols, but counts all of them.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InvalidSymbolsError {
+    indices: Vec<usize>,
+    num_invalid: usize,
+}
+
+impl InvalidSymbolsError {
+    /// The maximum number of positions reported by [`indices`](Self::indices).
+    pub const MAX_REPORTED: usize = 10;
+
+    pub(crate) fn new() -> Self {
+        Self {
+            indices: Vec::new(),
+            num_invalid: 0,
+        }
+    }
+
+    pub(crate) fn record(&mut self, index: usize) {
+        if self.indices.len() < Self::MAX_REPORTED {
+            self.indices.push(index);
+        }
+        self.num_invalid += 1;
+    }
+
+    pub(crate) fn into_result(self) -> Result<(), Self> {
+        if self.num_invalid == 0 {
+            Ok(())
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Returns the positions of the first (up to [`MAX_REPORTED`](Self::MAX_REPORTED))
+    /// offending symbols, in increasing order.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Returns the total number of offending symbols.
+    pub fn num_invalid(&self) -> usize {
+        self.num_invalid
+    }
+}
+
+impl Display for InvalidSymbolsError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} symbol(s) outside the support of their entropy models, ",
+            self.num_invalid
+        )?;
+        if self.num_invalid > self.indices.len() {
+            write!(f, "first ones at indices {:?}.", self.indices)
+        } else {
+            write!(f, "at indices {:?}.", self.indices)
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidSymbolsError {}
@@ -44,6 +44,32 @@
 //!   small `PRECISION`. See [`SmallContiguousLookupDecoderModel`] and
 //!   [`SmallNonContiguousLookupDecoderModel`].
 //!
+//! The following submodules build on these utilities and provide more specialized models:
+//!
+//! - [`audio`]: ready-made models for the prediction residuals of lossless audio codecs.
+//! - [`cauchy`]: a heavy-tailed distribution that you can quantize like the distributions
+//!   from the crate [`probability`].
+//! - [`mixture`]: mixtures of Gaussian or logistic distributions that you can quantize in
+//!   the same way, and a combinator that mixes arbitrary entropy models.
+//! - [`bernoulli`]: an allocation-free model for binary symbols.
+//! - [`binomial`]: a binomial model whose fixed point probabilities are reproducible bit
+//!   for bit across platforms.
+//! - [`poisson`] and [`geometric`]: Poisson and geometric models that fold their unbounded
+//!   tails into a maximum (or escape) symbol. Both can be evaluated without lookup tables,
+//!   so their parameters can change cheaply from symbol to symbol. [`geometric`] also
+//!   provides a two-sided geometric model for prediction residuals.
+//! - [`negative_binomial`]: a model for overdispersed counts.
+//! - [`escape`]: wraps any integer model with an Exp-Golomb escape for symbols outside of
+//!   its support, so that you don't have to clamp symbols to a fixed support.
+//! - [`empirical`]: a nonparametric model that follows the smoothed histogram of some
+//!   sample data.
+//! - [`dyn_precision`]: a categorical model that can present itself at any fixed point
+//!   precision at runtime, e.g., for experiments that compare precisions.
+//! - [`zipf`]: a power-law model for ranks or token IDs over large alphabets.
+//! - [`auto`]: fits several candidate models to a sample and selects the best one.
+//! - [`autoregressive`]: a combinator for sequences where the model of each symbol depends
+//!   on the preceding symbols (e.g., Markov chains).
+//!
 //! # Examples
 //!
 //! See [`LeakyQuantizer`](LeakyQuantizer#examples), [`ContiguousCategoricalEntropyModel`],
@@ -112,6 +138,21 @@
 //! distribution function either isn't monotonic or has an image that exceeds the interval
 //! `[0, 1]`).
 //!
+//! # Serialization
+//!
+//! If you enable the optional feature `serde`, then categorical entropy models and lookup
+//! decoder models implement `serde::Serialize` and `serde::Deserialize`, and a
+//! [`LeakilyQuantizedDistribution`] implements `serde::Serialize`. The serialized
+//! representation consists of the fixed-point probabilities (and, where applicable, the
+//! symbols) together with the `PRECISION`, so that a deserialized model is bit-exactly the
+//! same as the serialized one and can decode data that was encoded with it. Deserialization
+//! verifies all constraints listed above, and it fails if the serialized `PRECISION`
+//! doesn't match the `PRECISION` of the target type. Models over arbitrary symbols (e.g.,
+//! a [`NonContiguousCategoricalEncoderModel`], a [`NonContiguousCategoricalDecoderModel`],
+//! and a [`LeakilyQuantizedDistribution`]) all share the same serialized representation,
+//! so you can, e.g., serialize a `LeakilyQuantizedDistribution` on the encoder side and
+//! deserialize it as a [`LookupDecoderModel`] on the decoder side.
+//!
 //! [`stack`]: super::stack
 //! [`queue`]: super::queue
 //! [`Binomial`]: probability::distribution::Binomial
@@ -129,14 +170,23 @@ use hashbrown::hash_map::{
     HashMap,
 };
 
-use alloc::{boxed::Box, vec::Vec};
-use core::{borrow::Borrow, fmt::Debug, hash::Hash, marker::PhantomData, ops::RangeInclusive};
+use alloc::{
+    boxed::Box,
+    collections::{BTreeMap, BinaryHeap},
+    vec::Vec,
+};
+use core::{
+    borrow::Borrow, cell::RefCell, fmt::Debug, hash::Hash, marker::PhantomData, ops::RangeInclusive,
+};
 use num::{
     cast::AsPrimitive,
     traits::{WrappingAdd, WrappingSub},
     Float, One, PrimInt, Zero,
 };
 
+#[cfg(feature = "derive")]
+pub use constriction_derive::static_categorical;
+
 /// Re-export or replacement of [`probability::distribution::Distribution`].
 ///
 /// Most users will never have to interact with this trait directly. When a method requires
@@ -243,6 +293,27 @@ pub trait Inverse: Distribution {
 
 use crate::{wrapping_pow2, BitArray, NonZeroBitArray};
 
+#[cfg(feature = "probability")]
+pub mod audio;
+#[cfg(feature = "probability")]
+pub mod auto;
+pub mod autoregressive;
+pub mod bernoulli;
+pub mod binomial;
+#[cfg(feature = "probability")]
+pub mod cauchy;
+pub mod dyn_precision;
+pub mod empirical;
+pub mod escape;
+pub mod geometric;
+pub mod mixture;
+pub mod negative_binomial;
+pub mod poisson;
+pub mod zipf;
+
+#[cfg(feature = "serde")]
+mod serde_impls;
+
 /// Base trait for probabilistic models of a data source.
 ///
 /// All entropy models (see [module level documentation](self)) that can be used for
@@ -452,7 +523,6 @@ pub trait IterableEntropyModel<'m, const PRECISION: usize>: EntropyModel<PRECISI
     {
         let entropy_scaled = self
             .symbol_table()
-            .into_iter()
             .map(|(_, _, probability)| {
                 let probability = probability.get().into();
                 probability * probability.log2() // probability is guaranteed to be nonzero.
@@ -463,6 +533,106 @@ pub trait IterableEntropyModel<'m, const PRECISION: usize>: EntropyModel<PRECISI
         F::from(PRECISION).unwrap() - entropy_scaled / whole
     }
 
+    /// Returns the cross entropy between `self` and `other` in units of bits (i.e., base 2).
+    ///
+    /// The cross entropy is the expected bit rate (up to the small constant overhead of the
+    /// entropy coder) for encoding symbols that are distributed according to `self` with
+    /// the entropy model `other`, i.e., it is the expected code length if the data follows
+    /// `self` but your encoder and decoder use `other`. This allows you to estimate bit
+    /// rates without running an entropy coder. Both models are evaluated with their
+    /// quantized fixed point probabilities, and `other` may have a different precision than
+    /// `self`.
+    ///
+    /// Returns infinity if the support of `self` contains a symbol that `other` can't
+    /// encode.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{
+    ///     DefaultContiguousCategoricalEntropyModel, IterableEntropyModel,
+    ///     SmallContiguousCategoricalEntropyModel,
+    /// };
+    ///
+    /// let data_model = DefaultContiguousCategoricalEntropyModel
+    ///     ::from_floating_point_probabilities(&[0.5, 0.25, 0.25]).unwrap();
+    /// let coding_model = SmallContiguousCategoricalEntropyModel
+    ///     ::from_floating_point_probabilities(&[0.25, 0.25, 0.5]).unwrap();
+    ///
+    /// let entropy = data_model.entropy_base2::<f64>();
+    /// let cross_entropy = data_model.cross_entropy_base2::<f64, _, 12>(&coding_model);
+    /// assert_eq!(entropy, 1.5);
+    /// assert_eq!(cross_entropy, 1.75);
+    /// assert_eq!(data_model.kl_divergence_base2::<f64, _, 12>(&coding_model), 0.25);
+    /// ```
+    ///
+    /// # See also
+    ///
+    /// - [`entropy_base2`](Self::entropy_base2), which is the cross entropy of a model with
+    ///   itself, i.e., the expected bit rate when encoding with the correct model;
+    /// - [`kl_divergence_base2`](Self::kl_divergence_base2), which is the expected
+    ///   overhead of encoding with `other` rather than with `self`.
+    fn cross_entropy_base2<F, M, const OTHER_PRECISION: usize>(&'m self, other: M) -> F
+    where
+        F: Float + core::iter::Sum,
+        Self::Probability: Into<F>,
+        M: EncoderModel<OTHER_PRECISION, Symbol = Self::Symbol>,
+        M::Probability: Into<F>,
+    {
+        let whole = (F::one() + F::one()) * (Self::Probability::one() << (PRECISION - 1)).into();
+        let other_precision = F::from(OTHER_PRECISION).unwrap();
+        self.symbol_table()
+            .map(
+                |(symbol, _, probability)| match other.left_cumulative_and_probability(symbol) {
+                    Some((_, other_probability)) => {
+                        let information_content =
+                            other_precision - other_probability.get().into().log2();
+                        probability.get().into() / whole * information_content
+                    }
+                    None => F::infinity(),
+                },
+            )
+            .sum::<F>()
+    }
+
+    /// Returns the Kullback-Leibler divergence from `other` to `self` in units of bits (i.e.,
+    /// base 2).
+    ///
+    /// The KL divergence is the expected number of bits that you waste per symbol if you
+    /// encode symbols that are distributed according to `self` with the entropy model
+    /// `other`. It is the difference between the [cross
+    /// entropy](Self::cross_entropy_base2) and the [entropy](Self::entropy_base2), but this
+    /// method calculates it directly, so it is exactly zero if both models have the same
+    /// probabilities. As with `cross_entropy_base2`, both models are evaluated with their
+    /// quantized fixed point probabilities, and `other` may have a different precision than
+    /// `self`.
+    ///
+    /// Returns infinity if the support of `self` contains a symbol that `other` can't
+    /// encode.
+    fn kl_divergence_base2<F, M, const OTHER_PRECISION: usize>(&'m self, other: M) -> F
+    where
+        F: Float + core::iter::Sum,
+        Self::Probability: Into<F>,
+        M: EncoderModel<OTHER_PRECISION, Symbol = Self::Symbol>,
+        M::Probability: Into<F>,
+    {
+        let whole = (F::one() + F::one()) * (Self::Probability::one() << (PRECISION - 1)).into();
+        let precision_difference = F::from(OTHER_PRECISION).unwrap() - F::from(PRECISION).unwrap();
+        self.symbol_table()
+            .map(
+                |(symbol, _, probability)| match other.left_cumulative_and_probability(symbol) {
+                    Some((_, other_probability)) => {
+                        let probability = probability.get().into();
+                        let log_ratio = probability.log2() - other_probability.get().into().log2()
+                            + precision_difference;
+                        probability / whole * log_ratio
+                    }
+                    None => F::infinity(),
+                },
+            )
+            .sum::<F>()
+    }
+
     /// Creates an [`EncoderModel`] from this `EntropyModel`
     ///
     /// This is a fallback method that should only be used if no more specialized
@@ -693,6 +863,48 @@ pub trait EncoderModel<const PRECISION: usize>: EntropyModel<PRECISION> {
             .map_or(Self::Probability::zero(), |(_, p)| p.get());
         probability.into() / whole
     }
+
+    /// Returns the information content of the given symbol in bits, i.e., the negative
+    /// base-2 logarithm of its quantized probability.
+    ///
+    /// This is the number of bits that encoding `symbol` with this model adds to the
+    /// compressed data (up to the small constant overhead of the entropy coder, which gets
+    /// amortized over many symbols). It is calculated from the same fixed point
+    /// probability that the entropy coders use, so it is useful for, e.g., rate-distortion
+    /// optimization, where you want to know what the coder would charge for a symbol
+    /// without actually encoding it. You may have to specify the return type explicitly
+    /// using "turbofish" notation `::<f64>(...)` or `::<f32>(...)`, see example below.
+    ///
+    /// Returns infinity if `symbol` is not in the support of the entropy model.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{EncoderModel, DefaultNonContiguousCategoricalEncoderModel};
+    ///
+    /// let symbols = vec!['a', 'b', 'c', 'd'];
+    /// let probabilities = vec![1u32 << 21, 1 << 23, 1 << 22, 1 << 21];
+    /// let model = DefaultNonContiguousCategoricalEncoderModel
+    ///     ::from_symbols_and_nonzero_fixed_point_probabilities(
+    ///         symbols.iter().copied(), &probabilities, false)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(model.bit_cost::<f64>('a'), 3.0);
+    /// assert_eq!(model.bit_cost::<f64>('b'), 1.0);
+    /// assert_eq!(model.bit_cost::<f64>('c'), 2.0);
+    /// assert_eq!(model.bit_cost::<f64>('x'), f64::INFINITY);
+    /// ```
+    #[inline]
+    fn bit_cost<F>(&self, symbol: impl Borrow<Self::Symbol>) -> F
+    where
+        F: Float,
+        Self::Probability: Into<F>,
+    {
+        match self.left_cumulative_and_probability(symbol) {
+            Some((_, probability)) => F::from(PRECISION).unwrap() - probability.get().into().log2(),
+            None => F::infinity(),
+        }
+    }
 }
 
 /// A trait for [`EntropyModel`]s that can be used for decoding (decompressing) data.
@@ -800,6 +1012,26 @@ where
         (*self).entropy_base2()
     }
 
+    fn cross_entropy_base2<F, M2, const OTHER_PRECISION: usize>(&'m self, other: M2) -> F
+    where
+        F: Float + core::iter::Sum,
+        Self::Probability: Into<F>,
+        M2: EncoderModel<OTHER_PRECISION, Symbol = Self::Symbol>,
+        M2::Probability: Into<F>,
+    {
+        (*self).cross_entropy_base2(other)
+    }
+
+    fn kl_divergence_base2<F, M2, const OTHER_PRECISION: usize>(&'m self, other: M2) -> F
+    where
+        F: Float + core::iter::Sum,
+        Self::Probability: Into<F>,
+        M2: EncoderModel<OTHER_PRECISION, Symbol = Self::Symbol>,
+        M2::Probability: Into<F>,
+    {
+        (*self).kl_divergence_base2(other)
+    }
+
     #[inline(always)]
     fn to_generic_encoder_model(
         &'m self,
@@ -863,8 +1095,24 @@ pub struct UniformModel<Probability: BitArray, const PRECISION: usize> {
 }
 
 impl<Probability: BitArray, const PRECISION: usize> UniformModel<Probability, PRECISION> {
+    /// Creates a uniform distribution over the symbols `0..range`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is smaller than two or larger than `1 << PRECISION`. Use
+    /// [`checked_new`](Self::checked_new) if `range` isn't known to be valid.
     pub fn new(range: Probability) -> Self {
-        assert!(range > Probability::one()); // We don't support degenerate probability distributions (i.e. range=1).
+        Self::checked_new(range)
+            .expect("Range of Uniform model must be at least 2 and must not exceed 1 << PRECISION.")
+    }
+
+    /// Same as [`new`](Self::new) but returns `None` instead of panicking if `range` is
+    /// smaller than two or larger than `1 << PRECISION`.
+    pub fn checked_new(range: Probability) -> Option<Self> {
+        // We don't support degenerate probability distributions (i.e. range=1).
+        if range <= Probability::one() {
+            return None;
+        }
         let range = unsafe { range.into_nonzero_unchecked() }; // For performance hint.
         let last_symbol = range.get() - Probability::one();
 
@@ -872,20 +1120,17 @@ impl<Probability: BitArray, const PRECISION: usize> UniformModel<Probability, PR
             let probability_per_bin =
                 (Probability::zero().wrapping_sub(&range.get()) / range.get()) + Probability::one();
             unsafe {
-                Self {
+                Some(Self {
                     probability_per_bin: probability_per_bin.into_nonzero_unchecked(),
                     last_symbol,
-                }
+                })
             }
         } else {
             let probability_per_bin = (Probability::one() << PRECISION) / range.get();
-            let probability_per_bin = probability_per_bin
-                .into_nonzero()
-                .expect("Range of Uniform model must not exceed 1 << PRECISION.");
-            Self {
-                probability_per_bin,
+            Some(Self {
+                probability_per_bin: probability_per_bin.into_nonzero()?,
                 last_symbol,
-            }
+            })
         }
     }
 }
@@ -1017,6 +1262,154 @@ impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
     }
 }
 
+/// A uniform distribution over a contiguous range of integers `min..=max`.
+///
+/// Unlike a [`ContiguousCategoricalEntropyModel`], this model doesn't build up any table:
+/// both encoding and decoding a symbol only take a few arithmetic operations, regardless
+/// of the size of the range. This makes it the model of choice for "raw" symbols that are
+/// (close to) uniformly distributed over a large range, such as the least significant bits
+/// of some quantity. It behaves like a [`UniformModel`] that is shifted to start at `min`,
+/// so the symbol `max` typically has a very slightly higher probability than the other
+/// symbols due to rounding.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::DefaultUniformRangeModel, queue::DefaultRangeEncoder, Decode, Encode,
+/// };
+///
+/// let model = DefaultUniformRangeModel::new(-1000..=1000).unwrap();
+/// let symbols = [-1000, 0, 17, 1000, -3];
+///
+/// let mut encoder = DefaultRangeEncoder::new();
+/// encoder.encode_iid_symbols(&symbols, model).unwrap();
+/// let mut decoder = encoder.into_decoder().unwrap();
+/// let decoded = decoder
+///     .decode_iid_symbols(symbols.len(), model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct UniformRangeModel<Symbol, Probability: BitArray, const PRECISION: usize> {
+    min_symbol_inclusive: Symbol,
+    max_symbol_inclusive: Symbol,
+    inner: UniformModel<Probability, PRECISION>,
+}
+
+/// Type alias for a typical [`UniformRangeModel`].
+///
+/// See:
+/// - [`UniformRangeModel`]
+/// - [discussion of presets](super#presets)
+pub type DefaultUniformRangeModel<Symbol = i32> = UniformRangeModel<Symbol, u32, 24>;
+
+/// Type alias for a [`UniformRangeModel`] optimized for compatibility with lookup decoder
+/// models.
+///
+/// See:
+/// - [`UniformRangeModel`]
+/// - [discussion of presets](super#presets)
+pub type SmallUniformRangeModel<Symbol = i32> = UniformRangeModel<Symbol, u16, 12>;
+
+impl<Symbol, Probability, const PRECISION: usize> UniformRangeModel<Symbol, Probability, PRECISION>
+where
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub,
+    Probability: BitArray,
+{
+    /// Creates a uniform distribution over all symbols in `range`.
+    ///
+    /// Returns an error if `range` contains fewer than two symbols or more than
+    /// `2^PRECISION` symbols.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(range: RangeInclusive<Symbol>) -> Result<Self, ()> {
+        assert!(PRECISION > 0 && PRECISION <= Probability::BITS);
+
+        let (min_symbol_inclusive, max_symbol_inclusive) = range.into_inner();
+        if min_symbol_inclusive >= max_symbol_inclusive {
+            return Err(());
+        }
+
+        let max_probability = Probability::max_value() >> (Probability::BITS - PRECISION);
+        let last_symbol: Probability =
+            checked_slack(max_symbol_inclusive, min_symbol_inclusive).ok_or(())?;
+        if last_symbol > max_probability
+            || (last_symbol == max_probability && PRECISION == Probability::BITS)
+        {
+            return Err(());
+        }
+
+        Ok(Self {
+            min_symbol_inclusive,
+            max_symbol_inclusive,
+            inner: UniformModel::new(last_symbol + Probability::one()),
+        })
+    }
+}
+
+impl<Symbol, Probability, const PRECISION: usize> UniformRangeModel<Symbol, Probability, PRECISION>
+where
+    Symbol: PrimInt + WrappingAdd + 'static,
+    Probability: BitArray + AsPrimitive<Symbol>,
+{
+    /// Returns the range of symbols that have nonzero probability.
+    ///
+    /// This is the same range that was passed to [`new`](Self::new).
+    #[inline]
+    pub fn support(&self) -> RangeInclusive<Symbol> {
+        self.min_symbol_inclusive..=self.max_symbol_inclusive
+    }
+}
+
+impl<Symbol, Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for UniformRangeModel<Symbol, Probability, PRECISION>
+{
+    type Symbol = Symbol;
+    type Probability = Probability;
+}
+
+impl<Symbol, Probability, const PRECISION: usize> EncoderModel<PRECISION>
+    for UniformRangeModel<Symbol, Probability, PRECISION>
+where
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub,
+    Probability: BitArray,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Symbol>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let symbol = *symbol.borrow();
+        if symbol < self.min_symbol_inclusive || symbol > self.max_symbol_inclusive {
+            return None;
+        }
+        // The constructor checked that `slack` doesn't truncate within the support.
+        self.inner
+            .left_cumulative_and_probability(slack(symbol, self.min_symbol_inclusive))
+    }
+}
+
+impl<Symbol, Probability, const PRECISION: usize> DecoderModel<PRECISION>
+    for UniformRangeModel<Symbol, Probability, PRECISION>
+where
+    Symbol: PrimInt + WrappingAdd + 'static,
+    Probability: BitArray + AsPrimitive<Symbol>,
+{
+    #[inline]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (Symbol, Probability, Probability::NonZero) {
+        let (offset, left_cumulative, probability) = self.inner.quantile_function(quantile);
+        (
+            self.min_symbol_inclusive.wrapping_add(&offset.as_()),
+            left_cumulative,
+            probability,
+        )
+    }
+}
+
 /// Quantizes probability distributions and represents them in fixed-point precision.
 ///
 /// You will usually want to use this type through one of its type aliases,
@@ -1192,6 +1585,17 @@ impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
 /// [`IterableEntropyModel`] into scope to call these conversion methods (`use
 /// constriction::stream::model::IterableEntropyModel`).
 ///
+/// # The Float Type `F`
+///
+/// The type parameter `F` is the floating point type in which a `LeakyQuantizer` stores its
+/// probability budget. It is `f64` for quantizers constructed with [`new`](Self::new) and
+/// `f32` for quantizers constructed with [`new_f32`](Self::new_f32). It doesn't affect the
+/// resulting entropy models: the underlying [`Distribution`] evaluates its CDF in `f64`
+/// anyway, and the budget (which `F` has to represent exactly) is converted to `f64` before
+/// it scales the CDF. Thus, a `LeakyQuantizer<f32, ...>` and a
+/// `LeakyQuantizer<f64, ...>` produce bit-identical entropy models, and you can pick
+/// whichever `F` fits the rest of your code (e.g., `f32` outputs of a neural network).
+///
 /// # Requirements for Correctness
 ///
 /// The original distribution that you pass to the method [`quantize`] can only be an
@@ -1203,20 +1607,24 @@ impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
 /// met:
 ///
 /// - The cumulative distribution function (CDF) [`Distribution::distribution`] is defined
-///   on all finite non-NaN floating point numbers, monotonically nondecreasing, and its
-///   values do not exceed the closed interval `[0.0, 1.0]`. It is OK if, due to rounding
-///   errors, the CDF does not cover the entire interval from `0.0` to `1.0`.
-/// - The quantile function or inverse CDF [`Inverse::inverse`] evaluates to a finite
-///   non-NaN value everywhere on the open interval `(0.0, 1.0)`, and it is monotonically
-///   nondecreasing on this interval. It does not have to be defined at the boundaries `0.0`
-///   or `1.0` (more precisely, it only has to be defined on the closed interval
-///   `[epsilon, 1.0 - epsilon]` where `epsilon := 2.0^{-(PRECISION+1)}` and `^` denotes
-///   mathematical exponentiation). Further, the implementation of `Inverse::inverse` does
-///   not actually have to be the inverse of `Distribution::distribution` because it is only
-///   used as an initial hint where to start a search for the true inverse. It is OK if
-///   `Inverse::inverse` is just some approximation of the true inverse CDF. Any deviations
-///   between `Inverse::inverse` and the true inverse CDF will negatively impact runtime
-///   performance but will otherwise have no observable effect.
+///   on all finite non-NaN floating point numbers and monotonically nondecreasing once its
+///   values are clamped to the closed interval `[0.0, 1.0]`. The quantizer saturates the
+///   CDF explicitly, so it is OK if, due to rounding errors, the CDF slightly undershoots
+///   `0.0` or overshoots `1.0`, or if it does not cover the entire interval from `0.0` to
+///   `1.0`. A CDF value of NaN is treated like `0.0`, which is fine in the left tail (where
+///   it typically arises, e.g., for a [`Gaussian`] with vanishing standard deviation) but
+///   would violate monotonicity anywhere else.
+/// - The quantile function or inverse CDF [`Inverse::inverse`] may return any value, even
+///   NaN or an infinity, since the implementation of `Inverse::inverse` does not actually
+///   have to be the inverse of `Distribution::distribution`: it is only used as an initial
+///   hint where to start a search for the true inverse. It is OK if `Inverse::inverse` is
+///   just some approximation of the true inverse CDF. Any deviations between
+///   `Inverse::inverse` and the true inverse CDF will negatively impact runtime performance
+///   but will otherwise have no observable effect.
+///
+/// Under these requirements, every symbol within the support retains a nonzero
+/// probability, regardless of how extreme the parameters of the underlying distribution
+/// are (e.g., a mean far outside of the support or a standard deviation close to zero).
 ///
 /// [`quantize`]: Self::quantize
 /// [`Gaussian`]: probability::distribution::Gaussian
@@ -1248,12 +1656,11 @@ pub type DefaultLeakyQuantizer<F, Symbol> = LeakyQuantizer<F, Symbol, u32, 24>;
 /// - [discussion of presets](super#presets)
 pub type SmallLeakyQuantizer<F, Symbol> = LeakyQuantizer<F, Symbol, u16, 12>;
 
-impl<F, Symbol, Probability, const PRECISION: usize>
-    LeakyQuantizer<F, Symbol, Probability, PRECISION>
+impl<Symbol, Probability, const PRECISION: usize>
+    LeakyQuantizer<f64, Symbol, Probability, PRECISION>
 where
-    Probability: BitArray + Into<F>,
+    Probability: BitArray,
     Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub + WrappingAdd,
-    F: Float,
 {
     /// Constructs a `LeakyQuantizer` with a finite support.
     ///
@@ -1280,27 +1687,105 @@ where
     ///   representable nonzero probability to all elements of `support` would exceed our
     ///   probability budge).
     ///
+    /// Use [`checked_new`](Self::checked_new) if `support` isn't known to be valid.
+    ///
     /// [`quantize`]: #method.quantize
     pub fn new(support: RangeInclusive<Symbol>) -> Self {
+        Self::with_float_type(support)
+    }
+
+    /// Same as [`new`](Self::new) but returns `None` instead of panicking if `support` is
+    /// invalid.
+    pub fn checked_new(support: RangeInclusive<Symbol>) -> Option<Self> {
+        Self::checked_with_float_type(support)
+    }
+}
+
+impl<Symbol, Probability, const PRECISION: usize>
+    LeakyQuantizer<f32, Symbol, Probability, PRECISION>
+where
+    Probability: BitArray,
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub + WrappingAdd,
+{
+    /// Like [`new`](LeakyQuantizer::new) but stores the probability budget as an `f32`.
+    ///
+    /// The resulting entropy models are identical to the ones created by a quantizer that
+    /// was constructed with `new` (see [discussion of the float type
+    /// `F`](LeakyQuantizer#the-float-type-f)).
+    ///
+    /// # Panics
+    ///
+    /// Panics under the same conditions as [`new`](LeakyQuantizer::new), and also if `PRECISION >
+    /// 24` and the remaining probability budget can't be represented exactly as an `f32`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode};
+    /// use probability::distribution::Gaussian;
+    ///
+    /// // Parameters that were, e.g., predicted by a neural network in single precision.
+    /// let (mean, std_dev) = (3.2f32, 5.7f32);
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new_f32(-100..=100);
+    /// let model = quantizer.quantize(Gaussian::new(mean.into(), std_dev.into()));
+    /// let mut coder = DefaultAnsCoder::new();
+    /// coder.encode_iid_symbols_reverse(&[4, -2, 7], &model).unwrap();
+    /// let decoded = coder.decode_iid_symbols(3, &model).collect::<Result<Vec<_>, _>>().unwrap();
+    /// assert_eq!(decoded, [4, -2, 7]);
+    /// ```
+    pub fn new_f32(support: RangeInclusive<Symbol>) -> Self {
+        Self::with_float_type(support)
+    }
+
+    /// Same as [`new_f32`](Self::new_f32) but returns `None` instead of panicking.
+    pub fn checked_new_f32(support: RangeInclusive<Symbol>) -> Option<Self> {
+        Self::checked_with_float_type(support)
+    }
+}
+
+impl<F, Symbol, Probability, const PRECISION: usize>
+    LeakyQuantizer<F, Symbol, Probability, PRECISION>
+where
+    Probability: BitArray,
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub + WrappingAdd,
+    F: Float,
+{
+    fn with_float_type(support: RangeInclusive<Symbol>) -> Self {
         assert!(PRECISION > 0 && PRECISION <= Probability::BITS);
 
         // We don't support degenerate probability distributions (i.e., distributions that
         // place all probability mass on a single symbol).
         assert!(support.end() > support.start());
 
-        let support_size_minus_one = support.end().wrapping_sub(support.start()).as_();
+        let support_size_minus_one = slack(*support.end(), *support.start());
         let max_probability = Probability::max_value() >> (Probability::BITS - PRECISION);
-        let free_weight = max_probability
-            .checked_sub(&support_size_minus_one)
-            .expect("The support is too large to assign a nonzero probability to each element.")
-            .into();
+        assert!(
+            support_size_minus_one <= max_probability,
+            "The support is too large to assign a nonzero probability to each element."
+        );
 
-        LeakyQuantizer {
+        Self::checked_with_float_type(support).expect("`PRECISION` is too high for `f32`.")
+    }
+
+    fn checked_with_float_type(support: RangeInclusive<Symbol>) -> Option<Self> {
+        if PRECISION == 0 || PRECISION > Probability::BITS || support.end() <= support.start() {
+            return None;
+        }
+
+        let support_size_minus_one = slack(*support.end(), *support.start());
+        let max_probability = Probability::max_value() >> (Probability::BITS - PRECISION);
+        let free_weight = max_probability.checked_sub(&support_size_minus_one)?;
+        // Rounding `free_weight` up could make the quantized probabilities overflow.
+        let free_weight =
+            F::from(free_weight).filter(|&converted| converted.to_u64() == free_weight.to_u64())?;
+
+        Some(LeakyQuantizer {
             min_symbol_inclusive: *support.start(),
             max_symbol_inclusive: *support.end(),
             free_weight,
             phantom: PhantomData,
-        }
+        })
     }
 
     /// Quantizes the given probability distribution and returns an [`EntropyModel`].
@@ -1348,8 +1833,8 @@ where
 /// density function of a continuous probability distribution, or a mathematical formula for
 /// the probability mass functions of some discrete probability distribution). Examples of
 /// probabilistic models that lend themselves to being quantized are continuous
-/// distributions such as [`Gaussian`], [`Laplace`], or [`Exponential`], as well as discrete
-/// distributions with some analytic expression, such as [`Binomial`].
+/// distributions such as [`Gaussian`], [`Laplace`], [`Logistic`], or [`Exponential`], as
+/// well as discrete distributions with some analytic expression, such as [`Binomial`].
 ///
 /// Do *not* use a `LeakilyQuantizedDistribution` if your probabilistic model can only be
 /// presented as an explicit probability table. While you could, in principle, apply a
@@ -1369,6 +1854,7 @@ where
 ///
 /// [`Gaussian`]: probability::distribution::Gaussian
 /// [`Laplace`]: probability::distribution::Laplace
+/// [`Logistic`]: probability::distribution::Logistic
 /// [`Exponential`]: probability::distribution::Exponential
 /// [`Binomial`]: probability::distribution::Binomial
 /// [`Categorical`]: probability::distribution::Categorical
@@ -1378,7 +1864,7 @@ pub struct LeakilyQuantizedDistribution<F, Symbol, Probability, D, const PRECISI
     quantizer: LeakyQuantizer<F, Symbol, Probability, PRECISION>,
 }
 
-impl<'q, F, Symbol, Probability, D, const PRECISION: usize>
+impl<F, Symbol, Probability, D, const PRECISION: usize>
     LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
 where
     Probability: BitArray + Into<F>,
@@ -1469,7 +1955,64 @@ where
     symbol.borrow().wrapping_sub(&min_symbol_inclusive).as_() & mask
 }
 
-impl<'q, F, Symbol, Probability, D, const PRECISION: usize> EntropyModel<PRECISION>
+/// Same as [`slack`], but returns `None` if the result doesn't fit into `Probability`.
+///
+/// Requires `symbol >= min_symbol_inclusive`.
+#[inline(always)]
+fn checked_slack<Probability, Symbol>(
+    symbol: Symbol,
+    min_symbol_inclusive: Symbol,
+) -> Option<Probability>
+where
+    Probability: BitArray,
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub,
+{
+    let slack = slack(symbol, min_symbol_inclusive);
+    if core::mem::size_of::<Symbol>() <= core::mem::size_of::<Probability>() {
+        // `slack` doesn't truncate anything in this case.
+        return Some(slack);
+    }
+
+    // If `Symbol` is signed and the difference wrapped around to a negative number, then
+    // the true difference is at least `2^(Symbol::BITS - 1)`, which doesn't fit into the
+    // narrower `Probability`, and `to_u128` returns `None`.
+    let difference = symbol.wrapping_sub(&min_symbol_inclusive).to_u128();
+    if difference == slack.to_u128() {
+        Some(slack)
+    } else {
+        None
+    }
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize>
+    LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Probability: BitArray,
+    D: Distribution,
+{
+    /// Evaluates the underlying CDF at `x` and scales it to `free_weight` (without slack).
+    ///
+    /// Rather than relying on how float-to-int casts behave at the extremes, this saturates
+    /// explicitly: CDF values at or below zero (as well as NaN, which approximate CDFs tend
+    /// to return deep in the left tail, e.g., for a vanishing standard deviation) map to
+    /// zero, and values at or above one map to exactly `free_weight`. Thus, the result
+    /// always lies within `0..=free_weight`, and adding the slack can never overflow or
+    /// collapse the probability of a symbol to zero as long as the CDF is monotonic.
+    #[inline(always)]
+    fn non_leaky_cumulative(&self, x: f64) -> Probability {
+        let cdf = self.inner.distribution(x);
+        if cdf > 0.0 {
+            let free_weight: f64 = self.quantizer.free_weight.into();
+            (free_weight * cdf.min(1.0)).as_()
+        } else {
+            Probability::zero()
+        }
+    }
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize> EntropyModel<PRECISION>
     for LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
 where
     Probability: BitArray,
@@ -1478,9 +2021,10 @@ where
     type Symbol = Symbol;
 }
 
-impl<'q, Symbol, Probability, D, const PRECISION: usize> EncoderModel<PRECISION>
-    for LeakilyQuantizedDistribution<f64, Symbol, Probability, D, PRECISION>
+impl<F, Symbol, Probability, D, const PRECISION: usize> EncoderModel<PRECISION>
+    for LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
 where
+    F: Float + Into<f64>,
     f64: AsPrimitive<Probability>,
     Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub,
     Probability: BitArray + Into<f64>,
@@ -1493,9 +2037,9 @@ where
     ///
     /// Panics if it detects some invalidity in the underlying probability distribution.
     /// This means that there is a bug in the implementation of [`Distribution`] for the
-    /// distribution `D`: the cumulative distribution function is either not monotonically
-    /// nondecreasing, returns NaN, or its values exceed the interval `[0.0, 1.0]` at some
-    /// point.
+    /// distribution `D`: the cumulative distribution function is not monotonically
+    /// nondecreasing (values outside of `[0.0, 1.0]` and NaNs in the left tail are fine
+    /// since the quantizer saturates them).
     ///
     /// More precisely, this method panics if the quantization procedure leads to a zero
     /// probability despite the added leakiness (and despite the fact that the constructor
@@ -1512,7 +2056,6 @@ where
     ) -> Option<(Probability, Probability::NonZero)> {
         let min_symbol_inclusive = self.quantizer.min_symbol_inclusive;
         let max_symbol_inclusive = self.quantizer.max_symbol_inclusive;
-        let free_weight = self.quantizer.free_weight;
 
         if symbol.borrow() < &min_symbol_inclusive || symbol.borrow() > &max_symbol_inclusive {
             return None;
@@ -1525,8 +2068,7 @@ where
             // left tail of the distribution.
             Probability::zero()
         } else {
-            let non_leaky: Probability =
-                (free_weight * self.inner.distribution((*symbol.borrow()).into() - 0.5)).as_();
+            let non_leaky: Probability = self.non_leaky_cumulative((*symbol.borrow()).into() - 0.5);
             non_leaky + slack
         };
 
@@ -1537,8 +2079,7 @@ where
             // rounding down.
             wrapping_pow2(PRECISION)
         } else {
-            let non_leaky: Probability =
-                (free_weight * self.inner.distribution((*symbol.borrow()).into() + 0.5)).as_();
+            let non_leaky: Probability = self.non_leaky_cumulative((*symbol.borrow()).into() + 0.5);
             non_leaky + slack + Probability::one()
         };
 
@@ -1551,9 +2092,10 @@ where
     }
 }
 
-impl<'q, Symbol, Probability, D, const PRECISION: usize> DecoderModel<PRECISION>
-    for LeakilyQuantizedDistribution<f64, Symbol, Probability, D, PRECISION>
+impl<F, Symbol, Probability, D, const PRECISION: usize> DecoderModel<PRECISION>
+    for LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
 where
+    F: Float + Into<f64>,
     f64: AsPrimitive<Probability>,
     Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub + WrappingAdd,
     Probability: BitArray + Into<f64>,
@@ -1573,7 +2115,6 @@ where
 
         let min_symbol_inclusive = self.quantizer.min_symbol_inclusive;
         let max_symbol_inclusive = self.quantizer.max_symbol_inclusive;
-        let free_weight = self.quantizer.free_weight;
 
         // Make an initial guess for the inverse of the leaky CDF.
         let mut symbol: Self::Symbol = self
@@ -1591,8 +2132,7 @@ where
                 symbol = max_symbol_inclusive;
             }
 
-            let non_leaky: Probability =
-                (free_weight * self.inner.distribution(symbol.into() - 0.5)).as_();
+            let non_leaky: Probability = self.non_leaky_cumulative(symbol.into() - 0.5);
             non_leaky + slack(symbol, min_symbol_inclusive)
         };
 
@@ -1615,9 +2155,15 @@ where
                     break old_left_sided_cumulative;
                 }
 
-                let non_leaky: Probability =
-                    (free_weight * self.inner.distribution(symbol.into() - 0.5)).as_();
-                left_sided_cumulative = non_leaky + slack(symbol, min_symbol_inclusive);
+                left_sided_cumulative = if symbol == min_symbol_inclusive {
+                    // The exponential search may land on `min_symbol_inclusive` with a large
+                    // step size. The generic calculation below would be wrong here if the
+                    // left tail that we cut off carries (almost) all the probability mass.
+                    Probability::zero()
+                } else {
+                    let non_leaky: Probability = self.non_leaky_cumulative(symbol.into() - 0.5);
+                    non_leaky + slack(symbol, min_symbol_inclusive)
+                };
 
                 if left_sided_cumulative <= quantile {
                     found_lower_bound = true;
@@ -1628,7 +2174,7 @@ where
                             wrapping_pow2(PRECISION)
                         } else {
                             let non_leaky: Probability =
-                                (free_weight * self.inner.distribution(symbol.into() + 0.5)).as_();
+                                self.non_leaky_cumulative(symbol.into() + 0.5);
                             (non_leaky + slack(symbol, min_symbol_inclusive))
                                 .wrapping_add(&Probability::one())
                         };
@@ -1647,7 +2193,8 @@ where
                     symbol = symbol - step;
                 } else {
                     // We're still in the downward search phase with exponentially increasing step size.
-                    if step << 1 != Symbol::zero() {
+                    // (Stop doubling before `step` wraps to zero or, for signed symbols, turns negative.)
+                    if step << 1 > Symbol::zero() {
                         step = step << 1;
                     }
 
@@ -1673,8 +2220,7 @@ where
                 let right_sided_cumulative = if symbol == max_symbol_inclusive {
                     let right_sided_cumulative = max_probability.wrapping_add(&Probability::one());
                     if step <= Symbol::one() {
-                        let non_leaky: Probability =
-                            (free_weight * self.inner.distribution(symbol.into() - 0.5)).as_();
+                        let non_leaky: Probability = self.non_leaky_cumulative(symbol.into() - 0.5);
                         left_sided_cumulative = non_leaky + slack(symbol, min_symbol_inclusive);
 
                         // SAFETY: we have to manually check here.
@@ -1687,8 +2233,7 @@ where
                         right_sided_cumulative
                     }
                 } else {
-                    let non_leaky: Probability =
-                        (free_weight * self.inner.distribution(symbol.into() + 0.5)).as_();
+                    let non_leaky: Probability = self.non_leaky_cumulative(symbol.into() + 0.5);
                     (non_leaky + slack(symbol, min_symbol_inclusive))
                         .wrapping_add(&Probability::one())
                 };
@@ -1704,7 +2249,7 @@ where
                             Probability::zero()
                         } else {
                             let non_leaky: Probability =
-                                (free_weight * self.inner.distribution(symbol.into() - 0.5)).as_();
+                                self.non_leaky_cumulative(symbol.into() - 0.5);
                             non_leaky + slack(symbol, min_symbol_inclusive)
                         };
 
@@ -1725,7 +2270,7 @@ where
                     symbol = symbol + step;
                 } else {
                     // We're still in the upward search phase with exponentially increasing step size.
-                    if step << 1 != Symbol::zero() {
+                    if step << 1 > Symbol::zero() {
                         step = step << 1;
                     }
 
@@ -1753,9 +2298,10 @@ where
     }
 }
 
-impl<'m, 'q: 'm, Symbol, Probability, D, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
-    for LeakilyQuantizedDistribution<f64, Symbol, Probability, D, PRECISION>
+impl<'m, F, Symbol, Probability, D, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
 where
+    F: Float + Into<f64> + 'm,
     f64: AsPrimitive<Probability>,
     Symbol: PrimInt + AsPrimitive<Probability> + AsPrimitive<usize> + Into<f64> + WrappingSub,
     Probability: BitArray + Into<f64>,
@@ -1773,8 +2319,554 @@ where
     }
 }
 
-/// Iterator over the [`symbol_table`] of a [`LeakilyQuantizedDistribution`].
-///
+impl<F, Symbol, Probability, D, const PRECISION: usize>
+    LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + AsPrimitive<usize> + Into<f64> + WrappingSub,
+    Probability: BitArray + Into<f64>,
+    D: Distribution,
+    D::Value: AsPrimitive<Symbol>,
+{
+    /// Reports how well the fixed-point quantization fits the chosen support.
+    ///
+    /// Use this method to check whether the support that you passed to
+    /// [`LeakyQuantizer::new`] fits the underlying distribution: a large
+    /// [`tail_mass`](QuantizationReport::tail_mass) means that the support cuts off a
+    /// significant part of the distribution (so the symbols at the boundaries of the
+    /// support get assigned more probability than they should), whereas an
+    /// [`effective_support`](QuantizationReport::effective_support) that is much narrower
+    /// than the [`support`](QuantizationReport::support) means that a lot of symbols get
+    /// assigned only the minimal "leaky" probability, which costs a little bit of rate for
+    /// all other symbols.
+    ///
+    /// This method iterates over the entire support, so its runtime is linear in the
+    /// support size.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::DefaultLeakyQuantizer;
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+    ///
+    /// // A narrow distribution only uses a small part of the support ...
+    /// let report = quantizer.quantize(Gaussian::new(0.0, 2.0)).describe();
+    /// assert_eq!(report.support, -100..=100);
+    /// let effective_support = report.effective_support.unwrap();
+    /// assert!(*effective_support.start() > -20 && *effective_support.end() < 20);
+    /// assert_eq!(report.min_probability.get(), 1); // (i.e., `1 / (1 << 24)`)
+    /// assert_eq!(report.tail_mass, 0);
+    ///
+    /// // ... whereas a wide distribution leaks a lot of probability mass out of the support.
+    /// let report = quantizer.quantize(Gaussian::new(50.0, 100.0)).describe();
+    /// assert_eq!(report.effective_support.unwrap(), -100..=100);
+    /// assert!(report.tail_mass > 1 << 22); // (i.e., more than 25 %)
+    /// ```
+    pub fn describe(&self) -> QuantizationReport<Symbol, Probability> {
+        let mut effective_support: Option<RangeInclusive<Symbol>> = None;
+        let mut min_probability = None::<Probability::NonZero>;
+        for (symbol, _, probability) in self.symbol_table() {
+            if min_probability.map_or(true, |min| probability.get() < min.get()) {
+                min_probability = Some(probability);
+            }
+
+            // Each symbol gets one unit of "leaky" probability on top of the probability mass
+            // that it receives from the underlying distribution.
+            if probability.get() > Probability::one() {
+                let start = effective_support.map_or(symbol, |range| *range.start());
+                effective_support = Some(start..=symbol);
+            }
+        }
+
+        let min_symbol_inclusive = self.quantizer.min_symbol_inclusive;
+        let max_symbol_inclusive = self.quantizer.max_symbol_inclusive;
+        let left_tail = self.non_leaky_cumulative(min_symbol_inclusive.into() - 0.5);
+        let free_weight: f64 = self.quantizer.free_weight.into();
+        let right_tail =
+            free_weight.as_() - self.non_leaky_cumulative(max_symbol_inclusive.into() + 0.5);
+
+        QuantizationReport {
+            support: min_symbol_inclusive..=max_symbol_inclusive,
+            effective_support,
+            min_probability: min_probability.expect("The support is never empty."),
+            tail_mass: left_tail + right_tail,
+        }
+    }
+}
+
+/// Return type of [`LeakilyQuantizedDistribution::describe`].
+///
+/// All probabilities are in fixed-point representation, i.e., they have to be divided by
+/// `1 << PRECISION` to obtain probabilities in the interval `[0, 1]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuantizationReport<Symbol, Probability: BitArray> {
+    /// The range of symbols that have nonzero probability, see
+    /// [`LeakyQuantizer::support`].
+    pub support: RangeInclusive<Symbol>,
+
+    /// The smallest range of symbols that contains all symbols that receive some
+    /// probability mass from the underlying distribution (in addition to the minimal
+    /// probability that the `LeakyQuantizer` assigns to each symbol within the `support`).
+    ///
+    /// Is `None` if no symbol receives any such probability mass, which can only happen if
+    /// the support has (almost) `1 << PRECISION` symbols.
+    pub effective_support: Option<RangeInclusive<Symbol>>,
+
+    /// The smallest probability of any symbol within the `support`.
+    pub min_probability: Probability::NonZero,
+
+    /// The probability mass that the underlying distribution puts outside of the
+    /// `support` (more precisely, below `support.start() - 0.5` and above `support.end() +
+    /// 0.5`), which gets added to the probabilities of the boundary symbols.
+    ///
+    /// This is scaled to the probability mass that the quantizer distributes according to
+    /// the underlying distribution, i.e., to `1 << PRECISION` minus the size of the
+    /// `support`.
+    pub tail_mass: Probability,
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize>
+    LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub + WrappingAdd,
+    Probability: BitArray + Into<f64>,
+    D: Inverse,
+    D::Value: AsPrimitive<Symbol>,
+{
+    /// Builds a coarse index that speeds up decoding with this entropy model.
+    ///
+    /// The returned [`IndexedLeakilyQuantizedDistribution`] is the same entropy model as
+    /// `self` (i.e., it encodes and decodes identically), but its
+    /// [`quantile_function`](DecoderModel::quantile_function) doesn't have to invert the
+    /// underlying distribution. Instead, it looks up which range of symbols the quantile
+    /// falls into in a table of (up to) 256 buckets and then runs a binary search within
+    /// this range. This pays off if you decode many symbols with the same model (or if
+    /// inverting the CDF is expensive), but building the index evaluates the quantile
+    /// function 257 times, so don't use it for models that you only use a few times.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode,
+    /// };
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-1000..=1000);
+    /// let model = quantizer.quantize(Gaussian::new(3.2, 50.0));
+    /// let indexed_model = model.with_decoder_index();
+    /// let symbols = (0..100).map(|i| (i * 37) % 201 - 100).collect::<Vec<_>>();
+    ///
+    /// let mut coder = DefaultAnsCoder::new();
+    /// coder.encode_iid_symbols_reverse(&symbols, model).unwrap();
+    /// let decoded = coder
+    ///     .decode_iid_symbols(symbols.len(), &indexed_model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, symbols);
+    /// ```
+    pub fn with_decoder_index(
+        self,
+    ) -> IndexedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION> {
+        let log2_num_buckets = PRECISION.min(IndexedLeakilyQuantizedDistribution::<
+            F,
+            Symbol,
+            Probability,
+            D,
+            PRECISION,
+        >::LOG2_MAX_NUM_BUCKETS);
+        let shift = PRECISION - log2_num_buckets;
+        let max_quantile = Probability::max_value() >> (Probability::BITS - PRECISION);
+
+        let mut buckets = (0..1usize << log2_num_buckets)
+            .map(|bucket| {
+                let quantile = Probability::from(bucket).expect("bucket fits into `Probability`");
+                self.quantile_function(quantile << shift).0
+            })
+            .collect::<Vec<_>>();
+        buckets.push(self.quantile_function(max_quantile).0);
+
+        IndexedLeakilyQuantizedDistribution {
+            inner: self,
+            buckets,
+        }
+    }
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize>
+    LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub,
+    Probability: BitArray + Into<f64>,
+    D: Distribution,
+    D::Value: AsPrimitive<Symbol>,
+{
+    /// Wraps the model in a cache that memoizes evaluations of the underlying CDF.
+    ///
+    /// A `LeakilyQuantizedDistribution` evaluates the CDF of the underlying distribution
+    /// (twice) for every symbol that you encode, and it additionally inverts the CDF for
+    /// every symbol that you decode. This is a good trade-off if you use a different model
+    /// for each symbol, but it is wasteful if you encode or decode many i.i.d. symbols with
+    /// the same model. The returned [`CachedLeakilyQuantizedDistribution`] represents the
+    /// same entropy model as `self` (i.e., it encodes and decodes identically), but it
+    /// remembers the fixed point CDF of each symbol that it has seen. Decoding then runs a
+    /// binary search over the (cached) CDF instead of inverting the underlying
+    /// distribution, so the underlying distribution doesn't even have to implement
+    /// [`Inverse`].
+    ///
+    /// The cache grows by one entry per distinct symbol whose CDF was needed, so it is
+    /// bounded by the size of the [`support`](Self::support). The cache uses interior
+    /// mutability, so a `CachedLeakilyQuantizedDistribution` is not `Sync`. If you decode
+    /// many symbols and can afford to build the full index upfront, consider
+    /// [`with_decoder_index`](Self::with_decoder_index) instead, or tabularize the model
+    /// with [`IterableEntropyModel::to_generic_lookup_decoder_model`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, Decode, Encode,
+    /// };
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+    /// let model = quantizer.quantize(Gaussian::new(3.2, 10.0)).with_cdf_cache();
+    /// let symbols = (0..1000).map(|i| (i * 7) % 41 - 17).collect::<Vec<_>>();
+    ///
+    /// let mut encoder = DefaultRangeEncoder::new();
+    /// encoder.encode_iid_symbols(&symbols, &model).unwrap();
+    /// // The 1000 symbols take on only 41 distinct values, so the cache stays small.
+    /// assert!(model.num_cached() <= 42);
+    ///
+    /// let mut decoder = encoder.into_decoder().unwrap();
+    /// let decoded = decoder
+    ///     .decode_iid_symbols(symbols.len(), &model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, symbols);
+    /// ```
+    ///
+    /// [`Inverse`]: probability::distribution::Inverse
+    pub fn with_cdf_cache(
+        self,
+    ) -> CachedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION> {
+        CachedLeakilyQuantizedDistribution {
+            inner: self,
+            cache: RefCell::new(BTreeMap::new()),
+        }
+    }
+
+    /// Evaluates only the left-sided cumulative of the leaky distribution (see
+    /// [`EncoderModel::left_cumulative_and_probability`]).
+    #[inline(always)]
+    fn left_cumulative(&self, symbol: Symbol) -> Probability {
+        let min_symbol_inclusive = self.quantizer.min_symbol_inclusive;
+        if symbol == min_symbol_inclusive {
+            Probability::zero()
+        } else {
+            let non_leaky: Probability = self.non_leaky_cumulative(symbol.into() - 0.5);
+            non_leaky + slack(symbol, min_symbol_inclusive)
+        }
+    }
+}
+
+/// A [`LeakilyQuantizedDistribution`] with an index for faster decoding.
+///
+/// Create an `IndexedLeakilyQuantizedDistribution` with
+/// [`LeakilyQuantizedDistribution::with_decoder_index`], which also explains how it works.
+/// An `IndexedLeakilyQuantizedDistribution` represents the exact same entropy model as the
+/// `LeakilyQuantizedDistribution` from which it was created, so you can use the original
+/// model on the encoder side and the indexed model on the decoder side (or vice versa).
+#[derive(Debug, Clone)]
+pub struct IndexedLeakilyQuantizedDistribution<F, Symbol, Probability, D, const PRECISION: usize> {
+    inner: LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>,
+
+    /// `buckets[i]` is the symbol that the quantile `i << (PRECISION - log2(num_buckets))`
+    /// decodes to, and the last entry is the symbol that the largest quantile decodes to.
+    buckets: Vec<Symbol>,
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize>
+    IndexedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+{
+    const LOG2_MAX_NUM_BUCKETS: usize = 8;
+
+    /// Returns the underlying `LeakilyQuantizedDistribution` without the index.
+    pub fn into_inner(self) -> LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION> {
+        self.inner
+    }
+
+    /// Returns a reference to the underlying `LeakilyQuantizedDistribution`.
+    pub fn inner(&self) -> &LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION> {
+        &self.inner
+    }
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize> EntropyModel<PRECISION>
+    for IndexedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    Probability: BitArray,
+{
+    type Probability = Probability;
+    type Symbol = Symbol;
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize> EncoderModel<PRECISION>
+    for IndexedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub,
+    Probability: BitArray + Into<f64>,
+    D: Distribution,
+    D::Value: AsPrimitive<Symbol>,
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Symbol>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        self.inner.left_cumulative_and_probability(symbol)
+    }
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize> DecoderModel<PRECISION>
+    for IndexedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub + WrappingAdd,
+    Probability: BitArray + Into<f64> + AsPrimitive<usize>,
+    D: Inverse,
+    D::Value: AsPrimitive<Symbol>,
+{
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (Self::Symbol, Probability, Probability::NonZero) {
+        let log2_num_buckets = PRECISION.min(Self::LOG2_MAX_NUM_BUCKETS);
+        let bucket: usize = (quantile >> (PRECISION - log2_num_buckets)).as_();
+
+        // The symbol we're looking for lies within `low..=high` because the quantile
+        // function is monotonic. Find the largest symbol in this range whose left-sided
+        // cumulative is at most `quantile` by binary search.
+        let mut low = self.buckets[bucket];
+        let mut high = self.buckets[bucket + 1];
+        while low < high {
+            // Round up to make progress; this expression can't overflow.
+            let mid = (low >> 1) + (high >> 1) + ((low | high) & Symbol::one());
+            if self.inner.left_cumulative(mid) <= quantile {
+                low = mid;
+            } else {
+                high = mid - Symbol::one();
+            }
+        }
+
+        let (left_sided_cumulative, probability) = self
+            .inner
+            .left_cumulative_and_probability(low)
+            .expect("`low` is within the support.");
+        (low, left_sided_cumulative, probability)
+    }
+}
+
+impl<'m, F, Symbol, Probability, D, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for IndexedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64> + 'm,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + AsPrimitive<usize> + Into<f64> + WrappingSub,
+    Probability: BitArray + Into<f64>,
+    D: Distribution + 'm,
+    D::Value: AsPrimitive<Symbol>,
+{
+    type Iter = LeakilyQuantizedDistributionIter<
+        Symbol,
+        Probability,
+        &'m LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>,
+        PRECISION,
+    >;
+
+    fn symbol_table(&'m self) -> Self::Iter {
+        self.inner.symbol_table()
+    }
+}
+
+/// A [`LeakilyQuantizedDistribution`] that memoizes evaluations of its CDF.
+///
+/// Create a `CachedLeakilyQuantizedDistribution` with
+/// [`LeakilyQuantizedDistribution::with_cdf_cache`], which also explains when to use it.
+/// A `CachedLeakilyQuantizedDistribution` represents the exact same entropy model as the
+/// `LeakilyQuantizedDistribution` from which it was created, so you can use the original
+/// model on the encoder side and the cached model on the decoder side (or vice versa).
+#[derive(Debug, Clone)]
+pub struct CachedLeakilyQuantizedDistribution<F, Symbol, Probability, D, const PRECISION: usize> {
+    inner: LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>,
+
+    /// Maps symbols to their left-sided cumulatives (i.e., including the leakiness).
+    cache: RefCell<BTreeMap<Symbol, Probability>>,
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize>
+    CachedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+{
+    /// Returns the underlying `LeakilyQuantizedDistribution` without the cache.
+    pub fn into_inner(self) -> LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION> {
+        self.inner
+    }
+
+    /// Returns a reference to the underlying `LeakilyQuantizedDistribution`.
+    pub fn inner(&self) -> &LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION> {
+        &self.inner
+    }
+
+    /// Returns the number of symbols whose CDF is currently cached.
+    pub fn num_cached(&self) -> usize {
+        self.cache.borrow().len()
+    }
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize>
+    CachedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub,
+    Probability: BitArray + Into<f64>,
+    D: Distribution,
+    D::Value: AsPrimitive<Symbol>,
+{
+    /// Like [`LeakilyQuantizedDistribution::left_cumulative`] but looks up (or fills) the
+    /// cache first.
+    #[inline]
+    fn left_cumulative(&self, symbol: Symbol) -> Probability {
+        *self
+            .cache
+            .borrow_mut()
+            .entry(symbol)
+            .or_insert_with(|| self.inner.left_cumulative(symbol))
+    }
+
+    /// Returns the right-sided cumulative of `symbol`, which must be within the support.
+    #[inline]
+    fn right_cumulative(&self, symbol: Symbol) -> Probability {
+        if symbol == self.inner.quantizer.max_symbol_inclusive {
+            wrapping_pow2(PRECISION)
+        } else {
+            self.left_cumulative(symbol + Symbol::one())
+        }
+    }
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize> EntropyModel<PRECISION>
+    for CachedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    Probability: BitArray,
+{
+    type Probability = Probability;
+    type Symbol = Symbol;
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize> EncoderModel<PRECISION>
+    for CachedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub,
+    Probability: BitArray + Into<f64>,
+    D: Distribution,
+    D::Value: AsPrimitive<Symbol>,
+{
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Symbol>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let symbol = *symbol.borrow();
+        if symbol < self.inner.quantizer.min_symbol_inclusive
+            || symbol > self.inner.quantizer.max_symbol_inclusive
+        {
+            return None;
+        }
+
+        let left_sided_cumulative = self.left_cumulative(symbol);
+        let probability = self
+            .right_cumulative(symbol)
+            .wrapping_sub(&left_sided_cumulative)
+            .into_nonzero()
+            .expect("Invalid underlying continuous probability distribution.");
+        Some((left_sided_cumulative, probability))
+    }
+}
+
+impl<F, Symbol, Probability, D, const PRECISION: usize> DecoderModel<PRECISION>
+    for CachedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + Into<f64> + WrappingSub,
+    Probability: BitArray + Into<f64>,
+    D: Distribution,
+    D::Value: AsPrimitive<Symbol>,
+{
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (Self::Symbol, Probability, Probability::NonZero) {
+        // Find the largest symbol whose left-sided cumulative is at most `quantile` by
+        // binary search (the left-sided cumulative of the first symbol is zero).
+        let mut low = self.inner.quantizer.min_symbol_inclusive;
+        let mut high = self.inner.quantizer.max_symbol_inclusive;
+        while low < high {
+            // Round up to make progress; this expression can't overflow.
+            let mid = (low >> 1) + (high >> 1) + ((low | high) & Symbol::one());
+            if self.left_cumulative(mid) <= quantile {
+                low = mid;
+            } else {
+                high = mid - Symbol::one();
+            }
+        }
+
+        let left_sided_cumulative = self.left_cumulative(low);
+        let probability = self
+            .right_cumulative(low)
+            .wrapping_sub(&left_sided_cumulative)
+            .into_nonzero()
+            .expect("Invalid underlying continuous probability distribution.");
+        (low, left_sided_cumulative, probability)
+    }
+}
+
+impl<'m, F, Symbol, Probability, D, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for CachedLeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64> + 'm,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt + AsPrimitive<Probability> + AsPrimitive<usize> + Into<f64> + WrappingSub,
+    Probability: BitArray + Into<f64>,
+    D: Distribution + 'm,
+    D::Value: AsPrimitive<Symbol>,
+{
+    type Iter = LeakilyQuantizedDistributionIter<
+        Symbol,
+        Probability,
+        &'m LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>,
+        PRECISION,
+    >;
+
+    fn symbol_table(&'m self) -> Self::Iter {
+        self.inner.symbol_table()
+    }
+}
+
+/// Iterator over the [`symbol_table`] of a [`LeakilyQuantizedDistribution`].
+///
 /// This type will become private once anonymous return types are allowed in trait methods.
 /// Do not use it outside of the `constriction` library.
 ///
@@ -1786,14 +2878,15 @@ pub struct LeakilyQuantizedDistributionIter<Symbol, Probability, M, const PRECIS
     left_sided_cumulative: Probability,
 }
 
-impl<'m, 'q, Symbol, Probability, D, const PRECISION: usize> Iterator
+impl<F, Symbol, Probability, D, const PRECISION: usize> Iterator
     for LeakilyQuantizedDistributionIter<
         Symbol,
         Probability,
-        &'m LeakilyQuantizedDistribution<f64, Symbol, Probability, D, PRECISION>,
+        &LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>,
         PRECISION,
     >
 where
+    F: Float + Into<f64>,
     f64: AsPrimitive<Probability>,
     Symbol: PrimInt + AsPrimitive<Probability> + AsPrimitive<usize> + Into<f64> + WrappingSub,
     Probability: BitArray + Into<f64>,
@@ -1811,9 +2904,10 @@ where
         } else {
             let next_symbol = symbol + Symbol::one();
             self.symbol = Some(next_symbol);
-            let non_leaky: Probability = (self.model.quantizer.free_weight
-                * self.model.inner.distribution((symbol).into() - 0.5))
-            .as_();
+            // The right boundary of `symbol` is the left boundary of `next_symbol`, i.e., the
+            // CDF at `next_symbol - 0.5 == symbol + 0.5`, which is also what
+            // `left_cumulative_and_probability` evaluates.
+            let non_leaky: Probability = self.model.non_leaky_cumulative(symbol.into() + 0.5);
             non_leaky + slack(next_symbol, self.model.quantizer.min_symbol_inclusive)
         };
 
@@ -1832,7 +2926,7 @@ where
 
     fn size_hint(&self) -> (usize, Option<usize>) {
         if let Some(symbol) = self.symbol {
-            let len = slack::<usize, _>(symbol, self.model.quantizer.max_symbol_inclusive)
+            let len = slack::<usize, _>(self.model.quantizer.max_symbol_inclusive, symbol)
                 .saturating_add(1);
             (len, None)
         } else {
@@ -1841,6 +2935,368 @@ where
     }
 }
 
+// CIRCULAR DISTRIBUTIONS (FOR USE WITH A `LeakyQuantizer`) ========================================
+
+/// Wraps a probability distribution over the real line around a circle of `period` bins.
+///
+/// Use this type to model circular data (such as phases or orientations) that you
+/// discretize into `num_bins` equally sized bins labeled by the integer symbols
+/// `0..num_bins`. A plain [`LeakyQuantizer`] over the support `0..=num_bins - 1` would cut
+/// off the tails of the underlying distribution at the seam of the circle (i.e., between
+/// the bins `num_bins - 1` and `0`) and lump all probability mass of the cut off tails into
+/// the first and last bin, respectively. By contrast, a `WrappedDistribution` folds the
+/// probability mass of the underlying distribution `inner` onto the interval `[-0.5,
+/// period - 0.5)` by adding up contributions from all shifts by integer multiples of
+/// `period`. Thus, if you quantize a `WrappedDistribution` with `period = num_bins as f64`
+/// using a `LeakyQuantizer` over the support `0..=num_bins - 1`, then each bin obtains the
+/// probability mass of all points on the circle that fall into this bin, regardless of
+/// where the seam is.
+///
+/// A wrapped normal distribution is obtained by wrapping a [`Gaussian`]. See [`VonMises`]
+/// for an alternative circular distribution.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{DefaultLeakyQuantizer, EncoderModel, WrappedDistribution},
+///     stack::DefaultAnsCoder,
+///     Decode,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// const NUM_BINS: i32 = 360; // Discretize angles in steps of one degree.
+/// let quantizer = DefaultLeakyQuantizer::new(0..=NUM_BINS - 1);
+///
+/// // Angles close to zero degrees, i.e., close to the seam of the circle.
+/// let symbols = [358, 2, 0, 355, 1, 359];
+/// let wrapped = quantizer.quantize(WrappedDistribution::new(Gaussian::new(0.0, 3.0), 360.0));
+///
+/// let mut coder = DefaultAnsCoder::new();
+/// coder.encode_iid_symbols_reverse(&symbols, &wrapped).unwrap();
+/// let decoded = coder.decode_iid_symbols(6, &wrapped).collect::<Result<Vec<_>, _>>();
+/// assert_eq!(decoded.unwrap(), symbols);
+///
+/// // Without wrapping, symbols just below the seam would be extremely improbable.
+/// let plain = quantizer.quantize(Gaussian::new(0.0, 3.0));
+/// assert!(wrapped.floating_point_probability::<f64>(358) > 0.1);
+/// assert!(plain.floating_point_probability::<f64>(358) < 1e-7);
+/// ```
+///
+/// # Computational Efficiency
+///
+/// Each evaluation of the (wrapped) cumulative distribution function evaluates the
+/// cumulative distribution function of `inner` a few times for each integer multiple of
+/// `period` that carries non-negligible probability mass. This is cheap if the scale of
+/// `inner` is comparable to or smaller than `period`. Contributions from more than
+/// [`MAX_WRAPS`](Self::MAX_WRAPS) periods away from the origin in either direction are
+/// ignored.
+///
+/// [`Gaussian`]: probability::distribution::Gaussian
+#[derive(Debug, Clone, Copy)]
+pub struct WrappedDistribution<D> {
+    inner: D,
+    period: f64,
+
+    /// Sum of the probability masses of all considered shifts of the circle, so that the
+    /// wrapped cumulative distribution function is exactly normalized.
+    normalization: f64,
+}
+
+impl<D: Distribution> WrappedDistribution<D> {
+    /// The maximum number of periods to either side of the origin from which probability
+    /// mass gets collected.
+    pub const MAX_WRAPS: i32 = 1 << 12;
+
+    /// Wraps the distribution `inner` around a circle of circumference `period`.
+    ///
+    /// The circle is parameterized by the interval `[-0.5, period - 0.5)` so that, if
+    /// `period` is an integer, the bins for quantization with a [`LeakyQuantizer`] over
+    /// the support `0..=period - 1` tile the circle exactly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is not a positive finite number. Use
+    /// [`checked_new`](Self::checked_new) if `period` isn't known to be valid.
+    pub fn new(inner: D, period: f64) -> Self {
+        Self::checked_new(inner, period).expect("`period` must be a positive finite number.")
+    }
+
+    /// Same as [`new`](Self::new) but returns `None` instead of panicking if `period` is
+    /// not a positive finite number.
+    pub fn checked_new(inner: D, period: f64) -> Option<Self> {
+        if !(period > 0.0 && period.is_finite()) {
+            return None;
+        }
+        let mut wrapped = Self {
+            inner,
+            period,
+            normalization: 1.0,
+        };
+        wrapped.normalization = wrapped.unnormalized_distribution(period - 0.5);
+        Some(wrapped)
+    }
+
+    /// Returns a reference to the underlying (unwrapped) distribution.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Consumes the `WrappedDistribution` and returns the underlying distribution.
+    pub fn into_inner(self) -> D {
+        self.inner
+    }
+
+    /// Returns the circumference of the circle, as passed to the constructor.
+    pub fn period(&self) -> f64 {
+        self.period
+    }
+
+    fn unnormalized_distribution(&self, x: f64) -> f64 {
+        let start = -0.5;
+        let x = x.clamp(start, start + self.period);
+
+        let mut total = 0.0;
+        for k in 0..=Self::MAX_WRAPS {
+            let offset = k as f64 * self.period;
+            let left = self.inner.distribution(start + offset);
+            if left >= 1.0 {
+                break;
+            }
+            total += self.inner.distribution(x + offset) - left;
+        }
+        for k in 1..=Self::MAX_WRAPS {
+            let offset = -(k as f64) * self.period;
+            if self.inner.distribution(start + self.period + offset) <= 0.0 {
+                break;
+            }
+            total += self.inner.distribution(x + offset) - self.inner.distribution(start + offset);
+        }
+        total
+    }
+}
+
+impl<D: Distribution> Distribution for WrappedDistribution<D> {
+    type Value = f64;
+
+    fn distribution(&self, x: f64) -> f64 {
+        (self.unnormalized_distribution(x) / self.normalization).clamp(0.0, 1.0)
+    }
+}
+
+impl<D: Distribution> Inverse for WrappedDistribution<D> {
+    fn inverse(&self, p: f64) -> f64 {
+        invert_by_bisection(|x| self.distribution(x), p, -0.5, self.period - 0.5)
+    }
+}
+
+/// A von Mises distribution over a circle that is discretized into `period` bins.
+///
+/// The von Mises distribution is the maximum entropy distribution on the circle for a
+/// given circular mean and circular variance, and it is commonly used to model
+/// directional data such as phases or orientations. Its density is proportional to
+/// `exp(kappa * cos(phi - phi_0))`, where `phi` is the angle, `phi_0` is the mean angle,
+/// and `kappa >= 0` is the concentration (which plays a similar role as the inverse
+/// variance of a normal distribution; `kappa = 0` results in a uniform distribution).
+///
+/// Like a [`WrappedDistribution`], a `VonMises` distribution is parameterized in units of
+/// bins rather than in radians, and it is defined on the interval `[-0.5, period - 0.5)`,
+/// where the point `x` corresponds to the angle `phi = 2π x / period`. Quantizing it with
+/// a [`LeakyQuantizer`] over the support `0..=period - 1` (for integer `period`) therefore
+/// takes the wrap-around at the seam of the circle correctly into account.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{SmallLeakyQuantizer, VonMises},
+///     queue::{SmallRangeDecoder, SmallRangeEncoder},
+///     Decode, Encode,
+/// };
+///
+/// const NUM_BINS: u16 = 64;
+/// let quantizer = SmallLeakyQuantizer::new(0..=NUM_BINS - 1);
+///
+/// // Phases with individual predicted means; some of them are close to the seam.
+/// let symbols = [63u16, 0, 17, 1, 40];
+/// let means = [0.5, 63.2, 16.0, 62.9, 41.7];
+/// let models = means
+///     .iter()
+///     .map(|&mean| quantizer.quantize(VonMises::new(mean, 50.0, NUM_BINS as f64)));
+///
+/// let mut encoder = SmallRangeEncoder::new();
+/// encoder.encode_symbols(symbols.iter().zip(models.clone())).unwrap();
+/// let compressed = encoder.into_compressed().unwrap();
+///
+/// let mut decoder = SmallRangeDecoder::from_compressed(compressed).unwrap();
+/// let decoded = decoder.decode_symbols(models).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+#[derive(Debug, Clone)]
+pub struct VonMises {
+    mean: f64,
+    kappa: f64,
+    period: f64,
+
+    /// Fourier coefficients `I_j(kappa) / (j * I_0(kappa))` of the cumulative distribution
+    /// function for `j = 1, 2, ...`, truncated once they become negligible.
+    coefficients: Vec<f64>,
+
+    /// Value of the (unwrapped) cumulative distribution function at the seam `x = -0.5`.
+    offset: f64,
+}
+
+impl VonMises {
+    /// Constructs a von Mises distribution with mean `mean` (in units of bins) and
+    /// concentration `kappa` over a circle that is discretized into `period` bins.
+    ///
+    /// The `mean` can be any finite number; it is implicitly reduced modulo `period`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is not a positive finite number, if `kappa` is negative or not
+    /// finite, or if `mean` is not finite. Use [`checked_new`](Self::checked_new) if the
+    /// parameters aren't known to be valid.
+    pub fn new(mean: f64, kappa: f64, period: f64) -> Self {
+        Self::checked_new(mean, kappa, period).expect("Invalid parameters for `VonMises`.")
+    }
+
+    /// Same as [`new`](Self::new) but returns `None` instead of panicking if any parameter
+    /// is invalid.
+    pub fn checked_new(mean: f64, kappa: f64, period: f64) -> Option<Self> {
+        let is_valid = period > 0.0
+            && period.is_finite()
+            && kappa >= 0.0
+            && kappa.is_finite()
+            && mean.is_finite();
+        if !is_valid {
+            return None;
+        }
+
+        // The ratios `I_j(kappa) / I_{j-1}(kappa)` of modified Bessel functions of the
+        // first kind satisfy a recurrence relation that is numerically stable when it is
+        // evaluated in backward direction, starting from a sufficiently large `j`. The
+        // coefficients decay roughly like `exp(-j^2 / (2 kappa))` for large `kappa`.
+        let num_coefficients = if kappa == 0.0 {
+            0
+        } else {
+            16 + (10.0 * kappa.sqrt()) as usize
+        };
+        let mut ratios = alloc::vec![0.0; num_coefficients];
+        let mut ratio = 0.0;
+        for j in (1..num_coefficients + 16).rev() {
+            ratio = 1.0 / (2.0 * j as f64 / kappa + ratio);
+            if j <= num_coefficients {
+                ratios[j - 1] = ratio;
+            }
+        }
+
+        let mut bessel_ratio = 1.0;
+        let mut coefficients = Vec::with_capacity(num_coefficients);
+        for (j, ratio) in ratios.into_iter().enumerate() {
+            bessel_ratio *= ratio;
+            if bessel_ratio < 1e-18 {
+                break;
+            }
+            coefficients.push(bessel_ratio / (j + 1) as f64);
+        }
+
+        let mut von_mises = Self {
+            mean,
+            kappa,
+            period,
+            coefficients,
+            offset: 0.0,
+        };
+        von_mises.offset = von_mises.unwrapped_distribution(-0.5);
+        Some(von_mises)
+    }
+
+    /// Returns the mean of the distribution (in units of bins), as passed to the
+    /// constructor.
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the concentration parameter `kappa`.
+    pub fn kappa(&self) -> f64 {
+        self.kappa
+    }
+
+    /// Returns the number of bins into which the circle is discretized.
+    pub fn period(&self) -> f64 {
+        self.period
+    }
+
+    /// Evaluates the cumulative distribution function lifted to the real line, i.e., a
+    /// monotonically increasing function that increases by exactly one per period.
+    fn unwrapped_distribution(&self, x: f64) -> f64 {
+        // Split `x` into a number of full periods (counted from the antipode of the mean)
+        // and a signed distance `d` from the mean, with `-0.5 <= d < 0.5` in units of
+        // periods. The cumulative distribution is then `n + sign(d) * half_mass(|d|)`.
+        // Evaluating `half_mass` on the half circle only, and snapping it to exactly
+        // `0.5` around the antipode, ensures that rounding errors in the truncated Fourier
+        // series don't make the result decrease where the density is negligibly small.
+        let relative = (x - self.mean) / self.period;
+        let num_periods = (relative + 0.5).floor();
+        let d = relative - num_periods;
+        let angle = 2.0 * core::f64::consts::PI * d.abs();
+
+        // Evaluate `sum_j coefficients[j - 1] * sin(j * angle)` using the recurrence
+        // `sin(j a) = 2 cos(a) sin((j - 1) a) - sin((j - 2) a)`.
+        let two_cos = 2.0 * angle.cos();
+        let (mut previous, mut current) = (0.0, angle.sin());
+        let mut series = 0.0;
+        for &coefficient in &self.coefficients {
+            series += coefficient * current;
+            let next = two_cos * current - previous;
+            previous = current;
+            current = next;
+        }
+
+        let mut half_mass = ((angle + 2.0 * series) / (2.0 * core::f64::consts::PI)).max(0.0);
+        if half_mass > 0.5 - 1e-12 {
+            half_mass = 0.5;
+        }
+
+        if d < 0.0 {
+            num_periods - half_mass
+        } else {
+            num_periods + half_mass
+        }
+    }
+}
+
+impl Distribution for VonMises {
+    type Value = f64;
+
+    fn distribution(&self, x: f64) -> f64 {
+        let x = x.clamp(-0.5, self.period - 0.5);
+        (self.unwrapped_distribution(x) - self.offset).clamp(0.0, 1.0)
+    }
+}
+
+impl Inverse for VonMises {
+    fn inverse(&self, p: f64) -> f64 {
+        invert_by_bisection(|x| self.distribution(x), p, -0.5, self.period - 0.5)
+    }
+}
+
+/// Finds `x` in `[lower, upper]` such that `cdf(x)` is approximately `p`, assuming that
+/// `cdf` is monotonically increasing.
+fn invert_by_bisection(cdf: impl Fn(f64) -> f64, p: f64, mut lower: f64, mut upper: f64) -> f64 {
+    let tolerance = 1e-9 * (upper - lower);
+    while upper - lower > tolerance {
+        let mid = 0.5 * (lower + upper);
+        if cdf(mid) < p {
+            lower = mid;
+        } else {
+            upper = mid;
+        }
+    }
+    0.5 * (lower + upper)
+}
+
 /// A trait for internal representations of various forms of categorical entropy models.
 ///
 /// This trait will become private once anonymous return types are allowed in trait methods.
@@ -2013,7 +3469,7 @@ impl<Symbol, Probability, Table> SymbolTableIter<Symbol, Probability, Table> {
     }
 }
 
-impl<'a, Symbol, Probability, Table> Iterator for SymbolTableIter<Symbol, Probability, Table>
+impl<Symbol, Probability, Table> Iterator for SymbolTableIter<Symbol, Probability, Table>
 where
     Probability: BitArray,
     Table: SymbolTable<Symbol, Probability>,
@@ -2040,10 +3496,103 @@ where
         }
     }
 
-    #[inline(always)]
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = self.table.support_size() - self.index;
-        (len, Some(len))
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.table.support_size() - self.index;
+        (len, Some(len))
+    }
+}
+
+/// Rounding convention for converting floating point probabilities to fixed point.
+///
+/// All categorical entropy models in this module can be constructed from floating point
+/// probabilities. Any such conversion has to round the (rescaled) probabilities to integers
+/// such that the result is exactly normalized and *leaky*, i.e., such that each symbol gets
+/// a fixed point probability of at least `1`. There are several reasonable ways to do
+/// this. The constructors named `*_with_rounding` let you pick one explicitly, which is
+/// necessary if you need bit-exact interoperability with some other implementation that
+/// prescribes a specific convention. All other constructors use
+/// [`RoundingPolicy::MinimalCrossEntropy`].
+///
+/// In the following, `N = 1 << PRECISION` is the total probability mass in fixed point
+/// arithmetic, `n` is the number of symbols, and `q_i = p_i * (N - n) / sum_j p_j` is the
+/// share of the freely distributable mass that falls onto symbol `i` (each symbol gets
+/// weight `1` up front to ensure leakiness). Ties are always broken in favor of the symbol
+/// that comes first in the provided list of probabilities.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::model::{
+///     DefaultContiguousCategoricalEntropyModel, EncoderModel, RoundingPolicy,
+/// };
+///
+/// let probabilities = [0.2, 0.45, 0.35];
+/// for rounding in [
+///     RoundingPolicy::MinimalCrossEntropy,
+///     RoundingPolicy::FloorWithRedistribution,
+///     RoundingPolicy::RoundHalfEven,
+///     RoundingPolicy::LargestRemainder,
+/// ] {
+///     let model = DefaultContiguousCategoricalEntropyModel
+///         ::from_floating_point_probabilities_with_rounding(&probabilities, rounding)
+///         .unwrap();
+///
+///     // With the default `PRECISION` of 24 bits, the policies differ only in the last
+///     // few bits of the fixed point probabilities.
+///     for (symbol, &exact) in probabilities.iter().enumerate() {
+///         let approx: f64 = model.floating_point_probability(symbol);
+///         assert!((approx - exact).abs() < 1e-6);
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoundingPolicy {
+    /// Minimizes the cross entropy from the provided floating point probabilities to the
+    /// resulting fixed point probabilities, i.e., the expected bit rate when encoding
+    /// symbols that are actually distributed according to the floating point
+    /// probabilities. This is the default and usually the best choice unless you need to
+    /// match a specific external convention or you construct a new model for each encoded
+    /// symbol, in which case the (at least linearithmic) construction time may dominate:
+    /// this policy starts from the rounded down weights and then repeatedly moves single
+    /// units of probability mass between symbols for as long as this reduces the cross
+    /// entropy. The other policies need only a single pass over the probabilities (plus a
+    /// sort in the case of [`LargestRemainder`](Self::LargestRemainder)).
+    ///
+    /// **Compatibility note:** versions of `constriction` up to 0.2.4 could, in rare cases,
+    /// get stuck in an infinite loop when moving probability mass between symbols due to
+    /// rounding errors. The fix for this issue can change the resulting fixed point
+    /// probabilities for some inputs that previously terminated as well. Thus, in rare
+    /// cases, data that was compressed with a model constructed by an earlier version of
+    /// `constriction` using this policy cannot be decoded with a model constructed from
+    /// the same floating point probabilities by the current version (and vice versa).
+    MinimalCrossEntropy,
+
+    /// Assigns weight `1 + floor(q_i)` to each symbol `i` and then adds the entire mass
+    /// that is left over from rounding down (less than `n` units) to the most probable
+    /// symbol.
+    ///
+    /// This is the fastest policy to construct, but it typically leads to a slightly
+    /// higher bit rate than the other policies since it concentrates all rounding errors
+    /// on a single symbol.
+    FloorWithRedistribution,
+
+    /// Rounds `p_i * N / sum_j p_j` to the nearest integer, breaking exact ties towards the
+    /// even neighbor, and raises any resulting zero to `1`. If the rounded weights don't
+    /// add up to exactly `N` then the missing mass is added to the most probable symbol or
+    /// the excess mass is taken away from the symbols with the largest weights, one unit at
+    /// a time.
+    RoundHalfEven,
+
+    /// Assigns weight `1 + floor(q_i)` to each symbol `i` and then distributes the units
+    /// that are left over from rounding down one by one to the symbols with the largest
+    /// fractional parts `q_i - floor(q_i)` (also known as the Hamilton method).
+    LargestRemainder,
+}
+
+impl Default for RoundingPolicy {
+    fn default() -> Self {
+        Self::MinimalCrossEntropy
     }
 }
 
@@ -2328,13 +3877,35 @@ impl<Probability: BitArray, const PRECISION: usize>
         f64: AsPrimitive<Probability>,
         usize: AsPrimitive<Probability>,
     {
-        let slots = optimize_leaky_categorical::<_, _, PRECISION>(probabilities)?;
-        Self::from_nonzero_fixed_point_probabilities(
-            slots.into_iter().map(|slot| slot.weight),
-            false,
+        Self::from_floating_point_probabilities_with_rounding(
+            probabilities,
+            RoundingPolicy::MinimalCrossEntropy,
         )
     }
 
+    /// Like [`from_floating_point_probabilities`] but with an explicit convention for
+    /// rounding the probabilities to fixed point.
+    ///
+    /// Calling this method with [`RoundingPolicy::MinimalCrossEntropy`] is equivalent to
+    /// calling [`from_floating_point_probabilities`]. See [`RoundingPolicy`] for the
+    /// alternatives and for an example.
+    ///
+    /// [`from_floating_point_probabilities`]: Self::from_floating_point_probabilities
+    #[allow(clippy::result_unit_err)]
+    pub fn from_floating_point_probabilities_with_rounding<F>(
+        probabilities: &[F],
+        rounding: RoundingPolicy,
+    ) -> Result<Self, ()>
+    where
+        F: Float + core::iter::Sum<F> + Into<f64>,
+        Probability: Into<f64> + AsPrimitive<usize>,
+        f64: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+    {
+        let weights = quantize_leaky_categorical::<_, _, PRECISION>(probabilities, rounding)?;
+        Self::from_nonzero_fixed_point_probabilities(weights, false)
+    }
+
     /// Constructs a distribution with a PMF given in fixed point arithmetic.
     ///
     /// This is a low level method that allows, e.g,. reconstructing a probability
@@ -2464,6 +4035,138 @@ impl<Probability: BitArray, const PRECISION: usize>
             phantom: PhantomData,
         })
     }
+
+    /// Constructs a distribution with exact rational probabilities `numerators[i] /
+    /// denominator` for the symbols `i = 0, 1, ..., numerators.len() - 1`.
+    ///
+    /// Use this constructor if a protocol specifies probabilities exactly (e.g., in a
+    /// standards document), so that no floating point arithmetic or rounding is involved.
+    /// The `denominator` has to be a power of two that is at most `1 << PRECISION`, and the
+    /// `numerators` have to be nonzero and sum up to exactly `denominator`. If `denominator`
+    /// is smaller than `1 << PRECISION`, then all numerators get multiplied by
+    /// `(1 << PRECISION) / denominator`, which doesn't change the represented
+    /// probabilities.
+    ///
+    /// This is a convenience wrapper around
+    /// [`from_nonzero_fixed_point_probabilities`], which you can call directly if you need
+    /// `PRECISION == 64` (where `1 << PRECISION` doesn't fit into a `u64`).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{
+    ///     DefaultContiguousCategoricalEntropyModel, IterableEntropyModel
+    /// };
+    ///
+    /// // Probabilities 3/16, 8/16, 5/16 as specified, e.g., by a standards document.
+    /// let model =
+    ///     DefaultContiguousCategoricalEntropyModel::from_rational_probabilities(&[3, 8, 5], 16)
+    ///         .unwrap();
+    /// let probabilities = model.floating_point_symbol_table::<f64>()
+    ///     .map(|(_, _, probability)| probability)
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(probabilities, [3.0 / 16.0, 0.5, 5.0 / 16.0]);
+    ///
+    /// // The sum of the numerators has to match the denominator exactly.
+    /// assert!(
+    ///     DefaultContiguousCategoricalEntropyModel::from_rational_probabilities(&[3, 8, 4], 16)
+    ///         .is_err()
+    /// );
+    /// ```
+    ///
+    /// # Error Handling
+    ///
+    /// Returns an error if `denominator` is not a power of two, if it is larger than
+    /// `1 << PRECISION`, if any numerator is zero, if the numerators don't sum up to
+    /// `denominator`, or if there are fewer than two numerators.
+    ///
+    /// [`from_nonzero_fixed_point_probabilities`]: Self::from_nonzero_fixed_point_probabilities
+    #[allow(clippy::result_unit_err)]
+    pub fn from_rational_probabilities<I>(numerators: I, denominator: u64) -> Result<Self, ()>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Probability>,
+    {
+        let log2_denominator = denominator.trailing_zeros() as usize;
+        // A denominator of one can't be split into two nonzero numerators.
+        if !denominator.is_power_of_two() || log2_denominator == 0 || log2_denominator > PRECISION {
+            return Err(());
+        }
+
+        let shift = PRECISION - log2_denominator;
+        let mut invalid = false;
+        let probabilities = numerators.into_iter().map(|numerator| {
+            let numerator = *numerator.borrow();
+            // Each numerator has to be smaller than the denominator (since there are at
+            // least two nonzero numerators), which ensures that the shift doesn't overflow.
+            if log2_denominator < Probability::BITS
+                && numerator >> log2_denominator != Probability::zero()
+            {
+                invalid = true;
+            }
+            numerator << shift
+        });
+        let model = Self::from_nonzero_fixed_point_probabilities(probabilities, false);
+
+        if invalid {
+            Err(())
+        } else {
+            model
+        }
+    }
+
+    /// Constructs a distribution that assigns the fixed point probability
+    /// `probabilities[i]` (in units of `2^(-PRECISION)`) to each symbol `i = 0, 1, ...,
+    /// probabilities.len() - 1`.
+    ///
+    /// The `probabilities` are used verbatim, i.e., without any floating point arithmetic
+    /// or rounding. Thus, the resulting model is guaranteed to be reproducible bit for bit
+    /// on all platforms, which makes this constructor a good fit for storing a model as part
+    /// of the compressed data (e.g., by serializing the probabilities of a model that was
+    /// originally constructed with [`from_floating_point_probabilities`] and then reading
+    /// them back with [`symbol_table`]).
+    ///
+    /// This is equivalent to calling [`from_nonzero_fixed_point_probabilities`] with
+    /// `infer_last_probability = false`, except that it rejects models with a single
+    /// symbol.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{
+    ///     DefaultContiguousCategoricalEntropyModel, IterableEntropyModel
+    /// };
+    ///
+    /// let original = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+    ///     &[0.2, 0.5, 0.3],
+    /// )
+    /// .unwrap();
+    /// let probabilities = original
+    ///     .symbol_table()
+    ///     .map(|(_, _, probability)| probability.get())
+    ///     .collect::<Vec<_>>();
+    ///
+    /// let reconstructed =
+    ///     DefaultContiguousCategoricalEntropyModel::from_fixed_point_probabilities(&probabilities)
+    ///         .unwrap();
+    /// assert!(reconstructed.symbol_table().eq(original.symbol_table()));
+    /// ```
+    ///
+    /// # Error Handling
+    ///
+    /// Returns an error if any entry of `probabilities` is zero, if the `probabilities`
+    /// don't sum up to exactly `1 << PRECISION`, or if there are fewer than two of them.
+    ///
+    /// [`from_floating_point_probabilities`]: Self::from_floating_point_probabilities
+    /// [`from_nonzero_fixed_point_probabilities`]: Self::from_nonzero_fixed_point_probabilities
+    /// [`symbol_table`]: IterableEntropyModel::symbol_table
+    #[allow(clippy::result_unit_err)]
+    pub fn from_fixed_point_probabilities(probabilities: &[Probability]) -> Result<Self, ()> {
+        if probabilities.len() < 2 {
+            return Err(());
+        }
+        Self::from_nonzero_fixed_point_probabilities(probabilities, false)
+    }
 }
 
 impl<Symbol, Probability: BitArray, const PRECISION: usize>
@@ -2520,6 +4223,30 @@ where
         symbols: &[Symbol],
         probabilities: &[F],
     ) -> Result<Self, ()>
+    where
+        F: Float + core::iter::Sum<F> + Into<f64>,
+        Probability: Into<f64> + AsPrimitive<usize>,
+        f64: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+    {
+        Self::from_symbols_and_floating_point_probabilities_with_rounding(
+            symbols,
+            probabilities,
+            RoundingPolicy::MinimalCrossEntropy,
+        )
+    }
+
+    /// Like [`from_symbols_and_floating_point_probabilities`] but with an explicit
+    /// convention for rounding the probabilities to fixed point, see [`RoundingPolicy`].
+    ///
+    /// [`from_symbols_and_floating_point_probabilities`]:
+    ///     Self::from_symbols_and_floating_point_probabilities
+    #[allow(clippy::result_unit_err)]
+    pub fn from_symbols_and_floating_point_probabilities_with_rounding<F>(
+        symbols: &[Symbol],
+        probabilities: &[F],
+        rounding: RoundingPolicy,
+    ) -> Result<Self, ()>
     where
         F: Float + core::iter::Sum<F> + Into<f64>,
         Probability: Into<f64> + AsPrimitive<usize>,
@@ -2530,10 +4257,10 @@ where
             return Err(());
         };
 
-        let slots = optimize_leaky_categorical::<_, _, PRECISION>(probabilities)?;
+        let weights = quantize_leaky_categorical::<_, _, PRECISION>(probabilities, rounding)?;
         Self::from_symbols_and_nonzero_fixed_point_probabilities(
             symbols.iter().cloned(),
-            slots.into_iter().map(|slot| slot.weight),
+            weights,
             false,
         )
     }
@@ -2661,6 +4388,115 @@ where
     }
 }
 
+/// Implements `const` constructors from static tables for the given probability types.
+///
+/// These constructors have to be implemented separately for each concrete type because
+/// `const fn`s can't call trait methods (e.g., for comparisons) on generic types.
+macro_rules! impl_static_contiguous_categorical {
+    ($($probability:ty),*) => {$(
+        impl<const PRECISION: usize>
+            ContiguousCategoricalEntropyModel<$probability, &'static [$probability], PRECISION>
+        {
+            /// Constructs a model from a static table of left-sided cumulatives in a `const`
+            /// context, i.e., without any runtime cost.
+            ///
+            /// The `cdf` has to start with zero, followed by the strictly increasing
+            /// left-sided cumulatives of all further symbols, and it has to end with the
+            /// total probability mass `1 << PRECISION` (which wraps around to zero if
+            /// `PRECISION` is the full bit width of the probability type). Thus, `cdf` has
+            /// one more entry than the model has symbols, and the model needs at least two
+            /// symbols. Violating any of these requirements panics, i.e., it results in a
+            /// compile error if the constructor is evaluated in a `const` or `static`
+            /// initializer.
+            ///
+            /// You'll usually not call this method directly but use the macro
+            /// `static_categorical!`, which computes `cdf` from a list of counts at compile
+            /// time (requires feature `derive`).
+            ///
+            /// # Example
+            ///
+            /// ```
+            /// use constriction::stream::{
+            ///     model::DefaultContiguousCategoricalEntropyModel, stack::DefaultAnsCoder,
+            ///     Decode, Encode,
+            /// };
+            ///
+            /// // Probabilities 1/4, 1/2, and 1/4 at 24 bit precision.
+            /// static MODEL: DefaultContiguousCategoricalEntropyModel<&[u32]> =
+            ///     DefaultContiguousCategoricalEntropyModel::from_static_cdf(&[
+            ///         0,
+            ///         1 << 22,
+            ///         3 << 22,
+            ///         1 << 24,
+            ///     ]);
+            ///
+            /// let mut coder = DefaultAnsCoder::new();
+            /// coder.encode_iid_symbols_reverse(&[2, 0, 1, 1], &MODEL).unwrap();
+            /// let decoded = coder
+            ///     .decode_iid_symbols(4, &MODEL)
+            ///     .collect::<Result<Vec<_>, _>>()
+            ///     .unwrap();
+            /// assert_eq!(decoded, [2, 0, 1, 1]);
+            /// ```
+            ///
+            /// Invalid tables are rejected at compile time:
+            ///
+            /// ```compile_fail
+            /// use constriction::stream::model::DefaultContiguousCategoricalEntropyModel;
+            ///
+            /// // The second symbol would have probability zero.
+            /// static MODEL: DefaultContiguousCategoricalEntropyModel<&[u32]> =
+            ///     DefaultContiguousCategoricalEntropyModel::from_static_cdf(&[
+            ///         0,
+            ///         1 << 22,
+            ///         1 << 22,
+            ///         1 << 24,
+            ///     ]);
+            /// ```
+            pub const fn from_static_cdf(cdf: &'static [$probability]) -> Self {
+                assert!(
+                    PRECISION > 0 && PRECISION <= <$probability>::BITS as usize,
+                    "`PRECISION` doesn't fit into the probability type."
+                );
+                assert!(
+                    cdf.len() >= 3,
+                    "A categorical entropy model needs at least two symbols."
+                );
+                assert!(cdf[0] == 0, "The `cdf` has to start at zero.");
+                let mut i = 2;
+                while i < cdf.len() - 1 {
+                    assert!(cdf[i] > cdf[i - 1], "All probabilities must be nonzero.");
+                    i += 1;
+                }
+                assert!(cdf[1] > 0, "All probabilities must be nonzero.");
+                if PRECISION == <$probability>::BITS as usize {
+                    assert!(
+                        cdf[cdf.len() - 1] == 0,
+                        "The `cdf` has to end at `1 << PRECISION`."
+                    );
+                } else {
+                    let total: $probability = 1 << PRECISION;
+                    assert!(
+                        cdf[cdf.len() - 1] == total,
+                        "The `cdf` has to end at `1 << PRECISION`."
+                    );
+                    assert!(
+                        cdf[cdf.len() - 2] < total,
+                        "All probabilities must be nonzero."
+                    );
+                }
+
+                Self {
+                    cdf: ContiguousSymbolTable(cdf),
+                    phantom: PhantomData,
+                }
+            }
+        }
+    )*};
+}
+
+impl_static_contiguous_categorical!(u8, u16, u32, u64);
+
 impl<Probability, Table, const PRECISION: usize>
     ContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
 where
@@ -2678,6 +4514,93 @@ where
         SymbolTable::<usize, Probability>::support_size(&self.cdf)
     }
 
+    /// Wraps the model in an index that makes decoding cost (expected) constant time,
+    /// regardless of the size of the alphabet.
+    ///
+    /// Decoding a symbol with a plain `ContiguousCategoricalEntropyModel` runs a binary
+    /// search over the entire cumulative distribution function (CDF), which takes
+    /// `Θ(log(N))` time for an alphabet of size `N`. For very large alphabets (e.g., the
+    /// tens of thousands of token IDs of a language model), this binary search dominates the
+    /// decoding time. The returned [`IndexedContiguousCategoricalEntropyModel`] splits the
+    /// range of quantiles into about `N` equally sized buckets (rounded up to a power of
+    /// two) and stores which symbol the first quantile of each bucket decodes to. Decoding
+    /// then only has to search among the symbols that start within the bucket of the given
+    /// quantile. Since quantiles are (approximately) uniformly distributed when decoding
+    /// real data, and since there are at least as many buckets as symbols, there's on
+    /// average at most one such symbol, so decoding takes expected constant time (and at
+    /// most `Θ(log(N))` time in the worst case). The index takes `Θ(N)` memory and
+    /// `Θ(N)` time to build, i.e., the same as the model itself.
+    ///
+    /// This is similar in spirit to the alias method for sampling from a categorical
+    /// distribution, but an alias table would scatter each symbol's probability mass across
+    /// several non-contiguous ranges of quantiles. Compressed data could then only be
+    /// decoded if it was encoded with a matching alias-aware encoder, and it would be
+    /// incompatible with range coding. By contrast, the index reproduces the exact same
+    /// quantile function as the original model, so an `IndexedContiguousCategoricalEntropyModel`
+    /// can decode data that was encoded with the original model on any coder (and vice
+    /// versa). Unlike a [`LookupDecoderModel`], the index doesn't need `Θ(2^PRECISION)`
+    /// memory, so it also works for high precisions, which large alphabets need.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultContiguousCategoricalEntropyModel, queue::DefaultRangeEncoder, Decode,
+    ///     Encode,
+    /// };
+    ///
+    /// // A model over a large alphabet (e.g., token IDs), with a few frequent symbols.
+    /// let probabilities = (0..5_000)
+    ///     .map(|i| if i % 1000 == 0 { 1.0 } else { 0.01 })
+    ///     .collect::<Vec<f64>>();
+    /// let model =
+    ///     DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+    ///         &probabilities,
+    ///     )
+    ///     .unwrap();
+    /// let symbols = [3000, 17, 4_999, 0, 3000];
+    ///
+    /// let mut encoder = DefaultRangeEncoder::new();
+    /// encoder.encode_iid_symbols(&symbols, &model).unwrap();
+    ///
+    /// let indexed_model = model.with_decoder_index();
+    /// let mut decoder = encoder.into_decoder().unwrap();
+    /// let decoded = decoder
+    ///     .decode_iid_symbols(symbols.len(), &indexed_model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, symbols);
+    /// ```
+    pub fn with_decoder_index(
+        self,
+    ) -> IndexedContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
+    where
+        usize: AsPrimitive<Probability>,
+    {
+        let support_size = self.support_size();
+        let log2_num_buckets =
+            PRECISION.min(support_size.next_power_of_two().trailing_zeros() as usize);
+        let shift = PRECISION - log2_num_buckets;
+
+        let cdf = self.cdf.0.as_ref();
+        let mut buckets = Vec::with_capacity((1 << log2_num_buckets) + 1);
+        let mut symbol = 0;
+        for bucket in 0..1usize << log2_num_buckets {
+            let quantile = bucket.as_() << shift;
+            while symbol + 1 < support_size && cdf[symbol + 1] <= quantile {
+                symbol += 1;
+            }
+            buckets.push(symbol);
+        }
+        buckets.push(support_size - 1);
+
+        IndexedContiguousCategoricalEntropyModel {
+            inner: self,
+            buckets,
+            shift,
+        }
+    }
+
     /// Makes a very cheap shallow copy of the model that can be used much like a shared
     /// reference.
     ///
@@ -2737,6 +4660,70 @@ where
     {
         self.into()
     }
+
+    /// Converts the model to a different fixed point precision without going through
+    /// floating point probabilities.
+    ///
+    /// Increasing the precision (`NEW_PRECISION >= PRECISION`) just scales all
+    /// probabilities by a power of two, so the resulting model is logically equivalent to
+    /// `self`. Decreasing the precision re-quantizes the probabilities in integer arithmetic
+    /// while preserving the guarantee that every symbol in the support retains a nonzero
+    /// probability. This is useful if you build a model with high precision but want to
+    /// decode with a lookup model, which requires a low precision (see
+    /// [`to_lookup_decoder_model`]).
+    ///
+    /// The resulting model may also use a different type `NewProbability` for its
+    /// probabilities, e.g., to turn a [`DefaultContiguousCategoricalEntropyModel`] into a
+    /// [`SmallContiguousCategoricalEntropyModel`].
+    ///
+    /// # Error Handling
+    ///
+    /// Returns an error if the model's support is too large to give each symbol a nonzero
+    /// probability at the new precision, i.e., if `support_size() > 1 << NEW_PRECISION`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::model::{
+    ///     DefaultContiguousCategoricalEntropyModel, IterableEntropyModel,
+    ///     SmallContiguousCategoricalEntropyModel,
+    /// };
+    ///
+    /// let probabilities = [0.3, 0.0001, 0.2, 0.4999];
+    /// let model = DefaultContiguousCategoricalEntropyModel
+    ///     ::from_floating_point_probabilities(&probabilities).unwrap();
+    ///
+    /// let small: SmallContiguousCategoricalEntropyModel = model.with_precision().unwrap();
+    /// let small_probabilities = small.symbol_table().map(|(_, _, p)| p.get()).collect::<Vec<_>>();
+    /// assert_eq!(small_probabilities.iter().sum::<u16>(), 1 << 12);
+    /// assert!(small_probabilities.iter().all(|&p| p != 0));
+    ///
+    /// // Going back to a higher precision is exact.
+    /// let restored = small.with_precision::<u32, 24>().unwrap();
+    /// assert!(restored
+    ///     .symbol_table()
+    ///     .zip(small.symbol_table())
+    ///     .all(|((_, _, p), (_, _, q))| p.get() == (q.get() as u32) << 12));
+    /// ```
+    ///
+    /// [`to_lookup_decoder_model`]: Self::to_lookup_decoder_model
+    #[allow(clippy::result_unit_err)]
+    pub fn with_precision<NewProbability, const NEW_PRECISION: usize>(
+        &self,
+    ) -> Result<
+        ContiguousCategoricalEntropyModel<NewProbability, Vec<NewProbability>, NEW_PRECISION>,
+        (),
+    >
+    where
+        Probability: AsPrimitive<NewProbability> + AsPrimitive<usize>,
+        NewProbability: BitArray,
+    {
+        let weights = requantize_nonzero_probabilities::<_, _, PRECISION, NEW_PRECISION>(
+            IterableEntropyModel::<PRECISION>::symbol_table(self).map(|(_, _, p)| p.get()),
+            self.support_size(),
+        )?;
+        ContiguousCategoricalEntropyModel::from_nonzero_fixed_point_probabilities(&weights, false)
+    }
 }
 
 impl<Symbol, Probability, Table, const PRECISION: usize>
@@ -2778,6 +4765,38 @@ where
             phantom: PhantomData,
         }
     }
+
+    /// Converts the model to a different fixed point precision without going through
+    /// floating point probabilities.
+    ///
+    /// See [`ContiguousCategoricalEntropyModel::with_precision`], which works analogously.
+    /// The support (i.e., the set of symbols with nonzero probability) is preserved.
+    #[allow(clippy::result_unit_err, clippy::type_complexity)]
+    pub fn with_precision<NewProbability, const NEW_PRECISION: usize>(
+        &self,
+    ) -> Result<
+        NonContiguousCategoricalDecoderModel<
+            Symbol,
+            NewProbability,
+            Vec<(NewProbability, Symbol)>,
+            NEW_PRECISION,
+        >,
+        (),
+    >
+    where
+        Probability: AsPrimitive<NewProbability> + AsPrimitive<usize>,
+        NewProbability: BitArray,
+    {
+        let symbol_table = self.as_view().cdf;
+        let symbols = SymbolTableIter::new(symbol_table).map(|(symbol, _, _)| symbol);
+        let weights = requantize_nonzero_probabilities::<_, _, PRECISION, NEW_PRECISION>(
+            SymbolTableIter::<Symbol, Probability, _>::new(symbol_table).map(|(_, _, p)| p.get()),
+            self.support_size(),
+        )?;
+        NonContiguousCategoricalDecoderModel::from_symbols_and_nonzero_fixed_point_probabilities(
+            symbols, &weights, false,
+        )
+    }
 }
 
 impl<Probability, Table, const PRECISION: usize> EntropyModel<PRECISION>
@@ -2859,43 +4878,154 @@ where
     }
 }
 
-/// `EncoderModel` is only implemented for *contiguous* generic categorical models. To
-/// decode encode symbols from a non-contiguous support, use an
-/// `NonContiguousCategoricalEncoderModel`.
-impl<Probability, Table, const PRECISION: usize> EncoderModel<PRECISION>
-    for ContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
+/// `EncoderModel` is only implemented for *contiguous* generic categorical models. To
+/// decode encode symbols from a non-contiguous support, use an
+/// `NonContiguousCategoricalEncoderModel`.
+impl<Probability, Table, const PRECISION: usize> EncoderModel<PRECISION>
+    for ContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
+where
+    Probability: BitArray,
+    Table: AsRef<[Probability]>,
+{
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let index = *symbol.borrow();
+
+        let (cdf, next_cdf) = unsafe {
+            // SAFETY: we perform a single check if index is within bounds (we compare
+            // `index >= len - 1` here and not `index + 1 >= len` because the latter could
+            // overflow/wrap but `len` is guaranteed to be nonzero; once the check passes,
+            // we know that `index + 1` doesn't wrap because `cdf.len()` can't be
+            // `usize::max_value()` since that would mean that there's no space left even
+            // for the call stack).
+            if index >= self.support_size() {
+                return None;
+            }
+            (
+                SymbolTable::<usize, Probability>::left_cumulative_unchecked(&self.cdf, index),
+                SymbolTable::<usize, Probability>::left_cumulative_unchecked(&self.cdf, index + 1),
+            )
+        };
+
+        let probability = unsafe {
+            // SAFETY: The constructors ensure that all probabilities within bounds are nonzero.
+            next_cdf.wrapping_sub(&cdf).into_nonzero_unchecked()
+        };
+
+        Some((cdf, probability))
+    }
+}
+
+/// A [`ContiguousCategoricalEntropyModel`] with an index for faster decoding of large
+/// alphabets.
+///
+/// Create an `IndexedContiguousCategoricalEntropyModel` with
+/// [`ContiguousCategoricalEntropyModel::with_decoder_index`], which also explains how it
+/// works. An `IndexedContiguousCategoricalEntropyModel` represents the exact same entropy
+/// model as the `ContiguousCategoricalEntropyModel` from which it was created, so you can
+/// use the original model on the encoder side and the indexed model on the decoder side (or
+/// vice versa).
+#[derive(Debug, Clone)]
+pub struct IndexedContiguousCategoricalEntropyModel<Probability, Table, const PRECISION: usize> {
+    inner: ContiguousCategoricalEntropyModel<Probability, Table, PRECISION>,
+
+    /// `buckets[i]` is the symbol that the quantile `i << shift` decodes to, and the last
+    /// entry is the last symbol of the support.
+    buckets: Vec<usize>,
+
+    shift: usize,
+}
+
+impl<Probability, Table, const PRECISION: usize>
+    IndexedContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
+{
+    /// Returns the underlying `ContiguousCategoricalEntropyModel` without the index.
+    pub fn into_inner(self) -> ContiguousCategoricalEntropyModel<Probability, Table, PRECISION> {
+        self.inner
+    }
+
+    /// Returns a reference to the underlying `ContiguousCategoricalEntropyModel`.
+    pub fn inner(&self) -> &ContiguousCategoricalEntropyModel<Probability, Table, PRECISION> {
+        &self.inner
+    }
+}
+
+impl<Probability, Table, const PRECISION: usize> EntropyModel<PRECISION>
+    for IndexedContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
+where
+    Probability: BitArray,
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability, Table, const PRECISION: usize> EncoderModel<PRECISION>
+    for IndexedContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
+where
+    Probability: BitArray,
+    Table: AsRef<[Probability]>,
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        self.inner.left_cumulative_and_probability(symbol)
+    }
+}
+
+impl<Probability, Table, const PRECISION: usize> DecoderModel<PRECISION>
+    for IndexedContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
+where
+    Probability: BitArray + AsPrimitive<usize>,
+    Table: AsRef<[Probability]>,
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (usize, Probability, Probability::NonZero) {
+        let bucket: usize = (quantile >> self.shift).as_();
+        let cdf = self.inner.cdf.0.as_ref();
+
+        // The symbol we're looking for lies within `low..=high` because the quantile
+        // function is monotonic. Find the largest symbol in this range whose left-sided
+        // cumulative is at most `quantile` by binary search. In the common case, `low` and
+        // `high` are equal or adjacent, so this takes at most one iteration.
+        let mut low = self.buckets[bucket];
+        let mut high = self.buckets[bucket + 1];
+        while low < high {
+            // Round up to make progress; `high` is at most the last symbol, so `cdf[mid]`
+            // is never the (possibly wrapped around) last entry of `cdf`.
+            let mid = low + (high - low + 1) / 2;
+            if cdf[mid] <= quantile {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+
+        let (left_sided_cumulative, probability) = self
+            .inner
+            .left_cumulative_and_probability(low)
+            .expect("`low` is within the support.");
+        (low, left_sided_cumulative, probability)
+    }
+}
+
+impl<'m, Probability, Table, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for IndexedContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
 where
-    Probability: BitArray,
+    Probability: BitArray + AsPrimitive<usize>,
     Table: AsRef<[Probability]>,
 {
-    fn left_cumulative_and_probability(
-        &self,
-        symbol: impl Borrow<usize>,
-    ) -> Option<(Probability, Probability::NonZero)> {
-        let index = *symbol.borrow();
-
-        let (cdf, next_cdf) = unsafe {
-            // SAFETY: we perform a single check if index is within bounds (we compare
-            // `index >= len - 1` here and not `index + 1 >= len` because the latter could
-            // overflow/wrap but `len` is guaranteed to be nonzero; once the check passes,
-            // we know that `index + 1` doesn't wrap because `cdf.len()` can't be
-            // `usize::max_value()` since that would mean that there's no space left even
-            // for the call stack).
-            if index >= self.support_size() {
-                return None;
-            }
-            (
-                SymbolTable::<usize, Probability>::left_cumulative_unchecked(&self.cdf, index),
-                SymbolTable::<usize, Probability>::left_cumulative_unchecked(&self.cdf, index + 1),
-            )
-        };
-
-        let probability = unsafe {
-            // SAFETY: The constructors ensure that all probabilities within bounds are nonzero.
-            next_cdf.wrapping_sub(&cdf).into_nonzero_unchecked()
-        };
+    type Iter = SymbolTableIter<usize, Probability, ContiguousSymbolTable<&'m [Probability]>>;
 
-        Some((cdf, probability))
+    #[inline(always)]
+    fn symbol_table(&'m self) -> Self::Iter {
+        self.inner.symbol_table()
     }
 }
 
@@ -3063,14 +5193,34 @@ where
         f64: AsPrimitive<Probability>,
         usize: AsPrimitive<Probability>,
     {
-        let slots = optimize_leaky_categorical::<_, _, PRECISION>(probabilities)?;
-        Self::from_symbols_and_nonzero_fixed_point_probabilities(
+        Self::from_symbols_and_floating_point_probabilities_with_rounding(
             symbols,
-            slots.into_iter().map(|slot| slot.weight),
-            false,
+            probabilities,
+            RoundingPolicy::MinimalCrossEntropy,
         )
     }
 
+    /// Like [`from_symbols_and_floating_point_probabilities`] but with an explicit
+    /// convention for rounding the probabilities to fixed point, see [`RoundingPolicy`].
+    ///
+    /// [`from_symbols_and_floating_point_probabilities`]:
+    ///     Self::from_symbols_and_floating_point_probabilities
+    #[allow(clippy::result_unit_err)]
+    pub fn from_symbols_and_floating_point_probabilities_with_rounding<F>(
+        symbols: impl IntoIterator<Item = Symbol>,
+        probabilities: &[F],
+        rounding: RoundingPolicy,
+    ) -> Result<Self, ()>
+    where
+        F: Float + core::iter::Sum<F> + Into<f64>,
+        Probability: Into<f64> + AsPrimitive<usize>,
+        f64: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+    {
+        let weights = quantize_leaky_categorical::<_, _, PRECISION>(probabilities, rounding)?;
+        Self::from_symbols_and_nonzero_fixed_point_probabilities(symbols, weights, false)
+    }
+
     /// Constructs a distribution with a PMF given in fixed point arithmetic.
     ///
     /// This method operates logically identically to
@@ -3258,6 +5408,135 @@ where
     Ok(symbols)
 }
 
+/// Converts floating point probabilities to nonzero fixed point weights that sum up to
+/// `1 << PRECISION`, following the provided [`RoundingPolicy`].
+fn quantize_leaky_categorical<Probability, F, const PRECISION: usize>(
+    probabilities: &[F],
+    rounding: RoundingPolicy,
+) -> Result<Vec<Probability>, ()>
+where
+    F: Float + core::iter::Sum<F> + Into<f64>,
+    Probability: BitArray + Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    if rounding == RoundingPolicy::MinimalCrossEntropy {
+        let slots = optimize_leaky_categorical::<_, _, PRECISION>(probabilities)?;
+        return Ok(slots.into_iter().map(|slot| slot.weight).collect());
+    }
+
+    assert!(PRECISION > 0 && PRECISION <= Probability::BITS);
+
+    let len = probabilities.len();
+    if len < 2
+        || len > Probability::max_value().as_()
+        || (PRECISION < usize::BITS as usize && len > 1 << PRECISION)
+    {
+        return Err(());
+    }
+
+    let probabilities = probabilities
+        .iter()
+        .map(|&probability| {
+            if probability < F::zero() {
+                Err(())
+            } else {
+                Ok(probability.into())
+            }
+        })
+        .collect::<Result<Vec<f64>, ()>>()?;
+    let normalization = probabilities.iter().sum::<f64>();
+    if !normalization.is_normal() || !normalization.is_sign_positive() {
+        return Err(());
+    }
+
+    let most_probable = probabilities
+        .iter()
+        .enumerate()
+        .fold(0, |best, (index, &probability)| {
+            if probability > probabilities[best] {
+                index
+            } else {
+                best
+            }
+        });
+
+    let total = wrapping_pow2::<Probability>(PRECISION);
+
+    if rounding == RoundingPolicy::RoundHalfEven {
+        let scale = (PRECISION as f64).exp2() / normalization;
+        let max_weight = total.wrapping_sub(&(len - 1).as_());
+        let mut sum = Probability::zero();
+        let mut weights = probabilities
+            .iter()
+            .map(|&probability| {
+                let scaled = probability * scale;
+                let mut rounded = scaled.round();
+                if rounded - scaled == 0.5 && rounded % 2.0 != 0.0 {
+                    // `f64::round` breaks ties away from zero but we want ties to even.
+                    rounded -= 1.0;
+                }
+                let weight = if rounded < 1.0 {
+                    Probability::one()
+                } else if rounded >= max_weight.into() {
+                    max_weight
+                } else {
+                    rounded.as_()
+                };
+                sum = sum.wrapping_add(&weight);
+                weight
+            })
+            .collect::<Vec<Probability>>();
+
+        // The rounded weights are off by at most about `len` units, so the wrapping
+        // difference tells us unambiguously whether we overshot or undershot.
+        let excess = sum.wrapping_sub(&total);
+        if excess >> (Probability::BITS - 1) == Probability::zero() {
+            remove_excess_from_largest_weights(&mut weights, excess.as_())?;
+        } else {
+            let deficit = total.wrapping_sub(&sum);
+            weights[most_probable] = weights[most_probable] + deficit;
+        }
+        return Ok(weights);
+    }
+
+    // Both remaining policies start by giving each symbol weight `1` and rounding down its
+    // share of the remaining mass.
+    let mut remaining = total.wrapping_sub(&len.as_());
+    let scale = remaining.into() / normalization;
+    let mut remainders = Vec::with_capacity(len);
+    let mut weights = probabilities
+        .iter()
+        .map(|&probability| {
+            let share = probability * scale;
+            let mut floor: Probability = share.floor().as_();
+            if floor > remaining {
+                // Can only happen due to floating point rounding errors.
+                floor = remaining;
+            }
+            remaining = remaining - floor;
+            remainders.push(share - share.floor());
+            floor + Probability::one()
+        })
+        .collect::<Vec<Probability>>();
+
+    if rounding == RoundingPolicy::FloorWithRedistribution {
+        weights[most_probable] = weights[most_probable] + remaining;
+    } else {
+        let mut order = (0..len).collect::<Vec<_>>();
+        // A stable sort breaks ties by original index.
+        order.sort_by(|&i, &j| remainders[j].partial_cmp(&remainders[i]).unwrap());
+        let mut cursor = order.iter().cycle();
+        while remaining != Probability::zero() {
+            let &index = cursor.next().expect("`order` is not empty");
+            weights[index] = weights[index] + Probability::one();
+            remaining = remaining - Probability::one();
+        }
+    }
+
+    Ok(weights)
+}
+
 fn optimize_leaky_categorical<Probability, F, const PRECISION: usize>(
     probabilities: &[F],
 ) -> Result<Vec<Slot<Probability>>, ()>
@@ -3273,6 +5552,25 @@ where
         return Err(());
     }
 
+    // How much the cross entropy would decrease when increasing the weight by one.
+    fn win<Probability: Into<f64>>(prob: f64, weight: Probability) -> f64 {
+        prob * (1.0f64 / weight.into()).ln_1p()
+    }
+
+    // How much the cross entropy would increase when decreasing the weight by one. We
+    // evaluate this as the `win` of the decreased weight so that moving one unit of weight
+    // from one slot to another and back again can never both look profitable due to
+    // rounding errors (which would make the loop below oscillate forever). Note that this
+    // changes the result for some inputs compared to evaluating the loss directly, which
+    // breaks bitstream compatibility (see `RoundingPolicy::MinimalCrossEntropy`).
+    fn loss<Probability: BitArray + Into<f64>>(prob: f64, weight: Probability) -> f64 {
+        if weight == Probability::one() {
+            f64::infinity()
+        } else {
+            win(prob, weight - Probability::one())
+        }
+    }
+
     // Start by assigning each symbol weight 1 and then distributing no more than
     // the remaining weight approximately evenly across all symbols.
     let mut remaining_free_weight =
@@ -3295,15 +5593,8 @@ where
             remaining_free_weight = remaining_free_weight - current_free_weight;
             let weight = current_free_weight + Probability::one();
 
-            // How much the cross entropy would decrease when increasing the weight by one.
-            let win = prob * (1.0f64 / weight.into()).ln_1p();
-
-            // How much the cross entropy would increase when decreasing the weight by one.
-            let loss = if weight == Probability::one() {
-                f64::infinity()
-            } else {
-                -prob * (-1.0f64 / weight.into()).ln_1p()
-            };
+            let win = win(prob, weight);
+            let loss = loss(prob, weight);
 
             Ok(Slot {
                 original_index,
@@ -3323,8 +5614,8 @@ where
         let batch_size = core::cmp::min(remaining_free_weight.as_(), slots.len());
         for slot in &mut slots[..batch_size] {
             slot.weight = slot.weight + Probability::one(); // Cannot end up in `max_weight` because win would otherwise be -infinity.
-            slot.win = slot.prob * (1.0f64 / slot.weight.into()).ln_1p();
-            slot.loss = -slot.prob * (-1.0f64 / slot.weight.into()).ln_1p();
+            slot.win = win(slot.prob, slot.weight);
+            slot.loss = loss(slot.prob, slot.weight);
         }
         remaining_free_weight = remaining_free_weight - batch_size.as_();
     }
@@ -3355,23 +5646,125 @@ where
         }
 
         seller.weight = seller.weight - Probability::one();
-        seller.win = seller.prob * (1.0f64 / seller.weight.into()).ln_1p();
-        seller.loss = if seller.weight == Probability::one() {
-            f64::infinity()
-        } else {
-            -seller.prob * (-1.0f64 / seller.weight.into()).ln_1p()
-        };
+        seller.win = win(seller.prob, seller.weight);
+        seller.loss = loss(seller.prob, seller.weight);
 
         let buyer = &mut slots[buyer_index];
         buyer.weight = buyer.weight + Probability::one();
-        buyer.win = buyer.prob * (1.0f64 / buyer.weight.into()).ln_1p();
-        buyer.loss = -buyer.prob * (-1.0f64 / buyer.weight.into()).ln_1p();
+        buyer.win = win(buyer.prob, buyer.weight);
+        buyer.loss = loss(buyer.prob, buyer.weight);
     }
 
     slots.sort_unstable_by_key(|slot| slot.original_index);
     Ok(slots)
 }
 
+/// Re-quantizes nonzero fixed point probabilities from `PRECISION` to `NEW_PRECISION` bits
+/// using only integer arithmetic.
+///
+/// The `len` items of `probabilities` must (logically) sum up to `1 << PRECISION`. The returned
+/// weights are all nonzero and (logically) sum up to `1 << NEW_PRECISION`. Increasing the
+/// precision is exact. When decreasing the precision, each weight is first rounded down,
+/// symbols whose weight would vanish get bumped up to one, and the remaining discrepancy is
+/// then resolved by incrementing the weights with the largest truncated remainders or by
+/// decrementing the largest weights, respectively.
+fn requantize_nonzero_probabilities<
+    Probability,
+    NewProbability,
+    const PRECISION: usize,
+    const NEW_PRECISION: usize,
+>(
+    probabilities: impl Iterator<Item = Probability>,
+    len: usize,
+) -> Result<Vec<NewProbability>, ()>
+where
+    Probability: BitArray + AsPrimitive<NewProbability> + AsPrimitive<usize>,
+    NewProbability: BitArray,
+{
+    assert!(NEW_PRECISION > 0 && NEW_PRECISION <= NewProbability::BITS);
+
+    if len < 2 || (NEW_PRECISION < usize::BITS as usize && len > 1 << NEW_PRECISION) {
+        return Err(());
+    }
+
+    if NEW_PRECISION >= PRECISION {
+        let shift = NEW_PRECISION - PRECISION;
+        return Ok(probabilities
+            .map(|probability| AsPrimitive::<NewProbability>::as_(probability) << shift)
+            .collect());
+    }
+
+    let shift = PRECISION - NEW_PRECISION;
+    let remainder_mask = (Probability::one() << shift) - Probability::one();
+    let mut remainders = Vec::with_capacity(len);
+    let mut total_remainder = Probability::zero();
+    let mut num_bumped = 0usize;
+    let mut weights = probabilities
+        .map(|probability| {
+            let remainder = probability & remainder_mask;
+            total_remainder = total_remainder.wrapping_add(&remainder);
+            let weight = probability >> shift;
+            if weight == Probability::zero() {
+                num_bumped += 1;
+                remainders.push(None);
+                NewProbability::one()
+            } else {
+                remainders.push(Some(remainder));
+                weight.as_()
+            }
+        })
+        .collect::<Vec<NewProbability>>();
+
+    // The truncated remainders add up to an integer multiple of `1 << shift`, which is the
+    // number of units by which the rounded down weights fall short of `1 << NEW_PRECISION`.
+    let deficit: usize = (total_remainder >> shift).as_();
+
+    match num_bumped.cmp(&deficit) {
+        core::cmp::Ordering::Less => {
+            // Distribute the missing units to the symbols with the largest remainders.
+            let mut candidates = remainders
+                .iter()
+                .enumerate()
+                .filter_map(|(index, remainder)| remainder.map(|remainder| (remainder, index)))
+                .collect::<Vec<_>>();
+            candidates.sort_by(|(a, i), (b, j)| b.cmp(a).then(i.cmp(j)));
+            for &(_, index) in &candidates[..deficit - num_bumped] {
+                weights[index] = weights[index] + NewProbability::one();
+            }
+        }
+        core::cmp::Ordering::Greater => {
+            remove_excess_from_largest_weights(&mut weights, num_bumped - deficit)?;
+        }
+        core::cmp::Ordering::Equal => {}
+    }
+
+    Ok(weights)
+}
+
+/// Decrements the largest `weights` (ties broken by lowest index) one unit at a time until
+/// `excess` units have been removed, never letting a weight drop below one. Taking mass
+/// away from the largest weights changes the relative probabilities the least.
+fn remove_excess_from_largest_weights<Probability: BitArray>(
+    weights: &mut [Probability],
+    excess: usize,
+) -> Result<(), ()> {
+    let mut heap = weights
+        .iter()
+        .enumerate()
+        .filter(|(_, &weight)| weight > Probability::one())
+        .map(|(index, &weight)| (weight, core::cmp::Reverse(index)))
+        .collect::<BinaryHeap<_>>();
+    for _ in 0..excess {
+        let (weight, core::cmp::Reverse(index)) = heap.pop().ok_or(())?;
+        let weight = weight - Probability::one();
+        weights[index] = weight;
+        if weight > Probability::one() {
+            heap.push((weight, core::cmp::Reverse(index)));
+        }
+    }
+    Ok(())
+}
+
 // LOOKUP TABLE ENTROPY MODELS (FOR FAST DECODING) ================================================
 
 /// A tabularized [`DecoderModel`] that is optimized for fast decoding of i.i.d. symbols
@@ -3562,6 +5955,72 @@ where
         }
     }
 
+    /// Materializes the lookup table of an arbitrary [`DecoderModel`].
+    ///
+    /// This makes the fast lookup decoding path available for any entropy model, e.g., a
+    /// [`LeakilyQuantizedDistribution`], a categorical model, or a custom model. The
+    /// returned `LookupDecoderModel` decodes exactly the same symbols from the same
+    /// compressed data as `model`, so you can keep using `model` (or anything equivalent
+    /// to it) for encoding.
+    ///
+    /// This method walks the support of `model` by calling its
+    /// [`quantile_function`](DecoderModel::quantile_function) once per symbol in the
+    /// support, so `model` doesn't need to implement [`IterableEntropyModel`] (if it does,
+    /// [`from_iterable_entropy_model`](Self::from_iterable_entropy_model) has the same
+    /// effect). The lookup table has `1 << PRECISION` entries, so this is only practical for
+    /// small precisions such as the 12 bits used by the `Small` presets.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::{LookupDecoderModel, SmallLeakyQuantizer},
+    ///     queue::{SmallRangeDecoder, SmallRangeEncoder},
+    ///     Decode, Encode,
+    /// };
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let model = SmallLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(3.2, 10.0));
+    /// let lookup_model = LookupDecoderModel::from_model(&model);
+    ///
+    /// let symbols = [5, -12, 3, 40, 0, 7];
+    /// let mut encoder = SmallRangeEncoder::new();
+    /// encoder.encode_iid_symbols(&symbols, &model).unwrap();
+    /// let mut decoder = SmallRangeDecoder::from_compressed(encoder.into_compressed().unwrap())
+    ///     .unwrap();
+    /// let decoded = decoder
+    ///     .decode_iid_symbols(symbols.len(), &lookup_model)
+    ///     .collect::<Result<Vec<_>, _>>()
+    ///     .unwrap();
+    /// assert_eq!(decoded, symbols);
+    /// ```
+    pub fn from_model<M>(model: &M) -> Self
+    where
+        M: DecoderModel<PRECISION, Symbol = Symbol, Probability = Probability> + ?Sized,
+    {
+        assert!(PRECISION > 0);
+        assert!(PRECISION <= Probability::BITS);
+        assert!(PRECISION < <usize as BitArray>::BITS);
+
+        let mut lookup_table = Vec::with_capacity(1 << PRECISION);
+        let mut cdf = Vec::new();
+        while lookup_table.len() < 1 << PRECISION {
+            let (symbol, left_sided_cumulative, probability) =
+                model.quantile_function(lookup_table.len().as_());
+            debug_assert_eq!(left_sided_cumulative, lookup_table.len().as_());
+            let index = cdf.len().as_();
+            cdf.push((lookup_table.len().as_(), symbol));
+            lookup_table.resize(lookup_table.len() + probability.get().into(), index);
+        }
+        cdf.push((wrapping_pow2(PRECISION), Symbol::default()));
+
+        Self {
+            lookup_table: lookup_table.into_boxed_slice(),
+            cdf: NonContiguousSymbolTable(cdf),
+            phantom: PhantomData,
+        }
+    }
+
     /// TODO: test
     pub fn from_iterable_entropy_model<'m, M>(model: &'m M) -> Self
     where
@@ -3664,6 +6123,100 @@ where
     }
 }
 
+/// Implements `const` constructors from static tables for the given probability types (see
+/// `impl_static_contiguous_categorical`).
+macro_rules! impl_static_contiguous_lookup {
+    ($($probability:ty),*) => {$(
+        impl<const PRECISION: usize>
+            LookupDecoderModel<
+                usize,
+                $probability,
+                ContiguousSymbolTable<&'static [$probability]>,
+                &'static [$probability],
+                PRECISION,
+            >
+        {
+            /// Constructs a lookup decoder model over a contiguous range of symbols from
+            /// static tables in a `const` context, i.e., without any runtime cost.
+            ///
+            /// The `cdf` has to satisfy the same requirements as for
+            /// [`ContiguousCategoricalEntropyModel::from_static_cdf`], and `lookup_table`
+            /// has to map each quantile `q` in `0..1 << PRECISION` to the index `i` of the
+            /// symbol with `cdf[i] <= q < cdf[i + 1]`. Violating any of these requirements
+            /// panics, i.e., it results in a compile error if the constructor is evaluated
+            /// in a `const` or `static` initializer. Note that checking the `lookup_table`
+            /// takes `Θ(2^PRECISION)` steps of compile time evaluation.
+            ///
+            /// You'll usually not call this method directly but use the macro
+            /// `static_categorical!` with the option `lookup`, which computes both tables
+            /// at compile time (requires feature `derive`).
+            ///
+            /// # Example
+            ///
+            /// ```
+            /// use constriction::stream::{
+            ///     model::SmallContiguousLookupDecoderModel, stack::SmallAnsCoder, Decode,
+            /// };
+            ///
+            /// // Probabilities 1/4 and 3/4 at 12 bit precision.
+            /// static CDF: [u16; 3] = [0, 1 << 10, 1 << 12];
+            /// static LOOKUP_TABLE: [u16; 1 << 12] = {
+            ///     let mut table = [0; 1 << 12];
+            ///     let mut quantile = 1 << 10;
+            ///     while quantile < 1 << 12 {
+            ///         table[quantile] = 1;
+            ///         quantile += 1;
+            ///     }
+            ///     table
+            /// };
+            /// static MODEL: SmallContiguousLookupDecoderModel<&[u16], &[u16]> =
+            ///     SmallContiguousLookupDecoderModel::from_static_tables(&CDF, &LOOKUP_TABLE);
+            ///
+            /// let mut coder = SmallAnsCoder::from_compressed(vec![0x1234, 0x5678]).unwrap();
+            /// let decoded = coder
+            ///     .decode_iid_symbols(8, &MODEL)
+            ///     .collect::<Result<Vec<_>, _>>()
+            ///     .unwrap();
+            /// assert!(decoded.iter().all(|&symbol| symbol < 2));
+            /// ```
+            pub const fn from_static_tables(
+                cdf: &'static [$probability],
+                lookup_table: &'static [$probability],
+            ) -> Self {
+                let _ = ContiguousCategoricalEntropyModel::<
+                    $probability,
+                    &'static [$probability],
+                    PRECISION,
+                >::from_static_cdf(cdf);
+                assert!(
+                    PRECISION < usize::BITS as usize && lookup_table.len() == 1 << PRECISION,
+                    "The `lookup_table` must have `1 << PRECISION` entries."
+                );
+
+                let mut quantile = 0;
+                while quantile < lookup_table.len() {
+                    let index = lookup_table[quantile] as usize;
+                    assert!(
+                        index + 1 < cdf.len()
+                            && cdf[index] as usize <= quantile
+                            && (index + 2 == cdf.len() || quantile < cdf[index + 1] as usize),
+                        "The `lookup_table` doesn't match the `cdf`."
+                    );
+                    quantile += 1;
+                }
+
+                Self {
+                    lookup_table,
+                    cdf: ContiguousSymbolTable(cdf),
+                    phantom: PhantomData,
+                }
+            }
+        }
+    )*};
+}
+
+impl_static_contiguous_lookup!(u8, u16);
+
 impl<Probability, Table, LookupTable, const PRECISION: usize>
     LookupDecoderModel<
         Probability,
@@ -3927,21 +6480,308 @@ mod tests {
 
     use super::super::{stack::DefaultAnsCoder, Decode};
 
-    use alloc::{string::String, vec};
-    use probability::distribution::{Binomial, Gaussian};
+    use alloc::{string::String, vec};
+    use probability::distribution::{Binomial, Gaussian, Laplace, Logistic};
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn leakily_quantized_normal() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-127..=127);
+        for &std_dev in &[0.0001, 0.1, 3.5, 123.45, 1234.56] {
+            for &mean in &[-300.6, -100.2, -5.2, 0.0, 50.3, 180.2, 2000.0] {
+                let distribution = Gaussian::new(mean, std_dev);
+                test_entropy_model(&quantizer.quantize(distribution), -127..128);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn leakily_quantized_laplace() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-127..=127);
+        for &scale in &[0.0001, 0.1, 3.5, 123.45, 1234.56] {
+            for &mean in &[-300.6, -100.2, -5.2, 0.0, 50.3, 180.2, 2000.0] {
+                let distribution = Laplace::new(mean, scale);
+                test_entropy_model(&quantizer.quantize(distribution), -127..128);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn leakily_quantized_logistic() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-127..=127);
+        for &scale in &[0.0001, 0.1, 3.5, 123.45, 1234.56] {
+            for &mean in &[-300.6, -100.2, -5.2, 0.0, 50.3, 180.2, 2000.0] {
+                let distribution = Logistic::new(mean, scale);
+                test_entropy_model(&quantizer.quantize(distribution), -127..128);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn leakily_quantized_cauchy() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-127..=127);
+        for &scale in &[0.0001, 0.1, 3.5, 123.45, 1234.56] {
+            for &mean in &[-300.6, -100.2, -5.2, 0.0, 50.3, 180.2, 2000.0] {
+                let distribution = cauchy::Cauchy::new(mean, scale);
+                test_entropy_model(&quantizer.quantize(distribution), -127..128);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn leakily_quantized_normal_extremes() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-127..=127);
+        let std_devs = [
+            f64::MIN_POSITIVE / 1e10,
+            f64::MIN_POSITIVE,
+            1e-300,
+            1e-10,
+            1e10,
+            1e300,
+            f64::MAX,
+            f64::INFINITY,
+        ];
+        let means = [
+            f64::NEG_INFINITY,
+            f64::MIN,
+            -1e300,
+            -1e10,
+            -127.5,
+            -127.0,
+            -126.5,
+            -0.5,
+            0.0,
+            3.5,
+            126.5,
+            127.0,
+            127.5,
+            1e10,
+            1e300,
+            f64::MAX,
+            f64::INFINITY,
+            f64::NAN,
+        ];
+        let small_quantizer = LeakyQuantizer::<_, _, u16, 12>::new(-100i8..=100);
+        for &std_dev in &std_devs {
+            for &mean in &means {
+                let distribution = Gaussian::new(mean, std_dev);
+                test_entropy_model(&quantizer.quantize(distribution), -127..128);
+                test_entropy_model(&small_quantizer.quantize(distribution), -100..101);
+            }
+        }
+    }
+
+    /// Regression test: the iterator used to evaluate the right boundary of each symbol at
+    /// `symbol - 0.5`, i.e., at the symbol's left boundary.
+    #[test]
+    fn leakily_quantized_symbol_table_matches_left_cumulative() {
+        let quantizer = DefaultLeakyQuantizer::new(-20..=20);
+        for &(mean, std_dev) in &[(0.0, 1.0), (-3.7, 2.1), (15.3, 0.4), (0.2, 30.0)] {
+            let model = quantizer.quantize(Gaussian::new(mean, std_dev));
+            let mut num_symbols = 0;
+            for (symbol, left_cumulative, probability) in model.symbol_table() {
+                assert_eq!(symbol, -20 + num_symbols);
+                assert_eq!(
+                    model.left_cumulative_and_probability(symbol).unwrap(),
+                    (left_cumulative, probability)
+                );
+                num_symbols += 1;
+            }
+            assert_eq!(num_symbols, 41);
+        }
+    }
+
+    /// A Gaussian whose CDF and its inverse suffer from the kind of rounding errors and
+    /// NaNs that approximate implementations produce in the far tails.
+    struct SloppyGaussian(Gaussian);
+
+    impl Distribution for SloppyGaussian {
+        type Value = f64;
+
+        fn distribution(&self, x: f64) -> f64 {
+            let cdf = self.0.distribution(x);
+            if cdf <= 1e-20 {
+                if x.is_finite() {
+                    -1e-12
+                } else {
+                    f64::NAN
+                }
+            } else if cdf >= 1.0 - 1e-15 {
+                1.0 + 1e-12
+            } else {
+                cdf
+            }
+        }
+    }
+
+    impl Inverse for SloppyGaussian {
+        fn inverse(&self, p: f64) -> f64 {
+            if p < 0.01 {
+                f64::NAN
+            } else if p > 0.99 {
+                f64::INFINITY
+            } else {
+                self.0.inverse(p)
+            }
+        }
+    }
 
     #[test]
     #[cfg_attr(miri, ignore)]
-    fn leakily_quantized_normal() {
+    fn leakily_quantized_sloppy_distribution() {
         let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-127..=127);
-        for &std_dev in &[0.0001, 0.1, 3.5, 123.45, 1234.56] {
-            for &mean in &[-300.6, -100.2, -5.2, 0.0, 50.3, 180.2, 2000.0] {
-                let distribution = Gaussian::new(mean, std_dev);
+        for &std_dev in &[1e-300, 0.01, 3.5, 1e10] {
+            for &mean in &[-1e300, -200.0, -127.5, 0.0, 3.5, 127.5, 200.0, 1e300] {
+                let distribution = SloppyGaussian(Gaussian::new(mean, std_dev));
                 test_entropy_model(&quantizer.quantize(distribution), -127..128);
             }
         }
     }
 
+    #[test]
+    fn leakily_quantized_describe() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-127..=127);
+        for &std_dev in &[0.0001, 3.5, 123.45] {
+            for &mean in &[-300.6, -5.2, 0.0, 180.2] {
+                let distribution = Gaussian::new(mean, std_dev);
+                let model = quantizer.quantize(distribution);
+                let report = model.describe();
+                assert_eq!(report.support, -127..=127);
+
+                let probabilities = model
+                    .symbol_table()
+                    .map(|(symbol, _, probability)| (symbol, probability.get()))
+                    .collect::<Vec<_>>();
+                let min_probability = probabilities.iter().map(|&(_, p)| p).min().unwrap();
+                assert_eq!(report.min_probability.get(), min_probability);
+
+                let effective_symbols = probabilities
+                    .iter()
+                    .filter(|&&(_, p)| p > 1)
+                    .map(|&(symbol, _)| symbol)
+                    .collect::<Vec<_>>();
+                let effective_support = report.effective_support.unwrap();
+                assert_eq!(*effective_support.start(), effective_symbols[0]);
+                assert_eq!(*effective_support.end(), *effective_symbols.last().unwrap());
+
+                // The quantizer reserves one unit of probability for each of the 255 symbols.
+                let free_weight = ((1u32 << 24) - 255) as f64;
+                let expected_tail_mass =
+                    distribution.distribution(-127.5) + (1.0 - distribution.distribution(127.5));
+                let tail_mass = report.tail_mass as f64 / free_weight;
+                assert!((tail_mass - expected_tail_mass).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn leakily_quantized_with_decoder_index() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-1000..=1000);
+        for &std_dev in &[0.0001, 0.1, 3.5, 123.45, 12345.6] {
+            for &mean in &[-2000.6, -100.2, 0.0, 50.3, 999.9] {
+                let model = quantizer.quantize(Gaussian::new(mean, std_dev));
+                let indexed_model = model.with_decoder_index();
+                test_entropy_model(&indexed_model, -1000..1001);
+            }
+        }
+
+        // Exhaustively compare all quantiles for a precision that is smaller than the
+        // number of bits of the index, and for one that is slightly larger.
+        let quantizer = LeakyQuantizer::<_, _, u16, 6>::new(-10..=10i32);
+        let model = quantizer.quantize(Gaussian::new(1.3, 2.0));
+        let indexed_model = model.with_decoder_index();
+        for quantile in 0..1 << 6 {
+            assert_eq!(
+                indexed_model.quantile_function(quantile),
+                model.quantile_function(quantile)
+            );
+        }
+        let quantizer = LeakyQuantizer::<_, _, u16, 12>::new(i32::MIN..=i32::MIN + 200);
+        let model = quantizer.quantize(Gaussian::new(i32::MIN as f64 + 100.0, 30.0));
+        let indexed_model = model.with_decoder_index();
+        for quantile in 0..1 << 12 {
+            assert_eq!(
+                indexed_model.quantile_function(quantile),
+                model.quantile_function(quantile)
+            );
+        }
+    }
+
+    #[test]
+    fn leakily_quantized_with_cdf_cache() {
+        /// Gaussian that counts how often its CDF gets evaluated.
+        struct CountingGaussian {
+            inner: Gaussian,
+            count: core::cell::Cell<usize>,
+        }
+
+        impl Distribution for CountingGaussian {
+            type Value = f64;
+
+            fn distribution(&self, x: f64) -> f64 {
+                self.count.set(self.count.get() + 1);
+                self.inner.distribution(x)
+            }
+        }
+
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-1000..=1000);
+        for &std_dev in &[0.0001, 0.1, 3.5, 123.45, 12345.6] {
+            for &mean in &[-2000.6, -100.2, 0.0, 50.3, 999.9] {
+                let model = quantizer.quantize(Gaussian::new(mean, std_dev));
+                let cached_model = model.with_cdf_cache();
+                test_entropy_model(&cached_model, -1000..1001);
+            }
+        }
+
+        // Exhaustively compare all symbols and all quantiles with the uncached model.
+        let quantizer = LeakyQuantizer::<_, _, u16, 12>::new(-100..=100i32);
+        let model = quantizer.quantize(Gaussian::new(1.3, 7.0));
+        let cached_model = model.with_cdf_cache();
+        for symbol in -102..=102 {
+            assert_eq!(
+                cached_model.left_cumulative_and_probability(symbol),
+                model.left_cumulative_and_probability(symbol)
+            );
+        }
+        for quantile in 0..1 << 12 {
+            assert_eq!(
+                cached_model.quantile_function(quantile),
+                model.quantile_function(quantile)
+            );
+        }
+        assert_eq!(cached_model.num_cached(), 201);
+
+        // Encoding and decoding many i.i.d. symbols evaluates the CDF at most once per
+        // symbol in the support.
+        let quantizer = DefaultLeakyQuantizer::new(-50..=50);
+        let model = quantizer
+            .quantize(CountingGaussian {
+                inner: Gaussian::new(3.2, 10.0),
+                count: core::cell::Cell::new(0),
+            })
+            .with_cdf_cache();
+        let symbols = (0..1000).map(|i| (i * 13) % 101 - 50).collect::<Vec<_>>();
+        let mut coder = DefaultAnsCoder::new();
+        coder.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let decoded = coder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(coder.is_empty());
+        assert!(model.inner().inner().count.get() <= 100);
+        assert_eq!(model.num_cached(), 101);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn leakily_quantized_binomial() {
@@ -3958,6 +6798,139 @@ mod tests {
         }
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn f32_parameters() {
+        let quantizer32 = DefaultLeakyQuantizer::new_f32(-127..=127);
+        let quantizer64 = DefaultLeakyQuantizer::new(-127..=127);
+        for &std_dev in &[0.1f32, 3.5, 123.45] {
+            for &mean in &[-100.2f32, 0.0, 50.3] {
+                let distribution = Gaussian::new(mean.into(), std_dev.into());
+                let model32 = quantizer32.quantize(distribution);
+                let model64 = quantizer64.quantize(distribution);
+                test_entropy_model(&model32, -127..128);
+                assert!(model32.symbol_table().eq(model64.symbol_table()));
+            }
+        }
+
+        let probabilities32 = [0.1f32, 0.25, 1e-7, 0.4, 0.25];
+        let probabilities64 = probabilities32.map(f64::from);
+        let model32 = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+            &probabilities32,
+        )
+        .unwrap();
+        let model64 = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+            &probabilities64,
+        )
+        .unwrap();
+        assert!(model32.symbol_table().eq(model64.symbol_table()));
+
+        // An `f32` can't represent the probability budget for `PRECISION = 32` exactly.
+        assert!(std::panic::catch_unwind(|| {
+            LeakyQuantizer::<f32, i32, u32, 32>::new_f32(-127..=127);
+        })
+        .is_err());
+        assert!(LeakyQuantizer::<f32, i32, u32, 32>::checked_new_f32(-127..=127).is_none());
+        assert!(LeakyQuantizer::<f32, i32, u32, 24>::checked_new_f32(-127..=127).is_some());
+        assert!(DefaultLeakyQuantizer::checked_new(5..=5).is_none());
+        assert!(DefaultLeakyQuantizer::checked_new(RangeInclusive::new(5, 4)).is_none());
+        assert!(LeakyQuantizer::<f64, i32, u16, 8>::checked_new(0..=255).is_some());
+        assert!(LeakyQuantizer::<f64, i32, u16, 8>::checked_new(0..=256).is_none());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn wrapped_distribution() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(0..=99);
+        for &(mean, std) in &[(0.0, 3.0), (99.4, 0.7), (-230.0, 20.0), (12.3, 400.0)] {
+            let distribution = WrappedDistribution::new(Gaussian::new(mean, std), 100.0);
+            assert_eq!(distribution.distribution(-0.5), 0.0);
+            assert!((distribution.distribution(99.5) - 1.0).abs() < 1e-12);
+
+            let model = quantizer.quantize(distribution);
+            test_entropy_model(&model, 0..100);
+
+            // Bins on opposite sides of the seam have the expected (mirrored) probabilities.
+            let mean_bin = mean.rem_euclid(100.0).round() as i32;
+            let probability = |symbol: i32| {
+                model
+                    .left_cumulative_and_probability(symbol.rem_euclid(100))
+                    .unwrap()
+                    .1
+                    .get() as f64
+            };
+            if mean.fract() == 0.0 {
+                for delta in 1..10 {
+                    let left = probability(mean_bin - delta);
+                    let right = probability(mean_bin + delta);
+                    assert!((left - right).abs() <= 2.0);
+                }
+            }
+            if std > 100.0 {
+                // Approximately uniform.
+                for symbol in 0..100 {
+                    assert!((probability(symbol) / (1u32 << 24) as f64 - 0.01).abs() < 1e-4);
+                }
+            }
+        }
+
+        let gaussian = Gaussian::new(0.0, 1.0);
+        assert!(WrappedDistribution::checked_new(gaussian, 100.0).is_some());
+        assert!(WrappedDistribution::checked_new(gaussian, -1.0).is_none());
+        assert!(WrappedDistribution::checked_new(gaussian, f64::NAN).is_none());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn von_mises() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(0..=63);
+        for &(mean, kappa) in &[
+            (0.0, 0.0),
+            (0.0, 3.0),
+            (63.2, 0.5),
+            (-20.0, 100.0),
+            (5.0, 1e4),
+        ] {
+            let distribution = VonMises::new(mean, kappa, 64.0);
+            assert_eq!(distribution.distribution(-0.5), 0.0);
+            assert!((distribution.distribution(63.5) - 1.0).abs() < 1e-12);
+
+            // Compare to a numerical integration of the density.
+            let density = |x: f64| {
+                let angle = 2.0 * core::f64::consts::PI * (x - mean) / 64.0;
+                (kappa * (angle.cos() - 1.0)).exp()
+            };
+            let num_steps = 64 * 1000;
+            let step = 64.0 / num_steps as f64;
+            let mut integral = 0.0;
+            let mut integrals = Vec::with_capacity(65);
+            for i in 0..num_steps {
+                if i % 1000 == 0 {
+                    integrals.push(integral);
+                }
+                integral += density(-0.5 + (i as f64 + 0.5) * step) * step;
+            }
+            integrals.push(integral);
+            for (i, &expected) in integrals.iter().enumerate() {
+                let x = i as f64 - 0.5;
+                assert!((distribution.distribution(x) - expected / integral).abs() < 1e-6);
+                let p = expected / integral;
+                if p > 1e-3 && p < 1.0 - 1e-3 {
+                    assert!((distribution.distribution(distribution.inverse(p)) - p).abs() < 1e-6);
+                }
+            }
+
+            let model = quantizer.quantize(distribution);
+            test_entropy_model(&model, 0..64);
+        }
+
+        assert!(VonMises::checked_new(0.0, 1.0, 64.0).is_some());
+        assert!(VonMises::checked_new(0.0, 1.0, 0.0).is_none());
+        assert!(VonMises::checked_new(0.0, -1.0, 64.0).is_none());
+        assert!(VonMises::checked_new(f64::NAN, 1.0, 64.0).is_none());
+        assert!(VonMises::checked_new(0.0, f64::INFINITY, 64.0).is_none());
+    }
+
     #[test]
     fn uniform() {
         for range in [2, 3, 4, 5, 6, 7, 8, 9, 62, 63, 64, 254, 255, 256] {
@@ -3972,6 +6945,91 @@ mod tests {
                 test_entropy_model(&UniformModel::<u8, 6>::new(range as u8), 0..range as u8);
             }
         }
+
+        assert!(UniformModel::<u32, 24>::checked_new(0).is_none());
+        assert!(UniformModel::<u32, 24>::checked_new(1).is_none());
+        assert!(UniformModel::<u32, 24>::checked_new(1 << 24).is_some());
+        assert!(UniformModel::<u32, 24>::checked_new((1 << 24) + 1).is_none());
+        assert!(UniformModel::<u32, 32>::checked_new(u32::MAX).is_some());
+        assert!(UniformModel::<u8, 6>::checked_new(65).is_none());
+    }
+
+    #[test]
+    fn uniform_range() {
+        fn test<Symbol, Probability, const PRECISION: usize>(
+            min: Symbol,
+            max: Symbol,
+            symbols: impl Iterator<Item = Symbol>,
+        ) where
+            Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub + WrappingAdd + Debug,
+            Probability: BitArray + AsPrimitive<Symbol> + Into<u64>,
+            usize: AsPrimitive<Probability>,
+        {
+            let model =
+                UniformRangeModel::<Symbol, Probability, PRECISION>::new(min..=max).unwrap();
+            assert_eq!(model.support(), min..=max);
+            let inner = UniformModel::<Probability, PRECISION>::new(
+                slack::<Probability, Symbol>(max, min) + Probability::one(),
+            );
+
+            let mut expected_left = 0u64;
+            for (offset, symbol) in symbols.enumerate() {
+                let (left, probability) = model.left_cumulative_and_probability(symbol).unwrap();
+                assert_eq!(left.into(), expected_left);
+                expected_left += probability.get().into();
+                let offset: Probability = offset.as_();
+                assert_eq!(
+                    Some((left, probability)),
+                    inner.left_cumulative_and_probability(offset)
+                );
+                assert_eq!(model.quantile_function(left), (symbol, left, probability));
+                assert_eq!(
+                    model.quantile_function(left + (probability.get() - Probability::one())),
+                    (symbol, left, probability)
+                );
+            }
+            assert_eq!(expected_left, 1 << PRECISION);
+            if max < Symbol::max_value() {
+                assert!(model
+                    .left_cumulative_and_probability(max + Symbol::one())
+                    .is_none());
+            }
+            if min > Symbol::min_value() {
+                assert!(model
+                    .left_cumulative_and_probability(min - Symbol::one())
+                    .is_none());
+            }
+        }
+
+        test::<i32, u32, 24>(-1000, 1000, -1000..=1000);
+        test::<i32, u32, 24>(5, 7, 5..=7);
+        test::<i32, u16, 12>(-4096, -1, -4096..=-1);
+        test::<i8, u32, 24>(-128, 127, -128..=127);
+        test::<i8, u8, 8>(-128, 126, -128..=126);
+        test::<u8, u16, 12>(0, 255, 0..=255);
+
+        assert!(DefaultUniformRangeModel::new(3..=3).is_err());
+        #[allow(clippy::reversed_empty_ranges)]
+        {
+            assert!(DefaultUniformRangeModel::new(3..=2).is_err());
+        }
+        assert!(SmallUniformRangeModel::new(0..=4095).is_ok());
+        assert!(SmallUniformRangeModel::new(0..=4096).is_err());
+        assert!(UniformRangeModel::<i8, u8, 8>::new(-128..=127).is_err());
+        assert!(UniformRangeModel::<i32, u32, 24>::new(i32::MIN..=i32::MAX).is_err());
+
+        // Ranges whose size doesn't fit into `Probability` must not be truncated.
+        assert!(UniformRangeModel::<i64, u32, 24>::new(0..=(1 << 32) + 5).is_err());
+        assert!(UniformRangeModel::<i64, u32, 24>::new(i64::MIN..=i64::MAX).is_err());
+        assert!(UniformRangeModel::<u64, u16, 12>::new(0..=(1 << 16)).is_err());
+        let model = UniformRangeModel::<i64, u32, 24>::new(0..=9).unwrap();
+        assert_eq!(model.support(), 0..=9);
+        assert!(model.left_cumulative_and_probability(1 << 32).is_none());
+        assert!(model.left_cumulative_and_probability(10).is_none());
+        let model = UniformRangeModel::<i64, u32, 24>::new(-5..=(1 << 24) - 6).unwrap();
+        assert!(model
+            .left_cumulative_and_probability((1 << 32) - 5)
+            .is_none());
     }
 
     #[test]
@@ -3989,6 +7047,82 @@ mod tests {
         }
     }
 
+    #[test]
+    fn bit_cost() {
+        let model =
+            LeakyQuantizer::<_, _, u32, 24>::new(-100..=100).quantize(Gaussian::new(3.2, 10.0));
+        for symbol in -100..=100 {
+            let bit_cost = model.bit_cost::<f64>(symbol);
+            let probability = model.floating_point_probability::<f64>(symbol);
+            assert!((bit_cost + probability.log2()).abs() < 1e-12);
+        }
+        assert_eq!(model.bit_cost::<f64>(101), f64::INFINITY);
+
+        // The total bit cost predicts the size of the compressed data up to a constant.
+        let symbols = (0..1000)
+            .map(|i| (i * 37) % 201 - 100)
+            .collect::<Vec<i32>>();
+        let total_bit_cost = symbols
+            .iter()
+            .map(|&symbol| model.bit_cost::<f64>(symbol))
+            .sum::<f64>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let num_bits = ans.num_valid_bits() as f64;
+        assert!((num_bits - total_bit_cost).abs() < 64.0);
+    }
+
+    #[test]
+    fn cross_entropy_and_kl_divergence() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-100..=100);
+        let small_quantizer = LeakyQuantizer::<_, _, u16, 12>::new(-100..=100);
+        let data_model = quantizer.quantize(Gaussian::new(3.2, 10.0));
+
+        // Cross entropy with the model itself is the entropy.
+        let entropy = data_model.entropy_base2::<f64>();
+        let cross_entropy = data_model.cross_entropy_base2::<f64, _, 24>(&data_model);
+        assert!((cross_entropy - entropy).abs() < 1e-10);
+        assert_eq!(
+            data_model.kl_divergence_base2::<f64, _, 24>(&data_model),
+            0.0
+        );
+
+        // Compare against the analytic KL divergence between two Gaussians, which the
+        // quantized models approximate well for large standard deviations.
+        for &(mean, std_dev) in &[(3.2, 10.0), (-5.0, 12.0), (10.0, 8.0)] {
+            let coding_model = quantizer.quantize(Gaussian::new(mean, std_dev));
+            let cross_entropy = data_model.cross_entropy_base2::<f64, _, 24>(&coding_model);
+            let kl_divergence = data_model.kl_divergence_base2::<f64, _, 24>(&coding_model);
+            assert!((cross_entropy - entropy - kl_divergence).abs() < 1e-10);
+
+            let expected_kl_divergence = ((std_dev / 10.0f64).ln()
+                + (100.0 + (3.2 - mean) * (3.2 - mean)) / (2.0 * std_dev * std_dev)
+                - 0.5)
+                / core::f64::consts::LN_2;
+            assert!((kl_divergence - expected_kl_divergence).abs() < 0.01);
+        }
+
+        // The coding model may have a different precision. Quantizing to fewer bits costs
+        // a bit of compression performance even if the underlying distribution is correct.
+        let small_model = small_quantizer.quantize(Gaussian::new(3.2, 10.0));
+        let cross_entropy = data_model.cross_entropy_base2::<f64, _, 12>(&small_model);
+        let kl_divergence = data_model.kl_divergence_base2::<f64, _, 12>(&small_model);
+        assert!((cross_entropy - entropy - kl_divergence).abs() < 1e-10);
+        assert!(kl_divergence > 0.0 && kl_divergence < 0.1);
+
+        // The coding model can't encode symbols outside of its support.
+        let narrow_model =
+            LeakyQuantizer::<_, _, u32, 24>::new(-50..=50).quantize(Gaussian::new(3.2, 10.0));
+        assert_eq!(
+            data_model.cross_entropy_base2::<f64, _, 24>(&narrow_model),
+            f64::INFINITY
+        );
+        assert_eq!(
+            data_model.kl_divergence_base2::<f64, _, 24>(&narrow_model),
+            f64::INFINITY
+        );
+    }
+
     /// Test that `optimal_weights` reproduces the same distribution when fed with an
     /// already quantized model.
     #[test]
@@ -4062,6 +7196,24 @@ mod tests {
         }
     }
 
+    /// Regression test: rounding errors used to make the optimization in
+    /// `optimize_leaky_categorical` move a unit of weight back and forth forever.
+    #[test]
+    fn optimal_weights_terminate() {
+        let counts = [26.0f64, 11.0, 9.0, 9.0, 5.0, 6.0, 1.0, 6.0];
+        let probabilities = counts.iter().map(|&x| (x + 0.5) / 77.0).collect::<Vec<_>>();
+        let categorical =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+                &probabilities,
+            )
+            .unwrap();
+        let weights: Vec<_> = categorical
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get())
+            .collect();
+        assert_eq!(weights.iter().sum::<u32>(), 1 << 24);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn contiguous_categorical() {
@@ -4080,6 +7232,72 @@ mod tests {
         test_entropy_model(&model, 0..probabilities.len());
     }
 
+    #[test]
+    fn rational_probabilities() {
+        type Model32 = ContiguousCategoricalEntropyModel<u32, Vec<u32>, 32>;
+
+        // Denominator equal to `1 << PRECISION`, including one that doesn't fit into `u32`.
+        let model =
+            Model32::from_rational_probabilities([1 << 30, 1 << 31, 1 << 30], 1 << 32).unwrap();
+        test_entropy_model(&model, 0..3);
+        let model = DefaultContiguousCategoricalEntropyModel::from_rational_probabilities(
+            [(1 << 24) - 5, 5],
+            1 << 24,
+        )
+        .unwrap();
+        assert_eq!(model.left_cumulative_and_probability(1).unwrap().1.get(), 5);
+
+        // Smaller denominators get scaled up exactly.
+        let model =
+            DefaultContiguousCategoricalEntropyModel::from_rational_probabilities([1, 2, 1], 4)
+                .unwrap();
+        let probabilities = model
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get())
+            .collect::<Vec<_>>();
+        assert_eq!(probabilities, [1 << 22, 1 << 23, 1 << 22]);
+
+        // Invalid inputs.
+        type Default = DefaultContiguousCategoricalEntropyModel;
+        assert!(Default::from_rational_probabilities([1, 2, 2], 6).is_err());
+        assert!(Default::from_rational_probabilities([1, 2, 1], 1 << 25).is_err());
+        assert!(Default::from_rational_probabilities([1, 2, 2], 4).is_err());
+        assert!(Default::from_rational_probabilities([4, 0], 4).is_err());
+        assert!(Default::from_rational_probabilities([5, 0xffff_ffff], 4).is_err());
+        assert!(Default::from_rational_probabilities([1], 1).is_err());
+        assert!(Model32::from_rational_probabilities([1], 1).is_err());
+        assert!(Default::from_rational_probabilities(&[] as &[u32], 4).is_err());
+    }
+
+    #[test]
+    fn fixed_point_probabilities() {
+        type Model32 = ContiguousCategoricalEntropyModel<u32, Vec<u32>, 32>;
+
+        let probabilities = [3 << 20, 1, (1 << 23) - 1, 5 << 20];
+        let model = DefaultContiguousCategoricalEntropyModel::from_fixed_point_probabilities(
+            &probabilities,
+        )
+        .unwrap();
+        test_entropy_model(&model, 0..4);
+        let reconstructed = model
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get())
+            .collect::<Vec<_>>();
+        assert_eq!(reconstructed, probabilities);
+
+        // Probabilities that sum up to `1 << PRECISION` even if it doesn't fit into `u32`.
+        let model = Model32::from_fixed_point_probabilities(&[1 << 31, 1 << 30, 1 << 30]).unwrap();
+        test_entropy_model(&model, 0..3);
+
+        // Invalid inputs.
+        type Default = DefaultContiguousCategoricalEntropyModel;
+        assert!(Default::from_fixed_point_probabilities(&[1 << 23, 1 << 22]).is_err());
+        assert!(Default::from_fixed_point_probabilities(&[1 << 24, 0]).is_err());
+        assert!(Default::from_fixed_point_probabilities(&[1 << 23, 1 << 23, 1]).is_err());
+        assert!(Default::from_fixed_point_probabilities(&[1 << 24]).is_err());
+        assert!(Default::from_fixed_point_probabilities(&[]).is_err());
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn non_contiguous_categorical() {
@@ -4102,6 +7320,171 @@ mod tests {
         test_iterable_entropy_model(&model, symbols.iter().cloned());
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn categorical_with_precision() {
+        let hist = [
+            1u32, 186545, 237403, 295700, 361445, 433686, 509456, 586943, 663946, 737772, 1657269,
+            896675, 922197, 930672, 916665, 0, 0, 0, 0, 0, 723031, 650522, 572300, 494702, 418703,
+            347600, 1, 283500, 226158, 178194, 136301, 103158, 76823, 55540, 39258, 27988, 54269,
+        ];
+        let probabilities = hist.iter().map(|&x| x as f64).collect::<Vec<_>>();
+        let symbols = "QWERTYUIOPASDFGHJKLZXCVBNM 1234567890"
+            .chars()
+            .collect::<Vec<_>>();
+
+        let model =
+            ContiguousCategoricalEntropyModel::<u32, _, 32>::from_floating_point_probabilities(
+                &probabilities,
+            )
+            .unwrap();
+        let reference =
+            ContiguousCategoricalEntropyModel::<u16, _, 12>::from_floating_point_probabilities(
+                &probabilities,
+            )
+            .unwrap();
+
+        let model12 = model.with_precision::<u16, 12>().unwrap();
+        test_entropy_model(&model12, 0..probabilities.len());
+        // Should be almost as good as quantizing the floating point probabilities directly.
+        let cross_entropy = |model: &ContiguousCategoricalEntropyModel<u16, Vec<u16>, 12>| {
+            let normalization = probabilities.iter().sum::<f64>();
+            model
+                .floating_point_symbol_table::<f64>()
+                .zip(&probabilities)
+                .map(|((_, _, q), &p)| -p / normalization * q.log2())
+                .sum::<f64>()
+        };
+        assert!(cross_entropy(&model12) < cross_entropy(&reference) + 1e-3);
+
+        // Going back to a higher precision is exact.
+        let model24 = model12.with_precision::<u32, 24>().unwrap();
+        test_entropy_model(&model24, 0..probabilities.len());
+        for ((_, _, p12), (_, _, p24)) in model12.symbol_table().zip(model24.symbol_table()) {
+            assert_eq!((p12.get() as u32) << 12, p24.get());
+        }
+
+        // Extreme case: almost all symbols get bumped up to the smallest nonzero probability.
+        let model6 = model.with_precision::<u8, 6>().unwrap();
+        test_entropy_model(&model6, 0..probabilities.len());
+
+        // Support is too large to fit into five bits of precision.
+        assert!(model.with_precision::<u8, 5>().is_err());
+
+        let non_contiguous = NonContiguousCategoricalDecoderModel::<_, u32, _, 32>
+            ::from_symbols_and_floating_point_probabilities(&symbols, &probabilities)
+            .unwrap();
+        let non_contiguous12 = non_contiguous.with_precision::<u16, 12>().unwrap();
+        test_iterable_entropy_model(&non_contiguous12, symbols.iter().cloned());
+        for ((_, _, p), (_, _, q)) in non_contiguous12.symbol_table().zip(model12.symbol_table()) {
+            assert_eq!(p, q);
+        }
+        assert!(non_contiguous.with_precision::<u8, 5>().is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn rounding_policies() {
+        use RoundingPolicy::*;
+
+        let weights = |probabilities: &[f64], rounding| {
+            ContiguousCategoricalEntropyModel::<u32, _, 4>::from_floating_point_probabilities_with_rounding(
+                probabilities,
+                rounding,
+            )
+            .unwrap()
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get())
+            .collect::<Vec<_>>()
+        };
+
+        // Worked example with `N = 16`: the shares of the free mass `N - 3 = 13` are
+        // `[2.6, 5.85, 4.55]`, and `N * p = [3.2, 7.2, 5.6]`.
+        let probabilities = [0.2, 0.45, 0.35];
+        assert_eq!(weights(&probabilities, FloorWithRedistribution), [3, 8, 5]);
+        assert_eq!(weights(&probabilities, LargestRemainder), [4, 7, 5]);
+        assert_eq!(weights(&probabilities, RoundHalfEven), [3, 7, 6]);
+
+        // `N * p = [1.5, 2.5, 12.0]`, so both ties get rounded to the even neighbor.
+        assert_eq!(weights(&[3.0, 5.0, 24.0], RoundHalfEven), [2, 2, 12]);
+
+        // Tiny probabilities get bumped up to one; the compensation comes from the largest
+        // weight.
+        assert_eq!(
+            weights(&[0.0, 0.01, 0.99], FloorWithRedistribution),
+            [1, 1, 14]
+        );
+        assert_eq!(weights(&[0.0, 0.01, 0.99], RoundHalfEven), [1, 1, 14]);
+        assert_eq!(weights(&[0.0, 0.01, 0.99], LargestRemainder), [1, 1, 14]);
+
+        // Rate impact: the default policy is optimal in terms of cross entropy, and the
+        // other policies cost only little extra bit rate at `PRECISION = 24`.
+        let mut rng = Xoshiro256StarStar::seed_from_u64(12345);
+        for &len in &[2, 3, 10, 100, 1000] {
+            let probabilities = (0..len)
+                .map(|_| {
+                    let x = rng.next_u32() as f64 / u32::MAX as f64;
+                    x * x * x
+                })
+                .collect::<Vec<_>>();
+            let normalization = probabilities.iter().sum::<f64>();
+
+            let cross_entropy = |rounding| {
+                let model =
+                    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+                        &probabilities,
+                        rounding,
+                    )
+                    .unwrap();
+                test_entropy_model(&model, 0..len);
+                model
+                    .floating_point_symbol_table::<f64>()
+                    .zip(&probabilities)
+                    .map(|((_, _, q), &p)| -p / normalization * q.log2())
+                    .sum::<f64>()
+            };
+
+            let optimal = cross_entropy(MinimalCrossEntropy);
+            assert_eq!(optimal, cross_entropy(RoundingPolicy::default()));
+            for &rounding in &[FloorWithRedistribution, RoundHalfEven, LargestRemainder] {
+                let overhead = cross_entropy(rounding) - optimal;
+                assert!(overhead > -1e-9);
+                assert!(overhead < 1e-4);
+            }
+
+            let symbols = (0..len).map(|i| i as i32 * 3).collect::<Vec<_>>();
+            let decoder_model = NonContiguousCategoricalDecoderModel::<_, u32, _, 24>
+                ::from_symbols_and_floating_point_probabilities_with_rounding(
+                    &symbols,
+                    &probabilities,
+                    LargestRemainder,
+                )
+                .unwrap();
+            let encoder_model = NonContiguousCategoricalEncoderModel::<_, u32, 24>
+                ::from_symbols_and_floating_point_probabilities_with_rounding(
+                    symbols.iter().cloned(),
+                    &probabilities,
+                    LargestRemainder,
+                )
+                .unwrap();
+            test_iterable_entropy_model(&decoder_model, symbols.iter().cloned());
+            for (symbol, left_cumulative, probability) in decoder_model.symbol_table() {
+                assert_eq!(
+                    encoder_model.left_cumulative_and_probability(symbol),
+                    Some((left_cumulative, probability))
+                );
+            }
+        }
+
+        assert!(
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+                &[0.5, -0.1, 0.6],
+                RoundHalfEven
+            )
+            .is_err()
+        );
+    }
+
     fn test_entropy_model<'m, D, const PRECISION: usize>(
         model: &'m D,
         support: impl Clone + Iterator<Item = D::Symbol>,
@@ -4130,6 +7513,13 @@ mod tests {
         }
         assert_eq!(sum, 1 << PRECISION);
 
+        for (symbol, left_cumulative, prob) in model.symbol_table() {
+            assert_eq!(
+                model.left_cumulative_and_probability(symbol).unwrap(),
+                (left_cumulative, prob)
+            );
+        }
+
         test_iterable_entropy_model(model, support);
     }
 
@@ -4221,7 +7611,7 @@ mod tests {
     #[test]
     fn lookup_noncontiguous() {
         let symbols = "axcy";
-        let probabilities = vec![3u8, 18, 1, 42];
+        let probabilities = [3u8, 18, 1, 42];
         let encoder_model = NonContiguousCategoricalEncoderModel::<_, u8, 6>::from_symbols_and_nonzero_fixed_point_probabilities(
             symbols.chars(),probabilities.iter(),false
         )
@@ -4277,4 +7667,90 @@ mod tests {
         assert_eq!(decoded, symbols);
         assert!(ans.is_empty());
     }
+
+    #[test]
+    fn lookup_from_model() {
+        let quantizer = LeakyQuantizer::<_, _, u16, 12>::new(-100..=100);
+        for &(mean, std_dev) in &[(3.2, 10.0), (-150.0, 20.0), (0.0, 0.01), (50.5, 1000.0)] {
+            let model = quantizer.quantize(Gaussian::new(mean, std_dev));
+            let lookup_model = LookupDecoderModel::from_model(&model);
+            for quantile in 0..1 << 12 {
+                assert_eq!(
+                    lookup_model.quantile_function(quantile),
+                    model.quantile_function(quantile)
+                );
+            }
+            assert!(lookup_model.symbol_table().eq(model.symbol_table()));
+        }
+
+        // A model that doesn't implement `IterableEntropyModel`.
+        let model = geometric::SmallTwoSidedGeometric::new_closed_form(-30..=30, 0.8, 2.5).unwrap();
+        let lookup_model = LookupDecoderModel::from_model(&model);
+        for quantile in 0..1 << 12 {
+            assert_eq!(
+                lookup_model.quantile_function(quantile),
+                model.quantile_function(quantile)
+            );
+        }
+    }
+
+    #[test]
+    fn contiguous_categorical_with_decoder_index() {
+        // Small alphabets and low precision: compare the full quantile functions.
+        for probabilities in [
+            vec![0.3, 0.7],
+            vec![0.1, 0.001, 0.0001, 0.5, 0.3, 0.0001, 0.0988],
+            (0..100).map(|i| (i % 7) as f64 + 0.01).collect(),
+        ] {
+            let model =
+                ContiguousCategoricalEntropyModel::<u16, _, 12>::from_floating_point_probabilities(
+                    &probabilities,
+                )
+                .unwrap();
+            let indexed = model.clone().with_decoder_index();
+            for quantile in 0..1 << 12 {
+                assert_eq!(
+                    indexed.quantile_function(quantile),
+                    model.quantile_function(quantile)
+                );
+            }
+            test_entropy_model(&indexed, 0..probabilities.len());
+        }
+
+        // Large alphabet with full precision.
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let probabilities = (0..5_000)
+            .map(|_| {
+                let x = (rng.next_u32() as f64 / u32::MAX as f64) * 20.0;
+                (-x).exp()
+            })
+            .collect::<Vec<f64>>();
+        let model =
+            ContiguousCategoricalEntropyModel::<u32, _, 32>::from_floating_point_probabilities(
+                &probabilities,
+            )
+            .unwrap();
+        let indexed = model.clone().with_decoder_index();
+        assert_eq!(indexed.inner().support_size(), 5_000);
+        for _ in 0..10_000 {
+            let quantile = rng.next_u32();
+            assert_eq!(
+                indexed.quantile_function(quantile),
+                model.quantile_function(quantile)
+            );
+        }
+        test_entropy_model(&indexed, 0..probabilities.len());
+
+        let symbols = (0..1000)
+            .map(|_| rng.next_u32() as usize % probabilities.len())
+            .collect::<Vec<_>>();
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let decoded = ans
+            .decode_iid_symbols(symbols.len(), &indexed)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(ans.is_empty());
+    }
 }
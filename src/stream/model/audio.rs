@@ -0,0 +1,503 @@
+//! Entropy models for prediction residuals of audio signals
+//!
+//! Lossless audio codecs typically run a linear predictor over the samples and entropy code
+//! the prediction residuals, i.e., the differences between the actual and the predicted
+//! samples. These residuals are centered around zero and roughly Laplace distributed, but
+//! their scale changes with the loudness of the signal, and transients produce occasional
+//! outliers that a plain Laplace distribution considers extremely improbable. This module
+//! provides two models that address these issues and that you can use as a starting point
+//! for lossless audio experiments:
+//!
+//! - [`LongTailLaplace`] is a continuous distribution that mixes a Laplace distribution
+//!   with a wider one. Quantized to integers with a [`LeakyQuantizer`], it turns into a
+//!   mixture of two two-sided geometric distributions, which keeps the cost of outliers
+//!   bounded.
+//! - [`AdaptiveLaplace`] is an adaptive entropy model that tracks the scale of the
+//!   residuals as it encodes or decodes them, similar to the adaptive models in
+//!   [`stream::adaptive`](crate::stream::adaptive).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::audio::DefaultAdaptiveLaplace,
+//!     queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+//! };
+//!
+//! // Residuals of a signal that gets louder halfway through.
+//! let residuals = [0, 1, -1, 0, 2, -1, 0, 1, 35, -48, 22, 61, -17, -40, 53, 8];
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! let mut model = DefaultAdaptiveLaplace::new(-1000..=1000, 1.0, 0.25).unwrap();
+//! for &residual in &residuals {
+//!     model.encode_symbol(&mut encoder, residual).unwrap();
+//! }
+//! let compressed = encoder.into_compressed().unwrap();
+//!
+//! // The decoder starts from the same initial state and performs the same updates.
+//! let mut decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+//! let mut model = DefaultAdaptiveLaplace::new(-1000..=1000, 1.0, 0.25).unwrap();
+//! let decoded = (0..residuals.len())
+//!     .map(|_| model.decode_symbol(&mut decoder).unwrap())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(decoded, residuals);
+//! ```
+
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use num::cast::AsPrimitive;
+use probability::distribution::{Distribution, Inverse, Laplace};
+
+use super::{LeakilyQuantizedDistribution, LeakyQuantizer};
+use crate::{
+    stream::{Decode, Encode},
+    BitArray, CoderError,
+};
+
+/// A mixture of two Laplace distributions with the same mean but different scales.
+///
+/// With probability `1 - tail_weight`, a sample is drawn from a Laplace distribution with
+/// the given `mean` and `scale`; with probability `tail_weight`, it is drawn from a Laplace
+/// distribution whose scale is `tail_factor` times larger. The second component gives the
+/// distribution a heavier tail than a single Laplace distribution with the same core, so
+/// that outliers (e.g., transients in an audio signal) cost at most about
+/// `-log2(tail_weight)` bits more than under a model that expected them.
+///
+/// Use a [`LeakyQuantizer`] to turn a `LongTailLaplace` into an entropy model.
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{audio::LongTailLaplace, DefaultLeakyQuantizer},
+///     stack::DefaultAnsCoder,
+///     Decode,
+/// };
+///
+/// let quantizer = DefaultLeakyQuantizer::new(-32768..=32767);
+/// let model = quantizer.quantize(LongTailLaplace::new(0.0, 3.0, 0.01, 100.0));
+/// let residuals = [2, -1, 0, 5, -3, 1200, -2, 0];
+///
+/// let mut coder = DefaultAnsCoder::new();
+/// coder.encode_iid_symbols_reverse(&residuals, model).unwrap();
+/// let decoded = coder
+///     .decode_iid_symbols(residuals.len(), model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, residuals);
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct LongTailLaplace {
+    core: Laplace,
+    tail: Laplace,
+    tail_weight: f64,
+}
+
+impl LongTailLaplace {
+    /// Creates a mixture of `Laplace(mean, scale)` (with weight `1 - tail_weight`) and
+    /// `Laplace(mean, tail_factor * scale)` (with weight `tail_weight`).
+    ///
+    /// # Panics
+    ///
+    /// If `scale` is not positive, if `tail_weight` is not within the interval `[0, 1]`,
+    /// or if `tail_factor` is smaller than one. Use [`try_new`](Self::try_new) for
+    /// parameters that come from an untrusted source.
+    pub fn new(mean: f64, scale: f64, tail_weight: f64, tail_factor: f64) -> Self {
+        assert!(scale > 0.0);
+        assert!((0.0..=1.0).contains(&tail_weight));
+        assert!(tail_factor >= 1.0);
+        Self {
+            core: Laplace::new(mean, scale),
+            tail: Laplace::new(mean, tail_factor * scale),
+            tail_weight,
+        }
+    }
+
+    /// Same as [`new`](Self::new) but returns an error instead of panicking.
+    ///
+    /// Also returns an error if `mean` is not finite, or if `scale` or `tail_factor *
+    /// scale` is infinite (which includes NaN parameters).
+    #[allow(clippy::result_unit_err)]
+    pub fn try_new(mean: f64, scale: f64, tail_weight: f64, tail_factor: f64) -> Result<Self, ()> {
+        if mean.is_finite()
+            && scale > 0.0
+            && (0.0..=1.0).contains(&tail_weight)
+            && tail_factor >= 1.0
+            && (tail_factor * scale).is_finite()
+        {
+            Ok(Self::new(mean, scale, tail_weight, tail_factor))
+        } else {
+            Err(())
+        }
+    }
+
+    /// Returns the mean of both mixture components.
+    pub fn mean(&self) -> f64 {
+        self.core.mu()
+    }
+
+    /// Returns the scale of the core component.
+    pub fn scale(&self) -> f64 {
+        self.core.b()
+    }
+
+    /// Returns the weight of the wide component.
+    pub fn tail_weight(&self) -> f64 {
+        self.tail_weight
+    }
+
+    /// Returns the ratio between the scales of the wide and the core component.
+    pub fn tail_factor(&self) -> f64 {
+        self.tail.b() / self.core.b()
+    }
+}
+
+impl Distribution for LongTailLaplace {
+    type Value = f64;
+
+    fn distribution(&self, x: f64) -> f64 {
+        (1.0 - self.tail_weight) * self.core.distribution(x)
+            + self.tail_weight * self.tail.distribution(x)
+    }
+}
+
+impl Inverse for LongTailLaplace {
+    /// Inverts the cumulative distribution function by bisection.
+    ///
+    /// Since both components have the same mean, the inverse of the mixture lies between
+    /// the inverses of the two components. The result only has to be approximate because a
+    /// [`LeakilyQuantizedDistribution`] uses it only as the starting point for an exact
+    /// search.
+    fn inverse(&self, p: f64) -> f64 {
+        let (a, b) = (self.core.inverse(p), self.tail.inverse(p));
+        let (mut low, mut high) = if a <= b { (a, b) } else { (b, a) };
+        if !low.is_finite() || !high.is_finite() {
+            return if p < 0.5 { low } else { high };
+        }
+
+        for _ in 0..40 {
+            let mid = 0.5 * (low + high);
+            if self.distribution(mid) < p {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        0.5 * (low + high)
+    }
+}
+
+/// An adaptive entropy model for residuals whose scale drifts over time.
+///
+/// An `AdaptiveLaplace` models each residual with a [`Laplace`] distribution centered at
+/// zero, quantized to the integers in `support`. Its scale is an exponential moving
+/// average of the magnitudes of all preceding residuals, which is the running maximum
+/// likelihood estimate of the scale of a Laplace distribution:
+///
+/// ```text
+/// mean_abs <- mean_abs + rate * (|residual| - mean_abs)
+/// ```
+///
+/// Larger values of `rate` let the model react faster to changes in loudness, and smaller
+/// values make the estimate less noisy on stationary signals. The scale never drops below
+/// [`MIN_SCALE`](Self::MIN_SCALE) so that digital silence doesn't produce a degenerate
+/// model.
+///
+/// Like the models in [`stream::adaptive`](crate::stream::adaptive), an `AdaptiveLaplace`
+/// updates itself after each encoded or decoded residual, so the decoder has to start
+/// from an identical model. See [module level documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct AdaptiveLaplace<Probability: BitArray, const PRECISION: usize> {
+    quantizer: LeakyQuantizer<f64, i32, Probability, PRECISION>,
+    mean_abs: f64,
+    rate: f64,
+}
+
+/// Type alias for an [`AdaptiveLaplace`] with sane settings.
+///
+/// Generates entropy models that can be used with a [`DefaultAnsCoder`] or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultAdaptiveLaplace = AdaptiveLaplace<u32, 24>;
+
+impl<Probability: BitArray, const PRECISION: usize> AdaptiveLaplace<Probability, PRECISION>
+where
+    Probability: Into<f64>,
+    f64: AsPrimitive<Probability>,
+    i32: AsPrimitive<Probability>,
+{
+    /// The smallest scale of the Laplace distribution that the model will use.
+    pub const MIN_SCALE: f64 = 0.05;
+
+    /// Creates a model for residuals in `support` whose scale starts at `initial_scale`.
+    ///
+    /// Returns an error if `initial_scale` is not positive or if `rate` is not within the
+    /// half-open interval `(0, 1]`.
+    ///
+    /// # Panics
+    ///
+    /// If `support` is empty or contains only a single symbol (see
+    /// [`LeakyQuantizer::new`]).
+    #[allow(clippy::result_unit_err)]
+    pub fn new(support: RangeInclusive<i32>, initial_scale: f64, rate: f64) -> Result<Self, ()> {
+        if initial_scale.is_nan() || initial_scale <= 0.0 || rate.is_nan() {
+            return Err(());
+        }
+        if rate <= 0.0 || rate > 1.0 {
+            return Err(());
+        }
+
+        Ok(Self {
+            quantizer: LeakyQuantizer::new(support),
+            mean_abs: initial_scale,
+            rate,
+        })
+    }
+
+    /// Returns the scale of the Laplace distribution for the next residual.
+    pub fn scale(&self) -> f64 {
+        self.mean_abs.max(Self::MIN_SCALE)
+    }
+
+    /// Returns the entropy model for the next residual.
+    pub fn model(&self) -> LeakilyQuantizedDistribution<f64, i32, Probability, Laplace, PRECISION> {
+        self.quantizer.quantize(Laplace::new(0.0, self.scale()))
+    }
+
+    /// Updates the scale estimate with an observed `residual`.
+    ///
+    /// You'll usually not have to call this method directly since [`encode_symbol`] and
+    /// [`decode_symbol`] call it for you.
+    ///
+    /// [`encode_symbol`]: Self::encode_symbol
+    /// [`decode_symbol`]: Self::decode_symbol
+    pub fn update(&mut self, residual: i32) {
+        let magnitude = (residual as f64).abs();
+        self.mean_abs += self.rate * (magnitude - self.mean_abs);
+    }
+
+    /// Encodes `residual` with the current [`model`](Self::model) and then [`update`]s the
+    /// scale estimate.
+    ///
+    /// Use this method with coders that have queue semantics (e.g., a [`RangeEncoder`]).
+    /// For coders with stack semantics, use [`encode_symbols_reverse`] instead.
+    ///
+    /// [`update`]: Self::update
+    /// [`RangeEncoder`]: crate::stream::queue::RangeEncoder
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    pub fn encode_symbol<E>(
+        &mut self,
+        encoder: &mut E,
+        residual: i32,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        encoder.encode_symbol(residual, self.model())?;
+        self.update(residual);
+        Ok(())
+    }
+
+    /// Encodes `residuals` on a coder with stack semantics (e.g., an [`AnsCoder`]) such
+    /// that calling [`decode_symbol`] repeatedly decodes them in their original order.
+    ///
+    /// Afterwards, the model is in the same state as after calling [`encode_symbol`] for
+    /// each residual.
+    ///
+    /// [`AnsCoder`]: crate::stream::stack::AnsCoder
+    /// [`decode_symbol`]: Self::decode_symbol
+    /// [`encode_symbol`]: Self::encode_symbol
+    pub fn encode_symbols_reverse<E>(
+        &mut self,
+        encoder: &mut E,
+        residuals: &[i32],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        Probability: Into<E::Word>,
+        E::Word: AsPrimitive<Probability>,
+    {
+        // Only the scales have to be recorded since the quantizer stays the same.
+        let scales = residuals
+            .iter()
+            .map(|&residual| {
+                let scale = self.scale();
+                self.update(residual);
+                scale
+            })
+            .collect::<Vec<_>>();
+        let quantizer = &self.quantizer;
+        encoder.encode_symbols(
+            residuals
+                .iter()
+                .zip(scales)
+                .rev()
+                .map(|(residual, scale)| (residual, quantizer.quantize(Laplace::new(0.0, scale)))),
+        )
+    }
+
+    /// Decodes a residual with the current [`model`](Self::model) and then [`update`]s the
+    /// scale estimate.
+    ///
+    /// [`update`]: Self::update
+    pub fn decode_symbol<D>(
+        &mut self,
+        decoder: &mut D,
+    ) -> Result<i32, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        Probability: Into<D::Word>,
+        D::Word: AsPrimitive<Probability>,
+    {
+        let residual = decoder.decode_symbol(self.model())?;
+        self.update(residual);
+        Ok(residual)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{DefaultLeakyQuantizer, EncoderModel},
+        *,
+    };
+    use crate::stream::{
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+    };
+
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    /// Generates an AR(1) process `x[t] = 0.9 * x[t-1] + noise[t]` with Laplace noise whose
+    /// scale jumps between quiet and loud passages, and returns the residuals of the
+    /// predictor `round(0.9 * x[t-1])`.
+    fn ar1_residuals(amt: usize, seed: u64) -> Vec<i32> {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(seed);
+        let mut x = 0i32;
+        (0..amt)
+            .map(|t| {
+                let scale = if (t / 500) % 2 == 0 { 2.0 } else { 200.0 };
+                let uniform = (rng.next_u32() as f64 + 0.5) / (1u64 << 32) as f64;
+                let noise = Laplace::new(0.0, scale).inverse(uniform);
+                let prediction = (0.9 * x as f64).round() as i32;
+                x = (0.9 * x as f64 + noise).round().clamp(-32768.0, 32767.0) as i32;
+                x - prediction
+            })
+            .collect()
+    }
+
+    #[test]
+    fn long_tail_laplace_inverse() {
+        let distribution = LongTailLaplace::new(1.5, 2.0, 0.05, 50.0);
+        for &p in &[1e-6, 0.01, 0.2, 0.5, 0.7, 0.99, 1.0 - 1e-6] {
+            let x = distribution.inverse(p);
+            assert!((distribution.distribution(x) - p).abs() < 1e-9);
+        }
+        assert_eq!(distribution.inverse(0.0), f64::NEG_INFINITY);
+        assert_eq!(distribution.inverse(1.0), f64::INFINITY);
+        assert!((distribution.tail_factor() - 50.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn long_tail_laplace_invalid_parameters() {
+        let distribution = LongTailLaplace::try_new(1.5, 2.0, 0.05, 50.0).unwrap();
+        assert_eq!(distribution.tail_factor(), 50.0);
+        assert!(LongTailLaplace::try_new(1.5, 0.0, 0.05, 50.0).is_err());
+        assert!(LongTailLaplace::try_new(1.5, f64::NAN, 0.05, 50.0).is_err());
+        assert!(LongTailLaplace::try_new(1.5, f64::INFINITY, 0.05, 50.0).is_err());
+        assert!(LongTailLaplace::try_new(f64::NAN, 2.0, 0.05, 50.0).is_err());
+        assert!(LongTailLaplace::try_new(1.5, 2.0, -0.1, 50.0).is_err());
+        assert!(LongTailLaplace::try_new(1.5, 2.0, 1.1, 50.0).is_err());
+        assert!(LongTailLaplace::try_new(1.5, 2.0, 0.05, 0.5).is_err());
+        assert!(LongTailLaplace::try_new(1.5, 2.0, 0.05, f64::INFINITY).is_err());
+        assert!(LongTailLaplace::try_new(1.5, 1e300, 0.05, 1e10).is_err());
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn long_tail_laplace_bounds_outliers() {
+        let quantizer = DefaultLeakyQuantizer::new(-32768..=32767);
+        let plain = quantizer.quantize(Laplace::new(0.0, 2.0));
+        let long_tail = quantizer.quantize(LongTailLaplace::new(0.0, 2.0, 0.01, 1000.0));
+
+        let bits = |probability: u32| 24.0 - (probability as f64).log2();
+        let (_, outlier_plain) = plain.left_cumulative_and_probability(1000).unwrap();
+        let (_, outlier_long_tail) = long_tail.left_cumulative_and_probability(1000).unwrap();
+        assert!(bits(outlier_long_tail.get()) < 20.0);
+        assert!(bits(outlier_plain.get()) > 23.0);
+
+        // The core of the distribution costs only slightly more.
+        let (_, zero_plain) = plain.left_cumulative_and_probability(0).unwrap();
+        let (_, zero_long_tail) = long_tail.left_cumulative_and_probability(0).unwrap();
+        assert!(bits(zero_long_tail.get()) - bits(zero_plain.get()) < 0.02);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn ar1_round_trip() {
+        let residuals = ar1_residuals(4000, 745);
+
+        // Adaptive model on a queue.
+        let mut encoder = DefaultRangeEncoder::new();
+        let mut model = DefaultAdaptiveLaplace::new(-65536..=65536, 1.0, 0.05).unwrap();
+        for &residual in &residuals {
+            model.encode_symbol(&mut encoder, residual).unwrap();
+        }
+        let adaptive_bits = encoder.num_bits();
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        let mut model = DefaultAdaptiveLaplace::new(-65536..=65536, 1.0, 0.05).unwrap();
+        for &residual in &residuals {
+            assert_eq!(model.decode_symbol(&mut decoder).unwrap(), residual);
+        }
+        assert!(decoder.maybe_exhausted());
+
+        // Adaptive model on a stack.
+        let mut coder = DefaultAnsCoder::new();
+        let mut encoder_model = DefaultAdaptiveLaplace::new(-65536..=65536, 1.0, 0.05).unwrap();
+        encoder_model
+            .encode_symbols_reverse(&mut coder, &residuals)
+            .unwrap();
+        let mut decoder_model = DefaultAdaptiveLaplace::new(-65536..=65536, 1.0, 0.05).unwrap();
+        for &residual in &residuals {
+            assert_eq!(decoder_model.decode_symbol(&mut coder).unwrap(), residual);
+        }
+        assert!(coder.is_empty());
+        assert_eq!(decoder_model.scale(), encoder_model.scale());
+
+        // Fixed long tail model, with a scale that fits neither passage particularly well.
+        let quantizer = DefaultLeakyQuantizer::new(-65536..=65536);
+        let long_tail = quantizer.quantize(LongTailLaplace::new(0.0, 20.0, 0.1, 10.0));
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&residuals, long_tail).unwrap();
+        let fixed_bits = encoder.num_bits();
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(residuals.len(), long_tail)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, residuals);
+
+        // Adapting to the loudness pays off.
+        assert!(adaptive_bits < fixed_bits * 9 / 10);
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DefaultAdaptiveLaplace::new(-10..=10, 0.0, 0.1).is_err());
+        assert!(DefaultAdaptiveLaplace::new(-10..=10, f64::NAN, 0.1).is_err());
+        assert!(DefaultAdaptiveLaplace::new(-10..=10, 1.0, 0.0).is_err());
+        assert!(DefaultAdaptiveLaplace::new(-10..=10, 1.0, 1.5).is_err());
+        assert!(DefaultAdaptiveLaplace::new(-10..=10, 1.0, 1.0).is_ok());
+    }
+}
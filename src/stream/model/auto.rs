@@ -0,0 +1,707 @@
+//! Automatic selection of an entropy model for some sample data
+//!
+//! If you have some integer data but don't know which family of entropy models fits it
+//! best, then [`auto_model`] (or its generic variant [`AutoModel::fit`]) can choose one for
+//! you. It fits several candidate families to the data:
+//!
+//! - a categorical model over the histogram of the data;
+//! - a quantized Gaussian distribution with the mean and standard deviation of the data;
+//! - a quantized Laplace distribution with the median of the data and the mean absolute
+//!   deviation from it; and
+//! - a geometric distribution over the offsets from the smallest value in the data.
+//!
+//! It then evaluates the cross entropy of each candidate on the data. The cross entropy is
+//! evaluated with the *fixed point* probabilities that an entropy coder would actually use
+//! at the given `PRECISION`, so it accounts for rounding and for the probability mass that
+//! leaky models reserve for improbable symbols.
+//!
+//! A decoder has to be able to reconstruct the chosen model, so the encoder usually has to
+//! transmit its [`ModelSpec`] alongside the compressed data. A spec for a categorical model
+//! holds the full histogram of the data, so it's much larger than the spec for one of the
+//! parametric models, and it pays off only for large data sets. Model selection therefore
+//! adds the size of the serialized spec, amortized over the data, to the cross entropy and
+//! picks the candidate with the smallest total. The parameter `budget_bits_per_symbol`
+//! sets an upper bound on this amortized size; candidates whose spec exceeds it aren't
+//! considered at all.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::auto::{auto_model, DefaultAutoModel, ModelSpec},
+//!     queue::DefaultRangeEncoder,
+//!     Decode, Encode,
+//! };
+//!
+//! let data = [0, 1, 0, 2, 0, 0, 1, 3, 0, 1, 0, 0, 5, 0, 2, 1, 0, 0, 1, 0];
+//! let model = auto_model(&data, 8.0).unwrap();
+//! println!(
+//!     "Selected {:?} at {:.2} bits per symbol.",
+//!     model.spec(),
+//!     model.cross_entropy(&data)
+//! );
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! encoder.encode_iid_symbols(&data, &model).unwrap();
+//! let compressed = encoder.into_compressed().unwrap();
+//! let spec = model.spec().to_bytes(); // Transmit this along with `compressed`.
+//!
+//! // On the decoder side, reconstruct the identical model from the spec.
+//! let model = DefaultAutoModel::from_spec(ModelSpec::from_bytes(&spec).unwrap()).unwrap();
+//! let mut decoder = constriction::stream::queue::DefaultRangeDecoder::from_compressed(
+//!     compressed,
+//! )
+//! .unwrap();
+//! let decoded = decoder
+//!     .decode_iid_symbols(data.len(), &model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, data);
+//! ```
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, convert::TryInto, ops::RangeInclusive};
+
+use num::cast::AsPrimitive;
+use probability::distribution::{Distribution, Gaussian, Inverse, Laplace};
+
+use super::{
+    geometric::Geometric, ContiguousCategoricalEntropyModel, DecoderModel, EncoderModel,
+    EntropyModel, LeakyQuantizer, RoundingPolicy,
+};
+use crate::{BitArray, NonZeroBitArray};
+
+/// A serializable description of an entropy model that an [`AutoModel`] can be
+/// reconstructed from.
+///
+/// Each variant stores the (inclusive) range of symbols that the model supports along with
+/// the parameters of its family. Use [`to_bytes`](Self::to_bytes) and
+/// [`from_bytes`](Self::from_bytes) to convert a `ModelSpec` to and from a compact,
+/// platform independent binary representation.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelSpec {
+    /// A categorical model whose probabilities are proportional to `counts`, where
+    /// `counts[i]` is the number of occurrences of the symbol `min_symbol + i`.
+    ///
+    /// Symbols with a count of zero still get a small nonzero probability.
+    Categorical { min_symbol: i32, counts: Vec<u32> },
+
+    /// A Gaussian distribution, quantized to the integers in `support`.
+    Gaussian {
+        support: RangeInclusive<i32>,
+        mean: f64,
+        std: f64,
+    },
+
+    /// A Laplace distribution, quantized to the integers in `support`.
+    Laplace {
+        support: RangeInclusive<i32>,
+        location: f64,
+        scale: f64,
+    },
+
+    /// A geometric distribution with success probability `p` over the offsets of the
+    /// symbols in `support` from `support.start()`, with its tail folded into
+    /// `support.end()` (see [`Geometric`]).
+    Geometric {
+        support: RangeInclusive<i32>,
+        p: f64,
+    },
+}
+
+const TAG_CATEGORICAL: u8 = 0;
+const TAG_GAUSSIAN: u8 = 1;
+const TAG_LAPLACE: u8 = 2;
+const TAG_GEOMETRIC: u8 = 3;
+
+impl ModelSpec {
+    /// Returns the range of symbols that the described model can encode.
+    pub fn support(&self) -> RangeInclusive<i32> {
+        match self {
+            Self::Categorical { min_symbol, counts } => {
+                let max_symbol = *min_symbol as i64 + counts.len() as i64 - 1;
+                *min_symbol..=max_symbol.clamp(i32::MIN as i64, i32::MAX as i64) as i32
+            }
+            Self::Gaussian { support, .. }
+            | Self::Laplace { support, .. }
+            | Self::Geometric { support, .. } => support.clone(),
+        }
+    }
+
+    /// Serializes the spec into a compact binary representation.
+    ///
+    /// The representation starts with a tag byte that identifies the family, followed by
+    /// the bounds of the support as little-endian `i32`s. Then come the parameters of the
+    /// model: the `counts` of a categorical model as LEB128 variable length integers, and
+    /// the parameters of all other families as little-endian `f64`s.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let support = self.support();
+        let mut bytes = Vec::new();
+        let tag = match self {
+            Self::Categorical { .. } => TAG_CATEGORICAL,
+            Self::Gaussian { .. } => TAG_GAUSSIAN,
+            Self::Laplace { .. } => TAG_LAPLACE,
+            Self::Geometric { .. } => TAG_GEOMETRIC,
+        };
+        bytes.push(tag);
+        bytes.extend_from_slice(&support.start().to_le_bytes());
+        bytes.extend_from_slice(&support.end().to_le_bytes());
+
+        match self {
+            Self::Categorical { counts, .. } => {
+                for &count in counts {
+                    let mut count = count;
+                    while count >= 0x80 {
+                        bytes.push(count as u8 | 0x80);
+                        count >>= 7;
+                    }
+                    bytes.push(count as u8);
+                }
+            }
+            Self::Gaussian {
+                mean: a, std: b, ..
+            }
+            | Self::Laplace {
+                location: a,
+                scale: b,
+                ..
+            } => {
+                bytes.extend_from_slice(&a.to_le_bytes());
+                bytes.extend_from_slice(&b.to_le_bytes());
+            }
+            Self::Geometric { p, .. } => bytes.extend_from_slice(&p.to_le_bytes()),
+        }
+
+        bytes
+    }
+
+    /// Deserializes a spec that was serialized with [`to_bytes`](Self::to_bytes).
+    ///
+    /// Returns an error if `bytes` is malformed, truncated, or has trailing bytes. This
+    /// method doesn't check whether the parameters are valid; [`AutoModel::from_spec`]
+    /// does that.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ()> {
+        let (&tag, mut rest) = bytes.split_first().ok_or(())?;
+        let mut take = |len: usize| -> Result<&[u8], ()> {
+            if rest.len() < len {
+                return Err(());
+            }
+            let (head, tail) = rest.split_at(len);
+            rest = tail;
+            Ok(head)
+        };
+        let min_symbol = i32::from_le_bytes(take(4)?.try_into().unwrap());
+        let max_symbol = i32::from_le_bytes(take(4)?.try_into().unwrap());
+        if max_symbol < min_symbol {
+            return Err(());
+        }
+        let support = min_symbol..=max_symbol;
+        let mut take_f64 =
+            || -> Result<f64, ()> { Ok(f64::from_le_bytes(take(8)?.try_into().unwrap())) };
+
+        let spec = match tag {
+            TAG_CATEGORICAL => {
+                let support_size = (max_symbol as i64 - min_symbol as i64 + 1) as usize;
+                // Each count takes at least one byte, so this doesn't overallocate.
+                if support_size > rest.len() {
+                    return Err(());
+                }
+                let mut counts = Vec::with_capacity(support_size);
+                let mut iter = rest.iter();
+                for _ in 0..support_size {
+                    let mut count = 0u32;
+                    for shift in (0..35).step_by(7) {
+                        let byte = *iter.next().ok_or(())?;
+                        if shift == 28 && byte > 0x0f {
+                            return Err(()); // Overflow.
+                        }
+                        count |= ((byte & 0x7f) as u32) << shift;
+                        if byte & 0x80 == 0 {
+                            break;
+                        }
+                    }
+                    counts.push(count);
+                }
+                rest = iter.as_slice();
+                Self::Categorical { min_symbol, counts }
+            }
+            TAG_GAUSSIAN => Self::Gaussian {
+                support,
+                mean: take_f64()?,
+                std: take_f64()?,
+            },
+            TAG_LAPLACE => Self::Laplace {
+                support,
+                location: take_f64()?,
+                scale: take_f64()?,
+            },
+            TAG_GEOMETRIC => Self::Geometric {
+                support,
+                p: take_f64()?,
+            },
+            _ => return Err(()),
+        };
+
+        if !rest.is_empty() {
+            return Err(());
+        }
+        Ok(spec)
+    }
+}
+
+/// An entropy model over `i32` symbols that was selected by [`AutoModel::fit`] or
+/// reconstructed from a [`ModelSpec`].
+///
+/// Regardless of the family, the model is tabulated as a
+/// [`ContiguousCategoricalEntropyModel`] over its support, so encoding and decoding are
+/// equally fast for all families, and reconstructing a model from its spec takes time
+/// linear in the size of the support. See [module level documentation](self) for an
+/// example.
+#[derive(Debug, Clone)]
+pub struct AutoModel<Probability: BitArray, const PRECISION: usize> {
+    spec: ModelSpec,
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+/// Type alias for an [`AutoModel`] with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultAutoModel = AutoModel<u32, 24>;
+
+/// Type alias for an [`AutoModel`] that can be used with coders that use `u16` words.
+///
+/// Note that the support has to fit into the `2^12` available fixed point probability
+/// units, i.e., the data may span at most `4096` consecutive integers.
+pub type SmallAutoModel = AutoModel<u16, 12>;
+
+/// Selects an entropy model for `samples` with sane settings.
+///
+/// This is a shortcut for [`DefaultAutoModel::fit`]; see there for details.
+#[allow(clippy::result_unit_err)]
+pub fn auto_model(samples: &[i32], budget_bits_per_symbol: f64) -> Result<DefaultAutoModel, ()> {
+    DefaultAutoModel::fit(samples, budget_bits_per_symbol)
+}
+
+impl<Probability: BitArray, const PRECISION: usize> AutoModel<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+    i32: AsPrimitive<Probability>,
+{
+    /// The smallest standard deviation or scale that a fitted Gaussian or Laplace
+    /// distribution will have, so that constant data doesn't produce a degenerate model.
+    pub const MIN_SCALE: f64 = 0.1;
+
+    /// Fits all candidate families to `samples` and returns the one that minimizes the
+    /// cross entropy plus the amortized size of its spec.
+    ///
+    /// The support of the returned model is the range from the smallest to the largest
+    /// value in `samples`, widened by one if all samples are equal (since an entropy model
+    /// needs at least two symbols). Candidates whose serialized [`ModelSpec`] takes more
+    /// than `budget_bits_per_symbol` bits per sample aren't considered. See [module level
+    /// documentation](self) for details. Candidates that can't represent the support
+    /// (e.g., a [`Geometric`] over more than `1 << (PRECISION - 1)` symbols) aren't
+    /// considered either.
+    ///
+    /// Returns an error if `samples` is empty, if `budget_bits_per_symbol` is negative or
+    /// NaN, if the support is larger than `1 << PRECISION` (since every symbol needs a
+    /// nonzero probability), or if no candidate fits within the budget. The run time is
+    /// linear in the number of samples plus the size of the support.
+    #[allow(clippy::result_unit_err)]
+    pub fn fit(samples: &[i32], budget_bits_per_symbol: f64) -> Result<Self, ()> {
+        if budget_bits_per_symbol.is_nan() || budget_bits_per_symbol < 0.0 {
+            return Err(());
+        }
+        let min_symbol = *samples.iter().min().ok_or(())?;
+        let max_symbol = *samples.iter().max().ok_or(())?;
+        let support = match (min_symbol, max_symbol) {
+            (min, max) if min != max => min..=max,
+            (min, max) if max != i32::MAX => min..=max + 1,
+            (min, max) => min - 1..=max,
+        };
+        check_support_size::<PRECISION>(&support)?;
+
+        let n = samples.len() as f64;
+        let mean = samples.iter().map(|&x| x as f64).sum::<f64>() / n;
+        let variance = samples
+            .iter()
+            .map(|&x| (x as f64 - mean) * (x as f64 - mean))
+            .sum::<f64>()
+            / n;
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+        let median = sorted[(sorted.len() - 1) / 2] as f64;
+        let mean_abs_deviation = samples
+            .iter()
+            .map(|&x| (x as f64 - median).abs())
+            .sum::<f64>()
+            / n;
+
+        let start = *support.start();
+        let mut counts = alloc::vec![0u32; (*support.end() as i64 - start as i64 + 1) as usize];
+        for &x in samples {
+            let count = &mut counts[(x as i64 - start as i64) as usize];
+            *count = count.saturating_add(1);
+        }
+
+        // Simpler models come first so that they win ties.
+        let candidates = [
+            ModelSpec::Geometric {
+                support: support.clone(),
+                p: 1.0 / (1.0 + mean - start as f64),
+            },
+            ModelSpec::Laplace {
+                support: support.clone(),
+                location: median,
+                scale: mean_abs_deviation.max(Self::MIN_SCALE),
+            },
+            ModelSpec::Gaussian {
+                support: support.clone(),
+                mean,
+                std: variance.sqrt().max(Self::MIN_SCALE),
+            },
+            ModelSpec::Categorical {
+                min_symbol: start,
+                counts,
+            },
+        ];
+
+        let mut best = None;
+        for spec in candidates.iter() {
+            let spec_bits_per_symbol = (8 * spec.to_bytes().len()) as f64 / n;
+            if spec_bits_per_symbol > budget_bits_per_symbol {
+                continue;
+            }
+            let model = match Self::from_spec(spec.clone()) {
+                Ok(model) => model,
+                Err(()) => continue,
+            };
+            let cost = model.cross_entropy(samples) + spec_bits_per_symbol;
+            match &best {
+                Some((best_cost, _)) if *best_cost <= cost => {}
+                _ => best = Some((cost, model)),
+            }
+        }
+
+        best.map(|(_, model)| model).ok_or(())
+    }
+
+    /// Reconstructs the model that `spec` describes.
+    ///
+    /// Returns an error if the parameters in `spec` are invalid (e.g., a negative
+    /// standard deviation), if the support contains fewer than two symbols, or if the
+    /// support is larger than `1 << PRECISION` (or, for a [`ModelSpec::Geometric`], than
+    /// `1 << (PRECISION - 1)`, see [`Geometric::new`]).
+    #[allow(clippy::result_unit_err)]
+    pub fn from_spec(spec: ModelSpec) -> Result<Self, ()> {
+        let support = spec.support();
+        if support.end() <= support.start() {
+            return Err(());
+        }
+        check_support_size::<PRECISION>(&support)?;
+
+        let model = match &spec {
+            ModelSpec::Categorical { counts, .. } => {
+                let weights = counts.iter().map(|&count| count as f64).collect::<Vec<_>>();
+                ContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+                    &weights,
+                    RoundingPolicy::LargestRemainder,
+                )?
+            }
+            &ModelSpec::Gaussian { mean, std, .. } => {
+                if !(mean.is_finite() && std > 0.0 && std.is_finite()) {
+                    return Err(());
+                }
+                tabulate(support, Gaussian::new(mean, std))?
+            }
+            &ModelSpec::Laplace {
+                location, scale, ..
+            } => {
+                if !(location.is_finite() && scale > 0.0 && scale.is_finite()) {
+                    return Err(());
+                }
+                tabulate(support, Laplace::new(location, scale))?
+            }
+            &ModelSpec::Geometric { p, .. } => {
+                let max_offset = (*support.end() as i64 - *support.start() as i64) as usize;
+                Geometric::<Probability, PRECISION>::new(p, max_offset)?
+                    .as_categorical()
+                    .expect("`Geometric::new` tabulates the model")
+                    .clone()
+            }
+        };
+
+        Ok(Self { spec, model })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> AutoModel<Probability, PRECISION> {
+    /// Returns the spec from which the model can be reconstructed with
+    /// [`from_spec`](Self::from_spec).
+    pub fn spec(&self) -> &ModelSpec {
+        &self.spec
+    }
+
+    /// Returns the range of symbols that the model can encode.
+    pub fn support(&self) -> RangeInclusive<i32> {
+        self.spec.support()
+    }
+
+    /// Returns the underlying categorical entropy model over the offsets of the symbols
+    /// from `support().start()`.
+    pub fn as_categorical(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.model
+    }
+
+    /// Returns the average number of bits per symbol that encoding `samples` with this
+    /// model would cost (up to the constant overhead of the entropy coder).
+    ///
+    /// Uses the model's fixed point probabilities. Returns infinity if some of the
+    /// `samples` lie outside of the [`support`](Self::support), and NaN if `samples` is
+    /// empty.
+    pub fn cross_entropy(&self, samples: &[i32]) -> f64
+    where
+        Probability: Into<f64>,
+    {
+        let mut total = 0.0;
+        for &symbol in samples {
+            match self.left_cumulative_and_probability(symbol) {
+                Some((_, probability)) => total -= probability.get().into().log2(),
+                None => return f64::INFINITY,
+            }
+        }
+        total / samples.len() as f64 + PRECISION as f64
+    }
+}
+
+fn check_support_size<const PRECISION: usize>(support: &RangeInclusive<i32>) -> Result<(), ()> {
+    let support_size = (*support.end() as i64 - *support.start() as i64 + 1) as u64;
+    if PRECISION < 64 && support_size > 1 << PRECISION {
+        Err(())
+    } else {
+        Ok(())
+    }
+}
+
+/// Quantizes `distribution` to the integers in `support` and stores the resulting fixed
+/// point probabilities in a lookup table.
+fn tabulate<Probability, D, const PRECISION: usize>(
+    support: RangeInclusive<i32>,
+    distribution: D,
+) -> Result<ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>, ()>
+where
+    Probability: BitArray + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    i32: AsPrimitive<Probability>,
+    D: Distribution + Inverse,
+    D::Value: AsPrimitive<i32>,
+{
+    let quantizer = LeakyQuantizer::<f64, i32, Probability, PRECISION>::new(support.clone());
+    let model = quantizer.quantize(distribution);
+    let probabilities = support
+        .map(|symbol| {
+            model
+                .left_cumulative_and_probability(symbol)
+                .expect("symbols within the support have nonzero probability")
+                .1
+                .get()
+        })
+        .collect::<Vec<_>>();
+    ContiguousCategoricalEntropyModel::from_nonzero_fixed_point_probabilities(&probabilities, false)
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for AutoModel<Probability, PRECISION>
+{
+    type Symbol = i32;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for AutoModel<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<i32>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let offset = *symbol.borrow() as i64 - *self.support().start() as i64;
+        if offset < 0 {
+            return None;
+        }
+        self.model.left_cumulative_and_probability(offset as usize)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for AutoModel<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(&self, quantile: Probability) -> (i32, Probability, Probability::NonZero) {
+        let (offset, left_cumulative, probability) = self.model.quantile_function(quantile);
+        (
+            (*self.support().start() as i64 + offset as i64) as i32,
+            left_cumulative,
+            probability,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::model::IterableEntropyModel;
+
+    use rand::{Rng, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    fn sample(distribution: impl Inverse<Value = f64>, amt: usize, seed: u64) -> Vec<i32> {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(seed);
+        (0..amt)
+            .map(|_| {
+                distribution
+                    .inverse(rng.gen_range(1e-6..1.0 - 1e-6))
+                    .round() as i32
+            })
+            .collect()
+    }
+
+    #[test]
+    fn selects_matching_family() {
+        let gaussian = sample(Gaussian::new(10.0, 30.0), 2000, 1);
+        let model = DefaultAutoModel::fit(&gaussian, 1.0).unwrap();
+        assert!(matches!(model.spec(), ModelSpec::Gaussian { .. }));
+
+        let laplace = sample(Laplace::new(-3.0, 4.0), 2000, 2);
+        let model = DefaultAutoModel::fit(&laplace, 1.0).unwrap();
+        assert!(matches!(model.spec(), ModelSpec::Laplace { .. }));
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(3);
+        let geometric = (0..2000)
+            .map(|_| {
+                let mut k = 5;
+                while rng.gen_bool(0.7) {
+                    k += 1;
+                }
+                k
+            })
+            .collect::<Vec<_>>();
+        let model = DefaultAutoModel::fit(&geometric, 1.0).unwrap();
+        assert!(matches!(model.spec(), ModelSpec::Geometric { .. }));
+    }
+
+    #[test]
+    fn budget_excludes_large_specs() {
+        // Bimodal data fits none of the parametric families well.
+        let mut rng = Xoshiro256StarStar::seed_from_u64(4);
+        let bimodal = (0..5000)
+            .map(|_| if rng.gen_bool(0.5) { -100 } else { 100 } + rng.gen_range(-3..=3))
+            .collect::<Vec<_>>();
+        let model = DefaultAutoModel::fit(&bimodal, 1.0).unwrap();
+        assert!(matches!(model.spec(), ModelSpec::Categorical { .. }));
+        let categorical_cost = model.cross_entropy(&bimodal);
+
+        let model = DefaultAutoModel::fit(&bimodal, 0.1).unwrap();
+        assert!(!matches!(model.spec(), ModelSpec::Categorical { .. }));
+        assert!(model.cross_entropy(&bimodal) > categorical_cost);
+
+        // No spec fits into a budget of zero bits.
+        assert!(DefaultAutoModel::fit(&bimodal, 0.0).is_err());
+    }
+
+    #[test]
+    fn spec_roundtrip() {
+        let data = sample(Gaussian::new(0.0, 3.0), 100, 5);
+        for &budget in &[2.0, 4.0, 100.0] {
+            let model = DefaultAutoModel::fit(&data, budget).unwrap();
+            let bytes = model.spec().to_bytes();
+            let spec = ModelSpec::from_bytes(&bytes).unwrap();
+            assert_eq!(&spec, model.spec());
+            let reconstructed = DefaultAutoModel::from_spec(spec).unwrap();
+            assert_eq!(
+                reconstructed
+                    .as_categorical()
+                    .symbol_table()
+                    .collect::<Vec<_>>(),
+                model.as_categorical().symbol_table().collect::<Vec<_>>()
+            );
+
+            for len in 0..bytes.len() {
+                assert!(ModelSpec::from_bytes(&bytes[..len]).is_err());
+            }
+            let mut trailing = bytes.clone();
+            trailing.push(0);
+            assert!(ModelSpec::from_bytes(&trailing).is_err());
+        }
+
+        let spec = ModelSpec::Categorical {
+            min_symbol: -2,
+            counts: alloc::vec![0, 1, 127, 128, 300, u32::MAX],
+        };
+        assert_eq!(ModelSpec::from_bytes(&spec.to_bytes()).unwrap(), spec);
+        assert_eq!(spec.support(), -2..=3);
+    }
+
+    #[test]
+    fn encode_decode() {
+        use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+        let data = sample(Laplace::new(100.0, 7.0), 500, 6);
+        let model = auto_model(&data, 1.0).unwrap();
+        assert!(model.cross_entropy(&data) < 6.0);
+        assert_eq!(
+            model.left_cumulative_and_probability(model.support().start() - 1),
+            None
+        );
+        assert_eq!(
+            model.left_cumulative_and_probability(model.support().end() + 1),
+            None
+        );
+
+        let mut coder = DefaultAnsCoder::new();
+        coder.encode_iid_symbols_reverse(&data, &model).unwrap();
+        let decoded = coder
+            .decode_iid_symbols(data.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, data);
+        assert!(coder.is_empty());
+    }
+
+    #[test]
+    fn edge_cases() {
+        assert!(DefaultAutoModel::fit(&[], 8.0).is_err());
+        assert!(DefaultAutoModel::fit(&[1, 2], f64::NAN).is_err());
+        assert!(DefaultAutoModel::fit(&[1, 2], -1.0).is_err());
+        assert!(SmallAutoModel::fit(&[0, 4095], 100.0).is_ok());
+        assert!(SmallAutoModel::fit(&[0, 4096], 100.0).is_err());
+
+        let model = DefaultAutoModel::fit(&[7; 100], 8.0).unwrap();
+        assert_eq!(model.support(), 7..=8);
+        assert!(model.cross_entropy(&[7; 100]) < 0.1);
+        let model = DefaultAutoModel::fit(&[i32::MAX; 10], 100.0).unwrap();
+        assert_eq!(model.support(), i32::MAX - 1..=i32::MAX);
+
+        assert!(DefaultAutoModel::from_spec(ModelSpec::Gaussian {
+            support: 0..=10,
+            mean: 0.0,
+            std: -1.0,
+        })
+        .is_err());
+        assert!(DefaultAutoModel::from_spec(ModelSpec::Geometric {
+            support: 0..=0,
+            p: 0.5,
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,383 @@
+//! Autoregressive entropy models, where each symbol's model depends on its predecessors
+//!
+//! Many data sources are best modeled autoregressively: the entropy model for each symbol
+//! is a function of the symbols that precede it (e.g., a Markov chain, where it depends
+//! only on the immediately preceding symbol, or a neural network that predicts the next
+//! token from all previous ones). Encoding such data with the batch methods
+//! [`Encode::encode_symbols`] and [`Decode::decode_symbols`] is awkward because the
+//! decoder can only construct the entropy model for a symbol once it has decoded all
+//! preceding symbols, so it can't provide an iterator over all models up front.
+//!
+//! The [`Autoregressive`] combinator in this module wraps a function `make_model` that maps
+//! the sequence of preceding symbols (the "context") to the entropy model for the next
+//! symbol. Its method [`decode_symbols`](Autoregressive::decode_symbols) returns a lazy
+//! iterator over decoded symbols that feeds each decoded symbol back into `make_model`, and
+//! its methods [`encode_symbols`](Autoregressive::encode_symbols) and
+//! [`encode_symbols_reverse`](Autoregressive::encode_symbols_reverse) encode a sequence of
+//! symbols with the same models (for coders with queue or stack semantics, respectively).
+//!
+//! # Example
+//!
+//! A first order Markov chain over the symbols `0..3`, where the initial symbol is modeled
+//! as if it was preceded by the symbol `0`:
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::{autoregressive::Autoregressive, DefaultContiguousCategoricalEntropyModel},
+//!     stack::DefaultAnsCoder,
+//! };
+//!
+//! let transitions = [[0.8, 0.1, 0.1], [0.2, 0.6, 0.2], [0.05, 0.05, 0.9]]
+//!     .iter()
+//!     .map(|probabilities| {
+//!         DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+//!             probabilities,
+//!         )
+//!         .unwrap()
+//!     })
+//!     .collect::<Vec<_>>();
+//! let transitions = &transitions;
+//!
+//! // Only the last symbol matters, so we limit the context to a single symbol.
+//! let markov = Autoregressive::with_max_context(
+//!     move |context: &[usize]| &transitions[context.last().copied().unwrap_or(0)],
+//!     1,
+//! );
+//!
+//! let symbols = [0, 0, 1, 1, 1, 2, 2, 2, 2, 0];
+//! let mut coder = DefaultAnsCoder::new();
+//! markov.encode_symbols_reverse(&mut coder, &symbols).unwrap();
+//! let decoded = markov
+//!     .decode_symbols(&mut coder, symbols.len())
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, symbols);
+//! assert!(coder.is_empty());
+//! ```
+
+use alloc::vec::Vec;
+
+use num::cast::AsPrimitive;
+
+use super::{DecoderModel, EncoderModel, EntropyModel};
+use crate::{
+    stream::{Decode, Encode},
+    CoderError,
+};
+
+/// Drives the encoding and decoding of a sequence of symbols whose entropy models depend
+/// on the preceding symbols.
+///
+/// The wrapped function `make_model` takes the context, i.e., a slice of the (at most
+/// `max_context`) symbols that precede the next symbol, in their original order, and it
+/// returns the entropy model for the next symbol. The first symbol gets an empty context.
+/// The decoder has to use the same function `make_model` and the same `max_context` as
+/// the encoder. See [module level documentation](self) for an example.
+#[derive(Debug, Clone, Copy)]
+pub struct Autoregressive<F> {
+    make_model: F,
+    max_context: usize,
+}
+
+impl<F> Autoregressive<F> {
+    /// Creates an autoregressive model whose context contains all preceding symbols.
+    ///
+    /// When decoding, the context grows with each decoded symbol. If your model only
+    /// depends on a bounded number of preceding symbols then use
+    /// [`with_max_context`](Self::with_max_context) instead, which bounds the memory that
+    /// the decoder has to keep.
+    pub fn new(make_model: F) -> Self {
+        Self::with_max_context(make_model, usize::MAX)
+    }
+
+    /// Creates an autoregressive model whose context contains only the `max_context`
+    /// immediately preceding symbols (or fewer at the beginning of the sequence).
+    ///
+    /// For example, `max_context = 1` results in a first order Markov model, and
+    /// `max_context = 0` results in a model that is called with an empty context for each
+    /// symbol.
+    pub fn with_max_context(make_model: F, max_context: usize) -> Self {
+        Self {
+            make_model,
+            max_context,
+        }
+    }
+
+    /// Returns the maximum number of preceding symbols that `make_model` gets to see.
+    pub fn max_context(&self) -> usize {
+        self.max_context
+    }
+
+    /// Returns a reference to the wrapped function.
+    pub fn make_model(&self) -> &F {
+        &self.make_model
+    }
+
+    /// Consumes the `Autoregressive` and returns the wrapped function.
+    pub fn into_inner(self) -> F {
+        self.make_model
+    }
+
+    fn context<'s, Symbol>(&self, preceding: &'s [Symbol]) -> &'s [Symbol] {
+        &preceding[preceding.len().saturating_sub(self.max_context)..]
+    }
+
+    /// Encodes `symbols` in order, each with the model that `make_model` returns for its
+    /// context.
+    ///
+    /// Use this method with encoders that have queue semantics (e.g., a [`RangeEncoder`]).
+    /// For encoders with stack semantics, use [`encode_symbols_reverse`] instead.
+    ///
+    /// [`RangeEncoder`]: crate::stream::queue::RangeEncoder
+    /// [`encode_symbols_reverse`]: Self::encode_symbols_reverse
+    pub fn encode_symbols<E, M, const PRECISION: usize>(
+        &self,
+        encoder: &mut E,
+        symbols: &[M::Symbol],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        F: Fn(&[M::Symbol]) -> M,
+        E: Encode<PRECISION>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        encoder.encode_symbols(
+            symbols.iter().enumerate().map(|(index, symbol)| {
+                (symbol, (self.make_model)(self.context(&symbols[..index])))
+            }),
+        )
+    }
+
+    /// Encodes `symbols` in reverse order on an encoder with stack semantics (e.g., an
+    /// [`AnsCoder`]) such that [`decode_symbols`] decodes them in their original order.
+    ///
+    /// Each symbol is still encoded with the model for its context of *preceding* symbols,
+    /// so the decoder can reconstruct each model from the symbols it has already decoded.
+    ///
+    /// [`AnsCoder`]: crate::stream::stack::AnsCoder
+    /// [`decode_symbols`]: Self::decode_symbols
+    pub fn encode_symbols_reverse<E, M, const PRECISION: usize>(
+        &self,
+        encoder: &mut E,
+        symbols: &[M::Symbol],
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        F: Fn(&[M::Symbol]) -> M,
+        E: Encode<PRECISION>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        encoder.encode_symbols(
+            symbols.iter().enumerate().rev().map(|(index, symbol)| {
+                (symbol, (self.make_model)(self.context(&symbols[..index])))
+            }),
+        )
+    }
+
+    /// Decodes `amt` symbols, feeding each decoded symbol back into the context of the
+    /// next one.
+    ///
+    /// Like [`Decode::decode_symbols`], this method is lazy: it returns an iterator that
+    /// decodes one symbol for each call to `next`.
+    pub fn decode_symbols<'a, D, M, const PRECISION: usize>(
+        &'a self,
+        decoder: &'a mut D,
+        amt: usize,
+    ) -> DecodeAutoregressive<'a, D, F, M, PRECISION>
+    where
+        F: Fn(&[M::Symbol]) -> M,
+        D: Decode<PRECISION>,
+        M: DecoderModel<PRECISION>,
+        M::Symbol: Clone,
+        M::Probability: Into<D::Word>,
+        D::Word: AsPrimitive<M::Probability>,
+    {
+        DecodeAutoregressive {
+            decoder,
+            model: self,
+            history: Vec::with_capacity(amt.min(self.max_context.saturating_mul(2))),
+            amt,
+        }
+    }
+}
+
+/// The iterator returned by [`Autoregressive::decode_symbols`].
+#[derive(Debug)]
+pub struct DecodeAutoregressive<
+    'a,
+    Decoder: ?Sized,
+    F,
+    M: EntropyModel<PRECISION>,
+    const PRECISION: usize,
+> {
+    decoder: &'a mut Decoder,
+    model: &'a Autoregressive<F>,
+    history: Vec<M::Symbol>,
+    amt: usize,
+}
+
+impl<'a, Decoder, F, M, const PRECISION: usize> Iterator
+    for DecodeAutoregressive<'a, Decoder, F, M, PRECISION>
+where
+    Decoder: Decode<PRECISION>,
+    F: Fn(&[M::Symbol]) -> M,
+    M: DecoderModel<PRECISION>,
+    M::Symbol: Clone,
+    M::Probability: Into<Decoder::Word>,
+    Decoder::Word: AsPrimitive<M::Probability>,
+{
+    type Item = Result<M::Symbol, CoderError<Decoder::FrontendError, Decoder::BackendError>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.amt == 0 {
+            return None;
+        }
+        self.amt -= 1;
+
+        let model = (self.model.make_model)(self.model.context(&self.history));
+        let symbol = match self.decoder.decode_symbol(model) {
+            Ok(symbol) => symbol,
+            Err(err) => return Some(Err(err)),
+        };
+
+        let max_context = self.model.max_context;
+        if max_context != 0 {
+            if self.history.len() >= max_context.saturating_mul(2) {
+                // Keep only the symbols that the next context needs. Shifting the history
+                // only every `max_context` symbols amortizes its cost.
+                self.history.drain(..self.history.len() + 1 - max_context);
+            }
+            self.history.push(symbol.clone());
+        }
+        Some(Ok(symbol))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.amt, Some(self.amt))
+    }
+}
+
+impl<'a, Decoder, F, M, const PRECISION: usize> ExactSizeIterator
+    for DecodeAutoregressive<'a, Decoder, F, M, PRECISION>
+where
+    Decoder: Decode<PRECISION>,
+    F: Fn(&[M::Symbol]) -> M,
+    M: DecoderModel<PRECISION>,
+    M::Symbol: Clone,
+    M::Probability: Into<Decoder::Word>,
+    Decoder::Word: AsPrimitive<M::Probability>,
+{
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::{
+        model::DefaultContiguousCategoricalEntropyModel, queue::DefaultRangeEncoder,
+        stack::DefaultAnsCoder,
+    };
+    use core::cell::Cell;
+
+    /// A model whose prediction depends on the two preceding symbols.
+    fn model_given(context: &[usize]) -> DefaultContiguousCategoricalEntropyModel {
+        let mut probabilities = [1.0; 8];
+        match context {
+            [] => {}
+            [last] => probabilities[(last + 1) % 8] = 20.0,
+            [.., second_last, last] => probabilities[(second_last + last) % 8] = 20.0,
+        }
+        DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&probabilities)
+            .unwrap()
+    }
+
+    fn test_symbols() -> Vec<usize> {
+        let mut symbols = alloc::vec![3, 4];
+        for i in 0..200 {
+            let next = if i % 17 == 0 {
+                i % 8
+            } else {
+                (symbols[symbols.len() - 2] + symbols[symbols.len() - 1]) % 8
+            };
+            symbols.push(next);
+        }
+        symbols
+    }
+
+    #[test]
+    fn roundtrip_range_coder() {
+        let symbols = test_symbols();
+        for &max_context in &[0, 1, 2, 5, usize::MAX] {
+            let max_seen = Cell::new(0);
+            let model = Autoregressive::with_max_context(
+                |context: &[usize]| {
+                    max_seen.set(max_seen.get().max(context.len()));
+                    model_given(context)
+                },
+                max_context,
+            );
+
+            let mut encoder = DefaultRangeEncoder::new();
+            model.encode_symbols(&mut encoder, &symbols).unwrap();
+            let mut decoder = encoder.into_decoder().unwrap();
+            let decoded = model.decode_symbols(&mut decoder, symbols.len());
+            assert_eq!(decoded.len(), symbols.len());
+            let decoded = decoded.collect::<Result<Vec<_>, _>>().unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.maybe_exhausted());
+            assert_eq!(max_seen.get(), max_context.min(symbols.len() - 1));
+        }
+    }
+
+    #[test]
+    fn matches_hand_rolled_loop() {
+        let symbols = test_symbols();
+        let model = Autoregressive::with_max_context(model_given, 2);
+
+        let mut coder = DefaultAnsCoder::new();
+        model.encode_symbols_reverse(&mut coder, &symbols).unwrap();
+
+        let mut expected = DefaultAnsCoder::new();
+        for index in (0..symbols.len()).rev() {
+            let context = &symbols[index.saturating_sub(2)..index];
+            expected
+                .encode_symbol(symbols[index], model_given(context))
+                .unwrap();
+        }
+        assert_eq!(
+            &*coder.get_compressed().unwrap(),
+            &*expected.get_compressed().unwrap()
+        );
+
+        // Exploiting the context pays off.
+        let mut iid = DefaultAnsCoder::new();
+        iid.encode_iid_symbols_reverse(&symbols, &model_given(&[]))
+            .unwrap();
+        assert!(coder.num_bits() < iid.num_bits() / 2);
+
+        let decoded = model
+            .decode_symbols(&mut coder, symbols.len())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(coder.is_empty());
+    }
+
+    #[test]
+    fn full_history() {
+        // The model for each symbol depends on the sum of all preceding symbols.
+        let model =
+            Autoregressive::new(|context: &[usize]| model_given(&[context.iter().sum::<usize>()]));
+        let symbols = test_symbols();
+
+        let mut coder = DefaultAnsCoder::new();
+        model.encode_symbols_reverse(&mut coder, &symbols).unwrap();
+        let decoded = model
+            .decode_symbols(&mut coder, symbols.len())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+    }
+}
@@ -0,0 +1,307 @@
+//! A dedicated entropy model for binary symbols
+//!
+//! Binary decisions (flags, significance bits, bit planes, ...) are the most common kind of
+//! symbol in many codecs. You could encode them with a [`ContiguousCategoricalEntropyModel`]
+//! over the alphabet `{0, 1}`, but a categorical model stores its probabilities in a heap
+//! allocated table and searches this table when decoding. The [`Bernoulli`] model in this
+//! module instead stores just the two fixed point probabilities inline, so that both
+//! encoding and decoding a bit take a single comparison, and it is `Copy`. Its symbols are
+//! `bool`s, and you can encode and decode sequences of them with the convenience methods
+//! [`Encode::encode_bits`] and [`Decode::decode_bits`].
+//!
+//! A `Bernoulli` model with probability `p` rounds to the same fixed point probabilities as
+//! a categorical model created with
+//! [`from_floating_point_probabilities(&[1.0 - p, p])`](super::ContiguousCategoricalEntropyModel::from_floating_point_probabilities),
+//! so the two models produce identical compressed data (with `false` in the role of `0`
+//! and `true` in the role of `1`).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::bernoulli::DefaultBernoulli, stack::DefaultAnsCoder, Decode, Encode,
+//! };
+//!
+//! let bits = [false, false, true, false, false, false, true, false];
+//! let model = DefaultBernoulli::new(0.2).unwrap();
+//!
+//! let mut coder = DefaultAnsCoder::new();
+//! coder.encode_bits(bits.iter().rev(), model).unwrap(); // (Reverse since ANS is a stack.)
+//!
+//! let decoded = coder
+//!     .decode_bits(bits.len(), model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, bits);
+//! assert!(coder.is_empty());
+//! ```
+//!
+//! [`Encode::encode_bits`]: crate::stream::Encode::encode_bits
+//! [`Decode::decode_bits`]: crate::stream::Decode::decode_bits
+//! [`ContiguousCategoricalEntropyModel`]: super::ContiguousCategoricalEntropyModel
+
+use core::{
+    borrow::Borrow,
+    iter::{Chain, Once},
+};
+
+use num::cast::AsPrimitive;
+
+use super::{
+    quantize_leaky_categorical, DecoderModel, EncoderModel, EntropyModel, IterableEntropyModel,
+    RoundingPolicy,
+};
+use crate::{BitArray, NonZeroBitArray};
+
+/// An entropy model over the symbols `false` and `true`.
+///
+/// The symbol `false` occupies the lower part of the fixed point probability range and the
+/// symbol `true` occupies the upper part. Both symbols always have a nonzero probability.
+///
+/// See [module level documentation](self) for an example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bernoulli<Probability: BitArray, const PRECISION: usize> {
+    probability_of_false: Probability::NonZero,
+    probability_of_true: Probability::NonZero,
+}
+
+/// Type alias for a [`Bernoulli`] model with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultBernoulli = Bernoulli<u32, 24>;
+
+/// Type alias for a [`Bernoulli`] model that can be used with coders that use `u16` words.
+pub type SmallBernoulli = Bernoulli<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> Bernoulli<Probability, PRECISION> {
+    /// Creates a model that assigns (approximately) probability `p` to the symbol `true`.
+    ///
+    /// The fixed point probabilities are the same as the ones of a
+    /// [`ContiguousCategoricalEntropyModel`](super::ContiguousCategoricalEntropyModel)
+    /// created with
+    /// [`from_floating_point_probabilities(&[1.0 - p, p])`](super::ContiguousCategoricalEntropyModel::from_floating_point_probabilities).
+    /// In particular, both symbols get a nonzero probability even if `p` is `0.0` or `1.0`.
+    ///
+    /// Returns an error if `p` is not within the closed interval `[0, 1]`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(p: f64) -> Result<Self, ()>
+    where
+        Probability: Into<f64> + AsPrimitive<usize>,
+        f64: AsPrimitive<Probability>,
+        usize: AsPrimitive<Probability>,
+    {
+        if !(0.0..=1.0).contains(&p) {
+            return Err(());
+        }
+        let weights = quantize_leaky_categorical::<Probability, _, PRECISION>(
+            &[1.0 - p, p],
+            RoundingPolicy::MinimalCrossEntropy,
+        )?;
+        Self::from_fixed_point_probability_of_true(weights[1])
+    }
+
+    /// Creates a model from the fixed point probability of the symbol `true`.
+    ///
+    /// The symbol `false` gets the remaining probability `(1 << PRECISION) -
+    /// probability_of_true`. Returns an error unless `0 < probability_of_true < 1 <<
+    /// PRECISION`, i.e., if either symbol would get zero probability.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_fixed_point_probability_of_true(
+        probability_of_true: Probability,
+    ) -> Result<Self, ()> {
+        assert!(PRECISION > 0 && PRECISION <= Probability::BITS);
+        let max_probability = Probability::max_value() >> (Probability::BITS - PRECISION);
+        if probability_of_true > max_probability {
+            return Err(());
+        }
+        // Wraps around to `1 << PRECISION` (i.e., to zero if `PRECISION == BITS`) minus
+        // `probability_of_true`.
+        let probability_of_false = max_probability
+            .wrapping_sub(&probability_of_true)
+            .wrapping_add(&Probability::one());
+
+        Ok(Self {
+            probability_of_false: Probability::NonZero::new(probability_of_false).ok_or(())?,
+            probability_of_true: Probability::NonZero::new(probability_of_true).ok_or(())?,
+        })
+    }
+
+    /// Returns the fixed point probability of the symbol `false`.
+    #[inline(always)]
+    pub fn probability_of_false(&self) -> Probability::NonZero {
+        self.probability_of_false
+    }
+
+    /// Returns the fixed point probability of the symbol `true`.
+    #[inline(always)]
+    pub fn probability_of_true(&self) -> Probability::NonZero {
+        self.probability_of_true
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Bernoulli<Probability, PRECISION>
+{
+    type Symbol = bool;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Bernoulli<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<bool>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        if *symbol.borrow() {
+            Some((self.probability_of_false.get(), self.probability_of_true))
+        } else {
+            Some((Probability::zero(), self.probability_of_false))
+        }
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Bernoulli<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (bool, Probability, Probability::NonZero) {
+        if quantile < self.probability_of_false.get() {
+            (false, Probability::zero(), self.probability_of_false)
+        } else {
+            (
+                true,
+                self.probability_of_false.get(),
+                self.probability_of_true,
+            )
+        }
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for Bernoulli<Probability, PRECISION>
+{
+    type Iter = Chain<
+        Once<(bool, Probability, Probability::NonZero)>,
+        Once<(bool, Probability, Probability::NonZero)>,
+    >;
+
+    #[inline(always)]
+    fn symbol_table(&'m self) -> Self::Iter {
+        core::iter::once((false, Probability::zero(), self.probability_of_false)).chain(
+            core::iter::once((
+                true,
+                self.probability_of_false.get(),
+                self.probability_of_true,
+            )),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::super::{stack::DefaultAnsCoder, Decode, Encode},
+        super::DefaultContiguousCategoricalEntropyModel,
+        *,
+    };
+
+    use alloc::vec::Vec;
+
+    fn assert_consistent<Probability, const PRECISION: usize>(
+        model: &Bernoulli<Probability, PRECISION>,
+    ) where
+        Probability: BitArray + Into<u64>,
+    {
+        let mut expected_left = 0u64;
+        for (bit, left, probability) in model.symbol_table() {
+            assert_eq!(left.into(), expected_left);
+            assert_eq!(
+                model.left_cumulative_and_probability(bit),
+                Some((left, probability))
+            );
+            let last = left + (probability.get() - Probability::one());
+            assert_eq!(model.quantile_function(left), (bit, left, probability));
+            assert_eq!(model.quantile_function(last), (bit, left, probability));
+            expected_left += probability.get().into();
+        }
+        assert_eq!(expected_left, 1 << PRECISION);
+    }
+
+    #[test]
+    fn matches_categorical() {
+        for &p in &[0.0, 1e-9, 0.1, 0.3, 0.5, 0.7, 0.999, 1.0] {
+            let bernoulli = DefaultBernoulli::new(p).unwrap();
+            let categorical =
+                DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+                    1.0 - p,
+                    p,
+                ])
+                .unwrap();
+            for &bit in &[false, true] {
+                assert_eq!(
+                    bernoulli.left_cumulative_and_probability(bit),
+                    categorical.left_cumulative_and_probability(bit as usize)
+                );
+            }
+            assert_consistent(&bernoulli);
+        }
+    }
+
+    #[test]
+    fn fixed_point() {
+        let model = Bernoulli::<u32, 32>::from_fixed_point_probability_of_true(3).unwrap();
+        assert_eq!(model.probability_of_false().get(), u32::MAX - 2);
+        assert_consistent(&model);
+        assert!(Bernoulli::<u32, 32>::from_fixed_point_probability_of_true(0).is_err());
+
+        let model = SmallBernoulli::from_fixed_point_probability_of_true(4095).unwrap();
+        assert_eq!(model.probability_of_false().get(), 1);
+        assert!(SmallBernoulli::from_fixed_point_probability_of_true(4096).is_err());
+
+        assert!(DefaultBernoulli::new(-0.1).is_err());
+        assert!(DefaultBernoulli::new(1.1).is_err());
+        assert!(DefaultBernoulli::new(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn encode_and_decode_bits() {
+        let bits = (0..1000).map(|i| i % 7 == 0).collect::<Vec<_>>();
+        let model = DefaultBernoulli::new(1.0 / 7.0).unwrap();
+        let categorical =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+                6.0 / 7.0,
+                1.0 / 7.0,
+            ])
+            .unwrap();
+
+        let mut coder = DefaultAnsCoder::new();
+        coder.encode_bits(bits.iter().rev(), model).unwrap();
+
+        let mut reference = DefaultAnsCoder::new();
+        reference
+            .encode_iid_symbols_reverse(bits.iter().map(|&bit| bit as usize), &categorical)
+            .unwrap();
+        assert_eq!(
+            *coder.get_compressed().unwrap(),
+            *reference.get_compressed().unwrap()
+        );
+
+        let decoded = coder
+            .decode_bits(bits.len(), model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, bits);
+        assert!(coder.is_empty());
+    }
+}
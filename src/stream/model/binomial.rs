@@ -0,0 +1,294 @@
+//! A binomial entropy model whose fixed point probabilities are reproducible bit for bit
+//!
+//! You can already obtain an entropy model for a binomial distribution by quantizing
+//! [`probability::distribution::Binomial`] with a [`LeakyQuantizer`](super::LeakyQuantizer).
+//! However, the resulting fixed point probabilities depend on the floating point
+//! implementations of special functions like `ln` and `exp`, which may differ in their
+//! last bits across platforms and library versions. This is fine for most applications
+//! since the encoder and the decoder typically run on the same machine, but it is a
+//! problem for bits-back coding of count data, where a sender decodes latent counts from
+//! one machine's bit string and the receiver has to re-encode them with bit-identical
+//! models.
+//!
+//! The [`Binomial`] model in this module evaluates the probability mass function with a
+//! recurrence that uses only multiplications and divisions, and it rounds the result to
+//! fixed point with [`RoundingPolicy::LargestRemainder`], which uses only basic arithmetic
+//! too. Since IEEE 754 specifies the results of these operations exactly, the fixed point
+//! probabilities are a deterministic function of `n` and `p`.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{model::binomial::DefaultBinomial, stack::DefaultAnsCoder, Decode};
+//!
+//! let model = DefaultBinomial::new(20, 0.3).unwrap();
+//! let counts = [6, 4, 7, 0, 20, 5];
+//!
+//! let mut coder = DefaultAnsCoder::new();
+//! coder.encode_iid_symbols_reverse(&counts, &model).unwrap();
+//! let decoded = coder
+//!     .decode_iid_symbols(counts.len(), &model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, counts);
+//! ```
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    ContiguousCategoricalEntropyModel, ContiguousSymbolTable, DecoderModel, EncoderModel,
+    EntropyModel, IterableEntropyModel, RoundingPolicy, SymbolTableIter,
+};
+use crate::BitArray;
+
+/// An entropy model for the number of successes in `n` independent trials that each
+/// succeed with probability `p`.
+///
+/// The support is `0..=n`. Like all entropy models in `constriction`, a `Binomial` is
+/// leaky, i.e., it assigns a nonzero probability to every symbol in its support, even for
+/// `p = 0.0` or `p = 1.0`. See [module level documentation](self) for how it differs from
+/// quantizing a [`probability::distribution::Binomial`].
+#[derive(Debug, Clone)]
+pub struct Binomial<Probability: BitArray, const PRECISION: usize> {
+    n: usize,
+    p: f64,
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+/// Type alias for a [`Binomial`] with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultBinomial = Binomial<u32, 24>;
+
+/// Type alias for a [`Binomial`] that can be used with coders that use `u16` words.
+///
+/// Note that the support `0..=n` has to fit into the `2^12` available fixed point
+/// probability units, i.e., `n` must be smaller than `4096`.
+pub type SmallBinomial = Binomial<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> Binomial<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Creates a binomial entropy model with `n` trials and success probability `p`.
+    ///
+    /// Returns an error if `n` is zero (a model with a single symbol can't be represented),
+    /// if `n + 1` exceeds `1 << PRECISION` (since every symbol needs a nonzero
+    /// probability), or if `p` is not within the interval `[0, 1]`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(n: usize, p: f64) -> Result<Self, ()> {
+        if n == 0 || !(0.0..=1.0).contains(&p) {
+            return Err(());
+        }
+        if PRECISION < usize::BITS as usize && n >= 1 << PRECISION {
+            // Check before allocating the table for the probability mass function.
+            return Err(());
+        }
+
+        let model =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+                &unnormalized_pmf(n, p),
+                RoundingPolicy::LargestRemainder,
+            )?;
+        Ok(Self { n, p, model })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Binomial<Probability, PRECISION> {
+    /// Returns the number of trials, i.e., the largest symbol in the support.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the success probability of each trial.
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+
+    /// Returns the underlying categorical entropy model over the symbols `0..=n`.
+    pub fn as_categorical(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.model
+    }
+}
+
+/// Evaluates the probability mass function of a binomial distribution up to normalization.
+///
+/// Starts from weight `1` at the mode and walks outwards with the ratios of successive
+/// probabilities, so that no intermediate value overflows and only multiplications and
+/// divisions are involved. Weights far out in the tails may underflow to zero, which is
+/// fine since the leaky quantization assigns them the smallest representable probability
+/// anyway.
+fn unnormalized_pmf(n: usize, p: f64) -> Vec<f64> {
+    let q = 1.0 - p;
+    let mode = (((n + 1) as f64 * p) as usize).min(n);
+
+    let mut weights = Vec::with_capacity(n + 1);
+    weights.resize(n + 1, 0.0);
+    weights[mode] = 1.0;
+
+    // We only evaluate `p / q` if `mode < n`, which implies `q > 0` (and analogously below).
+    for k in mode + 1..=n {
+        weights[k] = weights[k - 1] * ((n - k + 1) as f64 / k as f64) * (p / q);
+    }
+    for k in (0..mode).rev() {
+        weights[k] = weights[k + 1] * ((k + 1) as f64 / (n - k) as f64) * (q / p);
+    }
+
+    weights
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Binomial<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Binomial<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        self.model.left_cumulative_and_probability(symbol)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Binomial<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (usize, Probability, Probability::NonZero) {
+        self.model.quantile_function(quantile)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for Binomial<Probability, PRECISION>
+{
+    type Iter = SymbolTableIter<usize, Probability, ContiguousSymbolTable<&'m [Probability]>>;
+
+    #[inline(always)]
+    fn symbol_table(&'m self) -> Self::Iter {
+        self.model.symbol_table()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stream::{chain::DefaultChainCoder, Decode, Encode};
+    use alloc::vec;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn matches_pmf() {
+        use probability::distribution::{Binomial as FloatBinomial, Discrete};
+
+        for &n in &[1, 2, 10, 100, 1000, 50_000] {
+            for &p in &[0.0, 1e-30, 1e-3, 0.1, 0.4, 0.5, 0.9, 1.0] {
+                let model = DefaultBinomial::new(n, p).unwrap();
+                assert_eq!(model.n(), n);
+
+                let mut total = 0u64;
+                let mut last_symbol = None;
+                for (symbol, left_cumulative, probability) in model.symbol_table() {
+                    assert_eq!(left_cumulative as u64, total);
+                    total += probability.get() as u64;
+                    last_symbol = Some(symbol);
+                }
+                assert_eq!(total, 1 << 24);
+                assert_eq!(last_symbol, Some(n));
+                assert_eq!(model.left_cumulative_and_probability(n + 1), None);
+
+                if p == 0.0 || p == 1.0 {
+                    // Not supported by the reference (see `extreme_success_probabilities`).
+                    continue;
+                }
+
+                // Each symbol gets one unit up front and at most one unit from rounding.
+                let reference = FloatBinomial::new(n, p);
+                let units = (1u64 << 24) as f64;
+                for k in (0..=n).step_by(n / 100 + 1) {
+                    let exact = reference.mass(k);
+                    let approx: f64 = model.floating_point_probability(k);
+                    assert!((approx - exact).abs() <= (2.0 + exact * n as f64) / units + 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn extreme_success_probabilities() {
+        let zero = DefaultBinomial::new(10, 0.0).unwrap();
+        let one = DefaultBinomial::new(10, 1.0).unwrap();
+        let (_, probability) = zero.left_cumulative_and_probability(0).unwrap();
+        assert_eq!(probability.get(), (1 << 24) - 10);
+        let (_, probability) = one.left_cumulative_and_probability(10).unwrap();
+        assert_eq!(probability.get(), (1 << 24) - 10);
+        for k in 1..=10 {
+            let (_, probability) = zero.left_cumulative_and_probability(k).unwrap();
+            assert_eq!(probability.get(), 1);
+            let (_, probability) = one.left_cumulative_and_probability(k - 1).unwrap();
+            assert_eq!(probability.get(), 1);
+        }
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DefaultBinomial::new(0, 0.5).is_err());
+        assert!(DefaultBinomial::new(10, -0.1).is_err());
+        assert!(DefaultBinomial::new(10, 1.1).is_err());
+        assert!(DefaultBinomial::new(10, f64::NAN).is_err());
+        assert!(SmallBinomial::new(4095, 0.5).is_ok());
+        assert!(SmallBinomial::new(4096, 0.5).is_err());
+        assert!(DefaultBinomial::new(usize::MAX, 0.5).is_err());
+    }
+
+    #[test]
+    fn bits_back_round_trip() {
+        let data = vec![0x0123_4567u32, 0x89ab_cdef, 0x1357_9bdf, 0x2468_ace0];
+        let params = [(30, 0.2), (5, 0.9), (1000, 0.5), (7, 0.0)];
+        let models = params
+            .iter()
+            .map(|&(n, p)| DefaultBinomial::new(n, p).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut coder = DefaultChainCoder::from_binary(data.clone()).unwrap();
+        let counts = models
+            .iter()
+            .map(|model| coder.decode_symbol(model).unwrap())
+            .collect::<Vec<_>>();
+        for (&count, &(n, _)) in counts.iter().zip(&params) {
+            assert!(count <= n);
+        }
+
+        // Rebuilding the models from their parameters yields bit-identical models.
+        for (&count, &(n, p)) in counts.iter().zip(&params).rev() {
+            coder
+                .encode_symbol(count, DefaultBinomial::new(n, p).unwrap())
+                .unwrap();
+        }
+        let (prefix, suffix) = coder.into_binary().unwrap();
+        assert!(prefix.is_empty());
+        assert_eq!(suffix, data);
+    }
+}
@@ -0,0 +1,161 @@
+//! The Cauchy distribution, for heavy-tailed data
+//!
+//! The crate [`probability`] doesn't provide a Cauchy distribution, so this module provides
+//! one that you can quantize with a [`LeakyQuantizer`](super::LeakyQuantizer) like any
+//! other continuous distribution. Its tails decay only quadratically, so a quantized Cauchy
+//! distribution assigns a reasonable bit rate to outliers (e.g., in prediction residuals)
+//! that a Gaussian or Laplace model with the same core would consider practically
+//! impossible, and for which these models would therefore fall back to the minimal
+//! probability that a `LeakyQuantizer` assigns to every symbol in its support.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::{cauchy::Cauchy, DefaultLeakyQuantizer},
+//!     stack::DefaultAnsCoder,
+//!     Decode,
+//! };
+//!
+//! let quantizer = DefaultLeakyQuantizer::new(-10_000..=10_000);
+//! let model = quantizer.quantize(Cauchy::new(0.0, 2.5));
+//! let residuals = [1, -3, 0, 2, 7500, -4, 0, -9000];
+//!
+//! let mut coder = DefaultAnsCoder::new();
+//! coder.encode_iid_symbols_reverse(&residuals, model).unwrap();
+//! let decoded = coder
+//!     .decode_iid_symbols(residuals.len(), model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, residuals);
+//! ```
+
+use core::f64::consts::PI;
+
+use probability::distribution::{Distribution, Inverse};
+
+/// A Cauchy distribution with location `mean` and scale `scale`.
+///
+/// Strictly speaking, a Cauchy distribution doesn't have a mean (since its expectation
+/// diverges); the parameter `mean` is the location of its mode and median. We still call
+/// it `mean` for consistency with the other quantized models in `constriction`. See
+/// [module level documentation](self) for an example.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cauchy {
+    mean: f64,
+    scale: f64,
+}
+
+impl Cauchy {
+    /// Creates a Cauchy distribution with location `mean` and scale `scale`.
+    ///
+    /// It should hold that `mean` is finite and that `scale > 0` is finite. Use
+    /// [`try_new`](Self::try_new) for parameters that come from an untrusted source.
+    pub fn new(mean: f64, scale: f64) -> Self {
+        debug_assert!(scale > 0.0);
+        Self { mean, scale }
+    }
+
+    /// Same as [`new`](Self::new) but returns an error if `mean` is not finite or if
+    /// `scale` is not positive and finite (which includes NaN).
+    #[allow(clippy::result_unit_err)]
+    pub fn try_new(mean: f64, scale: f64) -> Result<Self, ()> {
+        if mean.is_finite() && scale > 0.0 && scale.is_finite() {
+            Ok(Self { mean, scale })
+        } else {
+            Err(())
+        }
+    }
+
+    /// Returns the location parameter (i.e., the median).
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// Returns the scale parameter (i.e., half the interquartile range).
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+}
+
+impl Distribution for Cauchy {
+    type Value = f64;
+
+    /// Evaluates the cumulative distribution function `1/2 + atan((x - mean) / scale) / pi`.
+    ///
+    /// In the left tail, we use the equivalent expression `atan(-scale / (x - mean)) / pi`,
+    /// which doesn't suffer from cancellation, so that even very improbable symbols get
+    /// accurate probabilities.
+    fn distribution(&self, x: f64) -> f64 {
+        let z = (x - self.mean) / self.scale;
+        if z < 0.0 {
+            (-1.0 / z).atan() / PI
+        } else {
+            0.5 + z.atan() / PI
+        }
+    }
+}
+
+impl Inverse for Cauchy {
+    fn inverse(&self, p: f64) -> f64 {
+        if p < 0.5 {
+            self.mean - self.scale / (PI * p).tan()
+        } else {
+            self.mean + self.scale * (PI * (p - 0.5)).tan()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{DefaultLeakyQuantizer, EncoderModel};
+    use super::*;
+
+    use probability::distribution::Laplace;
+
+    #[test]
+    fn distribution() {
+        let cauchy = Cauchy::new(1.5, 2.0);
+        assert_eq!(cauchy.distribution(1.5), 0.5);
+        assert!((cauchy.distribution(3.5) - 0.75).abs() < 1e-15);
+        assert!((cauchy.distribution(-0.5) - 0.25).abs() < 1e-15);
+        assert_eq!(cauchy.distribution(f64::NEG_INFINITY), 0.0);
+        assert_eq!(cauchy.distribution(f64::INFINITY), 1.0);
+
+        // The left tail is accurate even far away from the mode.
+        let x = 1.5 - 2.0e12;
+        let expected = 1.0 / (PI * 1.0e12);
+        assert!((cauchy.distribution(x) / expected - 1.0).abs() < 1e-10);
+
+        for &p in &[1e-12, 0.01, 0.25, 0.5, 0.6, 0.99] {
+            let x = cauchy.inverse(p);
+            assert!((cauchy.distribution(x) / p - 1.0).abs() < 1e-9);
+        }
+        assert_eq!(cauchy.inverse(0.0), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn invalid_parameters() {
+        assert_eq!(Cauchy::try_new(1.5, 2.0), Ok(Cauchy::new(1.5, 2.0)));
+        assert!(Cauchy::try_new(0.0, 0.0).is_err());
+        assert!(Cauchy::try_new(0.0, -1.0).is_err());
+        assert!(Cauchy::try_new(0.0, f64::NAN).is_err());
+        assert!(Cauchy::try_new(0.0, f64::INFINITY).is_err());
+        assert!(Cauchy::try_new(f64::NAN, 1.0).is_err());
+        assert!(Cauchy::try_new(f64::NEG_INFINITY, 1.0).is_err());
+    }
+
+    #[test]
+    fn outliers_are_cheap() {
+        let quantizer = DefaultLeakyQuantizer::new(-100_000..=100_000);
+        let cauchy = quantizer.quantize(Cauchy::new(0.0, 3.0));
+        let laplace = quantizer.quantize(Laplace::new(0.0, 3.0));
+
+        // Under the Laplace model, the outlier only gets the minimal probability that the
+        // `LeakyQuantizer` assigns to every symbol (i.e., 24 bits).
+        let cauchy_bits = -cauchy.floating_point_probability::<f64>(500).log2();
+        let laplace_bits = -laplace.floating_point_probability::<f64>(500).log2();
+        assert!(cauchy_bits < 19.0);
+        assert!(laplace_bits > 23.0);
+    }
+}
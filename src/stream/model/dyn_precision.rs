@@ -0,0 +1,350 @@
+//! An entropy model that can present itself at any fixed point precision at runtime
+//!
+//! The fixed point precision of an entropy model (the const generic `PRECISION`) trades
+//! off bit rate against memory and speed: lower precisions lead to smaller lookup tables
+//! and allow for smaller compressed word types, but they round the probabilities more
+//! coarsely. Comparing several precisions in an experiment usually means rebuilding every
+//! model from floating point probabilities for each precision. A [`DynPrecisionModel`]
+//! instead quantizes its probabilities only once, at 32 bit precision, and then re-rounds
+//! them on the fly to whichever lower precision you request with
+//! [`at_precision`](DynPrecisionModel::at_precision). Re-rounding is deterministic and uses
+//! only integer arithmetic, so an encoder and a decoder that request the same precision
+//! always see bit-identical models.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::dyn_precision::DynPrecisionModel, queue::DefaultRangeEncoder, Decode, Encode,
+//! };
+//!
+//! let model = DynPrecisionModel::from_floating_point_probabilities(&[0.1, 0.6, 0.3]).unwrap();
+//! let symbols = [0, 2, 1, 2, 0, 2, 0, 2, 1];
+//!
+//! // Encode and decode with 24 and with 8 bits of precision.
+//! let mut encoder24 = DefaultRangeEncoder::new();
+//! encoder24
+//!     .encode_iid_symbols(&symbols, model.at_precision::<u32, 24>().unwrap())
+//!     .unwrap();
+//! let mut encoder8 = DefaultRangeEncoder::new();
+//! encoder8
+//!     .encode_iid_symbols(&symbols, model.at_precision::<u32, 8>().unwrap())
+//!     .unwrap();
+//!
+//! let mut decoder8 = encoder8.into_decoder().unwrap();
+//! let decoded = decoder8
+//!     .decode_iid_symbols(symbols.len(), model.at_precision::<u32, 8>().unwrap())
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, symbols);
+//! ```
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, iter, marker::PhantomData, num::NonZeroU64};
+
+use num::{cast::AsPrimitive, Float};
+
+use super::{
+    ContiguousCategoricalEntropyModel, DecoderModel, EncoderModel, EntropyModel,
+    IterableEntropyModel, RoundingPolicy,
+};
+use crate::BitArray;
+
+/// A categorical entropy model over the symbols `0..num_symbols` that stores its
+/// probabilities at 32 bit precision and can present itself at any lower precision.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynPrecisionModel {
+    /// Left sided cumulatives at 32 bit precision, followed by `1 << 32`; strictly
+    /// increasing.
+    cumulatives: Vec<u64>,
+}
+
+impl DynPrecisionModel {
+    /// The precision at which the model stores its probabilities internally.
+    pub const MAX_PRECISION: usize = 32;
+
+    /// Quantizes the provided probabilities to 32 bit fixed point precision.
+    ///
+    /// The `probabilities` don't need to be normalized. Returns an error if there are
+    /// fewer than two probabilities, or if they can't be normalized (see
+    /// [`ContiguousCategoricalEntropyModel::from_floating_point_probabilities`]).
+    #[allow(clippy::result_unit_err)]
+    pub fn from_floating_point_probabilities<F>(probabilities: &[F]) -> Result<Self, ()>
+    where
+        F: Float + core::iter::Sum<F> + Into<f64>,
+    {
+        if probabilities.len() < 2 {
+            return Err(());
+        }
+
+        type Model32 = ContiguousCategoricalEntropyModel<u32, Vec<u32>, 32>;
+        let model = Model32::from_floating_point_probabilities_with_rounding(
+            probabilities,
+            RoundingPolicy::LargestRemainder,
+        )?;
+        let cumulatives = model
+            .symbol_table()
+            .map(|(_, left_sided_cumulative, _)| left_sided_cumulative as u64)
+            .chain(iter::once(1 << Self::MAX_PRECISION))
+            .collect();
+
+        Ok(Self { cumulatives })
+    }
+
+    pub fn num_symbols(&self) -> usize {
+        self.cumulatives.len() - 1
+    }
+
+    /// Returns the smallest precision at which the model can present itself, i.e., the
+    /// smallest `precision` with `2^precision >= self.num_symbols()`.
+    pub fn min_precision(&self) -> usize {
+        (usize::BITS - (self.num_symbols() - 1).leading_zeros()) as usize
+    }
+
+    /// Presents the model at fixed point precision `PRECISION`, using `Probability` to
+    /// represent probabilities.
+    ///
+    /// This is cheap since it doesn't allocate; the probabilities get re-rounded lazily
+    /// whenever the returned model is evaluated. Returns an error if `PRECISION` is larger
+    /// than [`MAX_PRECISION`](Self::MAX_PRECISION) or than `Probability::BITS`, or if it is
+    /// smaller than [`min_precision`](Self::min_precision).
+    #[allow(clippy::result_unit_err)]
+    pub fn at_precision<Probability: BitArray, const PRECISION: usize>(
+        &self,
+    ) -> Result<AtPrecision<'_, Probability, PRECISION>, ()> {
+        if PRECISION > Self::MAX_PRECISION
+            || PRECISION > Probability::BITS
+            || PRECISION < self.min_precision()
+        {
+            return Err(());
+        }
+
+        Ok(AtPrecision {
+            model: self,
+            phantom: PhantomData,
+        })
+    }
+
+    /// Returns the left sided cumulative and the probability of `symbol` after re-rounding
+    /// to `precision`, or `None` if `symbol >= self.num_symbols()`.
+    ///
+    /// This is the runtime counterpart of calling
+    /// [`left_cumulative_and_probability`](EncoderModel::left_cumulative_and_probability)
+    /// on the result of [`at_precision`](Self::at_precision).
+    ///
+    /// # Panics
+    ///
+    /// If `precision` is larger than [`MAX_PRECISION`](Self::MAX_PRECISION) or smaller than
+    /// [`min_precision`](Self::min_precision).
+    pub fn left_cumulative_and_probability_at(
+        &self,
+        symbol: usize,
+        precision: usize,
+    ) -> Option<(u64, NonZeroU64)> {
+        let free_weight = self.free_weight(precision);
+        if symbol >= self.num_symbols() {
+            return None;
+        }
+
+        let left_sided_cumulative = self.rounded_cumulative(symbol, free_weight);
+        let right_sided_cumulative = self.rounded_cumulative(symbol + 1, free_weight);
+        let probability = NonZeroU64::new(right_sided_cumulative - left_sided_cumulative)
+            .expect("The slack guarantees nonzero probabilities.");
+        Some((left_sided_cumulative, probability))
+    }
+
+    /// Returns the symbol, its left sided cumulative, and its probability for the given
+    /// `quantile` after re-rounding to `precision`.
+    ///
+    /// This is the runtime counterpart of calling
+    /// [`quantile_function`](DecoderModel::quantile_function) on the result of
+    /// [`at_precision`](Self::at_precision).
+    ///
+    /// # Panics
+    ///
+    /// If `precision` is larger than [`MAX_PRECISION`](Self::MAX_PRECISION) or smaller than
+    /// [`min_precision`](Self::min_precision), or if `quantile >= 2^precision`.
+    pub fn quantile_function_at(
+        &self,
+        quantile: u64,
+        precision: usize,
+    ) -> (usize, u64, NonZeroU64) {
+        let free_weight = self.free_weight(precision);
+        assert!(quantile < 1 << precision);
+
+        // Binary search for the largest symbol whose left sided cumulative is `<= quantile`.
+        let mut lower = 0;
+        let mut upper = self.num_symbols();
+        while upper - lower > 1 {
+            let middle = lower + (upper - lower) / 2;
+            if self.rounded_cumulative(middle, free_weight) <= quantile {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+        }
+
+        let (left_sided_cumulative, probability) = self
+            .left_cumulative_and_probability_at(lower, precision)
+            .expect("`lower < self.num_symbols()`");
+        (lower, left_sided_cumulative, probability)
+    }
+
+    /// Returns `2^precision - num_symbols`, i.e., the total probability mass that gets
+    /// distributed proportionally to the stored probabilities.
+    #[inline(always)]
+    fn free_weight(&self, precision: usize) -> u64 {
+        assert!(precision <= Self::MAX_PRECISION);
+        (1u64 << precision)
+            .checked_sub(self.num_symbols() as u64)
+            .expect("Precision too low for the number of symbols.")
+    }
+
+    /// Rounds down the stored cumulative at `index` to the new precision and adds one unit
+    /// of slack per preceding symbol, which guarantees nonzero probabilities.
+    #[inline(always)]
+    fn rounded_cumulative(&self, index: usize, free_weight: u64) -> u64 {
+        // Doesn't overflow since `self.cumulatives[index] <= 2^32` and `free_weight < 2^32`.
+        ((self.cumulatives[index] * free_weight) >> Self::MAX_PRECISION) + index as u64
+    }
+}
+
+/// A [`DynPrecisionModel`] presented at fixed point precision `PRECISION`.
+///
+/// Obtained via [`DynPrecisionModel::at_precision`].
+#[derive(Debug)]
+pub struct AtPrecision<'m, Probability, const PRECISION: usize> {
+    model: &'m DynPrecisionModel,
+    phantom: PhantomData<Probability>,
+}
+
+impl<'m, Probability, const PRECISION: usize> Clone for AtPrecision<'m, Probability, PRECISION> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'m, Probability, const PRECISION: usize> Copy for AtPrecision<'m, Probability, PRECISION> {}
+
+impl<'m, Probability, const PRECISION: usize> AtPrecision<'m, Probability, PRECISION> {
+    /// Returns the underlying model.
+    pub fn model(&self) -> &'m DynPrecisionModel {
+        self.model
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for AtPrecision<'m, Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<'m, Probability, const PRECISION: usize> EncoderModel<PRECISION>
+    for AtPrecision<'m, Probability, PRECISION>
+where
+    Probability: BitArray,
+    u64: AsPrimitive<Probability>,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let (left_sided_cumulative, probability) = self
+            .model
+            .left_cumulative_and_probability_at(*symbol.borrow(), PRECISION)?;
+        // SAFETY: `at_precision` ensures that `PRECISION <= Probability::BITS`, so the
+        // probability (which is at most `2^PRECISION - 1`) doesn't get truncated to zero.
+        let probability = unsafe { probability.get().as_().into_nonzero_unchecked() };
+        Some((left_sided_cumulative.as_(), probability))
+    }
+}
+
+impl<'m, Probability, const PRECISION: usize> DecoderModel<PRECISION>
+    for AtPrecision<'m, Probability, PRECISION>
+where
+    Probability: BitArray + Into<u64>,
+    u64: AsPrimitive<Probability>,
+{
+    #[inline]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (usize, Probability, Probability::NonZero) {
+        let (symbol, left_sided_cumulative, probability) =
+            self.model.quantile_function_at(quantile.into(), PRECISION);
+        // SAFETY: see `left_cumulative_and_probability`.
+        let probability = unsafe { probability.get().as_().into_nonzero_unchecked() };
+        (symbol, left_sided_cumulative.as_(), probability)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn at_precision() {
+        let probabilities = [0.1, 0.6, 0.3, 1e-12, 0.2];
+        let model = DynPrecisionModel::from_floating_point_probabilities(&probabilities).unwrap();
+        assert_eq!(model.num_symbols(), 5);
+        assert_eq!(model.min_precision(), 3);
+
+        fn check<const PRECISION: usize>(model: &DynPrecisionModel, probabilities: &[f64]) {
+            let presented = model.at_precision::<u32, PRECISION>().unwrap();
+            let total = probabilities.iter().sum::<f64>();
+            let mut expected_left = 0;
+            for (symbol, &p) in probabilities.iter().enumerate() {
+                let (left, probability) =
+                    presented.left_cumulative_and_probability(symbol).unwrap();
+                assert_eq!(left, expected_left);
+                expected_left += probability.get();
+                let scaled = p / total * (1u64 << PRECISION) as f64;
+                // The slack costs at most `num_symbols` units of the total mass.
+                let tolerance = 2.0 + probabilities.len() as f64 * p / total;
+                assert!((probability.get() as f64 - scaled).abs() <= tolerance);
+
+                assert_eq!(
+                    presented.quantile_function(left),
+                    (symbol, left, probability)
+                );
+                assert_eq!(
+                    presented.quantile_function(left + probability.get() - 1),
+                    (symbol, left, probability)
+                );
+            }
+            assert_eq!(expected_left as u64, 1 << PRECISION);
+            assert!(presented.left_cumulative_and_probability(5).is_none());
+        }
+
+        check::<3>(&model, &probabilities);
+        check::<8>(&model, &probabilities);
+        check::<12>(&model, &probabilities);
+        check::<24>(&model, &probabilities);
+        check::<31>(&model, &probabilities);
+
+        // At the lowest possible precision, every symbol gets a probability of at least one.
+        let (_, probability) = model
+            .at_precision::<u8, 3>()
+            .unwrap()
+            .left_cumulative_and_probability(3)
+            .unwrap();
+        assert_eq!(probability.get(), 1);
+
+        assert!(model.at_precision::<u32, 2>().is_err());
+        assert!(model.at_precision::<u16, 17>().is_err());
+        assert!(model.at_precision::<u64, 33>().is_err());
+        assert!(model.at_precision::<u32, 32>().is_ok());
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DynPrecisionModel::from_floating_point_probabilities::<f64>(&[]).is_err());
+        assert!(DynPrecisionModel::from_floating_point_probabilities(&[1.0]).is_err());
+        assert!(DynPrecisionModel::from_floating_point_probabilities(&[1.0, -1.0]).is_err());
+        assert!(DynPrecisionModel::from_floating_point_probabilities(&[1.0, f64::NAN]).is_err());
+    }
+}
@@ -0,0 +1,327 @@
+//! A nonparametric entropy model estimated from sample data
+//!
+//! Parametric models like a quantized Gaussian or Laplace distribution are a good fit for
+//! data that actually follows the assumed shape, but real-world residuals are often skewed,
+//! more peaked, or heavier tailed. The [`Empirical`] model in this module instead matches
+//! the histogram of some sample data, i.e., its cumulative distribution function is the
+//! (smoothed) empirical CDF of the samples. Smoothing adds a configurable pseudo count to
+//! each bin of the histogram, so that symbols that don't occur in the sample data still get
+//! a reasonable probability.
+//!
+//! The decoder has to use the exact same model as the encoder, so it needs the histogram
+//! (but not necessarily the samples). You can obtain the histogram with
+//! [`Empirical::histogram`], transmit it along with the compressed data, and rebuild an
+//! identical model with [`Empirical::from_histogram`]. Since the model rounds to fixed point
+//! with [`RoundingPolicy::LargestRemainder`], the rebuilt model is bit-identical on all
+//! platforms.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{model::empirical::DefaultEmpirical, stack::DefaultAnsCoder, Decode};
+//!
+//! // Fit the model to some training data, e.g., the residuals of a previous frame.
+//! let training_data = [0, 1, -1, 0, 0, 3, -2, 0, 1, 0, 12, -1, 0, 2];
+//! let model = DefaultEmpirical::from_samples(&training_data, 0.5).unwrap();
+//! assert_eq!(model.support(), -2..=12);
+//!
+//! let residuals = [1, 0, 0, -2, 5, 0, 1];
+//! let mut coder = DefaultAnsCoder::new();
+//! coder.encode_iid_symbols_reverse(&residuals, &model).unwrap();
+//!
+//! // The decoder rebuilds the model from the (transmitted) histogram.
+//! let decoder_model = DefaultEmpirical::from_histogram(
+//!     *model.support().start(),
+//!     model.histogram().to_vec(),
+//!     model.pseudo_count(),
+//! )
+//! .unwrap();
+//! let decoded = coder
+//!     .decode_iid_symbols(residuals.len(), &decoder_model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, residuals);
+//! ```
+
+use alloc::{vec, vec::Vec};
+use core::{borrow::Borrow, ops::RangeInclusive};
+
+use num::cast::AsPrimitive;
+
+use super::{
+    ContiguousCategoricalEntropyModel, DecoderModel, EncoderModel, EntropyModel, RoundingPolicy,
+};
+use crate::BitArray;
+
+/// An entropy model over a contiguous range of integers whose probabilities follow a
+/// smoothed histogram of sample data.
+///
+/// See [module level documentation](self) for an example. Like all entropy models in
+/// `constriction`, an `Empirical` is leaky, i.e., it assigns a nonzero probability to every
+/// symbol in its support, even if the symbol doesn't occur in the samples and the
+/// `pseudo_count` is zero.
+#[derive(Debug, Clone)]
+pub struct Empirical<Probability: BitArray, const PRECISION: usize> {
+    min_symbol: i32,
+    histogram: Vec<u32>,
+    pseudo_count: f64,
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+/// Type alias for an [`Empirical`] with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultEmpirical = Empirical<u32, 24>;
+
+/// Type alias for an [`Empirical`] that can be used with coders that use `u16` words.
+///
+/// Note that the support has to fit into the `2^12` available fixed point probability
+/// units, i.e., it must contain fewer than `4096` symbols.
+pub type SmallEmpirical = Empirical<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> Empirical<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Fits a model to `samples`, with the support ranging from the smallest to the
+    /// largest sample.
+    ///
+    /// Adds `pseudo_count` to the count of each symbol in the support before normalizing.
+    /// A value around `0.5` is a reasonable default; larger values make the model closer
+    /// to a uniform distribution over the support.
+    ///
+    /// Returns an error if `samples` is empty or all samples are equal (since a model with
+    /// a single symbol can't be represented), or under the same conditions as
+    /// [`from_histogram`](Self::from_histogram). Use
+    /// [`from_samples_with_support`](Self::from_samples_with_support) if you want to encode
+    /// symbols outside the range of the samples.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_samples(samples: &[i32], pseudo_count: f64) -> Result<Self, ()> {
+        let min_symbol = *samples.iter().min().ok_or(())?;
+        let max_symbol = *samples.iter().max().ok_or(())?;
+        Self::from_samples_with_support(samples, min_symbol..=max_symbol, pseudo_count)
+    }
+
+    /// Fits a model to `samples` over an explicitly provided `support`.
+    ///
+    /// Returns an error if any sample lies outside of `support`, or under the same
+    /// conditions as [`from_histogram`](Self::from_histogram).
+    #[allow(clippy::result_unit_err)]
+    pub fn from_samples_with_support(
+        samples: &[i32],
+        support: RangeInclusive<i32>,
+        pseudo_count: f64,
+    ) -> Result<Self, ()> {
+        let (min_symbol, max_symbol) = support.into_inner();
+        let support_size = check_support_size::<PRECISION>(min_symbol, max_symbol)?;
+
+        let mut histogram = vec![0u32; support_size];
+        for &sample in samples {
+            if sample < min_symbol || sample > max_symbol {
+                return Err(());
+            }
+            let count = &mut histogram[(sample as i64 - min_symbol as i64) as usize];
+            *count = count.checked_add(1).ok_or(())?;
+        }
+
+        Self::from_histogram(min_symbol, histogram, pseudo_count)
+    }
+
+    /// Builds a model from a `histogram`, where `histogram[i]` is the number of occurrences
+    /// of the symbol `min_symbol + i`.
+    ///
+    /// Use this method to rebuild a model on the decoder side from the
+    /// [`histogram`](Self::histogram) of a model that was fitted with
+    /// [`from_samples`](Self::from_samples) on the encoder side.
+    ///
+    /// Returns an error if `pseudo_count` is negative, infinite, or NaN, if `histogram` has
+    /// fewer than two entries, if the support extends beyond `i32::MAX`, if it contains at
+    /// least `1 << PRECISION` symbols (since every symbol needs a nonzero probability), or if
+    /// both `pseudo_count` and all entries of `histogram` are zero.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_histogram(
+        min_symbol: i32,
+        histogram: Vec<u32>,
+        pseudo_count: f64,
+    ) -> Result<Self, ()> {
+        if !(0.0..f64::INFINITY).contains(&pseudo_count) || histogram.is_empty() {
+            return Err(());
+        }
+        let max_symbol = min_symbol as i64 + (histogram.len() - 1) as i64;
+        if max_symbol > i32::MAX as i64 {
+            return Err(());
+        }
+        check_support_size::<PRECISION>(min_symbol, max_symbol as i32)?;
+
+        let weights = histogram
+            .iter()
+            .map(|&count| count as f64 + pseudo_count)
+            .collect::<Vec<_>>();
+        let model =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+                &weights,
+                RoundingPolicy::LargestRemainder,
+            )?;
+
+        Ok(Self {
+            min_symbol,
+            histogram,
+            pseudo_count,
+            model,
+        })
+    }
+}
+
+/// Returns the number of symbols in `min_symbol..=max_symbol`, or an error if there are
+/// fewer than two or at least `1 << PRECISION` of them.
+fn check_support_size<const PRECISION: usize>(
+    min_symbol: i32,
+    max_symbol: i32,
+) -> Result<usize, ()> {
+    let support_size = max_symbol as i64 - min_symbol as i64 + 1;
+    if support_size < 2 || (PRECISION < 63 && support_size >= 1 << PRECISION) {
+        Err(())
+    } else {
+        Ok(support_size as usize)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Empirical<Probability, PRECISION> {
+    /// Returns the range of symbols to which the model assigns a nonzero probability.
+    pub fn support(&self) -> RangeInclusive<i32> {
+        self.min_symbol..=self.min_symbol + (self.histogram.len() - 1) as i32
+    }
+
+    /// Returns the histogram, where entry `i` is the number of samples that were equal to
+    /// `self.support().start() + i`.
+    pub fn histogram(&self) -> &[u32] {
+        &self.histogram
+    }
+
+    /// Returns the pseudo count that was added to each entry of the histogram.
+    pub fn pseudo_count(&self) -> f64 {
+        self.pseudo_count
+    }
+
+    /// Returns the underlying categorical entropy model over the symbols
+    /// `0..self.histogram().len()`, i.e., with symbols shifted by
+    /// `-self.support().start()`.
+    pub fn as_categorical(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.model
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Empirical<Probability, PRECISION>
+{
+    type Symbol = i32;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Empirical<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<i32>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let index = (*symbol.borrow() as i64).wrapping_sub(self.min_symbol as i64);
+        if index < 0 {
+            return None;
+        }
+        self.model.left_cumulative_and_probability(index as usize)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Empirical<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(&self, quantile: Probability) -> (i32, Probability, Probability::NonZero) {
+        let (index, left_cumulative, probability) = self.model.quantile_function(quantile);
+        (self.min_symbol + index as i32, left_cumulative, probability)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_histogram() {
+        let samples = [3, 5, 5, 4, 5, 3, 9, 5, 4, 5];
+        let model = DefaultEmpirical::from_samples(&samples, 0.5).unwrap();
+        assert_eq!(model.support(), 3..=9);
+        assert_eq!(model.histogram(), &[2, 2, 5, 0, 0, 0, 1]);
+
+        let total = samples.len() as f64 + 0.5 * 7.0;
+        for symbol in 3..=9 {
+            let count = model.histogram()[(symbol - 3) as usize] as f64;
+            let approx: f64 = model.floating_point_probability(symbol);
+            assert!((approx - (count + 0.5) / total).abs() < 1e-6);
+        }
+        assert!(model.left_cumulative_and_probability(2).is_none());
+        assert!(model.left_cumulative_and_probability(10).is_none());
+        assert!(model.left_cumulative_and_probability(i32::MIN).is_none());
+
+        // Symbols that don't occur in the samples are still encodable without smoothing.
+        let model = DefaultEmpirical::from_samples(&samples, 0.0).unwrap();
+        let (_, probability) = model.left_cumulative_and_probability(7).unwrap();
+        assert_eq!(probability.get(), 1);
+    }
+
+    #[test]
+    fn round_trip_through_histogram() {
+        let samples = (0..1000)
+            .map(|i: i32| ((i * 7919) % 61 - 30) / ((i % 5) + 1))
+            .collect::<Vec<_>>();
+        let model = DefaultEmpirical::from_samples_with_support(&samples, -100..=100, 0.3).unwrap();
+        let rebuilt = DefaultEmpirical::from_histogram(
+            -100,
+            model.histogram().to_vec(),
+            model.pseudo_count(),
+        )
+        .unwrap();
+        assert_eq!(model.support(), rebuilt.support());
+
+        for symbol in -100..=100 {
+            assert_eq!(
+                model.left_cumulative_and_probability(symbol),
+                rebuilt.left_cumulative_and_probability(symbol)
+            );
+        }
+        for quantile in (0..1 << 24).step_by(4099) {
+            let (symbol, left_cumulative, probability) = model.quantile_function(quantile);
+            assert!(left_cumulative <= quantile && quantile - left_cumulative < probability.get());
+            assert_eq!(
+                model.left_cumulative_and_probability(symbol),
+                Some((left_cumulative, probability))
+            );
+        }
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DefaultEmpirical::from_samples(&[], 0.5).is_err());
+        assert!(DefaultEmpirical::from_samples(&[4, 4, 4], 0.5).is_err());
+        assert!(DefaultEmpirical::from_samples(&[1, 2], -0.5).is_err());
+        assert!(DefaultEmpirical::from_samples(&[1, 2], f64::NAN).is_err());
+        assert!(DefaultEmpirical::from_samples_with_support(&[1, 5], 0..=4, 0.5).is_err());
+        assert!(DefaultEmpirical::from_samples(&[i32::MIN, i32::MAX], 0.5).is_err());
+        assert!(DefaultEmpirical::from_histogram(0, vec![0, 0, 0], 0.0).is_err());
+        assert!(DefaultEmpirical::from_histogram(i32::MAX, vec![1, 1], 0.5).is_err());
+        assert!(SmallEmpirical::from_histogram(0, vec![1; 4095], 0.5).is_ok());
+        assert!(SmallEmpirical::from_histogram(0, vec![1; 4096], 0.5).is_err());
+    }
+}
@@ -0,0 +1,451 @@
+//! Coding unbounded integers with a bounded entropy model and an Exp-Golomb escape
+//!
+//! Every entropy model in `constriction` has a finite support, so you usually have to clamp
+//! symbols to some range before you can encode them. An [`ExpGolombEscapeModel`] lifts
+//! this restriction for integer symbols: it wraps an entropy model whose support covers a
+//! range `min_symbol..=max_symbol` of common values, and it uses the two boundary symbols
+//! as *escape symbols*. A symbol strictly between `min_symbol` and `max_symbol` is encoded
+//! directly with the wrapped model. A symbol `x >= max_symbol` is encoded as the escape
+//! symbol `max_symbol` followed by the excess `x - max_symbol` in [Exponential-Golomb
+//! code](https://en.wikipedia.org/wiki/Exponential-Golomb_coding), and analogously for
+//! symbols `x <= min_symbol`. The Exp-Golomb code consists of a unary prefix and a
+//! mantissa, whose bits are encoded on the same entropy coder with a uniform binary model,
+//! so an excess `e` costs `2 * floor(log2(e + 1)) + 1` bits on top of the escape symbol.
+//!
+//! Since encoding a symbol may involve several calls to the entropy coder, an
+//! `ExpGolombEscapeModel` doesn't implement [`EncoderModel`] or [`DecoderModel`] itself.
+//! Instead, it provides methods that take an encoder or decoder as an argument, similar to a
+//! [`MarkovModel`]. If you encode on a stack (e.g., an [`AnsCoder`]), use the methods whose
+//! names end in `_reverse`, which encode the parts of each symbol in reverse order so that
+//! the decoder reads them in the expected order.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::{escape::ExpGolombEscapeModel, DefaultLeakyQuantizer},
+//!     queue::DefaultRangeEncoder,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! // Most symbols are small, but some are arbitrarily large.
+//! let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+//! let model =
+//!     ExpGolombEscapeModel::new(quantizer.quantize(Gaussian::new(0.0, 10.0)), -100, 100)
+//!         .unwrap();
+//! let symbols = [3, -12, 0, 1_000_000, 7, i32::MIN, 99, 100, -101];
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! model.encode_symbols(&mut encoder, &symbols).unwrap();
+//!
+//! let mut decoder = encoder.into_decoder().unwrap();
+//! let decoded = model.decode_symbols(&mut decoder, symbols.len()).unwrap();
+//! assert_eq!(decoded, symbols);
+//! ```
+//!
+//! [`MarkovModel`]: crate::stream::markov::MarkovModel
+//! [`AnsCoder`]: crate::stream::stack::AnsCoder
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use num::{cast::AsPrimitive, Float, One, PrimInt, Zero};
+
+use super::{DecoderModel, EncoderModel, EntropyModel, UniformModel};
+use crate::{
+    stream::{Decode, Encode},
+    BitArray, CoderError,
+};
+
+/// Maximum length of the unary prefix of the Exp-Golomb code for a 64 bit excess.
+const MAX_EXPONENT: u32 = 64;
+
+/// Wrapper around an entropy model for integers that encodes symbols outside of the
+/// model's support with an Exp-Golomb escape.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone, Copy)]
+pub struct ExpGolombEscapeModel<M, const PRECISION: usize>
+where
+    M: EntropyModel<PRECISION>,
+{
+    inner: M,
+    min_symbol: M::Symbol,
+    max_symbol: M::Symbol,
+    bit_model: UniformModel<M::Probability, PRECISION>,
+}
+
+impl<M, const PRECISION: usize> ExpGolombEscapeModel<M, PRECISION>
+where
+    M: EncoderModel<PRECISION>,
+    M::Symbol: PrimInt + AsPrimitive<i64>,
+    i64: AsPrimitive<M::Symbol>,
+{
+    /// Wraps `inner`, using `min_symbol` and `max_symbol` as escape symbols.
+    ///
+    /// Symbols strictly between `min_symbol` and `max_symbol` are encoded directly with
+    /// `inner`, so `inner` should assign a nonzero probability to all of them. Returns an
+    /// error if `min_symbol >= max_symbol`, if `inner` assigns zero probability to
+    /// `min_symbol` or to `max_symbol`, or if the symbol type has more than 64 bits (e.g.,
+    /// `i128`), since the escape mechanism calculates on 64 bit integers. The probabilities that `inner` assigns to the
+    /// escape symbols should account for the probability mass of the tails (e.g., a
+    /// [`LeakilyQuantizedDistribution`](super::LeakilyQuantizedDistribution) assigns
+    /// the probability mass beyond the boundaries of its support to the boundary symbols).
+    #[allow(clippy::result_unit_err)]
+    pub fn new(inner: M, min_symbol: M::Symbol, max_symbol: M::Symbol) -> Result<Self, ()> {
+        if M::Symbol::zero().count_zeros() > 64
+            || min_symbol >= max_symbol
+            || inner.left_cumulative_and_probability(min_symbol).is_none()
+            || inner.left_cumulative_and_probability(max_symbol).is_none()
+        {
+            return Err(());
+        }
+
+        Ok(Self {
+            inner,
+            min_symbol,
+            max_symbol,
+            bit_model: UniformModel::new(M::Probability::one() + M::Probability::one()),
+        })
+    }
+
+    /// Returns the wrapped entropy model.
+    pub fn inner(&self) -> &M {
+        &self.inner
+    }
+
+    /// Returns the escape symbol for small symbols.
+    pub fn min_symbol(&self) -> M::Symbol {
+        self.min_symbol
+    }
+
+    /// Returns the escape symbol for large symbols.
+    pub fn max_symbol(&self) -> M::Symbol {
+        self.max_symbol
+    }
+
+    /// Splits `symbol` into the symbol for the wrapped model and, if it is an escape symbol,
+    /// the excess.
+    fn split(&self, symbol: M::Symbol) -> (M::Symbol, Option<u64>) {
+        // Calculating with wrapping arithmetic on 64 bits is exact for all symbol types with
+        // at most 64 bits because the excess always fits into a `u64`.
+        if symbol >= self.max_symbol {
+            let excess = symbol.as_().wrapping_sub(self.max_symbol.as_()) as u64;
+            (self.max_symbol, Some(excess))
+        } else if symbol <= self.min_symbol {
+            let excess = self.min_symbol.as_().wrapping_sub(symbol.as_()) as u64;
+            (self.min_symbol, Some(excess))
+        } else {
+            (symbol, None)
+        }
+    }
+
+    /// Encodes a single symbol on a queue (e.g., a [`RangeEncoder`]).
+    ///
+    /// [`RangeEncoder`]: crate::stream::queue::RangeEncoder
+    pub fn encode_symbol<E>(
+        &self,
+        encoder: &mut E,
+        symbol: impl Borrow<M::Symbol>,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        let (symbol, excess) = self.split(*symbol.borrow());
+        encoder.encode_symbol(symbol, &self.inner)?;
+        if let Some(excess) = excess {
+            encoder
+                .encode_iid_symbols(exp_golomb_bits::<M::Probability>(excess), self.bit_model)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes a single symbol on a stack (e.g., an [`AnsCoder`]).
+    ///
+    /// [`AnsCoder`]: crate::stream::stack::AnsCoder
+    pub fn encode_symbol_reverse<E>(
+        &self,
+        encoder: &mut E,
+        symbol: impl Borrow<M::Symbol>,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        let (symbol, excess) = self.split(*symbol.borrow());
+        if let Some(excess) = excess {
+            encoder.encode_iid_symbols(
+                exp_golomb_bits::<M::Probability>(excess).rev(),
+                self.bit_model,
+            )?;
+        }
+        encoder.encode_symbol(symbol, &self.inner)
+    }
+
+    /// Encodes a sequence of symbols on a queue (e.g., a [`RangeEncoder`]).
+    ///
+    /// [`RangeEncoder`]: crate::stream::queue::RangeEncoder
+    pub fn encode_symbols<E>(
+        &self,
+        encoder: &mut E,
+        symbols: impl IntoIterator<Item = impl Borrow<M::Symbol>>,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        for symbol in symbols {
+            self.encode_symbol(encoder, symbol)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes a sequence of symbols on a stack (e.g., an [`AnsCoder`]) in reverse order, so
+    /// that [`decode_symbols`](Self::decode_symbols) decodes them in their original order.
+    ///
+    /// [`AnsCoder`]: crate::stream::stack::AnsCoder
+    pub fn encode_symbols_reverse<E, I>(
+        &self,
+        encoder: &mut E,
+        symbols: I,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        I: IntoIterator,
+        I::Item: Borrow<M::Symbol>,
+        I::IntoIter: DoubleEndedIterator,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        for symbol in symbols.into_iter().rev() {
+            self.encode_symbol_reverse(encoder, symbol)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the number of bits that encoding `symbol` adds to the compressed data (up to
+    /// the small constant overhead of the entropy coder), see [`EncoderModel::bit_cost`].
+    ///
+    /// Returns infinity if the wrapped model can't encode `symbol` (which can only happen
+    /// if its support has gaps between the escape symbols).
+    pub fn bit_cost<F>(&self, symbol: impl Borrow<M::Symbol>) -> F
+    where
+        F: Float,
+        M::Probability: Into<F>,
+    {
+        let (symbol, excess) = self.split(*symbol.borrow());
+        let bit_cost = self.inner.bit_cost::<F>(symbol);
+        match excess {
+            Some(excess) => bit_cost + F::from(2 * exponent(excess) + 1).unwrap(),
+            None => bit_cost,
+        }
+    }
+}
+
+impl<M, const PRECISION: usize> ExpGolombEscapeModel<M, PRECISION>
+where
+    M: EncoderModel<PRECISION> + DecoderModel<PRECISION>,
+    M::Symbol: PrimInt + AsPrimitive<i64>,
+    i64: AsPrimitive<M::Symbol>,
+{
+    /// Decodes a single symbol.
+    ///
+    /// Decoding never fails due to the escape mechanism itself. If the compressed data is
+    /// corrupted, then the decoded symbol may be wrong, and it may wrap around if it
+    /// doesn't fit into the symbol type.
+    pub fn decode_symbol<D>(
+        &self,
+        decoder: &mut D,
+    ) -> Result<M::Symbol, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        M::Probability: Into<D::Word>,
+        D::Word: AsPrimitive<M::Probability>,
+    {
+        let symbol = decoder.decode_symbol(&self.inner)?;
+        if symbol == self.max_symbol {
+            let excess = self.decode_excess(decoder)?;
+            Ok(self.max_symbol.as_().wrapping_add(excess as i64).as_())
+        } else if symbol == self.min_symbol {
+            let excess = self.decode_excess(decoder)?;
+            Ok(self.min_symbol.as_().wrapping_sub(excess as i64).as_())
+        } else {
+            Ok(symbol)
+        }
+    }
+
+    /// Decodes `amt` symbols.
+    pub fn decode_symbols<D>(
+        &self,
+        decoder: &mut D,
+        amt: usize,
+    ) -> Result<Vec<M::Symbol>, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        M::Probability: Into<D::Word>,
+        D::Word: AsPrimitive<M::Probability>,
+    {
+        (0..amt).map(|_| self.decode_symbol(decoder)).collect()
+    }
+
+    fn decode_excess<D>(
+        &self,
+        decoder: &mut D,
+    ) -> Result<u64, CoderError<D::FrontendError, D::BackendError>>
+    where
+        D: Decode<PRECISION>,
+        M::Probability: Into<D::Word>,
+        D::Word: AsPrimitive<M::Probability>,
+    {
+        let mut exponent = 0;
+        while exponent < MAX_EXPONENT && decoder.decode_symbol(self.bit_model)?.is_zero() {
+            exponent += 1;
+        }
+
+        // Calculate `(1 << exponent) + mantissa - 1` without overflowing for `exponent == 64`.
+        let mut excess_plus_one = 1u128;
+        for _ in 0..exponent {
+            let bit = !decoder.decode_symbol(self.bit_model)?.is_zero();
+            excess_plus_one = (excess_plus_one << 1) | bit as u128;
+        }
+        Ok((excess_plus_one - 1) as u64)
+    }
+}
+
+/// Returns `floor(log2(excess + 1))`, i.e., the length of the unary prefix of the
+/// Exp-Golomb code for `excess`.
+fn exponent(excess: u64) -> u32 {
+    127 - (excess as u128 + 1).leading_zeros()
+}
+
+/// Returns the bits of the Exp-Golomb code for `excess`: a unary prefix of `exponent`
+/// zeros terminated by a one, followed by the `exponent` least significant bits of
+/// `excess + 1`, starting with the most significant one.
+fn exp_golomb_bits<Probability: BitArray>(
+    excess: u64,
+) -> impl DoubleEndedIterator<Item = Probability> {
+    let exponent = exponent(excess);
+    let excess_plus_one = excess as u128 + 1;
+    let to_bit = |bit: bool| {
+        if bit {
+            Probability::one()
+        } else {
+            Probability::zero()
+        }
+    };
+    (0..exponent)
+        .map(move |_| to_bit(false))
+        .chain(core::iter::once(to_bit(true)))
+        .chain(
+            (0..exponent)
+                .rev()
+                .map(move |i| to_bit((excess_plus_one >> i) & 1 != 0)),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        geometric::SmallGeometric, DefaultLeakyQuantizer,
+        DefaultNonContiguousCategoricalEncoderModel,
+    };
+    use super::*;
+    use crate::stream::{
+        queue::{DefaultRangeEncoder, SmallRangeEncoder},
+        stack::DefaultAnsCoder,
+    };
+
+    use alloc::vec;
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn exp_golomb_code() {
+        let bits = |excess| exp_golomb_bits::<u32>(excess).collect::<Vec<_>>();
+        assert_eq!(bits(0), vec![1]);
+        assert_eq!(bits(1), vec![0, 1, 0]);
+        assert_eq!(bits(2), vec![0, 1, 1]);
+        assert_eq!(bits(3), vec![0, 0, 1, 0, 0]);
+        assert_eq!(bits(6), vec![0, 0, 1, 1, 1]);
+        assert_eq!(bits(u64::MAX).len(), 2 * 64 + 1);
+        assert_eq!(exponent(u64::MAX), 64);
+    }
+
+    #[test]
+    fn range_coder_and_ans() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model =
+            ExpGolombEscapeModel::new(quantizer.quantize(Gaussian::new(3.2, 30.0)), -100, 100)
+                .unwrap();
+        let mut symbols = (0..500).map(|i| (i * 37) % 251 - 125).collect::<Vec<i32>>();
+        symbols.extend_from_slice(&[
+            i32::MIN,
+            i32::MAX,
+            i32::MIN + 1,
+            i32::MAX - 1,
+            -100,
+            100,
+            -101,
+            101,
+            99,
+            -99,
+            0,
+        ]);
+
+        let mut encoder = DefaultRangeEncoder::new();
+        model.encode_symbols(&mut encoder, &symbols).unwrap();
+        let mut decoder = encoder.into_decoder().unwrap();
+        assert_eq!(
+            model.decode_symbols(&mut decoder, symbols.len()).unwrap(),
+            symbols
+        );
+        assert!(decoder.maybe_exhausted());
+
+        let mut ans = DefaultAnsCoder::new();
+        model.encode_symbols_reverse(&mut ans, &symbols).unwrap();
+        let expected_bits = symbols
+            .iter()
+            .map(|symbol| model.bit_cost::<f64>(symbol))
+            .sum::<f64>();
+        assert!((ans.num_valid_bits() as f64 - expected_bits).abs() < 64.0);
+        assert_eq!(
+            model.decode_symbols(&mut ans, symbols.len()).unwrap(),
+            symbols
+        );
+        assert!(ans.is_empty());
+    }
+
+    #[test]
+    fn unsigned_symbols_and_small_precision() {
+        let model =
+            ExpGolombEscapeModel::new(SmallGeometric::new(0.3, 20).unwrap(), 0, 20).unwrap();
+        let symbols = [3, usize::MAX, 0, 1 << 40, 19, 20, 21, usize::MAX - 1, 1];
+
+        let mut encoder = SmallRangeEncoder::new();
+        model.encode_symbols(&mut encoder, symbols).unwrap();
+        let mut decoder = encoder.into_decoder().unwrap();
+        assert_eq!(
+            model.decode_symbols(&mut decoder, symbols.len()).unwrap(),
+            symbols
+        );
+    }
+
+    #[test]
+    fn invalid_escape_symbols() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        assert!(ExpGolombEscapeModel::new(model, 100, -100).is_err());
+        assert!(ExpGolombEscapeModel::new(model, -100, 101).is_err());
+        assert!(ExpGolombEscapeModel::new(model, -50, 50).is_ok());
+
+        let model =
+            DefaultNonContiguousCategoricalEncoderModel::from_symbols_and_floating_point_probabilities(
+                [-1i128, 0, 1].iter().copied(),
+                &[0.25, 0.5, 0.25],
+            )
+            .unwrap();
+        assert!(ExpGolombEscapeModel::new(model, -1, 1).is_err());
+    }
+}
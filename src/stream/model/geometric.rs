@@ -0,0 +1,1044 @@
+//! Geometric entropy models for run lengths and for prediction residuals
+//!
+//! A geometric distribution with success probability `p` models the number of failures
+//! before the first success in a sequence of independent trials, e.g., the length of a run
+//! of zeros in a sparse bit string. Its support is unbounded, so the [`Geometric`] model in
+//! this module takes a `max_symbol` and assigns it the entire tail mass
+//! `P(X >= max_symbol)`, analogous to the [`poisson`](super::poisson) model. Thus, you can
+//! either choose `max_symbol` large enough that longer runs can't occur, or you can use it
+//! as an escape symbol and encode the excess `run_length - max_symbol` with some other
+//! model.
+//!
+//! The [`TwoSidedGeometric`] model is the discrete analog of a Laplace distribution. It
+//! models prediction residuals in lossless image codecs like JPEG-LS and FLIF, where the
+//! probability of a residual decays geometrically with its distance from a (possibly
+//! fractional) offset. See its documentation for an example.
+//!
+//! # Fixed Point Arithmetic
+//!
+//! Both models define their fixed point probabilities by the closed form cumulative
+//! distribution function of the geometric distribution. An entropy model has to be
+//! reproducible bit for bit since the encoder and the decoder have to agree on the exact
+//! fixed point probabilities. The models in this module therefore don't call the
+//! platform's `log2` or `powf` functions, whose results may differ in their last bit
+//! across platforms. They instead evaluate logarithms and powers with series expansions
+//! that use only basic floating point arithmetic, which IEEE 754 specifies exactly. Each
+//! symbol in the support first gets two units of probability mass (which keeps the models
+//! leaky and absorbs rounding errors in the series expansions), and the remaining
+//! probability mass is distributed according to the cumulative distribution function,
+//! rounded down to fixed point.
+//!
+//! # Tabulated vs. Closed Form Evaluation
+//!
+//! Each model can be constructed in two ways, which result in the exact same fixed point
+//! probabilities (so you can, e.g., encode with one and decode with the other):
+//!
+//! - `new` evaluates the cumulative distribution function once for each symbol in the
+//!   support and stores the result in a lookup table. This takes time linear in the size of
+//!   the support, but it makes encoding and decoding cheap. Use this constructor if you
+//!   encode or decode many symbols with the same model.
+//! - `new_closed_form` takes constant time and doesn't allocate. The model then evaluates
+//!   the cumulative distribution function on the fly whenever you encode or decode a
+//!   symbol. Encoding takes constant time, and decoding typically takes constant time too
+//!   (and at most logarithmic time in the size of the support). Use this constructor if the
+//!   model parameters change from symbol to symbol, as is typical for prediction residuals
+//!   with context dependent decay rates.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::{geometric::DefaultGeometric, UniformModel},
+//!     stack::DefaultAnsCoder,
+//!     Decode, Encode,
+//! };
+//!
+//! // Encode the run lengths of zeros in a sparse bit string.
+//! let bits = [0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1];
+//! let run_lengths = bits
+//!     .split(|&bit| bit == 1)
+//!     .map(|run| run.len())
+//!     .collect::<Vec<_>>();
+//! assert_eq!(run_lengths, [3, 0, 15, 1, 0]);
+//!
+//! const MAX_SYMBOL: usize = 10;
+//! let model = DefaultGeometric::new(0.2, MAX_SYMBOL).unwrap();
+//! let excess_model = UniformModel::<u32, 24>::new(1 << 16);
+//!
+//! // Encode in reverse order since an ANS coder operates as a stack.
+//! let mut coder = DefaultAnsCoder::new();
+//! for &run_length in run_lengths.iter().rev() {
+//!     if run_length >= MAX_SYMBOL {
+//!         coder.encode_symbol((run_length - MAX_SYMBOL) as u32, excess_model).unwrap();
+//!     }
+//!     coder.encode_symbol(run_length.min(MAX_SYMBOL), &model).unwrap();
+//! }
+//!
+//! for &run_length in &run_lengths {
+//!     let mut decoded = coder.decode_symbol(&model).unwrap();
+//!     if decoded == MAX_SYMBOL {
+//!         decoded += coder.decode_symbol(excess_model).unwrap() as usize;
+//!     }
+//!     assert_eq!(decoded, run_length);
+//! }
+//! assert!(coder.is_empty());
+//! ```
+
+use core::{
+    borrow::Borrow,
+    f64::consts::{LN_2, SQRT_2},
+    ops::RangeInclusive,
+};
+
+use alloc::vec::Vec;
+
+use num::{cast::AsPrimitive, Float};
+
+use super::{
+    ContiguousCategoricalEntropyModel, DecoderModel, EncoderModel, EntropyModel,
+    IterableEntropyModel,
+};
+use crate::{wrapping_pow2, BitArray, NonZeroBitArray};
+
+/// The fixed point probability units that every symbol gets regardless of the parameters.
+const SLACK: usize = 2;
+
+/// The largest `PRECISION` for which the fixed point arithmetic in this module is exact
+/// enough to guarantee a nonzero probability for every symbol in the support.
+const MAX_PRECISION: usize = 32;
+
+/// An entropy model for the number of failures before the first success in independent
+/// trials that each succeed with probability `p`.
+///
+/// The support is `0..=max_symbol`, where `max_symbol` represents all counts that are
+/// larger or equal to it (see [module level documentation](self) for details and an
+/// example). Like all entropy models in `constriction`, a `Geometric` is leaky, i.e., it
+/// assigns a nonzero probability to every symbol in its support, even for `p = 1.0`.
+#[derive(Debug, Clone)]
+pub struct Geometric<Probability: BitArray, const PRECISION: usize> {
+    p: f64,
+    max_symbol: usize,
+
+    /// `-log2(1 - p)`, so that `(1 - p)^k = 2^(-k * neg_log2_q)`.
+    neg_log2_q: f64,
+
+    /// The probability mass that is distributed according to the cumulative distribution
+    /// function, i.e., `2^PRECISION` minus `SLACK` units per symbol.
+    free_weight: f64,
+
+    /// The same fixed point probabilities as a lookup table, or `None` if the model was
+    /// constructed with [`new_closed_form`](Self::new_closed_form).
+    table: Option<ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>>,
+}
+
+/// Type alias for a [`Geometric`] with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultGeometric = Geometric<u32, 24>;
+
+/// Type alias for a [`Geometric`] that can be used with coders that use `u16` words.
+///
+/// Note that `max_symbol` must be smaller than `2^11` since every symbol gets two of the
+/// `2^12` available fixed point probability units.
+pub type SmallGeometric = Geometric<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> Geometric<Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+{
+    /// Creates a geometric entropy model with success probability `p` over the symbols
+    /// `0..=max_symbol`, and tabulates its fixed point probabilities.
+    ///
+    /// Returns an error if `p` is not within the half-open interval `(0, 1]` (for `p = 0`,
+    /// the distribution can't be normalized), if `max_symbol` is zero (a model with a
+    /// single symbol can't be represented), if `2 * (max_symbol + 1)` exceeds `1 <<
+    /// PRECISION` (since every symbol gets two fixed point probability units), or if
+    /// `PRECISION` is larger than 32.
+    ///
+    /// The run time is linear in `max_symbol`. See [`new_closed_form`](Self::new_closed_form)
+    /// for a constant time alternative that results in the same fixed point probabilities.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(p: f64, max_symbol: usize) -> Result<Self, ()> {
+        let mut model = Self::new_closed_form(p, max_symbol)?;
+        let table = ContiguousCategoricalEntropyModel::from_nonzero_fixed_point_probabilities(
+            (0..=max_symbol).map(|symbol| {
+                model
+                    .closed_form_left_cumulative_and_probability(symbol)
+                    .1
+                    .get()
+            }),
+            false,
+        )?;
+        model.table = Some(table);
+        Ok(model)
+    }
+
+    /// Creates a geometric entropy model with success probability `p` over the symbols
+    /// `0..=max_symbol` without tabulating its fixed point probabilities.
+    ///
+    /// Has the same preconditions as [`new`](Self::new) and results in the same fixed point
+    /// probabilities, but it takes constant time and doesn't allocate. In return, encoding
+    /// and decoding is somewhat more expensive since the model evaluates its cumulative
+    /// distribution function on the fly (see [module level documentation](self)).
+    #[allow(clippy::result_unit_err)]
+    pub fn new_closed_form(p: f64, max_symbol: usize) -> Result<Self, ()> {
+        if !(p > 0.0 && p <= 1.0) || max_symbol == 0 {
+            return Err(());
+        }
+        let free_weight = free_weight::<PRECISION>((max_symbol as u64).saturating_add(1))?;
+
+        Ok(Self {
+            p,
+            max_symbol,
+            neg_log2_q: neg_log2(1.0 - p),
+            free_weight,
+            table: None,
+        })
+    }
+
+    /// Returns the left-sided cumulative in fixed point for `symbol` in `1..=max_symbol`.
+    #[inline(always)]
+    fn left_cumulative(&self, symbol: usize) -> Probability {
+        let cdf = 1.0 - exp2_neg(symbol as f64 * self.neg_log2_q);
+        ((SLACK * symbol) as f64 + Float::floor(self.free_weight * cdf)).as_()
+    }
+
+    #[inline(always)]
+    fn closed_form_left_cumulative_and_probability(
+        &self,
+        symbol: usize,
+    ) -> (Probability, Probability::NonZero) {
+        left_cumulative_and_probability::<_, PRECISION>(symbol, self.max_symbol + 1, |symbol| {
+            self.left_cumulative(symbol)
+        })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Geometric<Probability, PRECISION> {
+    /// Returns the success probability of each trial.
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+
+    /// Returns the largest symbol in the support, which represents all larger counts too.
+    pub fn max_symbol(&self) -> usize {
+        self.max_symbol
+    }
+
+    /// Returns the lookup table with the model's fixed point probabilities over the symbols
+    /// `0..=max_symbol`, or `None` if the model was constructed with
+    /// [`new_closed_form`](Self::new_closed_form).
+    pub fn as_categorical(
+        &self,
+    ) -> Option<&ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>> {
+        self.table.as_ref()
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Geometric<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Geometric<Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let symbol = *symbol.borrow();
+        match &self.table {
+            Some(table) => table.left_cumulative_and_probability(symbol),
+            None if symbol > self.max_symbol => None,
+            None => Some(self.closed_form_left_cumulative_and_probability(symbol)),
+        }
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Geometric<Probability, PRECISION>
+where
+    Probability: Into<f64>,
+    f64: AsPrimitive<Probability>,
+{
+    #[inline]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (usize, Probability, Probability::NonZero) {
+        if let Some(table) = &self.table {
+            return table.quantile_function(quantile);
+        }
+
+        // Invert the cumulative distribution function, ignoring the slack. This only
+        // serves as an initial guess, so it doesn't need to be reproducible.
+        let cdf = quantile.into() / (self.free_weight + (SLACK * (self.max_symbol + 1)) as f64);
+        let guess = (neg_log2(1.0 - cdf) / self.neg_log2_q).min(self.max_symbol as f64);
+
+        let symbol = invert_left_cumulative(quantile, self.max_symbol + 1, guess as usize, |s| {
+            self.left_cumulative(s)
+        });
+        let (left_cumulative, probability) =
+            self.closed_form_left_cumulative_and_probability(symbol);
+        (symbol, left_cumulative, probability)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for Geometric<Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+{
+    type Iter = GeometricSymbolTableIter<'m, Probability, PRECISION>;
+
+    #[inline(always)]
+    fn symbol_table(&'m self) -> Self::Iter {
+        GeometricSymbolTableIter {
+            model: self,
+            symbol: 0,
+        }
+    }
+}
+
+/// The iterator returned by [`symbol_table`] on a [`Geometric`].
+///
+/// Works regardless of whether the model was constructed with or without a lookup table.
+///
+/// [`symbol_table`]: IterableEntropyModel::symbol_table
+#[derive(Debug, Clone)]
+pub struct GeometricSymbolTableIter<'m, Probability: BitArray, const PRECISION: usize> {
+    model: &'m Geometric<Probability, PRECISION>,
+    symbol: usize,
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> Iterator
+    for GeometricSymbolTableIter<'m, Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+{
+    type Item = (usize, Probability, Probability::NonZero);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let symbol = self.symbol;
+        let (left_cumulative, probability) = self.model.left_cumulative_and_probability(symbol)?;
+        self.symbol += 1;
+        Some((symbol, left_cumulative, probability))
+    }
+
+    #[inline(always)]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.model.max_symbol + 1 - self.symbol;
+        (len, Some(len))
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> ExactSizeIterator
+    for GeometricSymbolTableIter<'m, Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+{
+}
+
+/// A two-sided geometric distribution over a range of integers, as used for prediction
+/// residuals in JPEG-LS.
+///
+/// The probability of a symbol `x` is proportional to `decay^|x - offset|` on the
+/// (unbounded) set of all integers, where `decay` is a number in the open interval `(0,
+/// 1)` and `offset` is a real number (which need not be an integer). The probability mass
+/// outside of the support is assigned to the first and last symbol in the support,
+/// respectively, so these symbols can serve as escape symbols. JPEG-LS uses this
+/// distribution with `offset = -s` for a bias `s` between zero and one. Smaller values of
+/// `decay` lead to a more peaked distribution.
+///
+/// The fixed point probabilities are reproducible bit for bit across platforms, also for
+/// non-integer offsets (see [module level documentation](self)).
+///
+/// # Examples
+///
+/// Encoding i.i.d. symbols with a tabulated model:
+///
+/// ```
+/// use constriction::stream::{
+///     model::geometric::DefaultTwoSidedGeometric, queue::DefaultRangeEncoder, Decode, Encode,
+/// };
+///
+/// let model = DefaultTwoSidedGeometric::new(-255..=255, 0.6, -0.3).unwrap();
+/// let residuals = [0, -1, 2, 0, 0, 1, -4, 17, 0];
+///
+/// let mut encoder = DefaultRangeEncoder::new();
+/// encoder.encode_iid_symbols(&residuals, &model).unwrap();
+/// let mut decoder = encoder.into_decoder().unwrap();
+/// let decoded = decoder
+///     .decode_iid_symbols(residuals.len(), &model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, residuals);
+/// ```
+///
+/// Encoding symbols with individual parameters, where constructing a model for each symbol
+/// with [`new_closed_form`](Self::new_closed_form) is cheap:
+///
+/// ```
+/// use constriction::stream::{
+///     model::geometric::DefaultTwoSidedGeometric, stack::DefaultAnsCoder, Decode,
+/// };
+///
+/// // Prediction residuals, each with its own (e.g., context dependent) decay rate.
+/// let residuals = [0, -1, 2, 0, 0, 1, -4, 17, 0];
+/// let decays = [0.3, 0.5, 0.5, 0.2, 0.3, 0.6, 0.7, 0.9, 0.4];
+/// let model =
+///     |decay| DefaultTwoSidedGeometric::new_closed_form(-255..=255, decay, 0.0).unwrap();
+///
+/// let mut coder = DefaultAnsCoder::new();
+/// coder
+///     .encode_symbols_reverse(residuals.iter().zip(&decays).map(|(&x, &d)| (x, model(d))))
+///     .unwrap();
+///
+/// let decoded = coder
+///     .decode_symbols(decays.iter().map(|&d| model(d)))
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, residuals);
+/// assert!(coder.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct TwoSidedGeometric<Probability: BitArray, const PRECISION: usize> {
+    min_symbol: i32,
+    max_symbol: i32,
+    decay: f64,
+    offset: f64,
+
+    /// `-log2(decay)`, so that `decay^t = 2^(-t * neg_log2_decay)`.
+    neg_log2_decay: f64,
+
+    /// `offset.floor()`, i.e., the largest integer that is not larger than `offset`.
+    floor_offset: f64,
+
+    /// `decay^(offset - floor_offset)`, i.e., the (unnormalized) probability mass of all
+    /// integers `<= floor_offset`, in units of `1 / (1 - decay)`.
+    left_mass: f64,
+
+    /// `decay^(floor_offset + 1 - offset)`, i.e., the (unnormalized) probability mass of
+    /// all integers `> floor_offset`, in units of `1 / (1 - decay)`.
+    right_mass: f64,
+
+    /// The probability mass that is distributed according to the cumulative distribution
+    /// function, i.e., `2^PRECISION` minus `SLACK` units per symbol.
+    free_weight: f64,
+
+    /// The same fixed point probabilities as a lookup table over the symbols
+    /// `0..support_size` (i.e., shifted by `-min_symbol`), or `None` if the model was
+    /// constructed with [`new_closed_form`](Self::new_closed_form).
+    table: Option<ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>>,
+}
+
+/// Type alias for a [`TwoSidedGeometric`] with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultTwoSidedGeometric = TwoSidedGeometric<u32, 24>;
+
+/// Type alias for a [`TwoSidedGeometric`] that can be used with coders that use `u16`
+/// words.
+///
+/// Note that the support must contain at most `2^11` symbols since every symbol gets two
+/// of the `2^12` available fixed point probability units.
+pub type SmallTwoSidedGeometric = TwoSidedGeometric<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> TwoSidedGeometric<Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+{
+    /// Creates a two-sided geometric entropy model over the symbols in `support`, and
+    /// tabulates its fixed point probabilities.
+    ///
+    /// Returns an error if `decay` is not within the open interval `(0, 1)` (for `decay =
+    /// 1`, the distribution over all integers can't be normalized), if `offset` is infinite
+    /// or NaN, if `support` contains fewer than two symbols, if twice the number of symbols
+    /// in `support` exceeds `1 << PRECISION`, or if `PRECISION` is larger than 32. The
+    /// `offset` may lie outside of `support`.
+    ///
+    /// The run time is linear in the size of the support. See
+    /// [`new_closed_form`](Self::new_closed_form) for a constant time alternative that
+    /// results in the same fixed point probabilities.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(support: RangeInclusive<i32>, decay: f64, offset: f64) -> Result<Self, ()> {
+        let mut model = Self::new_closed_form(support, decay, offset)?;
+        let table = ContiguousCategoricalEntropyModel::from_nonzero_fixed_point_probabilities(
+            (0..model.support_size()).map(|index| {
+                model
+                    .closed_form_left_cumulative_and_probability(index)
+                    .1
+                    .get()
+            }),
+            false,
+        )?;
+        model.table = Some(table);
+        Ok(model)
+    }
+
+    /// Creates a two-sided geometric entropy model over the symbols in `support` without
+    /// tabulating its fixed point probabilities.
+    ///
+    /// Has the same preconditions as [`new`](Self::new) and results in the same fixed point
+    /// probabilities, but it takes constant time and doesn't allocate. In return, encoding
+    /// and decoding is somewhat more expensive since the model evaluates its cumulative
+    /// distribution function on the fly (see [module level documentation](self)).
+    #[allow(clippy::result_unit_err)]
+    pub fn new_closed_form(
+        support: RangeInclusive<i32>,
+        decay: f64,
+        offset: f64,
+    ) -> Result<Self, ()> {
+        if !(decay > 0.0 && decay < 1.0 && offset.is_finite()) {
+            return Err(());
+        }
+        let (min_symbol, max_symbol) = support.into_inner();
+        let support_size = max_symbol as i64 - min_symbol as i64 + 1;
+        if support_size < 2 {
+            return Err(());
+        }
+        let free_weight = free_weight::<PRECISION>(support_size as u64)?;
+
+        let neg_log2_decay = neg_log2(decay);
+        let floor_offset = Float::floor(offset);
+        let fractional_offset = offset - floor_offset;
+
+        Ok(Self {
+            min_symbol,
+            max_symbol,
+            decay,
+            offset,
+            neg_log2_decay,
+            floor_offset,
+            left_mass: exp2_neg(fractional_offset * neg_log2_decay),
+            right_mass: exp2_neg((1.0 - fractional_offset) * neg_log2_decay),
+            free_weight,
+            table: None,
+        })
+    }
+
+    /// Returns the left-sided cumulative in fixed point for the symbol `min_symbol + index`
+    /// with `index` in `1..support_size`.
+    ///
+    /// For `x <= floor_offset + 1`, the (unnormalized) mass of all integers `< x` is
+    /// `decay^(offset + 1 - x)`. For `x > floor_offset + 1`, it is `left_mass` plus the
+    /// mass of the integers `floor_offset + 1..x`, i.e., `right_mass * (1 -
+    /// decay^(x - floor_offset - 1))`. All masses are in units of `1 / (1 - decay)`,
+    /// which cancels in the normalization.
+    #[inline(always)]
+    fn left_cumulative(&self, index: usize) -> Probability {
+        let x = (self.min_symbol as i64 + index as i64) as f64;
+        let distance = x - self.floor_offset - 1.0;
+        let mass = if distance <= 0.0 {
+            exp2_neg((self.offset + 1.0 - x) * self.neg_log2_decay)
+        } else {
+            self.left_mass + self.right_mass * (1.0 - exp2_neg(distance * self.neg_log2_decay))
+        };
+        let cdf = (mass / (self.left_mass + self.right_mass)).min(1.0);
+        ((SLACK * index) as f64 + Float::floor(self.free_weight * cdf)).as_()
+    }
+
+    #[inline(always)]
+    fn closed_form_left_cumulative_and_probability(
+        &self,
+        index: usize,
+    ) -> (Probability, Probability::NonZero) {
+        left_cumulative_and_probability::<_, PRECISION>(index, self.support_size(), |index| {
+            self.left_cumulative(index)
+        })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> TwoSidedGeometric<Probability, PRECISION> {
+    /// Returns the range of symbols to which the model assigns a nonzero probability.
+    pub fn support(&self) -> RangeInclusive<i32> {
+        self.min_symbol..=self.max_symbol
+    }
+
+    /// Returns the factor by which the probability decays per unit distance from the
+    /// `offset`.
+    pub fn decay(&self) -> f64 {
+        self.decay
+    }
+
+    /// Returns the (real valued) position around which the probability mass is centered.
+    pub fn offset(&self) -> f64 {
+        self.offset
+    }
+
+    /// Returns the lookup table with the model's fixed point probabilities over the symbols
+    /// `0..support_size`, i.e., with symbols shifted by `-self.support().start()`, or `None`
+    /// if the model was constructed with [`new_closed_form`](Self::new_closed_form).
+    pub fn as_categorical(
+        &self,
+    ) -> Option<&ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>> {
+        self.table.as_ref()
+    }
+
+    fn support_size(&self) -> usize {
+        (self.max_symbol as i64 - self.min_symbol as i64 + 1) as usize
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for TwoSidedGeometric<Probability, PRECISION>
+{
+    type Symbol = i32;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for TwoSidedGeometric<Probability, PRECISION>
+where
+    f64: AsPrimitive<Probability>,
+{
+    #[inline]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<i32>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let symbol = *symbol.borrow();
+        if symbol < self.min_symbol || symbol > self.max_symbol {
+            return None;
+        }
+        let index = (symbol as i64 - self.min_symbol as i64) as usize;
+        match &self.table {
+            Some(table) => table.left_cumulative_and_probability(index),
+            None => Some(self.closed_form_left_cumulative_and_probability(index)),
+        }
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for TwoSidedGeometric<Probability, PRECISION>
+where
+    Probability: Into<f64>,
+    f64: AsPrimitive<Probability>,
+{
+    #[inline]
+    fn quantile_function(&self, quantile: Probability) -> (i32, Probability, Probability::NonZero) {
+        let (index, left_cumulative, probability) = match &self.table {
+            Some(table) => table.quantile_function(quantile),
+            None => {
+                let support_size = self.support_size();
+
+                // Invert the cumulative distribution function, ignoring the slack. This only
+                // serves as an initial guess, so it doesn't need to be reproducible.
+                let total_weight = self.free_weight + (SLACK * support_size) as f64;
+                let mass = quantile.into() / total_weight * (self.left_mass + self.right_mass);
+                let x = if mass <= self.left_mass {
+                    self.offset + 1.0 - neg_log2(mass) / self.neg_log2_decay
+                } else {
+                    let remaining = 1.0 - (mass - self.left_mass) / self.right_mass;
+                    self.floor_offset + 1.0 + neg_log2(remaining) / self.neg_log2_decay
+                };
+                let guess = (x - self.min_symbol as f64).max(0.0);
+
+                let index =
+                    invert_left_cumulative(quantile, support_size, guess as usize, |index| {
+                        self.left_cumulative(index)
+                    });
+                let (left_cumulative, probability) =
+                    self.closed_form_left_cumulative_and_probability(index);
+                (index, left_cumulative, probability)
+            }
+        };
+        (
+            (self.min_symbol as i64 + index as i64) as i32,
+            left_cumulative,
+            probability,
+        )
+    }
+}
+
+/// Returns the probability mass that remains after reserving `SLACK` units for each of the
+/// `support_size` symbols, or an error if the support is too large or `PRECISION` is too
+/// high for the fixed point arithmetic in this module.
+fn free_weight<const PRECISION: usize>(support_size: u64) -> Result<f64, ()> {
+    if PRECISION > MAX_PRECISION || support_size > (1 << PRECISION) / SLACK as u64 {
+        return Err(());
+    }
+    Ok(((1u64 << PRECISION) - SLACK as u64 * support_size) as f64)
+}
+
+/// Returns the left-sided cumulative and the probability of the symbol at `index`, where
+/// `left_cumulative` must be defined on `1..support_size`.
+#[inline(always)]
+fn left_cumulative_and_probability<Probability: BitArray, const PRECISION: usize>(
+    index: usize,
+    support_size: usize,
+    left_cumulative: impl Fn(usize) -> Probability,
+) -> (Probability, Probability::NonZero) {
+    let left = if index == 0 {
+        Probability::zero()
+    } else {
+        left_cumulative(index)
+    };
+    let right = if index + 1 == support_size {
+        wrapping_pow2(PRECISION)
+    } else {
+        left_cumulative(index + 1)
+    };
+    let probability = right
+        .wrapping_sub(&left)
+        .into_nonzero()
+        .expect("The slack guarantees a nonzero probability for each symbol.");
+    (left, probability)
+}
+
+/// Returns the `index` in `0..support_size` that satisfies `left_cumulative(index) <=
+/// quantile < left_cumulative(index + 1)`, where `left_cumulative` must be defined on
+/// `1..support_size` and nondecreasing.
+///
+/// Starts from `guess` and gallops towards the solution before switching to a binary
+/// search. Thus, the run time is logarithmic in the distance between `guess` and the
+/// solution.
+#[inline(always)]
+fn invert_left_cumulative<Probability: BitArray>(
+    quantile: Probability,
+    support_size: usize,
+    guess: usize,
+    left_cumulative: impl Fn(usize) -> Probability,
+) -> usize {
+    let exceeds =
+        |index: usize| index == support_size || (index != 0 && left_cumulative(index) > quantile);
+
+    let mut lower = guess.min(support_size - 1);
+    let mut upper = lower + 1;
+    let mut step = 1;
+    if exceeds(lower) {
+        while exceeds(lower) {
+            upper = lower;
+            lower = lower.saturating_sub(step);
+            step *= 2;
+        }
+    } else {
+        while !exceeds(upper) {
+            lower = upper;
+            upper = (upper + step).min(support_size);
+            step *= 2;
+        }
+    }
+
+    while upper - lower > 1 {
+        let mid = lower + (upper - lower) / 2;
+        if exceeds(mid) {
+            upper = mid;
+        } else {
+            lower = mid;
+        }
+    }
+    lower
+}
+
+/// Evaluates `2^(-y)` for `y >= 0` using only basic floating point arithmetic, so that the
+/// result is reproducible bit for bit across platforms.
+///
+/// Splits `y = n + f` with integer `n` and `0 <= f < 1`, evaluates `2^(-f) = e^(-f ln 2)`
+/// with a truncated Taylor series, and multiplies by the exact power of two `2^(-n)`.
+/// Returns zero if the result would be subnormal.
+#[inline(always)]
+fn exp2_neg(y: f64) -> f64 {
+    if y.is_nan() || y >= 1022.0 {
+        return 0.0;
+    }
+    let y = y.max(0.0);
+    let n = y as u64;
+    let t = (y - n as f64) * LN_2;
+
+    // Horner scheme for `1 - t (1 - t/2 (1 - t/3 (...)))`. The truncation error is below
+    // `t^19 / 19! < 1e-18` since `t < ln 2`.
+    let mut series = 1.0;
+    for k in (1..=18).rev() {
+        series = 1.0 - series * t / k as f64;
+    }
+    series * f64::from_bits((1023 - n) << 52)
+}
+
+/// Evaluates `-log2(x)` using only basic floating point arithmetic, so that the result is
+/// reproducible bit for bit across platforms.
+///
+/// Splits `x = m * 2^e` with `1/sqrt(2) <= m < sqrt(2)`, and evaluates `ln(m) = 2
+/// atanh(z)` with `z = (m - 1) / (m + 1)` by a truncated series. Returns positive infinity
+/// for `x <= 0` and for NaN.
+fn neg_log2(x: f64) -> f64 {
+    if x.is_nan() || x <= 0.0 {
+        return f64::INFINITY;
+    }
+    let (x, extra_exponent) = if x < f64::MIN_POSITIVE {
+        (x * (1u64 << 54) as f64, 54)
+    } else {
+        (x, 0)
+    };
+
+    let bits = x.to_bits();
+    let mut exponent = ((bits >> 52) & 0x7ff) as i64 - 1023 - extra_exponent;
+    let mut mantissa = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    if mantissa > SQRT_2 {
+        mantissa *= 0.5;
+        exponent += 1;
+    }
+
+    // Since `|z| < 0.172`, the truncation error is below `2 |z|^25 / 25 < 1e-20`.
+    let z = (mantissa - 1.0) / (mantissa + 1.0);
+    let z2 = z * z;
+    let mut series = 0.0;
+    for k in (0..12).rev() {
+        series = series * z2 + 1.0 / (2 * k + 1) as f64;
+    }
+    -(exponent as f64) - 2.0 * z * series / LN_2
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use num::traits::{One, WrappingAdd, WrappingSub, Zero};
+
+    /// Checks that the model is normalized, that each symbol has a nonzero probability,
+    /// and that `quantile_function` inverts `left_cumulative_and_probability`.
+    fn verify<M, const PRECISION: usize>(model: &M, support: impl Iterator<Item = M::Symbol>)
+    where
+        M: EncoderModel<PRECISION> + DecoderModel<PRECISION>,
+        M::Symbol: PartialEq + core::fmt::Debug + Clone,
+    {
+        let mut expected_left = M::Probability::zero();
+        for symbol in support {
+            let (left, probability) = model.left_cumulative_and_probability(&symbol).unwrap();
+            assert_eq!(left, expected_left);
+            let right = left.wrapping_add(&probability.get());
+            for quantile in [left, right.wrapping_sub(&M::Probability::one())] {
+                let (decoded, decoded_left, decoded_probability) =
+                    model.quantile_function(quantile);
+                assert_eq!(decoded, symbol);
+                assert_eq!(decoded_left, left);
+                assert_eq!(decoded_probability, probability);
+            }
+            expected_left = right;
+        }
+        assert_eq!(expected_left, wrapping_pow2(PRECISION));
+    }
+
+    #[test]
+    fn series_expansions() {
+        for &y in &[0.0, 1e-10, 0.3, 0.5, 0.999, 1.0, 3.7, 52.1, 700.25] {
+            let expected = Float::powf(2.0f64, -y);
+            assert!((exp2_neg(y) - expected).abs() <= 4.0 * f64::EPSILON * expected);
+        }
+        assert_eq!(exp2_neg(f64::INFINITY), 0.0);
+        assert_eq!(exp2_neg(2000.0), 0.0);
+
+        for &x in &[1e-310, 1e-100, 0.01, 0.5, 0.7, 0.99, 1.0, 1.5, 3.0, 1e100] {
+            let expected = -Float::log2(x);
+            assert!((neg_log2(x) - expected).abs() <= 4.0 * f64::EPSILON * expected.abs());
+        }
+        assert_eq!(neg_log2(0.0), f64::INFINITY);
+        assert_eq!(neg_log2(1.0), 0.0);
+    }
+
+    #[test]
+    fn consistency() {
+        for &p in &[1e-20, 1e-3, 0.1, 0.5, 0.9, 1.0] {
+            for &max_symbol in &[1, 5, 30, 2047] {
+                let model = SmallGeometric::new_closed_form(p, max_symbol).unwrap();
+                assert_eq!(model.max_symbol(), max_symbol);
+                verify(&model, 0..=max_symbol);
+                assert!(model
+                    .left_cumulative_and_probability(max_symbol + 1)
+                    .is_none());
+            }
+
+            let model = DefaultGeometric::new_closed_form(p, 10_000).unwrap();
+            verify(&model, 0..=10_000);
+            let model = DefaultGeometric::new(p, 10_000).unwrap();
+            verify(&model, 0..=10_000);
+        }
+
+        for &decay in &[1e-5, 0.1, 0.5, 0.9, 0.999] {
+            for &offset in &[-3000.0, -2.5, 0.0, 0.3, 7.9, 1e9] {
+                for support in [-1..=0, -20..=20, 3..=10, -1024..=1023] {
+                    let model =
+                        SmallTwoSidedGeometric::new_closed_form(support.clone(), decay, offset)
+                            .unwrap();
+                    assert_eq!(model.support(), support);
+                    verify(&model, support.clone());
+                    assert!(model
+                        .left_cumulative_and_probability(support.end() + 1)
+                        .is_none());
+                    assert!(model
+                        .left_cumulative_and_probability(support.start() - 1)
+                        .is_none());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn tabulated_matches_closed_form() {
+        let quantiles = (0..1 << 24).step_by(997).chain(Some((1 << 24) - 1));
+
+        for &p in &[1e-3, 0.2, 0.7, 1.0] {
+            let tabulated = DefaultGeometric::new(p, 300).unwrap();
+            let closed_form = DefaultGeometric::new_closed_form(p, 300).unwrap();
+            assert!(tabulated.as_categorical().is_some());
+            assert!(closed_form.as_categorical().is_none());
+            assert!(tabulated.symbol_table().eq(closed_form.symbol_table()));
+            assert_eq!(closed_form.symbol_table().len(), 301);
+            for quantile in quantiles.clone() {
+                assert_eq!(
+                    tabulated.quantile_function(quantile),
+                    closed_form.quantile_function(quantile)
+                );
+            }
+        }
+
+        for &(decay, offset) in &[(0.6, -0.3), (0.05, 2.0), (0.99, 100.5), (0.5, -1e9)] {
+            let tabulated = DefaultTwoSidedGeometric::new(-255..=255, decay, offset).unwrap();
+            let closed_form =
+                DefaultTwoSidedGeometric::new_closed_form(-255..=255, decay, offset).unwrap();
+            for symbol in -256..=256 {
+                assert_eq!(
+                    tabulated.left_cumulative_and_probability(symbol),
+                    closed_form.left_cumulative_and_probability(symbol)
+                );
+            }
+            for quantile in quantiles.clone() {
+                assert_eq!(
+                    tabulated.quantile_function(quantile),
+                    closed_form.quantile_function(quantile)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn matches_pmf() {
+        for &p in &[1e-3, 0.1, 0.5, 0.9, 1.0] {
+            for &max_symbol in &[1, 5, 30, 2000] {
+                let model = DefaultGeometric::new(p, max_symbol).unwrap();
+                assert_eq!(model.max_symbol(), max_symbol);
+
+                // Each symbol gets `SLACK` units up front and loses at most one unit in
+                // rounding.
+                let units = (1u64 << 24) as f64;
+                let slack = (SLACK * (max_symbol + 1)) as f64;
+                let tolerance = |exact: f64| (3.0 + exact * slack) / units + 1e-9;
+                for k in 0..max_symbol {
+                    let exact = p * (1.0 - p).powi(k as i32);
+                    let approx: f64 = model.floating_point_probability(k);
+                    assert!((approx - exact).abs() <= tolerance(exact));
+                }
+                let exact_tail = (1.0 - p).powi(max_symbol as i32);
+                let approx_tail: f64 = model.floating_point_probability(max_symbol);
+                assert!((approx_tail - exact_tail).abs() <= tolerance(exact_tail));
+            }
+        }
+    }
+
+    #[test]
+    fn certain_success() {
+        let model = DefaultGeometric::new(1.0, 10).unwrap();
+        let (_, probability) = model.left_cumulative_and_probability(0).unwrap();
+        assert_eq!(probability.get(), (1 << 24) - 20);
+        for k in 1..=10 {
+            let (_, probability) = model.left_cumulative_and_probability(k).unwrap();
+            assert_eq!(probability.get(), 2);
+        }
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DefaultGeometric::new(0.0, 10).is_err());
+        assert!(DefaultGeometric::new(-0.1, 10).is_err());
+        assert!(DefaultGeometric::new(1.1, 10).is_err());
+        assert!(DefaultGeometric::new(f64::NAN, 10).is_err());
+        assert!(DefaultGeometric::new(0.5, 0).is_err());
+        assert!(DefaultGeometric::new(0.5, usize::MAX).is_err());
+        assert!(DefaultGeometric::new_closed_form(0.5, usize::MAX).is_err());
+        assert!(SmallGeometric::new(0.5, 2047).is_ok());
+        assert!(SmallGeometric::new(0.5, 2048).is_err());
+        assert!(SmallGeometric::new_closed_form(0.5, 2048).is_err());
+        assert!(Geometric::<u64, 40>::new_closed_form(0.5, 10).is_err());
+    }
+
+    #[test]
+    fn two_sided_matches_pmf() {
+        for &decay in &[0.05, 0.5, 0.9] {
+            for &offset in &[-3.0, -0.3, 0.0, 2.5, 7.75, 100.0, -1e9] {
+                let model = DefaultTwoSidedGeometric::new(-20..=20, decay, offset).unwrap();
+                assert_eq!(model.support(), -20..=20);
+                assert!(model.left_cumulative_and_probability(-21).is_none());
+                assert!(model.left_cumulative_and_probability(21).is_none());
+
+                // Normalization of `decay^|x - offset|` over all integers.
+                let fractional_offset = offset - offset.floor();
+                let normalization = (decay.powf(fractional_offset)
+                    + decay.powf(1.0 - fractional_offset))
+                    / (1.0 - decay);
+                let units = (1u64 << 24) as f64;
+                let mut total = 0u64;
+                for x in -20..=20 {
+                    let approx: f64 = model.floating_point_probability(x);
+                    if x != -20 && x != 20 {
+                        // The end points of the support absorb the tails.
+                        let exact = decay.powf((x as f64 - offset).abs()) / normalization;
+                        assert!((approx - exact).abs() <= (3.0 + exact * 82.0) / units + 1e-9);
+                    }
+                    total += model.left_cumulative_and_probability(x).unwrap().1.get() as u64;
+                }
+                assert_eq!(total, 1 << 24);
+            }
+        }
+    }
+
+    #[test]
+    fn two_sided_symmetry() {
+        let probability = |model: &DefaultTwoSidedGeometric, x: i32| {
+            model.left_cumulative_and_probability(x).unwrap().1.get() as i64
+        };
+
+        // An integer offset makes the weights exactly symmetric around the offset. Fixed
+        // point probabilities can still differ by the one unit that breaks ties in rounding.
+        let model = DefaultTwoSidedGeometric::new(-10..=14, 0.7, 2.0).unwrap();
+        for distance in 1..=11 {
+            let difference = probability(&model, 2 - distance) - probability(&model, 2 + distance);
+            assert!(difference.abs() <= 1);
+        }
+
+        // A half-integer offset puts (almost) equal mass on both neighbors.
+        let model = DefaultTwoSidedGeometric::new(-10..=11, 0.7, 0.5).unwrap();
+        assert!((probability(&model, 0) - probability(&model, 1)).abs() <= 1);
+    }
+
+    #[test]
+    fn two_sided_invalid_construction() {
+        assert!(DefaultTwoSidedGeometric::new(-10..=10, 0.0, 0.0).is_err());
+        assert!(DefaultTwoSidedGeometric::new(-10..=10, 1.0, 0.0).is_err());
+        assert!(DefaultTwoSidedGeometric::new(-10..=10, 1.1, 0.0).is_err());
+        assert!(DefaultTwoSidedGeometric::new(-10..=10, f64::NAN, 0.0).is_err());
+        assert!(DefaultTwoSidedGeometric::new(-10..=10, 0.5, f64::INFINITY).is_err());
+        assert!(DefaultTwoSidedGeometric::new(3..=3, 0.5, 0.0).is_err());
+        assert!(DefaultTwoSidedGeometric::new(i32::MIN..=i32::MAX, 0.5, 0.0).is_err());
+        assert!(SmallTwoSidedGeometric::new(-1024..=1023, 0.5, 0.0).is_ok());
+        assert!(SmallTwoSidedGeometric::new(-1024..=1024, 0.5, 0.0).is_err());
+        assert!(SmallTwoSidedGeometric::new_closed_form(-1024..=1024, 0.5, 0.0).is_err());
+    }
+}
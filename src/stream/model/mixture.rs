@@ -0,0 +1,697 @@
+//! Mixtures of entropy models and of continuous distributions
+//!
+//! This module provides two kinds of mixtures:
+//!
+//! - [`Mixture`] combines any number of existing [`EncoderModel`]s over the same symbols
+//!   into a single entropy model by mixing their fixed point cumulative distribution
+//!   functions with fixed point weights. This lets you build mixtures of arbitrary models
+//!   (e.g., of categorical and quantized models) without reimplementing quantization.
+//! - [`GaussianMixture`] and [`LogisticMixture`] are continuous distributions that you can
+//!   quantize with a [`LeakyQuantizer`](super::LeakyQuantizer) like any other continuous
+//!   distribution from the crate [`probability`]. Many learned image and video codecs
+//!   predict, for each latent variable, the parameters of a Gaussian mixture model (GMM)
+//!   rather than of a single Gaussian, and autoregressive image models in the style of
+//!   PixelCNN++ predict the parameters of a mixture of logistic distributions for each
+//!   pixel. These types are only available with the (default) feature `probability`.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::{mixture::GaussianMixture, DefaultLeakyQuantizer},
+//!     queue::DefaultRangeEncoder,
+//!     Decode, Encode,
+//! };
+//!
+//! let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+//! let mixture = GaussianMixture::new(&[0.7, 0.3], &[-3.2, 10.5], &[2.0, 5.5]).unwrap();
+//! let model = quantizer.quantize(mixture);
+//! let symbols = [-3, 12, -1, 7, -5, 20];
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! encoder.encode_iid_symbols(&symbols, &model).unwrap();
+//! let mut decoder = encoder.into_decoder().unwrap();
+//! let decoded = decoder
+//!     .decode_iid_symbols(symbols.len(), &model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, symbols);
+//! ```
+//!
+//! See [`Mixture`] for an example of a mixture of entropy models.
+
+use alloc::vec::Vec;
+use core::{borrow::Borrow, ops::RangeInclusive};
+
+use num::{
+    cast::AsPrimitive,
+    traits::{PrimInt, WrappingSub},
+};
+#[cfg(feature = "probability")]
+use probability::distribution::{Distribution, Gaussian, Inverse, Logistic};
+
+use super::{checked_slack, slack, DecoderModel, EncoderModel, EntropyModel};
+use crate::{BitArray, NonZeroBitArray};
+
+/// A weighted mixture of entropy models over a common range of integer symbols.
+///
+/// The mixture's (fixed point) cumulative distribution function is the weighted average
+/// of the cumulative distribution functions of its components, scaled down slightly so
+/// that every symbol within the `support` gets a nonzero probability (even if no
+/// component assigns it a nonzero probability), analogous to a [`LeakyQuantizer`]. The
+/// weights are fixed point numbers of type `Probability`; they don't have to sum to any
+/// particular value since the mixture normalizes them. Calculating the cumulative
+/// distribution function uses only integer arithmetic and is therefore reproducible bit
+/// for bit across platforms (provided that the components are).
+///
+/// All components must have the same type `M`, and each component with a nonzero weight
+/// must assign a nonzero probability to all symbols in the `support` (components with
+/// zero weight are ignored). Encoding a symbol costs one call to
+/// [`left_cumulative_and_probability`](EncoderModel::left_cumulative_and_probability)
+/// per component, and decoding a symbol performs a binary search over the support, which
+/// costs `O(log(support size))` such calls per component. If you decode many i.i.d.
+/// symbols from the same mixture then it's usually faster to tabulate its probabilities
+/// once in a [`ContiguousCategoricalEntropyModel`].
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::{mixture::DefaultMixture, DefaultLeakyQuantizer},
+///     stack::DefaultAnsCoder,
+///     Decode,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// // A mixture of a narrow and a broad quantized Gaussian with weights 3/4 and 1/4.
+/// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+/// let narrow = quantizer.quantize(Gaussian::new(0.0, 2.0));
+/// let broad = quantizer.quantize(Gaussian::new(10.0, 30.0));
+/// let mixture = DefaultMixture::new(vec![(3, narrow), (1, broad)], -100..=100).unwrap();
+///
+/// let symbols = [1, -2, 0, 57, -83, 3];
+/// let mut coder = DefaultAnsCoder::new();
+/// coder.encode_iid_symbols_reverse(&symbols, &mixture).unwrap();
+/// let decoded = coder
+///     .decode_iid_symbols(symbols.len(), &mixture)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// ```
+///
+/// [`LeakyQuantizer`]: super::LeakyQuantizer
+/// [`ContiguousCategoricalEntropyModel`]: super::ContiguousCategoricalEntropyModel
+#[derive(Debug, Clone)]
+pub struct Mixture<Symbol, Probability, M, const PRECISION: usize> {
+    /// Pairs of (unnormalized) weights and components.
+    components: Vec<(Probability, M)>,
+    min_symbol_inclusive: Symbol,
+    max_symbol_inclusive: Symbol,
+
+    /// `2^PRECISION` minus the number of symbols in the support.
+    free_weight: u128,
+
+    /// Weighted sum of the components' left sided cumulatives at `min_symbol_inclusive`.
+    offset: u128,
+
+    /// Weighted sum of the components' probability masses within the support.
+    normalization: u128,
+}
+
+/// Type alias for a typical [`Mixture`].
+///
+/// See:
+/// - [`Mixture`]
+/// - [discussion of presets](crate::stream#presets)
+pub type DefaultMixture<Symbol, M> = Mixture<Symbol, u32, M, 24>;
+
+/// Type alias for a [`Mixture`] optimized for compatibility with lookup decoder models.
+///
+/// See:
+/// - [`Mixture`]
+/// - [discussion of presets](crate::stream#presets)
+pub type SmallMixture<Symbol, M> = Mixture<Symbol, u16, M, 12>;
+
+impl<Symbol, Probability, M, const PRECISION: usize> Mixture<Symbol, Probability, M, PRECISION>
+where
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub,
+    Probability: BitArray + Into<u128>,
+    u128: AsPrimitive<Probability>,
+    M: EncoderModel<PRECISION, Symbol = Symbol, Probability = Probability>,
+{
+    /// Mixes the models in `components`, where each model comes with a fixed point weight.
+    ///
+    /// Returns an error if `components` is empty or all weights are zero, if `support`
+    /// contains fewer than two symbols or more than `2^PRECISION` symbols, if a component
+    /// with nonzero weight doesn't support the boundaries of `support`, or if the weights
+    /// are so large that intermediate results would overflow a `u128`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(
+        components: Vec<(Probability, M)>,
+        support: RangeInclusive<Symbol>,
+    ) -> Result<Self, ()> {
+        assert!(PRECISION > 0 && PRECISION <= Probability::BITS);
+
+        let (min_symbol_inclusive, max_symbol_inclusive) = support.into_inner();
+        if min_symbol_inclusive >= max_symbol_inclusive
+            || components
+                .iter()
+                .all(|(weight, _)| *weight == Probability::zero())
+        {
+            return Err(());
+        }
+
+        let num_symbols =
+            checked_slack::<Probability, Symbol>(max_symbol_inclusive, min_symbol_inclusive)
+                .ok_or(())?
+                .into()
+                + 1;
+        let free_weight = 1u128
+            .checked_shl(PRECISION as u32)
+            .and_then(|total| total.checked_sub(num_symbols))
+            .ok_or(())?;
+
+        let mut mixture = Self {
+            components,
+            min_symbol_inclusive,
+            max_symbol_inclusive,
+            free_weight,
+            offset: 0,
+            normalization: 1,
+        };
+        let (offset, _) = mixture
+            .weighted_cumulatives(min_symbol_inclusive)
+            .ok_or(())?;
+        let (_, end) = mixture
+            .weighted_cumulatives(max_symbol_inclusive)
+            .ok_or(())?;
+        let normalization = end.checked_sub(offset).ok_or(())?;
+        normalization.checked_mul(free_weight).ok_or(())?;
+        mixture.offset = offset;
+        mixture.normalization = normalization;
+
+        Ok(mixture)
+    }
+
+    /// Returns the pairs of weights and models that were passed to the constructor.
+    pub fn components(&self) -> &[(Probability, M)] {
+        &self.components
+    }
+
+    pub fn support(&self) -> RangeInclusive<Symbol> {
+        self.min_symbol_inclusive..=self.max_symbol_inclusive
+    }
+
+    /// Returns the weighted sums of the components' left and right sided cumulatives at
+    /// `symbol`, or `None` if a component with nonzero weight doesn't support `symbol` (or
+    /// if the sums overflow, which the constructor rules out for valid components).
+    fn weighted_cumulatives(&self, symbol: Symbol) -> Option<(u128, u128)> {
+        let mut left_sum = 0u128;
+        let mut right_sum = 0u128;
+        for (weight, component) in &self.components {
+            if *weight == Probability::zero() {
+                continue;
+            }
+            let weight = (*weight).into();
+            let (left, probability) = component.left_cumulative_and_probability(symbol)?;
+            let left = left.into();
+            let right = left + probability.get().into();
+            left_sum = left_sum.checked_add(weight.checked_mul(left)?)?;
+            right_sum = right_sum.checked_add(weight.checked_mul(right)?)?;
+        }
+        Some((left_sum, right_sum))
+    }
+
+    /// Maps a weighted sum of cumulatives to the range `0..=free_weight` (without slack).
+    ///
+    /// # Panics
+    ///
+    /// If `weighted_cumulative < self.offset`, which can only happen if a component's
+    /// cumulative distribution function is not monotonically nondecreasing.
+    #[inline(always)]
+    fn scale(&self, weighted_cumulative: u128) -> u128 {
+        let relative = weighted_cumulative
+            .checked_sub(self.offset)
+            .expect("Invalid component entropy model.");
+        relative * self.free_weight / self.normalization
+    }
+}
+
+impl<Symbol, Probability, M, const PRECISION: usize> EntropyModel<PRECISION>
+    for Mixture<Symbol, Probability, M, PRECISION>
+where
+    Probability: BitArray,
+{
+    type Symbol = Symbol;
+    type Probability = Probability;
+}
+
+impl<Symbol, Probability, M, const PRECISION: usize> EncoderModel<PRECISION>
+    for Mixture<Symbol, Probability, M, PRECISION>
+where
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub,
+    Probability: BitArray + Into<u128>,
+    u128: AsPrimitive<Probability>,
+    M: EncoderModel<PRECISION, Symbol = Symbol, Probability = Probability>,
+{
+    /// Combines the components' fixed point cumulatives.
+    ///
+    /// Returns `None` if `symbol` is outside of the mixture's support or if a component
+    /// with nonzero weight doesn't support `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// If a component is invalid, i.e., if its cumulative distribution function is not
+    /// monotonically nondecreasing across symbols.
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<Symbol>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        let symbol = *symbol.borrow();
+        if symbol < self.min_symbol_inclusive || symbol > self.max_symbol_inclusive {
+            return None;
+        }
+
+        let (left, right) = self.weighted_cumulatives(symbol)?;
+        let left = self.scale(left);
+        let right = self.scale(right);
+
+        // Every symbol gets one unit of slack, which guarantees a nonzero probability.
+        let slack: Probability = slack(symbol, self.min_symbol_inclusive);
+        let left_sided_cumulative = left.as_() + slack;
+        let probability = (right - left + 1)
+            .as_()
+            .into_nonzero()
+            .expect("Invalid component entropy model.");
+
+        Some((left_sided_cumulative, probability))
+    }
+}
+
+impl<Symbol, Probability, M, const PRECISION: usize> DecoderModel<PRECISION>
+    for Mixture<Symbol, Probability, M, PRECISION>
+where
+    Symbol: PrimInt + AsPrimitive<Probability> + WrappingSub,
+    Probability: BitArray + Into<u128>,
+    u128: AsPrimitive<Probability>,
+    M: EncoderModel<PRECISION, Symbol = Symbol, Probability = Probability>,
+{
+    /// Finds the symbol by binary search over the support.
+    ///
+    /// # Panics
+    ///
+    /// If a component with nonzero weight doesn't support all symbols in the mixture's
+    /// support, or if a component is invalid (see [`left_cumulative_and_probability`]).
+    ///
+    /// [`left_cumulative_and_probability`]: Self::left_cumulative_and_probability
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (Symbol, Probability, Probability::NonZero) {
+        let quantile = quantile.into();
+
+        // Find the largest symbol whose left sided cumulative is `<= quantile`.
+        let mut lower = self.min_symbol_inclusive;
+        let mut upper = self.max_symbol_inclusive;
+        while lower < upper {
+            // Rounds down and can't overflow, even for signed `Symbol`s.
+            let middle = (lower & upper) + ((lower ^ upper) >> 1);
+            let (_, right) = self
+                .weighted_cumulatives(middle)
+                .expect("A component doesn't support all symbols in the mixture's support.");
+            let slack: Probability = slack(middle, self.min_symbol_inclusive);
+            let right_sided_cumulative = self.scale(right) + slack.into() + 1;
+            if right_sided_cumulative <= quantile {
+                lower = middle + Symbol::one();
+            } else {
+                upper = middle;
+            }
+        }
+
+        let (left_sided_cumulative, probability) = self
+            .left_cumulative_and_probability(lower)
+            .expect("A component doesn't support all symbols in the mixture's support.");
+        (lower, left_sided_cumulative, probability)
+    }
+}
+
+#[cfg(feature = "probability")]
+/// A weighted mixture of continuous distributions of type `D`.
+///
+/// You'll usually want to use one of the type aliases [`GaussianMixture`] or
+/// [`LogisticMixture`]. See [module level documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct ContinuousMixture<D> {
+    /// Pairs of normalized weights and components.
+    components: Vec<(f64, D)>,
+}
+
+#[cfg(feature = "probability")]
+/// A weighted mixture of Gaussian distributions.
+///
+/// See [module level documentation](self) for an example.
+pub type GaussianMixture = ContinuousMixture<Gaussian>;
+
+#[cfg(feature = "probability")]
+/// A weighted mixture of logistic distributions, as used by PixelCNN++.
+///
+/// Note that PixelCNN++ parameterizes each component by the logarithm of its scale; pass
+/// the scale itself to [`LogisticMixture::new`].
+pub type LogisticMixture = ContinuousMixture<Logistic>;
+
+#[cfg(feature = "probability")]
+impl<D> ContinuousMixture<D> {
+    /// Checks the parameters and normalizes the weights.
+    ///
+    /// All second parameters (standard deviations or scales) must be positive and finite.
+    fn from_parameters(
+        weights: &[f64],
+        locations: &[f64],
+        scales: &[f64],
+        build_component: impl Fn(f64, f64) -> D,
+    ) -> Result<Self, ()> {
+        if weights.is_empty() || weights.len() != locations.len() || weights.len() != scales.len() {
+            return Err(());
+        }
+
+        let total_weight = weights.iter().sum::<f64>();
+        if !(total_weight > 0.0 && total_weight.is_finite())
+            || weights
+                .iter()
+                .any(|&weight| weight.is_nan() || weight < 0.0)
+            || locations.iter().any(|&location| !location.is_finite())
+            || scales
+                .iter()
+                .any(|&scale| !(scale > 0.0 && scale.is_finite()))
+        {
+            return Err(());
+        }
+
+        let components = weights
+            .iter()
+            .zip(locations)
+            .zip(scales)
+            .map(|((&weight, &location), &scale)| {
+                (weight / total_weight, build_component(location, scale))
+            })
+            .collect();
+        Ok(Self { components })
+    }
+}
+
+#[cfg(feature = "probability")]
+impl ContinuousMixture<Gaussian> {
+    /// Creates a mixture of Gaussian distributions with the given (unnormalized) `weights`,
+    /// `means`, and standard deviations `stds`.
+    ///
+    /// Returns an error if the three slices are empty or have different lengths, if any
+    /// weight is negative or NaN, if the weights sum to zero or to infinity, or if any mean
+    /// is not finite or any standard deviation is not positive and finite.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(weights: &[f64], means: &[f64], stds: &[f64]) -> Result<Self, ()> {
+        Self::from_parameters(weights, means, stds, Gaussian::new)
+    }
+
+    /// Returns an iterator over the triples `(weight, mean, std)` of all components, where
+    /// the weights are normalized so that they sum to one.
+    pub fn components(&self) -> impl ExactSizeIterator<Item = (f64, f64, f64)> + '_ {
+        self.components
+            .iter()
+            .map(|(weight, component)| (*weight, component.mu(), component.sigma()))
+    }
+}
+
+#[cfg(feature = "probability")]
+impl ContinuousMixture<Logistic> {
+    /// Creates a mixture of logistic distributions with the given (unnormalized) `weights`,
+    /// `means`, and `scales`.
+    ///
+    /// Returns an error if the three slices are empty or have different lengths, if any
+    /// weight is negative or NaN, if the weights sum to zero or to infinity, or if any mean
+    /// is not finite or any scale is not positive and finite.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(weights: &[f64], means: &[f64], scales: &[f64]) -> Result<Self, ()> {
+        Self::from_parameters(weights, means, scales, Logistic::new)
+    }
+
+    /// Returns an iterator over the triples `(weight, mean, scale)` of all components,
+    /// where the weights are normalized so that they sum to one.
+    pub fn components(&self) -> impl ExactSizeIterator<Item = (f64, f64, f64)> + '_ {
+        self.components
+            .iter()
+            .map(|(weight, component)| (*weight, component.mu(), component.s()))
+    }
+}
+
+#[cfg(feature = "probability")]
+impl<D: Distribution<Value = f64>> Distribution for ContinuousMixture<D> {
+    type Value = f64;
+
+    fn distribution(&self, x: f64) -> f64 {
+        let cdf = self
+            .components
+            .iter()
+            .map(|(weight, component)| weight * component.distribution(x))
+            .sum::<f64>();
+        // Guard against rounding errors in the normalization of the weights.
+        cdf.min(1.0)
+    }
+}
+
+#[cfg(feature = "probability")]
+impl<D: Distribution<Value = f64> + Inverse> Inverse for ContinuousMixture<D> {
+    /// Approximates the quantile function by bisection.
+    ///
+    /// Since the mixture's CDF is a convex combination of the components' CDFs, the
+    /// quantile lies between the smallest and the largest quantile of any component. A
+    /// [`LeakyQuantizer`](super::LeakyQuantizer) only uses the result as an initial guess
+    /// for the quantized symbol, so we stop once the interval is narrower than half a
+    /// quantization bin.
+    fn inverse(&self, p: f64) -> f64 {
+        let (mut lower, mut upper) = self.components.iter().fold(
+            (f64::INFINITY, f64::NEG_INFINITY),
+            |(lower, upper), (_, component)| {
+                let quantile = component.inverse(p);
+                (lower.min(quantile), upper.max(quantile))
+            },
+        );
+        if !(lower.is_finite() && upper.is_finite()) {
+            // Happens for `p` at (or beyond) zero or one.
+            return if p < 0.5 { lower } else { upper };
+        }
+
+        for _ in 0..64 {
+            if upper - lower <= 0.5 {
+                break;
+            }
+            let middle = 0.5 * (lower + upper);
+            if self.distribution(middle) < p {
+                lower = middle;
+            } else {
+                upper = middle;
+            }
+        }
+        0.5 * (lower + upper)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{geometric::DefaultTwoSidedGeometric, DefaultLeakyQuantizer};
+    use super::*;
+    use crate::stream::{stack::DefaultAnsCoder, Decode};
+
+    use alloc::vec;
+    use core::num::NonZeroU32;
+
+    #[test]
+    fn single_component_matches_gaussian() {
+        let quantizer = DefaultLeakyQuantizer::new(-50..=50);
+        let mixture = quantizer.quantize(GaussianMixture::new(&[2.5], &[3.7], &[4.2]).unwrap());
+        let gaussian = quantizer.quantize(Gaussian::new(3.7, 4.2));
+        for symbol in -50..=50 {
+            assert_eq!(
+                mixture.left_cumulative_and_probability(symbol),
+                gaussian.left_cumulative_and_probability(symbol)
+            );
+        }
+    }
+
+    #[test]
+    fn distribution_and_inverse() {
+        let mixture = GaussianMixture::new(&[1.0, 3.0], &[-10.0, 20.0], &[1.0, 2.0]).unwrap();
+        assert_eq!(
+            mixture.components().collect::<Vec<_>>(),
+            [(0.25, -10.0, 1.0), (0.75, 20.0, 2.0)]
+        );
+        assert!((mixture.distribution(5.0) - 0.25).abs() < 1e-12);
+        assert!((mixture.distribution(20.0) - 0.625).abs() < 1e-12);
+
+        for &p in &[1e-9, 0.1, 0.25, 0.5, 0.625, 0.99] {
+            let x = mixture.inverse(p);
+            assert!(mixture.distribution(x - 0.5) <= p + 1e-12);
+            assert!(mixture.distribution(x + 0.5) >= p - 1e-12);
+        }
+        assert_eq!(mixture.inverse(0.0), f64::NEG_INFINITY);
+        assert_eq!(mixture.inverse(1.0), f64::INFINITY);
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(GaussianMixture::new(&[], &[], &[]).is_err());
+        assert!(GaussianMixture::new(&[1.0, 1.0], &[0.0], &[1.0, 1.0]).is_err());
+        assert!(GaussianMixture::new(&[1.0, -0.5], &[0.0, 1.0], &[1.0, 1.0]).is_err());
+        assert!(GaussianMixture::new(&[0.0, 0.0], &[0.0, 1.0], &[1.0, 1.0]).is_err());
+        assert!(GaussianMixture::new(&[1.0, f64::NAN], &[0.0, 1.0], &[1.0, 1.0]).is_err());
+        assert!(GaussianMixture::new(&[1.0, 1.0], &[0.0, f64::INFINITY], &[1.0, 1.0]).is_err());
+        assert!(GaussianMixture::new(&[1.0, 1.0], &[0.0, 1.0], &[1.0, 0.0]).is_err());
+        assert!(GaussianMixture::new(&[1.0, 0.0], &[0.0, 1.0], &[1.0, 1.0]).is_ok());
+    }
+
+    #[test]
+    fn logistic_mixture() {
+        let quantizer = DefaultLeakyQuantizer::new(-50..=50);
+        let mixture = quantizer.quantize(LogisticMixture::new(&[0.3], &[3.7], &[4.2]).unwrap());
+        let logistic = quantizer.quantize(Logistic::new(3.7, 4.2));
+        for symbol in -50..=50 {
+            assert_eq!(
+                mixture.left_cumulative_and_probability(symbol),
+                logistic.left_cumulative_and_probability(symbol)
+            );
+        }
+
+        let mixture = LogisticMixture::new(&[2.0, 6.0], &[-10.0, 20.0], &[1.0, 2.0]).unwrap();
+        assert_eq!(
+            mixture.components().collect::<Vec<_>>(),
+            [(0.25, -10.0, 1.0), (0.75, 20.0, 2.0)]
+        );
+        assert!((mixture.distribution(20.0) - 0.625).abs() < 1e-8);
+        for &p in &[1e-9, 0.1, 0.5, 0.99] {
+            let x = mixture.inverse(p);
+            assert!(mixture.distribution(x - 0.5) <= p + 1e-12);
+            assert!(mixture.distribution(x + 0.5) >= p - 1e-12);
+        }
+
+        assert!(LogisticMixture::new(&[1.0, 1.0], &[0.0, 1.0], &[1.0, -1.0]).is_err());
+    }
+
+    #[test]
+    fn mixture_of_entropy_models() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let narrow = quantizer.quantize(Gaussian::new(0.0, 2.0));
+        let broad = quantizer.quantize(Gaussian::new(10.0, 30.0));
+        let mixture = DefaultMixture::new(vec![(3, narrow), (1, broad)], -100..=100).unwrap();
+
+        // The cumulatives are contiguous, cover the full range, and follow the weighted
+        // average of the components' probabilities.
+        let mut expected_left = 0u32;
+        for symbol in -100..=100 {
+            let (left, probability) = mixture.left_cumulative_and_probability(symbol).unwrap();
+            assert_eq!(left, expected_left);
+            expected_left += probability.get();
+
+            let expected = 0.75
+                * narrow
+                    .left_cumulative_and_probability(symbol)
+                    .unwrap()
+                    .1
+                    .get() as f64
+                + 0.25
+                    * broad
+                        .left_cumulative_and_probability(symbol)
+                        .unwrap()
+                        .1
+                        .get() as f64;
+            assert!((probability.get() as f64 - expected).abs() <= 2.0 + 1e-4 * expected);
+
+            assert_eq!(mixture.quantile_function(left), (symbol, left, probability));
+            assert_eq!(
+                mixture.quantile_function(left + probability.get() - 1),
+                (symbol, left, probability)
+            );
+        }
+        assert_eq!(expected_left, 1 << 24);
+        assert_eq!(mixture.left_cumulative_and_probability(-101), None);
+        assert_eq!(mixture.left_cumulative_and_probability(101), None);
+
+        // Components with zero weight are ignored.
+        let narrow_only = DefaultMixture::new(vec![(5, narrow), (0, broad)], -100..=100).unwrap();
+        for symbol in -100..=100 {
+            let (_, probability) = narrow_only.left_cumulative_and_probability(symbol).unwrap();
+            let (_, expected) = narrow.left_cumulative_and_probability(symbol).unwrap();
+            let expected = expected.get() as f64;
+            assert!((probability.get() as f64 - expected).abs() <= 2.0 + 1e-4 * expected);
+        }
+
+        let symbols = (0..100).map(|i| (i * 37) % 201 - 100).collect::<Vec<i32>>();
+        let mut coder = DefaultAnsCoder::new();
+        coder
+            .encode_iid_symbols_reverse(&symbols, &mixture)
+            .unwrap();
+        let decoded = coder
+            .decode_iid_symbols(symbols.len(), &mixture)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(coder.is_empty());
+    }
+
+    #[test]
+    fn mixture_of_categorical_models() {
+        let left = DefaultTwoSidedGeometric::new(-20..=20, 0.5, -8.0).unwrap();
+        let right = DefaultTwoSidedGeometric::new(-20..=20, 0.7, 6.0).unwrap();
+        let mixture = DefaultMixture::new(vec![(1, &left), (1, &right)], -20..=20).unwrap();
+        assert_eq!(mixture.support(), -20..=20);
+        assert_eq!(mixture.components().len(), 2);
+
+        let mut expected_left = 0u32;
+        for symbol in -20..=20 {
+            let (left, probability) = mixture.left_cumulative_and_probability(symbol).unwrap();
+            assert_eq!(left, expected_left);
+            expected_left += probability.get();
+            assert_eq!(mixture.quantile_function(left), (symbol, left, probability));
+        }
+        assert_eq!(expected_left, 1 << 24);
+
+        // The mixture may have a smaller support than its components.
+        let truncated = DefaultMixture::new(vec![(1, &left), (1, &right)], -5..=5).unwrap();
+        assert_eq!(truncated.left_cumulative_and_probability(-6), None);
+        assert_eq!(truncated.quantile_function((1 << 24) - 1).0, 5,);
+    }
+
+    #[test]
+    fn mixture_invalid_construction() {
+        let quantizer = DefaultLeakyQuantizer::new(-10..=10);
+        let model = quantizer.quantize(Gaussian::new(0.0, 2.0));
+        let mut no_components = vec![(1, model)];
+        no_components.clear();
+        assert!(DefaultMixture::new(no_components, -10..=10).is_err());
+        assert!(DefaultMixture::new(vec![(0, model), (0, model)], -10..=10).is_err());
+        assert!(DefaultMixture::new(vec![(1, model)], 3..=3).is_err());
+        assert!(DefaultMixture::new(vec![(1, model)], -11..=10).is_err());
+        assert!(DefaultMixture::new(vec![(1, model)], -10..=11).is_err());
+        assert!(DefaultMixture::new(vec![(1, model)], -10..=10).is_ok());
+        assert!(DefaultMixture::new(vec![(u32::MAX, model), (u32::MAX, model)], -10..=10).is_ok());
+
+        // A component that supports all `i64`s, so that only the size of the mixture's
+        // support can make the constructor fail.
+        #[derive(Clone, Copy)]
+        struct Step;
+        impl EntropyModel<24> for Step {
+            type Symbol = i64;
+            type Probability = u32;
+        }
+        impl EncoderModel<24> for Step {
+            fn left_cumulative_and_probability(
+                &self,
+                symbol: impl Borrow<i64>,
+            ) -> Option<(u32, NonZeroU32)> {
+                let left = if *symbol.borrow() <= 0 { 0 } else { 1 << 23 };
+                Some((left, NonZeroU32::new(1 << 23).unwrap()))
+            }
+        }
+        assert!(DefaultMixture::new(vec![(1, Step)], 0..=(1 << 20)).is_ok());
+        assert!(DefaultMixture::new(vec![(1, Step)], 0..=(1 << 32) + 5).is_err());
+        assert!(DefaultMixture::new(vec![(1, Step)], i64::MIN..=i64::MAX).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! A negative binomial entropy model for overdispersed counts
+//!
+//! Count data in practice often has a larger variance than a [`Poisson`](super::poisson)
+//! distribution with the same mean allows for, e.g., because the event rate itself varies
+//! from sample to sample. The negative binomial distribution with parameters `r > 0` and
+//! `p ∈ (0, 1]` is the standard model for such *overdispersed* counts. It has mean
+//! `r (1 - p) / p` and variance `r (1 - p) / p^2`, i.e., its variance exceeds its mean by a
+//! factor of `1 / p`. For integer `r`, it models the number of failures before the `r`-th
+//! success in independent trials that each succeed with probability `p`, and for `r = 1`
+//! it reduces to a [`Geometric`](super::geometric::Geometric) distribution.
+//!
+//! Like the [`poisson`](super::poisson) and [`geometric`](super::geometric) models, the
+//! [`NegativeBinomial`] model in this module takes a `max_symbol` and assigns it the entire
+//! tail mass `P(X >= max_symbol)`, so you can use `max_symbol` as an escape symbol (see
+//! the example in the [`poisson`](super::poisson) module).
+//!
+//! The model evaluates its probability mass function in the log domain (so that it
+//! neither underflows nor overflows for large `r`) and rounds the result to fixed point
+//! with [`RoundingPolicy::LargestRemainder`]. Since this relies on the platform's
+//! implementation of logarithms and exponentials, the fixed point probabilities may, in
+//! rare cases, differ in their last bit across platforms.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::negative_binomial::DefaultNegativeBinomial, queue::DefaultRangeEncoder, Decode,
+//!     Encode,
+//! };
+//!
+//! // Mean 6.0 and variance 18.0, i.e., three times as large as for a Poisson distribution.
+//! let model = DefaultNegativeBinomial::new(3.0, 1.0 / 3.0, 100).unwrap();
+//! let counts = [4, 0, 13, 6, 2, 25, 100, 7];
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! encoder.encode_iid_symbols(&counts, &model).unwrap();
+//! let mut decoder = encoder.into_decoder().unwrap();
+//! let decoded = decoder
+//!     .decode_iid_symbols(counts.len(), &model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, counts);
+//! ```
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use num::{cast::AsPrimitive, Float};
+
+use super::{
+    ContiguousCategoricalEntropyModel, ContiguousSymbolTable, DecoderModel, EncoderModel,
+    EntropyModel, IterableEntropyModel, RoundingPolicy, SymbolTableIter,
+};
+use crate::BitArray;
+
+/// An entropy model for overdispersed counts.
+///
+/// The support is `0..=max_symbol`, where `max_symbol` represents all counts that are
+/// larger or equal to it (see [module level documentation](self) for details and an
+/// example). Like all entropy models in `constriction`, a `NegativeBinomial` is leaky,
+/// i.e., it assigns a nonzero probability to every symbol in its support.
+#[derive(Debug, Clone)]
+pub struct NegativeBinomial<Probability: BitArray, const PRECISION: usize> {
+    r: f64,
+    p: f64,
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+/// Type alias for a [`NegativeBinomial`] with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultNegativeBinomial = NegativeBinomial<u32, 24>;
+
+/// Type alias for a [`NegativeBinomial`] that can be used with coders that use `u16` words.
+///
+/// Note that the support `0..=max_symbol` has to fit into the `2^12` available fixed point
+/// probability units, i.e., `max_symbol` must be smaller than `4096`.
+pub type SmallNegativeBinomial = NegativeBinomial<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> NegativeBinomial<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Creates a negative binomial entropy model with parameters `r` and `p` over the
+    /// symbols `0..=max_symbol`.
+    ///
+    /// Returns an error if `r` is not positive and finite, if `p` is not within the
+    /// half-open interval `(0, 1]`, if `max_symbol` is zero (a model with a single symbol
+    /// can't be represented), or if `max_symbol + 1` exceeds `1 << PRECISION` (since every
+    /// symbol needs a nonzero probability).
+    ///
+    /// The run time is linear in `max_symbol`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(r: f64, p: f64, max_symbol: usize) -> Result<Self, ()> {
+        if !(r > 0.0 && r.is_finite() && p > 0.0 && p <= 1.0) || max_symbol == 0 {
+            return Err(());
+        }
+        if PRECISION < usize::BITS as usize && max_symbol >= 1 << PRECISION {
+            // Check before allocating the table for the probability mass function.
+            return Err(());
+        }
+
+        let model =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+                &pmf(r, p, max_symbol),
+                RoundingPolicy::LargestRemainder,
+            )?;
+        Ok(Self { r, p, model })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> NegativeBinomial<Probability, PRECISION> {
+    /// Returns the parameter `r` (the number of successes for integer `r`).
+    pub fn r(&self) -> f64 {
+        self.r
+    }
+
+    /// Returns the success probability `p` of each trial.
+    pub fn p(&self) -> f64 {
+        self.p
+    }
+
+    /// Returns the largest symbol in the support, which represents all larger counts too.
+    pub fn max_symbol(&self) -> usize {
+        self.model.support_size() - 1
+    }
+
+    /// Returns the underlying categorical entropy model over the symbols `0..=max_symbol`.
+    pub fn as_categorical(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.model
+    }
+}
+
+/// Evaluates the probability mass function of a negative binomial distribution, with the
+/// mass of all symbols `>= max_symbol` accumulated at `max_symbol`.
+///
+/// Accumulates `log P(k)` with the ratio `P(k + 1) / P(k) = (k + r) (1 - p) / (k + 1)`,
+/// starting from `log P(0) = r log p`. Unlike the weights of the [`poisson`](super::poisson)
+/// model, these are normalized, so the tail mass is simply the complement of the head.
+/// Probabilities that underflow to zero are fine since the leaky quantization assigns them
+/// the smallest representable probability anyway.
+fn pmf(r: f64, p: f64, max_symbol: usize) -> Vec<f64> {
+    let log_q = Float::ln_1p(-p);
+    let mut probabilities = Vec::with_capacity(max_symbol + 1);
+    let mut log_probability = r * Float::ln(p);
+    let mut head = 0.0;
+    for k in 0..max_symbol {
+        let probability = Float::exp(log_probability);
+        probabilities.push(probability);
+        head += probability;
+        log_probability += Float::ln((k as f64 + r) / (k + 1) as f64) + log_q;
+    }
+
+    probabilities.push((1.0 - head).max(0.0));
+    probabilities
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for NegativeBinomial<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for NegativeBinomial<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        self.model.left_cumulative_and_probability(symbol)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for NegativeBinomial<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (usize, Probability, Probability::NonZero) {
+        self.model.quantile_function(quantile)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for NegativeBinomial<Probability, PRECISION>
+{
+    type Iter = SymbolTableIter<usize, Probability, ContiguousSymbolTable<&'m [Probability]>>;
+
+    #[inline(always)]
+    fn symbol_table(&'m self) -> Self::Iter {
+        self.model.symbol_table()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::geometric::DefaultGeometric, *};
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn matches_pmf() {
+        for &r in &[0.3, 1.0, 2.5, 10.0, 1000.0] {
+            for &p in &[0.01, 0.2, 0.5, 0.9, 1.0] {
+                for &max_symbol in &[1, 5, 30, 2000] {
+                    let model = DefaultNegativeBinomial::new(r, p, max_symbol).unwrap();
+                    assert_eq!(model.max_symbol(), max_symbol);
+
+                    let mut total = 0u64;
+                    for (_, left_cumulative, probability) in model.symbol_table() {
+                        assert_eq!(left_cumulative as u64, total);
+                        total += probability.get() as u64;
+                    }
+                    assert_eq!(total, 1 << 24);
+                    assert!(model
+                        .left_cumulative_and_probability(max_symbol + 1)
+                        .is_none());
+
+                    // Each symbol gets one unit up front and at most one unit from rounding.
+                    let units = (1u64 << 24) as f64;
+                    let tolerance =
+                        |exact: f64| (2.0 + exact * (max_symbol + 1) as f64) / units + 1e-9;
+                    let mut exact = Float::powf(p, r);
+                    let mut exact_tail = 1.0;
+                    for k in 0..max_symbol {
+                        exact_tail -= exact;
+                        let approx: f64 = model.floating_point_probability(k);
+                        assert!((approx - exact).abs() <= tolerance(exact));
+                        exact *= (k as f64 + r) * (1.0 - p) / (k + 1) as f64;
+                    }
+                    let approx_tail: f64 = model.floating_point_probability(max_symbol);
+                    assert!((approx_tail - exact_tail.max(0.0)).abs() <= tolerance(exact_tail));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn reduces_to_geometric() {
+        for &p in &[0.05, 0.3, 0.8] {
+            let negative_binomial = DefaultNegativeBinomial::new(1.0, p, 40).unwrap();
+            let geometric = DefaultGeometric::new(p, 40).unwrap();
+            for ((_, _, a), (_, _, b)) in negative_binomial
+                .symbol_table()
+                .zip(geometric.symbol_table())
+            {
+                // `Geometric` reserves two instead of one fixed point units per symbol, so it
+                // has 41 fewer units to distribute in proportion to the probabilities.
+                let a = a.get() as f64;
+                let b = b.get() as f64;
+                assert!((a - b).abs() <= 3.0 + 41.0 * a / (1u64 << 24) as f64);
+            }
+        }
+    }
+
+    #[test]
+    fn extreme_parameters() {
+        // Certain success: all mass on zero.
+        let model = DefaultNegativeBinomial::new(4.0, 1.0, 10).unwrap();
+        let (_, probability) = model.left_cumulative_and_probability(0).unwrap();
+        assert_eq!(probability.get(), (1 << 24) - 10);
+
+        // `p^r` underflows in the linear domain, but almost all mass is in the tail anyway.
+        let model = DefaultNegativeBinomial::new(1e4, 0.01, 100).unwrap();
+        let (_, probability) = model.left_cumulative_and_probability(100).unwrap();
+        assert_eq!(probability.get(), (1 << 24) - 100);
+
+        // Large `r` with the mode inside the support.
+        let model = DefaultNegativeBinomial::new(1e4, 0.9, 4000).unwrap();
+        let mode = model
+            .symbol_table()
+            .max_by_key(|(_, _, probability)| probability.get())
+            .unwrap()
+            .0;
+        assert!((1100..=1112).contains(&mode));
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DefaultNegativeBinomial::new(0.0, 0.5, 10).is_err());
+        assert!(DefaultNegativeBinomial::new(-1.0, 0.5, 10).is_err());
+        assert!(DefaultNegativeBinomial::new(f64::INFINITY, 0.5, 10).is_err());
+        assert!(DefaultNegativeBinomial::new(f64::NAN, 0.5, 10).is_err());
+        assert!(DefaultNegativeBinomial::new(2.0, 0.0, 10).is_err());
+        assert!(DefaultNegativeBinomial::new(2.0, 1.1, 10).is_err());
+        assert!(DefaultNegativeBinomial::new(2.0, f64::NAN, 10).is_err());
+        assert!(DefaultNegativeBinomial::new(2.0, 0.5, 0).is_err());
+        assert!(DefaultNegativeBinomial::new(2.0, 0.5, usize::MAX).is_err());
+        assert!(SmallNegativeBinomial::new(2.0, 0.5, 4095).is_ok());
+        assert!(SmallNegativeBinomial::new(2.0, 0.5, 4096).is_err());
+    }
+}
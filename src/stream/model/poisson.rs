@@ -0,0 +1,318 @@
+//! A Poisson entropy model whose unbounded tail is folded into a maximum symbol
+//!
+//! A Poisson distribution has support on all nonnegative integers, but an entropy model in
+//! `constriction` has to assign a nonzero fixed point probability to each symbol in its
+//! support, so its support has to be finite. The [`Poisson`] model in this module therefore
+//! takes a `max_symbol` and assigns it the entire tail mass `P(X >= max_symbol)`. You can use
+//! `max_symbol` in one of two ways:
+//!
+//! - choose it large enough that larger counts can't occur in your application, and encode
+//!   counts directly; or
+//! - treat it as an *escape symbol*: encode `min(count, max_symbol)` with the `Poisson`
+//!   model and, if `count >= max_symbol`, encode the excess `count - max_symbol` with some
+//!   other entropy model (see example below).
+//!
+//! Like the [`binomial`](super::binomial) model, a `Poisson` model evaluates its
+//! probability mass function with a recurrence that uses only multiplications, divisions,
+//! and additions, and it rounds the result to fixed point with
+//! [`RoundingPolicy::LargestRemainder`]. Thus, its fixed point probabilities are a
+//! deterministic function of `lambda` and `max_symbol` on all platforms.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::{poisson::DefaultPoisson, UniformModel},
+//!     queue::DefaultRangeEncoder,
+//!     Decode, Encode,
+//! };
+//!
+//! const MAX_SYMBOL: usize = 20;
+//! let model = DefaultPoisson::new(3.5, MAX_SYMBOL).unwrap();
+//! let excess_model = UniformModel::<u32, 24>::new(1 << 16);
+//! let counts = [3, 5, 0, 2, 4, 1000, 7, 20];
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! for &count in &counts {
+//!     encoder.encode_symbol(count.min(MAX_SYMBOL), &model).unwrap();
+//!     if count >= MAX_SYMBOL {
+//!         encoder.encode_symbol((count - MAX_SYMBOL) as u32, excess_model).unwrap();
+//!     }
+//! }
+//!
+//! let mut decoder = encoder.into_decoder().unwrap();
+//! for &count in &counts {
+//!     let mut decoded = decoder.decode_symbol(&model).unwrap();
+//!     if decoded == MAX_SYMBOL {
+//!         decoded += decoder.decode_symbol(excess_model).unwrap() as usize;
+//!     }
+//!     assert_eq!(decoded, count);
+//! }
+//! ```
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    ContiguousCategoricalEntropyModel, ContiguousSymbolTable, DecoderModel, EncoderModel,
+    EntropyModel, IterableEntropyModel, RoundingPolicy, SymbolTableIter,
+};
+use crate::BitArray;
+
+/// An entropy model for counts of events that occur independently at rate `lambda`.
+///
+/// The support is `0..=max_symbol`, where `max_symbol` represents all counts that are
+/// larger or equal to it (see [module level documentation](self) for details and an
+/// example). Like all entropy models in `constriction`, a `Poisson` is leaky, i.e., it
+/// assigns a nonzero probability to every symbol in its support.
+#[derive(Debug, Clone)]
+pub struct Poisson<Probability: BitArray, const PRECISION: usize> {
+    lambda: f64,
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+/// Type alias for a [`Poisson`] with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultPoisson = Poisson<u32, 24>;
+
+/// Type alias for a [`Poisson`] that can be used with coders that use `u16` words.
+///
+/// Note that the support `0..=max_symbol` has to fit into the `2^12` available fixed point
+/// probability units, i.e., `max_symbol` must be smaller than `4096`.
+pub type SmallPoisson = Poisson<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> Poisson<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Creates a Poisson entropy model with rate `lambda` over the symbols `0..=max_symbol`.
+    ///
+    /// Returns an error if `lambda` is negative or NaN, if `lambda` is so large that the
+    /// mode (and the symbols around it) can't be represented as a `usize` (i.e., if it is
+    /// at least `usize::MAX / 2`, which also covers infinity), if `max_symbol` is zero (a
+    /// model with a single symbol can't be represented), or if `max_symbol + 1` exceeds `1
+    /// << PRECISION` (since every symbol needs a nonzero probability).
+    ///
+    /// The run time is linear in `max_symbol` and in the square root of `lambda`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(lambda: f64, max_symbol: usize) -> Result<Self, ()> {
+        if !(0.0..(usize::MAX / 2) as f64).contains(&lambda) || max_symbol == 0 {
+            return Err(());
+        }
+        if PRECISION < usize::BITS as usize && max_symbol >= 1 << PRECISION {
+            // Check before allocating the table for the probability mass function.
+            return Err(());
+        }
+
+        let model =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+                &unnormalized_pmf(lambda, max_symbol),
+                RoundingPolicy::LargestRemainder,
+            )?;
+        Ok(Self { lambda, model })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Poisson<Probability, PRECISION> {
+    /// Returns the rate parameter, i.e., the mean of the Poisson distribution before folding
+    /// its tail into `max_symbol`.
+    pub fn lambda(&self) -> f64 {
+        self.lambda
+    }
+
+    /// Returns the largest symbol in the support, which represents all larger counts too.
+    pub fn max_symbol(&self) -> usize {
+        self.model.support_size() - 1
+    }
+
+    /// Returns the underlying categorical entropy model over the symbols `0..=max_symbol`.
+    pub fn as_categorical(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.model
+    }
+}
+
+/// Evaluates the probability mass function of a Poisson distribution up to normalization,
+/// with the mass of all symbols `>= max_symbol` accumulated at `max_symbol`.
+///
+/// Starts from weight `1` at the mode and walks outwards with the ratios of successive
+/// probabilities. Walking down stops at zero or once the weights drop below `f64::EPSILON`
+/// (i.e., below the rounding error of the weight at the mode), and walking up stops once
+/// the weights no longer change the accumulated tail mass. Since the weights decay like a
+/// Gaussian with standard deviation `sqrt(lambda)` away from the mode, both walks take
+/// `O(sqrt(lambda))` steps outside of the support.
+///
+/// The caller has to ensure that `lambda < usize::MAX / 2`, so that `k` can't overflow.
+fn unnormalized_pmf(lambda: f64, max_symbol: usize) -> Vec<f64> {
+    let mode = lambda as usize;
+
+    let mut weights = Vec::with_capacity(max_symbol + 1);
+    weights.resize(max_symbol + 1, 0.0);
+    let mut tail = 0.0;
+
+    let mut weight = 1.0;
+    let mut k = mode;
+    loop {
+        if k < max_symbol {
+            weights[k] = weight;
+        } else {
+            tail += weight;
+        }
+        if k == 0 || weight < f64::EPSILON {
+            break;
+        }
+        weight *= k as f64 / lambda;
+        k -= 1;
+    }
+
+    let mut weight = 1.0;
+    let mut k = mode;
+    loop {
+        weight *= lambda / (k + 1) as f64;
+        k += 1;
+        if k < max_symbol {
+            weights[k] = weight;
+        } else {
+            let new_tail = tail + weight;
+            if new_tail == tail && k as f64 > lambda {
+                break;
+            }
+            tail = new_tail;
+        }
+    }
+
+    weights[max_symbol] = tail;
+    weights
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Poisson<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Poisson<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        self.model.left_cumulative_and_probability(symbol)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Poisson<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (usize, Probability, Probability::NonZero) {
+        self.model.quantile_function(quantile)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for Poisson<Probability, PRECISION>
+{
+    type Iter = SymbolTableIter<usize, Probability, ContiguousSymbolTable<&'m [Probability]>>;
+
+    #[inline(always)]
+    fn symbol_table(&'m self) -> Self::Iter {
+        self.model.symbol_table()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn matches_pmf() {
+        for &lambda in &[1e-3, 0.5, 1.0, 3.7, 20.0, 1000.0] {
+            for &max_symbol in &[1, 5, 30, 2000] {
+                let model = DefaultPoisson::new(lambda, max_symbol).unwrap();
+                assert_eq!(model.max_symbol(), max_symbol);
+
+                let mut total = 0u64;
+                for (_, left_cumulative, probability) in model.symbol_table() {
+                    assert_eq!(left_cumulative as u64, total);
+                    total += probability.get() as u64;
+                }
+                assert_eq!(total, 1 << 24);
+                assert!(model
+                    .left_cumulative_and_probability(max_symbol + 1)
+                    .is_none());
+
+                // Each symbol gets one unit up front and at most one unit from rounding.
+                let units = (1u64 << 24) as f64;
+                let tolerance = |exact: f64| (2.0 + exact * (max_symbol + 1) as f64) / units + 1e-9;
+                let mut log_factorial = 0.0;
+                let mut exact_tail = 1.0;
+                for k in 0..max_symbol {
+                    if k != 0 {
+                        log_factorial += (k as f64).ln();
+                    }
+                    let exact = (k as f64 * lambda.ln() - lambda - log_factorial).exp();
+                    exact_tail -= exact;
+                    let approx: f64 = model.floating_point_probability(k);
+                    assert!((approx - exact).abs() <= tolerance(exact));
+                }
+                let approx_tail: f64 = model.floating_point_probability(max_symbol);
+                assert!((approx_tail - exact_tail).abs() <= tolerance(exact_tail) + 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn degenerate_and_huge_rates() {
+        let model = DefaultPoisson::new(0.0, 10).unwrap();
+        let (_, probability) = model.left_cumulative_and_probability(0).unwrap();
+        assert_eq!(probability.get(), (1 << 24) - 10);
+
+        // Almost all mass ends up in the tail; walking down from the mode terminates early.
+        let model = DefaultPoisson::new(1e6, 100).unwrap();
+        let (_, probability) = model.left_cumulative_and_probability(100).unwrap();
+        assert_eq!(probability.get(), (1 << 24) - 100);
+
+        // The walks only cover a few standard deviations around the mode, so even huge rates
+        // are cheap (this used to take time linear in `lambda`).
+        for &lambda in &[1e10, 1e12] {
+            let model = DefaultPoisson::new(lambda, 100).unwrap();
+            let (_, probability) = model.left_cumulative_and_probability(100).unwrap();
+            assert_eq!(probability.get(), (1 << 24) - 100);
+        }
+
+        // The mode has to fit into a `usize` with room to spare.
+        assert!(DefaultPoisson::new((usize::MAX / 2) as f64, 100).is_err());
+        assert!(DefaultPoisson::new(1e30, 100).is_err());
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DefaultPoisson::new(-0.1, 10).is_err());
+        assert!(DefaultPoisson::new(f64::NAN, 10).is_err());
+        assert!(DefaultPoisson::new(f64::INFINITY, 10).is_err());
+        assert!(DefaultPoisson::new(1.0, 0).is_err());
+        assert!(DefaultPoisson::new(1.0, usize::MAX).is_err());
+        assert!(SmallPoisson::new(1.0, 4095).is_ok());
+        assert!(SmallPoisson::new(1.0, 4096).is_err());
+    }
+}
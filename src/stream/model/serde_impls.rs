@@ -0,0 +1,456 @@
+//! Implementations of `serde::Serialize` and `serde::Deserialize` for entropy models.
+//!
+//! All models are serialized as their fixed point probabilities (and symbols, if the model
+//! supports arbitrary symbols) together with their `PRECISION`, so that deserialization
+//! reproduces the exact same model, and so that the serialized format doesn't depend on
+//! the internal representation. In particular, the serialized representations of a
+//! [`NonContiguousCategoricalEncoderModel`], a [`NonContiguousCategoricalDecoderModel`], a
+//! non-contiguous [`LookupDecoderModel`], and a [`LeakilyQuantizedDistribution`] are
+//! interchangeable, as are the ones of a [`ContiguousCategoricalEntropyModel`] and a
+//! contiguous `LookupDecoderModel`. Deserialization goes through the same validating
+//! constructors as constructing a model from fixed point probabilities, so invalid input
+//! results in an error rather than in a corrupt model.
+
+use alloc::{boxed::Box, vec::Vec};
+use core::hash::Hash;
+
+use num::{cast::AsPrimitive, traits::WrappingSub, Float, PrimInt};
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use super::{
+    ContiguousCategoricalEntropyModel, ContiguousSymbolTable, Distribution, IterableEntropyModel,
+    LeakilyQuantizedDistribution, LookupDecoderModel, NonContiguousCategoricalDecoderModel,
+    NonContiguousCategoricalEncoderModel, NonContiguousSymbolTable,
+};
+use crate::{BitArray, NonZeroBitArray};
+
+/// Serialized representation of models over a contiguous range of symbols `0..n`.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "ContiguousCategorical")]
+struct ContiguousTable<Probability> {
+    precision: usize,
+    probabilities: Vec<Probability>,
+}
+
+/// Serialized representation of models over arbitrary symbols, in order of increasing
+/// left-sided cumulative.
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "NonContiguousCategorical")]
+struct NonContiguousTable<Symbol, Probability> {
+    precision: usize,
+    symbols: Vec<Symbol>,
+    probabilities: Vec<Probability>,
+}
+
+/// Checks the parts of a serialized table that the validating constructors don't check.
+fn check_table<E: Error, const PRECISION: usize>(
+    precision: usize,
+    num_symbols: Option<usize>,
+    num_probabilities: usize,
+) -> Result<(), E> {
+    if precision != PRECISION {
+        Err(E::custom(format_args!(
+            "serialized entropy model has precision {} but {} was expected",
+            precision, PRECISION
+        )))
+    } else if matches!(num_symbols, Some(num_symbols) if num_symbols != num_probabilities) {
+        Err(E::custom(
+            "`symbols` and `probabilities` have different lengths",
+        ))
+    } else if num_probabilities < 2 {
+        // Degenerate entropy models that put all probability mass on a single symbol are
+        // not supported (see `EncoderModel::left_cumulative_and_probability`).
+        Err(E::custom("entropy models must have at least two symbols"))
+    } else {
+        Ok(())
+    }
+}
+
+fn invalid_probabilities<E: Error>() -> E {
+    E::custom("fixed point probabilities are not all nonzero or don't sum to one")
+}
+
+fn contiguous_probabilities<Probability: BitArray>(cdf: &[Probability]) -> Vec<Probability> {
+    cdf.windows(2)
+        .map(|pair| pair[1].wrapping_sub(&pair[0]))
+        .collect()
+}
+
+fn non_contiguous_table<Symbol: Clone, Probability: BitArray, const PRECISION: usize>(
+    cdf: &[(Probability, Symbol)],
+) -> NonContiguousTable<Symbol, Probability> {
+    let (symbols, probabilities) = cdf
+        .windows(2)
+        .map(|pair| (pair[0].1.clone(), pair[1].0.wrapping_sub(&pair[0].0)))
+        .unzip();
+    NonContiguousTable {
+        precision: PRECISION,
+        symbols,
+        probabilities,
+    }
+}
+
+impl<Probability, Table, const PRECISION: usize> Serialize
+    for ContiguousCategoricalEntropyModel<Probability, Table, PRECISION>
+where
+    Probability: BitArray + Serialize,
+    Table: AsRef<[Probability]>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ContiguousTable {
+            precision: PRECISION,
+            probabilities: contiguous_probabilities(self.cdf.0.as_ref()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, Probability, const PRECISION: usize> Deserialize<'de>
+    for ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>
+where
+    Probability: BitArray + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = ContiguousTable::<Probability>::deserialize(deserializer)?;
+        check_table::<D::Error, PRECISION>(table.precision, None, table.probabilities.len())?;
+        Self::from_nonzero_fixed_point_probabilities(&table.probabilities, false)
+            .map_err(|()| invalid_probabilities())
+    }
+}
+
+impl<Symbol, Probability, Table, const PRECISION: usize> Serialize
+    for NonContiguousCategoricalDecoderModel<Symbol, Probability, Table, PRECISION>
+where
+    Symbol: Clone + Serialize,
+    Probability: BitArray + Serialize,
+    Table: AsRef<[(Probability, Symbol)]>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        non_contiguous_table::<_, _, PRECISION>(self.cdf.0.as_ref()).serialize(serializer)
+    }
+}
+
+impl<'de, Symbol, Probability, const PRECISION: usize> Deserialize<'de>
+    for NonContiguousCategoricalDecoderModel<
+        Symbol,
+        Probability,
+        Vec<(Probability, Symbol)>,
+        PRECISION,
+    >
+where
+    Symbol: Clone + Deserialize<'de>,
+    Probability: BitArray + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = NonContiguousTable::<Symbol, Probability>::deserialize(deserializer)?;
+        check_table::<D::Error, PRECISION>(
+            table.precision,
+            Some(table.symbols.len()),
+            table.probabilities.len(),
+        )?;
+        Self::from_symbols_and_nonzero_fixed_point_probabilities(
+            table.symbols,
+            &table.probabilities,
+            false,
+        )
+        .map_err(|()| invalid_probabilities())
+    }
+}
+
+impl<Symbol, Probability, const PRECISION: usize> Serialize
+    for NonContiguousCategoricalEncoderModel<Symbol, Probability, PRECISION>
+where
+    Symbol: Hash + Clone + Serialize,
+    Probability: BitArray + Serialize,
+{
+    /// Serializes the symbols in order of increasing left-sided cumulative (rather than in
+    /// the unspecified order of the internal hash map) so that the serialized representation
+    /// is deterministic and can also be deserialized into a decoder model.
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut entries = self.table.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(_, &(left_cumulative, _))| left_cumulative);
+        let (symbols, probabilities) = entries
+            .into_iter()
+            .map(|(symbol, &(_, probability))| (symbol.clone(), probability.get()))
+            .unzip();
+        NonContiguousTable {
+            precision: PRECISION,
+            symbols,
+            probabilities,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, Symbol, Probability, const PRECISION: usize> Deserialize<'de>
+    for NonContiguousCategoricalEncoderModel<Symbol, Probability, PRECISION>
+where
+    Symbol: Hash + Eq + Deserialize<'de>,
+    Probability: BitArray + Deserialize<'de>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = NonContiguousTable::<Symbol, Probability>::deserialize(deserializer)?;
+        check_table::<D::Error, PRECISION>(
+            table.precision,
+            Some(table.symbols.len()),
+            table.probabilities.len(),
+        )?;
+        Self::from_symbols_and_nonzero_fixed_point_probabilities(
+            table.symbols,
+            &table.probabilities,
+            false,
+        )
+        .map_err(|()| invalid_probabilities())
+    }
+}
+
+impl<Symbol, Probability, Table, LookupTable, const PRECISION: usize> Serialize
+    for LookupDecoderModel<
+        Symbol,
+        Probability,
+        ContiguousSymbolTable<Table>,
+        LookupTable,
+        PRECISION,
+    >
+where
+    Probability: BitArray + Serialize,
+    Table: AsRef<[Probability]>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        ContiguousTable {
+            precision: PRECISION,
+            probabilities: contiguous_probabilities(self.cdf.0.as_ref()),
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, Symbol, Probability, const PRECISION: usize> Deserialize<'de>
+    for LookupDecoderModel<
+        Symbol,
+        Probability,
+        ContiguousSymbolTable<Vec<Probability>>,
+        Box<[Probability]>,
+        PRECISION,
+    >
+where
+    Symbol: Copy + Default,
+    Probability: BitArray + Into<usize> + Deserialize<'de>,
+    usize: AsPrimitive<Probability>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = ContiguousTable::<Probability>::deserialize(deserializer)?;
+        check_table::<D::Error, PRECISION>(table.precision, None, table.probabilities.len())?;
+        Self::from_nonzero_fixed_point_probabilities_contiguous(&table.probabilities, false)
+            .map_err(|()| invalid_probabilities())
+    }
+}
+
+impl<Symbol, Probability, Table, LookupTable, const PRECISION: usize> Serialize
+    for LookupDecoderModel<
+        Symbol,
+        Probability,
+        NonContiguousSymbolTable<Table>,
+        LookupTable,
+        PRECISION,
+    >
+where
+    Symbol: Clone + Serialize,
+    Probability: BitArray + Serialize,
+    Table: AsRef<[(Probability, Symbol)]>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        non_contiguous_table::<_, _, PRECISION>(self.cdf.0.as_ref()).serialize(serializer)
+    }
+}
+
+impl<'de, Symbol, Probability, const PRECISION: usize> Deserialize<'de>
+    for LookupDecoderModel<
+        Symbol,
+        Probability,
+        NonContiguousSymbolTable<Vec<(Probability, Symbol)>>,
+        Box<[Probability]>,
+        PRECISION,
+    >
+where
+    Symbol: Copy + Default + Deserialize<'de>,
+    Probability: BitArray + Into<usize> + Deserialize<'de>,
+    usize: AsPrimitive<Probability>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let table = NonContiguousTable::<Symbol, Probability>::deserialize(deserializer)?;
+        check_table::<D::Error, PRECISION>(
+            table.precision,
+            Some(table.symbols.len()),
+            table.probabilities.len(),
+        )?;
+        Self::from_symbols_and_nonzero_fixed_point_probabilities(
+            table.symbols,
+            &table.probabilities,
+            false,
+        )
+        .map_err(|()| invalid_probabilities())
+    }
+}
+
+/// There's no corresponding `Deserialize` implementation because the underlying
+/// distribution can't be recovered from its quantized probabilities. Deserialize into a
+/// [`NonContiguousCategoricalDecoderModel`], a [`NonContiguousCategoricalEncoderModel`], or
+/// a non-contiguous [`LookupDecoderModel`] instead, which reproduces the quantized model
+/// exactly.
+impl<F, Symbol, Probability, D, const PRECISION: usize> Serialize
+    for LeakilyQuantizedDistribution<F, Symbol, Probability, D, PRECISION>
+where
+    F: Float + Into<f64>,
+    f64: AsPrimitive<Probability>,
+    Symbol: PrimInt
+        + AsPrimitive<Probability>
+        + AsPrimitive<usize>
+        + Into<f64>
+        + WrappingSub
+        + Serialize,
+    Probability: BitArray + Into<f64> + Serialize,
+    D: Distribution,
+    D::Value: AsPrimitive<Symbol>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let (symbols, probabilities) = self
+            .symbol_table()
+            .map(|(symbol, _, probability)| (symbol, probability.get()))
+            .unzip();
+        NonContiguousTable {
+            precision: PRECISION,
+            symbols,
+            probabilities,
+        }
+        .serialize(serializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        DecoderModel, DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer,
+        DefaultNonContiguousCategoricalDecoderModel, DefaultNonContiguousCategoricalEncoderModel,
+        EncoderModel, LeakyQuantizer, SmallContiguousCategoricalEntropyModel,
+        SmallContiguousLookupDecoderModel, SmallNonContiguousLookupDecoderModel,
+    };
+    use super::*;
+
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn contiguous() {
+        let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+            0.1, 0.001, 0.3, 0.2, 0.399,
+        ])
+        .unwrap();
+        let json = serde_json::to_string(&model).unwrap();
+        let deserialized: DefaultContiguousCategoricalEntropyModel =
+            serde_json::from_str(&json).unwrap();
+        assert!(deserialized.symbol_table().eq(model.symbol_table()));
+
+        // The contiguous lookup model has the same serialized representation.
+        let small_model =
+            SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+                0.1, 0.001, 0.3, 0.2, 0.399,
+            ])
+            .unwrap();
+        let json = serde_json::to_string(&small_model).unwrap();
+        let lookup_model: SmallContiguousLookupDecoderModel = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&lookup_model).unwrap(), json);
+        for quantile in 0..1 << 12 {
+            assert_eq!(
+                lookup_model.quantile_function(quantile),
+                small_model.quantile_function(quantile)
+            );
+        }
+
+        // Deserializing with the wrong precision fails.
+        assert!(serde_json::from_str::<DefaultContiguousCategoricalEntropyModel>(&json).is_err());
+    }
+
+    #[test]
+    fn non_contiguous() {
+        let symbols = ['a', 'x', 'b', 'y', 'c'];
+        let probabilities = [0.1, 0.001, 0.3, 0.2, 0.399];
+        let decoder_model =
+            DefaultNonContiguousCategoricalDecoderModel::from_symbols_and_floating_point_probabilities(
+                &symbols,
+                &probabilities,
+            )
+            .unwrap();
+        let encoder_model =
+            DefaultNonContiguousCategoricalEncoderModel::from_symbols_and_floating_point_probabilities(
+                symbols.iter().copied(),
+                &probabilities,
+            )
+            .unwrap();
+
+        // Encoder and decoder models have the same serialized representation, which is
+        // deterministic despite the hash map in the encoder model.
+        let json = serde_json::to_string(&decoder_model).unwrap();
+        assert_eq!(serde_json::to_string(&encoder_model).unwrap(), json);
+
+        let deserialized_encoder: DefaultNonContiguousCategoricalEncoderModel<char> =
+            serde_json::from_str(&json).unwrap();
+        let deserialized_decoder: DefaultNonContiguousCategoricalDecoderModel<char> =
+            serde_json::from_str(&json).unwrap();
+        assert!(deserialized_decoder
+            .symbol_table()
+            .eq(decoder_model.symbol_table()));
+        for (symbol, left_cumulative, probability) in decoder_model.symbol_table() {
+            assert_eq!(
+                deserialized_encoder.left_cumulative_and_probability(symbol),
+                Some((left_cumulative, probability))
+            );
+        }
+    }
+
+    #[test]
+    fn lookup_and_quantized() {
+        let quantizer = LeakyQuantizer::<f64, i32, u16, 12>::new(-50..=50);
+        let quantized = quantizer.quantize(Gaussian::new(3.2, 10.0));
+        let json = serde_json::to_string(&quantized).unwrap();
+
+        let lookup_model: SmallNonContiguousLookupDecoderModel<i32> =
+            serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&lookup_model).unwrap(), json);
+        for quantile in 0..1 << 12 {
+            assert_eq!(
+                lookup_model.quantile_function(quantile),
+                quantized.quantile_function(quantile)
+            );
+        }
+
+        // A default quantizer has a different precision.
+        let quantized = DefaultLeakyQuantizer::new(-50..=50).quantize(Gaussian::new(3.2, 10.0));
+        let json = serde_json::to_string(&quantized).unwrap();
+        assert!(serde_json::from_str::<SmallNonContiguousLookupDecoderModel<i32>>(&json).is_err());
+        let model: DefaultNonContiguousCategoricalDecoderModel<i32> =
+            serde_json::from_str(&json).unwrap();
+        assert!(model.symbol_table().eq(quantized.symbol_table()));
+    }
+
+    #[test]
+    fn invalid_input() {
+        let invalid_jsons = [
+            // Probabilities don't sum to one.
+            r#"{"precision":24,"probabilities":[4194304,4194304]}"#,
+            // Zero probability.
+            r#"{"precision":24,"probabilities":[8388608,0,8388608]}"#,
+            // Only one symbol.
+            r#"{"precision":24,"probabilities":[16777216]}"#,
+        ];
+        for json in invalid_jsons.iter() {
+            assert!(
+                serde_json::from_str::<DefaultContiguousCategoricalEntropyModel>(json).is_err()
+            );
+        }
+
+        let json = r#"{"precision":24,"symbols":[1,2,3],"probabilities":[8388608,8388608]}"#;
+        assert!(
+            serde_json::from_str::<DefaultNonContiguousCategoricalDecoderModel<i32>>(json).is_err()
+        );
+    }
+}
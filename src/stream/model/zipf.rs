@@ -0,0 +1,230 @@
+//! A power-law entropy model for ranks over large alphabets
+//!
+//! The frequencies of words in natural language, of token IDs sorted by frequency, and of
+//! many other ranked quantities approximately follow Zipf's law: the `r`-th most frequent
+//! item occurs with a probability proportional to `r^(-s)` for some exponent `s` (usually
+//! close to one). The [`Zipf`] model in this module represents this distribution over the
+//! symbols `0..n`, where symbol `k` stands for rank `k + 1`. It evaluates the weights
+//! `(k + 1)^(-s)` itself, so you only have to provide the two parameters `s` and `n` rather
+//! than a table of `n` floating point probabilities.
+//!
+//! The weights are rounded to fixed point with [`RoundingPolicy::LargestRemainder`]. Since
+//! evaluating a power with a non-integer exponent relies on the platform's implementation
+//! of `powf`, the fixed point probabilities may, in rare cases, differ in their last bit
+//! across platforms unless `s` is an integer.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{model::zipf::DefaultZipf, queue::DefaultRangeEncoder, Decode, Encode};
+//!
+//! // Token IDs, sorted by frequency, from a vocabulary of 50,000 tokens.
+//! let token_ids = [0, 3, 1, 0, 17, 2, 0, 4711, 1, 8];
+//! let model = DefaultZipf::new(1.1, 50_000).unwrap();
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! encoder.encode_iid_symbols(&token_ids, &model).unwrap();
+//! let mut decoder = encoder.into_decoder().unwrap();
+//! let decoded = decoder
+//!     .decode_iid_symbols(token_ids.len(), &model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, token_ids);
+//! ```
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    ContiguousCategoricalEntropyModel, ContiguousSymbolTable, DecoderModel, EncoderModel,
+    EntropyModel, IterableEntropyModel, RoundingPolicy, SymbolTableIter,
+};
+use crate::BitArray;
+
+/// An entropy model over the symbols `0..n` where symbol `k` has a probability
+/// proportional to `(k + 1)^(-s)`.
+///
+/// See [module level documentation](self) for details and an example. Like all entropy
+/// models in `constriction`, a `Zipf` model is leaky, i.e., it assigns a nonzero
+/// probability to every symbol in its support, no matter how large the exponent `s`.
+#[derive(Debug, Clone)]
+pub struct Zipf<Probability: BitArray, const PRECISION: usize> {
+    s: f64,
+    model: ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION>,
+}
+
+/// Type alias for a [`Zipf`] model with sane settings.
+///
+/// Can be used with a [`DefaultAnsCoder`], a [`DefaultChainCoder`], or a
+/// [`DefaultRangeEncoder`] / [`DefaultRangeDecoder`].
+///
+/// [`DefaultAnsCoder`]: crate::stream::stack::DefaultAnsCoder
+/// [`DefaultChainCoder`]: crate::stream::chain::DefaultChainCoder
+/// [`DefaultRangeEncoder`]: crate::stream::queue::DefaultRangeEncoder
+/// [`DefaultRangeDecoder`]: crate::stream::queue::DefaultRangeDecoder
+pub type DefaultZipf = Zipf<u32, 24>;
+
+/// Type alias for a [`Zipf`] model that can be used with coders that use `u16` words.
+///
+/// Note that the alphabet has to fit into the `2^12` available fixed point probability
+/// units, i.e., `n` must be at most `4096`.
+pub type SmallZipf = Zipf<u16, 12>;
+
+impl<Probability: BitArray, const PRECISION: usize> Zipf<Probability, PRECISION>
+where
+    Probability: Into<f64> + AsPrimitive<usize>,
+    f64: AsPrimitive<Probability>,
+    usize: AsPrimitive<Probability>,
+{
+    /// Creates a Zipf model with exponent `s` over the symbols `0..n`.
+    ///
+    /// Returns an error if `s` is negative, infinite, or NaN (`s = 0` results in a uniform
+    /// distribution), if `n` is smaller than two, or if `n` exceeds `1 << PRECISION`
+    /// (since every symbol needs a nonzero probability).
+    ///
+    /// The run time is linear in `n`.
+    #[allow(clippy::result_unit_err)]
+    pub fn new(s: f64, n: usize) -> Result<Self, ()> {
+        if !(s >= 0.0 && s.is_finite()) || n < 2 {
+            return Err(());
+        }
+        if PRECISION < usize::BITS as usize && n > 1 << PRECISION {
+            // Check before allocating the table of weights.
+            return Err(());
+        }
+
+        let weights = (1..=n)
+            .map(|rank| (rank as f64).powf(-s))
+            .collect::<Vec<_>>();
+        let model =
+            ContiguousCategoricalEntropyModel::from_floating_point_probabilities_with_rounding(
+                &weights,
+                RoundingPolicy::LargestRemainder,
+            )?;
+        Ok(Self { s, model })
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> Zipf<Probability, PRECISION> {
+    /// Returns the exponent of the power law.
+    pub fn s(&self) -> f64 {
+        self.s
+    }
+
+    /// Returns the size `n` of the alphabet `0..n`.
+    pub fn n(&self) -> usize {
+        self.model.support_size()
+    }
+
+    /// Returns the underlying categorical entropy model over the symbols `0..n`.
+    pub fn as_categorical(
+        &self,
+    ) -> &ContiguousCategoricalEntropyModel<Probability, Vec<Probability>, PRECISION> {
+        &self.model
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EntropyModel<PRECISION>
+    for Zipf<Probability, PRECISION>
+{
+    type Symbol = usize;
+    type Probability = Probability;
+}
+
+impl<Probability: BitArray, const PRECISION: usize> EncoderModel<PRECISION>
+    for Zipf<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn left_cumulative_and_probability(
+        &self,
+        symbol: impl Borrow<usize>,
+    ) -> Option<(Probability, Probability::NonZero)> {
+        self.model.left_cumulative_and_probability(symbol)
+    }
+}
+
+impl<Probability: BitArray, const PRECISION: usize> DecoderModel<PRECISION>
+    for Zipf<Probability, PRECISION>
+{
+    #[inline(always)]
+    fn quantile_function(
+        &self,
+        quantile: Probability,
+    ) -> (usize, Probability, Probability::NonZero) {
+        self.model.quantile_function(quantile)
+    }
+}
+
+impl<'m, Probability: BitArray, const PRECISION: usize> IterableEntropyModel<'m, PRECISION>
+    for Zipf<Probability, PRECISION>
+{
+    type Iter = SymbolTableIter<usize, Probability, ContiguousSymbolTable<&'m [Probability]>>;
+
+    #[inline(always)]
+    fn symbol_table(&'m self) -> Self::Iter {
+        self.model.symbol_table()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_pmf() {
+        for &s in &[0.0, 0.5, 1.0, 1.3, 3.0] {
+            for &n in &[2, 10, 1000, 100_000] {
+                let model = DefaultZipf::new(s, n).unwrap();
+                assert_eq!(model.n(), n);
+                assert!(model.left_cumulative_and_probability(n).is_none());
+
+                let mut total = 0u64;
+                for (_, left_cumulative, probability) in model.symbol_table() {
+                    assert_eq!(left_cumulative as u64, total);
+                    total += probability.get() as u64;
+                }
+                assert_eq!(total, 1 << 24);
+
+                // Each symbol gets one unit up front and at most one unit from rounding.
+                let normalization = (1..=n).map(|rank| (rank as f64).powf(-s)).sum::<f64>();
+                let units = (1u64 << 24) as f64;
+                for k in (0..n).step_by(n / 10 + 1) {
+                    let exact = ((k + 1) as f64).powf(-s) / normalization;
+                    let approx: f64 = model.floating_point_probability(k);
+                    assert!((approx - exact).abs() <= (2.0 + exact * n as f64) / units + 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn monotonic() {
+        let model = DefaultZipf::new(1.0, 5000).unwrap();
+        let probabilities = model
+            .symbol_table()
+            .map(|(_, _, probability)| probability.get())
+            .collect::<Vec<_>>();
+        assert!(probabilities.windows(2).all(|pair| pair[0] >= pair[1]));
+
+        // For `s = 0`, the model is uniform (up to rounding).
+        let model = DefaultZipf::new(0.0, 3).unwrap();
+        for k in 0..3 {
+            let (_, probability) = model.left_cumulative_and_probability(k).unwrap();
+            assert!((probability.get() as i64 - (1 << 24) / 3).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn invalid_construction() {
+        assert!(DefaultZipf::new(-0.1, 10).is_err());
+        assert!(DefaultZipf::new(f64::NAN, 10).is_err());
+        assert!(DefaultZipf::new(f64::INFINITY, 10).is_err());
+        assert!(DefaultZipf::new(1.0, 0).is_err());
+        assert!(DefaultZipf::new(1.0, 1).is_err());
+        assert!(DefaultZipf::new(1.0, usize::MAX).is_err());
+        assert!(SmallZipf::new(1.0, 4096).is_ok());
+        assert!(SmallZipf::new(1.0, 4097).is_err());
+    }
+}
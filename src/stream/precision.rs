@@ -0,0 +1,305 @@
+//! Pinning the fixed point precision of a coder at the type level
+//!
+//! Entropy models in `constriction` declare their fixed point precision as a const generic
+//! parameter `PRECISION` of the traits [`EntropyModel`], [`EncoderModel`], and
+//! [`DecoderModel`]. The stream coders, however, implement [`Encode<PRECISION>`] and
+//! [`Decode<PRECISION>`] for *all* `PRECISION`s that fit into their word size, so that you
+//! can mix models with different precisions on the same coder. The flip side is that the
+//! compiler can't catch it if you accidentally encode a symbol with, say, a 24 bit model
+//! and then decode it with a 12 bit model (e.g., because two crates disagree on which
+//! preset to use). Such a mismatch only surfaces at run time, as garbled symbols or as an
+//! error deep inside decoding.
+//!
+//! A [`FixedPrecision`] wrapper pins a coder to a single precision: it implements
+//! `Encode<PRECISION>` and `Decode<PRECISION>` only for the `PRECISION` of the zero-sized
+//! [`Precision`] marker that you pass to its constructor. Thus, passing an entropy model
+//! with any other precision to it is a compile error. The type aliases [`DefaultPrecision`]
+//! and [`SmallPrecision`] match the presets of the same names (see [discussion of
+//! presets](super#presets)), and [`Precision::of`] infers the precision from an existing
+//! entropy model.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::DefaultLeakyQuantizer,
+//!     precision::{DefaultPrecision, FixedPrecision},
+//!     stack::DefaultAnsCoder,
+//!     Decode, Encode,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+//! let symbols = [17, -3, 0, 42];
+//!
+//! let mut coder = FixedPrecision::new(DefaultAnsCoder::new(), DefaultPrecision::new());
+//! coder.encode_iid_symbols(symbols.iter().rev(), &model).unwrap();
+//! let decoded = coder
+//!     .decode_iid_symbols(symbols.len(), &model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, symbols);
+//! ```
+//!
+//! Using a model with a different precision doesn't compile:
+//!
+//! ```compile_fail
+//! use constriction::stream::{
+//!     model::SmallLeakyQuantizer,
+//!     precision::{DefaultPrecision, FixedPrecision},
+//!     stack::DefaultAnsCoder,
+//!     Encode,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! // A `SmallLeakyQuantizer` has a precision of 12 bits, but the coder expects 24 bits.
+//! let model = SmallLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+//! let mut coder = FixedPrecision::new(DefaultAnsCoder::new(), DefaultPrecision::new());
+//! coder.encode_symbol(17, &model).unwrap();
+//! ```
+//!
+//! [`EntropyModel`]: super::model::EntropyModel
+//! [`EncoderModel`]: super::model::EncoderModel
+//! [`DecoderModel`]: super::model::DecoderModel
+
+use core::borrow::Borrow;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    model::{DecoderModel, EncoderModel, EntropyModel},
+    Code, Decode, Encode,
+};
+use crate::{CoderError, Pos, PosSeek, Seek};
+
+/// Zero-sized marker for a fixed point precision of `PRECISION` bits.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Precision<const PRECISION: usize>;
+
+/// Marker for the precision of the default presets, e.g., [`DefaultAnsCoder`] and
+/// [`DefaultLeakyQuantizer`].
+///
+/// [`DefaultAnsCoder`]: super::stack::DefaultAnsCoder
+/// [`DefaultLeakyQuantizer`]: super::model::DefaultLeakyQuantizer
+pub type DefaultPrecision = Precision<24>;
+
+/// Marker for the precision of the small presets, e.g., [`SmallAnsCoder`] and
+/// [`SmallLeakyQuantizer`].
+///
+/// [`SmallAnsCoder`]: super::stack::SmallAnsCoder
+/// [`SmallLeakyQuantizer`]: super::model::SmallLeakyQuantizer
+pub type SmallPrecision = Precision<12>;
+
+impl<const PRECISION: usize> Precision<PRECISION> {
+    /// The number of bits of precision.
+    pub const BITS: usize = PRECISION;
+
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Returns the marker for the precision of `model`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::SmallContiguousCategoricalEntropyModel,
+    ///     precision::{Precision, SmallPrecision},
+    /// };
+    ///
+    /// let model = SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+    ///     &[0.3, 0.7],
+    /// )
+    /// .unwrap();
+    /// let precision: SmallPrecision = Precision::of(&model);
+    /// assert_eq!(SmallPrecision::BITS, 12);
+    /// ```
+    pub fn of<M: EntropyModel<PRECISION>>(_model: &M) -> Self {
+        Self
+    }
+}
+
+/// Wrapper around a coder that accepts only entropy models with a fixed precision.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct FixedPrecision<Coder, const PRECISION: usize> {
+    inner: Coder,
+}
+
+impl<Coder, const PRECISION: usize> FixedPrecision<Coder, PRECISION> {
+    /// Wraps `inner` so that it can only be used with entropy models whose precision is
+    /// `PRECISION`.
+    pub fn new(inner: Coder, _precision: Precision<PRECISION>) -> Self {
+        Self { inner }
+    }
+
+    /// Returns the marker for the precision that this coder is pinned to.
+    pub fn precision(&self) -> Precision<PRECISION> {
+        Precision
+    }
+
+    /// Returns a reference to the wrapped coder.
+    pub fn get_ref(&self) -> &Coder {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped coder.
+    ///
+    /// Encoding or decoding directly with the returned reference bypasses the check of
+    /// the precision.
+    pub fn get_mut(&mut self) -> &mut Coder {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped coder.
+    pub fn into_inner(self) -> Coder {
+        self.inner
+    }
+}
+
+impl<Coder: Code, const PRECISION: usize> Code for FixedPrecision<Coder, PRECISION> {
+    type Word = Coder::Word;
+    type State = Coder::State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Coder, const PRECISION: usize> Encode<PRECISION> for FixedPrecision<Coder, PRECISION>
+where
+    Coder: Encode<PRECISION>,
+{
+    type FrontendError = Coder::FrontendError;
+    type BackendError = Coder::BackendError;
+
+    #[inline(always)]
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.inner.encode_symbol(symbol, model)
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.inner.maybe_full()
+    }
+}
+
+impl<Coder, const PRECISION: usize> Decode<PRECISION> for FixedPrecision<Coder, PRECISION>
+where
+    Coder: Decode<PRECISION>,
+{
+    type FrontendError = Coder::FrontendError;
+    type BackendError = Coder::BackendError;
+
+    #[inline(always)]
+    fn decode_symbol<D>(
+        &mut self,
+        model: D,
+    ) -> Result<D::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<D::Probability>,
+    {
+        self.inner.decode_symbol(model)
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.inner.maybe_exhausted()
+    }
+}
+
+impl<Coder: PosSeek, const PRECISION: usize> PosSeek for FixedPrecision<Coder, PRECISION> {
+    type Position = Coder::Position;
+}
+
+impl<Coder: Pos, const PRECISION: usize> Pos for FixedPrecision<Coder, PRECISION> {
+    #[inline(always)]
+    fn pos(&self) -> Self::Position {
+        self.inner.pos()
+    }
+}
+
+impl<Coder: Seek, const PRECISION: usize> Seek for FixedPrecision<Coder, PRECISION> {
+    #[inline(always)]
+    fn seek(&mut self, pos: Self::Position) -> Result<(), ()> {
+        self.inner.seek(pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::{DefaultLeakyQuantizer, SmallLeakyQuantizer},
+        queue::{SmallRangeDecoder, SmallRangeEncoder},
+        stack::DefaultAnsCoder,
+    };
+    use super::*;
+
+    use alloc::vec::Vec;
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn matches_unwrapped_coder() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        let symbols = (0..50).map(|i| (i * 7) % 201 - 100).collect::<Vec<i32>>();
+
+        let mut reference = DefaultAnsCoder::new();
+        reference
+            .encode_iid_symbols_reverse(&symbols, &model)
+            .unwrap();
+
+        let mut coder = FixedPrecision::new(DefaultAnsCoder::new(), Precision::of(&model));
+        coder
+            .encode_iid_symbols(symbols.iter().rev(), &model)
+            .unwrap();
+        assert_eq!(coder.precision(), DefaultPrecision::new());
+        assert_eq!(
+            &*coder.get_mut().get_compressed().unwrap(),
+            &*reference.get_compressed().unwrap()
+        );
+
+        let decoded = coder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(coder.into_inner().is_empty());
+    }
+
+    #[test]
+    fn small_precision() {
+        let model = SmallLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+        let symbols = (0..50).map(|i| (i * 11) % 201 - 100).collect::<Vec<i32>>();
+
+        let mut encoder = FixedPrecision::new(SmallRangeEncoder::new(), SmallPrecision::new());
+        encoder.encode_iid_symbols(&symbols[..25], &model).unwrap();
+        let checkpoint = encoder.pos();
+        encoder.encode_iid_symbols(&symbols[25..], &model).unwrap();
+        let compressed = encoder.into_inner().into_compressed().unwrap();
+
+        let decoder = SmallRangeDecoder::from_compressed(compressed).unwrap();
+        let mut decoder = FixedPrecision::new(decoder, SmallPrecision::new());
+        decoder.seek(checkpoint).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(25, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols[25..]);
+    }
+}
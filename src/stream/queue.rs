@@ -34,20 +34,26 @@
 use alloc::vec::Vec;
 use core::{
     borrow::Borrow,
+    convert::Infallible,
     fmt::{Debug, Display},
     marker::PhantomData,
-    num::NonZeroUsize,
     ops::Deref,
 };
 
 use num::cast::AsPrimitive;
 
 use super::{
+    checksum::{self, ChecksumError},
     model::{DecoderModel, EncoderModel},
+    renorm::{self, CarryBuffer},
+    strict::StrictDecode,
     Code, Decode, Encode, IntoDecoder,
 };
 use crate::{
-    backends::{AsReadWords, BoundedReadWords, Cursor, IntoReadWords, ReadWords, WriteWords},
+    backends::{
+        AsReadWords, BackendStats, BoundedReadWords, Cursor, IntoReadWords, ReadWords, ReportStats,
+        WriteWords,
+    },
     BitArray, CoderError, DefaultEncoderError, DefaultEncoderFrontendError, NonZeroBitArray, Pos,
     PosSeek, Queue, Seek, UnwrapInfallible,
 };
@@ -71,7 +77,7 @@ pub struct RangeCoderState<Word, State: BitArray> {
 impl<Word: BitArray, State: BitArray> RangeCoderState<Word, State> {
     #[allow(clippy::result_unit_err)]
     pub fn new(lower: State, range: State) -> Result<Self, ()> {
-        if range >> (State::BITS - Word::BITS) == State::zero() {
+        if renorm::needs_renorm::<Word, State>(range) {
             Err(())
         } else {
             Ok(Self {
@@ -112,21 +118,39 @@ where
 {
     bulk: Backend,
     state: RangeCoderState<Word, State>,
-    situation: EncoderSituation<Word>,
+    carry_buffer: CarryBuffer<Word>,
+    carry_stats: CarryStats,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum EncoderSituation<Word> {
-    Normal,
+/// Statistics about carry propagation in a [`RangeEncoder`], see
+/// [`RangeEncoder::carry_stats`].
+///
+/// A range encoder is in an *inverted* situation when its current interval straddles the
+/// overflow point of its internal state (i.e., when `lower + range` wraps around). In this
+/// situation, the encoder can't know yet whether the words it splits off from `lower` will
+/// receive a carry, so it holds them back until the interval ends up entirely on one side
+/// of the overflow point. This happens rarely (roughly once every `2^Word::BITS` emitted
+/// words for typical data), so the corresponding code paths are hard to exercise in tests.
+/// These statistics make it possible to check that a test actually covers them, e.g., when
+/// validating a reimplementation of the range coder in another language against this one.
+///
+/// Only inversions that get resolved by encoding more symbols count towards
+/// `num_carries`; words that are still held back when the encoder is sealed are resolved
+/// without being counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub struct CarryStats {
+    /// How often the encoder entered the inverted situation.
+    pub num_inversions: u64,
 
-    /// Wraps `num_inverted` and `first_inverted_lower_word`
-    Inverted(NonZeroUsize, Word),
-}
+    /// How many inversions were resolved with a carry into the held back words.
+    pub num_carries: u64,
 
-impl<Word> Default for EncoderSituation<Word> {
-    fn default() -> Self {
-        Self::Normal
-    }
+    /// The total number of words that were held back over all inversions.
+    pub num_held_back_words: u64,
+
+    /// The largest number of words that were held back at the same time.
+    pub max_held_back_words: usize,
 }
 
 /// Type alias for an [`RangeEncoder`] with sane parameters for typical use cases.
@@ -144,6 +168,35 @@ pub type DefaultRangeEncoder<Backend = Vec<u32>> = RangeEncoder<u32, u64, Backen
 /// [`SmallNonContiguousLookupDecoderModel`]: super::model::SmallNonContiguousLookupDecoderModel
 pub type SmallRangeEncoder<Backend = Vec<u16>> = RangeEncoder<u16, u32, Backend>;
 
+/// Type alias for a [`RangeEncoder`] with 64 bit words and a 128 bit internal state
+///
+/// This encoder supports entropy models with a `PRECISION` of more than 32 bits (e.g.,
+/// models with `u64` probabilities), and it needs fewer renormalization steps than a
+/// [`DefaultRangeEncoder`] on 64 bit hardware. Decode the compressed data with a
+/// [`WideRangeDecoder`].
+pub type WideRangeEncoder<Backend = Vec<u64>> = RangeEncoder<u64, u128, Backend>;
+
+/// Type alias for a [`RangeEncoder`] whose compressed data is a stream of bytes
+///
+/// The compressed data of this encoder is a sequence of `u8` words, so you can embed it in
+/// byte-oriented container formats (or write it to a file) without converting words to
+/// bytes, and without having to worry about endianness. Since probabilities have to fit
+/// into a single word, this encoder only works with entropy models that use `u8`
+/// probabilities, i.e., with a `PRECISION` of at most 8 bits. Decode the compressed data
+/// with a [`ByteRangeDecoder`].
+///
+/// See also [`ByteRangeEncoder64`], which uses a 64 bit internal state.
+pub type ByteRangeEncoder<Backend = Vec<u8>> = RangeEncoder<u8, u32, Backend>;
+
+/// Type alias for a [`RangeEncoder`] with 8 bit words and a 64 bit internal state
+///
+/// Like a [`ByteRangeEncoder`], but the larger state approximates the entropy models'
+/// probabilities more accurately with the coder's finite range. This reduces the (already
+/// small) per-symbol overhead but makes sealing the compressed data emit up to four
+/// additional bytes, so it only pays off for long messages. Decode the compressed data
+/// with a [`ByteRangeDecoder64`].
+pub type ByteRangeEncoder64<Backend = Vec<u8>> = RangeEncoder<u8, u64, Backend>;
+
 impl<Word, State, Backend> Code for RangeEncoder<Word, State, Backend>
 where
     Word: BitArray + Into<State>,
@@ -175,12 +228,12 @@ where
     Backend: WriteWords<Word> + Pos<Position = usize>,
 {
     fn pos(&self) -> Self::Position {
-        let num_inverted = if let EncoderSituation::Inverted(num_inverted, _) = self.situation {
-            num_inverted.get()
-        } else {
-            0
-        };
-        (self.bulk.pos() + num_inverted, self.state())
+        let pos = self
+            .bulk
+            .pos()
+            .checked_add(self.carry_buffer.len())
+            .expect("position overflows addressable space");
+        (pos, self.state())
     }
 }
 
@@ -210,7 +263,8 @@ where
         Self {
             bulk: Vec::new(),
             state: RangeCoderState::default(),
-            situation: EncoderSituation::Normal,
+            carry_buffer: CarryBuffer::Empty,
+            carry_stats: CarryStats::default(),
         }
     }
 }
@@ -254,7 +308,8 @@ where
         Self {
             bulk: backend,
             state: RangeCoderState::default(),
-            situation: EncoderSituation::Normal,
+            carry_buffer: CarryBuffer::Empty,
+            carry_stats: CarryStats::default(),
         }
     }
 
@@ -276,7 +331,7 @@ where
     /// Same as IntoDecoder::into_decoder(self) but can be used for any `PRECISION`
     /// and therefore doesn't require type arguments on the caller side.
     ///
-    /// TODO: there should also be a `decoder()` method that takes `&mut self`
+    /// See [`as_decoder`](Self::as_decoder) for a decoder that doesn't consume the encoder.
     #[allow(clippy::result_unit_err)]
     pub fn into_decoder(self) -> Result<RangeDecoder<Word, State, Backend::IntoReadWords>, ()>
     where
@@ -286,17 +341,62 @@ where
         RangeDecoder::from_compressed(self.into_compressed().map_err(|_| ())?).map_err(|_| ())
     }
 
+    /// Returns a decoder that reads from the current compressed data without consuming or
+    /// modifying the encoder.
+    ///
+    /// Unlike [`decoder`](RangeEncoder::decoder), which is only implemented for encoders
+    /// backed by a `Vec<Word>`, this method works for any backend that can be temporarily
+    /// used as a source of compressed words (see [`AsReadWords`]). Instead of sealing the
+    /// backend, the returned decoder reads the words that sealing would append (see
+    /// [`SealedReadWords`]) after it has read all words from the backend. Thus, the
+    /// returned decoder sees the same data as a decoder obtained from
+    /// [`into_decoder`](Self::into_decoder), but you can continue encoding once you've
+    /// dropped it.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     backends::CountingWords,
+    ///     stream::{model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, Decode, Encode},
+    /// };
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+    /// let mut encoder = DefaultRangeEncoder::with_backend(CountingWords::new(Vec::new()));
+    /// encoder.encode_iid_symbols(-10..10, model).unwrap();
+    ///
+    /// let mut decoder = encoder.as_decoder().unwrap();
+    /// let decoded = decoder.decode_iid_symbols(20, model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(decoded.unwrap(), (-10..10).collect::<Vec<_>>());
+    /// assert!(decoder.maybe_exhausted());
+    ///
+    /// // The encoder can still be used.
+    /// encoder.encode_symbol(5, model).unwrap();
+    /// ```
+    pub fn as_decoder<'a>(
+        &'a self,
+    ) -> Result<
+        RangeDecoder<Word, State, SealedReadWords<Word, Backend::AsReadWords>>,
+        <Backend::AsReadWords as ReadWords<Word, Queue>>::ReadError,
+    >
+    where
+        Backend: AsReadWords<'a, Word, Queue>,
+    {
+        RangeDecoder::with_backend(SealedReadWords::new(self))
+    }
+
     pub fn into_compressed(mut self) -> Result<Backend, Backend::WriteError> {
         self.seal()?;
         Ok(self.bulk)
     }
 
-    /// Private method; flushes held-back words if in inverted situation and adds one or two
+    /// Private method; flushes held-back words (if any) and adds one or two
     /// additional words that identify the range regardless of what the compressed data may
     /// be concatenated with (unless no symbols have been encoded yet, in which case this is
     /// a no-op).
     ///
-    /// Doesn't change `self.state` or `self.situation` so that this operation can be
+    /// Doesn't change `self.state` or `self.carry_buffer` so that this operation can be
     /// reversed if the backend supports removing words (see method `unseal`);
     fn seal(&mut self) -> Result<(), Backend::WriteError> {
         if self.state.range.get() == State::max_value() {
@@ -310,30 +410,17 @@ where
         let point = self
             .state
             .lower
-            .wrapping_add(&((State::one() << (State::BITS - Word::BITS)) - State::one()));
+            .wrapping_add(&(renorm::lower_bound::<Word, State>() - State::one()));
 
-        if let EncoderSituation::Inverted(num_inverted, first_inverted_lower_word) = self.situation
-        {
-            let (first_word, consecutive_words) = if point < self.state.lower {
-                // Unlikely case (addition has wrapped).
-                (first_inverted_lower_word + Word::one(), Word::zero())
-            } else {
-                // Likely case.
-                (first_inverted_lower_word, Word::max_value())
-            };
+        // The addition above wraps around in the unlikely case that held back words
+        // receive a carry.
+        self.carry_buffer
+            .write_resolved(point < self.state.lower, &mut self.bulk)?;
 
-            self.bulk.write(first_word)?;
-            for _ in 1..num_inverted.get() {
-                self.bulk.write(consecutive_words)?;
-            }
-        }
-
-        let point_word = (point >> (State::BITS - Word::BITS)).as_();
+        let point_word = renorm::high_word::<Word, State>(point);
         self.bulk.write(point_word)?;
 
-        let upper_word = (self.state.lower.wrapping_add(&self.state.range.get())
-            >> (State::BITS - Word::BITS))
-            .as_();
+        let upper_word = renorm::high_word(self.state.lower.wrapping_add(&self.state.range.get()));
         if upper_word == point_word {
             self.bulk.write(Word::zero())?;
         }
@@ -349,17 +436,12 @@ where
         let point = self
             .state
             .lower
-            .wrapping_add(&((State::one() << (State::BITS - Word::BITS)) - State::one()));
-        let point_word = (point >> (State::BITS - Word::BITS)).as_();
-        let upper_word = (self.state.lower.wrapping_add(&self.state.range.get())
-            >> (State::BITS - Word::BITS))
-            .as_();
-        let mut count = if upper_word == point_word { 2 } else { 1 };
-
-        if let EncoderSituation::Inverted(num_inverted, _) = self.situation {
-            count += num_inverted.get();
-        }
-        count
+            .wrapping_add(&(renorm::lower_bound::<Word, State>() - State::one()));
+        let point_word = renorm::high_word::<Word, State>(point);
+        let upper_word = renorm::high_word(self.state.lower.wrapping_add(&self.state.range.get()));
+        let count = if upper_word == point_word { 2 } else { 1 };
+
+        count + self.carry_buffer.len()
     }
 
     /// Returns the number of compressed words on the ans.
@@ -382,7 +464,21 @@ where
         Backend: AsReadWords<'a, Word, Queue>,
         Backend::AsReadWords: BoundedReadWords<Word, Queue>,
     {
-        self.bulk.as_read_words().remaining() + self.num_seal_words()
+        self.checked_num_words()
+            .expect("number of words overflows addressable space")
+    }
+
+    /// Like [`num_words`](#method.num_words), but returns `None` instead of panicking if the
+    /// number of words doesn't fit into a `usize`.
+    pub fn checked_num_words<'a>(&'a self) -> Option<usize>
+    where
+        Backend: AsReadWords<'a, Word, Queue>,
+        Backend::AsReadWords: BoundedReadWords<Word, Queue>,
+    {
+        self.bulk
+            .as_read_words()
+            .remaining()
+            .checked_add(self.num_seal_words())
     }
 
     /// Returns the size of the current queue of compressed data in bits.
@@ -392,17 +488,85 @@ where
     ///
     /// The returned value is a multiple of the bitlength of the compressed word
     /// type `Word`.
+    ///
+    /// # Panics
+    ///
+    /// If the number of bits doesn't fit into a `usize`. This can only happen on platforms
+    /// with a pointer width of 32 bits or less (e.g., for more than 512 MiB of compressed
+    /// data on a 32-bit platform). Call [`num_words`](#method.num_words) instead if you need
+    /// to deal with such large amounts of compressed data on these platforms.
     pub fn num_bits<'a>(&'a self) -> usize
     where
         Backend: AsReadWords<'a, Word, Queue>,
         Backend::AsReadWords: BoundedReadWords<Word, Queue>,
     {
-        Word::BITS * self.num_words()
+        self.checked_num_bits()
+            .expect("number of bits overflows addressable space")
+    }
+
+    /// Like [`num_bits`](#method.num_bits), but returns `None` instead of panicking if the
+    /// number of bits doesn't fit into a `usize`.
+    pub fn checked_num_bits<'a>(&'a self) -> Option<usize>
+    where
+        Backend: AsReadWords<'a, Word, Queue>,
+        Backend::AsReadWords: BoundedReadWords<Word, Queue>,
+    {
+        self.checked_num_words()?.checked_mul(Word::BITS)
     }
 
     pub fn bulk(&self) -> &Backend {
         &self.bulk
     }
+
+    /// Returns the statistics gathered by the backend, e.g., by a [`CountingWords`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     backends::CountingWords,
+    ///     stream::{model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, Encode},
+    /// };
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+    /// let mut encoder = DefaultRangeEncoder::with_backend(CountingWords::new(Vec::new()));
+    /// encoder.encode_iid_symbols(-100..=100, model).unwrap();
+    ///
+    /// let stats = encoder.backend_stats();
+    /// assert_eq!(stats.num_words_written as usize, encoder.bulk().get_ref().len());
+    /// ```
+    ///
+    /// [`CountingWords`]: crate::backends::CountingWords
+    pub fn backend_stats(&self) -> BackendStats
+    where
+        Backend: ReportStats,
+    {
+        self.bulk.stats()
+    }
+
+    /// Returns statistics about carry propagation since the encoder was created or last
+    /// [`clear`](Self::clear)ed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultLeakyQuantizer, queue::DefaultRangeEncoder, Encode,
+    /// };
+    /// use probability::distribution::Gaussian;
+    ///
+    /// let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+    /// let mut encoder = DefaultRangeEncoder::new();
+    /// encoder.encode_iid_symbols(-100..=100, model).unwrap();
+    ///
+    /// let stats = encoder.carry_stats();
+    /// assert!(stats.num_carries <= stats.num_inversions);
+    /// assert!(stats.num_held_back_words >= stats.num_inversions);
+    /// ```
+    pub fn carry_stats(&self) -> CarryStats {
+        self.carry_stats
+    }
 }
 
 impl<Word, State> RangeEncoder<Word, State>
@@ -415,6 +579,8 @@ where
     pub fn clear(&mut self) {
         self.bulk.clear();
         self.state = RangeCoderState::default();
+        self.carry_buffer.clear();
+        self.carry_stats = CarryStats::default();
     }
 
     /// Assembles the current compressed data into a single slice.
@@ -433,6 +599,14 @@ where
 
     // TODO: implement `iter_compressed`
 
+    /// Seals the compressed data and appends a CRC-32 checksum to it.
+    ///
+    /// Use [`RangeDecoder::from_checksummed`] to decode the returned data. See module
+    /// [`checksum`] for details and for an example.
+    pub fn seal_with_checksum(self) -> Vec<Word> {
+        checksum::seal_with_checksum(self.into_compressed().unwrap_infallible())
+    }
+
     /// A decoder for temporary use.
     ///
     /// Once the returned decoder gets dropped, you can continue using this encoder. If you
@@ -442,7 +616,10 @@ where
     /// because we have to temporarily seal the encoder and then unseal it when the returned
     /// decoder is dropped, which requires precise knowledge of the backend (and which is
     /// also the reason why this method takes a `&mut self`receiver). If you're using a
-    /// different backend than a `Vec`, consider calling [`into_decoder`] instead.
+    /// different backend than a `Vec`, consider calling [`as_decoder`] or
+    /// [`into_decoder`] instead.
+    ///
+    /// [`as_decoder`]: RangeEncoder::as_decoder
     ///
     /// [`into_decoder`]: Self::into_decoder
     pub fn decoder(
@@ -457,6 +634,43 @@ where
             debug_assert!(word.is_some());
         }
     }
+
+    /// Records the current end of the compressed data and the full internal state so that
+    /// a failed batch of `encode` operations can be undone with [`rollback`].
+    ///
+    /// This works because encoding only ever appends to `bulk`: words that are still
+    /// subject to carry propagation are held back in `carry_buffer` rather than being written.
+    ///
+    /// [`rollback`]: Self::rollback
+    #[cfg(feature = "pybindings")]
+    pub(crate) fn checkpoint(&self) -> RangeEncoderCheckpoint<Word, State> {
+        RangeEncoderCheckpoint {
+            len: self.bulk.len(),
+            state: self.state,
+            carry_buffer: self.carry_buffer,
+            carry_stats: self.carry_stats,
+        }
+    }
+
+    /// Restores the encoder to a [`checkpoint`](Self::checkpoint) taken earlier on the same
+    /// encoder, discarding everything that was encoded since.
+    #[cfg(feature = "pybindings")]
+    pub(crate) fn rollback(&mut self, checkpoint: RangeEncoderCheckpoint<Word, State>) {
+        debug_assert!(checkpoint.len <= self.bulk.len());
+        self.bulk.truncate(checkpoint.len);
+        self.state = checkpoint.state;
+        self.carry_buffer = checkpoint.carry_buffer;
+        self.carry_stats = checkpoint.carry_stats;
+    }
+}
+
+#[cfg(feature = "pybindings")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RangeEncoderCheckpoint<Word: BitArray, State: BitArray> {
+    len: usize,
+    state: RangeCoderState<Word, State>,
+    carry_buffer: CarryBuffer<Word>,
+    carry_stats: CarryStats,
 }
 
 impl<Word, State, Backend, const PRECISION: usize> IntoDecoder<PRECISION>
@@ -510,30 +724,20 @@ where
             .lower
             .wrapping_add(&(scale * left_sided_cumulative.into().into()));
 
-        if let EncoderSituation::Inverted(num_inverted, first_inverted_lower_word) = self.situation
+        if self.carry_buffer != CarryBuffer::Empty
+            && new_lower.wrapping_add(&self.state.range.get()) > new_lower
         {
-            // unlikely branch
-            if new_lower.wrapping_add(&self.state.range.get()) > new_lower {
-                // We've transitioned from an inverted to a normal situation.
-
-                let (first_word, consecutive_words) = if new_lower < self.state.lower {
-                    (first_inverted_lower_word + Word::one(), Word::zero())
-                } else {
-                    (first_inverted_lower_word, Word::max_value())
-                };
-
-                self.bulk.write(first_word)?;
-                for _ in 1..num_inverted.get() {
-                    self.bulk.write(consecutive_words)?;
-                }
-
-                self.situation = EncoderSituation::Normal;
-            }
+            // Unlikely branch: the interval no longer straddles the overflow point, so we
+            // now know whether or not the held back words receive a carry.
+            let carry = new_lower < self.state.lower;
+            self.carry_buffer.write_resolved(carry, &mut self.bulk)?;
+            self.carry_buffer.clear();
+            self.carry_stats.num_carries += carry as u64;
         }
 
         self.state.lower = new_lower;
 
-        if self.state.range.get() < State::one() << (State::BITS - Word::BITS) {
+        if renorm::needs_renorm::<Word, State>(self.state.range.get()) {
             // Invariant `range >= State::one() << (State::BITS - Word::BITS)` is
             // violated. Since `left_cumulative_and_probability` succeeded, we know that
             // `probability != 0` and therefore:
@@ -550,20 +754,18 @@ where
                 (self.state.range.get() << Word::BITS).into_nonzero_unchecked()
             };
 
-            let lower_word = (self.state.lower >> (State::BITS - Word::BITS)).as_();
-            self.state.lower = self.state.lower << Word::BITS;
-
-            if let EncoderSituation::Inverted(num_inverted, _) = &mut self.situation {
-                // Transition from an inverted to an inverted situation (TODO: mark as unlikely branch).
-                *num_inverted = NonZeroUsize::new(num_inverted.get().wrapping_add(1))
-                    .expect("Cannot encode more symbols than what's addressable with usize.");
-            } else if self.state.lower.wrapping_add(&self.state.range.get()) > self.state.lower {
-                // Transition from a normal to a normal situation (the most common case).
-                self.bulk.write(lower_word)?;
-            } else {
-                // Transition from a normal to an inverted situation.
-                self.situation =
-                    EncoderSituation::Inverted(NonZeroUsize::new(1).expect("1 != 0"), lower_word);
+            let (lower_word, lower) = renorm::split_high(self.state.lower);
+            self.state.lower = lower;
+            let may_carry = lower.wrapping_add(&self.state.range.get()) <= lower;
+            self.carry_buffer
+                .push(lower_word, may_carry, &mut self.bulk)?;
+
+            let num_held_back = self.carry_buffer.len();
+            if num_held_back != 0 {
+                let stats = &mut self.carry_stats;
+                stats.num_inversions += (num_held_back == 1) as u64;
+                stats.num_held_back_words += 1;
+                stats.max_held_back_words = stats.max_held_back_words.max(num_held_back);
             }
         }
 
@@ -588,6 +790,11 @@ where
 
     /// Invariant: `point.wrapping_sub(&state.lower) < state.range`
     point: State,
+
+    /// Number of zero words that the decoder padded the compressed data with because it
+    /// tried to read past the end of `bulk`. Needed to make [`Pos::pos`] on a decoder
+    /// consistent with the positions recorded by the encoder.
+    num_words_past_end: usize,
 }
 
 /// Type alias for a [`RangeDecoder`] with sane parameters for typical use cases.
@@ -612,6 +819,23 @@ pub type DefaultRangeDecoder<Backend = Cursor<u32, Vec<u32>>> = RangeDecoder<u32
 /// [`SmallNonContiguousLookupDecoderModel`]: super::model::SmallNonContiguousLookupDecoderModel
 pub type SmallRangeDecoder<Backend> = RangeDecoder<u16, u32, Backend>;
 
+/// Type alias for a [`RangeDecoder`] with 64 bit words and a 128 bit internal state
+///
+/// Decodes data that was encoded with a [`WideRangeEncoder`].
+pub type WideRangeDecoder<Backend = Cursor<u64, Vec<u64>>> = RangeDecoder<u64, u128, Backend>;
+
+/// Type alias for a [`RangeDecoder`] that reads its compressed data from a stream of bytes
+///
+/// Decodes data that was encoded with a [`ByteRangeEncoder`]. Use
+/// `ByteRangeDecoder::<Cursor<u8, &[u8]>>::from_compressed(bytes)` to decode directly from
+/// a borrowed byte slice (e.g., from a section of a larger container format).
+pub type ByteRangeDecoder<Backend = Cursor<u8, Vec<u8>>> = RangeDecoder<u8, u32, Backend>;
+
+/// Type alias for a [`RangeDecoder`] with 8 bit words and a 64 bit internal state
+///
+/// Decodes data that was encoded with a [`ByteRangeEncoder64`].
+pub type ByteRangeDecoder64<Backend = Cursor<u8, Vec<u8>>> = RangeDecoder<u8, u64, Backend>;
+
 impl<Word, State, Backend> RangeDecoder<Word, State, Backend>
 where
     Word: BitArray + Into<State>,
@@ -626,12 +850,13 @@ where
         assert_eq!(State::BITS % Word::BITS, 0);
 
         let mut bulk = compressed.into_read_words();
-        let point = Self::read_point(&mut bulk)?;
+        let (point, num_words_past_end) = Self::read_point(&mut bulk)?;
 
         Ok(RangeDecoder {
             bulk,
             state: RangeCoderState::default(),
             point,
+            num_words_past_end,
         })
     }
 
@@ -640,12 +865,13 @@ where
         assert_eq!(State::BITS % Word::BITS, 0);
 
         let mut bulk = backend;
-        let point = Self::read_point(&mut bulk)?;
+        let (point, num_words_past_end) = Self::read_point(&mut bulk)?;
 
         Ok(RangeDecoder {
             bulk,
             state: RangeCoderState::default(),
             point,
+            num_words_past_end,
         })
     }
 
@@ -657,12 +883,13 @@ where
         assert_eq!(State::BITS % Word::BITS, 0);
 
         let mut bulk = compressed.as_read_words();
-        let point = Self::read_point(&mut bulk)?;
+        let (point, num_words_past_end) = Self::read_point(&mut bulk)?;
 
         Ok(RangeDecoder {
             bulk,
             state: RangeCoderState::default(),
             point,
+            num_words_past_end,
         })
     }
 
@@ -680,27 +907,37 @@ where
         (self.bulk, self.state)
     }
 
-    fn read_point<B: ReadWords<Word, Queue>>(bulk: &mut B) -> Result<State, B::ReadError> {
+    /// Returns the statistics gathered by the backend, e.g., by a [`CountingWords`].
+    ///
+    /// See [`RangeEncoder::backend_stats`] for an example.
+    ///
+    /// [`CountingWords`]: crate::backends::CountingWords
+    pub fn backend_stats(&self) -> BackendStats
+    where
+        Backend: ReportStats,
+    {
+        self.bulk.stats()
+    }
+
+    /// Reads the initial `point`, padding the compressed data with zero words if it ends
+    /// early. Returns the `point` and the number of padding words.
+    fn read_point<B: ReadWords<Word, Queue>>(bulk: &mut B) -> Result<(State, usize), B::ReadError> {
         let mut num_read = 0;
         let mut point = State::zero();
         while let Some(word) = bulk.read()? {
-            point = point << Word::BITS | word.into();
+            point = renorm::shift_in_low(point, word);
             num_read += 1;
             if num_read == State::BITS / Word::BITS {
                 break;
             }
         }
 
-        #[allow(clippy::collapsible_if)]
-        if num_read < State::BITS / Word::BITS {
-            if num_read != 0 {
-                point = point << (State::BITS - num_read * Word::BITS);
-            }
-            // TODO: do we need to advance the Backend's `pos` beyond the end to make
-            // `PosBackend` consistent with its implementation for the encoder?
+        let num_words_past_end = State::BITS / Word::BITS - num_read;
+        if num_words_past_end != 0 && num_read != 0 {
+            point = point << (State::BITS - num_read * Word::BITS);
         }
 
-        Ok(point)
+        Ok((point, num_words_past_end))
     }
 
     /// Same as `Decoder::maybe_exhausted`, but can be called on a concrete type without
@@ -709,7 +946,7 @@ where
         // The maximum possible difference between `point` and `lower`, even if the
         // compressed data was concatenated with a lot of one bits.
         let max_difference =
-            ((State::one() << (State::BITS - Word::BITS)) << 1).wrapping_sub(&State::one());
+            (renorm::lower_bound::<Word, State>() << 1).wrapping_sub(&State::one());
 
         // The check for `self.state.range == State::max_value()` is for the special case of
         // an empty buffer.
@@ -717,6 +954,216 @@ where
             && (self.state.range.get() == State::max_value()
                 || self.point.wrapping_sub(&self.state.lower) < max_difference)
     }
+
+    /// Decodes a symbol unless this would require compressed data beyond the end of the
+    /// data (only checked if `strict` is `true`), in which case it returns `Ok(None)`
+    /// without modifying `self`.
+    ///
+    /// Shared implementation of [`Decode::decode_symbol`] and
+    /// [`StrictDecode::decode_symbol_within_data`](super::strict::StrictDecode::decode_symbol_within_data).
+    #[inline(always)]
+    fn decode_symbol_within_data<D, const PRECISION: usize>(
+        &mut self,
+        model: D,
+        strict: bool,
+    ) -> Result<Option<D::Symbol>, CoderError<DecoderFrontendError, Backend::ReadError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Word>,
+        Word: AsPrimitive<D::Probability>,
+    {
+        // We maintain the following invariant (*):
+        //   point (-) lower < range
+        // where (-) denotes wrapping subtraction (in `Self::State`).
+
+        let scale = self.state.range.get() >> PRECISION;
+        let quantile = self.point.wrapping_sub(&self.state.lower) / scale;
+        if quantile >= State::one() << PRECISION {
+            return Err(CoderError::Frontend(DecoderFrontendError::InvalidData));
+        }
+
+        let (symbol, left_sided_cumulative, probability) =
+            model.quantile_function(quantile.as_().as_());
+        let range = scale * probability.get().into().into();
+
+        if strict {
+            // A sealed message never makes the decoder pad more than `STATE_WORDS - 1`
+            // zero words (see `RangeEncoder::seal`), so reading past that point means that
+            // we've run out of compressed data. Check before mutating any state so that a
+            // failed call leaves the decoder unchanged.
+            let max_words_past_end = State::BITS / Word::BITS - 1;
+            let num_words_past_end =
+                if renorm::needs_renorm::<Word, State>(range) && self.bulk.maybe_exhausted() {
+                    self.num_words_past_end + 1
+                } else {
+                    self.num_words_past_end
+                };
+            if num_words_past_end > max_words_past_end {
+                return Ok(None);
+            }
+        }
+
+        // Update `state` in the same way as we do in `encode_symbol` (see comments there):
+        self.state.lower = self
+            .state
+            .lower
+            .wrapping_add(&(scale * left_sided_cumulative.into().into()));
+        self.state.range = range.into_nonzero().expect(
+            "`scale` and `probability` are both nonzero and their product doesn't overflow",
+        );
+
+        // Invariant (*) is still satisfied at this point because:
+        //   (point (-) lower) / scale = (point (-) old_lower) / scale (-) left_sided_cumulative
+        //                             = quantile (-) left_sided_cumulative
+        //                             < probability
+        // Therefore, we have:
+        //   point (-) lower < scale * probability <= range
+
+        if renorm::needs_renorm::<Word, State>(self.state.range.get()) {
+            // First update `state` in the same way as we do in `encode_symbol`:
+            self.state.lower = self.state.lower << Word::BITS;
+            self.state.range = unsafe {
+                // SAFETY:
+                // - `range` is nonzero because it is a `State::NonZero`
+                // - Shifting `range` left by `Word::BITS` bits doesn't truncate
+                //   because we checked that `range < 1 << (State::BITS - Word::Bits)`.
+                (self.state.range.get() << Word::BITS).into_nonzero_unchecked()
+            };
+
+            // Then update `point`, which restores invariant (*):
+            let word = match self.bulk.read()? {
+                Some(word) => word,
+                None => {
+                    self.num_words_past_end += 1;
+                    Word::zero()
+                }
+            };
+            self.point = renorm::shift_in_low(self.point, word);
+        }
+
+        Ok(Some(symbol))
+    }
+}
+
+impl<Word, State, Buf> RangeDecoder<Word, State, Cursor<Word, Buf>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Buf: AsRef<[Word]> + Clone,
+{
+    /// Returns a new decoder that reads from the same compressed data as `self`.
+    ///
+    /// The returned decoder starts out at the same position and state as `self` but is
+    /// otherwise independent of it, i.e., decoding from (or [`seek`]ing) either decoder
+    /// does not affect the other one. Forking is cheap if cloning `Buf` is cheap. In
+    /// particular, if you construct the original decoder from an `Arc<[Word]>` then all
+    /// forks share a single immutable buffer and can be sent to different threads, e.g.,
+    /// to decode independent chunks of a long message in parallel.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{
+    ///         model::DefaultLeakyQuantizer,
+    ///         queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+    ///         Decode, Encode,
+    ///     },
+    ///     Pos, Seek,
+    /// };
+    /// use std::sync::Arc;
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+    /// let model = quantizer.quantize(probability::distribution::Gaussian::new(0.0, 10.0));
+    ///
+    /// // Encode two chunks and remember where the second chunk starts.
+    /// let mut encoder = DefaultRangeEncoder::new();
+    /// encoder.encode_iid_symbols(&[7, -3, 12], &model).unwrap();
+    /// let second_chunk = encoder.pos();
+    /// encoder.encode_iid_symbols(&[-20, 5], &model).unwrap();
+    /// let compressed: Arc<[u32]> = encoder.into_compressed().unwrap().into();
+    ///
+    /// let mut decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+    /// let mut fork = decoder.fork();
+    /// let handle = std::thread::spawn(move || {
+    ///     fork.seek(second_chunk).unwrap();
+    ///     fork.decode_iid_symbols(2, &model).collect::<Result<Vec<_>, _>>().unwrap()
+    /// });
+    ///
+    /// let first = decoder.decode_iid_symbols(3, &model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(first.unwrap(), [7, -3, 12]);
+    /// assert_eq!(handle.join().unwrap(), [-20, 5]);
+    /// ```
+    ///
+    /// [`seek`]: crate::Seek::seek
+    pub fn fork(&self) -> Self {
+        RangeDecoder {
+            bulk: self.bulk.clone(),
+            state: self.state,
+            point: self.point,
+            num_words_past_end: self.num_words_past_end,
+        }
+    }
+}
+
+impl<Word, State> RangeDecoder<Word, State, Cursor<Word, Vec<Word>>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    /// Verifies and strips off the checksum appended by
+    /// [`RangeEncoder::seal_with_checksum`] and creates a decoder for the remaining
+    /// compressed data.
+    ///
+    /// See module [`checksum`] for an example.
+    pub fn from_checksummed(compressed: Vec<Word>) -> Result<Self, ChecksumError> {
+        let compressed = checksum::verify_checksum(compressed)?;
+        Ok(Self::from_compressed(compressed).unwrap_infallible())
+    }
+}
+
+impl<Word, State, Buf> RangeDecoder<Word, State, Cursor<Word, Buf>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Buf: AsRef<[Word]>,
+{
+    /// Records the read position and full internal state so that a failed batch of
+    /// `decode` operations can be undone with [`rollback`](Self::rollback).
+    ///
+    /// Unlike [`Seek::seek`], restoring from a checkpoint doesn't need to re-read the
+    /// current `point` from the compressed data, so it also works at the very end of the
+    /// data.
+    #[cfg(feature = "pybindings")]
+    pub(crate) fn checkpoint(&self) -> RangeDecoderCheckpoint<Word, State> {
+        RangeDecoderCheckpoint {
+            pos: self.bulk.pos(),
+            state: self.state,
+            point: self.point,
+            num_words_past_end: self.num_words_past_end,
+        }
+    }
+
+    /// Restores the decoder to a [`checkpoint`](Self::checkpoint) taken earlier on the same
+    /// decoder.
+    #[cfg(feature = "pybindings")]
+    pub(crate) fn rollback(&mut self, checkpoint: RangeDecoderCheckpoint<Word, State>) {
+        self.bulk
+            .seek(checkpoint.pos)
+            .expect("Checkpoint was taken on the same buffer.");
+        self.state = checkpoint.state;
+        self.point = checkpoint.point;
+        self.num_words_past_end = checkpoint.num_words_past_end;
+    }
+}
+
+#[cfg(feature = "pybindings")]
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RangeDecoderCheckpoint<Word: BitArray, State: BitArray> {
+    pos: usize,
+    state: RangeCoderState<Word, State>,
+    point: State,
+    num_words_past_end: usize,
 }
 
 impl<Word, State, Backend> Code for RangeDecoder<Word, State, Backend>
@@ -744,20 +1191,58 @@ where
     type Position = (Backend::Position, <Self as Code>::State);
 }
 
+impl<Word, State, Backend> Pos for RangeDecoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Queue> + Pos<Position = usize>,
+{
+    /// Returns the position and state in the same format as [`RangeEncoder::pos`].
+    ///
+    /// After decoding a sequence of symbols, the decoder returns the same position and
+    /// state that the encoder returned after encoding the same symbols. This also holds
+    /// close to the end of the compressed data, where the decoder reads past the end: such
+    /// (virtual) reads count towards the position as if the compressed data were padded
+    /// with zero words. The position therefore doesn't necessarily agree with the position
+    /// of the backend, which lags behind at the end of the data and is otherwise
+    /// `State::BITS / Word::BITS` words ahead since the decoder has to look ahead.
+    ///
+    /// [`RangeEncoder::pos`]: crate::Pos::pos
+    fn pos(&self) -> Self::Position {
+        let pos = self
+            .bulk
+            .pos()
+            .checked_add(self.num_words_past_end)
+            .expect("position overflows addressable space")
+            - State::BITS / Word::BITS;
+        (pos, self.state())
+    }
+}
+
 impl<Word, State, Backend> Seek for RangeDecoder<Word, State, Backend>
 where
     Word: BitArray + Into<State>,
     State: BitArray + AsPrimitive<Word>,
     Backend: ReadWords<Word, Queue> + Seek,
 {
+    /// Jumps to a position and state that were recorded by calling [`Pos::pos`](crate::Pos::pos)
+    /// on a [`RangeEncoder`].
+    ///
+    /// See [`Seek::seek`](crate::Seek::seek) for the guarantees close to the end of the
+    /// compressed data. If the backend returns a read error after it has already been
+    /// repositioned, then this method returns `Err(())` but leaves the decoder at the new
+    /// position with an unspecified state.
     fn seek(&mut self, pos_and_state: Self::Position) -> Result<(), ()> {
         let (pos, state) = pos_and_state;
 
         self.bulk.seek(pos)?;
-        self.point = Self::read_point(&mut self.bulk).map_err(|_| ())?;
-        self.state = state;
 
-        // TODO: deal with positions very close to end.
+        // If fewer than `State::BITS / Word::BITS` words remain, then `read_point` pads
+        // `point` with zero words, just like `decode_symbol` does when it reads past the end.
+        let (point, num_words_past_end) = Self::read_point(&mut self.bulk).map_err(|_| ())?;
+        self.point = point;
+        self.num_words_past_end = num_words_past_end;
+        self.state = state;
 
         Ok(())
     }
@@ -778,18 +1263,18 @@ where
     }
 }
 
-// TODO (implement for infallible case)
-// impl<'a, Word, State, Backend> From<&'a mut RangeEncoder<Word, State, Backend>>
-//     for RangeDecoder<Word, State, Backend::AsReadWords>
-// where
-//     Word: BitArray + Into<State>,
-//     State: BitArray + AsPrimitive<Word>,
-//     Backend: WriteWords<Word> + AsReadWords<'a, Word, Queue>,
-// {
-//     fn from(encoder: &'a mut RangeEncoder<Word, State, Backend>) -> Self {
-//         encoder.as_decoder()
-//     }
-// }
+impl<'a, Word, State, Backend, Inner> From<&'a RangeEncoder<Word, State, Backend>>
+    for RangeDecoder<Word, State, SealedReadWords<Word, Inner>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word> + AsReadWords<'a, Word, Queue, AsReadWords = Inner>,
+    Inner: ReadWords<Word, Queue, ReadError = Infallible>,
+{
+    fn from(encoder: &'a RangeEncoder<Word, State, Backend>) -> Self {
+        encoder.as_decoder().unwrap_infallible()
+    }
+}
 
 impl<Word, State, Backend, const PRECISION: usize> Decode<PRECISION>
     for RangeDecoder<Word, State, Backend>
@@ -826,60 +1311,156 @@ where
         D::Probability: Into<Self::Word>,
         Self::Word: AsPrimitive<D::Probability>,
     {
-        // We maintain the following invariant (*):
-        //   point (-) lower < range
-        // where (-) denotes wrapping subtraction (in `Self::State`).
+        let symbol = self.decode_symbol_within_data::<D, PRECISION>(model, false)?;
+        Ok(symbol.expect("Decoding past the end of the data can't fail in permissive mode."))
+    }
 
-        let scale = self.state.range.get() >> PRECISION;
-        let quantile = self.point.wrapping_sub(&self.state.lower) / scale;
-        if quantile >= State::one() << PRECISION {
-            return Err(CoderError::Frontend(DecoderFrontendError::InvalidData));
-        }
+    fn maybe_exhausted(&self) -> bool {
+        RangeDecoder::maybe_exhausted(self)
+    }
+}
 
-        let (symbol, left_sided_cumulative, probability) =
-            model.quantile_function(quantile.as_().as_());
+impl<Word, State, Backend, const PRECISION: usize> StrictDecode<PRECISION>
+    for RangeDecoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Queue>,
+{
+    /// Decodes a symbol unless this would require reading more compressed words than a
+    /// [`RangeEncoder`] could have omitted when it sealed the data.
+    #[inline(always)]
+    fn decode_symbol_within_data<D>(
+        &mut self,
+        model: D,
+    ) -> Result<Option<D::Symbol>, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<D::Probability>,
+    {
+        RangeDecoder::decode_symbol_within_data::<D, PRECISION>(self, model, true)
+    }
+}
 
-        // Update `state` in the same way as we do in `encode_symbol` (see comments there):
-        self.state.lower = self
+/// A source of compressed words that reads from the backend of a [`RangeEncoder`] and then
+/// appends the words that sealing the encoder would write.
+///
+/// Returned (wrapped in a [`RangeDecoder`]) by [`RangeEncoder::as_decoder`]. The words
+/// that sealing appends consist of any words that the encoder holds back because they may
+/// still receive a carry, followed by one or two words that identify the final range.
+#[derive(Debug, Clone)]
+pub struct SealedReadWords<Word, Inner> {
+    inner: Inner,
+
+    /// Words that the encoder holds back, already resolved (first word, followed by
+    /// `num_consecutive_words` copies of `consecutive_word`).
+    first_held_back: Option<Word>,
+    consecutive_word: Word,
+    num_consecutive_words: usize,
+
+    /// The final words, read from `tail[tail_pos..tail_len]`.
+    tail: [Word; 2],
+    tail_pos: u8,
+    tail_len: u8,
+}
+
+impl<Word: BitArray, Inner> SealedReadWords<Word, Inner> {
+    fn new<'a, State, Backend>(encoder: &'a RangeEncoder<Word, State, Backend>) -> Self
+    where
+        Word: Into<State>,
+        State: BitArray + AsPrimitive<Word>,
+        Backend: WriteWords<Word> + AsReadWords<'a, Word, Queue, AsReadWords = Inner>,
+    {
+        let mut sealed = Self {
+            inner: encoder.bulk.as_read_words(),
+            first_held_back: None,
+            consecutive_word: Word::zero(),
+            num_consecutive_words: 0,
+            tail: [Word::zero(); 2],
+            tail_pos: 0,
+            tail_len: 0,
+        };
+
+        if encoder.state.range.get() == State::max_value() {
+            // Sealing an empty encoder is a no-op (see `RangeEncoder::seal`).
+            return sealed;
+        }
+
+        let point = encoder
             .state
             .lower
-            .wrapping_add(&(scale * left_sided_cumulative.into().into()));
-        self.state.range = (scale * probability.get().into().into())
-            .into_nonzero()
-            .expect("TODO");
+            .wrapping_add(&(renorm::lower_bound::<Word, State>() - State::one()));
+
+        if let CarryBuffer::Pending(num_words, first_word) = encoder.carry_buffer {
+            // The addition above wraps around in the unlikely case that held back words
+            // receive a carry.
+            let carry = point < encoder.state.lower;
+            sealed.num_consecutive_words = num_words.get() - 1;
+            if carry {
+                sealed.first_held_back = Some(first_word + Word::one());
+            } else {
+                sealed.first_held_back = Some(first_word);
+                sealed.consecutive_word = Word::max_value();
+            }
+        }
 
-        // Invariant (*) is still satisfied at this point because:
-        //   (point (-) lower) / scale = (point (-) old_lower) / scale (-) left_sided_cumulative
-        //                             = quantile (-) left_sided_cumulative
-        //                             < probability
-        // Therefore, we have:
-        //   point (-) lower < scale * probability <= range
+        let point_word = renorm::high_word::<Word, State>(point);
+        let upper_word =
+            renorm::high_word(encoder.state.lower.wrapping_add(&encoder.state.range.get()));
+        sealed.tail[0] = point_word;
+        sealed.tail_len = if upper_word == point_word { 2 } else { 1 };
 
-        if self.state.range.get() < State::one() << (State::BITS - Word::BITS) {
-            // First update `state` in the same way as we do in `encode_symbol`:
-            self.state.lower = self.state.lower << Word::BITS;
-            self.state.range = unsafe {
-                // SAFETY:
-                // - `range` is nonzero because it is a `State::NonZero`
-                // - Shifting `range` left by `Word::BITS` bits doesn't truncate
-                //   because we checked that `range < 1 << (State::BITS - Word::Bits)`.
-                (self.state.range.get() << Word::BITS).into_nonzero_unchecked()
-            };
+        sealed
+    }
 
-            // Then update `point`, which restores invariant (*):
-            self.point = self.point << Word::BITS;
-            if let Some(word) = self.bulk.read()? {
-                self.point = self.point | word.into();
-            }
+    fn num_sealing_words(&self) -> usize {
+        self.first_held_back.is_some() as usize
+            + self.num_consecutive_words
+            + (self.tail_len - self.tail_pos) as usize
+    }
+}
+
+impl<Word, Inner> ReadWords<Word, Queue> for SealedReadWords<Word, Inner>
+where
+    Word: BitArray,
+    Inner: ReadWords<Word, Queue>,
+{
+    type ReadError = Inner::ReadError;
 
-            // TODO: register reads past end?
+    #[inline(always)]
+    fn read(&mut self) -> Result<Option<Word>, Self::ReadError> {
+        if let Some(word) = self.inner.read()? {
+            return Ok(Some(word));
         }
 
-        Ok(symbol)
+        if let Some(word) = self.first_held_back.take() {
+            Ok(Some(word))
+        } else if self.num_consecutive_words != 0 {
+            self.num_consecutive_words -= 1;
+            Ok(Some(self.consecutive_word))
+        } else if self.tail_pos < self.tail_len {
+            self.tail_pos += 1;
+            Ok(Some(self.tail[self.tail_pos as usize - 1]))
+        } else {
+            Ok(None)
+        }
     }
 
+    #[inline(always)]
     fn maybe_exhausted(&self) -> bool {
-        RangeDecoder::maybe_exhausted(self)
+        self.inner.maybe_exhausted() && self.num_sealing_words() == 0
+    }
+}
+
+impl<Word, Inner> BoundedReadWords<Word, Queue> for SealedReadWords<Word, Inner>
+where
+    Word: BitArray,
+    Inner: BoundedReadWords<Word, Queue>,
+{
+    #[inline(always)]
+    fn remaining(&self) -> usize {
+        self.inner.remaining() + self.num_sealing_words()
     }
 }
 
@@ -956,10 +1537,14 @@ mod tests {
     extern crate std;
     use std::dbg;
 
-    use super::super::model::{
-        ContiguousCategoricalEntropyModel, IterableEntropyModel, LeakyQuantizer,
+    use super::super::{
+        model::{
+            ContiguousCategoricalEntropyModel, IterableEntropyModel, LeakyQuantizer, UniformModel,
+        },
+        InvalidSymbolsError, TryCodingError,
     };
     use super::*;
+    use crate::backends::CountingWords;
 
     use probability::distribution::{Gaussian, Inverse};
     use rand_xoshiro::{
@@ -1013,7 +1598,7 @@ mod tests {
         let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-127..=127);
         let model = quantizer.quantize(Gaussian::new(3.2, 5.1));
 
-        encoder.encode_iid_symbols(symbols.clone(), &model).unwrap();
+        encoder.encode_iid_symbols(symbols.clone(), model).unwrap();
         let compressed = encoder.into_compressed().unwrap();
         assert_eq!(compressed.len(), expected_size);
 
@@ -1024,6 +1609,18 @@ mod tests {
         assert!(decoder.maybe_exhausted());
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn compress_many_u64_u128_32() {
+        generic_compress_many::<u64, u128, u32, 32>();
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn compress_many_u64_u128_24() {
+        generic_compress_many::<u64, u128, u32, 24>();
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn compress_many_u32_u64_32() {
@@ -1102,6 +1699,259 @@ mod tests {
         generic_compress_many::<u8, u16, u8, 8>();
     }
 
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn adversarial_carries_u64_u128_32() {
+        generic_adversarial_carries::<u64, u128, u32, 32>(7439);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn adversarial_carries_u32_u64_24() {
+        generic_adversarial_carries::<u32, u64, u32, 24>(7440);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn adversarial_carries_u16_u32_12() {
+        generic_adversarial_carries::<u16, u32, u16, 12>(7441);
+    }
+
+    #[test]
+    fn wide_coder_with_high_precision() {
+        let categorical = ContiguousCategoricalEntropyModel::<u64, Vec<u64>, 48>::from_nonzero_fixed_point_probabilities(
+            [1, 3, (1 << 40) - 1, (1 << 47) + 12345],
+            true,
+        )
+        .unwrap();
+        let uniform = UniformModel::<u64, 48>::new((1 << 45) + 17);
+        let mut rng = Xoshiro256StarStar::seed_from_u64(42);
+        let categorical_symbols = (0..1000)
+            .map(|_| categorical.quantile_function(rng.next_u64() >> 16).0)
+            .collect::<Vec<_>>();
+        let uniform_symbols = (0..1000)
+            .map(|_| (rng.next_u64() >> 19) % ((1 << 45) + 17))
+            .collect::<Vec<_>>();
+
+        let mut encoder = WideRangeEncoder::new();
+        for (categorical_symbol, uniform_symbol) in categorical_symbols.iter().zip(&uniform_symbols)
+        {
+            encoder
+                .encode_symbol(categorical_symbol, &categorical)
+                .unwrap();
+            encoder.encode_symbol(uniform_symbol, uniform).unwrap();
+        }
+        let compressed = encoder.into_compressed().unwrap();
+
+        let mut decoder = WideRangeDecoder::from_compressed(compressed).unwrap();
+        for (&categorical_symbol, &uniform_symbol) in
+            categorical_symbols.iter().zip(&uniform_symbols)
+        {
+            assert_eq!(
+                decoder.decode_symbol(&categorical).unwrap(),
+                categorical_symbol
+            );
+            assert_eq!(decoder.decode_symbol(uniform).unwrap(), uniform_symbol);
+        }
+        assert!(decoder.maybe_exhausted());
+    }
+
+    #[test]
+    fn byte_coder_in_container() {
+        let categorical =
+            ContiguousCategoricalEntropyModel::<u8, Vec<u8>, 8>::from_floating_point_probabilities(
+                &[0.1, 0.4, 0.05, 0.3, 0.15],
+            )
+            .unwrap();
+        let quantizer = LeakyQuantizer::<f64, i32, u8, 8>::new(-20..=20);
+        let mut rng = Xoshiro256StarStar::seed_from_u64(5);
+        let symbols = (0..500)
+            .map(|_| {
+                let categorical_symbol = categorical.quantile_function(rng.next_u32() as u8).0;
+                let gaussian_symbol = (rng.next_u32() % 41) as i32 - 20;
+                (categorical_symbol, gaussian_symbol)
+            })
+            .collect::<Vec<_>>();
+
+        let mut encoder = ByteRangeEncoder::new();
+        for &(categorical_symbol, gaussian_symbol) in &symbols {
+            encoder
+                .encode_symbol(categorical_symbol, &categorical)
+                .unwrap();
+            encoder
+                .encode_symbol(
+                    gaussian_symbol,
+                    quantizer.quantize(Gaussian::new(0.0, 10.0)),
+                )
+                .unwrap();
+        }
+        let compressed: Vec<u8> = encoder.into_compressed().unwrap();
+
+        // Embed the compressed data in a container with a header and a trailer.
+        let mut container = b"HEADER".to_vec();
+        container.extend_from_slice(&compressed);
+        container.extend_from_slice(b"TRAILER");
+        let section = &container[6..6 + compressed.len()];
+
+        let mut decoder = ByteRangeDecoder::<Cursor<u8, &[u8]>>::from_compressed(section).unwrap();
+        for &(categorical_symbol, gaussian_symbol) in &symbols {
+            assert_eq!(
+                decoder.decode_symbol(&categorical).unwrap(),
+                categorical_symbol
+            );
+            assert_eq!(
+                decoder
+                    .decode_symbol(quantizer.quantize(Gaussian::new(0.0, 10.0)))
+                    .unwrap(),
+                gaussian_symbol
+            );
+        }
+        assert!(decoder.maybe_exhausted());
+
+        let mut decoder = ByteRangeDecoder::from_compressed(compressed).unwrap();
+        assert_eq!(decoder.decode_symbol(&categorical).unwrap(), symbols[0].0);
+    }
+
+    #[test]
+    fn byte_coder_with_64_bit_state() {
+        let categorical =
+            ContiguousCategoricalEntropyModel::<u8, Vec<u8>, 8>::from_floating_point_probabilities(
+                &[0.1, 0.4, 0.05, 0.3, 0.15],
+            )
+            .unwrap();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(782);
+        let symbols = (0..1000)
+            .map(|_| categorical.quantile_function(rng.next_u32() as u8).0)
+            .collect::<Vec<_>>();
+
+        let mut encoder = ByteRangeEncoder64::new();
+        encoder.encode_iid_symbols(&symbols, &categorical).unwrap();
+        let compressed: Vec<u8> = encoder.into_compressed().unwrap();
+
+        let mut decoder = ByteRangeDecoder64::from_compressed(compressed).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), &categorical)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.maybe_exhausted());
+    }
+
+    /// Steers the encoder into long inversions by repeatedly choosing the half of the
+    /// current interval that contains a point at which a carry would occur.
+    #[test]
+    fn as_decoder_with_generic_backend() {
+        let model = ContiguousCategoricalEntropyModel::<u32, Vec<u32>, 24>::from_floating_point_probabilities(
+            &[0.2, 0.5, 0.01, 0.29],
+        )
+        .unwrap();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let symbols = (0..1000)
+            .map(|_| model.quantile_function(rng.next_u32() >> 8).0)
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultRangeEncoder::with_backend(CountingWords::new(Vec::new()));
+        assert!(encoder.as_decoder().unwrap().maybe_exhausted());
+        assert_eq!(SealedReadWords::new(&encoder).remaining(), 0);
+
+        for (i, &symbol) in symbols.iter().enumerate() {
+            encoder.encode_symbol(symbol, &model).unwrap();
+            if i % 100 == 17 {
+                assert_eq!(
+                    SealedReadWords::new(&encoder).remaining(),
+                    encoder.num_words()
+                );
+                let mut decoder: DefaultRangeDecoder<_> = (&encoder).into();
+                for &expected in &symbols[..=i] {
+                    assert_eq!(decoder.decode_symbol(&model).unwrap(), expected);
+                }
+                assert!(decoder.maybe_exhausted());
+            }
+        }
+
+        let num_words = encoder.num_words();
+        let compressed = encoder.into_compressed().unwrap().into_inner();
+        assert_eq!(compressed.len(), num_words);
+    }
+
+    fn generic_adversarial_carries<Word, State, Probability, const PRECISION: usize>(seed: u64)
+    where
+        State: BitArray + AsPrimitive<Word>,
+        Word: BitArray + Into<State> + AsPrimitive<Probability>,
+        Probability: BitArray + Into<Word> + AsPrimitive<usize> + Into<f64>,
+        usize: AsPrimitive<Probability>,
+        f64: AsPrimitive<Probability>,
+    {
+        let model = ContiguousCategoricalEntropyModel::<Probability, Vec<Probability>, PRECISION>
+            ::from_floating_point_probabilities(&[0.5, 0.5])
+            .unwrap();
+        let (left_cumulative, _) = model.left_cumulative_and_probability(1).unwrap();
+        let boundary: State = Into::<Word>::into(left_cumulative).into();
+        let alignment = State::BITS - Word::BITS;
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(seed);
+        let mut encoder = RangeEncoder::<Word, State>::new();
+        let mut symbols = Vec::new();
+        let mut adversarial_steps = 0;
+        // Wider words take proportionally more symbols to shift through the state.
+        for _ in 0..20_000 * (State::BITS / 64).max(1) {
+            if adversarial_steps == 0 && rng.next_u32() % 16 == 0 {
+                adversarial_steps = rng.next_u32() % (20 * State::BITS as u32);
+            }
+
+            let state = encoder.state();
+            let (lower, range) = (state.lower(), state.range().get());
+            let overflow_offset = State::zero().wrapping_sub(&lower);
+            let aligned_offset =
+                (((lower >> alignment) + State::one()) << alignment).wrapping_sub(&lower);
+            let target_offset = if overflow_offset < range && overflow_offset != State::zero() {
+                Some(overflow_offset)
+            } else if aligned_offset < range {
+                // Once this point is shifted to the top, the interval straddles it.
+                Some(aligned_offset)
+            } else {
+                None
+            };
+
+            let symbol = match target_offset {
+                Some(offset) if adversarial_steps != 0 => {
+                    adversarial_steps -= 1;
+                    (offset >= (range >> PRECISION) * boundary) as usize
+                }
+                _ => {
+                    adversarial_steps = adversarial_steps.saturating_sub(1);
+                    (rng.next_u32() % 2) as usize
+                }
+            };
+            encoder.encode_symbol(symbol, &model).unwrap();
+            symbols.push(symbol);
+
+            if symbols.len() % 97 == 0 {
+                // Reading the sealed words without sealing has to resolve held back words
+                // exactly like sealing does.
+                let mut sealed = SealedReadWords::new(&encoder);
+                let mut words = Vec::with_capacity(sealed.remaining());
+                while let Some(word) = sealed.read().unwrap_infallible() {
+                    words.push(word);
+                }
+                assert_eq!(&words[..], &*encoder.get_compressed());
+            }
+        }
+
+        let stats = encoder.carry_stats();
+        assert!(stats.num_inversions > 20);
+        assert!(stats.num_carries > 5);
+        assert!(stats.num_inversions - stats.num_carries > 5);
+        assert!(stats.max_held_back_words >= 8);
+        assert!(stats.num_held_back_words > 2 * stats.num_inversions);
+
+        let mut decoder = encoder.into_decoder().unwrap();
+        for &symbol in &symbols {
+            assert_eq!(decoder.decode_symbol(&model).unwrap(), symbol);
+        }
+        assert!(decoder.maybe_exhausted());
+    }
+
     fn generic_compress_many<Word, State, Probability, const PRECISION: usize>()
     where
         State: BitArray + AsPrimitive<Word>,
@@ -1123,10 +1973,7 @@ mod tests {
             let std_dev = (10.0 / u32::MAX as f64) * rng.next_u32() as f64 + 0.001;
             let quantile = (rng.next_u32() as f64 + 0.5) / (1u64 << 32) as f64;
             let dist = Gaussian::new(mean, std_dev);
-            let symbol = core::cmp::max(
-                -127,
-                core::cmp::min(127, dist.inverse(quantile).round() as i32),
-            );
+            let symbol = (dist.inverse(quantile).round() as i32).clamp(-127, 127);
 
             symbols_gaussian.push(symbol);
             means.push(mean);
@@ -1219,10 +2066,10 @@ mod tests {
 
         let mut decoder = encoder.decoder();
 
-        // Verify we can decode the chunks normally (we can't veryify that coding and
-        // decoding lead to same `pos_and_state` because the range decoder currently doesn't
-        // implement `Pos` due to complications at the stream end.)
-        for (chunk, _) in symbols.iter().zip(&jump_table) {
+        // Verify we can decode the chunks normally and that encoding and decoding lead to
+        // the same `pos_and_state`.
+        for (chunk, &pos_and_state) in symbols.iter().zip(&jump_table) {
+            assert_eq!(decoder.pos(), pos_and_state);
             let decoded = decoder
                 .decode_iid_symbols(SYMBOLS_PER_CHUNK, &model)
                 .collect::<Result<Vec<_>, _>>()
@@ -1230,6 +2077,7 @@ mod tests {
             assert_eq!(&decoded, chunk);
         }
         assert!(decoder.maybe_exhausted());
+        assert_eq!(decoder.pos(), final_pos_and_state);
 
         // Seek to some random offsets in the jump table and decode one chunk
         for i in 0..100 {
@@ -1242,6 +2090,7 @@ mod tests {
 
             let pos_and_state = jump_table[chunk_index];
             decoder.seek(pos_and_state).unwrap();
+            assert_eq!(decoder.pos(), pos_and_state);
             let decoded = decoder
                 .decode_iid_symbols(SYMBOLS_PER_CHUNK, &model)
                 .collect::<Result<Vec<_>, _>>()
@@ -1254,6 +2103,210 @@ mod tests {
         assert!(!decoder.maybe_exhausted());
         decoder.seek(final_pos_and_state).unwrap();
         assert!(decoder.maybe_exhausted());
+        assert_eq!(decoder.pos(), final_pos_and_state);
+    }
+
+    #[test]
+    fn pos_with_cursor_backends() {
+        fn test<Word, State>(num_chunks: usize, symbols_per_chunk: usize)
+        where
+            Word: BitArray + Into<State> + AsPrimitive<u16>,
+            State: BitArray + AsPrimitive<Word>,
+            u16: Into<Word>,
+        {
+            let quantizer = LeakyQuantizer::<f64, i32, u16, 12>::new(-100..=100);
+            let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+            let mut rng = Xoshiro256StarStar::seed_from_u64(
+                (num_chunks * symbols_per_chunk + Word::BITS) as u64,
+            );
+
+            // Encode through a `Cursor` into a preallocated buffer.
+            let buf = alloc::vec![Word::zero(); 10_000];
+            let mut encoder =
+                RangeEncoder::<Word, State, _>::with_backend(Cursor::new_at_write_beginning(buf));
+            let mut symbols = Vec::with_capacity(num_chunks);
+            let mut jump_table = Vec::with_capacity(num_chunks);
+            for _ in 0..num_chunks {
+                jump_table.push(encoder.pos());
+                let chunk = (0..symbols_per_chunk)
+                    .map(|_| model.quantile_function(rng.next_u32() as u16 % (1 << 12)).0)
+                    .collect::<Vec<_>>();
+                encoder.encode_iid_symbols(&chunk, &model).unwrap();
+                symbols.push(chunk);
+            }
+            let final_pos_and_state = encoder.pos();
+            let (buf, len) = encoder.into_compressed().unwrap().into_buf_and_pos();
+
+            // Decode through a `Cursor` that reads from the same buffer.
+            let mut decoder = RangeDecoder::<Word, State, _>::with_backend(
+                Cursor::new_at_write_beginning(&buf[..len]),
+            )
+            .unwrap();
+            for (chunk, &pos_and_state) in symbols.iter().zip(&jump_table) {
+                assert_eq!(decoder.pos(), pos_and_state);
+                let decoded = decoder
+                    .decode_iid_symbols(symbols_per_chunk, &model)
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+                assert_eq!(&decoded, chunk);
+            }
+            assert_eq!(decoder.pos(), final_pos_and_state);
+
+            // Seeking in reverse order restores the same positions.
+            for (chunk, &pos_and_state) in symbols.iter().zip(&jump_table).rev() {
+                decoder.seek(pos_and_state).unwrap();
+                assert_eq!(decoder.pos(), pos_and_state);
+                let decoded = decoder
+                    .decode_iid_symbols(symbols_per_chunk, &model)
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+                assert_eq!(&decoded, chunk);
+            }
+            decoder.seek(final_pos_and_state).unwrap();
+            assert_eq!(decoder.pos(), final_pos_and_state);
+        }
+
+        // Very short messages make the decoder read past the end of the compressed data.
+        test::<u32, u64>(1, 1);
+        test::<u32, u64>(3, 2);
+        test::<u32, u64>(20, 100);
+        test::<u16, u32>(1, 1);
+        test::<u16, u32>(3, 2);
+        test::<u16, u32>(20, 100);
+        test::<u16, u64>(3, 2);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn fork() {
+        const NUM_CHUNKS: usize = 8;
+        const SYMBOLS_PER_CHUNK: usize = 100;
+
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+
+        let mut encoder = DefaultRangeEncoder::new();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let mut symbols = Vec::with_capacity(NUM_CHUNKS);
+        let mut jump_table = Vec::with_capacity(NUM_CHUNKS);
+
+        for _ in 0..NUM_CHUNKS {
+            jump_table.push(encoder.pos());
+            let chunk = (0..SYMBOLS_PER_CHUNK)
+                .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                .collect::<Vec<_>>();
+            encoder.encode_iid_symbols(&chunk, &model).unwrap();
+            symbols.push(chunk);
+        }
+
+        let compressed: std::sync::Arc<[u32]> = encoder.into_compressed().unwrap().into();
+        let mut decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+        let handles = jump_table
+            .iter()
+            .zip(&symbols)
+            .map(|(&pos_and_state, chunk)| {
+                let mut fork = decoder.fork();
+                let chunk = chunk.clone();
+                std::thread::spawn(move || {
+                    let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-100..=100);
+                    let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+                    fork.seek(pos_and_state).unwrap();
+                    let decoded = fork
+                        .decode_iid_symbols(SYMBOLS_PER_CHUNK, &model)
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap();
+                    assert_eq!(decoded, chunk);
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Decoding from the original decoder isn't affected by the forks.
+        for chunk in &symbols {
+            let decoded = decoder
+                .decode_iid_symbols(SYMBOLS_PER_CHUNK, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, chunk);
+        }
+        assert!(decoder.maybe_exhausted());
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn encode_symbols_validated() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-20..=20);
+        let means = (0..30).map(|i| i as f64 - 15.0).collect::<Vec<_>>();
+        let mut symbols = means.iter().map(|&mean| mean as i32).collect::<Vec<_>>();
+        let models = || {
+            means
+                .iter()
+                .map(|&mean| quantizer.quantize(Gaussian::new(mean, 3.0)))
+        };
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder
+            .encode_symbols_validated(symbols.iter().zip(models()))
+            .unwrap();
+        let mut decoder = encoder.decoder();
+        for (&symbol, model) in symbols.iter().zip(models()) {
+            assert_eq!(decoder.decode_symbol(model).unwrap(), symbol);
+        }
+
+        // Put some symbols outside of the support and check that nothing gets encoded.
+        let invalid_indices = [1, 3, 4, 8, 9, 10, 12, 17, 20, 21, 25, 29];
+        for &index in &invalid_indices {
+            symbols[index] = if index % 2 == 0 { 21 } else { -100 };
+        }
+        let mut encoder = DefaultRangeEncoder::new();
+        let err = encoder
+            .encode_symbols_validated(symbols.iter().zip(models()))
+            .unwrap_err();
+        match err {
+            TryCodingError::InvalidEntropyModel(err) => {
+                assert_eq!(err.num_invalid(), invalid_indices.len());
+                assert_eq!(
+                    err.indices(),
+                    &invalid_indices[..InvalidSymbolsError::MAX_REPORTED]
+                );
+            }
+            TryCodingError::CodingError(_) => panic!(),
+        }
+        assert!(encoder.is_empty());
+    }
+
+    #[test]
+    fn skip_symbols() {
+        let quantizer = LeakyQuantizer::<_, _, u32, 24>::new(-20..=20);
+        let means = (0..40).map(|i| i as f64 * 0.7 - 14.0).collect::<Vec<_>>();
+        let symbols = means
+            .iter()
+            .map(|&mean| (mean * 1.1) as i32)
+            .collect::<Vec<_>>();
+        let models = means
+            .iter()
+            .map(|&mean| quantizer.quantize(Gaussian::new(mean, 3.0)))
+            .collect::<Vec<_>>();
+        let iid_model = quantizer.quantize(Gaussian::new(2.0, 5.0));
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_symbols(symbols.iter().zip(&models)).unwrap();
+        encoder
+            .encode_iid_symbols(&symbols[..10], &iid_model)
+            .unwrap();
+
+        let mut decoder = encoder.decoder();
+        decoder.skip_symbols(&models[..25]).unwrap();
+        for (&symbol, model) in symbols.iter().zip(&models).skip(25) {
+            assert_eq!(decoder.decode_symbol(model).unwrap(), symbol);
+        }
+        decoder.skip_iid_symbols(7, &iid_model).unwrap();
+        for &symbol in &symbols[7..10] {
+            assert_eq!(decoder.decode_symbol(&iid_model).unwrap(), symbol);
+        }
+        assert!(decoder.maybe_exhausted());
     }
 }
 
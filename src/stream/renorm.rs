@@ -0,0 +1,239 @@
+//! Renormalization primitives shared by the stream coders
+//!
+//! All stream coders in this crate keep some internal `State` that is larger than a `Word`
+//! of compressed data, and they move whole `Word`s between the `State` and the backend
+//! whenever the `State` leaves a certain interval ("renormalization"). This module
+//! collects the word-level operations that are needed for this, so that each coder only
+//! has to decide *when* to renormalize but not *how* to shuffle bits around:
+//!
+//! - ANS (see [`stack`](super::stack)) reads and writes words at the least significant
+//!   end of its state, see [`shift_in_low`] and [`split_low`].
+//! - Range Coding (see [`queue`](super::queue)) emits words from the most significant end
+//!   of `lower` (see [`split_high`]) and reads them into the least significant end of
+//!   `point` (see [`shift_in_low`]). Since `lower + range` may still overflow after a word
+//!   has been split off, the encoder may have to hold back words until it knows whether
+//!   they'll receive a carry, see [`CarryBuffer`].
+//!
+//! All coders keep their state (or `range`, for Range Coding) at or above
+//! [`lower_bound`] (except for a few documented corner cases).
+
+use core::num::NonZeroUsize;
+
+use num::cast::AsPrimitive;
+
+use crate::{backends::WriteWords, BitArray};
+
+/// Returns `State::one() << (State::BITS - Word::BITS)`.
+///
+/// Renormalization keeps the relevant part of a coder's state at or above this bound, so
+/// that it always holds at least `State::BITS - Word::BITS` significant bits. Conversely,
+/// any value below this bound can be shifted left by a whole `Word` without truncation.
+#[inline(always)]
+pub(crate) fn lower_bound<Word: BitArray, State: BitArray>() -> State {
+    State::one() << (State::BITS - Word::BITS)
+}
+
+/// Returns `true` iff `x` is below [`lower_bound`], i.e., iff a coder has to renormalize.
+#[inline(always)]
+pub(crate) fn needs_renorm<Word: BitArray, State: BitArray>(x: State) -> bool {
+    x < lower_bound::<Word, State>()
+}
+
+/// Shifts `x` left by one word and inserts `word` at the least significant end.
+///
+/// Truncates the most significant word of `x` unless [`needs_renorm`] holds for it.
+#[inline(always)]
+pub(crate) fn shift_in_low<Word, State>(x: State, word: Word) -> State
+where
+    Word: BitArray + Into<State>,
+    State: BitArray,
+{
+    (x << Word::BITS) | word.into()
+}
+
+/// Splits off the least significant word of `x`; inverse of [`shift_in_low`].
+///
+/// Returns the split off word and the remaining bits of `x`, shifted right by one word.
+#[inline(always)]
+pub(crate) fn split_low<Word, State>(x: State) -> (Word, State)
+where
+    Word: BitArray,
+    State: BitArray + AsPrimitive<Word>,
+{
+    (x.as_(), x >> Word::BITS)
+}
+
+/// Returns the most significant word of `x`.
+#[inline(always)]
+pub(crate) fn high_word<Word, State>(x: State) -> Word
+where
+    Word: BitArray,
+    State: BitArray + AsPrimitive<Word>,
+{
+    (x >> (State::BITS - Word::BITS)).as_()
+}
+
+/// Splits off the most significant word of `x`.
+///
+/// Returns the split off word and the remaining bits of `x`, shifted left by one word.
+#[inline(always)]
+pub(crate) fn split_high<Word, State>(x: State) -> (Word, State)
+where
+    Word: BitArray,
+    State: BitArray + AsPrimitive<Word>,
+{
+    (high_word(x), x << Word::BITS)
+}
+
+/// Words that a range encoder has split off from `lower` but that may still receive a carry.
+///
+/// When a range encoder splits off the most significant word of `lower` while `lower +
+/// range` overflows, then it can't know yet whether the interval will eventually end up
+/// below or above the overflow point. In the former case, the split off word is final; in
+/// the latter case, it has to be incremented by one. Any further words that are split off
+/// before this is resolved are all-ones words (if there's no carry) or all-zeros words (if
+/// there is a carry), so it suffices to count them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CarryBuffer<Word> {
+    /// No words are held back.
+    Empty,
+
+    /// Holds back `num_words` words, the first one of which is `first_word` if there won't
+    /// be a carry (all following ones are `Word::max_value()` in this case).
+    Pending(NonZeroUsize, Word),
+}
+
+impl<Word> Default for CarryBuffer<Word> {
+    fn default() -> Self {
+        Self::Empty
+    }
+}
+
+impl<Word: BitArray> CarryBuffer<Word> {
+    /// Returns the number of words that are currently held back.
+    #[inline(always)]
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Self::Empty => 0,
+            Self::Pending(num_words, _) => num_words.get(),
+        }
+    }
+
+    /// Emits a `word` that was split off from `lower`.
+    ///
+    /// If `may_carry` is `false` and no words are pending then `word` is written to `bulk`
+    /// right away. Otherwise, it is held back.
+    #[inline(always)]
+    pub(crate) fn push<B: WriteWords<Word>>(
+        &mut self,
+        word: Word,
+        may_carry: bool,
+        bulk: &mut B,
+    ) -> Result<(), B::WriteError> {
+        match self {
+            Self::Pending(num_words, _) => {
+                *num_words = NonZeroUsize::new(num_words.get().wrapping_add(1))
+                    .expect("Cannot encode more symbols than what's addressable with usize.");
+            }
+            Self::Empty if !may_carry => bulk.write(word)?,
+            Self::Empty => *self = Self::Pending(NonZeroUsize::new(1).expect("1 != 0"), word),
+        }
+        Ok(())
+    }
+
+    /// Writes out all held back words, resolving them with or without a `carry`.
+    ///
+    /// Doesn't modify `self` (so that callers can undo sealing); call [`clear`](Self::clear)
+    /// if the held back words are no longer pending.
+    pub(crate) fn write_resolved<B: WriteWords<Word>>(
+        &self,
+        carry: bool,
+        bulk: &mut B,
+    ) -> Result<(), B::WriteError> {
+        if let Self::Pending(num_words, first_word) = *self {
+            let (first_word, consecutive_words) = if carry {
+                (first_word + Word::one(), Word::zero())
+            } else {
+                (first_word, Word::max_value())
+            };
+
+            bulk.write(first_word)?;
+            for _ in 1..num_words.get() {
+                bulk.write(consecutive_words)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Forgets about all held back words.
+    #[inline(always)]
+    pub(crate) fn clear(&mut self) {
+        *self = Self::Empty;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::vec::Vec;
+
+    #[test]
+    fn shifting() {
+        assert_eq!(lower_bound::<u32, u64>(), 1 << 32);
+        assert_eq!(lower_bound::<u16, u32>(), 1 << 16);
+        assert_eq!(lower_bound::<u8, u16>(), 1 << 8);
+        assert!(needs_renorm::<u32, u64>((1 << 32) - 1));
+        assert!(!needs_renorm::<u32, u64>(1 << 32));
+
+        let x = 0x0123_4567_89AB_CDEFu64;
+        assert_eq!(split_low::<u32, u64>(x), (0x89AB_CDEF, 0x0123_4567));
+        assert_eq!(
+            split_high::<u32, u64>(x),
+            (0x0123_4567, 0x89AB_CDEF_0000_0000)
+        );
+        assert_eq!(high_word::<u16, u64>(x), 0x0123);
+        assert_eq!(shift_in_low::<u16, u32>(0x0000_ABCD, 0x1234), 0xABCD_1234);
+
+        // `shift_in_low` inverts `split_low` on renormalized states ...
+        let (word, rest) = split_low::<u16, u32>(0xABCD_1234);
+        assert!(needs_renorm::<u16, u32>(rest));
+        assert_eq!(shift_in_low(rest, word), 0xABCD_1234u32);
+
+        // ... and truncates otherwise.
+        assert_eq!(shift_in_low::<u8, u16>(0xABCD, 0x12), 0xCD12);
+    }
+
+    #[test]
+    fn carry_buffer() {
+        let mut bulk = Vec::<u16>::new();
+        let mut buffer = CarryBuffer::default();
+
+        // Words that can't receive a carry pass through.
+        buffer.push(0x1111, false, &mut bulk).unwrap();
+        assert_eq!(buffer.len(), 0);
+        assert_eq!(bulk, [0x1111]);
+
+        // Once a word may receive a carry, it and all following ones are held back.
+        buffer.push(0x2222, true, &mut bulk).unwrap();
+        buffer.push(0xFFFF, false, &mut bulk).unwrap();
+        buffer.push(0xFFFF, true, &mut bulk).unwrap();
+        assert_eq!(buffer.len(), 3);
+        assert_eq!(bulk, [0x1111]);
+
+        let mut without_carry = bulk.clone();
+        buffer.write_resolved(false, &mut without_carry).unwrap();
+        assert_eq!(without_carry, [0x1111, 0x2222, 0xFFFF, 0xFFFF]);
+
+        let mut with_carry = bulk.clone();
+        buffer.write_resolved(true, &mut with_carry).unwrap();
+        assert_eq!(with_carry, [0x1111, 0x2223, 0x0000, 0x0000]);
+
+        // Resolving doesn't clear the buffer by itself.
+        assert_eq!(buffer.len(), 3);
+        buffer.clear();
+        assert_eq!(buffer, CarryBuffer::Empty);
+        buffer.write_resolved(true, &mut bulk).unwrap();
+        assert_eq!(bulk, [0x1111]);
+    }
+}
@@ -0,0 +1,423 @@
+//! Reusing scratch memory across many batches of encoding or decoding
+//!
+//! Code that encodes or decodes many small batches of symbols in a tight loop (e.g., one
+//! batch per row of an image or per packet of a network stream) often ends up allocating
+//! a fresh `Vec` for each batch, either to collect decoded symbols or to buffer symbols
+//! that have to be encoded in reverse order on a stack. A [`DecodeSession`] or an
+//! [`EncodeSession`] wraps a coder together with such a buffer and keeps the buffer alive
+//! between batches, so that the loop stops allocating once the buffer has grown to the
+//! size of the largest batch.
+//!
+//! Sessions only reuse the buffer of symbols. They don't pool any memory owned by
+//! entropy models, so constructing a model that allocates (e.g., a categorical entropy
+//! model, which owns its table of cumulative probabilities) still allocates on each
+//! construction. If the same model is used for many batches, then construct it once
+//! outside the loop and pass it by reference (or pass a cheap view of it, see, e.g.,
+//! [`ContiguousCategoricalEntropyModel::as_view`]).
+//!
+//! Both session types implement the same coding traits as the coders they wrap, so you
+//! can still encode or decode individual symbols through the session, and you get the
+//! wrapped coder back with [`DecodeSession::into_inner`] or [`EncodeSession::into_inner`].
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::DefaultContiguousCategoricalEntropyModel,
+//!     session::{DecodeSession, EncodeSession},
+//!     stack::DefaultAnsCoder,
+//! };
+//!
+//! let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+//!     0.1, 0.6, 0.2, 0.1,
+//! ])
+//! .unwrap();
+//! let rows = (0..100)
+//!     .map(|i| (0..32).map(|j| (i * j) % 4).collect::<Vec<usize>>())
+//!     .collect::<Vec<_>>();
+//!
+//! // Encode rows in reverse order (the last row first) since an `AnsCoder` is a stack.
+//! // The session buffers each row (which we generate lazily here) so that it can encode
+//! // the row in reverse order, and it reuses the same buffer for all rows.
+//! let mut encoder = EncodeSession::new(DefaultAnsCoder::new());
+//! for row in rows.iter().rev() {
+//!     encoder.encode_iid_batch_reverse(row.iter().copied(), &model).unwrap();
+//! }
+//!
+//! let mut decoder = DecodeSession::new(encoder.into_inner());
+//! for row in &rows {
+//!     let decoded: &[usize] = decoder.decode_iid_batch(row.len(), &model).unwrap();
+//!     assert_eq!(decoded, &row[..]);
+//! }
+//! assert!(decoder.get_ref().is_empty());
+//! ```
+//!
+//! [`ContiguousCategoricalEntropyModel::as_view`]:
+//!     super::model::ContiguousCategoricalEntropyModel::as_view
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    model::{DecoderModel, EncoderModel},
+    Code, Decode, Encode,
+};
+use crate::{CoderError, Pos, PosSeek, Seek};
+
+/// Wrapper around a decoder that decodes batches of symbols into a reusable buffer.
+///
+/// See [module level documentation](self) for an example.
+#[derive(Debug, Clone)]
+pub struct DecodeSession<Decoder, Symbol> {
+    inner: Decoder,
+    symbols: Vec<Symbol>,
+}
+
+impl<Decoder, Symbol> DecodeSession<Decoder, Symbol> {
+    /// Wraps `inner`, starting with an empty buffer.
+    pub fn new(inner: Decoder) -> Self {
+        Self::with_capacity(inner, 0)
+    }
+
+    /// Wraps `inner`, with a buffer that can hold `capacity` symbols before it has to grow.
+    pub fn with_capacity(inner: Decoder, capacity: usize) -> Self {
+        Self {
+            inner,
+            symbols: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of symbols that the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.symbols.capacity()
+    }
+
+    /// Decodes `amt` symbols with the same entropy model and returns them as a slice into
+    /// the session's buffer.
+    ///
+    /// The buffer is cleared at the beginning of each call but keeps its capacity.
+    /// Short-circuits on the first error, in which case the decoder may have consumed
+    /// some of the compressed data.
+    pub fn decode_iid_batch<M, const PRECISION: usize>(
+        &mut self,
+        amt: usize,
+        model: M,
+    ) -> Result<&[Symbol], CoderError<Decoder::FrontendError, Decoder::BackendError>>
+    where
+        Decoder: Decode<PRECISION>,
+        M: DecoderModel<PRECISION, Symbol = Symbol> + Copy,
+        M::Probability: Into<Decoder::Word>,
+        Decoder::Word: AsPrimitive<M::Probability>,
+    {
+        self.symbols.clear();
+        self.symbols.reserve(amt);
+        for _ in 0..amt {
+            self.symbols.push(self.inner.decode_symbol(model)?);
+        }
+        Ok(&self.symbols)
+    }
+
+    /// Decodes one symbol per entropy model yielded by `models` and returns the symbols
+    /// as a slice into the session's buffer.
+    ///
+    /// Behaves like [`decode_iid_batch`](Self::decode_iid_batch) except that each symbol
+    /// is decoded with its individual entropy model.
+    pub fn decode_batch<M, const PRECISION: usize>(
+        &mut self,
+        models: impl IntoIterator<Item = M>,
+    ) -> Result<&[Symbol], CoderError<Decoder::FrontendError, Decoder::BackendError>>
+    where
+        Decoder: Decode<PRECISION>,
+        M: DecoderModel<PRECISION, Symbol = Symbol>,
+        M::Probability: Into<Decoder::Word>,
+        Decoder::Word: AsPrimitive<M::Probability>,
+    {
+        self.symbols.clear();
+        for model in models {
+            self.symbols.push(self.inner.decode_symbol(model)?);
+        }
+        Ok(&self.symbols)
+    }
+
+    /// Returns a reference to the wrapped decoder.
+    pub fn get_ref(&self) -> &Decoder {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped decoder.
+    pub fn get_mut(&mut self) -> &mut Decoder {
+        &mut self.inner
+    }
+
+    /// Consumes the session and returns the wrapped decoder, dropping the buffer.
+    pub fn into_inner(self) -> Decoder {
+        self.inner
+    }
+}
+
+impl<Decoder: Code, Symbol> Code for DecodeSession<Decoder, Symbol> {
+    type Word = Decoder::Word;
+    type State = Decoder::State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Decoder, Symbol, const PRECISION: usize> Decode<PRECISION> for DecodeSession<Decoder, Symbol>
+where
+    Decoder: Decode<PRECISION>,
+{
+    type FrontendError = Decoder::FrontendError;
+    type BackendError = Decoder::BackendError;
+
+    /// Decodes a symbol with the wrapped decoder, bypassing the buffer.
+    #[inline(always)]
+    fn decode_symbol<D>(
+        &mut self,
+        model: D,
+    ) -> Result<D::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<D::Probability>,
+    {
+        self.inner.decode_symbol(model)
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.inner.maybe_exhausted()
+    }
+}
+
+impl<Decoder: PosSeek, Symbol> PosSeek for DecodeSession<Decoder, Symbol> {
+    type Position = Decoder::Position;
+}
+
+impl<Decoder: Pos, Symbol> Pos for DecodeSession<Decoder, Symbol> {
+    #[inline(always)]
+    fn pos(&self) -> Self::Position {
+        self.inner.pos()
+    }
+}
+
+impl<Decoder: Seek, Symbol> Seek for DecodeSession<Decoder, Symbol> {
+    #[inline(always)]
+    fn seek(&mut self, pos: Self::Position) -> Result<(), ()> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Wrapper around an encoder that buffers batches of symbols in a reusable buffer so that
+/// it can encode them in reverse order.
+///
+/// Encoders with stack semantics (such as an [`AnsCoder`]) decode symbols in the reverse
+/// order in which they were encoded, so encoding a batch for later decoding in its natural
+/// order requires iterating over the batch backwards. This is easy if the symbols are
+/// already in a slice, but it requires buffering them if they are produced by an iterator
+/// that can't run backwards (e.g., an iterator over a compressed or computed source). An
+/// `EncodeSession` keeps this buffer alive across batches.
+///
+/// See [module level documentation](self) for an example.
+///
+/// [`AnsCoder`]: super::stack::AnsCoder
+#[derive(Debug, Clone)]
+pub struct EncodeSession<Encoder, Symbol> {
+    inner: Encoder,
+    symbols: Vec<Symbol>,
+}
+
+impl<Encoder, Symbol> EncodeSession<Encoder, Symbol> {
+    /// Wraps `inner`, starting with an empty buffer.
+    pub fn new(inner: Encoder) -> Self {
+        Self::with_capacity(inner, 0)
+    }
+
+    /// Wraps `inner`, with a buffer that can hold `capacity` symbols before it has to grow.
+    pub fn with_capacity(inner: Encoder, capacity: usize) -> Self {
+        Self {
+            inner,
+            symbols: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the number of symbols that the buffer can hold without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.symbols.capacity()
+    }
+
+    /// Collects `symbols` into the session's buffer and then encodes them in reverse order
+    /// with the same entropy model.
+    ///
+    /// On an encoder with stack semantics, a subsequent call to
+    /// [`Decode::decode_iid_symbols`] (or [`DecodeSession::decode_iid_batch`]) decodes
+    /// the symbols in the order in which `symbols` yields them. Short-circuits on the first
+    /// error, in which case some of the symbols (from the end of the batch) may already
+    /// have been encoded.
+    pub fn encode_iid_batch_reverse<M, const PRECISION: usize>(
+        &mut self,
+        symbols: impl IntoIterator<Item = Symbol>,
+        model: M,
+    ) -> Result<(), CoderError<Encoder::FrontendError, Encoder::BackendError>>
+    where
+        Encoder: Encode<PRECISION>,
+        Symbol: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION> + Copy,
+        M::Probability: Into<Encoder::Word>,
+        Encoder::Word: AsPrimitive<M::Probability>,
+    {
+        self.symbols.clear();
+        self.symbols.extend(symbols);
+        for symbol in self.symbols.iter().rev() {
+            self.inner.encode_symbol(symbol.borrow(), model)?;
+        }
+        Ok(())
+    }
+
+    /// Returns a reference to the wrapped encoder.
+    pub fn get_ref(&self) -> &Encoder {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped encoder.
+    pub fn get_mut(&mut self) -> &mut Encoder {
+        &mut self.inner
+    }
+
+    /// Consumes the session and returns the wrapped encoder, dropping the buffer.
+    pub fn into_inner(self) -> Encoder {
+        self.inner
+    }
+}
+
+impl<Encoder: Code, Symbol> Code for EncodeSession<Encoder, Symbol> {
+    type Word = Encoder::Word;
+    type State = Encoder::State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Encoder, Symbol, const PRECISION: usize> Encode<PRECISION> for EncodeSession<Encoder, Symbol>
+where
+    Encoder: Encode<PRECISION>,
+{
+    type FrontendError = Encoder::FrontendError;
+    type BackendError = Encoder::BackendError;
+
+    /// Encodes a symbol with the wrapped encoder, bypassing the buffer.
+    #[inline(always)]
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.inner.encode_symbol(symbol, model)
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.inner.maybe_full()
+    }
+}
+
+impl<Encoder: PosSeek, Symbol> PosSeek for EncodeSession<Encoder, Symbol> {
+    type Position = Encoder::Position;
+}
+
+impl<Encoder: Pos, Symbol> Pos for EncodeSession<Encoder, Symbol> {
+    #[inline(always)]
+    fn pos(&self) -> Self::Position {
+        self.inner.pos()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            model::DefaultLeakyQuantizer,
+            queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+            stack::DefaultAnsCoder,
+        },
+        *,
+    };
+
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn stack_roundtrip() {
+        let quantizer = DefaultLeakyQuantizer::new(-50..=50);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let batches = (0..20)
+            .map(|i| (0..i).map(|j| (i * j) % 41 - 20).collect::<Vec<i32>>())
+            .collect::<Vec<_>>();
+
+        let mut encoder = EncodeSession::new(DefaultAnsCoder::new());
+        let mut reference = DefaultAnsCoder::new();
+        for batch in batches.iter().rev() {
+            encoder
+                .encode_iid_batch_reverse(batch.iter().copied(), &model)
+                .unwrap();
+            reference.encode_iid_symbols_reverse(batch, &model).unwrap();
+        }
+        assert!(encoder.capacity() >= 19);
+        let mut coder = encoder.into_inner();
+        assert_eq!(
+            *coder.get_compressed().unwrap(),
+            *reference.get_compressed().unwrap()
+        );
+
+        let mut decoder = DecodeSession::with_capacity(coder, 5);
+        for batch in &batches {
+            assert_eq!(
+                decoder.decode_iid_batch(batch.len(), &model).unwrap(),
+                &batch[..]
+            );
+        }
+        assert!(decoder.capacity() >= 19);
+        assert!(decoder.get_ref().is_empty());
+    }
+
+    #[test]
+    fn queue_with_individual_models() {
+        let quantizer = DefaultLeakyQuantizer::new(-50..=50);
+        let means = [-3.2, 10.7, 0.0, 25.1, -40.3];
+        let symbols = [-2, 12, 0, 30, -41];
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder
+            .encode_symbols(
+                symbols
+                    .iter()
+                    .zip(&means)
+                    .map(|(symbol, &mean)| (symbol, quantizer.quantize(Gaussian::new(mean, 5.0)))),
+            )
+            .unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+
+        let mut decoder =
+            DecodeSession::new(DefaultRangeDecoder::from_compressed(compressed).unwrap());
+        let decoded = decoder
+            .decode_batch(
+                means
+                    .iter()
+                    .map(|&mean| quantizer.quantize(Gaussian::new(mean, 5.0))),
+            )
+            .unwrap();
+        assert_eq!(decoded, symbols);
+
+        assert!(decoder.decoder_maybe_exhausted::<24>());
+    }
+}
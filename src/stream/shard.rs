@@ -0,0 +1,424 @@
+//! Splitting compressed data into shards that can be decoded independently
+//!
+//! A long stream of compressed data can only be decoded sequentially: a decoder that wants
+//! to start in the middle has to [`Seek`] to a position and state that the encoder recorded
+//! with [`Pos::pos`], and it needs access to the full compressed data. If you want to
+//! distribute one long stream across several workers or storage blocks, it's more
+//! convenient to cut the compressed data into *shards* at such recorded checkpoints, where
+//! each shard carries the coder state that's necessary to decode it on its own.
+//!
+//! This module provides functions that split a finished compressed buffer at a list of
+//! checkpoints, and that set up a decoder for each shard:
+//!
+//! - [`split_ans`] splits data that was produced by an [`AnsCoder`]. Each shard is again
+//!   a valid compressed buffer in the format of [`AnsCoder::into_compressed`], i.e., a
+//!   slice of the original compressed words followed by the coder state at the checkpoint
+//!   (at the top of the stack). Decode it with [`ans_coder_from_shard`] (which is just
+//!   [`AnsCoder::from_compressed`]).
+//! - [`split_range`] splits data that was produced by a [`RangeEncoder`]. Each shard
+//!   starts with a stub that holds the coder state at the checkpoint, followed by a slice
+//!   of the original compressed words. Since a range decoder reads a few words ahead, the
+//!   slices of consecutive shards overlap by `State::BITS / Word::BITS` words. Decode a
+//!   shard with [`range_decoder_from_shard`].
+//!
+//! In both cases, the overhead per shard is a constant number of words, and splitting
+//! only copies words, i.e., it doesn't have to decode or re-encode any symbols. The
+//! entropy models aren't stored in the shards, so the decoder of each shard still has to
+//! know which models to use for the symbols in its shard (and how many symbols to decode).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::{
+//!     stream::{
+//!         model::DefaultLeakyQuantizer,
+//!         queue::DefaultRangeEncoder,
+//!         shard::{range_decoder_from_shard, split_range},
+//!         Decode, Encode,
+//!     },
+//!     Pos,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+//! let blocks = (0..4)
+//!     .map(|block| (0..50).map(|i| (block * 37 + i * 11) % 61 - 30).collect::<Vec<i32>>())
+//!     .collect::<Vec<_>>();
+//!
+//! // Record a checkpoint between any two consecutive blocks.
+//! let mut encoder = DefaultRangeEncoder::new();
+//! let mut checkpoints = Vec::new();
+//! for (i, block) in blocks.iter().enumerate() {
+//!     if i != 0 {
+//!         checkpoints.push(encoder.pos());
+//!     }
+//!     encoder.encode_iid_symbols(block, &model).unwrap();
+//! }
+//! let compressed = encoder.into_compressed().unwrap();
+//!
+//! // Each shard can now be decoded without access to any of the other shards.
+//! let shards = split_range(&compressed, &checkpoints).unwrap();
+//! assert_eq!(shards.len(), blocks.len());
+//! for (shard, block) in shards.into_iter().zip(&blocks) {
+//!     let mut decoder = range_decoder_from_shard::<u32, u64>(shard).unwrap();
+//!     let decoded = decoder
+//!         .decode_iid_symbols(block.len(), &model)
+//!         .collect::<Result<Vec<_>, _>>()
+//!         .unwrap();
+//!     assert_eq!(&decoded, block);
+//! }
+//! ```
+//!
+//! [`Seek`]: crate::Seek
+//! [`Pos::pos`]: crate::Pos::pos
+//! [`AnsCoder`]: super::stack::AnsCoder
+//! [`AnsCoder::into_compressed`]: super::stack::AnsCoder::into_compressed
+//! [`AnsCoder::from_compressed`]: super::stack::AnsCoder::from_compressed
+//! [`RangeEncoder`]: super::queue::RangeEncoder
+
+use alloc::vec::Vec;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    queue::{RangeCoderState, RangeDecoder},
+    stack::AnsCoder,
+};
+use crate::{backends::Cursor, bit_array_to_chunks_truncated, BitArray, NonZeroBitArray, Seek};
+
+/// Splits data that was compressed with an [`AnsCoder`] into independently decodable
+/// shards.
+///
+/// The argument `compressed` has to be the output of [`AnsCoder::into_compressed`], and
+/// `checkpoints` have to be positions and states that were recorded with
+/// [`Pos::pos`](crate::Pos::pos) on the coder (either while encoding or while decoding).
+/// Since an `AnsCoder` is a stack, the decoder reaches the checkpoints in the reverse
+/// order in which the encoder recorded them; `checkpoints` has to list them in the order
+/// in which the *decoder* reaches them, i.e., by decreasing position.
+///
+/// Returns `checkpoints.len() + 1` shards: the first shard decodes the symbols from the
+/// beginning of decoding up to the first checkpoint, the second shard decodes the symbols
+/// between the first and the second checkpoint, and so on, and the last shard decodes
+/// the symbols after the last checkpoint. Each shard is in the format of
+/// [`AnsCoder::into_compressed`], so you can decode it with [`ans_coder_from_shard`] or
+/// with [`AnsCoder::from_compressed`].
+///
+/// Returns an error if `compressed` is not a valid compressed buffer, if the positions
+/// of the `checkpoints` increase or exceed the length of the compressed data, or if a
+/// checkpoint has an invalid state.
+#[allow(clippy::result_unit_err)]
+pub fn split_ans<Word, State>(
+    compressed: &[Word],
+    checkpoints: &[(usize, State)],
+) -> Result<Vec<Vec<Word>>, ()>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    let coder = AnsCoder::<Word, State>::from_compressed(compressed.to_vec()).map_err(|_| ())?;
+    let (bulk, state) = coder.into_raw_parts();
+
+    let starts = core::iter::once((bulk.len(), state)).chain(checkpoints.iter().cloned());
+    let ends = checkpoints
+        .iter()
+        .map(|&(pos, _)| pos)
+        .chain(core::iter::once(0));
+
+    starts
+        .zip(ends)
+        .map(|((start, state), end)| {
+            if end > start || start > bulk.len() {
+                return Err(());
+            }
+            if start != 0 && state >> (State::BITS - Word::BITS) == State::zero() {
+                // Violates the invariant of `AnsCoder`, so `from_compressed` would parse
+                // the shard incorrectly.
+                return Err(());
+            }
+
+            let mut shard = Vec::with_capacity(start - end + State::BITS / Word::BITS);
+            shard.extend_from_slice(&bulk[end..start]);
+            shard.extend(bit_array_to_chunks_truncated::<_, Word>(state).rev());
+            Ok(shard)
+        })
+        .collect()
+}
+
+/// Creates an [`AnsCoder`] that decodes a shard created by [`split_ans`].
+///
+/// This is equivalent to calling [`AnsCoder::from_compressed`]. Returns an error if the
+/// shard is malformed.
+#[allow(clippy::result_unit_err)]
+pub fn ans_coder_from_shard<Word, State>(shard: Vec<Word>) -> Result<AnsCoder<Word, State>, ()>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    AnsCoder::from_compressed(shard).map_err(|_| ())
+}
+
+/// Splits data that was compressed with a [`RangeEncoder`] into independently decodable
+/// shards.
+///
+/// The argument `compressed` has to be the output of [`RangeEncoder::into_compressed`],
+/// and `checkpoints` have to be positions and states that were recorded with
+/// [`Pos::pos`](crate::Pos::pos) on the encoder (or on a decoder), listed in the order in
+/// which they were recorded, i.e., by increasing position.
+///
+/// Returns `checkpoints.len() + 1` shards: the first shard decodes the symbols up to the
+/// first checkpoint, the second shard decodes the symbols between the first and the
+/// second checkpoint, and so on, and the last shard decodes the symbols after the last
+/// checkpoint. Decode each shard with [`range_decoder_from_shard`].
+///
+/// Each shard starts with a stub of `2 * State::BITS / Word::BITS` words that holds the
+/// lower bound and the size of the range at the checkpoint (most significant words
+/// first), followed by the compressed words from the checkpoint's position up to the
+/// next checkpoint's position plus the `State::BITS / Word::BITS` words that a decoder
+/// reads ahead (or up to the end of the compressed data).
+///
+/// Returns an error if the positions of the `checkpoints` decrease or exceed the length
+/// of the compressed data.
+///
+/// [`RangeEncoder`]: super::queue::RangeEncoder
+/// [`RangeEncoder::into_compressed`]: super::queue::RangeEncoder::into_compressed
+#[allow(clippy::result_unit_err)]
+pub fn split_range<Word, State>(
+    compressed: &[Word],
+    checkpoints: &[(usize, RangeCoderState<Word, State>)],
+) -> Result<Vec<Vec<Word>>, ()>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    let lookahead = State::BITS / Word::BITS;
+    let starts =
+        core::iter::once((0, RangeCoderState::default())).chain(checkpoints.iter().cloned());
+    let ends = checkpoints
+        .iter()
+        .map(|&(pos, _)| pos)
+        .chain(core::iter::once(compressed.len()));
+
+    starts
+        .zip(ends)
+        .map(|((start, state), end)| {
+            if end < start || end > compressed.len() {
+                return Err(());
+            }
+
+            let end = compressed.len().min(end + lookahead);
+            let mut shard = Vec::with_capacity(2 * lookahead + end - start);
+            for value in [state.lower(), state.range().get()] {
+                shard.extend(
+                    (0..lookahead)
+                        .rev()
+                        .map(|i| (value >> (i * Word::BITS)).as_()),
+                );
+            }
+            shard.extend_from_slice(&compressed[start..end]);
+            Ok(shard)
+        })
+        .collect()
+}
+
+/// Creates a [`RangeDecoder`] that decodes a shard created by [`split_range`].
+///
+/// The returned decoder is positioned right after the stub at the beginning of the shard,
+/// so any positions that it reports with [`Pos::pos`](crate::Pos::pos) are offset by the
+/// size of the stub (`2 * State::BITS / Word::BITS` words).
+///
+/// Returns an error if the shard is too short to contain a stub or if the stub holds an
+/// invalid state.
+#[allow(clippy::result_unit_err)]
+pub fn range_decoder_from_shard<Word, State>(
+    shard: Vec<Word>,
+) -> Result<RangeDecoder<Word, State, Cursor<Word, Vec<Word>>>, ()>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    let lookahead = State::BITS / Word::BITS;
+    if shard.len() < 2 * lookahead {
+        return Err(());
+    }
+    let read_state = |words: &[Word]| {
+        words.iter().fold(State::zero(), |state, &word| {
+            (state << Word::BITS) | word.into()
+        })
+    };
+    let lower = read_state(&shard[..lookahead]);
+    let range = read_state(&shard[lookahead..2 * lookahead]);
+    let state = RangeCoderState::new(lower, range)?;
+
+    let mut decoder = RangeDecoder::from_compressed(shard).map_err(|_| ())?;
+    decoder.seek((2 * lookahead, state))?;
+    Ok(decoder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        stream::{
+            model::{DefaultLeakyQuantizer, SmallLeakyQuantizer},
+            queue::{DefaultRangeEncoder, SmallRangeEncoder},
+            stack::DefaultAnsCoder,
+            Decode, Encode,
+        },
+        Pos,
+    };
+
+    use probability::distribution::Gaussian;
+
+    fn blocks() -> Vec<Vec<i32>> {
+        // Includes an empty block and blocks of very different information content.
+        [0, 120, 7, 0, 300, 1]
+            .iter()
+            .enumerate()
+            .map(|(block, &len)| {
+                (0..len)
+                    .map(|i| ((block * 31 + i * 17) % (8 * block + 3)) as i32 - 4 * block as i32)
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn range_shards() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 8.0));
+        let blocks = blocks();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        let mut checkpoints = Vec::new();
+        for (i, block) in blocks.iter().enumerate() {
+            if i != 0 {
+                checkpoints.push(encoder.pos());
+            }
+            encoder.encode_iid_symbols(block, &model).unwrap();
+        }
+        let compressed = encoder.into_compressed().unwrap();
+
+        let shards = split_range(&compressed, &checkpoints).unwrap();
+        assert_eq!(shards.len(), blocks.len());
+        let total_len = shards.iter().map(Vec::len).sum::<usize>();
+        assert!(total_len <= compressed.len() + shards.len() * 3 * 2);
+
+        for (shard, block) in shards.into_iter().zip(&blocks) {
+            let mut decoder = range_decoder_from_shard::<u32, u64>(shard).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(block.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, block);
+        }
+
+        // Checkpoints out of order or out of bounds.
+        let mut reversed = checkpoints.clone();
+        reversed.reverse();
+        assert!(split_range(&compressed, &reversed).is_err());
+        assert!(split_range(&compressed[..1], &checkpoints).is_err());
+
+        // Malformed shards.
+        assert!(range_decoder_from_shard::<u32, u64>(alloc::vec![1, 2, 3]).is_err());
+        assert!(range_decoder_from_shard::<u32, u64>(alloc::vec![0, 0, 0, 5]).is_err());
+    }
+
+    #[test]
+    fn range_shards_small_words() {
+        let model = SmallLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 8.0));
+        let blocks = blocks();
+
+        let mut encoder = SmallRangeEncoder::new();
+        let mut checkpoints = Vec::new();
+        for (i, block) in blocks.iter().enumerate() {
+            if i != 0 {
+                checkpoints.push(encoder.pos());
+            }
+            encoder.encode_iid_symbols(block, &model).unwrap();
+        }
+        let compressed = encoder.into_compressed().unwrap();
+
+        let shards = split_range(&compressed, &checkpoints).unwrap();
+        for (shard, block) in shards.into_iter().zip(&blocks) {
+            let mut decoder = range_decoder_from_shard::<u16, u32>(shard).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(block.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, block);
+        }
+    }
+
+    #[test]
+    fn ans_shards() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 8.0));
+        let blocks = blocks();
+
+        // Encode the blocks in reverse order so that they decode in their original order.
+        let mut coder = DefaultAnsCoder::new();
+        let mut checkpoints = Vec::new();
+        for (i, block) in blocks.iter().enumerate().rev() {
+            coder.encode_iid_symbols_reverse(block, &model).unwrap();
+            if i != 0 {
+                checkpoints.push(coder.pos());
+            }
+        }
+        checkpoints.reverse(); // Order in which the decoder reaches the checkpoints.
+        let compressed = coder.into_compressed().unwrap();
+
+        let shards = split_ans(&compressed, &checkpoints).unwrap();
+        assert_eq!(shards.len(), blocks.len());
+        let total_len = shards.iter().map(Vec::len).sum::<usize>();
+        assert!(total_len <= compressed.len() + shards.len() * 2);
+
+        for (shard, block) in shards.into_iter().zip(&blocks) {
+            let mut decoder = ans_coder_from_shard::<u32, u64>(shard).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(block.len(), &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, block);
+        }
+
+        // The last shard ends where the encoder started, so decoding it empties the coder.
+        let shards = split_ans(&compressed, &checkpoints).unwrap();
+        let mut decoder = ans_coder_from_shard::<u32, u64>(shards[5].clone()).unwrap();
+        decoder.decode_iid_symbols(1, &model).for_each(|_| ());
+        assert!(decoder.is_empty());
+
+        // Checkpoints out of order or out of bounds.
+        let mut reversed = checkpoints.clone();
+        reversed.reverse();
+        assert!(split_ans(&compressed, &reversed).is_err());
+        let mut out_of_bounds = checkpoints.clone();
+        out_of_bounds[0].0 = compressed.len() + 1;
+        assert!(split_ans(&compressed, &out_of_bounds).is_err());
+        let mut invalid_state = checkpoints.clone();
+        invalid_state[1].1 = 1;
+        assert!(split_ans(&compressed, &invalid_state).is_err());
+    }
+
+    #[test]
+    fn no_checkpoints() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 8.0));
+        let symbols = blocks().concat();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+        let shards = split_range::<u32, u64>(&compressed, &[]).unwrap();
+        assert_eq!(shards.len(), 1);
+        let mut decoder = range_decoder_from_shard::<u32, u64>(shards[0].clone()).unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(symbols.len(), &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.maybe_exhausted());
+
+        let mut coder = DefaultAnsCoder::new();
+        coder.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+        let compressed = coder.into_compressed().unwrap();
+        let shards = split_ans::<u32, u64>(&compressed, &[]).unwrap();
+        assert_eq!(shards, [compressed]);
+    }
+}
@@ -16,6 +16,13 @@
 //! The parent module contains a more detailed discussion of the [differences between ANS
 //! Coding and Range Coding](super#which-stream-code-should-i-use) .
 //!
+//! # Interleaved ANS Coding
+//!
+//! The [`InterleavedAnsCoder`] maintains several independent ANS states that take turns in
+//! coding consecutive symbols. It provides the same encoding and decoding API as the
+//! `AnsCoder`, but it allows the CPU to decode several symbols in parallel, which makes
+//! decoding faster.
+//!
 //! # References
 //!
 //! \[1] Duda, Jarek, et al. "The use of asymmetric numeral systems as an accurate
@@ -23,25 +30,33 @@
 //!
 //! [`queue`]: super::queue
 
-use alloc::vec::Vec;
+use alloc::{sync::Arc, vec::Vec};
 use core::{
     borrow::Borrow, convert::Infallible, fmt::Debug, iter::Fuse, marker::PhantomData, ops::Deref,
 };
 use num::cast::AsPrimitive;
 
 use super::{
+    checksum::{self, ChecksumError},
     model::{DecoderModel, EncoderModel},
+    renorm,
+    strict::StrictDecode,
     AsDecoder, Code, Decode, Encode, IntoDecoder, TryCodingError,
 };
 use crate::{
     backends::{
-        self, AsReadWords, AsSeekReadWords, BoundedReadWords, Cursor, FallibleIteratorReadWords,
-        IntoReadWords, IntoSeekReadWords, ReadWords, Reverse, WriteWords,
+        self, AsReadWords, AsSeekReadWords, BackendStats, BoundedReadWords, Cursor,
+        FallibleIteratorReadWords, IntoReadWords, IntoSeekReadWords, ReadWords, ReportStats,
+        Reverse, SafeBuf, WriteWords,
     },
     bit_array_to_chunks_truncated, BitArray, CoderError, DefaultEncoderError,
     DefaultEncoderFrontendError, NonZeroBitArray, Pos, PosSeek, Seek, Stack, UnwrapInfallible,
 };
 
+mod interleaved;
+
+pub use interleaved::{DefaultInterleavedAnsCoder, InterleavedAnsCoder, SmallInterleavedAnsCoder};
+
 /// Entropy coder for both encoding and decoding on a stack.
 ///
 /// This is the generic struct for an ANS coder. It provides fine-tuned control over type
@@ -340,8 +355,8 @@ where
 
             let mut state = first_word.into();
             while let Some(word) = read_word().map_err(|_| ())? {
-                state = state << Word::BITS | word.into();
-                if state >= State::one() << (State::BITS - Word::BITS) {
+                state = renorm::shift_in_low(state, word);
+                if !renorm::needs_renorm::<Word, State>(state) {
                     break;
                 }
             }
@@ -378,9 +393,9 @@ where
     {
         let mut state = State::one();
 
-        while state < State::one() << (State::BITS - Word::BITS) {
+        while renorm::needs_renorm::<Word, State>(state) {
             if let Some(word) = data.read()? {
-                state = state << Word::BITS | word.into();
+                state = renorm::shift_in_low(state, word);
             } else {
                 break;
             }
@@ -398,6 +413,18 @@ where
         &self.bulk
     }
 
+    /// Returns the statistics gathered by the backend, e.g., by a [`CountingWords`].
+    ///
+    /// See [`CountingWords`] for an example.
+    ///
+    /// [`CountingWords`]: backends::CountingWords
+    pub fn backend_stats(&self) -> BackendStats
+    where
+        Backend: ReportStats,
+    {
+        self.bulk.stats()
+    }
+
     pub fn into_raw_parts(self) -> (Backend, State) {
         (self.bulk, self.state)
     }
@@ -511,7 +538,7 @@ where
     ///
     /// [`get_compressed`]: #method.get_compressed
     /// [`into_compressed`]: #method.into_compressed
-    pub fn iter_compressed<'a>(&'a self) -> impl Iterator<Item = Word> + '_
+    pub fn iter_compressed<'a>(&'a self) -> impl Iterator<Item = Word> + 'a
     where
         &'a Backend: IntoIterator<Item = &'a Word>,
     {
@@ -539,23 +566,77 @@ where
     where
         Backend: BoundedReadWords<Word, Stack>,
     {
-        self.bulk.remaining() + bit_array_to_chunks_truncated::<_, Word>(self.state).len()
+        self.checked_num_words()
+            .expect("number of words overflows addressable space")
     }
 
+    /// Like [`num_words`], but returns `None` instead of panicking if the number of words
+    /// doesn't fit into a `usize`.
+    ///
+    /// [`num_words`]: #method.num_words
+    pub fn checked_num_words(&self) -> Option<usize>
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        self.bulk
+            .remaining()
+            .checked_add(bit_array_to_chunks_truncated::<_, Word>(self.state).len())
+    }
+
+    /// Returns `Word::BITS` times the result of [`num_words`].
+    ///
+    /// # Panics
+    ///
+    /// If the number of bits doesn't fit into a `usize`. This can only happen on platforms
+    /// with a pointer width of 32 bits or less (e.g., for more than 512 MiB of compressed
+    /// data on a 32-bit platform). Call [`num_words`] instead if you need to deal with such
+    /// large amounts of compressed data on these platforms.
+    ///
+    /// [`num_words`]: #method.num_words
     pub fn num_bits(&self) -> usize
     where
         Backend: BoundedReadWords<Word, Stack>,
     {
-        Word::BITS * self.num_words()
+        self.checked_num_bits()
+            .expect("number of bits overflows addressable space")
+    }
+
+    /// Like [`num_bits`], but returns `None` instead of panicking if the number of bits
+    /// doesn't fit into a `usize`.
+    ///
+    /// [`num_bits`]: #method.num_bits
+    pub fn checked_num_bits(&self) -> Option<usize>
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        self.checked_num_words()?.checked_mul(Word::BITS)
     }
 
+    /// Returns the number of bits of compressed data, not rounded up to full words.
+    ///
+    /// # Panics
+    ///
+    /// Under the same (rare) conditions as [`num_bits`](#method.num_bits).
     pub fn num_valid_bits(&self) -> usize
     where
         Backend: BoundedReadWords<Word, Stack>,
     {
-        Word::BITS * self.bulk.remaining()
-            + core::cmp::max(State::BITS - self.state.leading_zeros() as usize, 1)
-            - 1
+        self.checked_num_valid_bits()
+            .expect("number of bits overflows addressable space")
+    }
+
+    /// Like [`num_valid_bits`], but returns `None` instead of panicking if the number of
+    /// bits doesn't fit into a `usize`.
+    ///
+    /// [`num_valid_bits`]: #method.num_valid_bits
+    pub fn checked_num_valid_bits(&self) -> Option<usize>
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        self.bulk
+            .remaining()
+            .checked_mul(Word::BITS)?
+            .checked_add(core::cmp::max(State::BITS - self.state.leading_zeros() as usize, 1) - 1)
     }
 
     pub fn into_decoder(self) -> AnsCoder<Word, State, Backend::IntoReadWords>
@@ -643,6 +724,123 @@ where
         self.bulk.clear();
         self.state = State::zero();
     }
+
+    /// Returns the compressed data and resets the coder to an empty state.
+    ///
+    /// The returned vector is the same as what [`into_compressed`] would return, but this
+    /// method only needs a `&mut self` receiver, so you can keep using the same coder for
+    /// the next message. Unlike [`get_compressed`], it doesn't copy or temporarily seal
+    /// anything: it appends the state to the internal buffer once and then hands out the
+    /// buffer itself. The coder gets a fresh buffer with the same capacity, so encoding a
+    /// series of messages of similar size doesn't have to grow the buffer for each message.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::{
+    ///     model::DefaultContiguousCategoricalEntropyModel, stack::DefaultAnsCoder, Decode,
+    /// };
+    ///
+    /// let model = DefaultContiguousCategoricalEntropyModel
+    ///     ::from_floating_point_probabilities(&[0.2, 0.5, 0.3]).unwrap();
+    /// let messages = [vec![0, 2, 1], vec![1, 1], vec![2, 0, 0, 1]];
+    ///
+    /// let mut coder = DefaultAnsCoder::new();
+    /// let mut compressed_messages = Vec::new();
+    /// for message in &messages {
+    ///     coder.encode_iid_symbols_reverse(message, &model).unwrap();
+    ///     compressed_messages.push(coder.take_compressed());
+    ///     assert!(coder.is_empty());
+    /// }
+    ///
+    /// for (compressed, message) in compressed_messages.into_iter().zip(&messages) {
+    ///     let mut decoder = DefaultAnsCoder::from_compressed(compressed).unwrap();
+    ///     let decoded = decoder.decode_iid_symbols(message.len(), &model)
+    ///         .collect::<Result<Vec<_>, _>>()
+    ///         .unwrap();
+    ///     assert_eq!(&decoded, message);
+    /// }
+    /// ```
+    ///
+    /// [`into_compressed`]: Self::into_compressed
+    /// [`get_compressed`]: Self::get_compressed
+    pub fn take_compressed(&mut self) -> Vec<Word> {
+        self.bulk
+            .extend(bit_array_to_chunks_truncated::<_, Word>(self.state).rev());
+        self.state = State::zero();
+        let capacity = self.bulk.capacity();
+        core::mem::replace(&mut self.bulk, Vec::with_capacity(capacity))
+    }
+
+    /// Returns the compressed data with an appended CRC-32 checksum.
+    ///
+    /// Use [`from_checksummed`](Self::from_checksummed) to decode the returned data. See
+    /// module [`checksum`] for details.
+    pub fn seal_with_checksum(self) -> Vec<Word> {
+        checksum::seal_with_checksum(self.into_compressed().unwrap_infallible())
+    }
+
+    /// Verifies and strips off the checksum appended by
+    /// [`seal_with_checksum`](Self::seal_with_checksum) and creates a coder for the
+    /// remaining compressed data.
+    ///
+    /// Returns [`ChecksumError::InvalidData`] if the checksum matches but the data can't be
+    /// passed to [`from_compressed`](Self::from_compressed).
+    pub fn from_checksummed(compressed: Vec<Word>) -> Result<Self, ChecksumError> {
+        let compressed = checksum::verify_checksum(compressed)?;
+        Self::from_compressed(compressed).map_err(|_| ChecksumError::InvalidData)
+    }
+
+    /// Consumes the `AnsCoder` and returns a decoder whose compressed data lives in a
+    /// reference counted buffer.
+    ///
+    /// This moves the compressed data into an `Arc<[Word]>` once, after which you can call
+    /// [`fork_read_only`] on the returned decoder to obtain any number of additional
+    /// decoders that share the same buffer but have their own position and state. Since
+    /// the buffer is immutable and reference counted, the forked decoders can be sent to
+    /// different threads, e.g., to decode independent chunks in parallel after [`seek`]ing
+    /// each one to a different position.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::{
+    ///     stream::{model::DefaultLeakyQuantizer, stack::DefaultAnsCoder, Decode},
+    ///     Pos, Seek,
+    /// };
+    ///
+    /// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+    /// let model = quantizer.quantize(probability::distribution::Gaussian::new(0.0, 10.0));
+    ///
+    /// // Encode two chunks and remember where the (in decoding order) second chunk starts.
+    /// let mut coder = DefaultAnsCoder::new();
+    /// coder.encode_iid_symbols_reverse(&[7, -3, 12], &model).unwrap();
+    /// let second_chunk = coder.pos();
+    /// coder.encode_iid_symbols_reverse(&[-20, 5], &model).unwrap();
+    ///
+    /// let decoder = coder.into_shared_decoder();
+    /// let mut fork = decoder.fork_read_only();
+    /// let handle = std::thread::spawn(move || {
+    ///     fork.seek(second_chunk).unwrap();
+    ///     fork.decode_iid_symbols(3, &model).collect::<Result<Vec<_>, _>>().unwrap()
+    /// });
+    ///
+    /// let mut decoder = decoder;
+    /// let first = decoder.decode_iid_symbols(2, &model).collect::<Result<Vec<_>, _>>();
+    /// assert_eq!(first.unwrap(), [-20, 5]);
+    /// assert_eq!(handle.join().unwrap(), [7, -3, 12]);
+    /// ```
+    ///
+    /// [`fork_read_only`]: AnsCoder::fork_read_only
+    /// [`seek`]: crate::Seek::seek
+    pub fn into_shared_decoder(self) -> AnsCoder<Word, State, Cursor<Word, Arc<[Word]>>> {
+        let (bulk, state) = self.into_raw_parts();
+        AnsCoder {
+            bulk: Cursor::new_at_write_end(bulk.into()),
+            state,
+            phantom: PhantomData,
+        }
+    }
 }
 
 impl<'bulk, Word, State> AnsCoder<Word, State, Cursor<Word, &'bulk [Word]>>
@@ -746,6 +944,56 @@ where
         self.encode_iid_symbols(symbols.into_iter().rev(), model)
     }
 
+    /// Pushes a raw fixed-point uniform `quantile` with `precision` bits onto the stack.
+    ///
+    /// This is equivalent to encoding the symbol `quantile` with a uniform entropy model
+    /// over the alphabet `0..(1 << precision)` and `PRECISION = precision`, except that
+    /// `precision` is a runtime parameter and no entropy model is involved. It is the
+    /// inverse of [`decode_uniform`], which is the core operation of bits-back coding: the
+    /// decoder draws a sample by calling `decode_uniform` (which consumes bits from the
+    /// stack), and the encoder returns those bits by calling `encode_uniform` with the
+    /// same quantile. Pushing a uniform onto the stack always increases the size of the
+    /// compressed data by exactly `precision` bits (up to rounding).
+    ///
+    /// # Panics
+    ///
+    /// If `precision` is zero or larger than `Word::BITS`, or if `quantile` doesn't fit
+    /// into `precision` bits.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use constriction::stream::stack::DefaultAnsCoder;
+    ///
+    /// let mut ans = DefaultAnsCoder::new();
+    /// ans.encode_uniform(0x2A5, 10).unwrap();
+    /// ans.encode_uniform(3, 2).unwrap();
+    ///
+    /// assert_eq!(ans.decode_uniform(2).unwrap(), 3);
+    /// assert_eq!(ans.decode_uniform(10).unwrap(), 0x2A5);
+    /// assert!(ans.is_empty());
+    /// ```
+    ///
+    /// [`decode_uniform`]: Self::decode_uniform
+    pub fn encode_uniform(
+        &mut self,
+        quantile: Word,
+        precision: usize,
+    ) -> Result<(), Backend::WriteError> {
+        assert!(precision != 0 && precision <= Word::BITS);
+        assert!(precision == Word::BITS || quantile >> precision == Word::zero());
+
+        // Same as `encode_symbol` with `probability == 1` and `PRECISION == precision`.
+        if self.state >> (State::BITS - precision) != State::zero() {
+            let (word, state) = renorm::split_low(self.state);
+            self.bulk.write(word)?;
+            self.state = state;
+        }
+        self.state = self.state << precision | quantile.into();
+
+        Ok(())
+    }
+
     /// Consumes the ANS coder and returns the compressed data.
     ///
     /// The returned data can be used to recreate an ANS coder with the same state
@@ -842,7 +1090,7 @@ where
     pub fn into_binary(mut self) -> Result<Backend, Option<Backend::WriteError>> {
         let valid_bits = (State::BITS - 1).wrapping_sub(self.state.leading_zeros() as usize);
 
-        if valid_bits % Word::BITS != 0 || valid_bits == usize::max_value() {
+        if valid_bits % Word::BITS != 0 || valid_bits == usize::MAX {
             Err(None)
         } else {
             let truncated_state = self.state ^ (State::one() << valid_bits);
@@ -853,6 +1101,41 @@ where
     }
 }
 
+impl<Word, State, Backend> AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Stack>,
+{
+    /// Pops a raw fixed-point uniform with `precision` bits off the stack.
+    ///
+    /// This is the inverse of [`encode_uniform`], see there for details and an example.
+    /// Like [`decode_symbol`](Decode::decode_symbol), this method doesn't fail if the
+    /// stack is empty. It then returns quantiles that are deterministic and not uniformly
+    /// distributed, but pushing them back with `encode_uniform` still restores the
+    /// original state of the coder.
+    ///
+    /// # Panics
+    ///
+    /// If `precision` is zero or larger than `Word::BITS`.
+    ///
+    /// [`encode_uniform`]: Self::encode_uniform
+    pub fn decode_uniform(&mut self, precision: usize) -> Result<Word, Backend::ReadError> {
+        assert!(precision != 0 && precision <= Word::BITS);
+
+        // Same as `decode_symbol` with `probability == 1` and `PRECISION == precision`.
+        let quantile = (self.state % (State::one() << precision)).as_();
+        self.state = self.state >> precision;
+        if renorm::needs_renorm::<Word, State>(self.state) {
+            if let Some(word) = self.bulk.read()? {
+                self.state = renorm::shift_in_low(self.state, word);
+            }
+        }
+
+        Ok(quantile)
+    }
+}
+
 impl<Word, State, Buf> AnsCoder<Word, State, Cursor<Word, Buf>>
 where
     Word: BitArray,
@@ -885,6 +1168,32 @@ where
     }
 }
 
+impl<Word, State, Buf> AnsCoder<Word, State, Cursor<Word, Buf>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Buf: SafeBuf<Word> + Clone,
+{
+    /// Returns a new read-only decoder that shares its compressed data with `self`.
+    ///
+    /// The returned decoder starts out at the same position and state as `self` but is
+    /// otherwise independent of it, i.e., decoding from (or [`seek`]ing) either decoder
+    /// does not affect the other one. Forking is cheap if cloning `Buf` is cheap, which is
+    /// the case for a shared reference `&[Word]` and for the reference counted buffers
+    /// `Rc<[Word]>` and `Arc<[Word]>`. With `Buf = Arc<[Word]>` (see
+    /// [`into_shared_decoder`]), the forks can be moved to different threads.
+    ///
+    /// [`seek`]: crate::Seek::seek
+    /// [`into_shared_decoder`]: AnsCoder::into_shared_decoder
+    pub fn fork_read_only(&self) -> Self {
+        AnsCoder {
+            bulk: self.bulk.clone(),
+            state: self.state,
+            phantom: PhantomData,
+        }
+    }
+}
+
 impl<Word, State, Backend> Code for AnsCoder<Word, State, Backend>
 where
     Word: BitArray + Into<State>,
@@ -945,8 +1254,9 @@ where
             .ok_or_else(|| DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())?;
 
         if (self.state >> (State::BITS - PRECISION)) >= probability.get().into().into() {
-            self.bulk.write(self.state.as_())?;
-            self.state = self.state >> Word::BITS;
+            let (word, state) = renorm::split_low(self.state);
+            self.bulk.write(word)?;
+            self.state = state;
             // At this point, the invariant on `self.state` (see its doc comment) is
             // temporarily violated, but it will be restored below.
         }
@@ -1007,11 +1317,11 @@ where
         let remainder = quantile - left_sided_cumulative;
         self.state =
             (self.state >> PRECISION) * probability.get().into().into() + remainder.into().into();
-        if self.state < State::one() << (State::BITS - Word::BITS) {
+        if renorm::needs_renorm::<Word, State>(self.state) {
             // Invariant on `self.state` (see its doc comment) is violated. Restore it by
             // refilling with a compressed word from `self.bulk` if available.
             if let Some(word) = self.bulk.read()? {
-                self.state = (self.state << Word::BITS) | word.into();
+                self.state = renorm::shift_in_low(self.state, word);
             }
         }
 
@@ -1023,6 +1333,35 @@ where
     }
 }
 
+impl<Word, State, Backend, const PRECISION: usize> StrictDecode<PRECISION>
+    for AnsCoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Stack>,
+{
+    /// Always decodes a symbol since an `AnsCoder` can't detect the end of its data.
+    ///
+    /// Every state of an `AnsCoder`, including the empty state, can result from encoding
+    /// valid symbols. In particular, encoding a symbol whose left-sided cumulative is zero
+    /// onto an empty `AnsCoder` leaves it empty, and decoding from an empty `AnsCoder`
+    /// produces exactly such a symbol and leaves the coder empty. Rejecting any state would
+    /// therefore reject some valid data. See [module level documentation of
+    /// `strict`](super::strict) for alternatives.
+    #[inline(always)]
+    fn decode_symbol_within_data<M>(
+        &mut self,
+        model: M,
+    ) -> Result<Option<M::Symbol>, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: DecoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.decode_symbol(model).map(Some)
+    }
+}
+
 impl<Word, State, Backend> PosSeek for AnsCoder<Word, State, Backend>
 where
     Word: BitArray + Into<State>,
@@ -1039,6 +1378,12 @@ where
     State: BitArray + AsPrimitive<Word>,
     Backend: Seek,
 {
+    /// Jumps to a position and state that were recorded with [`Pos::pos`](crate::Pos::pos).
+    ///
+    /// See [`Seek::seek`](crate::Seek::seek) for the guarantees close to the end of the
+    /// compressed data. Since an `AnsCoder` only reads from its backend when it decodes
+    /// symbols, seeking can only fail if the backend rejects `pos`, in which case the coder
+    /// is left unchanged.
     fn seek(&mut self, (pos, state): Self::Position) -> Result<(), ()> {
         self.bulk.seek(pos)?;
         self.state = state;
@@ -1145,7 +1490,7 @@ where
 mod tests {
     use super::super::model::{
         ContiguousCategoricalEntropyModel, DefaultLeakyQuantizer, IterableEntropyModel,
-        LeakyQuantizer,
+        LeakyQuantizer, UniformModel,
     };
     use super::*;
     extern crate std;
@@ -1205,7 +1550,7 @@ mod tests {
 
         // We don't reuse the same encoder for decoding because we want to test
         // if exporting and re-importing of compressed data works.
-        encoder.encode_iid_symbols(symbols.clone(), &model).unwrap();
+        encoder.encode_iid_symbols(symbols.clone(), model).unwrap();
         let compressed = encoder.into_compressed().unwrap();
         assert_eq!(compressed.len(), expected_size);
 
@@ -1321,10 +1666,7 @@ mod tests {
             let std_dev = (10.0 / u32::MAX as f64) * rng.next_u32() as f64 + 0.001;
             let quantile = (rng.next_u32() as f64 + 0.5) / (1u64 << 32) as f64;
             let dist = Gaussian::new(mean, std_dev);
-            let symbol = core::cmp::max(
-                -127,
-                core::cmp::min(127, dist.inverse(quantile).round() as i32),
-            );
+            let symbol = (dist.inverse(quantile).round() as i32).clamp(-127, 127);
 
             symbols_gaussian.push(symbol);
             means.push(mean);
@@ -1481,4 +1823,164 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn fork_read_only() {
+        const NUM_CHUNKS: usize = 8;
+        const SYMBOLS_PER_CHUNK: usize = 100;
+
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+
+        let mut encoder = DefaultAnsCoder::new();
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let mut symbols = Vec::with_capacity(NUM_CHUNKS);
+        let mut jump_table = Vec::with_capacity(NUM_CHUNKS);
+
+        for _ in 0..NUM_CHUNKS {
+            let chunk = (0..SYMBOLS_PER_CHUNK)
+                .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                .collect::<Vec<_>>();
+            encoder.encode_iid_symbols_reverse(&chunk, &model).unwrap();
+            symbols.push(chunk);
+            jump_table.push(encoder.pos());
+        }
+
+        let mut decoder = encoder.into_shared_decoder();
+        let handles = jump_table
+            .iter()
+            .zip(&symbols)
+            .map(|(&pos_and_state, chunk)| {
+                let mut fork = decoder.fork_read_only();
+                let chunk = chunk.clone();
+                std::thread::spawn(move || {
+                    let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+                    let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+                    fork.seek(pos_and_state).unwrap();
+                    let decoded = fork
+                        .decode_iid_symbols(SYMBOLS_PER_CHUNK, &model)
+                        .collect::<Result<Vec<_>, _>>()
+                        .unwrap();
+                    assert_eq!(decoded, chunk);
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // Decoding from the original decoder isn't affected by the forks.
+        for chunk in symbols.iter().rev() {
+            let decoded = decoder
+                .decode_iid_symbols(SYMBOLS_PER_CHUNK, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded, chunk);
+        }
+        assert!(decoder.is_empty());
+
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn take_compressed() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+        let mut rng = Xoshiro256StarStar::seed_from_u64(456);
+
+        let mut coder = DefaultAnsCoder::new();
+        assert!(coder.take_compressed().is_empty());
+
+        for &len in &[1, 10, 0, 100] {
+            let symbols = (0..len)
+                .map(|_| model.quantile_function(rng.next_u32() % (1 << 24)).0)
+                .collect::<Vec<_>>();
+            coder.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+            let expected = coder.clone().into_compressed().unwrap();
+
+            let compressed = coder.take_compressed();
+            assert_eq!(compressed, expected);
+            assert!(coder.is_empty());
+            assert!(coder.bulk.capacity() >= compressed.capacity());
+            assert_eq!(coder.state(), 0);
+
+            let mut decoder = DefaultAnsCoder::from_compressed(compressed).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(len, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn uniform() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(743);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+
+        // Interleave uniforms of various precisions with symbols from a regular model.
+        let items = (0..1000)
+            .map(|_| {
+                let precision = (rng.next_u32() % 33) as usize;
+                let value = rng.next_u32();
+                if precision == 0 {
+                    (0, (value % 201) as i32 - 100)
+                } else {
+                    let quantile = if precision == 32 {
+                        value
+                    } else {
+                        value % (1 << precision)
+                    };
+                    (precision, quantile as i32)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut coder = DefaultAnsCoder::new();
+        for &(precision, value) in items.iter().rev() {
+            if precision == 0 {
+                coder.encode_symbol(value, model).unwrap();
+            } else {
+                coder.encode_uniform(value as u32, precision).unwrap();
+            }
+        }
+        let compressed = coder.get_compressed().unwrap().clone();
+
+        for &(precision, value) in &items {
+            if precision == 0 {
+                assert_eq!(coder.decode_symbol(model).unwrap(), value);
+            } else {
+                assert_eq!(coder.decode_uniform(precision).unwrap(), value as u32);
+            }
+        }
+        assert!(coder.is_empty());
+
+        // Decoding past the end of the data and pushing the result back restores the coder.
+        let mut bits_back = DefaultAnsCoder::from_compressed(alloc::vec![0x1234_5678]).unwrap();
+        let samples = (0..100)
+            .map(|i| bits_back.decode_uniform(1 + i % 32).unwrap())
+            .collect::<Vec<_>>();
+        assert!(bits_back.is_empty());
+        for (i, &sample) in samples.iter().enumerate().rev() {
+            bits_back.encode_uniform(sample, 1 + i % 32).unwrap();
+        }
+        assert_eq!(bits_back.into_compressed().unwrap(), [0x1234_5678]);
+
+        // `encode_uniform` agrees with a uniform entropy model.
+        let mut coder = DefaultAnsCoder::from_compressed(compressed).unwrap();
+        let uniform_model = UniformModel::<u32, 8>::new(256);
+        let mut reference = coder.clone();
+        for quantile in 0..256 {
+            coder.encode_uniform(quantile, 8).unwrap();
+            reference.encode_symbol(quantile, uniform_model).unwrap();
+        }
+        assert_eq!(
+            *coder.get_compressed().unwrap(),
+            *reference.get_compressed().unwrap()
+        );
+    }
 }
@@ -0,0 +1,494 @@
+use alloc::vec::Vec;
+use core::{borrow::Borrow, convert::Infallible, fmt::Debug, marker::PhantomData};
+
+use num::cast::AsPrimitive;
+
+use super::super::{
+    model::{DecoderModel, EncoderModel},
+    renorm, Code, Decode, Encode,
+};
+use crate::{
+    backends::{BoundedReadWords, ReadWords, WriteWords},
+    BitArray, CoderError, DefaultEncoderError, DefaultEncoderFrontendError, NonZeroBitArray, Stack,
+};
+
+/// Entropy coder on a stack that interleaves several independent rANS states.
+///
+/// An `InterleavedAnsCoder` behaves like an [`AnsCoder`] (i.e., it encodes and decodes
+/// symbols in "last in first out" order, and you can interleave encoding and decoding
+/// operations arbitrarily), but it maintains `LANES` independent ANS states and assigns
+/// consecutive symbols to the states in round-robin order. All states read and write
+/// compressed words from and to a single shared buffer. This is transparent to the user:
+/// you encode and decode symbols with the usual methods of the [`Encode`] and [`Decode`]
+/// traits, and the coder keeps track of which state is responsible for the next symbol.
+///
+/// The advantage of interleaving is that decoding `LANES` consecutive symbols involves
+/// `LANES` independent chains of arithmetic operations (which are dominated by a
+/// multiplication and, for many entropy models, a table lookup). Modern CPUs can execute
+/// these chains in parallel, which can speed up decoding considerably, in particular with
+/// `LANES = 2` or `LANES = 4` and with fast entropy models such as a
+/// [`SmallContiguousLookupDecoderModel`]. The price is a slightly larger constant overhead:
+/// [`into_compressed`](Self::into_compressed) writes out all `LANES` states in full.
+///
+/// Compressed data is not compatible between coders with different `LANES`, nor between an
+/// `InterleavedAnsCoder` and an [`AnsCoder`].
+///
+/// # Example
+///
+/// ```
+/// use constriction::stream::{
+///     model::DefaultLeakyQuantizer, stack::DefaultInterleavedAnsCoder, Decode,
+/// };
+/// use probability::distribution::Gaussian;
+///
+/// let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+/// let model = quantizer.quantize(Gaussian::new(0.0, 10.0));
+/// let symbols = vec![-10, 4, 0, 3, 27, -5, 8];
+///
+/// // Use four interleaved states. Encode in reverse order since this is a stack.
+/// let mut coder = DefaultInterleavedAnsCoder::<4>::new();
+/// coder.encode_iid_symbols_reverse(&symbols, model).unwrap();
+/// let compressed = coder.into_compressed().unwrap();
+///
+/// let mut coder = DefaultInterleavedAnsCoder::<4>::from_compressed(compressed).unwrap();
+/// let decoded = coder
+///     .decode_iid_symbols(symbols.len(), model)
+///     .collect::<Result<Vec<_>, _>>()
+///     .unwrap();
+/// assert_eq!(decoded, symbols);
+/// assert!(coder.is_empty());
+/// ```
+///
+/// [`AnsCoder`]: super::AnsCoder
+/// [`SmallContiguousLookupDecoderModel`]: crate::stream::model::SmallContiguousLookupDecoderModel
+#[derive(Clone)]
+pub struct InterleavedAnsCoder<Word, State, const LANES: usize, Backend = Vec<Word>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    bulk: Backend,
+
+    /// Invariant: `states[i] >= State::one() << (State::BITS - Word::BITS)` for all `i`.
+    states: [State; LANES],
+
+    /// Index into `states` for the next encoded symbol. The next decoded symbol uses the
+    /// state that precedes it (cyclically).
+    cursor: usize,
+
+    /// We keep track of the `Word` type so that we can statically enforce the invariant
+    /// `Word: Into<State>`.
+    phantom: PhantomData<Word>,
+}
+
+/// Type alias for an [`InterleavedAnsCoder`] with sane parameters for typical use cases.
+pub type DefaultInterleavedAnsCoder<const LANES: usize, Backend = Vec<u32>> =
+    InterleavedAnsCoder<u32, u64, LANES, Backend>;
+
+/// Type alias for an [`InterleavedAnsCoder`] for use with [lookup models].
+///
+/// [lookup models]: crate::stream::model::SmallContiguousLookupDecoderModel
+pub type SmallInterleavedAnsCoder<const LANES: usize, Backend = Vec<u16>> =
+    InterleavedAnsCoder<u16, u32, LANES, Backend>;
+
+impl<Word, State, const LANES: usize, Backend> Debug
+    for InterleavedAnsCoder<Word, State, LANES, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: Debug,
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("InterleavedAnsCoder")
+            .field("bulk", &self.bulk)
+            .field("states", &self.states)
+            .field("cursor", &self.cursor)
+            .finish()
+    }
+}
+
+impl<Word, State, const LANES: usize> InterleavedAnsCoder<Word, State, LANES, Vec<Word>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    /// Creates an empty interleaved ANS coder.
+    ///
+    /// To create an empty coder with a different backend than a `Vec`, call
+    /// [`Default::default`] instead.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<Word, State, const LANES: usize, Backend> Default
+    for InterleavedAnsCoder<Word, State, LANES, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: Default,
+{
+    fn default() -> Self {
+        Self::with_backend(Default::default())
+    }
+}
+
+impl<Word, State, const LANES: usize, Backend> InterleavedAnsCoder<Word, State, LANES, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    /// Creates an empty coder that appends compressed data to `backend`.
+    ///
+    /// # Panics
+    ///
+    /// If `LANES == 0`, or if `State` can't hold two `Word`s or isn't a multiple of a
+    /// `Word`.
+    pub fn with_backend(backend: Backend) -> Self {
+        assert!(LANES > 0);
+        assert!(State::BITS >= 2 * Word::BITS);
+        assert_eq!(State::BITS % Word::BITS, 0);
+
+        Self {
+            bulk: backend,
+            states: [renorm::lower_bound::<Word, State>(); LANES],
+            cursor: 0,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Creates a coder for decoding the compressed data that was obtained from
+    /// [`into_compressed`](Self::into_compressed).
+    ///
+    /// Returns `Err(compressed)` if `compressed` is too short to contain all `LANES`
+    /// states or if it contains an invalid state.
+    pub fn from_compressed(mut compressed: Backend) -> Result<Self, Backend>
+    where
+        Backend: ReadWords<Word, Stack>,
+    {
+        let mut coder_states = [State::zero(); LANES];
+        for state in coder_states.iter_mut().rev() {
+            for _ in 0..Self::words_per_state() {
+                match compressed.read() {
+                    Ok(Some(word)) => *state = renorm::shift_in_low(*state, word),
+                    _ => return Err(compressed),
+                }
+            }
+            if renorm::needs_renorm::<Word, State>(*state) {
+                return Err(compressed);
+            }
+        }
+
+        let mut coder = Self::with_backend(compressed);
+        coder.states = coder_states;
+        Ok(coder)
+    }
+
+    /// Writes out all states and returns the compressed data.
+    ///
+    /// The returned data can be decoded again with [`from_compressed`](Self::from_compressed).
+    pub fn into_compressed(mut self) -> Result<Backend, Backend::WriteError>
+    where
+        Backend: WriteWords<Word>,
+    {
+        // Rotate the states so that a fresh decoder, which starts at `cursor == 0`, picks up
+        // where we left off.
+        self.states.rotate_left(self.cursor);
+        for &state in &self.states {
+            let mut state = state;
+            for _ in 0..Self::words_per_state() {
+                let (word, remainder) = renorm::split_low(state);
+                self.bulk.write(word)?;
+                state = remainder;
+            }
+        }
+        Ok(self.bulk)
+    }
+
+    /// Returns `true` if no data for decoding is left.
+    pub fn is_empty(&self) -> bool
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        self.bulk.is_exhausted() && self.states_are_initial()
+    }
+
+    /// Returns the number of compressed words that [`into_compressed`] would return.
+    ///
+    /// [`into_compressed`]: Self::into_compressed
+    pub fn num_words(&self) -> usize
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        self.bulk.remaining() + LANES * Self::words_per_state()
+    }
+
+    /// Returns the number of compressed bits that [`into_compressed`] would return.
+    ///
+    /// [`into_compressed`]: Self::into_compressed
+    pub fn num_bits(&self) -> usize
+    where
+        Backend: BoundedReadWords<Word, Stack>,
+    {
+        Word::BITS * self.num_words()
+    }
+
+    /// Returns a reference to the backend, which doesn't contain the states.
+    pub fn bulk(&self) -> &Backend {
+        &self.bulk
+    }
+
+    pub fn encode_symbols_reverse<S, M, I, const PRECISION: usize>(
+        &mut self,
+        symbols_and_models: I,
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        Backend: WriteWords<Word>,
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = (S, M)>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        self.encode_symbols(symbols_and_models.into_iter().rev())
+    }
+
+    pub fn encode_iid_symbols_reverse<S, M, I, const PRECISION: usize>(
+        &mut self,
+        symbols: I,
+        model: M,
+    ) -> Result<(), DefaultEncoderError<Backend::WriteError>>
+    where
+        Backend: WriteWords<Word>,
+        S: Borrow<M::Symbol>,
+        M: EncoderModel<PRECISION> + Copy,
+        M::Probability: Into<Word>,
+        Word: AsPrimitive<M::Probability>,
+        I: IntoIterator<Item = S>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        self.encode_iid_symbols(symbols.into_iter().rev(), model)
+    }
+
+    fn words_per_state() -> usize {
+        State::BITS / Word::BITS
+    }
+
+    fn states_are_initial(&self) -> bool {
+        let initial = renorm::lower_bound::<Word, State>();
+        self.states.iter().all(|&state| state == initial)
+    }
+}
+
+impl<Word, State, const LANES: usize, Backend> Code
+    for InterleavedAnsCoder<Word, State, LANES, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    type Word = Word;
+
+    /// The states of all lanes, in the order in which they'll be used for decoding (i.e.,
+    /// the last entry is the state that will decode the next symbol).
+    type State = [State; LANES];
+
+    fn state(&self) -> Self::State {
+        let mut states = self.states;
+        states.rotate_left(self.cursor);
+        states
+    }
+}
+
+impl<Word, State, const LANES: usize, Backend, const PRECISION: usize> Encode<PRECISION>
+    for InterleavedAnsCoder<Word, State, LANES, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word>,
+{
+    type FrontendError = DefaultEncoderFrontendError;
+    type BackendError = Backend::WriteError;
+
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), DefaultEncoderError<Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        let (left_sided_cumulative, probability) = model
+            .left_cumulative_and_probability(symbol)
+            .ok_or_else(|| DefaultEncoderFrontendError::ImpossibleSymbol.into_coder_error())?;
+
+        let lane = self.cursor;
+        let mut state = self.states[lane];
+        if (state >> (State::BITS - PRECISION)) >= probability.get().into().into() {
+            let (word, remainder) = renorm::split_low(state);
+            self.bulk.write(word)?;
+            state = remainder;
+        }
+
+        let remainder = (state % probability.get().into().into()).as_().as_();
+        let prefix = state / probability.get().into().into();
+        let quantile = left_sided_cumulative + remainder;
+        self.states[lane] = prefix << PRECISION | quantile.into().into();
+        self.cursor = if lane + 1 == LANES { 0 } else { lane + 1 };
+
+        Ok(())
+    }
+
+    fn maybe_full(&self) -> bool {
+        self.bulk.maybe_full()
+    }
+}
+
+impl<Word, State, const LANES: usize, Backend, const PRECISION: usize> Decode<PRECISION>
+    for InterleavedAnsCoder<Word, State, LANES, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Stack>,
+{
+    /// Like with an [`AnsCoder`](super::AnsCoder), decoding is deterministic and cannot
+    /// fail, even past the end of the compressed data.
+    type FrontendError = Infallible;
+
+    type BackendError = Backend::ReadError;
+
+    #[inline(always)]
+    fn decode_symbol<M>(
+        &mut self,
+        model: M,
+    ) -> Result<M::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        M: DecoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        let lane = if self.cursor == 0 {
+            LANES - 1
+        } else {
+            self.cursor - 1
+        };
+        self.cursor = lane;
+
+        let state = self.states[lane];
+        let quantile = (state % (State::one() << PRECISION)).as_().as_();
+        let (symbol, left_sided_cumulative, probability) = model.quantile_function(quantile);
+        let remainder = quantile - left_sided_cumulative;
+        let mut state =
+            (state >> PRECISION) * probability.get().into().into() + remainder.into().into();
+        if renorm::needs_renorm::<Word, State>(state) {
+            // The invariant on the state is violated. Restore it by refilling with a
+            // compressed word from `self.bulk` if available.
+            if let Some(word) = self.bulk.read()? {
+                state = renorm::shift_in_low(state, word);
+            }
+        }
+        self.states[lane] = state;
+
+        Ok(symbol)
+    }
+
+    fn maybe_exhausted(&self) -> bool {
+        self.bulk.maybe_exhausted() && self.states_are_initial()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{super::model::DefaultLeakyQuantizer, DefaultAnsCoder};
+    use super::*;
+
+    use probability::distribution::Gaussian;
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    fn test_round_trip<const LANES: usize>(amt: usize) {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(amt as u64 + LANES as u64);
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let means = (0..amt)
+            .map(|_| (rng.next_u32() % 100) as f64 - 50.0)
+            .collect::<Vec<_>>();
+        let symbols = means
+            .iter()
+            .map(|&mean| mean as i32 + (rng.next_u32() % 21) as i32 - 10)
+            .collect::<Vec<_>>();
+        let models = means
+            .iter()
+            .map(|&mean| quantizer.quantize(Gaussian::new(mean, 5.0)))
+            .collect::<Vec<_>>();
+
+        let mut coder = DefaultInterleavedAnsCoder::<LANES>::new();
+        assert!(coder.is_empty());
+        coder
+            .encode_symbols_reverse(symbols.iter().zip(&models))
+            .unwrap();
+        assert_eq!(coder.is_empty(), amt == 0);
+
+        let mut reference = DefaultAnsCoder::new();
+        reference
+            .encode_symbols_reverse(symbols.iter().zip(&models))
+            .unwrap();
+        assert!(coder.num_bits() <= reference.num_bits() + 64 * LANES + 64);
+
+        let num_words = coder.num_words();
+        let compressed = coder.into_compressed().unwrap();
+        assert_eq!(compressed.len(), num_words);
+
+        let mut coder = DefaultInterleavedAnsCoder::<LANES>::from_compressed(compressed).unwrap();
+        let decoded = coder
+            .decode_symbols(&models)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(coder.is_empty());
+    }
+
+    #[test]
+    fn round_trip() {
+        for amt in [0, 1, 3, 4, 5, 1000, 1001] {
+            test_round_trip::<1>(amt);
+            test_round_trip::<2>(amt);
+            test_round_trip::<4>(amt);
+        }
+    }
+
+    #[test]
+    fn interleaved_encoding_and_decoding() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let model = quantizer.quantize(Gaussian::new(0.0, 20.0));
+        let mut coder = DefaultInterleavedAnsCoder::<4>::new();
+
+        coder.encode_iid_symbols_reverse([1, 2, 3], model).unwrap();
+        assert_eq!(coder.decode_symbol(model).unwrap(), 1);
+        coder.encode_iid_symbols_reverse([-7, 8], model).unwrap();
+        let decoded = coder
+            .decode_iid_symbols(4, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, [-7, 8, 2, 3]);
+        assert!(coder.is_empty());
+
+        // The returned state is independent of how the lanes are labeled internally.
+        let mut coder1 = DefaultInterleavedAnsCoder::<2>::new();
+        coder1.encode_iid_symbols([5, 6, 7], model).unwrap();
+        let compressed = coder1.clone().into_compressed().unwrap();
+        let coder2 = DefaultInterleavedAnsCoder::<2>::from_compressed(compressed).unwrap();
+        assert_eq!(coder1.state(), coder2.state());
+    }
+
+    #[test]
+    fn invalid_compressed_data() {
+        assert!(DefaultInterleavedAnsCoder::<2>::from_compressed(alloc::vec![1, 2, 3]).is_err());
+        // The high word of a state can't be zero.
+        assert!(DefaultInterleavedAnsCoder::<2>::from_compressed(alloc::vec![1, 0, 1, 1]).is_err());
+        assert!(DefaultInterleavedAnsCoder::<2>::from_compressed(alloc::vec![0, 1, 0, 1]).is_ok());
+    }
+}
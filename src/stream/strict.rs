@@ -0,0 +1,343 @@
+//! Detecting attempts to decode more symbols than were encoded
+//!
+//! By default, an [`AnsCoder`] and a [`RangeDecoder`] happily keep decoding symbols after
+//! they've run out of compressed data. The symbols that they produce in this case are a
+//! deterministic function of the compressed data and the entropy models, but they don't
+//! correspond to anything that was encoded. This permissive behavior is deliberate since it
+//! is what makes bits-back coding with an `AnsCoder` work, but it also means that a bug in
+//! your code that decodes too many symbols (e.g., because it uses a different message
+//! length than the encoder) goes unnoticed.
+//!
+//! Wrapping a decoder in a [`StrictDecoder`] makes it fail with
+//! [`StrictDecodeError::OutOfCompressedData`] instead when it detects that decoding a
+//! symbol would require compressed data beyond the end of the data. A failed call leaves
+//! the wrapped decoder unchanged. The detection is not perfect, though, since a stream code
+//! doesn't store the number of encoded symbols:
+//!
+//! - An [`AnsCoder`] never fails. Encoding a symbol whose left-sided cumulative is zero
+//!   (e.g., the symbol `0` of a categorical model) onto an empty `AnsCoder` doesn't
+//!   change the compressed data, so every state of an `AnsCoder` (including the empty
+//!   state) can result from encoding valid symbols, and rejecting any of them would
+//!   reject valid data. `StrictDecoder` still accepts an `AnsCoder` so that generic code
+//!   can be written against both kinds of decoders.
+//! - A [`RangeDecoder`] fails once it would have to read more compressed words than the
+//!   encoder's final flush could have omitted. This never rejects valid data, but since
+//!   symbols past the end of the message often fit into the words that were already
+//!   read, a `RangeDecoder` may still produce some garbage symbols before it fails (the
+//!   more predictable the symbols, the more of them).
+//!
+//! Thus, strict mode catches the most common mistakes with a `RangeDecoder`, but it is not
+//! a replacement for storing the message length (which a
+//! [`TerminatedRangeEncoder`](super::terminated::TerminatedRangeEncoder) can do for you).
+//! If you decode data from an untrusted source, also consider bounding the number of
+//! decoded symbols with a [`LimitedDecoder`](super::limit::LimitedDecoder).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::{
+//!     stream::{
+//!         model::DefaultLeakyQuantizer,
+//!         queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+//!         strict::{StrictDecodeError, StrictDecoder},
+//!         Decode, Encode,
+//!     },
+//!     CoderError,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+//! let symbols = [12, -5, 31, 0, -18];
+//!
+//! let mut encoder = DefaultRangeEncoder::new();
+//! encoder.encode_iid_symbols(&symbols, &model).unwrap();
+//! let compressed = encoder.into_compressed().unwrap();
+//!
+//! let decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+//! let mut decoder = StrictDecoder::new(decoder);
+//! let decoded = decoder
+//!     .decode_iid_symbols(5, &model)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, symbols);
+//!
+//! // Decoding too many symbols eventually fails instead of producing garbage forever.
+//! let err = loop {
+//!     if let Err(err) = decoder.decode_symbol(&model) {
+//!         break err;
+//!     }
+//! };
+//! assert!(matches!(
+//!     err,
+//!     CoderError::Frontend(StrictDecodeError::OutOfCompressedData)
+//! ));
+//! ```
+//!
+//! [`AnsCoder`]: super::stack::AnsCoder
+//! [`RangeDecoder`]: super::queue::RangeDecoder
+
+use core::fmt::{Debug, Display};
+
+use num::cast::AsPrimitive;
+
+use super::{model::DecoderModel, Code, Decode};
+use crate::{CoderError, Pos, PosSeek, Seek};
+
+/// A decoder that can detect whether decoding a symbol would require compressed data
+/// beyond the end of its data.
+///
+/// Implemented for [`AnsCoder`](super::stack::AnsCoder) and
+/// [`RangeDecoder`](super::queue::RangeDecoder). You'll usually use this trait through a
+/// [`StrictDecoder`] rather than directly.
+pub trait StrictDecode<const PRECISION: usize>: Decode<PRECISION> {
+    /// Decodes a symbol unless this would require compressed data beyond the end of the
+    /// data, in which case it returns `Ok(None)` and leaves `self` unchanged.
+    #[allow(clippy::type_complexity)]
+    fn decode_symbol_within_data<D>(
+        &mut self,
+        model: D,
+    ) -> Result<Option<D::Symbol>, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<D::Probability>;
+}
+
+/// Wrapper around a decoder that fails instead of decoding past the end of the data.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct StrictDecoder<Decoder> {
+    inner: Decoder,
+}
+
+impl<Decoder> StrictDecoder<Decoder> {
+    /// Wraps `inner` so that it fails instead of decoding past the end of its data.
+    pub fn new(inner: Decoder) -> Self {
+        Self { inner }
+    }
+
+    /// Returns a reference to the wrapped decoder.
+    pub fn get_ref(&self) -> &Decoder {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the wrapped decoder.
+    ///
+    /// Decoding directly from the returned reference is permissive again.
+    pub fn get_mut(&mut self) -> &mut Decoder {
+        &mut self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped decoder.
+    pub fn into_inner(self) -> Decoder {
+        self.inner
+    }
+}
+
+impl<Decoder: Code> Code for StrictDecoder<Decoder> {
+    type Word = Decoder::Word;
+    type State = Decoder::State;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Decoder, const PRECISION: usize> Decode<PRECISION> for StrictDecoder<Decoder>
+where
+    Decoder: StrictDecode<PRECISION>,
+{
+    type FrontendError = StrictDecodeError<Decoder::FrontendError>;
+    type BackendError = Decoder::BackendError;
+
+    /// Decodes a symbol from the wrapped decoder unless this would require compressed data
+    /// beyond the end of the data.
+    fn decode_symbol<D>(
+        &mut self,
+        model: D,
+    ) -> Result<D::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<D::Probability>,
+    {
+        self.inner
+            .decode_symbol_within_data(model)
+            .map_err(|err| err.map_frontend(StrictDecodeError::Inner))?
+            .ok_or(CoderError::Frontend(StrictDecodeError::OutOfCompressedData))
+    }
+
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.inner.maybe_exhausted()
+    }
+}
+
+impl<Decoder: PosSeek> PosSeek for StrictDecoder<Decoder> {
+    type Position = Decoder::Position;
+}
+
+impl<Decoder: Pos> Pos for StrictDecoder<Decoder> {
+    #[inline(always)]
+    fn pos(&self) -> Self::Position {
+        self.inner.pos()
+    }
+}
+
+impl<Decoder: Seek> Seek for StrictDecoder<Decoder> {
+    #[inline(always)]
+    fn seek(&mut self, pos: Self::Position) -> Result<(), ()> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Frontend error type of a [`StrictDecoder`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StrictDecodeError<FrontendError> {
+    /// Decoding another symbol would require compressed data beyond the end of the data,
+    /// i.e., the caller tried to decode more symbols than were encoded.
+    OutOfCompressedData,
+
+    /// The wrapped decoder returned a frontend error.
+    Inner(FrontendError),
+}
+
+impl<FrontendError: Display> Display for StrictDecodeError<FrontendError> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::OutOfCompressedData => write!(
+                f,
+                "Out of compressed data (tried to decode more symbols than were encoded)."
+            ),
+            Self::Inner(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<FrontendError: std::error::Error + 'static> std::error::Error
+    for StrictDecodeError<FrontendError>
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::OutOfCompressedData => None,
+            Self::Inner(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::{DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer},
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+        Encode,
+    };
+    use super::*;
+
+    use alloc::vec::Vec;
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn accepts_valid_data() {
+        for seed in 0..500u64 {
+            let amt = (seed % 40) as usize;
+            let std = 0.5 + (seed % 7) as f64 * 3.0;
+            let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, std));
+            let symbols = (0..amt as u64)
+                .map(|i| ((seed * 31 + i * i * 17) % 201) as i32 - 100)
+                .collect::<Vec<_>>();
+
+            let mut encoder = DefaultRangeEncoder::new();
+            encoder.encode_iid_symbols(&symbols, &model).unwrap();
+            let compressed = encoder.into_compressed().unwrap();
+            let mut decoder =
+                StrictDecoder::new(DefaultRangeDecoder::from_compressed(&compressed).unwrap());
+            let decoded = decoder
+                .decode_iid_symbols(amt, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(&symbols, &model).unwrap();
+            let mut decoder = StrictDecoder::new(ans);
+            let decoded = decoder
+                .decode_iid_symbols(amt, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+        }
+    }
+
+    #[test]
+    fn ans_coder_accepts_symbols_without_trace() {
+        let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+            0.2, 0.5, 0.3,
+        ])
+        .unwrap();
+
+        // Encoding the symbol `0` (which has left-sided cumulative zero) first leaves the
+        // coder empty, so it has to be decodable from an empty coder.
+        for symbols in [[0, 0, 0], [0, 2, 1], [2, 1, 0], [0, 1, 0]].iter() {
+            let mut ans = DefaultAnsCoder::new();
+            ans.encode_iid_symbols_reverse(symbols, &model).unwrap();
+            let mut decoder = StrictDecoder::new(ans);
+            let decoded = decoder
+                .decode_iid_symbols(3, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(&decoded[..], &symbols[..]);
+            assert!(decoder.get_ref().is_empty());
+        }
+
+        let mut decoder = StrictDecoder::new(DefaultAnsCoder::new());
+        assert_eq!(decoder.decode_symbol(&model), Ok(0));
+        assert!(decoder.get_ref().is_empty());
+    }
+
+    #[test]
+    fn range_decoder_detects_end() {
+        let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+            0.2, 0.5, 0.3,
+        ])
+        .unwrap();
+        let symbols = (0..100).map(|i| (i * 7 + 1) % 3).collect::<Vec<usize>>();
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_iid_symbols(&symbols, &model).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+
+        let mut decoder =
+            StrictDecoder::new(DefaultRangeDecoder::from_compressed(&compressed).unwrap());
+        let decoded = decoder
+            .decode_iid_symbols(100, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+
+        // The decoder may produce a few more symbols, but it eventually fails, and a failed
+        // call doesn't change the decoder.
+        let mut num_extra = 0;
+        let err = loop {
+            match decoder.decode_symbol(&model) {
+                Ok(_) => num_extra += 1,
+                Err(err) => break err,
+            }
+        };
+        assert!(num_extra < 64);
+        assert!(matches!(
+            err,
+            CoderError::Frontend(StrictDecodeError::OutOfCompressedData)
+        ));
+        let pos = decoder.pos();
+        assert!(decoder.decode_symbol(&model).is_err());
+        assert_eq!(decoder.pos(), pos);
+
+        // An empty message can't be decoded at all.
+        let mut decoder = StrictDecoder::new(DefaultRangeDecoder::from_compressed(&[]).unwrap());
+        assert!(decoder.decode_symbol(&model).is_err());
+    }
+}
@@ -0,0 +1,518 @@
+//! Table-driven ANS ("tANS", as in the FSE library) for very fast coding at low precision
+//!
+//! This module provides the [`TansCoder`], an entropy coder based on the *tabled* variant
+//! of Asymmetric Numeral Systems \[1], and the [`TansTable`], which holds the precomputed
+//! state transitions for a single categorical distribution. Where the [`AnsCoder`] in the
+//! sister module [`stack`] evaluates an entropy model and performs a multiplication and a
+//! division for each symbol, the `TansCoder` performs a single table lookup and reads or
+//! writes a few bits. This makes it very fast for decoding long sequences of i.i.d.
+//! symbols, but it only supports low precisions (up to 16 bits, since the table has
+//! `2^PRECISION` entries), and building a `TansTable` is much more expensive than
+//! building a categorical entropy model. Use a `TansCoder` if you encode or decode many
+//! symbols with a small number of fixed distributions.
+//!
+//! Like the `AnsCoder`, the `TansCoder` operates as a stack: decoding yields symbols in
+//! reverse order compared to encoding, and you can interleave encoding and decoding
+//! operations arbitrarily. Symbols are integers `0..n`, like for a
+//! [`ContiguousCategoricalEntropyModel`], and you can use different `TansTable`s for
+//! different symbols as long as they have the same `PRECISION`. You can build a `TansTable`
+//! from any categorical entropy model that iterates over its symbols, see
+//! [`TansTable::from_model`].
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{model::SmallContiguousCategoricalEntropyModel, tans::{TansCoder, TansTable}};
+//!
+//! let model = SmallContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+//!     &[0.1, 0.6, 0.05, 0.25],
+//! )
+//! .unwrap();
+//! let table = TansTable::from_model(&model).unwrap();
+//!
+//! let symbols = [1, 3, 1, 1, 0, 2, 1, 3];
+//! let mut coder = TansCoder::new();
+//! coder.encode_iid_symbols_reverse(&symbols, &table).unwrap();
+//! let compressed = coder.into_compressed();
+//!
+//! let mut coder = TansCoder::from_compressed(compressed).unwrap();
+//! let decoded = coder.decode_iid_symbols(symbols.len(), &table).collect::<Vec<_>>();
+//! assert_eq!(decoded, symbols);
+//! assert!(coder.is_empty());
+//! ```
+//!
+//! # References
+//!
+//! \[1] Duda, Jarek. "Asymmetric numeral systems: entropy coding combining speed of
+//! Huffman coding with compression rate of arithmetic coding." arXiv preprint
+//! arXiv:1311.2540 (2013).
+//!
+//! [`AnsCoder`]: super::stack::AnsCoder
+//! [`stack`]: super::stack
+//! [`ContiguousCategoricalEntropyModel`]: super::model::ContiguousCategoricalEntropyModel
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use super::model::IterableEntropyModel;
+use crate::{DefaultEncoderFrontendError, NonZeroBitArray};
+
+/// Largest supported `PRECISION`.
+const MAX_PRECISION: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct DecodingEntry {
+    symbol: u32,
+    num_bits: u8,
+
+    /// The next state (minus `1 << PRECISION`) is `base + (bits read from the stream)`.
+    base: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct SymbolInfo {
+    probability: u32,
+    cumulative: u32,
+
+    /// `PRECISION - floor(log2(probability))`, i.e., the maximum number of bits emitted
+    /// when encoding the symbol.
+    max_num_bits: u32,
+}
+
+/// Precomputed state transitions for coding symbols from a categorical distribution with
+/// a [`TansCoder`].
+///
+/// A `TansTable` represents a distribution over symbols `0..n` with fixed point
+/// probabilities that sum to `1 << PRECISION`. It holds a decoding table with `1 <<
+/// PRECISION` entries and an encoding table of the same size, which are arranged so that
+/// symbols are spread evenly over the states (using the same spreading strategy as the FSE
+/// library). See [module level documentation](self) for an example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TansTable<const PRECISION: usize> {
+    decoding: Vec<DecodingEntry>,
+    encoding: Vec<u16>,
+    symbols: Vec<SymbolInfo>,
+}
+
+impl<const PRECISION: usize> TansTable<PRECISION> {
+    /// Builds a table from the fixed point probabilities of symbols `0..n`.
+    ///
+    /// Symbols may have zero probability (in which case they can't be encoded), but the
+    /// probabilities have to sum to exactly `1 << PRECISION`. Returns an error if they
+    /// don't, if fewer than two symbols have nonzero probability, or if `PRECISION` is zero
+    /// or larger than 16.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_fixed_point_probabilities(probabilities: &[u32]) -> Result<Self, ()> {
+        if PRECISION == 0 || PRECISION > MAX_PRECISION {
+            return Err(());
+        }
+        let table_size = 1u32 << PRECISION;
+        let total = probabilities.iter().map(|&p| p as u64).sum::<u64>();
+        if total != table_size as u64 || probabilities.iter().filter(|&&p| p != 0).count() < 2 {
+            return Err(());
+        }
+
+        // Spread symbols over the table so that each symbol's states are distributed
+        // evenly. Since the step size is odd, it is coprime with the table size and thus
+        // visits all entries of the table. The formula only yields an even step for
+        // `PRECISION == 3`, so forcing the lowest bit doesn't change larger tables.
+        let mask = table_size - 1;
+        let step = ((table_size >> 1) + (table_size >> 3) + 3) | 1;
+        let mut spread = alloc::vec![0u32; table_size as usize];
+        let mut pos = 0;
+        for (symbol, &probability) in probabilities.iter().enumerate() {
+            for _ in 0..probability {
+                spread[pos as usize] = symbol as u32;
+                pos = (pos + step) & mask;
+            }
+        }
+
+        let mut cumulative = 0;
+        let symbols = probabilities
+            .iter()
+            .map(|&probability| {
+                let info = SymbolInfo {
+                    probability,
+                    cumulative,
+                    max_num_bits: if probability == 0 {
+                        0
+                    } else {
+                        PRECISION as u32 - (31 - probability.leading_zeros())
+                    },
+                };
+                cumulative += probability;
+                info
+            })
+            .collect::<Vec<_>>();
+
+        // Decoding state `table_size + i` emits the symbol `spread[i]`, and it was reached
+        // by the encoder from the reduced state `next_reduced_state[symbol]`, which runs
+        // through `probability..2 * probability` for each symbol.
+        let mut next_reduced_state = probabilities.to_vec();
+        let mut encoding = alloc::vec![0u16; table_size as usize];
+        let decoding = spread
+            .iter()
+            .enumerate()
+            .map(|(i, &symbol)| {
+                let info = &symbols[symbol as usize];
+                let reduced_state = next_reduced_state[symbol as usize];
+                next_reduced_state[symbol as usize] += 1;
+                encoding[(info.cumulative + reduced_state - info.probability) as usize] = i as u16;
+
+                let num_bits = PRECISION as u32 - (31 - reduced_state.leading_zeros());
+                DecodingEntry {
+                    symbol,
+                    num_bits: num_bits as u8,
+                    base: ((reduced_state << num_bits) - table_size) as u16,
+                }
+            })
+            .collect();
+
+        Ok(Self {
+            decoding,
+            encoding,
+            symbols,
+        })
+    }
+
+    /// Builds a table from a categorical entropy model over unsigned integer symbols.
+    ///
+    /// This works, e.g., for a [`ContiguousCategoricalEntropyModel`] or for a
+    /// [`NonContiguousCategoricalDecoderModel`] with `usize` symbols, as long as the model
+    /// has the same `PRECISION` (which has to be at most 16 bits). Symbols that the model
+    /// doesn't iterate over get zero probability. Returns an error under the same conditions as
+    /// [`from_fixed_point_probabilities`](Self::from_fixed_point_probabilities).
+    ///
+    /// [`ContiguousCategoricalEntropyModel`]: super::model::ContiguousCategoricalEntropyModel
+    /// [`NonContiguousCategoricalDecoderModel`]: super::model::NonContiguousCategoricalDecoderModel
+    #[allow(clippy::result_unit_err)]
+    pub fn from_model<'m, M>(model: &'m M) -> Result<Self, ()>
+    where
+        M: IterableEntropyModel<'m, PRECISION>,
+        M::Symbol: Into<usize>,
+        M::Probability: Into<u32>,
+    {
+        let mut probabilities = Vec::new();
+        for (symbol, _, probability) in model.symbol_table() {
+            let symbol = symbol.into();
+            if symbol >= probabilities.len() {
+                probabilities.resize(symbol + 1, 0);
+            }
+            probabilities[symbol] = probability.get().into();
+        }
+        Self::from_fixed_point_probabilities(&probabilities)
+    }
+
+    /// Returns the size of the alphabet, i.e., one plus the largest symbol.
+    pub fn support_size(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// Returns the fixed point probability of `symbol` (zero if `symbol` is out of range).
+    pub fn probability(&self, symbol: usize) -> u32 {
+        self.symbols.get(symbol).map_or(0, |info| info.probability)
+    }
+}
+
+/// Entropy coder for both encoding and decoding on a stack with [`TansTable`]s.
+///
+/// The compressed data is a sequence of `u32` words. See [module level
+/// documentation](self) for an example.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TansCoder<const PRECISION: usize> {
+    bulk: Vec<u32>,
+
+    /// Bits that haven't been written to `bulk` yet; the most recently written bit is
+    /// the least significant one. Invariant: `num_buffered_bits < 32`, and all bits of
+    /// `buffer` above `num_buffered_bits` are zero.
+    buffer: u64,
+    num_buffered_bits: u32,
+
+    /// The ANS state minus `1 << PRECISION`, i.e., an index into the tables.
+    state: u32,
+}
+
+impl<const PRECISION: usize> Default for TansCoder<PRECISION> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const PRECISION: usize> TansCoder<PRECISION> {
+    /// Creates an empty coder.
+    pub fn new() -> Self {
+        Self {
+            bulk: Vec::new(),
+            buffer: 0,
+            num_buffered_bits: 0,
+            state: 0,
+        }
+    }
+
+    /// Creates a coder for decoding the compressed data obtained from
+    /// [`into_compressed`](Self::into_compressed).
+    ///
+    /// Returns `Err(compressed)` if `compressed` ends in a zero word, which can't be the
+    /// case for data obtained from `into_compressed`.
+    pub fn from_compressed(mut compressed: Vec<u32>) -> Result<Self, Vec<u32>> {
+        let mut coder = Self::new();
+        let last = match compressed.pop() {
+            None => return Ok(coder),
+            Some(0) => {
+                compressed.push(0);
+                return Err(compressed);
+            }
+            Some(last) => last,
+        };
+
+        // Strip off the padding and the terminating one bit (see `into_compressed`).
+        let trailing_zeros = last.trailing_zeros();
+        coder.bulk = compressed;
+        coder.buffer = (last as u64) >> (trailing_zeros + 1);
+        coder.num_buffered_bits = 31 - trailing_zeros;
+        coder.state = coder.pop_bits(PRECISION as u32);
+        Ok(coder)
+    }
+
+    /// Returns the compressed data.
+    ///
+    /// Returns an empty vector if the coder is empty. Otherwise, the compressed data
+    /// includes the current state and a terminating one bit, followed by zero padding to
+    /// the next word boundary.
+    pub fn into_compressed(mut self) -> Vec<u32> {
+        if !self.is_empty() {
+            self.push_bits(self.state, PRECISION as u32);
+            self.push_bits(1, 1);
+            if self.num_buffered_bits != 0 {
+                self.push_bits(0, 32 - self.num_buffered_bits);
+            }
+        }
+        self.bulk
+    }
+
+    /// Returns `true` if no data for decoding is left.
+    pub fn is_empty(&self) -> bool {
+        self.state == 0 && self.num_buffered_bits == 0 && self.bulk.is_empty()
+    }
+
+    /// Returns the number of bits of compressed data, excluding the overhead for the state
+    /// and padding that [`into_compressed`](Self::into_compressed) adds.
+    pub fn num_bits(&self) -> usize {
+        32 * self.bulk.len() + self.num_buffered_bits as usize
+    }
+
+    /// Encodes a single symbol and pushes it on the stack.
+    ///
+    /// Returns an error if `symbol` has zero probability under `table`.
+    pub fn encode_symbol(
+        &mut self,
+        symbol: usize,
+        table: &TansTable<PRECISION>,
+    ) -> Result<(), DefaultEncoderFrontendError> {
+        let info = match table.symbols.get(symbol) {
+            Some(info) if info.probability != 0 => *info,
+            _ => return Err(DefaultEncoderFrontendError::ImpossibleSymbol),
+        };
+
+        let state = self.state + (1 << PRECISION);
+        let num_bits = if (state >> info.max_num_bits) >= info.probability {
+            info.max_num_bits
+        } else {
+            info.max_num_bits - 1
+        };
+        self.push_bits(state & ((1 << num_bits) - 1), num_bits);
+        let reduced_state = state >> num_bits;
+        self.state =
+            table.encoding[(info.cumulative + reduced_state - info.probability) as usize] as u32;
+        Ok(())
+    }
+
+    /// Encodes a sequence of i.i.d. symbols in reverse order, so that
+    /// [`decode_iid_symbols`](Self::decode_iid_symbols) decodes them in their original
+    /// order.
+    pub fn encode_iid_symbols_reverse<I>(
+        &mut self,
+        symbols: I,
+        table: &TansTable<PRECISION>,
+    ) -> Result<(), DefaultEncoderFrontendError>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<usize>,
+        I::IntoIter: DoubleEndedIterator,
+    {
+        for symbol in symbols.into_iter().rev() {
+            self.encode_symbol(*symbol.borrow(), table)?;
+        }
+        Ok(())
+    }
+
+    /// Decodes a single symbol and pops it off the stack.
+    ///
+    /// Decoding can't fail. Decoding past the end of the compressed data deterministically
+    /// produces symbols that don't carry any information.
+    #[inline(always)]
+    pub fn decode_symbol(&mut self, table: &TansTable<PRECISION>) -> usize {
+        let entry = table.decoding[self.state as usize];
+        self.state = entry.base as u32 + self.pop_bits(entry.num_bits as u32);
+        entry.symbol as usize
+    }
+
+    /// Decodes `amt` i.i.d. symbols.
+    pub fn decode_iid_symbols<'a>(
+        &'a mut self,
+        amt: usize,
+        table: &'a TansTable<PRECISION>,
+    ) -> impl Iterator<Item = usize> + 'a {
+        (0..amt).map(move |_| self.decode_symbol(table))
+    }
+
+    #[inline(always)]
+    fn push_bits(&mut self, bits: u32, num_bits: u32) {
+        self.buffer = (self.buffer << num_bits) | bits as u64;
+        self.num_buffered_bits += num_bits;
+        if self.num_buffered_bits >= 32 {
+            self.num_buffered_bits -= 32;
+            self.bulk
+                .push((self.buffer >> self.num_buffered_bits) as u32);
+            self.buffer &= (1 << self.num_buffered_bits) - 1;
+        }
+    }
+
+    #[inline(always)]
+    fn pop_bits(&mut self, num_bits: u32) -> u32 {
+        if self.num_buffered_bits < num_bits {
+            // Pad with zero bits past the end of the compressed data.
+            let word = self.bulk.pop().unwrap_or(0);
+            self.buffer |= (word as u64) << self.num_buffered_bits;
+            self.num_buffered_bits += 32;
+        }
+        let bits = (self.buffer & ((1 << num_bits) - 1)) as u32;
+        self.buffer >>= num_bits;
+        self.num_buffered_bits -= num_bits;
+        bits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::model::{
+        ContiguousCategoricalEntropyModel, DecoderModel, IterableEntropyModel,
+        SmallNonContiguousCategoricalDecoderModel,
+    };
+    use super::*;
+
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    fn invalid_tables() {
+        assert!(TansTable::<4>::from_fixed_point_probabilities(&[8, 7]).is_err());
+        assert!(TansTable::<4>::from_fixed_point_probabilities(&[16, 0]).is_err());
+        assert!(TansTable::<4>::from_fixed_point_probabilities(&[8, 0, 8]).is_ok());
+        assert!(TansTable::<17>::from_fixed_point_probabilities(&[1 << 16, 1 << 16]).is_err());
+
+        let table = TansTable::<4>::from_fixed_point_probabilities(&[8, 0, 8]).unwrap();
+        let mut coder = TansCoder::new();
+        assert_eq!(
+            coder.encode_symbol(1, &table),
+            Err(DefaultEncoderFrontendError::ImpossibleSymbol)
+        );
+        assert_eq!(
+            coder.encode_symbol(3, &table),
+            Err(DefaultEncoderFrontendError::ImpossibleSymbol)
+        );
+        assert!(coder.encode_symbol(2, &table).is_ok());
+    }
+
+    #[test]
+    fn round_trip_and_compression_effectiveness() {
+        let probabilities = [0.3, 0.01, 0.2, 0.002, 0.25, 0.1, 0.138];
+        let model =
+            ContiguousCategoricalEntropyModel::<u32, _, 12>::from_floating_point_probabilities(
+                &probabilities,
+            )
+            .unwrap();
+        let table = TansTable::from_model(&model).unwrap();
+        assert_eq!(table.support_size(), probabilities.len());
+        for (symbol, _, probability) in model.symbol_table() {
+            assert_eq!(table.probability(symbol), probability.get());
+        }
+
+        let mut rng = Xoshiro256StarStar::seed_from_u64(42);
+        let amt = 10_000;
+        let symbols = (0..amt)
+            .map(|_| model.quantile_function(rng.next_u32() % (1 << 12)).0)
+            .collect::<Vec<_>>();
+
+        let mut coder = TansCoder::new();
+        coder.encode_iid_symbols_reverse(&symbols, &table).unwrap();
+        let entropy = amt as f64 * model.entropy_base2::<f64>();
+        assert!((coder.num_bits() as f64) < 1.01 * entropy);
+
+        let compressed = coder.into_compressed();
+        let mut coder = TansCoder::from_compressed(compressed).unwrap();
+        let decoded = coder.decode_iid_symbols(amt, &table).collect::<Vec<_>>();
+        assert_eq!(decoded, symbols);
+        assert!(coder.is_empty());
+    }
+
+    #[test]
+    fn round_trip_with_low_precisions() {
+        fn test<const PRECISION: usize>(probabilities: &[u32]) {
+            let table =
+                TansTable::<PRECISION>::from_fixed_point_probabilities(probabilities).unwrap();
+            let mut rng = Xoshiro256StarStar::seed_from_u64(PRECISION as u64);
+            let amt = 1000;
+            let symbols = (0..amt)
+                .map(|_| rng.next_u32() as usize % probabilities.len())
+                .collect::<Vec<_>>();
+
+            let mut coder = TansCoder::new();
+            coder.encode_iid_symbols_reverse(&symbols, &table).unwrap();
+            let mut coder = TansCoder::from_compressed(coder.into_compressed()).unwrap();
+            let decoded = coder.decode_iid_symbols(amt, &table).collect::<Vec<_>>();
+            assert_eq!(decoded, symbols);
+            assert!(coder.is_empty());
+        }
+
+        test::<1>(&[1, 1]);
+        test::<2>(&[2, 1, 1]);
+        test::<3>(&[5, 2, 1]);
+        test::<3>(&[2, 2, 2, 2]);
+        test::<4>(&[9, 4, 2, 1]);
+        test::<5>(&[17, 8, 4, 2, 1]);
+    }
+
+    #[test]
+    fn mixed_tables_and_interleaved_operations() {
+        let table1 = TansTable::<12>::from_fixed_point_probabilities(&[3200, 800, 96]).unwrap();
+        let model = SmallNonContiguousCategoricalDecoderModel::<usize>::from_symbols_and_nonzero_fixed_point_probabilities(
+            [4, 0, 3, 1],
+            [32u16, 16, 2400, 1648],
+            false,
+        )
+        .unwrap();
+        let table2 = TansTable::from_model(&model).unwrap();
+        assert_eq!(table2.support_size(), 5);
+        assert_eq!(table2.probability(2), 0);
+
+        let mut coder = TansCoder::new();
+        assert!(coder.encode_symbol(2, &table2).is_err());
+        assert!(TansCoder::<12>::new().into_compressed().is_empty());
+        coder.encode_symbol(2, &table1).unwrap();
+        coder.encode_symbol(4, &table2).unwrap();
+        coder.encode_symbol(0, &table1).unwrap();
+        assert_eq!(coder.decode_symbol(&table1), 0);
+        coder.encode_symbol(0, &table2).unwrap();
+
+        let mut coder = TansCoder::from_compressed(coder.into_compressed()).unwrap();
+        assert_eq!(coder.decode_symbol(&table2), 0);
+        assert_eq!(coder.decode_symbol(&table2), 4);
+        assert_eq!(coder.decode_symbol(&table1), 2);
+        assert!(coder.is_empty());
+
+        assert!(TansCoder::<12>::from_compressed(alloc::vec![5, 0]).is_err());
+    }
+}
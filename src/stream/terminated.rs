@@ -0,0 +1,497 @@
+//! Range coding with an explicit terminator for precise end-of-stream detection
+//!
+//! A [`RangeDecoder`] can't tell whether it has decoded all symbols that were encoded since
+//! the compressed data doesn't store the number of encoded symbols. Its method
+//! [`maybe_exhausted`](RangeDecoder::maybe_exhausted) may therefore return `false` even
+//! after the last symbol was decoded, and a [`StrictDecoder`] may still produce some
+//! garbage symbols before it detects the end of the data. For variable-length messages,
+//! you'd thus usually have to store the message length yourself or encode a sentinel
+//! symbol at the end of the message.
+//!
+//! This module provides an optional termination mode that does this bookkeeping for you:
+//! a [`TerminatedRangeEncoder`] counts the symbols that you encode and, when you call
+//! [`into_compressed`](TerminatedRangeEncoder::into_compressed), appends the count as a
+//! short terminator after the regular (sealed) compressed data. A
+//! [`TerminatedRangeDecoder`] reads the terminator when it's created, decodes exactly
+//! this many symbols, and fails with [`StrictDecodeError::OutOfCompressedData`] if you try
+//! to decode more. Its method [`is_exhausted`](TerminatedRangeDecoder::is_exhausted) is
+//! precise, i.e., it returns `true` if and only if all encoded symbols have been decoded.
+//!
+//! The terminator stores the symbol count in little-endian chunks of `Word::BITS - 1` bits
+//! (starting from the end of the data), where the most significant bit of each word
+//! indicates whether more chunks precede it. It thus takes up a single word unless you
+//! encode at least `2^(Word::BITS - 1)` symbols (i.e., 128 symbols for `Word = u8`).
+//! Since a `RangeEncoder` seals its data so that it can be decoded regardless of what
+//! follows, the terminator doesn't affect the decoded symbols.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::{
+//!     stream::{
+//!         model::DefaultLeakyQuantizer,
+//!         strict::StrictDecodeError,
+//!         terminated::{DefaultTerminatedRangeDecoder, DefaultTerminatedRangeEncoder},
+//!         Decode, Encode,
+//!     },
+//!     CoderError,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 10.0));
+//! let message = [12, -5, 31, 0, -18, 7];
+//!
+//! let mut encoder = DefaultTerminatedRangeEncoder::new();
+//! encoder.encode_iid_symbols(&message, &model).unwrap();
+//! assert_eq!(encoder.num_symbols(), 6);
+//! let compressed = encoder.into_compressed().unwrap();
+//!
+//! // The decoder doesn't need to know the message length.
+//! let mut decoder = DefaultTerminatedRangeDecoder::from_compressed(compressed).unwrap();
+//! let mut decoded = Vec::new();
+//! while !decoder.is_exhausted() {
+//!     decoded.push(decoder.decode_symbol(&model).unwrap());
+//! }
+//! assert_eq!(decoded, message);
+//! assert!(matches!(
+//!     decoder.decode_symbol(&model),
+//!     Err(CoderError::Frontend(StrictDecodeError::OutOfCompressedData))
+//! ));
+//! ```
+//!
+//! [`StrictDecoder`]: super::strict::StrictDecoder
+
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+
+use num::{cast::AsPrimitive, NumCast};
+
+use super::{
+    model::{DecoderModel, EncoderModel},
+    queue::{DecoderFrontendError, RangeCoderState, RangeDecoder, RangeEncoder},
+    strict::StrictDecodeError,
+    Code, Decode, Encode,
+};
+use crate::{
+    backends::{Cursor, IntoReadWords, ReadWords, WriteWords},
+    BitArray, CoderError, DefaultEncoderError, DefaultEncoderFrontendError, Queue,
+};
+
+/// A [`RangeEncoder`] that counts the encoded symbols so that it can terminate the
+/// compressed data with the symbol count.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct TerminatedRangeEncoder<Word, State, Backend = Vec<Word>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word>,
+{
+    inner: RangeEncoder<Word, State, Backend>,
+    num_symbols: usize,
+}
+
+/// Type alias for a [`TerminatedRangeEncoder`] with sane parameters for typical use cases.
+///
+/// Uses the same parameters as a [`DefaultRangeEncoder`](super::queue::DefaultRangeEncoder).
+pub type DefaultTerminatedRangeEncoder<Backend = Vec<u32>> =
+    TerminatedRangeEncoder<u32, u64, Backend>;
+
+/// Type alias for a [`TerminatedRangeDecoder`] that decodes data from a
+/// [`DefaultTerminatedRangeEncoder`].
+pub type DefaultTerminatedRangeDecoder<Backend = Cursor<u32, Vec<u32>>> =
+    TerminatedRangeDecoder<u32, u64, Backend>;
+
+impl<Word, State> TerminatedRangeEncoder<Word, State>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    /// Creates an empty encoder that writes to a `Vec<Word>`.
+    pub fn new() -> Self {
+        Self::with_backend(Vec::new())
+    }
+}
+
+impl<Word, State> Default for TerminatedRangeEncoder<Word, State>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Word, State, Backend> TerminatedRangeEncoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word>,
+{
+    /// Creates an empty encoder that writes to `backend`.
+    ///
+    /// See [`RangeEncoder::with_backend`].
+    pub fn with_backend(backend: Backend) -> Self {
+        Self {
+            inner: RangeEncoder::with_backend(backend),
+            num_symbols: 0,
+        }
+    }
+
+    /// Returns the number of symbols that have been encoded so far.
+    pub fn num_symbols(&self) -> usize {
+        self.num_symbols
+    }
+
+    /// Returns a reference to the wrapped encoder.
+    ///
+    /// There's deliberately no `get_mut` method since encoding directly on the wrapped
+    /// encoder would bypass the symbol count.
+    pub fn get_ref(&self) -> &RangeEncoder<Word, State, Backend> {
+        &self.inner
+    }
+
+    /// Seals the compressed data, appends the terminator, and returns the backend.
+    pub fn into_compressed(self) -> Result<Backend, Backend::WriteError> {
+        let mut bulk = self.inner.into_compressed()?;
+        write_terminator(self.num_symbols, &mut bulk)?;
+        Ok(bulk)
+    }
+}
+
+impl<Word, State, Backend> Code for TerminatedRangeEncoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word>,
+{
+    type Word = Word;
+    type State = RangeCoderState<Word, State>;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Word, State, Backend, const PRECISION: usize> Encode<PRECISION>
+    for TerminatedRangeEncoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: WriteWords<Word>,
+{
+    type FrontendError = DefaultEncoderFrontendError;
+    type BackendError = Backend::WriteError;
+
+    /// Encodes a symbol with the wrapped encoder and counts it if encoding succeeds.
+    fn encode_symbol<M>(
+        &mut self,
+        symbol: impl Borrow<M::Symbol>,
+        model: M,
+    ) -> Result<(), DefaultEncoderError<Self::BackendError>>
+    where
+        M: EncoderModel<PRECISION>,
+        M::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<M::Probability>,
+    {
+        self.inner.encode_symbol(symbol, model)?;
+        self.num_symbols = self
+            .num_symbols
+            .checked_add(1)
+            .expect("Cannot encode more symbols than what's addressable with usize.");
+        Ok(())
+    }
+
+    #[inline(always)]
+    fn maybe_full(&self) -> bool {
+        self.inner.maybe_full()
+    }
+}
+
+/// A [`RangeDecoder`] for data from a [`TerminatedRangeEncoder`], which knows exactly how
+/// many symbols are left to decode.
+///
+/// See [module level documentation](self).
+#[derive(Debug, Clone)]
+pub struct TerminatedRangeDecoder<Word, State, Backend = Cursor<Word, Vec<Word>>>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Queue>,
+{
+    inner: RangeDecoder<Word, State, Backend>,
+    remaining: usize,
+}
+
+impl<Word, State, Backend> TerminatedRangeDecoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Queue>,
+{
+    /// Reads the terminator at the end of `compressed` and prepares for decoding the
+    /// symbols before it.
+    ///
+    /// Returns an error if `compressed` doesn't end in a valid terminator (e.g., because
+    /// it's empty). Note that the terminator can't detect all forms of corrupted data,
+    /// e.g., most truncations still leave a valid (but wrong) terminator.
+    #[allow(clippy::result_unit_err)]
+    pub fn from_compressed<Buf>(compressed: Buf) -> Result<Self, ()>
+    where
+        Buf: AsRef<[Word]> + IntoReadWords<Word, Queue, IntoReadWords = Backend>,
+    {
+        let remaining = read_terminator(compressed.as_ref())?;
+        let inner = RangeDecoder::from_compressed(compressed).map_err(|_| ())?;
+        Ok(Self { inner, remaining })
+    }
+
+    /// Returns the number of symbols that are left to decode.
+    pub fn num_remaining_symbols(&self) -> usize {
+        self.remaining
+    }
+
+    /// Returns `true` if and only if all encoded symbols have been decoded.
+    ///
+    /// Unlike [`RangeDecoder::maybe_exhausted`], this is exact.
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// Returns a reference to the wrapped decoder.
+    pub fn get_ref(&self) -> &RangeDecoder<Word, State, Backend> {
+        &self.inner
+    }
+
+    /// Consumes the wrapper and returns the wrapped decoder.
+    ///
+    /// Note that the returned decoder will decode the terminator as if it was part of the
+    /// regular compressed data if you decode beyond the end of the message.
+    pub fn into_inner(self) -> RangeDecoder<Word, State, Backend> {
+        self.inner
+    }
+}
+
+impl<Word, State, Backend> Code for TerminatedRangeDecoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Queue>,
+{
+    type Word = Word;
+    type State = RangeCoderState<Word, State>;
+
+    #[inline(always)]
+    fn state(&self) -> Self::State {
+        self.inner.state()
+    }
+}
+
+impl<Word, State, Backend, const PRECISION: usize> Decode<PRECISION>
+    for TerminatedRangeDecoder<Word, State, Backend>
+where
+    Word: BitArray + Into<State>,
+    State: BitArray + AsPrimitive<Word>,
+    Backend: ReadWords<Word, Queue>,
+{
+    type FrontendError = StrictDecodeError<DecoderFrontendError>;
+    type BackendError = Backend::ReadError;
+
+    /// Decodes a symbol unless all encoded symbols have already been decoded.
+    fn decode_symbol<D>(
+        &mut self,
+        model: D,
+    ) -> Result<D::Symbol, CoderError<Self::FrontendError, Self::BackendError>>
+    where
+        D: DecoderModel<PRECISION>,
+        D::Probability: Into<Self::Word>,
+        Self::Word: AsPrimitive<D::Probability>,
+    {
+        if self.remaining == 0 {
+            return Err(CoderError::Frontend(StrictDecodeError::OutOfCompressedData));
+        }
+
+        let symbol = self
+            .inner
+            .decode_symbol(model)
+            .map_err(|err| err.map_frontend(StrictDecodeError::Inner))?;
+        self.remaining -= 1;
+        Ok(symbol)
+    }
+
+    /// Same as [`is_exhausted`](TerminatedRangeDecoder::is_exhausted).
+    #[inline(always)]
+    fn maybe_exhausted(&self) -> bool {
+        self.is_exhausted()
+    }
+}
+
+fn write_terminator<Word: BitArray, B: WriteWords<Word>>(
+    num_symbols: usize,
+    bulk: &mut B,
+) -> Result<(), B::WriteError> {
+    let payload_bits = Word::BITS - 1;
+    let significant_bits = usize::BITS as usize - num_symbols.leading_zeros() as usize;
+    let num_words = core::cmp::max(1, (significant_bits + payload_bits - 1) / payload_bits);
+    let continuation_flag = Word::one() << payload_bits;
+
+    // Write the most significant chunk first so that the least significant chunk ends up
+    // at the very end of the data, where the decoder starts reading.
+    for i in (0..num_words).rev() {
+        let chunk = if i * payload_bits < usize::BITS as usize {
+            num_symbols >> (i * payload_bits)
+        } else {
+            0
+        };
+        let chunk = if payload_bits < usize::BITS as usize {
+            chunk & ((1 << payload_bits) - 1)
+        } else {
+            chunk
+        };
+        let mut word = <Word as NumCast>::from(chunk).expect("chunk fits into `Word`");
+        if i != num_words - 1 {
+            word = word | continuation_flag;
+        }
+        bulk.write(word)?;
+    }
+
+    Ok(())
+}
+
+fn read_terminator<Word: BitArray>(compressed: &[Word]) -> Result<usize, ()> {
+    let payload_bits = Word::BITS - 1;
+    let continuation_flag = Word::one() << payload_bits;
+
+    let mut num_symbols = 0usize;
+    let mut shift = 0;
+    for &word in compressed.iter().rev() {
+        let chunk = (word & !continuation_flag).to_usize().ok_or(())?;
+        if shift < usize::BITS as usize {
+            let shifted = chunk << shift;
+            if shifted >> shift != chunk {
+                return Err(());
+            }
+            num_symbols |= shifted;
+        } else if chunk != 0 {
+            return Err(());
+        }
+
+        if word & continuation_flag == Word::zero() {
+            return Ok(num_symbols);
+        }
+        shift += payload_bits;
+    }
+
+    // Ran out of data before the end of the terminator.
+    Err(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::model::{
+        DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer, LeakyQuantizer,
+    };
+    use super::*;
+
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn terminator() {
+        for &num_symbols in &[0, 1, 127, 128, 300, 16_383, 16_384, 1 << 20, usize::MAX] {
+            let mut bulk = Vec::<u8>::new();
+            write_terminator(num_symbols, &mut bulk).unwrap();
+            let expected_len = core::cmp::max(
+                1,
+                (usize::BITS as usize - num_symbols.leading_zeros() as usize).div_ceil(7),
+            );
+            assert_eq!(bulk.len(), expected_len);
+            assert_eq!(read_terminator(&bulk), Ok(num_symbols));
+
+            // Data before the terminator doesn't matter.
+            bulk.insert(0, 0xff);
+            assert_eq!(read_terminator(&bulk), Ok(num_symbols));
+
+            let mut bulk = Vec::<u32>::new();
+            write_terminator(num_symbols, &mut bulk).unwrap();
+            assert_eq!(read_terminator(&bulk), Ok(num_symbols));
+        }
+
+        let mut bulk = Vec::<u32>::new();
+        write_terminator(1000, &mut bulk).unwrap();
+        assert_eq!(bulk, [1000]);
+
+        assert!(read_terminator::<u32>(&[]).is_err());
+        assert!(read_terminator::<u8>(&[0x85]).is_err());
+        assert!(read_terminator::<u8>(&[0xff; 20]).is_err());
+    }
+
+    #[test]
+    fn variable_length_messages() {
+        let model = DefaultLeakyQuantizer::new(-100..=100).quantize(Gaussian::new(0.0, 3.0));
+        let categorical =
+            DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+                0.98, 0.01, 0.01,
+            ])
+            .unwrap();
+
+        for len in [0, 1, 2, 10, 100, 1000] {
+            // Highly predictable symbols are the hardest case for detecting the end of the
+            // data without a terminator since they barely leave a trace in the data.
+            let symbols = (0..len)
+                .map(|i| if i % 37 == 36 { 1 } else { 0 })
+                .collect::<Vec<usize>>();
+            let mut encoder = DefaultTerminatedRangeEncoder::new();
+            encoder.encode_iid_symbols(&symbols, &categorical).unwrap();
+            encoder.encode_symbol(-7, model).unwrap();
+            assert_eq!(encoder.num_symbols(), len + 1);
+            let compressed = encoder.into_compressed().unwrap();
+
+            let mut decoder = DefaultTerminatedRangeDecoder::from_compressed(compressed).unwrap();
+            assert_eq!(decoder.num_remaining_symbols(), len + 1);
+            for &symbol in &symbols {
+                assert!(!decoder.is_exhausted());
+                assert_eq!(decoder.decode_symbol(&categorical).unwrap(), symbol);
+            }
+            assert!(!Decode::<24>::maybe_exhausted(&decoder));
+            assert_eq!(decoder.decode_symbol(model).unwrap(), -7);
+            assert!(decoder.is_exhausted());
+            assert!(Decode::<24>::maybe_exhausted(&decoder));
+            assert!(matches!(
+                decoder.decode_symbol(&categorical),
+                Err(CoderError::Frontend(StrictDecodeError::OutOfCompressedData))
+            ));
+        }
+    }
+
+    #[test]
+    fn byte_words_and_borrowed_data() {
+        let model = LeakyQuantizer::<f64, i32, u8, 8>::new(-20..=20);
+        let model = model.quantize(Gaussian::new(0.0, 5.0));
+        let symbols = (0..200).map(|i| (i * 7) % 41 - 20).collect::<Vec<i32>>();
+
+        let mut encoder = TerminatedRangeEncoder::<u8, u32>::new();
+        encoder.encode_iid_symbols(&symbols, model).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+        assert_eq!(
+            compressed[compressed.len() - 2..],
+            [0x01, 0x80 | (200 - 128)]
+        );
+
+        let mut decoder =
+            TerminatedRangeDecoder::<u8, u32, Cursor<u8, &[u8]>>::from_compressed(&compressed[..])
+                .unwrap();
+        let decoded = decoder
+            .decode_iid_symbols(200, model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+        assert!(decoder.is_exhausted());
+
+        // Data without a terminator is rejected.
+        assert!(
+            TerminatedRangeDecoder::<u8, u32, Cursor<u8, &[u8]>>::from_compressed(&[][..]).is_err()
+        );
+    }
+}
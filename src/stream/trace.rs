@@ -0,0 +1,315 @@
+//! Recording and replaying sequences of encoding operations
+//!
+//! A [`Trace`] logs each encoded symbol together with a user-defined "model spec", i.e., a
+//! small value (such as a tuple of distribution parameters) from which the entropy model
+//! can be reconstructed. Since a trace stores model specs rather than models, you can
+//! replay it against any coder and with any way of turning specs into models. This is
+//! useful for debugging bit rate regressions (e.g., by replaying a workload captured with
+//! an old configuration against a new one) and for comparing different coders on exactly
+//! the same workload.
+//!
+//! Replaying a trace is deterministic: replaying the same trace against the same coder
+//! configuration always produces the same compressed data as the original session.
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::LeakyQuantizer, queue::DefaultRangeEncoder, stack::DefaultAnsCoder,
+//!     trace::Trace, Decode,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! // Record an encoding session. The model spec is a tuple `(mean, std)`.
+//! let make_model = |&(mean, std): &(f64, f64)| {
+//!     LeakyQuantizer::<f64, i32, u32, 24>::new(-100..=100).quantize(Gaussian::new(mean, std))
+//! };
+//! let mut encoder = DefaultRangeEncoder::new();
+//! let mut trace = Trace::new();
+//! for (symbol, spec) in [(3, (2.5, 4.0)), (-12, (-10.2, 3.1)), (40, (35.0, 10.0))] {
+//!     trace.record(&mut encoder, symbol, spec, make_model).unwrap();
+//! }
+//!
+//! // Replaying against the same coder configuration reproduces the compressed data ...
+//! let mut replayed = DefaultRangeEncoder::new();
+//! trace.replay(&mut replayed, make_model).unwrap();
+//! assert_eq!(&*replayed.get_compressed(), &*encoder.get_compressed());
+//!
+//! // ... and we can replay the same workload against a different coder with a
+//! // different (here: coarser) model precision, and compare bit rates.
+//! let make_small_model = |&(mean, std): &(f64, f64)| {
+//!     LeakyQuantizer::<f64, i32, u32, 16>::new(-100..=100).quantize(Gaussian::new(mean, std))
+//! };
+//! let mut ans = DefaultAnsCoder::new();
+//! trace.replay_reverse(&mut ans, make_small_model).unwrap();
+//! println!(
+//!     "range coder: {} bits; ANS with 16 bit precision: {} bits; ideal: {:.1} bits",
+//!     encoder.num_bits(),
+//!     ans.num_bits(),
+//!     trace.information_content::<f64, _, 24>(make_model),
+//! );
+//!
+//! let decoded = ans
+//!     .decode_symbols(trace.operations().iter().map(|(_, spec)| make_small_model(spec)))
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, [3, -12, 40]);
+//! ```
+
+use alloc::vec::Vec;
+
+use num::{cast::AsPrimitive, Float};
+
+use super::{model::EncoderModel, Encode};
+use crate::CoderError;
+
+/// A log of `(symbol, model spec)` pairs. See [module level documentation](self).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Trace<Symbol, Spec> {
+    operations: Vec<(Symbol, Spec)>,
+}
+
+impl<Symbol, Spec> Default for Trace<Symbol, Spec> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Symbol, Spec> Trace<Symbol, Spec> {
+    /// Creates an empty trace.
+    pub fn new() -> Self {
+        Self {
+            operations: Vec::new(),
+        }
+    }
+
+    /// Appends an operation without encoding anything.
+    ///
+    /// Use this if you already know the workload and just want to replay it (see
+    /// [`replay`](Self::replay)), or if you want to construct a trace from a log that you
+    /// stored elsewhere.
+    pub fn push(&mut self, symbol: Symbol, spec: Spec) {
+        self.operations.push((symbol, spec))
+    }
+
+    /// Encodes `symbol` on `encoder` with the model `make_model(&spec)`, and appends the
+    /// operation to the trace if encoding succeeds.
+    ///
+    /// Constructing the model from `spec` (rather than passing a model directly) ensures
+    /// that the recorded spec really describes the model that was used.
+    pub fn record<E, M, const PRECISION: usize>(
+        &mut self,
+        encoder: &mut E,
+        symbol: Symbol,
+        spec: Spec,
+        make_model: impl FnOnce(&Spec) -> M,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        M: EncoderModel<PRECISION, Symbol = Symbol>,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        encoder.encode_symbol(&symbol, make_model(&spec))?;
+        self.operations.push((symbol, spec));
+        Ok(())
+    }
+
+    /// Encodes all recorded symbols on `encoder` in the order in which they were recorded.
+    ///
+    /// The model for each symbol is `make_model(&spec)`, where `make_model` may differ from
+    /// the one used when recording. Use this method with coders that have queue semantics
+    /// (e.g., a [`RangeEncoder`]). For coders with stack semantics, you'll usually want to
+    /// call [`replay_reverse`] instead so that decoding yields the symbols in the recorded
+    /// order.
+    ///
+    /// [`RangeEncoder`]: super::queue::RangeEncoder
+    /// [`replay_reverse`]: Self::replay_reverse
+    pub fn replay<E, M, const PRECISION: usize>(
+        &self,
+        encoder: &mut E,
+        mut make_model: impl FnMut(&Spec) -> M,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        M: EncoderModel<PRECISION, Symbol = Symbol>,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        encoder.encode_symbols(
+            self.operations
+                .iter()
+                .map(|(symbol, spec)| (symbol, make_model(spec))),
+        )
+    }
+
+    /// Same as [`replay`](Self::replay) but encodes the symbols in reverse order, as is
+    /// appropriate for coders with stack semantics (e.g., an [`AnsCoder`]).
+    ///
+    /// [`AnsCoder`]: super::stack::AnsCoder
+    pub fn replay_reverse<E, M, const PRECISION: usize>(
+        &self,
+        encoder: &mut E,
+        mut make_model: impl FnMut(&Spec) -> M,
+    ) -> Result<(), CoderError<E::FrontendError, E::BackendError>>
+    where
+        E: Encode<PRECISION>,
+        M: EncoderModel<PRECISION, Symbol = Symbol>,
+        M::Probability: Into<E::Word>,
+        E::Word: AsPrimitive<M::Probability>,
+    {
+        encoder.encode_symbols(
+            self.operations
+                .iter()
+                .rev()
+                .map(|(symbol, spec)| (symbol, make_model(spec))),
+        )
+    }
+
+    /// Returns the total information content (in bits) of all recorded symbols under the
+    /// models `make_model(&spec)`.
+    ///
+    /// This is the bit rate that an ideal entropy coder would achieve when replaying the
+    /// trace, which makes it a useful baseline for comparing actual bit rates. Returns
+    /// infinity if any symbol has zero probability under its model.
+    pub fn information_content<F, M, const PRECISION: usize>(
+        &self,
+        mut make_model: impl FnMut(&Spec) -> M,
+    ) -> F
+    where
+        F: Float + core::iter::Sum,
+        M: EncoderModel<PRECISION, Symbol = Symbol>,
+        M::Probability: Into<F>,
+    {
+        self.operations
+            .iter()
+            .map(|(symbol, spec)| make_model(spec).bit_cost::<F>(symbol))
+            .sum()
+    }
+
+    /// Returns the recorded `(symbol, spec)` pairs in the order in which they were recorded.
+    pub fn operations(&self) -> &[(Symbol, Spec)] {
+        &self.operations
+    }
+
+    /// Consumes the trace and returns the recorded `(symbol, spec)` pairs.
+    pub fn into_operations(self) -> Vec<(Symbol, Spec)> {
+        self.operations
+    }
+
+    /// Returns the number of recorded operations.
+    pub fn len(&self) -> usize {
+        self.operations.len()
+    }
+
+    /// Returns `true` if no operations have been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.operations.is_empty()
+    }
+
+    /// Removes all recorded operations.
+    pub fn clear(&mut self) {
+        self.operations.clear()
+    }
+}
+
+impl<Symbol, Spec> From<Vec<(Symbol, Spec)>> for Trace<Symbol, Spec> {
+    fn from(operations: Vec<(Symbol, Spec)>) -> Self {
+        Self { operations }
+    }
+}
+
+impl<Symbol, Spec> core::iter::FromIterator<(Symbol, Spec)> for Trace<Symbol, Spec> {
+    fn from_iter<I: IntoIterator<Item = (Symbol, Spec)>>(iter: I) -> Self {
+        Self {
+            operations: iter.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{
+            model::LeakyQuantizer,
+            queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+            stack::DefaultAnsCoder,
+            Decode,
+        },
+        *,
+    };
+
+    use probability::distribution::Gaussian;
+    use rand_xoshiro::{
+        rand_core::{RngCore, SeedableRng},
+        Xoshiro256StarStar,
+    };
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn record_and_replay() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(723);
+        let make_model = |&(mean, std): &(f64, f64)| {
+            LeakyQuantizer::<f64, i32, u32, 24>::new(-100..=100).quantize(Gaussian::new(mean, std))
+        };
+
+        let mut encoder = DefaultRangeEncoder::new();
+        let mut trace = Trace::new();
+        for _ in 0..1000 {
+            let mean = (rng.next_u32() % 100) as f64 - 50.0;
+            let std = (rng.next_u32() % 100) as f64 / 10.0 + 0.5;
+            let symbol = mean as i32 + (rng.next_u32() % 5) as i32 - 2;
+            trace
+                .record(&mut encoder, symbol, (mean, std), make_model)
+                .unwrap();
+        }
+        assert_eq!(trace.len(), 1000);
+
+        // A symbol outside the model's support is not recorded.
+        let mut other_trace = Trace::new();
+        assert!(other_trace
+            .record(
+                &mut DefaultRangeEncoder::new(),
+                1000,
+                (0.0, 1.0),
+                make_model
+            )
+            .is_err());
+        assert!(other_trace.is_empty());
+
+        let mut replayed = DefaultRangeEncoder::new();
+        trace.replay(&mut replayed, make_model).unwrap();
+        let compressed = encoder.into_compressed().unwrap();
+        assert_eq!(replayed.into_compressed().unwrap(), compressed);
+
+        let ideal = trace.information_content::<f64, _, 24>(make_model);
+        assert!(ideal <= (compressed.len() * 32) as f64);
+        assert!(ideal + 64.0 > (compressed.len() * 32) as f64);
+
+        let mut decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+        let decoded = decoder
+            .decode_symbols(trace.operations().iter().map(|(_, spec)| make_model(spec)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(decoded
+            .iter()
+            .zip(trace.operations())
+            .all(|(decoded, (symbol, _))| decoded == symbol));
+
+        let mut ans = DefaultAnsCoder::new();
+        trace.replay_reverse(&mut ans, make_model).unwrap();
+        let decoded = ans
+            .decode_symbols(trace.operations().iter().map(|(_, spec)| make_model(spec)))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(
+            decoded,
+            trace
+                .into_operations()
+                .into_iter()
+                .map(|(s, _)| s)
+                .collect::<Vec<_>>()
+        );
+        assert!(ans.is_empty());
+    }
+}
@@ -0,0 +1,257 @@
+//! Converting compressed data between coder and model configurations
+//!
+//! Compressed data produced by `constriction` can only be decoded with the same kind of
+//! coder, the same word size, and the same entropy models (including their fixed point
+//! precision) that were used for encoding. If you want to migrate existing data to a
+//! different configuration (e.g., from a [`DefaultAnsCoder`] with 24 bit precision to a
+//! [`SmallRangeEncoder`] with 12 bit precision), you have to decode it and encode it again.
+//! The function [`transcode`] does this in a single streaming pass: it decodes one symbol
+//! at a time from the source decoder and immediately encodes it onto the destination
+//! encoder, so it never holds the decoded message in memory (apart from what the two coders
+//! themselves hold).
+//!
+//! # Order of Symbols
+//!
+//! `transcode` encodes symbols in the order in which it decodes them. If the destination is
+//! a queue (e.g., a [`RangeEncoder`]), then the resulting compressed data decodes in the
+//! same order as the source data. If the destination is a stack (i.e., an [`AnsCoder`]),
+//! then the resulting compressed data decodes in *reverse* order, i.e., the last symbol that
+//! was transcoded is the first one that gets decoded. Reversing the order while streaming
+//! isn't possible with bounded memory, so you have to take this into account when you
+//! decode the transcoded data (e.g., by decoding it with the models in reverse order).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::stream::{
+//!     model::{DefaultLeakyQuantizer, SmallLeakyQuantizer},
+//!     queue::{SmallRangeDecoder, SmallRangeEncoder},
+//!     stack::DefaultAnsCoder,
+//!     transcode::transcode,
+//!     Decode, Encode,
+//! };
+//! use probability::distribution::Gaussian;
+//!
+//! // Some data that was compressed with an `AnsCoder` and 24 bit precision.
+//! let means = [3.2, -10.1, 0.4, 25.7, 7.0];
+//! let symbols = [5, -12, 1, 20, 7];
+//! let old_quantizer = DefaultLeakyQuantizer::new(-100..=100);
+//! let old_models = means
+//!     .iter()
+//!     .map(|&mean| old_quantizer.quantize(Gaussian::new(mean, 5.0)))
+//!     .collect::<Vec<_>>();
+//! let mut ans = DefaultAnsCoder::new();
+//! ans.encode_symbols_reverse(symbols.iter().zip(&old_models)).unwrap();
+//!
+//! // Migrate it to a range coder with 16 bit words and 12 bit precision.
+//! let new_quantizer = SmallLeakyQuantizer::new(-100..=100);
+//! let new_models = means
+//!     .iter()
+//!     .map(|&mean| new_quantizer.quantize(Gaussian::new(mean, 5.0)))
+//!     .collect::<Vec<_>>();
+//! let mut encoder = SmallRangeEncoder::new();
+//! let count = transcode(&mut ans, &mut encoder, old_models.iter().zip(&new_models)).unwrap();
+//! assert_eq!(count, symbols.len());
+//! assert!(ans.is_empty());
+//!
+//! // The range coder decodes the symbols in the same order as the original data.
+//! let mut decoder = SmallRangeDecoder::from_compressed(encoder.into_compressed().unwrap())
+//!     .unwrap();
+//! let decoded = decoder
+//!     .decode_symbols(&new_models)
+//!     .collect::<Result<Vec<_>, _>>()
+//!     .unwrap();
+//! assert_eq!(decoded, symbols);
+//! ```
+//!
+//! [`DefaultAnsCoder`]: super::stack::DefaultAnsCoder
+//! [`AnsCoder`]: super::stack::AnsCoder
+//! [`SmallRangeEncoder`]: super::queue::SmallRangeEncoder
+//! [`RangeEncoder`]: super::queue::RangeEncoder
+
+use core::fmt::Display;
+
+use num::cast::AsPrimitive;
+
+use super::{
+    model::{DecoderModel, EncoderModel},
+    Decode, Encode,
+};
+use crate::CoderError;
+
+/// Decodes symbols from `src` and re-encodes them onto `dst`, one symbol at a time.
+///
+/// The iterator `models` yields one pair `(src_model, dst_model)` per symbol, where
+/// `src_model` is the entropy model with which the symbol was originally encoded onto `src`
+/// and `dst_model` is the entropy model with which it should be encoded onto `dst`. The two
+/// models may have different precisions and probability types, but they have to agree on
+/// the symbol type. Returns the number of transcoded symbols, i.e., the number of items
+/// that `models` yielded.
+///
+/// On error, `src` and `dst` are left in the state in which the error occurred, i.e., all
+/// symbols before the failing one have already been transcoded. The error reports the
+/// index of the failing symbol, see [`TranscodeError`].
+///
+/// See [module level documentation](self) for an example and for a discussion of the
+/// order in which the transcoded symbols get decoded.
+pub fn transcode<
+    Src,
+    Dst,
+    SrcModel,
+    DstModel,
+    const SRC_PRECISION: usize,
+    const DST_PRECISION: usize,
+>(
+    src: &mut Src,
+    dst: &mut Dst,
+    models: impl IntoIterator<Item = (SrcModel, DstModel)>,
+) -> Result<
+    usize,
+    TranscodeError<
+        CoderError<Src::FrontendError, Src::BackendError>,
+        CoderError<Dst::FrontendError, Dst::BackendError>,
+    >,
+>
+where
+    Src: Decode<SRC_PRECISION>,
+    Dst: Encode<DST_PRECISION>,
+    SrcModel: DecoderModel<SRC_PRECISION>,
+    DstModel: EncoderModel<DST_PRECISION, Symbol = SrcModel::Symbol>,
+    SrcModel::Probability: Into<Src::Word>,
+    Src::Word: AsPrimitive<SrcModel::Probability>,
+    DstModel::Probability: Into<Dst::Word>,
+    Dst::Word: AsPrimitive<DstModel::Probability>,
+{
+    let mut count = 0;
+    for (src_model, dst_model) in models {
+        let symbol = src
+            .decode_symbol(src_model)
+            .map_err(|source| TranscodeError::Decode {
+                index: count,
+                source,
+            })?;
+        dst.encode_symbol(symbol, dst_model)
+            .map_err(|source| TranscodeError::Encode {
+                index: count,
+                source,
+            })?;
+        count += 1;
+    }
+
+    Ok(count)
+}
+
+/// Error type for [`transcode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TranscodeError<DecodeError, EncodeError> {
+    /// Decoding the symbol at position `index` from the source decoder failed.
+    Decode { index: usize, source: DecodeError },
+
+    /// Encoding the symbol at position `index` onto the destination encoder failed (e.g.,
+    /// because the symbol lies outside the support of the destination entropy model).
+    Encode { index: usize, source: EncodeError },
+}
+
+impl<DecodeError, EncodeError> TranscodeError<DecodeError, EncodeError> {
+    /// Returns the position of the symbol at which transcoding failed, i.e., the number of
+    /// symbols that were successfully transcoded before the error occurred.
+    pub fn index(&self) -> usize {
+        match self {
+            Self::Decode { index, .. } | Self::Encode { index, .. } => *index,
+        }
+    }
+}
+
+impl<DecodeError: Display, EncodeError: Display> Display
+    for TranscodeError<DecodeError, EncodeError>
+{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Decode { index, source } => {
+                write!(f, "Error while decoding symbol {}: {}", index, source)
+            }
+            Self::Encode { index, source } => {
+                write!(f, "Error while re-encoding symbol {}: {}", index, source)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<DecodeError, EncodeError> std::error::Error for TranscodeError<DecodeError, EncodeError>
+where
+    DecodeError: std::error::Error + 'static,
+    EncodeError: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Decode { source, .. } => Some(source),
+            Self::Encode { source, .. } => Some(source),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        model::DefaultLeakyQuantizer,
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+    };
+    use super::*;
+    use crate::DefaultEncoderFrontendError;
+
+    use alloc::vec::Vec;
+    use probability::distribution::Gaussian;
+
+    #[test]
+    fn range_coder_to_ans() {
+        let quantizer = DefaultLeakyQuantizer::new(-100..=100);
+        let models = (0..100)
+            .map(|i| quantizer.quantize(Gaussian::new((i % 17) as f64 - 8.0, 3.0 + i as f64)))
+            .collect::<Vec<_>>();
+        let symbols = (0..100).map(|i| (i * 13) % 201 - 100).collect::<Vec<i32>>();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        encoder.encode_symbols(symbols.iter().zip(&models)).unwrap();
+        let mut decoder =
+            DefaultRangeDecoder::from_compressed(encoder.into_compressed().unwrap()).unwrap();
+
+        let mut ans = DefaultAnsCoder::new();
+        let count = transcode(&mut decoder, &mut ans, models.iter().zip(&models)).unwrap();
+        assert_eq!(count, symbols.len());
+        assert!(decoder.maybe_exhausted());
+
+        // The stack decodes the symbols in reverse order.
+        let decoded = ans
+            .decode_symbols(models.iter().rev())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert!(decoded.iter().rev().eq(&symbols));
+        assert!(ans.is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::manual_repeat_n)]
+    fn encode_error() {
+        let src_model = DefaultLeakyQuantizer::new(0..=9).quantize(Gaussian::new(5.0, 3.0));
+        let dst_model = DefaultLeakyQuantizer::new(0..=5).quantize(Gaussian::new(2.0, 3.0));
+        let symbols = [2, 0, 3, 7, 1];
+
+        let mut ans = DefaultAnsCoder::new();
+        ans.encode_iid_symbols_reverse(&symbols, &src_model)
+            .unwrap();
+
+        let mut encoder = DefaultRangeEncoder::new();
+        let models = core::iter::repeat((&src_model, &dst_model)).take(symbols.len());
+        let err = transcode(&mut ans, &mut encoder, models).unwrap_err();
+        assert_eq!(err.index(), 3);
+        assert_eq!(
+            err,
+            TranscodeError::Encode {
+                index: 3,
+                source: CoderError::Frontend(DefaultEncoderFrontendError::ImpossibleSymbol)
+            }
+        );
+    }
+}
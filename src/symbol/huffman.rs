@@ -1,5 +1,20 @@
 //! Codebooks for Huffman Coding
 //!
+//! # Tie Breaking
+//!
+//! Huffman's algorithm repeatedly merges the two nodes with the smallest probabilities,
+//! but it doesn't specify which nodes to merge if several nodes have the same probability.
+//! Different choices lead to different (but equally efficient) codebooks, so an encoder
+//! and a decoder that build their trees independently from the same probabilities have to
+//! agree on a [`TieBreaking`] policy. All constructors of [`EncoderHuffmanTree`] and
+//! [`DecoderHuffmanTree`] use [`TieBreaking::ByIndex`] unless you pass a different policy
+//! to one of the `_with_tie_breaking` constructors. The policies are deterministic and
+//! fully specified in their documentation, so they produce the same trees across versions
+//! of `constriction` and can be reimplemented in other languages. If you store the
+//! probabilities together with compressed data, store the policy too, e.g., as a single
+//! byte (see [`TieBreaking`]). Note that exact ties are only reliable for integer
+//! probabilities since sums of floating point probabilities are subject to rounding.
+//!
 //! # References
 //!
 //! Huffman, David A. "A method for the construction of minimum-redundancy codes."
@@ -11,7 +26,7 @@ use alloc::{collections::BinaryHeap, vec, vec::Vec};
 use core::{
     borrow::Borrow,
     cmp::Reverse,
-    convert::Infallible,
+    convert::{Infallible, TryFrom},
     fmt::{Debug, Display},
     ops::Add,
 };
@@ -28,11 +43,14 @@ pub struct EncoderHuffmanTree {
     /// - root node if `x == 0`;
     /// - otherwise, the lowest significant bit distinguishes left vs right children,
     ///   and the parent node is at index `x >> 1`.
+    ///
     /// (This works the node with index 0, if it exists, is always a leaf node, i.e., it
     /// cannot be any other node's parent node.)
     ///
     /// It is guaranteed that `num_symbols != 0` i.e., `nodes` is not empty.
     nodes: Vec<usize>,
+
+    tie_breaking: TieBreaking,
 }
 
 impl EncoderHuffmanTree {
@@ -61,62 +79,116 @@ impl EncoderHuffmanTree {
         )
     }
 
+    /// Like [`from_float_probabilities`](Self::from_float_probabilities), but returns an
+    /// error instead of panicking if `probabilities` is empty or too long.
+    pub fn checked_from_float_probabilities<P, I>(
+        probabilities: I,
+    ) -> Result<Self, HuffmanError<NanError>>
+    where
+        P: Float + Clone + Add<Output = P>,
+        I: IntoIterator,
+        I::Item: Borrow<P>,
+    {
+        Self::checked_from_probabilities(
+            probabilities
+                .into_iter()
+                .map(|p| NonNanFloat::new(*p.borrow())),
+        )
+    }
+
+    /// # Panics
+    ///
+    /// If `probabilities` is empty or too long. See [`checked_from_probabilities`] for a
+    /// variant of this method that never panics.
+    ///
+    /// [`checked_from_probabilities`]: Self::checked_from_probabilities
     pub fn try_from_probabilities<P, E, I>(probabilities: I) -> Result<Self, E>
     where
         P: Ord + Clone + Add<Output = P>,
         I: IntoIterator<Item = Result<P, E>>,
     {
-        let mut heap = probabilities
-            .into_iter()
-            .enumerate()
-            .map(|(i, s)| s.map(|s| (Reverse((s, i)))))
-            .collect::<Result<BinaryHeap<_>, E>>()?;
+        Self::checked_from_probabilities(probabilities).map_err(HuffmanError::unwrap_probability)
+    }
 
-        if heap.is_empty() || heap.len() > usize::max_value() / 4 {
-            panic!();
-        }
+    /// Builds a Huffman tree without panicking.
+    ///
+    /// Returns an error if any item of `probabilities` is an error, if `probabilities` is
+    /// empty, or if it has too many items to index all nodes of the tree with a `usize`.
+    pub fn checked_from_probabilities<P, E, I>(probabilities: I) -> Result<Self, HuffmanError<E>>
+    where
+        P: Ord + Clone + Add<Output = P>,
+        I: IntoIterator<Item = Result<P, E>>,
+    {
+        Self::checked_from_probabilities_with_tie_breaking(probabilities, TieBreaking::default())
+    }
 
-        let mut nodes = vec![0; heap.len() * 2 - 1];
-        let mut next_node_index = heap.len();
+    /// Like [`checked_from_float_probabilities`](Self::checked_from_float_probabilities),
+    /// but with an explicit [`TieBreaking`] policy.
+    pub fn checked_from_float_probabilities_with_tie_breaking<P, I>(
+        probabilities: I,
+        tie_breaking: TieBreaking,
+    ) -> Result<Self, HuffmanError<NanError>>
+    where
+        P: Float + Clone + Add<Output = P>,
+        I: IntoIterator,
+        I::Item: Borrow<P>,
+    {
+        Self::checked_from_probabilities_with_tie_breaking(
+            probabilities
+                .into_iter()
+                .map(|p| NonNanFloat::new(*p.borrow())),
+            tie_breaking,
+        )
+    }
+
+    /// Like [`checked_from_probabilities`](Self::checked_from_probabilities), but with an
+    /// explicit [`TieBreaking`] policy.
+    ///
+    /// A [`DecoderHuffmanTree`] built from the same `probabilities` with the same
+    /// `tie_breaking` policy can decode the codewords of the resulting tree.
+    pub fn checked_from_probabilities_with_tie_breaking<P, E, I>(
+        probabilities: I,
+        tie_breaking: TieBreaking,
+    ) -> Result<Self, HuffmanError<E>>
+    where
+        P: Ord + Clone + Add<Output = P>,
+        I: IntoIterator<Item = Result<P, E>>,
+    {
+        let mut heap = HuffmanHeap::new(probabilities, tie_breaking, usize::MAX / 4)?;
+        let mut nodes = vec![0; heap.num_symbols() * 2 - 1];
 
-        while let (Some(Reverse((prob0, index0))), Some(Reverse((prob1, index1)))) =
-            (heap.pop(), heap.pop())
-        {
-            heap.push(Reverse((prob0 + prob1, next_node_index)));
+        while let Some((index0, index1, parent_index)) = heap.merge_next() {
             unsafe {
                 // SAFETY:
-                // - `nodes.len() == original_heap_len * 2 - 1` (which we made sure doesn't wrap),
-                //   where `original_heap_len` is the value of `heap.len()` before entering this
-                //   `while` loop, which we checked is nonzero.
-                // - We access `nodes` and indices found in `heap`. These have to be either the
-                //   indices `0..original_heap_len` that we wrote into it initially (which are all
-                //   smaller than `original_heap_len * 2 - 1` since we checked that
-                //   `!heap.is_empty()`, i.e., that `original_heap_len != 0`); or they have to be
-                //   the indices we write to the heap in this `while` loop, which come from
-                //   `next_node_index`.
-                // - `next_node_index` starts at `original_heap_len` and increases by one in each
-                //   iteration of this `while` loop.
-                // - Each iteration of this `while` loop removes two elements from `heap` and
-                //   pushes one element back onto `heap`; so each iteration reduces the number of
-                //   elements on `heap` by one; since we terminate as soon as there are fewer than
-                //   2 elements on `heap`, this `while` loop iterates `original_heap_len - 1` times
-                //   (which is nonnegative since `original_heap_len != 0`).
-                // - Thus, the largest value that `next_node_index` can take is
-                //   `original_heap_len * 2 - 1`; but since we access `next_node_index` before
-                //   incrementing it, all values we ever push on the heap are strictly smaller than
-                //   `original_heap_len * 2 - 1`, and thus are valid indices.
-                *nodes.get_unchecked_mut(index0) = next_node_index << 1;
-                *nodes.get_unchecked_mut(index1) = (next_node_index << 1) | 1;
+                // - `nodes.len() == num_symbols * 2 - 1` (which we made sure doesn't wrap),
+                //   where `num_symbols` is nonzero (checked by `HuffmanHeap::new`).
+                // - `HuffmanHeap::merge_next` only ever returns either the indices
+                //   `0..num_symbols` of the leaf nodes, or indices of merged nodes, which it
+                //   assigns in increasing order starting at `num_symbols`.
+                // - Each call to `merge_next` reduces the number of nodes on the heap by one,
+                //   and it returns `None` as soon as there are fewer than 2 nodes on the heap.
+                //   So it returns `Some(_)` exactly `num_symbols - 1` times.
+                // - Thus, the largest `parent_index` is `num_symbols * 2 - 2`, and all indices
+                //   that `merge_next` returns are valid indices into `nodes`.
+                *nodes.get_unchecked_mut(index0) = parent_index << 1;
+                *nodes.get_unchecked_mut(index1) = (parent_index << 1) | 1;
             }
-            next_node_index += 1;
         }
 
-        Ok(Self { nodes })
+        Ok(Self {
+            nodes,
+            tie_breaking,
+        })
     }
 
     pub fn num_symbols(&self) -> usize {
         self.nodes.len() / 2 + 1
     }
+
+    /// Returns the [`TieBreaking`] policy with which the tree was built.
+    pub fn tie_breaking(&self) -> TieBreaking {
+        self.tie_breaking
+    }
 }
 
 impl Codebook for EncoderHuffmanTree {
@@ -168,6 +240,8 @@ pub struct DecoderHuffmanTree {
     /// - `num_symbols != 0` (but `nodes` can still be empty if `num_symbols == 1`.
     /// - All entries of `nodes` are strictly smaller than `2 * nodes.len()`.
     nodes: Vec<[usize; 2]>,
+
+    tie_breaking: TieBreaking,
 }
 
 impl DecoderHuffmanTree {
@@ -196,38 +270,102 @@ impl DecoderHuffmanTree {
         )
     }
 
+    /// Like [`from_float_probabilities`](Self::from_float_probabilities), but returns an
+    /// error instead of panicking if `probabilities` is empty or too long.
+    pub fn checked_from_float_probabilities<P, I>(
+        probabilities: I,
+    ) -> Result<Self, HuffmanError<NanError>>
+    where
+        P: Float + Clone + Add<Output = P>,
+        I: IntoIterator,
+        I::Item: Borrow<P>,
+    {
+        Self::checked_from_probabilities(
+            probabilities
+                .into_iter()
+                .map(|p| NonNanFloat::new(*p.borrow())),
+        )
+    }
+
+    /// # Panics
+    ///
+    /// If `probabilities` is empty or too long. See [`checked_from_probabilities`] for a
+    /// variant of this method that never panics.
+    ///
+    /// [`checked_from_probabilities`]: Self::checked_from_probabilities
     pub fn try_from_probabilities<P, E, I>(probabilities: I) -> Result<Self, E>
     where
         P: Ord + Clone + Add<Output = P>,
         I: IntoIterator<Item = Result<P, E>>,
     {
-        let mut heap = probabilities
-            .into_iter()
-            .enumerate()
-            .map(|(i, s)| s.map(|s| (Reverse((s, i)))))
-            .collect::<Result<BinaryHeap<_>, E>>()?;
+        Self::checked_from_probabilities(probabilities).map_err(HuffmanError::unwrap_probability)
+    }
 
-        if heap.is_empty() || heap.len() > usize::max_value() / 2 {
-            panic!();
-        }
+    /// Builds a Huffman tree without panicking.
+    ///
+    /// Returns an error if any item of `probabilities` is an error, if `probabilities` is
+    /// empty, or if it has too many items to index all nodes of the tree with a `usize`.
+    pub fn checked_from_probabilities<P, E, I>(probabilities: I) -> Result<Self, HuffmanError<E>>
+    where
+        P: Ord + Clone + Add<Output = P>,
+        I: IntoIterator<Item = Result<P, E>>,
+    {
+        Self::checked_from_probabilities_with_tie_breaking(probabilities, TieBreaking::default())
+    }
 
-        let mut nodes = Vec::with_capacity(heap.len() - 1);
-        let mut next_node_index = heap.len();
+    /// Like [`checked_from_float_probabilities`](Self::checked_from_float_probabilities),
+    /// but with an explicit [`TieBreaking`] policy.
+    pub fn checked_from_float_probabilities_with_tie_breaking<P, I>(
+        probabilities: I,
+        tie_breaking: TieBreaking,
+    ) -> Result<Self, HuffmanError<NanError>>
+    where
+        P: Float + Clone + Add<Output = P>,
+        I: IntoIterator,
+        I::Item: Borrow<P>,
+    {
+        Self::checked_from_probabilities_with_tie_breaking(
+            probabilities
+                .into_iter()
+                .map(|p| NonNanFloat::new(*p.borrow())),
+            tie_breaking,
+        )
+    }
+
+    /// Like [`checked_from_probabilities`](Self::checked_from_probabilities), but with an
+    /// explicit [`TieBreaking`] policy.
+    ///
+    /// Decodes the codewords of an [`EncoderHuffmanTree`] that was built from the same
+    /// `probabilities` with the same `tie_breaking` policy.
+    pub fn checked_from_probabilities_with_tie_breaking<P, E, I>(
+        probabilities: I,
+        tie_breaking: TieBreaking,
+    ) -> Result<Self, HuffmanError<E>>
+    where
+        P: Ord + Clone + Add<Output = P>,
+        I: IntoIterator<Item = Result<P, E>>,
+    {
+        let mut heap = HuffmanHeap::new(probabilities, tie_breaking, usize::MAX / 2)?;
+        let mut nodes = Vec::with_capacity(heap.num_symbols() - 1);
 
-        while let (Some(Reverse((prob0, index0))), Some(Reverse((prob1, index1)))) =
-            (heap.pop(), heap.pop())
-        {
-            heap.push(Reverse((prob0 + prob1, next_node_index)));
+        while let Some((index0, index1, _)) = heap.merge_next() {
             nodes.push([index0, index1]);
-            next_node_index += 1;
         }
 
-        Ok(Self { nodes })
+        Ok(Self {
+            nodes,
+            tie_breaking,
+        })
     }
 
     pub fn num_symbols(&self) -> usize {
         self.nodes.len() + 1
     }
+
+    /// Returns the [`TieBreaking`] policy with which the tree was built.
+    pub fn tie_breaking(&self) -> TieBreaking {
+        self.tie_breaking
+    }
 }
 
 impl Codebook for DecoderHuffmanTree {
@@ -269,6 +407,181 @@ impl DecoderCodebook for DecoderHuffmanTree {
     }
 }
 
+/// Policy for which nodes to merge when building a Huffman tree from probabilities that
+/// contain ties.
+///
+/// Huffman's algorithm maintains a set of nodes, which initially contains one leaf node
+/// per symbol. It repeatedly removes the two nodes with the smallest probabilities from
+/// the set and merges them into a new node with the sum of their probabilities. The first
+/// removed node becomes the child for bit `0` and the second removed node becomes the
+/// child for bit `1`. If several nodes have the same probability then the policy decides
+/// which of them are removed first. Each policy defines a strict total order on nodes with
+/// equal probabilities, so trees are fully determined by the probabilities and the policy.
+///
+/// Each policy has a stable numeric code (its discriminant), which you can obtain with
+/// `u8::from(policy)` and convert back with `TieBreaking::try_from(code)`. Use it to
+/// store the policy alongside the probabilities from which you build a tree.
+///
+/// # Example
+///
+/// ```
+/// use constriction::symbol::huffman::{DecoderHuffmanTree, EncoderHuffmanTree, TieBreaking};
+/// use std::convert::TryFrom;
+///
+/// let probabilities = [2u32, 1, 1, 2];
+/// let policy = TieBreaking::BySymbol;
+///
+/// // Store the policy along with the probabilities ...
+/// let code = u8::from(policy);
+/// assert_eq!(code, 1);
+///
+/// // ... and rebuild identical trees on the other side.
+/// let policy = TieBreaking::try_from(code).unwrap();
+/// let encoder_tree = EncoderHuffmanTree::checked_from_probabilities_with_tie_breaking(
+///     probabilities.iter().map(|&p| Ok::<_, ()>(p)),
+///     policy,
+/// )
+/// .unwrap();
+/// let decoder_tree = DecoderHuffmanTree::checked_from_probabilities_with_tie_breaking(
+///     probabilities.iter().map(|&p| Ok::<_, ()>(p)),
+///     policy,
+/// )
+/// .unwrap();
+/// assert_eq!(encoder_tree.tie_breaking(), decoder_tree.tie_breaking());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum TieBreaking {
+    /// Removes nodes in the order in which they were added to the set, i.e., leaf nodes in
+    /// the order of their symbols first, followed by merged nodes in the order in which
+    /// they were created. This is a stable policy: a merged node never overtakes an older
+    /// node with the same probability, which keeps the tree as shallow as possible among
+    /// all Huffman trees for the given probabilities. This is the default, and it's the
+    /// policy used by all constructors that don't take a `tie_breaking` argument.
+    ByIndex = 0,
+
+    /// Removes the node whose subtree contains the smallest symbol first.
+    BySymbol = 1,
+
+    /// Removes merged nodes before leaf nodes, and more recently merged nodes before
+    /// older merged nodes. Leaf nodes are removed in the order of their symbols. This
+    /// policy tends to produce deep trees with a large variance of codeword lengths.
+    MergedFirst = 2,
+}
+
+impl Default for TieBreaking {
+    fn default() -> Self {
+        Self::ByIndex
+    }
+}
+
+impl TieBreaking {
+    /// Secondary sort key of the leaf node for `symbol`, i.e., node with index `symbol`.
+    fn leaf_key(self, symbol: usize) -> (bool, usize) {
+        match self {
+            Self::ByIndex | Self::BySymbol => (false, symbol),
+            Self::MergedFirst => (true, symbol),
+        }
+    }
+
+    /// Secondary sort key of a merged node with index `index` and children with the
+    /// secondary sort keys `key0` and `key1`.
+    fn merged_key(self, index: usize, key0: (bool, usize), key1: (bool, usize)) -> (bool, usize) {
+        match self {
+            Self::ByIndex => (false, index),
+            // Both keys are the smallest symbols in the children's subtrees.
+            Self::BySymbol => (false, key0.1.min(key1.1)),
+            Self::MergedFirst => (false, usize::MAX - index),
+        }
+    }
+}
+
+impl From<TieBreaking> for u8 {
+    fn from(tie_breaking: TieBreaking) -> Self {
+        tie_breaking as u8
+    }
+}
+
+impl TryFrom<u8> for TieBreaking {
+    type Error = ();
+
+    /// Returns an error if `code` is not the code of any policy.
+    fn try_from(code: u8) -> Result<Self, ()> {
+        match code {
+            0 => Ok(Self::ByIndex),
+            1 => Ok(Self::BySymbol),
+            2 => Ok(Self::MergedFirst),
+            _ => Err(()),
+        }
+    }
+}
+
+/// The set of nodes in Huffman's algorithm, shared by [`EncoderHuffmanTree`] and
+/// [`DecoderHuffmanTree`].
+///
+/// Leaf nodes have the indices `0..num_symbols`, and merged nodes get the indices
+/// `num_symbols..2 * num_symbols - 1` in the order in which they are created.
+struct HuffmanHeap<P> {
+    heap: BinaryHeap<Reverse<(P, (bool, usize), usize)>>,
+    tie_breaking: TieBreaking,
+    num_symbols: usize,
+    next_node_index: usize,
+}
+
+impl<P: Ord + Add<Output = P>> HuffmanHeap<P> {
+    /// Returns an error if `probabilities` is empty or has more than `max_num_symbols`
+    /// items, or if any of its items is an error.
+    fn new<E>(
+        probabilities: impl IntoIterator<Item = Result<P, E>>,
+        tie_breaking: TieBreaking,
+        max_num_symbols: usize,
+    ) -> Result<Self, HuffmanError<E>> {
+        let heap = probabilities
+            .into_iter()
+            .enumerate()
+            .map(|(i, s)| s.map(|s| Reverse((s, tie_breaking.leaf_key(i), i))))
+            .collect::<Result<BinaryHeap<_>, E>>()
+            .map_err(HuffmanError::InvalidProbability)?;
+
+        if heap.is_empty() {
+            return Err(HuffmanError::EmptyAlphabet);
+        }
+        if heap.len() > max_num_symbols {
+            return Err(HuffmanError::TooManySymbols);
+        }
+
+        let num_symbols = heap.len();
+        Ok(Self {
+            heap,
+            tie_breaking,
+            num_symbols,
+            next_node_index: num_symbols,
+        })
+    }
+
+    fn num_symbols(&self) -> usize {
+        self.num_symbols
+    }
+
+    /// Merges the next two nodes and returns `(index0, index1, parent_index)`, or returns
+    /// `None` if the tree is complete.
+    fn merge_next(&mut self) -> Option<(usize, usize, usize)> {
+        if self.heap.len() < 2 {
+            return None;
+        }
+        let Reverse((prob0, key0, index0)) = self.heap.pop()?;
+        let Reverse((prob1, key1, index1)) = self.heap.pop()?;
+
+        let parent_index = self.next_node_index;
+        let parent_key = self.tie_breaking.merged_key(parent_index, key0, key1);
+        self.heap
+            .push(Reverse((prob0 + prob1, parent_key, parent_index)));
+        self.next_node_index += 1;
+
+        Some((index0, index1, parent_index))
+    }
+}
+
 #[derive(PartialOrd, Clone, Copy)]
 struct NonNanFloat<F: Float> {
     inner: F,
@@ -324,9 +637,55 @@ impl Display for NanError {
     }
 }
 
-#[cfg(std)]
+#[cfg(feature = "std")]
 impl std::error::Error for NanError {}
 
+/// Error type for the `checked_*` constructors of [`EncoderHuffmanTree`] and
+/// [`DecoderHuffmanTree`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HuffmanError<E> {
+    /// There has to be at least one symbol.
+    EmptyAlphabet,
+
+    /// The number of symbols is so large that the nodes of the tree can't be indexed with
+    /// a `usize`.
+    TooManySymbols,
+
+    /// The iterator of probabilities returned an error.
+    InvalidProbability(E),
+}
+
+impl<E> HuffmanError<E> {
+    /// Panics on the errors that the non-`checked` constructors don't report.
+    fn unwrap_probability(self) -> E {
+        match self {
+            Self::InvalidProbability(err) => err,
+            Self::EmptyAlphabet => panic!("A Huffman tree needs at least one symbol."),
+            Self::TooManySymbols => panic!("Too many symbols for a Huffman tree."),
+        }
+    }
+}
+
+impl<E: Display> Display for HuffmanError<E> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::EmptyAlphabet => write!(f, "A Huffman tree needs at least one symbol."),
+            Self::TooManySymbols => write!(f, "Too many symbols for a Huffman tree."),
+            Self::InvalidProbability(err) => write!(f, "Invalid probability: {}", err),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for HuffmanError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidProbability(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::{
@@ -450,4 +809,76 @@ mod tests {
                 .unwrap(),
         );
     }
+
+    #[test]
+    fn tie_breaking() {
+        let probabilities = [2u32, 1, 1, 2];
+        for &(tie_breaking, expected) in &[
+            (TieBreaking::ByIndex, ["10", "00", "01", "11"]),
+            (TieBreaking::BySymbol, ["10", "110", "111", "0"]),
+            (TieBreaking::MergedFirst, ["11", "100", "101", "0"]),
+        ] {
+            let encoder_tree = EncoderHuffmanTree::checked_from_probabilities_with_tie_breaking(
+                probabilities.iter().map(|&p| Ok::<_, Infallible>(p)),
+                tie_breaking,
+            )
+            .unwrap();
+            let decoder_tree = DecoderHuffmanTree::checked_from_probabilities_with_tie_breaking(
+                probabilities.iter().map(|&p| Ok::<_, Infallible>(p)),
+                tie_breaking,
+            )
+            .unwrap();
+            assert_eq!(encoder_tree.tie_breaking(), tie_breaking);
+            assert_eq!(decoder_tree.tie_breaking(), tie_breaking);
+            assert_eq!(
+                TieBreaking::try_from(u8::from(tie_breaking)),
+                Ok(tie_breaking)
+            );
+
+            for (symbol, &expected) in expected.iter().enumerate() {
+                let mut codeword = String::new();
+                encoder_tree
+                    .encode_symbol_prefix(symbol, |bit| {
+                        codeword.push(if bit { '1' } else { '0' });
+                        Result::<_, Infallible>::Ok(())
+                    })
+                    .unwrap();
+                assert_eq!(codeword, expected);
+
+                let mut bits = SmallBitStack::new();
+                encoder_tree
+                    .encode_symbol_suffix(symbol, |bit| bits.write_bit(bit))
+                    .unwrap();
+                assert_eq!(decoder_tree.decode_symbol(&mut bits).unwrap(), symbol);
+            }
+        }
+
+        let default_tree = EncoderHuffmanTree::from_probabilities::<u32, _>(&probabilities);
+        assert_eq!(default_tree.tie_breaking(), TieBreaking::ByIndex);
+        assert_eq!(TieBreaking::try_from(3), Err(()));
+    }
+
+    #[test]
+    fn checked_from_probabilities() {
+        let empty: [u32; 0] = [];
+        assert_eq!(
+            EncoderHuffmanTree::checked_from_probabilities(empty.iter().map(|&p| Ok::<_, ()>(p)))
+                .unwrap_err(),
+            HuffmanError::EmptyAlphabet
+        );
+        assert_eq!(
+            DecoderHuffmanTree::checked_from_probabilities(empty.iter().map(|&p| Ok::<_, ()>(p)))
+                .unwrap_err(),
+            HuffmanError::EmptyAlphabet
+        );
+        assert_eq!(
+            DecoderHuffmanTree::checked_from_float_probabilities::<f32, _>(&[0.5, f32::NAN])
+                .unwrap_err(),
+            HuffmanError::InvalidProbability(NanError::NaN)
+        );
+
+        let tree = EncoderHuffmanTree::checked_from_float_probabilities::<f32, _>(&[0.3, 0.2, 0.5])
+            .unwrap();
+        assert_eq!(tree.num_symbols(), 3);
+    }
 }
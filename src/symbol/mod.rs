@@ -8,6 +8,7 @@
 
 pub mod exp_golomb;
 pub mod huffman;
+pub mod rice;
 
 use alloc::vec::Vec;
 use core::{
@@ -50,6 +51,7 @@ pub trait ReadBitStream<S: Semantics> {
         }
     }
 
+    #[allow(clippy::manual_repeat_n)]
     fn decode_iid_symbols<'a, C>(
         &'a mut self,
         amt: usize,
@@ -167,7 +169,7 @@ pub struct SymbolCoder<Word: BitArray, S: Semantics, B = Vec<Word>> {
     ///   the backend (in case of a write backend) and/or if reading the next bit would
     ///   require obtaining a new word from the backend (in case of a read backend); This
     ///   includes the case of an empty `QueueEncoder`. In all of these cases, `current_word`
-    ///  as to be zero.
+    ///   has to be zero.
     /// - otherwise, `mask_last_written` has a single set bit which marks the position in
     ///   `current_word` where the next bit should be written if any.
     mask_last_written: Word,
@@ -0,0 +1,221 @@
+use core::{borrow::Borrow, convert::Infallible, marker::PhantomData};
+
+use num::{PrimInt, Unsigned};
+
+use super::{Codebook, DecoderCodebook, EncoderCodebook, SymbolCodeError};
+use crate::{CoderError, DefaultEncoderError};
+
+/// Codebook for Rice Coding (i.e., Golomb Coding with a power-of-two divisor).
+///
+/// A symbol `n` is encoded as the quotient `n >> k` in unary (as a sequence of zeros
+/// terminated by a one), followed by the `k` least significant bits of `n`, most
+/// significant bit first. Rice codes are optimal for geometrically distributed symbols and
+/// are a popular choice for prediction residuals (e.g., in lossless audio codecs), where
+/// `k` is typically adapted to a running estimate of the mean (see, e.g., the module
+/// [`timeseries`](crate::timeseries)).
+///
+/// To bound the length of code words, a symbol whose quotient would require `N::BITS` or
+/// more zeros is instead encoded as an escape sequence of exactly `N::BITS` zeros followed
+/// by all `N::BITS` bits of the symbol. Thus, every symbol of type `N` can be encoded.
+///
+/// # Example
+///
+/// ```
+/// use constriction::{
+///     symbol::{DefaultQueueEncoder, WriteBitStream, ReadBitStream},
+///     symbol::rice::Rice,
+///     UnwrapInfallible,
+/// };
+///
+/// let codebook = Rice::<u32>::new(2);
+/// let mut encoder = DefaultQueueEncoder::new();
+/// encoder.encode_iid_symbols(&[3, 9, 0], &codebook).unwrap();
+/// let mut decoder = encoder.into_decoder().unwrap_infallible();
+/// let bit_string = decoder.clone().take(11).map(
+///     |bit| if bit.unwrap_infallible() { '1' } else { '0' }
+/// ).collect::<String>();
+/// assert_eq!(bit_string, "11100101100");
+///
+/// let decoded = decoder.decode_iid_symbols(3, &codebook).collect::<Result<Vec<_>, _>>().unwrap();
+/// assert_eq!(decoded, [3, 9, 0]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rice<N> {
+    k: u32,
+    phantom: PhantomData<N>,
+}
+
+impl<N: Unsigned + PrimInt> Rice<N> {
+    /// Creates a Rice codebook that emits the `k` least significant bits of each symbol
+    /// verbatim.
+    ///
+    /// # Panics
+    ///
+    /// If `k` is not smaller than the number of bits of `N`.
+    pub fn new(k: u32) -> Self {
+        assert!(k < N::zero().count_zeros());
+        Self {
+            k,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the number of least significant bits that are emitted verbatim, i.e., the
+    /// parameter `k` that was passed to [`new`](Self::new).
+    pub fn k(&self) -> u32 {
+        self.k
+    }
+}
+
+impl<N> Codebook for Rice<N> {
+    type Symbol = N;
+}
+
+impl<N: Unsigned + PrimInt> EncoderCodebook for Rice<N> {
+    fn encode_symbol_prefix<BackendError>(
+        &self,
+        symbol: impl Borrow<Self::Symbol>,
+        mut emit: impl FnMut(bool) -> Result<(), BackendError>,
+    ) -> Result<(), DefaultEncoderError<BackendError>> {
+        let symbol = *symbol.borrow();
+        let bits = N::zero().count_zeros();
+        let quotient = symbol >> self.k as usize;
+
+        let num_low_bits = if quotient >= N::from(bits).unwrap_or_else(N::max_value) {
+            for _ in 0..bits {
+                emit(false)?;
+            }
+            bits
+        } else {
+            for _ in 0..quotient.to_u32().expect("quotient < bits") {
+                emit(false)?;
+            }
+            emit(true)?;
+            self.k
+        };
+
+        for i in (0..num_low_bits).rev() {
+            emit((symbol >> i as usize) & N::one() != N::zero())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<N: Unsigned + PrimInt> DecoderCodebook for Rice<N> {
+    type InvalidCodeword = Infallible;
+
+    fn decode_symbol<BackendError>(
+        &self,
+        mut source: impl Iterator<Item = Result<bool, BackendError>>,
+    ) -> Result<Self::Symbol, CoderError<SymbolCodeError<Self::InvalidCodeword>, BackendError>>
+    {
+        let bits = N::zero().count_zeros();
+        let mut quotient = 0u32;
+        while quotient != bits {
+            match source.next().transpose()? {
+                Some(false) => quotient += 1,
+                Some(true) => break,
+                None => return Err(SymbolCodeError::OutOfCompressedData.into_coder_error()),
+            }
+        }
+
+        let (mut symbol, num_low_bits) = if quotient == bits {
+            (N::zero(), bits)
+        } else {
+            (N::from(quotient).expect("quotient < bits"), self.k)
+        };
+
+        for _ in 0..num_low_bits {
+            match source.next().transpose()? {
+                Some(bit) => symbol = symbol << 1 | if bit { N::one() } else { N::zero() },
+                None => return Err(SymbolCodeError::OutOfCompressedData.into_coder_error()),
+            }
+        }
+
+        Ok(symbol)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    use super::{
+        super::{DefaultQueueEncoder, DefaultStackCoder, ReadBitStream, WriteBitStream},
+        *,
+    };
+    use crate::UnwrapInfallible;
+
+    use alloc::vec::Vec;
+
+    #[test]
+    fn explicit_examples() {
+        fn test_one<N: Unsigned + PrimInt>(k: u32, symbol: N, expected: &[u8]) {
+            let codebook = Rice::new(k);
+            let mut index = 0;
+            codebook
+                .encode_symbol_prefix(symbol, |bit| {
+                    assert_eq!(expected[index], if bit { b'1' } else { b'0' });
+                    index += 1;
+                    Result::<_, Infallible>::Ok(())
+                })
+                .unwrap();
+            assert_eq!(index, expected.len());
+        }
+
+        test_one(0, 0u32, b"1");
+        test_one(0, 3u32, b"0001");
+        test_one(2, 3u32, b"111");
+        test_one(2, 9u32, b"00101");
+        test_one(3, 7u32, b"1111");
+        test_one(0, 7u8, b"00000001");
+        test_one(0, 8u8, b"0000000000001000");
+        test_one(7, u8::MAX, b"011111111");
+        test_one(2, u8::MAX, b"0000000011111111");
+    }
+
+    #[test]
+    fn encode_decode_iid_queue() {
+        let amt = 1000;
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        for k in [0, 1, 5, 31] {
+            let symbols = (0..amt)
+                .map(|_| rng.next_u32() >> (rng.next_u32() % 32))
+                .collect::<Vec<_>>();
+            let codebook = Rice::<u32>::new(k);
+
+            let mut encoder = DefaultQueueEncoder::new();
+            encoder.encode_iid_symbols(&symbols, &codebook).unwrap();
+            let mut decoder = encoder.into_decoder().unwrap_infallible();
+            let reconstructed = decoder
+                .decode_iid_symbols(amt, &codebook)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+
+            assert_eq!(reconstructed, symbols);
+            assert!(decoder.maybe_exhausted());
+        }
+    }
+
+    #[test]
+    fn encode_decode_iid_stack() {
+        let amt = 1000;
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let symbols = (0..amt).map(|_| rng.next_u32() % 40).collect::<Vec<_>>();
+        let codebook = Rice::<u32>::new(3);
+
+        let mut coder = DefaultStackCoder::new();
+        coder
+            .encode_iid_symbols_reverse(&symbols, &codebook)
+            .unwrap();
+        let reconstructed = coder
+            .decode_iid_symbols(amt, &codebook)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+
+        assert_eq!(reconstructed, symbols);
+        assert!(coder.is_empty());
+    }
+}
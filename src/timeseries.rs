@@ -0,0 +1,328 @@
+//! Lossless compression of integer time series (e.g., sensor readings)
+//!
+//! This module combines three ingredients that are commonly used to compress smooth integer
+//! signals: a simple linear predictor (see [`Predictor`]), a [`Rice`] code for the
+//! prediction residuals whose parameter adapts to a running estimate of the residuals'
+//! magnitude, and a compact header that records the number of samples and the predictor.
+//! The result is a self-contained bit string that you can obtain with [`encode`] and
+//! invert with [`decode`].
+//!
+//! All arithmetic on samples wraps around, so any sequence of `i32`s can be encoded
+//! (including sequences that jump between `i32::MIN` and `i32::MAX`), and outliers only
+//! cost a bounded number of bits (see escape sequences in [`Rice`]).
+//!
+//! # Example
+//!
+//! ```
+//! use constriction::timeseries::{decode, encode, Predictor};
+//!
+//! let samples = [1000, 1003, 1007, 1010, 1012, 1015, 1017, 1018, 1020, 1021];
+//! let compressed = encode(&samples, Predictor::SecondOrderDelta);
+//! assert_eq!(compressed.len(), 2); // 64 bits instead of 320 bits.
+//!
+//! let decoded = decode(&compressed).unwrap();
+//! assert_eq!(decoded, samples);
+//! ```
+//!
+//! # Format
+//!
+//! The compressed data is written into a [`DefaultQueueEncoder`], i.e., bits are packed
+//! into `u32` words starting at the least significant bit. It consists of the following:
+//!
+//! - the number of samples, encoded with [`ExpGolomb<u64>`](ExpGolomb);
+//! - the order of the predictor (`1` or `2`), encoded with [`ExpGolomb<u32>`](ExpGolomb);
+//! - the first sample, zigzag-mapped to an unsigned integer and encoded with
+//!   [`ExpGolomb<u32>`](ExpGolomb); and
+//! - the prediction residuals of all remaining samples, zigzag-mapped to unsigned integers
+//!   and encoded with an adaptive [`Rice`] code. For [`Predictor::SecondOrderDelta`], the
+//!   second sample is predicted with [`Predictor::Delta`] since there's not enough history
+//!   yet.
+//!
+//! The Rice parameter `k` for each residual is the smallest `k` for which `count << k` is
+//! at least `sum`, where `sum` and `count` track the magnitudes and the number of preceding
+//! residuals, respectively. Both are halved whenever `count` reaches 64 so that the
+//! parameter keeps adapting to nonstationary signals.
+//!
+//! [`Rice`]: crate::symbol::rice::Rice
+//! [`ExpGolomb`]: crate::symbol::exp_golomb::ExpGolomb
+//! [`DefaultQueueEncoder`]: crate::symbol::DefaultQueueEncoder
+
+use alloc::vec::Vec;
+use core::{convert::TryFrom, fmt::Display};
+
+use crate::{
+    backends::Cursor,
+    symbol::{
+        exp_golomb::ExpGolomb, rice::Rice, DefaultQueueEncoder, QueueDecoder, ReadBitStream,
+        WriteBitStream,
+    },
+    UnwrapInfallible,
+};
+
+/// The linear predictor with which [`encode`] decorrelates consecutive samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Predictor {
+    /// Predicts each sample to be equal to its predecessor, i.e., encodes the differences
+    /// between consecutive samples. Works well for signals that resemble a random walk.
+    Delta,
+
+    /// Predicts each sample by linear extrapolation from its two predecessors, i.e.,
+    /// encodes the differences between consecutive differences. Works well for smooth
+    /// signals that change slowly.
+    SecondOrderDelta,
+}
+
+impl Predictor {
+    /// Returns the order of the predictor, i.e., `1` for [`Delta`](Self::Delta) and `2`
+    /// for [`SecondOrderDelta`](Self::SecondOrderDelta).
+    pub fn order(self) -> u32 {
+        match self {
+            Self::Delta => 1,
+            Self::SecondOrderDelta => 2,
+        }
+    }
+
+    /// Inverse of [`order`](Self::order). Returns `None` for unsupported orders.
+    pub fn from_order(order: u32) -> Option<Self> {
+        match order {
+            1 => Some(Self::Delta),
+            2 => Some(Self::SecondOrderDelta),
+            _ => None,
+        }
+    }
+
+    fn predict(self, history: &[i32]) -> i32 {
+        match (self, history) {
+            (Self::SecondOrderDelta, [.., before_last, last]) => {
+                last.wrapping_mul(2).wrapping_sub(*before_last)
+            }
+            (_, [.., last]) => *last,
+            (_, []) => 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum DecodingError {
+    /// The header is not a valid header for this format.
+    InvalidHeader,
+
+    /// The compressed data ended before all samples were decoded.
+    OutOfCompressedData,
+}
+
+impl Display for DecodingError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::InvalidHeader => write!(f, "Invalid header for compressed time series."),
+            Self::OutOfCompressedData => write!(
+                f,
+                "The compressed data ended before all samples were decoded."
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodingError {}
+
+/// Compresses a sequence of samples into a bit string that can be decoded with [`decode`].
+///
+/// See [module level documentation](self) for an example and for details of the format.
+pub fn encode(samples: &[i32], predictor: Predictor) -> Vec<u32> {
+    let mut encoder = DefaultQueueEncoder::new();
+    encoder
+        .encode_symbol(samples.len() as u64, ExpGolomb::new())
+        .unwrap();
+    encoder
+        .encode_symbol(predictor.order(), ExpGolomb::new())
+        .unwrap();
+
+    if let Some(&first) = samples.first() {
+        encoder
+            .encode_symbol(zigzag(first), ExpGolomb::new())
+            .unwrap();
+    }
+
+    let mut adapter = RiceAdapter::new();
+    for (i, &sample) in samples.iter().enumerate().skip(1) {
+        let residual = zigzag(sample.wrapping_sub(predictor.predict(&samples[..i])));
+        encoder.encode_symbol(residual, adapter.codebook()).unwrap();
+        adapter.update(residual);
+    }
+
+    encoder.into_compressed().unwrap_infallible()
+}
+
+/// Reconstructs the samples from a bit string that was generated by [`encode`].
+///
+/// Trailing data after the last sample is ignored.
+///
+/// # Errors
+///
+/// Returns an error if `compressed` is not a valid encoding of a time series. Note that
+/// not all invalid data can be detected, so this method may also succeed and return
+/// arbitrary samples if `compressed` was not generated by [`encode`].
+pub fn decode(compressed: &[u32]) -> Result<Vec<i32>, DecodingError> {
+    let mut decoder = QueueDecoder::from_compressed(Cursor::new_at_write_beginning(compressed));
+
+    let len = decoder
+        .decode_symbol(ExpGolomb::<u64>::new())
+        .map_err(|_| DecodingError::InvalidHeader)?;
+    let predictor = decoder
+        .decode_symbol(ExpGolomb::<u32>::new())
+        .ok()
+        .and_then(Predictor::from_order)
+        .ok_or(DecodingError::InvalidHeader)?;
+    let len = usize::try_from(len).map_err(|_| DecodingError::InvalidHeader)?;
+
+    // Each sample takes at least one bit, so don't trust `len` for preallocation.
+    let mut samples = Vec::with_capacity(len.min(compressed.len() * 32));
+    if len != 0 {
+        let first = decoder
+            .decode_symbol(ExpGolomb::<u32>::new())
+            .map_err(|_| DecodingError::OutOfCompressedData)?;
+        samples.push(unzigzag(first));
+    }
+
+    let mut adapter = RiceAdapter::new();
+    for _ in 1..len {
+        let residual = decoder
+            .decode_symbol(adapter.codebook())
+            .map_err(|_| DecodingError::OutOfCompressedData)?;
+        adapter.update(residual);
+        samples.push(predictor.predict(&samples).wrapping_add(unzigzag(residual)));
+    }
+
+    Ok(samples)
+}
+
+/// Maps signed integers with small magnitudes to small unsigned integers:
+/// `0, -1, 1, -2, 2, ...` to `0, 1, 2, 3, 4, ...`.
+fn zigzag(x: i32) -> u32 {
+    ((x as u32) << 1) ^ ((x >> 31) as u32)
+}
+
+fn unzigzag(x: u32) -> i32 {
+    ((x >> 1) as i32) ^ -((x & 1) as i32)
+}
+
+/// Running estimate of the Rice parameter, following the approach of LOCO-I / JPEG-LS.
+#[derive(Debug, Clone)]
+struct RiceAdapter {
+    sum: u64,
+    count: u64,
+}
+
+impl RiceAdapter {
+    const RESET_COUNT: u64 = 64;
+
+    fn new() -> Self {
+        Self { sum: 4, count: 1 }
+    }
+
+    fn codebook(&self) -> Rice<u32> {
+        let mut k = 0;
+        while k < 31 && self.count << k < self.sum {
+            k += 1;
+        }
+        Rice::new(k)
+    }
+
+    fn update(&mut self, residual: u32) {
+        self.sum += residual as u64;
+        self.count += 1;
+        if self.count == Self::RESET_COUNT {
+            self.sum >>= 1;
+            self.count >>= 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::{RngCore, SeedableRng};
+    use rand_xoshiro::Xoshiro256StarStar;
+
+    #[test]
+    fn zigzag_roundtrip() {
+        for &x in &[0, 1, -1, 2, -2, 1000, -1000, i32::MAX, i32::MIN] {
+            assert_eq!(unzigzag(zigzag(x)), x);
+        }
+        assert_eq!(
+            [0, -1, 1, -2, 2]
+                .iter()
+                .map(|&x| zigzag(x))
+                .collect::<Vec<_>>(),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(zigzag(i32::MAX), u32::MAX - 1);
+        assert_eq!(zigzag(i32::MIN), u32::MAX);
+    }
+
+    #[test]
+    fn edge_cases() {
+        let cases: &[&[i32]] = &[
+            &[],
+            &[0],
+            &[-17],
+            &[i32::MIN],
+            &[5, 5],
+            &[i32::MAX, i32::MIN, i32::MAX, 0, i32::MIN, i32::MIN],
+            &[i32::MIN, i32::MIN + 1, i32::MIN + 2, i32::MAX],
+        ];
+
+        for &samples in cases {
+            for &predictor in &[Predictor::Delta, Predictor::SecondOrderDelta] {
+                let compressed = encode(samples, predictor);
+                assert_eq!(decode(&compressed).unwrap(), samples);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn smooth_signal() {
+        let mut rng = Xoshiro256StarStar::seed_from_u64(123);
+        let mut velocity = 0i32;
+        let mut value = 100_000i32;
+        let samples = (0..10_000)
+            .map(|_| {
+                velocity += (rng.next_u32() % 5) as i32 - 2;
+                value = value.wrapping_add(velocity);
+                value
+            })
+            .collect::<Vec<_>>();
+
+        let delta = encode(&samples, Predictor::Delta);
+        let second_order = encode(&samples, Predictor::SecondOrderDelta);
+        assert_eq!(decode(&delta).unwrap(), samples);
+        assert_eq!(decode(&second_order).unwrap(), samples);
+
+        // The second differences are uniform over `-2..=2`, i.e., they have an entropy of
+        // `log2(5) ≈ 2.32` bits per sample, and the adaptive Rice code should get close.
+        assert!(second_order.len() * 32 < samples.len() * 3);
+        assert!(second_order.len() < delta.len());
+    }
+
+    #[test]
+    fn invalid_data() {
+        assert_eq!(decode(&[]), Err(DecodingError::InvalidHeader));
+
+        // Header for 3 samples but predictor order 3.
+        let mut encoder = DefaultQueueEncoder::new();
+        encoder.encode_symbol(3u64, ExpGolomb::new()).unwrap();
+        encoder.encode_symbol(3u32, ExpGolomb::new()).unwrap();
+        let compressed = encoder.into_compressed().unwrap_infallible();
+        assert_eq!(decode(&compressed), Err(DecodingError::InvalidHeader));
+
+        let compressed = encode(&[1, 2, 3, 4, 1000, -1000], Predictor::Delta);
+        assert_eq!(
+            decode(&compressed[..compressed.len() - 1]),
+            Err(DecodingError::OutOfCompressedData)
+        );
+    }
+}
@@ -0,0 +1,69 @@
+//! Tests that code written against the pre-0.2 module layout still compiles and produces
+//! the same compressed data as the current API.
+
+#![warn(rust_2018_idioms)]
+#![allow(deprecated)]
+
+use constriction::{
+    distributions::{Categorical, LeakyQuantizer},
+    stack::Stack,
+    stream::{
+        model::{DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer},
+        stack::DefaultAnsCoder,
+        Decode,
+    },
+};
+use probability::distribution::Gaussian;
+
+#[test]
+fn old_paths_match_new_api() {
+    let probabilities = [0.1, 0.2, 0.3, 0.15, 0.25];
+    let categorical_symbols = [2, 4, 0, 1, 1, 3, 2];
+    let gaussian_symbols = [-12, 5, 0, 33, -7];
+
+    let old_categorical =
+        Categorical::<u32, 24>::from_floating_point_probabilities(&probabilities).unwrap();
+    let old_quantizer = LeakyQuantizer::<f64, i32, u32, 24>::new(-100..=100);
+    let mut old_coder = Stack::<u32, u64>::new();
+    old_coder
+        .encode_iid_symbols_reverse(categorical_symbols, &old_categorical)
+        .unwrap();
+    old_coder
+        .encode_iid_symbols_reverse(
+            gaussian_symbols,
+            old_quantizer.quantize(Gaussian::new(0.0, 10.0)),
+        )
+        .unwrap();
+
+    let new_categorical =
+        DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&probabilities)
+            .unwrap();
+    let new_quantizer = DefaultLeakyQuantizer::new(-100..=100);
+    let mut new_coder = DefaultAnsCoder::new();
+    new_coder
+        .encode_iid_symbols_reverse(categorical_symbols, &new_categorical)
+        .unwrap();
+    new_coder
+        .encode_iid_symbols_reverse(
+            gaussian_symbols,
+            new_quantizer.quantize(Gaussian::new(0.0, 10.0)),
+        )
+        .unwrap();
+
+    assert_eq!(
+        *old_coder.get_compressed().unwrap(),
+        *new_coder.get_compressed().unwrap()
+    );
+
+    let decoded = old_coder
+        .decode_iid_symbols(5, old_quantizer.quantize(Gaussian::new(0.0, 10.0)))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded, gaussian_symbols);
+    let decoded = old_coder
+        .decode_iid_symbols(7, &old_categorical)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded, categorical_symbols);
+    assert!(old_coder.is_empty());
+}
@@ -0,0 +1,234 @@
+//! Golden tests that pin down the exact compressed output of the stream codes.
+//!
+//! The test vectors live in `tests/golden/stream.txt` and are shared with the Python test
+//! suite (`tests/python/test_golden.py`), which checks that the Python bindings produce
+//! byte-identical output for the same models and symbols. See the header of the fixture
+//! file for its format.
+
+use std::{fmt::Debug, fs, path::PathBuf};
+
+use constriction::stream::{
+    model::{
+        binomial::DefaultBinomial,
+        geometric::{DefaultGeometric, DefaultTwoSidedGeometric},
+        mixture::{GaussianMixture, LogisticMixture},
+        poisson::DefaultPoisson,
+        DecoderModel, DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer,
+        DefaultUniformRangeModel, EncoderModel, UniformModel,
+    },
+    queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+    stack::DefaultAnsCoder,
+    Decode, Encode,
+};
+use probability::distribution::{Binomial, Gaussian, Laplace};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Coder {
+    Queue,
+    Stack,
+}
+
+/// Encodes `symbols` with `coder`, checks that they decode back correctly, and returns
+/// the compressed words.
+fn roundtrip<M>(coder: Coder, model: M, symbols: &[M::Symbol]) -> Vec<u32>
+where
+    M: EncoderModel<24, Probability = u32> + DecoderModel<24> + Copy,
+    M::Symbol: Copy + PartialEq + Debug,
+{
+    match coder {
+        Coder::Queue => {
+            let mut encoder = DefaultRangeEncoder::new();
+            encoder
+                .encode_iid_symbols(symbols.iter().copied(), model)
+                .unwrap();
+            let compressed = encoder.into_compressed().unwrap();
+
+            let mut decoder = DefaultRangeDecoder::from_compressed(compressed.clone()).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.maybe_exhausted());
+
+            compressed
+        }
+        Coder::Stack => {
+            let mut coder = DefaultAnsCoder::new();
+            coder
+                .encode_iid_symbols_reverse(symbols.iter().copied(), model)
+                .unwrap();
+            let compressed = coder.into_compressed().unwrap();
+
+            let mut decoder = DefaultAnsCoder::from_compressed(compressed.clone()).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(symbols.len(), model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+            assert!(decoder.is_empty());
+
+            compressed
+        }
+    }
+}
+
+fn parse_all<T: core::str::FromStr>(s: &str) -> Vec<T>
+where
+    T::Err: Debug,
+{
+    s.split_whitespace().map(|x| x.parse().unwrap()).collect()
+}
+
+fn compress(coder: &str, model: &str, symbols: &str) -> Vec<u32> {
+    let coder = match coder {
+        "queue" => Coder::Queue,
+        "stack" => Coder::Stack,
+        other => panic!("unknown coder `{}`", other),
+    };
+    let (name, params) = model.split_once(' ').unwrap_or((model, ""));
+    let params = parse_all::<f64>(params);
+
+    match name {
+        "categorical" => {
+            let model =
+                DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(
+                    &params,
+                )
+                .unwrap();
+            roundtrip(coder, &model, &parse_all::<usize>(symbols))
+        }
+        "uniform" => {
+            let model = UniformModel::<u32, 24>::new(params[0] as u32);
+            roundtrip(coder, model, &parse_all::<u32>(symbols))
+        }
+        "uniform_range" => {
+            let model = DefaultUniformRangeModel::new(params[0] as i32..=params[1] as i32).unwrap();
+            roundtrip(coder, model, &parse_all::<i32>(symbols))
+        }
+        "gaussian" => {
+            let quantizer = DefaultLeakyQuantizer::new(params[0] as i32..=params[1] as i32);
+            let model = quantizer.quantize(Gaussian::new(params[2], params[3]));
+            roundtrip(coder, model, &parse_all::<i32>(symbols))
+        }
+        "laplace" => {
+            let quantizer = DefaultLeakyQuantizer::new(params[0] as i32..=params[1] as i32);
+            let model = quantizer.quantize(Laplace::new(params[2], params[3]));
+            roundtrip(coder, model, &parse_all::<i32>(symbols))
+        }
+        "gaussian_mixture" | "logistic_mixture" => {
+            // The remaining parameters are `(weight, mean, scale)` triples.
+            let quantizer = DefaultLeakyQuantizer::new(params[0] as i32..=params[1] as i32);
+            let (mut weights, mut means, mut scales) = (Vec::new(), Vec::new(), Vec::new());
+            for component in params[2..].chunks_exact(3) {
+                weights.push(component[0]);
+                means.push(component[1]);
+                scales.push(component[2]);
+            }
+            let symbols = parse_all::<i32>(symbols);
+            if name == "gaussian_mixture" {
+                let mixture = GaussianMixture::new(&weights, &means, &scales).unwrap();
+                roundtrip(coder, &quantizer.quantize(mixture), &symbols)
+            } else {
+                let mixture = LogisticMixture::new(&weights, &means, &scales).unwrap();
+                roundtrip(coder, &quantizer.quantize(mixture), &symbols)
+            }
+        }
+        "binomial" => {
+            let n = params[0] as i32;
+            let quantizer = DefaultLeakyQuantizer::new(0..=n);
+            let model = quantizer.quantize(Binomial::new(n as usize, params[1]));
+            roundtrip(coder, model, &parse_all::<i32>(symbols))
+        }
+        "exact_binomial" => {
+            let model = DefaultBinomial::new(params[0] as usize, params[1]).unwrap();
+            roundtrip(coder, &model, &parse_all::<usize>(symbols))
+        }
+        "poisson" => {
+            let model = DefaultPoisson::new(params[1], params[0] as usize).unwrap();
+            roundtrip(coder, &model, &parse_all::<usize>(symbols))
+        }
+        "geometric" => {
+            let model = DefaultGeometric::new(params[1], params[0] as usize).unwrap();
+            roundtrip(coder, &model, &parse_all::<usize>(symbols))
+        }
+        "two_sided_geometric" => {
+            let support = params[0] as i32..=params[1] as i32;
+            let model = DefaultTwoSidedGeometric::new(support, params[2], params[3]).unwrap();
+            roundtrip(coder, &model, &parse_all::<i32>(symbols))
+        }
+        "bernoulli" => {
+            let p = params[0];
+            let model =
+                DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+                    1.0 - p,
+                    p,
+                ])
+                .unwrap();
+            roundtrip(coder, &model, &parse_all::<usize>(symbols))
+        }
+        other => panic!("unknown model `{}`", other),
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn golden_stream() {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/golden/stream.txt");
+    let contents = fs::read_to_string(&path).unwrap();
+    let bless = std::env::var_os("CONSTRICTION_BLESS_GOLDEN").is_some();
+
+    let mut blessed = String::new();
+    let mut mismatches = Vec::new();
+    for (line_number, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() || line.starts_with('#') {
+            blessed.push_str(line);
+            blessed.push('\n');
+            continue;
+        }
+
+        let fields = line.split(';').map(str::trim).collect::<Vec<_>>();
+        assert_eq!(
+            fields.len(),
+            4,
+            "malformed line {}: {}",
+            line_number + 1,
+            line
+        );
+        let (coder, model, symbols) = (fields[0], fields[1], fields[2]);
+        let expected = parse_all::<u32>(fields[3]);
+
+        let compressed = compress(coder, model, symbols);
+        if compressed != expected {
+            mismatches.push(format!(
+                "line {}: `{}` with `{}`: expected {:?}, got {:?}",
+                line_number + 1,
+                coder,
+                model,
+                expected,
+                compressed
+            ));
+        }
+
+        let words = compressed
+            .iter()
+            .map(u32::to_string)
+            .collect::<Vec<_>>()
+            .join(" ");
+        blessed.push_str(&format!(
+            "{} ; {} ; {} ; {}\n",
+            coder, model, symbols, words
+        ));
+    }
+
+    if bless {
+        fs::write(&path, blessed).unwrap();
+    } else {
+        assert!(
+            mismatches.is_empty(),
+            "compressed output differs from golden file (rerun with \
+            `CONSTRICTION_BLESS_GOLDEN=1` if the change is intentional):\n{}",
+            mismatches.join("\n")
+        );
+    }
+}
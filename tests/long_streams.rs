@@ -0,0 +1,292 @@
+//! Tests positions and word counts of very long streams of compressed data.
+//!
+//! We can't allocate several GiB of compressed data in a test. Instead, the backends below
+//! pretend that the stream starts with `offset` words that are never actually read, so
+//! that positions and word counts reach arbitrary values while only a few words are stored
+//! in memory.
+
+#![warn(rust_2018_idioms)]
+
+use std::convert::Infallible;
+
+use constriction::{
+    backends::{BoundedReadWords, ReadWords, WriteWords},
+    stream::{
+        model::DefaultContiguousCategoricalEntropyModel,
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+        Decode, Encode,
+    },
+    CoderError, Pos, PosSeek, Queue, Seek, Stack,
+};
+
+#[derive(Debug, PartialEq, Eq)]
+struct Full;
+
+/// A stack whose bottom consists of `offset` virtual words.
+#[derive(Debug)]
+struct OffsetStack {
+    offset: usize,
+    words: Vec<u32>,
+}
+
+impl OffsetStack {
+    fn new(offset: usize) -> Self {
+        Self {
+            offset,
+            words: Vec::new(),
+        }
+    }
+}
+
+impl WriteWords<u32> for OffsetStack {
+    type WriteError = Full;
+
+    fn write(&mut self, word: u32) -> Result<(), Full> {
+        if self.pos() == usize::MAX {
+            return Err(Full);
+        }
+        self.words.push(word);
+        Ok(())
+    }
+}
+
+impl ReadWords<u32, Stack> for OffsetStack {
+    type ReadError = Infallible;
+
+    fn read(&mut self) -> Result<Option<u32>, Infallible> {
+        Ok(self.words.pop())
+    }
+}
+
+impl BoundedReadWords<u32, Stack> for OffsetStack {
+    fn remaining(&self) -> usize {
+        self.pos()
+    }
+}
+
+impl PosSeek for OffsetStack {
+    type Position = usize;
+}
+
+impl Pos for OffsetStack {
+    fn pos(&self) -> usize {
+        self.offset + self.words.len()
+    }
+}
+
+impl Seek for OffsetStack {
+    fn seek(&mut self, pos: usize) -> Result<(), ()> {
+        if pos < self.offset || pos > self.pos() {
+            return Err(());
+        }
+        self.words.truncate(pos - self.offset);
+        Ok(())
+    }
+}
+
+/// A cursor with queue semantics into a buffer that starts with `offset` virtual words.
+#[derive(Debug)]
+struct OffsetCursor {
+    offset: usize,
+    words: Vec<u32>,
+    pos: usize,
+}
+
+impl OffsetCursor {
+    fn new(offset: usize) -> Self {
+        Self {
+            offset,
+            words: Vec::new(),
+            pos: offset,
+        }
+    }
+}
+
+impl WriteWords<u32> for OffsetCursor {
+    type WriteError = Full;
+
+    fn write(&mut self, word: u32) -> Result<(), Full> {
+        if self.pos == usize::MAX {
+            return Err(Full);
+        }
+        let index = self.pos - self.offset;
+        if index == self.words.len() {
+            self.words.push(word);
+        } else {
+            self.words[index] = word;
+        }
+        self.pos += 1;
+        Ok(())
+    }
+}
+
+impl ReadWords<u32, Queue> for OffsetCursor {
+    type ReadError = Infallible;
+
+    fn read(&mut self) -> Result<Option<u32>, Infallible> {
+        let word = self.words.get(self.pos - self.offset).copied();
+        if word.is_some() {
+            self.pos += 1;
+        }
+        Ok(word)
+    }
+}
+
+impl BoundedReadWords<u32, Queue> for OffsetCursor {
+    fn remaining(&self) -> usize {
+        self.offset + self.words.len() - self.pos
+    }
+}
+
+impl PosSeek for OffsetCursor {
+    type Position = usize;
+}
+
+impl Pos for OffsetCursor {
+    fn pos(&self) -> usize {
+        self.pos
+    }
+}
+
+impl Seek for OffsetCursor {
+    fn seek(&mut self, pos: usize) -> Result<(), ()> {
+        if pos < self.offset || pos > self.offset + self.words.len() {
+            return Err(());
+        }
+        self.pos = pos;
+        Ok(())
+    }
+}
+
+fn model() -> DefaultContiguousCategoricalEntropyModel {
+    DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+        0.1, 0.2, 0.3, 0.15, 0.25,
+    ])
+    .unwrap()
+}
+
+fn message(amt: usize, seed: usize) -> Vec<usize> {
+    (0..amt)
+        .map(|i| (i.wrapping_mul(0x6979_E2F3) ^ seed) % 5)
+        .collect()
+}
+
+/// Encodes two messages at the given `offset`, seeks back to the boundary between them,
+/// and checks that decoding from there works.
+fn ans_seek_at_offset(offset: usize) {
+    let model = model();
+    let part1 = message(100, 1);
+    let part2 = message(100, 2);
+
+    let backend = OffsetStack::new(offset);
+    let mut coder = DefaultAnsCoder::from_compressed(backend).unwrap();
+    coder.encode_iid_symbols_reverse(&part2, &model).unwrap();
+    let checkpoint = coder.pos();
+    coder.encode_iid_symbols_reverse(&part1, &model).unwrap();
+
+    assert!(checkpoint.0 > offset);
+    assert_eq!(coder.num_words(), coder.bulk().pos() + 2);
+
+    let decoded1 = coder
+        .decode_iid_symbols(part1.len(), &model)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded1, part1);
+    assert_eq!(coder.pos(), checkpoint);
+
+    coder.seek(checkpoint).unwrap();
+    let decoded2 = coder
+        .decode_iid_symbols(part2.len(), &model)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded2, part2);
+    assert_eq!(coder.bulk().pos(), offset);
+
+    assert!(coder.seek((offset - 1, checkpoint.1)).is_err());
+}
+
+fn range_seek_at_offset(offset: usize) {
+    let model = model();
+    let part1 = message(100, 3);
+    let part2 = message(100, 4);
+
+    let mut encoder = DefaultRangeEncoder::with_backend(OffsetCursor::new(offset));
+    encoder.encode_iid_symbols(&part1, &model).unwrap();
+    let checkpoint = encoder.pos();
+    encoder.encode_iid_symbols(&part2, &model).unwrap();
+    assert!(checkpoint.0 > offset);
+
+    let mut backend = encoder.into_compressed().unwrap();
+    assert!(backend.pos() > checkpoint.0);
+    backend.seek(offset).unwrap();
+
+    let mut decoder = DefaultRangeDecoder::with_backend(backend).unwrap();
+    let decoded1 = decoder
+        .decode_iid_symbols(part1.len(), &model)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded1, part1);
+
+    decoder.seek(checkpoint).unwrap();
+    let decoded2 = decoder
+        .decode_iid_symbols(part2.len(), &model)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(decoded2, part2);
+}
+
+#[test]
+#[cfg(target_pointer_width = "64")]
+fn beyond_4_gib() {
+    // More than 2^32 words, i.e., more than 16 GiB of compressed data.
+    let offset = (1usize << 32) + 12345;
+    ans_seek_at_offset(offset);
+    range_seek_at_offset(offset);
+
+    let model = model();
+    let mut coder = DefaultAnsCoder::from_compressed(OffsetStack::new(offset)).unwrap();
+    coder
+        .encode_iid_symbols_reverse(message(10, 5), &model)
+        .unwrap();
+    assert_eq!(coder.num_bits(), 32 * coder.num_words());
+    assert!(coder.num_bits() > 32 << 32);
+    assert!(coder.num_valid_bits() <= coder.num_bits());
+    assert!(coder.num_valid_bits() > coder.num_bits() - 32);
+}
+
+#[test]
+fn close_to_usize_max() {
+    // Positions right below `usize::MAX` exercise the same arithmetic that very long
+    // streams run into on 32-bit platforms (where this is a bit less than 16 GiB).
+    let offset = usize::MAX - 1000;
+    ans_seek_at_offset(offset);
+    range_seek_at_offset(offset);
+}
+
+#[test]
+fn overflowing_usize_fails_cleanly() {
+    let model = model();
+    let offset = usize::MAX - 2;
+
+    // Encoding past the end of the addressable space returns an error.
+    let mut coder = DefaultAnsCoder::from_compressed(OffsetStack::new(offset)).unwrap();
+    let result = coder.encode_iid_symbols_reverse(message(1000, 6), &model);
+    assert_eq!(result, Err(CoderError::Backend(Full)));
+    assert_eq!(coder.bulk().pos(), usize::MAX);
+
+    let mut encoder = DefaultRangeEncoder::with_backend(OffsetCursor::new(offset));
+    let result = encoder.encode_iid_symbols(message(1000, 7), &model);
+    assert_eq!(result, Err(CoderError::Backend(Full)));
+}
+
+#[test]
+#[should_panic(expected = "overflows addressable space")]
+fn num_bits_overflow_panics() {
+    let model = model();
+    let mut coder = DefaultAnsCoder::from_compressed(OffsetStack::new(usize::MAX / 16)).unwrap();
+    coder
+        .encode_iid_symbols_reverse(message(10, 8), &model)
+        .unwrap();
+    let _ = coder.num_bits();
+}
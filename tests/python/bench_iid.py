@@ -0,0 +1,48 @@
+"""Measures the per-symbol cost of entropy coding with the python bindings.
+
+Compares encoding a batch of i.i.d. symbols in a single call to encoding the same symbols
+one at a time. The difference is the per-call overhead that gets amortized over a batch.
+
+Run with `python tests/python/bench_iid.py` after building the python bindings (e.g., with
+`maturin develop --release --features pybindings`).
+"""
+
+import timeit
+
+import constriction
+import numpy as np
+import scipy.stats
+
+
+def bench(name, model, symbols, repeat=5):
+    def batch():
+        encoder = constriction.stream.queue.RangeEncoder()
+        encoder.encode(symbols, model)
+
+    def one_by_one():
+        encoder = constriction.stream.queue.RangeEncoder()
+        for symbol in symbols:
+            encoder.encode(int(symbol), model)
+
+    batch_time = min(timeit.repeat(batch, number=1, repeat=repeat))
+    single_time = min(timeit.repeat(one_by_one, number=1, repeat=repeat))
+    print(f"{name:>20}: {1e9 * batch_time / len(symbols):10.1f} ns/symbol (batch), "
+          f"{1e9 * single_time / len(symbols):10.1f} ns/symbol (one by one)")
+
+
+def main():
+    rng = np.random.default_rng(123)
+    symbols = np.round(rng.normal(3.2, 10.0, size=10_000)).astype(np.int32)
+    symbols = np.clip(symbols, -100, 100)
+
+    bench("QuantizedGaussian",
+          constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0),
+          symbols)
+    bench("ScipyModel",
+          constriction.stream.model.ScipyModel(
+              scipy.stats.norm(3.2, 10.0), -100, 100),
+          symbols[:1000])
+
+
+if __name__ == "__main__":
+    main()
@@ -348,3 +348,1505 @@ def test_huffman_queue():
     reconstructed = [decoder.decode_symbol(
         decoder_codebook) for _ in range(len(symbols))]
     assert reconstructed == symbols
+
+
+def test_failed_batch_leaves_coder_unchanged():
+    import pytest
+
+    probabilities = np.array([0.2, 0.4, 0.1, 0.3], dtype=np.float64)
+    model = constriction.stream.model.Categorical(probabilities)
+    good = np.array([1, 2, 0, 3], dtype=np.int32)
+    bad = np.array([2, 1, 7, 0], dtype=np.int32)  # (`7` is not in the alphabet)
+
+    for coder, encode in [
+        (constriction.stream.queue.RangeEncoder(), 'encode'),
+        (constriction.stream.stack.AnsCoder(), 'encode_reverse'),
+    ]:
+        getattr(coder, encode)(good, model)
+        before = coder.get_compressed().copy()
+        try:
+            getattr(coder, encode)(bad, model)
+            assert False, "expected an exception"
+        except KeyError:
+            pass
+        assert np.all(coder.get_compressed() == before)
+
+    ans = constriction.stream.stack.AnsCoder()
+    ans.encode_reverse(good, model)
+    before = ans.get_compressed().copy()
+
+    def failing_ppf(xi):
+        raise RuntimeError("oops")
+
+    failing_model = constriction.stream.model.CustomModel(
+        lambda x: x / 4, failing_ppf, 0, 3)
+    try:
+        ans.decode(failing_model, 4)
+        assert False, "expected an exception"
+    except RuntimeError:
+        pass
+    assert np.all(ans.get_compressed() == before)
+    assert np.all(ans.decode(model, 4) == good)
+
+    # Exceptions raised by a python callback propagate unchanged.
+    def failing_cdf(x, threshold):
+        if x > threshold:
+            raise ValueError("cdf failed")
+        return (x + 0.5) / 4
+
+    failing_family = constriction.stream.model.CustomModel(
+        failing_cdf, lambda xi, threshold: 4 * xi - 0.5, 0, 3)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(good, model)
+    before = encoder.get_compressed().copy()
+    with pytest.raises(ValueError, match="cdf failed"):
+        encoder.encode(good, failing_family, np.array(
+            [10.0, 10.0, -10.0, 10.0]))
+    assert np.all(encoder.get_compressed() == before)
+
+    # A `ChainCoder` moves words between two buffers; both get restored.
+    rng = np.random.RandomState(123)
+    chain = constriction.stream.chain.ChainCoder(
+        rng.randint(2**32, size=100, dtype=np.uint32), seal=True)
+    chain.decode(model, 4)
+    before = [part.copy() for part in chain.get_remainders()]
+    with pytest.raises(ValueError, match="cdf failed"):
+        chain.decode(failing_family, np.array([10.0, -10.0]))
+    assert all(np.all(a == b) for a, b in zip(chain.get_remainders(), before))
+    with pytest.raises(ValueError, match="cdf failed"):
+        chain.encode_reverse(good[:2], failing_family, np.array([-10.0, 10.0]))
+    assert all(np.all(a == b) for a, b in zip(chain.get_remainders(), before))
+
+    # So do invalid per-symbol parameters of models that assert on their parameters.
+    before = ans.get_compressed().copy()
+    with pytest.raises(ValueError, match="index 1"):
+        ans.decode(constriction.stream.model.Uniform(),
+                   np.array([4, 1, 4], dtype=np.int32))
+    with pytest.raises(ValueError, match="index 2"):
+        ans.decode(constriction.stream.model.Binomial(p=0.5),
+                   np.array([3, 3, 0], dtype=np.int32))
+    assert np.all(ans.get_compressed() == before)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Uniform(1)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Binomial(0, 0.5)
+
+
+def test_iter_decode():
+    probabilities = np.array([0.1, 0.6, 0.3], dtype=np.float64)
+    model = constriction.stream.model.Categorical(probabilities)
+    symbols = np.array([0, 2, 1, 2, 0, 2, 0, 2, 1], dtype=np.int32)
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    decoder = encoder.get_decoder()
+    iterator = decoder.iter_decode(model, 5)
+    assert list(iterator) == list(symbols[:5])
+    assert list(iterator) == []
+    assert np.all(decoder.decode(model, 4) == symbols[5:])
+
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model)
+    decoded = []
+    for symbol in coder.iter_decode(model, len(symbols)):
+        decoded.append(symbol)
+    assert decoded == list(symbols)
+    assert coder.is_empty()
+
+
+def test_timeseries():
+    rng = np.random.RandomState(123)
+    velocity = np.cumsum(rng.randint(-2, 3, size=1000))
+    samples = (100_000 + np.cumsum(velocity)).astype(np.int32)
+
+    for order in [1, 2]:
+        compressed = constriction.timeseries.encode(samples, order)
+        assert compressed.dtype == np.uint32
+        assert np.all(constriction.timeseries.decode(compressed) == samples)
+
+    assert len(constriction.timeseries.encode(samples, 2)) < len(
+        constriction.timeseries.encode(samples, 1))
+
+    extreme = np.array([2**31 - 1, -2**31, 0, -2**31, 5], dtype=np.int32)
+    compressed = constriction.timeseries.encode(extreme, 2)
+    assert np.all(constriction.timeseries.decode(compressed) == extreme)
+
+    empty = np.array([], dtype=np.int32)
+    assert len(constriction.timeseries.decode(
+        constriction.timeseries.encode(empty))) == 0
+
+    try:
+        constriction.timeseries.encode(samples, 3)
+        assert False, "expected an exception"
+    except ValueError:
+        pass
+
+
+def test_info():
+    info = constriction.info()
+    assert isinstance(info["version"], str)
+    assert "pybindings" in info["features"]
+
+    expected = {"word_size": 32, "state_size": 64, "precision": 24}
+    assert info["coders"]["AnsCoder"] == expected
+    assert constriction.stream.stack.AnsCoder().config == expected
+    assert constriction.stream.queue.RangeEncoder().config == expected
+    compressed = np.array([1, 2], dtype=np.uint32)
+    assert constriction.stream.queue.RangeDecoder(compressed).config == expected
+    assert constriction.stream.chain.ChainCoder(compressed).config == expected
+
+
+def test_easy_bytes():
+    rng = np.random.RandomState(727)
+    symbols = rng.randint(-100, 101, size=1000).astype(np.int32)
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, -5.3, 20.0)
+
+    data = constriction.stream.easy.encode_to_bytes(symbols, model)
+    assert isinstance(data, bytes)
+    decoded = constriction.stream.easy.decode_from_bytes(
+        data, model, len(symbols))
+    assert np.all(decoded == symbols)
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    assert data == encoder.get_compressed().astype('<u4').tobytes()
+
+    # Model families with individual parameters for each symbol.
+    family = constriction.stream.model.QuantizedGaussian(-100, 100)
+    means = rng.uniform(-50, 50, size=len(symbols))
+    stds = rng.uniform(5, 30, size=len(symbols))
+    data = constriction.stream.easy.encode_to_bytes(
+        symbols, family, means, stds)
+    decoded = constriction.stream.easy.decode_from_bytes(
+        data, family, means, stds)
+    assert np.all(decoded == symbols)
+
+    try:
+        constriction.stream.easy.decode_from_bytes(
+            data[:-1], family, means, stds)
+        assert False, "expected an exception"
+    except ValueError:
+        pass
+
+
+def test_encode_python_lists():
+    probabilities = np.array([0.1, 0.6, 0.3], dtype=np.float64)
+    model = constriction.stream.model.Categorical(probabilities)
+    symbols = [0, 2, 1, 2, 0, 2, 0, 2, 1]
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    reference = constriction.stream.queue.RangeEncoder()
+    reference.encode(np.array(symbols, dtype=np.int32), model)
+    assert np.all(encoder.get_compressed() == reference.get_compressed())
+
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(tuple(symbols), model)
+    assert np.all(coder.decode(model, len(symbols)) == symbols)
+
+    model_family = constriction.stream.model.QuantizedGaussian(-100, 100)
+    means = np.array([10.3, -4.7, 20.5], dtype=np.float64)
+    stds = np.array([5.2, 24.2, 3.1], dtype=np.float64)
+    coder.encode_reverse([12, -13, 25], model_family, means, stds)
+    assert np.all(coder.decode(model_family, means, stds) == [12, -13, 25])
+
+    try:
+        coder.encode_reverse([0, 2**31], model)
+        assert False, "expected an exception"
+    except OverflowError:
+        pass
+    assert coder.is_empty()
+
+
+def test_scipy_model_validation():
+    import constriction
+    import numpy as np
+    import pytest
+    import scipy.stats
+
+    # Valid models pass validation, both as concrete models and as model families.
+    constriction.stream.model.ScipyModel(scipy.stats.norm(1.2, 4.9), -100, 100)
+    model_family = constriction.stream.model.ScipyModel(
+        scipy.stats.norm, -100, 100)
+    model_family.validate(1.2, 4.9)
+    with pytest.raises(ValueError, match="between 0.0 and 1.0"):
+        model_family.validate(1.2, -4.9)
+
+    class BrokenModel:
+        """A model whose CDF decreases on x in (10, 20)."""
+
+        def cdf(self, x):
+            x = np.asarray(x, dtype=np.float64)
+            y = scipy.stats.norm.cdf(x, 0.0, 30.0)
+            return np.where((x > 10) & (x < 20), y - 0.1, y)
+
+        def ppf(self, xi):
+            return scipy.stats.norm.ppf(xi, 0.0, 30.0)
+
+    with pytest.raises(ValueError) as excinfo:
+        constriction.stream.model.ScipyModel(BrokenModel(), -100, 100)
+    message = str(excinfo.value)
+    assert "decreases between x = 9.5 and x = 10.5" in message
+    assert "decreases between x = 19.5 and x = 20.5" not in message
+
+    # Validation can be turned off.
+    constriction.stream.model.ScipyModel(
+        BrokenModel(), -100, 100, validate=False)
+
+
+
+def test_quantized_model_describe():
+    import pytest
+
+    # A narrow distribution uses only a small part of the support.
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 2.0)
+    report = model.describe()
+    assert report["support"] == (-100, 100)
+    start, end = report["effective_support"]
+    assert -20 < start and end < 20
+    assert report["min_probability"] == 2**-24
+    assert report["tail_mass"] == 0.0
+
+    # A wide distribution leaks a lot of probability mass out of the support.
+    model_family = constriction.stream.model.QuantizedLaplace(-100, 100)
+    report = model_family.describe(50.0, 100.0)
+    assert report["effective_support"] == (-100, 100)
+    expected_tail_mass = scipy.stats.laplace.cdf(-100.5, 50.0, 100.0) + \
+        scipy.stats.laplace.sf(100.5, 50.0, 100.0)
+    assert abs(report["tail_mass"] - expected_tail_mass) < 1e-4
+
+    # Parameters that were delayed to encoding or decoding have to be provided.
+    model_family = constriction.stream.model.QuantizedGaussian(
+        -100, 100, mean=3.0)
+    with pytest.raises(AttributeError):
+        model_family.describe()
+    assert model_family.describe(std=10.0)["tail_mass"] < 1e-10
+
+
+def test_seek_close_to_end():
+    import pytest
+
+    model = constriction.stream.model.QuantizedGaussian(-20, 20, 0.0, 5.0)
+    for length in range(20):
+        message = (np.arange(length, dtype=np.int32) * 7) % 41 - 20
+
+        encoder = constriction.stream.queue.RangeEncoder()
+        checkpoints = [encoder.pos()]
+        for symbol in message:
+            encoder.encode(np.array([symbol], dtype=np.int32), model)
+            checkpoints.append(encoder.pos())
+        compressed = encoder.get_compressed()
+
+        decoder = constriction.stream.queue.RangeDecoder(compressed)
+        for i, (position, state) in reversed(list(enumerate(checkpoints))):
+            decoder.seek(position, state)
+            assert np.all(decoder.decode(model, length - i) == message[i:])
+            assert decoder.maybe_exhausted()
+        position, state = checkpoints[-1]
+        decoder.seek(len(compressed), state)
+        with pytest.raises(AttributeError):
+            decoder.seek(len(compressed) + 1, state)
+
+        coder = constriction.stream.stack.AnsCoder()
+        coder.encode_reverse(message, model)
+        end, state = coder.pos()
+        with pytest.raises(AttributeError):
+            coder.seek(end + 1, state)
+        assert np.all(coder.decode(model, length) == message)
+        assert coder.is_empty()
+
+
+def test_audio_models():
+    rng = np.random.default_rng(745)
+    noise = rng.laplace(0.0, 2.0, 2000)
+    noise[1000:] *= 100.0
+    x = 0
+    residuals = []
+    for n in noise:
+        prediction = round(0.9 * x)
+        x = int(np.clip(round(0.9 * x + n), -32768, 32767))
+        residuals.append(x - prediction)
+    residuals = np.array(residuals, dtype=np.int32)
+
+    adaptive = constriction.stream.model.AdaptiveLaplace(-65536, 65536, 1.0, 0.05)
+    scales = adaptive.scales(residuals)
+    model_family = constriction.stream.model.QuantizedLaplace(-65536, 65536)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(
+        residuals, model_family, np.zeros(len(residuals)), scales)
+
+    adaptive = constriction.stream.model.AdaptiveLaplace(-65536, 65536, 1.0, 0.05)
+    for residual in residuals:
+        assert coder.decode(adaptive.model()) == residual
+        adaptive.update(residual)
+    assert coder.is_empty()
+    expected_scale = scales[-1] + 0.05 * (abs(residuals[-1]) - scales[-1])
+    assert abs(adaptive.scale() - expected_scale) < 1e-9
+
+    model = constriction.stream.model.QuantizedLongTailLaplace(
+        -65536, 65536, 0.1, 10.0, mean=0.0)
+    scales = np.full(len(residuals), 20.0)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(residuals, model, scales)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model, scales) == residuals)
+
+    # Invalid per-symbol scales raise a `ValueError` instead of panicking.
+    import pytest
+    for bad_scale in [0.0, -1.0, np.nan]:
+        bad_scales = scales.copy()
+        bad_scales[3] = bad_scale
+        with pytest.raises(ValueError):
+            encoder.encode(residuals, model, bad_scales)
+    with pytest.raises(ValueError):
+        constriction.stream.model.QuantizedLongTailLaplace(
+            -65536, 65536, 0.1, 10.0, 0.0, -1.0)
+
+
+def test_huffman_empty_alphabet():
+    import pytest
+
+    with pytest.raises(ValueError):
+        constriction.symbol.huffman.EncoderHuffmanTree(np.array([], dtype=np.float64))
+    with pytest.raises(ValueError):
+        constriction.symbol.huffman.DecoderHuffmanTree(np.array([], dtype=np.float64))
+
+
+def test_archive(tmp_path):
+    import pytest
+
+    path = str(tmp_path / "archive.cnsa")
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 20.0)
+    messages = {f"message-{i}": (np.arange(10 * i, dtype=np.int32) * 7) % 41 - 20
+                for i in range(5)}
+
+    writer = constriction.archive.ArchiveWriter(path)
+    checkpoints = {}
+    for i, (name, message) in enumerate(messages.items()):
+        coder = constriction.stream.stack.AnsCoder()
+        coder.encode_reverse(message[len(message) // 2:], model)
+        checkpoints[name] = coder.pos()
+        coder.encode_reverse(message[:len(message) // 2], model)
+        writer.add(name, coder.get_compressed(), model_id=i,
+                   checkpoint=checkpoints[name])
+    with pytest.raises(ValueError):
+        writer.add("message-0", np.array([1], dtype=np.uint32))
+    assert len(writer) == len(messages)
+    writer.close()
+
+    reader = constriction.archive.ArchiveReader(path)
+    assert len(reader) == len(messages)
+    assert reader.keys() == sorted(messages.keys())
+    assert "message-5" not in reader
+    with pytest.raises(KeyError):
+        reader.read("message-5")
+
+    for i, (name, message) in reversed(list(enumerate(messages.items()))):
+        entry = reader.entry(name)
+        assert entry["model_id"] == i
+        assert entry["checkpoint"] == checkpoints[name]
+        compressed = reader.read(name)
+        assert len(compressed) == entry["len"]
+        assert np.all(constriction.stream.stack.AnsCoder(
+            compressed).decode(model, len(message)) == message)
+
+        coder = constriction.stream.stack.AnsCoder(compressed)
+        coder.seek(*entry["checkpoint"])
+        rest = len(message) - len(message) // 2
+        assert np.all(coder.decode(model, rest) == message[len(message) // 2:])
+
+
+def test_rational_categorical():
+    import pytest
+
+    numerators = np.array([3, 8, 5], dtype=np.uint32)
+    model = constriction.stream.model.Categorical.from_rational(numerators, 16)
+    reference = constriction.stream.model.Categorical(
+        np.array([3, 8, 5], dtype=np.float64))
+
+    symbols = np.array([1, 2, 0, 1, 1, 2, 0], dtype=np.int32)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model)
+    reference_coder = constriction.stream.stack.AnsCoder()
+    reference_coder.encode_reverse(symbols, reference)
+    # Probabilities with a power-of-two denominator are exactly representable.
+    assert np.all(coder.get_compressed() == reference_coder.get_compressed())
+    assert np.all(coder.decode(model, len(symbols)) == symbols)
+
+    for numerators, denominator in [([3, 8, 4], 16), ([3, 8, 5], 15), ([16], 16),
+                                    ([0, 16], 16), ([3, 8, 5], 2**25)]:
+        with pytest.raises(ValueError):
+            constriction.stream.model.Categorical.from_rational(
+                np.array(numerators, dtype=np.uint32), denominator)
+
+
+
+def test_float32_parameters():
+    symbols = np.array([3, -7, 0, 12, -1, 5], dtype=np.int32)
+    means = np.array([2.5, -6.0, 0.3, 10.0, -1.5, 4.0], dtype=np.float32)
+    stds = np.array([1.0, 3.5, 0.7, 4.2, 2.0, 9.9], dtype=np.float32)
+    model_family = constriction.stream.model.QuantizedGaussian(-50, 50)
+
+    coder32 = constriction.stream.stack.AnsCoder()
+    coder32.encode_reverse(symbols, model_family, means, stds)
+    coder64 = constriction.stream.stack.AnsCoder()
+    coder64.encode_reverse(symbols, model_family, means.astype(
+        np.float64), stds.astype(np.float64))
+    assert np.all(coder32.get_compressed() == coder64.get_compressed())
+    # Non-contiguous float32 arrays work too.
+    assert np.all(coder32.decode(
+        model_family, np.repeat(means, 2)[::2], stds) == symbols)
+
+    probabilities = np.array([[0.1, 0.6, 0.3], [0.5, 0.2, 0.3]], dtype=np.float32)
+    categorical_symbols = np.array([1, 0], dtype=np.int32)
+    coder32 = constriction.stream.stack.AnsCoder()
+    coder32.encode_reverse(categorical_symbols,
+                           constriction.stream.model.Categorical(), probabilities)
+    coder64 = constriction.stream.stack.AnsCoder()
+    coder64.encode_reverse(categorical_symbols, constriction.stream.model.Categorical(),
+                           probabilities.astype(np.float64))
+    assert np.all(coder32.get_compressed() == coder64.get_compressed())
+
+    model32 = constriction.stream.model.Categorical(probabilities[0])
+    model64 = constriction.stream.model.Categorical(
+        probabilities[0].astype(np.float64))
+    coder32 = constriction.stream.stack.AnsCoder()
+    coder32.encode_reverse(symbols % 3, model32)
+    coder64 = constriction.stream.stack.AnsCoder()
+    coder64.encode_reverse(symbols % 3, model64)
+    assert np.all(coder32.get_compressed() == coder64.get_compressed())
+
+
+def test_categorical_rounding():
+    import pytest
+
+    probabilities = np.array([0.2, 0.45, 0.35, 1e-9, 0.0], dtype=np.float64)
+    symbols = np.array([1, 2, 0, 3, 1, 4, 2], dtype=np.int32)
+    default_coder = constriction.stream.stack.AnsCoder()
+    default_coder.encode_reverse(
+        symbols, constriction.stream.model.Categorical(probabilities))
+
+    for rounding in ['minimal_cross_entropy', 'floor_with_redistribution',
+                     'round_half_even', 'largest_remainder']:
+        model = constriction.stream.model.Categorical(
+            probabilities, rounding=rounding)
+        coder = constriction.stream.stack.AnsCoder()
+        coder.encode_reverse(symbols, model)
+        if rounding == 'minimal_cross_entropy':
+            assert np.all(coder.get_compressed() ==
+                          default_coder.get_compressed())
+        assert np.all(coder.decode(model, len(symbols)) == symbols)
+
+        # Delaying the probabilities until encoding uses the same rounding.
+        model_family = constriction.stream.model.Categorical(rounding=rounding)
+        family_coder = constriction.stream.stack.AnsCoder()
+        family_probabilities = np.tile(probabilities, (len(symbols), 1))
+        family_coder.encode_reverse(
+            symbols, model_family, family_probabilities)
+        assert np.all(family_coder.get_compressed() == coder.get_compressed())
+        assert np.all(family_coder.decode(
+            model_family, family_probabilities) == symbols)
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.Categorical(probabilities, rounding='nearest')
+
+def test_categorical_from_fixed_point():
+    import pytest
+
+    probabilities = np.array([3 << 20, 1, (1 << 23) - 1, 5 << 20],
+                             dtype=np.uint32)
+    model = constriction.stream.model.Categorical.from_fixed_point(
+        probabilities)
+    reference = constriction.stream.model.Categorical.from_rational(
+        probabilities, 2**24)
+
+    symbols = np.array([1, 2, 0, 3, 3, 2, 0], dtype=np.int32)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model)
+    reference_coder = constriction.stream.stack.AnsCoder()
+    reference_coder.encode_reverse(symbols, reference)
+    assert np.all(coder.get_compressed() == reference_coder.get_compressed())
+    assert np.all(coder.decode(model, len(symbols)) == symbols)
+
+    for probabilities in [[1 << 23, 1 << 22], [1 << 24, 0], [1 << 24], []]:
+        with pytest.raises(ValueError):
+            constriction.stream.model.Categorical.from_fixed_point(
+                np.array(probabilities, dtype=np.uint32))
+
+
+def test_narrow_symbol_dtypes():
+    import pytest
+
+    model = constriction.stream.model.QuantizedGaussian(0, 255, 128.0, 40.0)
+    symbols = np.random.RandomState(3).randint(0, 256, size=1000)
+    reference = constriction.stream.queue.RangeEncoder()
+    reference.encode(symbols.astype(np.int32), model)
+
+    for dtype in [np.int16, np.uint8]:
+        encoder = constriction.stream.queue.RangeEncoder()
+        encoder.encode(symbols.astype(dtype), model)
+        assert np.all(encoder.get_compressed() == reference.get_compressed())
+
+        decoder = constriction.stream.queue.RangeDecoder(
+            encoder.get_compressed())
+        decoded = decoder.decode(model, len(symbols), dtype=dtype)
+        assert decoded.dtype == dtype
+        assert np.all(decoded == symbols)
+
+        coder = constriction.stream.stack.AnsCoder()
+        coder.encode_reverse(symbols.astype(dtype), model)
+        decoded = coder.decode(model, len(symbols), dtype=dtype)
+        assert decoded.dtype == dtype
+        assert np.all(decoded == symbols)
+
+    # Symbols that don't fit into the requested dtype raise an error and don't consume data.
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(np.array([300, 2], dtype=np.int16), model)
+    compressed = coder.get_compressed()
+    with pytest.raises(OverflowError):
+        coder.decode(model, 2, dtype=np.uint8)
+    assert np.all(coder.get_compressed() == compressed)
+
+    with pytest.raises(ValueError):
+        coder.decode(model, 2, dtype=np.float32)
+
+
+def test_strided_arrays():
+    rng = np.random.RandomState(17)
+    table = np.empty((100, 3))
+    table[:, 0] = rng.randint(-20, 21, size=100)
+    table[:, 1] = rng.randn(100) * 5
+    table[:, 2] = rng.rand(100) * 10 + 1
+    symbols = table[:, 0].astype(np.int32)
+    strided_symbols = np.repeat(symbols, 2)[::2]
+    means, stds = table[:, 1], table[:, 2]
+    assert not strided_symbols.flags.c_contiguous
+    assert not means.flags.c_contiguous
+
+    model_family = constriction.stream.model.QuantizedGaussian(-50, 50)
+    reference = constriction.stream.stack.AnsCoder()
+    reference.encode_reverse(symbols, model_family, means.copy(), stds.copy())
+
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(strided_symbols, model_family, means, stds)
+    assert np.all(coder.get_compressed() == reference.get_compressed())
+    assert np.all(coder.decode(model_family, means, stds) == symbols)
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(strided_symbols, model_family, means, stds)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model_family, means, stds) == symbols)
+
+    # Rank-2 model parameters whose rows aren't contiguous.
+    probabilities = rng.rand(10, 8)
+    strided_probabilities = probabilities[:, ::2]
+    categorical_symbols = rng.randint(0, 4, size=10).astype(np.int32)
+    model_family = constriction.stream.model.Categorical()
+    reference = constriction.stream.stack.AnsCoder()
+    reference.encode_reverse(categorical_symbols, model_family,
+                             strided_probabilities.copy())
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(categorical_symbols, model_family, strided_probabilities)
+    assert np.all(coder.get_compressed() == reference.get_compressed())
+    assert np.all(coder.decode(model_family, strided_probabilities)
+                  == categorical_symbols)
+
+
+
+def test_strict_decoding():
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 10.0)
+    symbols = np.array([12, -5, 31, 3, -18], dtype=np.int32)
+
+    # Permissive by default.
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model)
+    assert np.all(coder.decode(model, 5) == symbols)
+    coder.decode(model)
+
+    # An `AnsCoder` can't detect the end of its data: encoding a symbol with left-sided
+    # cumulative zero onto an empty coder leaves it empty, and it still has to decode.
+    categorical = constriction.stream.model.Categorical(
+        np.array([0.2, 0.5, 0.3], dtype=np.float64))
+    categorical_symbols = np.array([0, 2, 1], dtype=np.int32)
+    coder = constriction.stream.stack.AnsCoder(strict=True)
+    coder.encode_reverse(categorical_symbols, categorical)
+    assert np.all(coder.decode(categorical, 3) == categorical_symbols)
+    assert coder.is_empty()
+    coder = constriction.stream.stack.AnsCoder(strict=True)
+    coder.encode_reverse(symbols, model)
+    assert np.all(coder.decode(model, 5) == symbols)
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    compressed = encoder.get_compressed()
+    decoder = constriction.stream.queue.RangeDecoder(compressed, strict=True)
+    assert np.all(decoder.decode(model, 5) == symbols)
+    with pytest.raises(AssertionError):
+        for _ in range(100):
+            decoder.decode(model)
+    decoder = constriction.stream.queue.RangeDecoder(
+        np.array([], dtype=np.uint32), strict=True)
+    with pytest.raises(AssertionError):
+        decoder.decode(model)
+
+def test_quantized_logistic():
+    model_family = constriction.stream.model.QuantizedLogistic(-100, 100)
+    means = np.array([10.3, -4.7, 20.5], dtype=np.float64)
+    scales = np.array([5.2, 24.2, 3.1], dtype=np.float64)
+    symbols = np.array([12, -13, 25], dtype=np.int32)
+
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model_family, means, scales)
+    assert np.all(coder.decode(model_family, means, scales) == symbols)
+
+    # A concrete model gives the same result as the corresponding model family.
+    model = constriction.stream.model.QuantizedLogistic(-100, 100, 16.7, 9.3)
+    symbols = np.array([18, 43, 25, 20, 8, 11], dtype=np.int32)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model)
+    reference = constriction.stream.stack.AnsCoder()
+    reference.encode_reverse(
+        symbols, model_family, np.full(6, 16.7), np.full(6, 9.3))
+    assert np.all(coder.get_compressed() == reference.get_compressed())
+    assert np.all(coder.decode(model, 6) == symbols)
+
+    report = model.describe()
+    assert report["support"] == (-100, 100)
+
+    # Invalid parameters raise a `ValueError` and leave the coder unchanged.
+    import pytest
+    for scale in [0.0, -1.0, np.nan]:
+        with pytest.raises(ValueError):
+            constriction.stream.model.QuantizedLogistic(-100, 100, 0.0, scale)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols[:3], model_family, means, scales)
+    before = coder.get_compressed()
+    for bad_scale in [0.0, -1.0, np.nan]:
+        bad_scales = scales.copy()
+        bad_scales[1] = bad_scale
+        with pytest.raises(ValueError):
+            coder.encode_reverse(symbols[:3], model_family, means, bad_scales)
+    assert np.all(coder.get_compressed() == before)
+
+
+def test_quantized_cauchy():
+    model_family = constriction.stream.model.QuantizedCauchy(-10000, 10000)
+    means = np.array([10.3, -4.7, 20.5, 0.0], dtype=np.float64)
+    scales = np.array([5.2, 24.2, 3.1, 2.0], dtype=np.float64)
+    symbols = np.array([12, -13, 25, 8000], dtype=np.int32)
+
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model_family, means, scales)
+    assert np.all(coder.decode(model_family, means, scales) == symbols)
+
+    # An outlier is much cheaper under a Cauchy model than under a Laplace model.
+    cauchy = constriction.stream.model.QuantizedCauchy(-10000, 10000, 0.0, 2.0)
+    laplace = constriction.stream.model.QuantizedLaplace(-10000, 10000, 0.0, 2.0)
+    outliers = np.array([500, -700, 600], dtype=np.int32)
+    sizes = []
+    for model in [cauchy, laplace]:
+        coder = constriction.stream.stack.AnsCoder()
+        coder.encode_reverse(outliers, model)
+        sizes.append(coder.num_bits())
+        assert np.all(coder.decode(model, 3) == outliers)
+    assert sizes[0] < sizes[1]
+
+    # Probabilities agree with scipy's Cauchy distribution up to quantization errors.
+    report = cauchy.describe()
+    assert report["support"] == (-10000, 10000)
+    expected_tail_mass = 2 * scipy.stats.cauchy(0.0, 2.0).cdf(-10000.5)
+    assert abs(report["tail_mass"] - expected_tail_mass) < 1e-6
+
+    # Invalid parameters raise a `ValueError` and leave the coder unchanged.
+    import pytest
+    for scale in [0.0, -1.0, np.nan, np.inf]:
+        with pytest.raises(ValueError):
+            constriction.stream.model.QuantizedCauchy(-10000, 10000, 0.0, scale)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model_family, means, scales)
+    before = coder.get_compressed()
+    for bad_scales in [[5.2, -1.0, 3.1, 2.0], [5.2, np.nan, 3.1, 2.0]]:
+        with pytest.raises(ValueError):
+            coder.encode_reverse(symbols, model_family, means,
+                                 np.array(bad_scales, dtype=np.float64))
+    with pytest.raises(ValueError):
+        coder.encode_reverse(symbols, model_family,
+                             np.array([np.nan, 0.0, 0.0, 0.0]), scales)
+    assert np.all(coder.get_compressed() == before)
+
+
+def test_chain_binary_round_trip():
+    import pytest
+    data = np.array([0x0123_4567, 0x89ab_cdef], dtype=np.uint32)
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 10.0)
+
+    coder = constriction.stream.chain.ChainCoder.from_binary(data)
+    assert coder.is_whole()
+    symbols = coder.decode(model, 1)
+    assert not coder.is_whole()
+    with pytest.raises(AssertionError, match="is_whole"):
+        coder.get_binary()
+    with pytest.raises(AssertionError, match="is_whole"):
+        coder.get_data()
+
+    coder.encode_reverse(symbols, model)
+    assert coder.is_whole()
+    prefix, suffix = coder.get_binary()
+    assert np.all(np.concatenate((prefix, suffix)) == data)
+    prefix2, suffix2 = coder.get_data(unseal=True)
+    assert np.all(prefix2 == prefix) and np.all(suffix2 == suffix)
+
+
+def test_exact_binomial():
+    import pytest
+    symbols = np.array([15, 33, 22], dtype=np.int32)
+    ns = np.array([20, 53, 42], dtype=np.int32)
+    ps = np.array([0.6, 0.7, 0.5], dtype=np.float64)
+
+    # Parameters provided at encoding and decoding time (e.g., for bits-back coding):
+    data = np.array([0x0123_4567, 0x89ab_cdef, 0x1357_9bdf], dtype=np.uint32)
+    model = constriction.stream.model.ExactBinomial()
+    coder = constriction.stream.chain.ChainCoder(data, seal=True)
+    counts = coder.decode(model, ns, ps)
+    assert np.all(counts <= ns)
+    coder.encode_reverse(counts, model, ns, ps)
+    prefix, suffix = coder.get_data(unseal=True)
+    assert np.all(np.concatenate((prefix, suffix)) == data)
+
+    # Fixed `n`, per-symbol `p`:
+    model = constriction.stream.model.ExactBinomial(100)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model, ps)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model, ps) == symbols)
+
+    # Fully parameterized, including extreme values of `p`:
+    for p in [0.0, 0.5, 1.0]:
+        model = constriction.stream.model.ExactBinomial(40, p)
+        coder = constriction.stream.stack.AnsCoder()
+        coder.encode_reverse(symbols, model)
+        assert np.all(coder.decode(model, 3) == symbols)
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.ExactBinomial(0, 0.5)
+    with pytest.raises(ValueError):
+        constriction.stream.model.ExactBinomial(10, 1.5)
+
+    # Invalid per-symbol parameters raise a `ValueError` and leave the coder unchanged:
+    model = constriction.stream.model.ExactBinomial(40)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model, ps)
+    compressed = encoder.get_compressed().copy()
+    with pytest.raises(ValueError, match="index 1"):
+        encoder.encode(symbols, model, np.array([0.5, 1.5, 0.5]))
+    assert np.all(encoder.get_compressed() == compressed)
+
+
+def test_validate_symbols():
+    import pytest
+    model = constriction.stream.model.QuantizedGaussian(-10, 10, 0.0, 3.0)
+    symbols = np.array([2, -4, 15, 0, -23, 7], dtype=np.int32)
+
+    for encoder, encode in [
+        (constriction.stream.queue.RangeEncoder(), 'encode'),
+        (constriction.stream.stack.AnsCoder(), 'encode_reverse'),
+    ]:
+        with pytest.raises(KeyError, match=r"2 symbol\(s\).*\[2, 4\]"):
+            getattr(encoder, encode)(symbols, model, validate=True)
+        assert encoder.num_bits() == 0
+
+    # Per-symbol model parameters:
+    model_family = constriction.stream.model.QuantizedGaussian(-10, 10)
+    means = np.zeros(len(symbols), dtype=np.float64)
+    stds = np.ones(len(symbols), dtype=np.float64)
+    encoder = constriction.stream.queue.RangeEncoder()
+    with pytest.raises(KeyError, match=r"\[2, 4\]"):
+        encoder.encode(symbols, model_family, means, stds, validate=True)
+
+    symbols[2], symbols[4] = 10, -10
+    encoder.encode(symbols, model_family, means, stds, validate=True)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model_family, means, stds) == symbols)
+
+
+def test_poisson():
+    import pytest
+    max_symbol = 20
+    counts = np.array([3, 0, 57, 8, 20, 1], dtype=np.int32)
+    lams = np.array([2.5, 0.1, 40.0, 6.0, 18.0, 0.0], dtype=np.float64)
+
+    # Per-symbol rates, with `max_symbol` used as an escape symbol:
+    model = constriction.stream.model.Poisson(max_symbol)
+    excess_model = constriction.stream.model.Uniform(1 << 16)
+    clipped = np.minimum(counts, max_symbol)
+    excess = counts[counts >= max_symbol] - max_symbol
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(clipped, model, lams)
+    encoder.encode(excess, excess_model)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    decoded = decoder.decode(model, lams)
+    assert np.all(decoded == clipped)
+    decoded[decoded == max_symbol] += decoder.decode(excess_model, len(excess))
+    assert np.all(decoded == counts)
+
+    # Fixed rate:
+    model = constriction.stream.model.Poisson(max_symbol, 4.0)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(clipped, model)
+    assert np.all(coder.decode(model, len(clipped)) == clipped)
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.Poisson(0, 1.0)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Poisson(10, -1.0)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Poisson(1 << 24)
+    with pytest.raises(ValueError):
+        coder.encode_reverse(np.array([1, 2], dtype=np.int32),
+                             constriction.stream.model.Poisson(max_symbol),
+                             np.array([1.0, -1.0]))
+
+
+def test_huffman_tie_breaking():
+    import pytest
+    probabilities = np.array([2.0, 1.0, 1.0, 2.0], dtype=np.float64)
+    symbols = [3, 1, 0, 2, 3]
+
+    for tie_breaking, codeword_len in [('by_index', 2), ('by_symbol', 1), ('merged_first', 1)]:
+        encoder_codebook = constriction.symbol.huffman.EncoderHuffmanTree(
+            probabilities, tie_breaking=tie_breaking)
+        decoder_codebook = constriction.symbol.huffman.DecoderHuffmanTree(
+            probabilities, tie_breaking)
+
+        encoder = constriction.symbol.QueueEncoder()
+        encoder.encode_symbol(3, encoder_codebook)
+        _, compressed_len = encoder.get_compressed()
+        assert compressed_len == codeword_len
+
+        encoder = constriction.symbol.QueueEncoder()
+        for symbol in symbols:
+            encoder.encode_symbol(symbol, encoder_codebook)
+        decoder = encoder.get_decoder()
+        reconstructed = [decoder.decode_symbol(
+            decoder_codebook) for _ in range(len(symbols))]
+        assert reconstructed == symbols
+
+    with pytest.raises(ValueError):
+        constriction.symbol.huffman.EncoderHuffmanTree(probabilities, tie_breaking='random')
+
+
+def test_encode_to_array():
+    model_family = constriction.stream.model.QuantizedGaussian(-50, 50)
+    means = np.linspace(-20.0, 20.0, 12, dtype=np.float64)
+    stds = np.full(12, 4.0, dtype=np.float64)
+    symbols = np.round(means * 0.9).astype(np.int32)
+    iid_model = constriction.stream.model.QuantizedGaussian(-50, 50, 0.0, 10.0)
+    iid_symbols = np.array([3, -7, 0, 12, -1], dtype=np.int32)
+
+    reference = constriction.stream.queue.RangeEncoder()
+    reference.encode(symbols, model_family, means, stds)
+    reference.encode(iid_symbols, iid_model)
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    compressed1 = encoder.encode_to_array(symbols, model_family, means, stds)
+    compressed2 = encoder.encode_to_array(iid_symbols, iid_model)
+    assert compressed1.dtype == np.uint32
+    assert np.all(compressed2 == reference.get_compressed())
+    assert np.all(encoder.get_compressed() == reference.get_compressed())
+
+    decoder = constriction.stream.queue.RangeDecoder(compressed1)
+    assert np.all(decoder.decode(model_family, means, stds) == symbols)
+
+
+def test_skip():
+    model_family = constriction.stream.model.QuantizedGaussian(-50, 50)
+    means = np.linspace(-20.0, 20.0, 12, dtype=np.float64)
+    stds = np.full(12, 4.0, dtype=np.float64)
+    symbols = np.round(means * 0.9).astype(np.int32)
+    iid_model = constriction.stream.model.QuantizedGaussian(-50, 50, 0.0, 10.0)
+    iid_symbols = np.array([3, -7, 0, 12, -1], dtype=np.int32)
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model_family, means, stds)
+    encoder.encode(iid_symbols, iid_model)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    decoder.skip(model_family, means[:8], stds[:8])
+    assert np.all(decoder.decode(model_family, means[8:], stds[8:]) == symbols[8:])
+    decoder.skip(iid_model, 3)
+    decoder.skip(iid_model)
+    assert decoder.decode(iid_model) == iid_symbols[4]
+
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(iid_symbols, iid_model)
+    coder.encode_reverse(symbols, model_family, means, stds)
+    coder.skip(model_family, means, stds)
+    coder.skip(iid_model, 2)
+    assert np.all(coder.decode(iid_model, 3) == iid_symbols[2:])
+    assert coder.is_empty()
+
+    data = np.array([0x0123_4567, 0x89ab_cdef, 0x1357_9bdf, 0x2468_ace0], dtype=np.uint32)
+    coder = constriction.stream.chain.ChainCoder(data, seal=True)
+    reference = constriction.stream.chain.ChainCoder(data, seal=True)
+    coder.skip(iid_model, 2)
+    reference.decode(iid_model, 2)
+    for part, reference_part in zip(coder.get_remainders(), reference.get_remainders()):
+        assert np.all(part == reference_part)
+
+
+def test_ans_snapshot_restore():
+    import pytest
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 0.0, 30.0)
+    message = np.array([12, -15, 4, -2, 18, 5, 77, -60, 0, 3], dtype=np.int32)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(message, model)
+    compressed = coder.get_compressed()
+
+    outer = coder.snapshot()
+    for attempt in range(3):
+        assert np.all(coder.decode(model, 4) == message[:4])
+        inner = coder.snapshot()
+        assert np.all(coder.decode(model, 3) == message[4:7])
+        coder.encode_reverse(np.array([attempt, 1, 2], dtype=np.int32), model)
+        coder.restore(inner)
+        assert np.all(coder.decode(model, 6) == message[4:])
+        coder.restore(outer)
+        assert np.all(coder.get_compressed() == compressed)
+
+    # Taking a snapshot after decoding past `outer` and encoding new data invalidates `outer`.
+    coder.decode(model, 2)
+    coder.encode_reverse(np.array([7], dtype=np.int32), model)
+    newer = coder.snapshot()
+    with pytest.raises(ValueError):
+        coder.restore(outer)
+    coder.decode(model, 1)
+    coder.restore(newer)
+    assert coder.decode(model) == 7
+
+    with pytest.raises(ValueError):
+        constriction.stream.stack.AnsCoder().restore(newer)
+    coder.clear()
+    with pytest.raises(ValueError):
+        coder.restore(newer)
+
+
+def test_negative_binomial():
+    import pytest
+    counts = np.array([4, 0, 13, 6, 2, 25, 100, 7], dtype=np.int32)
+
+    model = constriction.stream.model.NegativeBinomial(100, 3.0, 1 / 3)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(counts, model)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model, len(counts)) == counts)
+
+    rs = np.array([1.0, 0.5, 10.0, 2.5, 3.0, 7.0, 1.0, 4.0], dtype=np.float64)
+    ps = np.array([0.2, 0.9, 0.4, 0.3, 0.5, 0.1, 0.01, 0.6], dtype=np.float64)
+    model = constriction.stream.model.NegativeBinomial(100)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(counts, model, rs, ps)
+    assert np.all(coder.decode(model, rs, ps) == counts)
+
+    model = constriction.stream.model.NegativeBinomial(100, r=2.0)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(counts, model, ps)
+    assert np.all(coder.decode(model, ps) == counts)
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.NegativeBinomial(10, 0.0, 0.5)
+    with pytest.raises(ValueError):
+        constriction.stream.model.NegativeBinomial(10, 2.0, 0.0)
+    with pytest.raises(ValueError):
+        constriction.stream.model.NegativeBinomial(0, 2.0, 0.5)
+    with pytest.raises(ValueError):
+        coder.encode_reverse(counts[:2], model, np.array([0.5, 0.0]))
+
+
+def test_geometric():
+    import pytest
+    run_lengths = np.array([3, 0, 5, 1, 30, 0], dtype=np.int32)
+    ps = np.array([0.2, 0.9, 0.1, 0.5, 0.05, 1.0], dtype=np.float64)
+
+    model = constriction.stream.model.Geometric(30)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(run_lengths, model, ps)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model, ps) == run_lengths)
+
+    model = constriction.stream.model.Geometric(30, 0.3)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(run_lengths, model)
+    assert np.all(coder.decode(model, len(run_lengths)) == run_lengths)
+
+    # A scalar `p` (tabulated model) and per-symbol `p`s (closed form) are interchangeable.
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(run_lengths, model)
+    family = constriction.stream.model.Geometric(30)
+    assert np.all(coder.decode(family, np.full(len(run_lengths), 0.3)) == run_lengths)
+    assert coder.is_empty()
+
+    model = constriction.stream.model.Geometric((1 << 23) - 1)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(run_lengths, model, ps)
+    assert np.all(coder.decode(model, ps) == run_lengths)
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.Geometric(10, 0.0)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Geometric(0, 0.5)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Geometric(1 << 23, 0.5)
+    with pytest.raises(ValueError):
+        coder.encode_reverse(run_lengths[:2], family, np.array([0.5, 0.0]))
+
+
+def test_zipf():
+    import pytest
+    token_ids = np.array([0, 3, 1, 0, 17, 2, 0, 4711, 1, 8], dtype=np.int32)
+
+    model = constriction.stream.model.Zipf(50_000, 1.1)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(token_ids, model)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model, len(token_ids)) == token_ids)
+
+    exponents = np.array([1.0, 0.5, 2.0, 1.2, 0.0, 1.0, 1.5, 0.8, 1.0, 3.0],
+                         dtype=np.float64)
+    model = constriction.stream.model.Zipf(5000)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(token_ids, model, exponents)
+    assert np.all(coder.decode(model, exponents) == token_ids)
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.Zipf(1, 1.0)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Zipf(10, -1.0)
+    with pytest.raises(ValueError):
+        coder.encode_reverse(token_ids[:2], model, np.array([1.0, -1.0]))
+
+
+def test_quantized_gaussian_mixture():
+    import pytest
+    weights = np.array([[0.7, 0.3], [0.5, 0.5], [0.9, 0.1], [1.0, 0.0]],
+                       dtype=np.float64)
+    means = np.array([[-3.2, 10.5], [0.0, 4.0], [20.1, -7.5], [1.0, 2.0]],
+                     dtype=np.float64)
+    stds = np.array([[2.0, 5.5], [1.0, 1.0], [3.3, 12.0], [0.5, 0.5]],
+                    dtype=np.float64)
+    symbols = np.array([-4, 3, 18, 1], dtype=np.int32)
+
+    model_family = constriction.stream.model.QuantizedGaussianMixture(-100, 100)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model_family, weights, means, stds)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model_family, weights, means, stds) == symbols)
+
+    # Non-contiguous parameter arrays work too.
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(symbols, model_family, weights.T.copy().T, means, stds)
+    assert np.all(coder.decode(model_family, weights, means, stds) == symbols)
+
+    # A mixture with a single component is a quantized Gaussian.
+    mixture = constriction.stream.model.QuantizedGaussianMixture(
+        -100, 100, np.array([1.0]), np.array([3.7]), np.array([4.2]))
+    gaussian = constriction.stream.model.QuantizedGaussian(-100, 100, 3.7, 4.2)
+    encoder1 = constriction.stream.queue.RangeEncoder()
+    encoder1.encode(symbols, mixture)
+    encoder2 = constriction.stream.queue.RangeEncoder()
+    encoder2.encode(symbols, gaussian)
+    assert np.all(encoder1.get_compressed() == encoder2.get_compressed())
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.QuantizedGaussianMixture(
+            -100, 100, np.array([1.0]), np.array([0.0]))
+    with pytest.raises(ValueError):
+        constriction.stream.model.QuantizedGaussianMixture(
+            -100, 100, np.array([1.0]), np.array([0.0]), np.array([0.0]))
+    with pytest.raises(ValueError):
+        coder.encode_reverse(symbols, model_family, weights, means, -stds)
+
+
+def test_quantized_logistic_mixture():
+    import pytest
+    weights = np.array([[0.7, 0.3], [0.5, 0.5], [0.9, 0.1]], dtype=np.float64)
+    means = np.array([[12.3, 200.5], [128.0, 4.0], [250.1, 17.5]],
+                     dtype=np.float64)
+    scales = np.array([[2.0, 5.5], [10.0, 1.0], [3.3, 12.0]], dtype=np.float64)
+    pixels = np.array([14, 130, 255], dtype=np.int32)
+
+    model_family = constriction.stream.model.QuantizedLogisticMixture(0, 255)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(pixels, model_family, weights, means, scales)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model_family, weights, means, scales) == pixels)
+
+    # A mixture with a single component is a quantized logistic distribution.
+    mixture = constriction.stream.model.QuantizedLogisticMixture(
+        0, 255, np.array([1.0]), np.array([128.3]), np.array([4.2]))
+    logistic = constriction.stream.model.QuantizedLogistic(0, 255, 128.3, 4.2)
+    encoder1 = constriction.stream.queue.RangeEncoder()
+    encoder1.encode(pixels, mixture)
+    encoder2 = constriction.stream.queue.RangeEncoder()
+    encoder2.encode(pixels, logistic)
+    assert np.all(encoder1.get_compressed() == encoder2.get_compressed())
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.QuantizedLogisticMixture(
+            0, 255, np.array([1.0]), np.array([0.0]), np.array([-1.0]))
+
+
+def test_two_sided_geometric():
+    import pytest
+    residuals = np.array([0, -1, 2, 0, 0, 1, -4, 17, 0], dtype=np.int32)
+    decays = np.array([0.5, 0.6, 0.7, 0.3, 0.9, 0.6, 0.8, 0.99, 0.1],
+                      dtype=np.float64)
+    offsets = np.array([0.0, -0.3, 1.5, 0.2, -2.0, 0.0, -3.7, 5.0, 0.0],
+                       dtype=np.float64)
+
+    model = constriction.stream.model.TwoSidedGeometric(-255, 255)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(residuals, model, decays, offsets)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model, decays, offsets) == residuals)
+
+    model = constriction.stream.model.TwoSidedGeometric(-255, 255, 0.6)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(residuals, model, offsets)
+    assert np.all(coder.decode(model, offsets) == residuals)
+
+    model = constriction.stream.model.TwoSidedGeometric(
+        -255, 255, offset=-0.3)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(residuals, model, decays)
+    assert np.all(coder.decode(model, decays) == residuals)
+
+    model = constriction.stream.model.TwoSidedGeometric(-255, 255, 0.6, -0.3)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(residuals, model)
+    assert np.all(coder.decode(model, len(residuals)) == residuals)
+
+    # Scalar parameters (tabulated model) and per-symbol parameters (closed form) are
+    # interchangeable.
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(residuals, model)
+    family = constriction.stream.model.TwoSidedGeometric(-255, 255, offset=-0.3)
+    assert np.all(
+        coder.decode(family, np.full(len(residuals), 0.6)) == residuals)
+    assert coder.is_empty()
+
+    model = constriction.stream.model.TwoSidedGeometric(
+        -(1 << 22), (1 << 22) - 1, offset=-0.3)
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(residuals, model, decays)
+    assert np.all(coder.decode(model, decays) == residuals)
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.TwoSidedGeometric(-10, 10, 0.0, 0.0)
+    with pytest.raises(ValueError):
+        constriction.stream.model.TwoSidedGeometric(-10, 10, 1.0, 0.0)
+    with pytest.raises(ValueError):
+        constriction.stream.model.TwoSidedGeometric(5, 5, 0.5, 0.0)
+    with pytest.raises(ValueError):
+        coder.encode_reverse(residuals[:2], family, np.array([0.5, 1.0]))
+
+
+def test_empirical():
+    import pytest
+    rng = np.random.default_rng(123)
+    samples = np.round(rng.standard_t(3, size=1000) * 5).astype(np.int32)
+    samples = np.clip(samples, -100, 100)
+    symbols = np.array([0, 3, -7, 1, 0, -100, 42, 2], dtype=np.int32)
+
+    model = constriction.stream.model.Empirical(samples, -100, 100)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    compressed = encoder.get_compressed()
+
+    # Rebuild the model on the decoder side from the histogram only.
+    counts = np.bincount(samples + 100, minlength=201)
+    rebuilt_samples = np.repeat(np.arange(-100, 101, dtype=np.int32), counts)
+    decoder_model = constriction.stream.model.Empirical(rebuilt_samples, -100, 100)
+    decoder = constriction.stream.queue.RangeDecoder(compressed)
+    assert np.all(decoder.decode(decoder_model, len(symbols)) == symbols)
+
+    # The support defaults to the range of the samples.
+    model = constriction.stream.model.Empirical(np.array([3, 5, 5, 4], dtype=np.int32))
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(np.array([3, 4, 5], dtype=np.int32), model)
+    with pytest.raises(KeyError):
+        coder.encode_reverse(np.array([6], dtype=np.int32), model)
+
+    with pytest.raises(ValueError):
+        constriction.stream.model.Empirical(samples, -10, 10)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Empirical(samples, -100)
+
+
+def test_dyn_precision_categorical():
+    import pytest
+    probabilities = np.array([0.1, 0.6, 0.3, 0.0001, 0.2], dtype=np.float64)
+    model = constriction.stream.model.DynPrecisionCategorical(probabilities)
+    rng = np.random.default_rng(123)
+    symbols = rng.choice(5, size=1000, p=probabilities / probabilities.sum()).astype(np.int32)
+
+    num_bits = {}
+    for precision in [3, 8, 12, 24]:
+        encoder = constriction.stream.queue.RangeEncoder()
+        encoder.encode(symbols, model, precision=precision)
+        num_bits[precision] = encoder.num_bits()
+        decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+        assert np.all(decoder.decode(model, len(symbols), precision=precision) == symbols)
+
+        coder = constriction.stream.stack.AnsCoder()
+        coder.encode_reverse(symbols, model, precision=precision)
+        assert np.all(coder.decode(model, len(symbols), precision=precision) == symbols)
+        assert coder.is_empty()
+
+    # Coarse rounding costs bit rate.
+    assert num_bits[3] > num_bits[12]
+
+    # Omitting `precision` is equivalent to `precision=24`.
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    assert encoder.num_bits() == num_bits[24]
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    with pytest.raises(ValueError):
+        encoder.encode(symbols, model, precision=2)
+    with pytest.raises(ValueError):
+        encoder.encode(symbols, model, precision=25)
+    with pytest.raises(ValueError):
+        encoder.encode(symbols, constriction.stream.model.Categorical(probabilities), precision=12)
+    with pytest.raises(ValueError):
+        constriction.stream.model.DynPrecisionCategorical(np.array([1.0]))
+
+
+def test_uniform_from_range():
+    import pytest
+    model = constriction.stream.model.Uniform.from_range(-1000, 1000)
+    symbols = np.array([-1000, 1000, 0, 17, -3, 999], dtype=np.int32)
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model, len(symbols)) == symbols)
+
+    # Each symbol costs about log2(2001) bits.
+    coder = constriction.stream.stack.AnsCoder()
+    many_symbols = np.arange(-1000, 1001, dtype=np.int32)
+    coder.encode_reverse(many_symbols, model)
+    assert abs(coder.num_bits() / len(many_symbols) - np.log2(2001)) < 0.1
+    assert np.all(coder.decode(model, len(many_symbols)) == many_symbols)
+
+    with pytest.raises(KeyError):
+        coder.encode_reverse(np.array([1001], dtype=np.int32), model, validate=True)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Uniform.from_range(5, 5)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Uniform.from_range(0, 1 << 24)
+
+
+def test_markov_model():
+    import pytest
+    initial_probabilities = np.array([0.5, 0.25, 0.25], dtype=np.float64)
+    transition_probabilities = np.array(
+        [[0.8, 0.1, 0.1], [0.1, 0.8, 0.1], [0.1, 0.1, 0.8]], dtype=np.float64)
+    markov = constriction.stream.model.MarkovModel(
+        initial_probabilities, transition_probabilities)
+    assert markov.alphabet_size() == 3
+
+    np.random.seed(762)
+    symbols = np.zeros(500, dtype=np.int32)
+    for i in range(1, len(symbols)):
+        symbols[i] = symbols[i - 1] if np.random.rand() < 0.8 else np.random.randint(3)
+
+    coder = constriction.stream.stack.AnsCoder()
+    markov.encode_reverse(coder, symbols)
+    # A sticky chain compresses better than i.i.d. symbols over three letters.
+    assert coder.num_bits() < len(symbols) * np.log2(3)
+    assert np.all(markov.decode(coder, len(symbols)) == symbols)
+    assert coder.is_empty()
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    markov.encode(encoder, symbols)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    decoded = [decoder.decode(markov.model())]
+    for _ in range(len(symbols) - 1):
+        decoded.append(decoder.decode(markov.model(decoded[-1])))
+    assert np.all(np.array(decoded) == symbols)
+
+    with pytest.raises(ValueError):
+        markov.model(3)
+    with pytest.raises(ValueError):
+        constriction.stream.model.MarkovModel(
+            initial_probabilities, transition_probabilities[:2, :])
+
+
+def test_bernoulli():
+    import pytest
+    bits = np.array([0, 0, 1, 0, 0, 0, 1, 0, 1, 1], dtype=np.int32)
+    model = constriction.stream.model.Bernoulli(0.3)
+    categorical = constriction.stream.model.Categorical(
+        np.array([0.7, 0.3], dtype=np.float64))
+
+    coder = constriction.stream.stack.AnsCoder()
+    coder.encode_reverse(bits, model)
+    reference = constriction.stream.stack.AnsCoder()
+    reference.encode_reverse(bits, categorical)
+    assert np.all(coder.get_compressed() == reference.get_compressed())
+    assert np.all(coder.decode(model, len(bits)) == bits)
+
+    ps = np.linspace(0.0, 1.0, len(bits))
+    model_family = constriction.stream.model.Bernoulli()
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(bits, model_family, ps)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(model_family, ps) == bits)
+
+    with pytest.raises(KeyError):
+        coder.encode_reverse(np.array([2], dtype=np.int32), model, validate=True)
+    with pytest.raises(ValueError):
+        constriction.stream.model.Bernoulli(1.5)
+    with pytest.raises(ValueError):
+        encoder.encode(bits[:2], model_family, np.array([0.5, 1.5]))
+
+
+def test_transcode():
+    import pytest
+    symbols = np.array([5, -3, 12, 0, 7, 4, -20, 9], dtype=np.int32)
+    means = np.linspace(-5.0, 5.0, len(symbols))
+    stds = np.linspace(3.0, 12.0, len(symbols))
+    model_family = constriction.stream.model.QuantizedGaussian(-100, 100)
+
+    # Range coder -> ANS with the same models; the stack decodes in reverse order.
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model_family, means, stds)
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    ans = constriction.stream.stack.AnsCoder()
+    count = constriction.stream.transcode(
+        decoder, ans, model_family, means, stds)
+    assert count == len(symbols)
+    assert decoder.maybe_exhausted()
+    assert np.all(ans.decode(model_family, means[::-1], stds[::-1])
+                  == symbols[::-1])
+
+    # ANS -> range coder with a different concrete target model.
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+    target_model = constriction.stream.model.QuantizedLaplace(
+        -100, 100, 3.2, 8.0)
+    ans = constriction.stream.stack.AnsCoder()
+    ans.encode_reverse(symbols, model)
+    encoder = constriction.stream.queue.RangeEncoder()
+    count = constriction.stream.transcode(
+        ans, encoder, model, len(symbols), target_model=target_model)
+    assert count == len(symbols)
+    assert ans.is_empty()
+    decoder = constriction.stream.queue.RangeDecoder(encoder.get_compressed())
+    assert np.all(decoder.decode(target_model, len(symbols)) == symbols)
+
+    # A failure leaves both coders unchanged.
+    ans = constriction.stream.stack.AnsCoder()
+    ans.encode_reverse(symbols, model)
+    compressed = ans.get_compressed()
+    narrow_model = constriction.stream.model.QuantizedGaussian(
+        -10, 10, 3.2, 10.0)
+    encoder = constriction.stream.queue.RangeEncoder()
+    with pytest.raises(KeyError):
+        constriction.stream.transcode(
+            ans, encoder, model, len(symbols), target_model=narrow_model)
+    assert np.all(ans.get_compressed() == compressed)
+    assert encoder.is_empty()
+
+    with pytest.raises(ValueError):
+        constriction.stream.transcode(ans, ans, model)
+    with pytest.raises(TypeError):
+        constriction.stream.transcode(encoder, ans, model)
+
+
+def test_entropy():
+    data_model = constriction.stream.model.Categorical(
+        np.array([0.5, 0.25, 0.25], dtype=np.float64))
+    coding_model = constriction.stream.model.Categorical(
+        np.array([0.25, 0.25, 0.5], dtype=np.float64))
+    assert data_model.entropy() == 1.5
+    assert data_model.cross_entropy(coding_model) == 1.75
+    assert data_model.cross_entropy(data_model) == 1.5
+    assert data_model.kl_divergence(coding_model) == 0.25
+    assert data_model.kl_divergence(data_model) == 0.0
+
+    # The entropy estimates the actual bit rate for a long message.
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+    entropy = model.entropy()
+    symbols = np.round(np.random.default_rng(123).normal(
+        3.2, 10.0, 10000)).astype(np.int32).clip(-100, 100)
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    assert abs(encoder.num_bits() / len(symbols) - entropy) < 0.05
+
+    # Symbols outside of the support of the coding model have infinite cost.
+    narrow_model = constriction.stream.model.QuantizedGaussian(
+        -10, 10, 3.2, 10.0)
+    assert model.cross_entropy(narrow_model) == float('inf')
+
+    # Model families return one entropy per set of model parameters.
+    model_family = constriction.stream.model.QuantizedGaussian(-100, 100)
+    means = np.array([0.0, 3.2, 10.0], dtype=np.float64)
+    stds = np.array([1.0, 10.0, 20.0], dtype=np.float64)
+    entropies = model_family.entropy(means, stds)
+    assert entropies.shape == (3,)
+    assert entropies[1] == entropy
+    assert entropies[0] < entropies[1] < entropies[2]
+
+
+def test_bit_cost():
+    model = constriction.stream.model.Categorical(
+        np.array([0.5, 0.25, 0.25], dtype=np.float64))
+    assert model.bit_cost(0) == 1.0
+    assert model.bit_cost(2) == 2.0
+    assert model.bit_cost(3) == float('inf')
+    assert np.all(model.bit_cost([1, 0, 2]) == [2.0, 1.0, 2.0])
+
+    # The total bit cost predicts the size of the compressed data.
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+    symbols = np.array([(i * 37) % 201 - 100 for i in range(1000)],
+                       dtype=np.int32)
+    bit_costs = model.bit_cost(symbols)
+    assert bit_costs.shape == symbols.shape
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    assert abs(encoder.num_bits() - bit_costs.sum()) < 64
+
+    # Model families take one set of model parameters per symbol.
+    model_family = constriction.stream.model.QuantizedGaussian(-100, 100)
+    means = np.full(len(symbols), 3.2)
+    stds = np.full(len(symbols), 10.0)
+    assert np.all(model_family.bit_cost(symbols, means, stds) == bit_costs)
+
+
+def test_checksum():
+    import pytest
+    model = constriction.stream.model.QuantizedGaussian(-100, 100, 3.2, 10.0)
+    symbols = np.array([(i * 37) % 201 - 100 for i in range(100)],
+                       dtype=np.int32)
+
+    encoder = constriction.stream.queue.RangeEncoder()
+    encoder.encode(symbols, model)
+    sealed = encoder.seal_with_checksum()
+    assert len(sealed) == encoder.num_words() + 1
+    compressed = constriction.stream.verify_checksum(sealed)
+    assert np.all(compressed == encoder.get_compressed())
+    decoder = constriction.stream.queue.RangeDecoder(compressed)
+    assert np.all(decoder.decode(model, len(symbols)) == symbols)
+
+    ans = constriction.stream.stack.AnsCoder()
+    ans.encode_reverse(symbols, model)
+    sealed = ans.seal_with_checksum()
+    decoder = constriction.stream.stack.AnsCoder(
+        constriction.stream.verify_checksum(sealed))
+    assert np.all(decoder.decode(model, len(symbols)) == symbols)
+
+    # Corrupted or truncated data is detected.
+    sealed[len(sealed) // 2] ^= 1 << 7
+    with pytest.raises(ValueError):
+        constriction.stream.verify_checksum(sealed)
+    with pytest.raises(ValueError):
+        constriction.stream.verify_checksum(np.array([], dtype=np.uint32))
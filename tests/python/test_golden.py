@@ -0,0 +1,84 @@
+import os
+
+import constriction
+import numpy as np
+
+GOLDEN_FILE = os.path.join(
+    os.path.dirname(__file__), '..', 'golden', 'stream.txt')
+
+
+def make_model(spec):
+    name, *params = spec.split()
+    model = constriction.stream.model
+    if name == 'categorical':
+        return model.Categorical(np.array([float(p) for p in params], dtype=np.float64))
+    elif name == 'uniform':
+        return model.Uniform(int(params[0]))
+    elif name == 'uniform_range':
+        return model.Uniform.from_range(int(params[0]), int(params[1]))
+    elif name == 'gaussian':
+        return model.QuantizedGaussian(
+            int(params[0]), int(params[1]), float(params[2]), float(params[3]))
+    elif name == 'laplace':
+        return model.QuantizedLaplace(
+            int(params[0]), int(params[1]), float(params[2]), float(params[3]))
+    elif name in ('gaussian_mixture', 'logistic_mixture'):
+        components = np.array([float(p) for p in params[2:]], dtype=np.float64)
+        model_class = (model.QuantizedGaussianMixture if name == 'gaussian_mixture'
+                       else model.QuantizedLogisticMixture)
+        return model_class(
+            int(params[0]), int(params[1]),
+            components[0::3], components[1::3], components[2::3])
+    elif name == 'binomial':
+        return model.Binomial(int(params[0]), float(params[1]))
+    elif name == 'exact_binomial':
+        return model.ExactBinomial(int(params[0]), float(params[1]))
+    elif name == 'poisson':
+        return model.Poisson(int(params[0]), float(params[1]))
+    elif name == 'geometric':
+        return model.Geometric(int(params[0]), float(params[1]))
+    elif name == 'two_sided_geometric':
+        return model.TwoSidedGeometric(
+            int(params[0]), int(params[1]), float(params[2]), float(params[3]))
+    elif name == 'bernoulli':
+        return model.Bernoulli(float(params[0]))
+    else:
+        raise ValueError(f'unknown model `{name}`')
+
+
+def load_cases():
+    with open(GOLDEN_FILE) as f:
+        for line in f:
+            line = line.strip()
+            if not line or line.startswith('#'):
+                continue
+            coder, model, symbols, compressed = (
+                field.strip() for field in line.split(';'))
+            symbols = np.array([int(s) for s in symbols.split()], dtype=np.int32)
+            compressed = np.array([int(w) for w in compressed.split()], dtype=np.uint32)
+            yield coder, model, symbols, compressed
+
+
+def test_golden_stream():
+    num_cases = 0
+    for coder, spec, symbols, expected in load_cases():
+        model = make_model(spec)
+        if coder == 'queue':
+            encoder = constriction.stream.queue.RangeEncoder()
+            encoder.encode(symbols, model)
+            compressed = encoder.get_compressed()
+            decoder = constriction.stream.queue.RangeDecoder(compressed)
+        elif coder == 'stack':
+            encoder = constriction.stream.stack.AnsCoder()
+            encoder.encode_reverse(symbols, model)
+            compressed = encoder.get_compressed()
+            decoder = constriction.stream.stack.AnsCoder(compressed)
+        else:
+            raise ValueError(f'unknown coder `{coder}`')
+
+        assert compressed.dtype == np.uint32
+        assert np.all(compressed == expected), (coder, spec, compressed)
+        assert np.all(decoder.decode(model, len(symbols)) == symbols)
+        num_cases += 1
+
+    assert num_cases != 0
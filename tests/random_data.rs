@@ -16,7 +16,7 @@ use constriction::{
 fn make_random_normal(
     amt: usize,
     domain: RangeInclusive<i32>,
-) -> impl Iterator<Item = (i32, Gaussian)> + DoubleEndedIterator + Clone {
+) -> impl DoubleEndedIterator<Item = (i32, Gaussian)> + Clone {
     (0..amt).map(move |i| {
         // Generate random numbers that can also be reproduced when iterating in reverse direction.
         let mut rng = Xorshift128Plus::new([i as u64 + 123, i as u64 + 456]);
@@ -0,0 +1,119 @@
+//! Tests the guarantees of `Seek::seek` close to the end of the compressed data.
+//!
+//! Messages of many different lengths make sure that the end of the compressed data falls
+//! at all possible offsets relative to word boundaries, so that seeking close to the end
+//! runs into compressed data that doesn't fill up the coder's entire state.
+
+#![warn(rust_2018_idioms)]
+
+use constriction::{
+    stream::{
+        model::{DefaultContiguousCategoricalEntropyModel, DefaultLeakyQuantizer},
+        queue::{DefaultRangeDecoder, DefaultRangeEncoder},
+        stack::DefaultAnsCoder,
+        Decode, Encode,
+    },
+    Pos, Seek,
+};
+use probability::distribution::Gaussian;
+
+fn message(len: usize, seed: usize) -> Vec<i32> {
+    (0..len)
+        .map(|i| ((i.wrapping_mul(0x6979_E2F3) ^ seed) % 41) as i32 - 20)
+        .collect()
+}
+
+#[test]
+fn range_coder() {
+    let quantizer = DefaultLeakyQuantizer::new(-20..=20);
+    for &std in &[0.2, 5.0, 100.0] {
+        let model = quantizer.quantize(Gaussian::new(0.0, std));
+        for len in 0..60 {
+            let symbols = message(len, len);
+
+            let mut encoder = DefaultRangeEncoder::new();
+            let mut snapshots = vec![encoder.pos()];
+            for &symbol in &symbols {
+                encoder.encode_symbol(symbol, model).unwrap();
+                snapshots.push(encoder.pos());
+            }
+            let compressed = encoder.into_compressed().unwrap();
+            let end = compressed.len();
+            let mut decoder = DefaultRangeDecoder::from_compressed(compressed).unwrap();
+
+            // Seek to every snapshot, including the final one, and decode the rest.
+            for (i, &snapshot) in snapshots.iter().enumerate().rev() {
+                decoder.seek(snapshot).unwrap();
+                let decoded = decoder
+                    .decode_iid_symbols(len - i, &model)
+                    .collect::<Result<Vec<_>, _>>()
+                    .unwrap();
+                assert_eq!(decoded, symbols[i..]);
+                assert!(decoder.maybe_exhausted());
+            }
+
+            // One past the last word is valid, beyond that isn't.
+            let final_state = snapshots.last().unwrap().1;
+            decoder.seek((end, final_state)).unwrap();
+            assert!(decoder.seek((end + 1, final_state)).is_err());
+
+            // A failed `seek` leaves the decoder unchanged.
+            decoder.seek(snapshots[0]).unwrap();
+            assert!(decoder.seek((end + 1, final_state)).is_err());
+            let decoded = decoder
+                .decode_iid_symbols(len, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols);
+        }
+    }
+}
+
+#[test]
+fn ans_coder() {
+    let model = DefaultContiguousCategoricalEntropyModel::from_floating_point_probabilities(&[
+        0.6, 0.3, 0.05, 0.05,
+    ])
+    .unwrap();
+    for len in 0..60 {
+        let symbols = message(len, len)
+            .into_iter()
+            .map(|symbol| (symbol.rem_euclid(4)) as usize)
+            .collect::<Vec<_>>();
+
+        let mut encoder = DefaultAnsCoder::new();
+        let mut snapshots = vec![encoder.pos()];
+        for &symbol in symbols.iter().rev() {
+            encoder.encode_symbol(symbol, &model).unwrap();
+            snapshots.push(encoder.pos());
+        }
+        let mut decoder = encoder.as_seekable_decoder();
+        let end = decoder.pos().0;
+
+        // Seek to every snapshot, including the final one (i.e., the first one for a
+        // stack), and decode the rest.
+        for (i, &snapshot) in snapshots.iter().enumerate() {
+            decoder.seek(snapshot).unwrap();
+            let decoded = decoder
+                .decode_iid_symbols(i, &model)
+                .collect::<Result<Vec<_>, _>>()
+                .unwrap();
+            assert_eq!(decoded, symbols[len - i..]);
+            assert!(decoder.is_empty());
+            assert_eq!(decoder.pos(), snapshots[0]);
+        }
+
+        // One past the last word is valid, beyond that isn't.
+        let final_state = snapshots.last().unwrap().1;
+        decoder.seek((end, final_state)).unwrap();
+        assert!(decoder.seek((end + 1, final_state)).is_err());
+
+        // A failed `seek` leaves the decoder unchanged.
+        assert_eq!(decoder.pos(), (end, final_state));
+        let decoded = decoder
+            .decode_iid_symbols(len, &model)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(decoded, symbols);
+    }
+}